@@ -3,6 +3,7 @@ pub use baml_types::{GeneratorDefaultClientMode, GeneratorOutputType};
 use bstd::ProjectFqn;
 use derive_builder::Builder;
 use enumflags2::BitFlags;
+use internal_baml_diagnostics::DiagnosticCode;
 use std::path::PathBuf;
 
 #[derive(Debug)]
@@ -26,6 +27,19 @@ impl Configuration {
             .iter()
             .fold(BitFlags::empty(), |acc, _generator| acc)
     }
+
+    /// Warning codes silenced for the whole project by any generator's
+    /// `allowed_warnings`.
+    pub fn allowed_warnings(&self) -> Vec<DiagnosticCode> {
+        let mut codes: Vec<_> = self
+            .generators
+            .iter()
+            .flat_map(|generator| generator.allowed_warnings().iter().copied())
+            .collect();
+        codes.sort();
+        codes.dedup();
+        codes
+    }
 }
 
 #[derive(Debug)]
@@ -34,6 +48,15 @@ pub enum Generator {
     BoundaryCloud(CloudProject),
 }
 
+impl Generator {
+    pub fn allowed_warnings(&self) -> &[DiagnosticCode] {
+        match self {
+            Generator::Codegen(generator) => &generator.allowed_warnings,
+            Generator::BoundaryCloud(project) => &project.allowed_warnings,
+        }
+    }
+}
+
 // TODO: we should figure out how to model generator fields using serde, since
 // the generator blocks are essentially a serde_json parse
 // problem is that serde_json has atrocious error messages and we need to provide
@@ -47,6 +70,8 @@ pub struct CodegenGenerator {
     pub on_generate: Vec<String>,
     output_dir: PathBuf,
     pub version: String,
+    #[builder(default)]
+    pub allowed_warnings: Vec<DiagnosticCode>,
 
     pub span: crate::ast::Span,
 }
@@ -93,6 +118,8 @@ pub struct CloudProject {
     pub project_fqn: ProjectFqn,
 
     pub version: String,
+    #[builder(default)]
+    pub allowed_warnings: Vec<DiagnosticCode>,
 
     pub span: crate::ast::Span,
 }
@@ -85,7 +85,7 @@ impl IRHelper for IntermediateRepr {
     }
 
     fn find_enum(&self, enum_name: &str) -> Result<EnumWalker<'_>> {
-        match self.walk_enums().find(|e| e.name() == enum_name) {
+        match self.enum_by_name(enum_name) {
             Some(e) => Ok(e),
             None => {
                 // Get best match.
@@ -96,7 +96,7 @@ impl IRHelper for IntermediateRepr {
     }
 
     fn find_class<'a>(&'a self, class_name: &str) -> Result<ClassWalker<'a>> {
-        match self.walk_classes().find(|e| e.name() == class_name) {
+        match self.class_by_name(class_name) {
             Some(e) => Ok(e),
             None => {
                 // Get best match.
@@ -107,7 +107,7 @@ impl IRHelper for IntermediateRepr {
     }
 
     fn find_function<'a>(&'a self, function_name: &str) -> Result<FunctionWalker<'a>> {
-        match self.walk_functions().find(|f| f.name() == function_name) {
+        match self.function_by_name(function_name) {
             Some(f) => Ok(f),
 
             None => {
@@ -119,7 +119,7 @@ impl IRHelper for IntermediateRepr {
     }
 
     fn find_client<'a>(&'a self, client_name: &str) -> Result<ClientWalker<'a>> {
-        match self.walk_clients().find(|c| c.name() == client_name) {
+        match self.client_by_name(client_name) {
             Some(c) => Ok(c),
             None => {
                 // Get best match.
@@ -134,10 +134,7 @@ impl IRHelper for IntermediateRepr {
 
     // find_retry_policy
     fn find_retry_policy(&self, retry_policy_name: &str) -> Result<RetryPolicyWalker<'_>> {
-        match self
-            .walk_retry_policies()
-            .find(|r| r.name() == retry_policy_name)
-        {
+        match self.retry_policy_by_name(retry_policy_name) {
             Some(r) => Ok(r),
             None => {
                 // Get best match.
@@ -152,10 +149,7 @@ impl IRHelper for IntermediateRepr {
 
     // find_template_string
     fn find_template_string(&self, template_string_name: &str) -> Result<TemplateStringWalker<'_>> {
-        match self
-            .walk_template_strings()
-            .find(|t| t.name() == template_string_name)
-        {
+        match self.template_string_by_name(template_string_name) {
             Some(t) => Ok(t),
             None => {
                 // Get best match.
@@ -31,6 +31,17 @@ struct GenericScope {
     name: Option<String>,
     errors: Vec<String>,
     warnings: Vec<String>,
+    /// The same errors as `errors`, with their path relative to this scope.
+    located_errors: Vec<ScopeError>,
+}
+
+/// An error together with where in the checked value it occurred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeError {
+    /// Names of the enclosing scopes, outermost first, e.g.
+    /// `["resume", "experience", "0"]`.
+    pub path: Vec<String>,
+    pub message: String,
 }
 
 #[allow(dead_code)]
@@ -55,6 +66,7 @@ impl GenericScope {
             name: Some(name),
             errors: Vec::new(),
             warnings: Vec::new(),
+            located_errors: Vec::new(),
         }
     }
 
@@ -107,6 +119,7 @@ impl ScopeStack {
                 name: None,
                 errors: Vec::new(),
                 warnings: Vec::new(),
+                located_errors: Vec::new(),
             }],
         }
     }
@@ -128,6 +141,17 @@ impl ScopeStack {
         let scope = self.scopes.pop().unwrap();
         let parent_scope = self.scopes.last_mut().unwrap();
 
+        if !errors_as_warnings {
+            parent_scope
+                .located_errors
+                .extend(scope.located_errors.into_iter().map(|mut e| {
+                    if let Some(name) = &scope.name {
+                        e.path.insert(0, name.clone());
+                    }
+                    e
+                }));
+        }
+
         if let Some(name) = scope.name {
             if errors_as_warnings {
                 parent_scope
@@ -152,10 +176,64 @@ impl ScopeStack {
     }
 
     pub fn push_error(&mut self, error: String) {
-        self.scopes.last_mut().unwrap().errors.push(error);
+        let scope = self.scopes.last_mut().unwrap();
+        scope.located_errors.push(ScopeError {
+            path: vec![],
+            message: error.clone(),
+        });
+        scope.errors.push(error);
+    }
+
+    /// All errors, each with the full path of the scope it was reported in.
+    pub fn errors(&self) -> Vec<ScopeError> {
+        let mut prefix = vec![];
+        let mut errors = vec![];
+        for scope in &self.scopes {
+            prefix.extend(scope.name.clone());
+            errors.extend(scope.located_errors.iter().map(|e| ScopeError {
+                path: prefix.iter().chain(&e.path).cloned().collect(),
+                message: e.message.clone(),
+            }));
+        }
+        errors
     }
 
     pub fn push_warning(&mut self, warning: String) {
         self.scopes.last_mut().unwrap().warnings.push(warning);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_keep_their_path() {
+        let mut scope = ScopeStack::new();
+        scope.push("resume".to_string());
+        scope.push("experience".to_string());
+        scope.push("0".to_string());
+        scope.push_error("Expected type string, got `1`".to_string());
+        scope.pop(false);
+        scope.pop(false);
+        scope.push("name".to_string());
+        scope.push_error("Not a name".to_string());
+        assert_eq!(
+            scope.errors(),
+            vec![
+                ScopeError {
+                    path: vec!["resume".into(), "experience".into(), "0".into()],
+                    message: "Expected type string, got `1`".into(),
+                },
+                ScopeError {
+                    path: vec!["resume".into(), "name".into()],
+                    message: "Not a name".into(),
+                },
+            ]
+        );
+
+        // Errors demoted to warnings are not reported.
+        scope.pop(true);
+        assert_eq!(scope.errors().len(), 1);
+    }
+}
@@ -156,9 +156,34 @@ impl ArgCoercer {
                                 s.to_string(),
                                 mime_type,
                             )))
+                        } else if let Some(BamlValue::String(s)) = kv.get("file_id") {
+                            let mime_type = match kv.get("media_type") {
+                                Some(t) => match t.as_str() {
+                                    Some(s) => Some(s.to_string()),
+                                    None => {
+                                        scope.push_error(format!("Invalid property `media_type` on file {}: expected string, got {:?}", media_type, t.r#type()));
+                                        return Err(());
+                                    }
+                                },
+                                None => None,
+                            };
+                            for key in kv.keys() {
+                                if !["file_id", "media_type"].contains(&key.as_str()) {
+                                    scope.push_error(format!(
+                                        "Invalid property `{}` on uploaded {}: `media_type` is the only supported property",
+                                        key,
+                                        media_type
+                                    ));
+                                }
+                            }
+                            Ok(BamlValue::Media(baml_types::BamlMedia::file_ref(
+                                *media_type,
+                                s.to_string(),
+                                mime_type,
+                            )))
                         } else {
                             scope.push_error(format!(
-                                "Invalid image: expected `file`, `url`, or `base64`, got `{}`",
+                                "Invalid image: expected `file`, `url`, `base64`, or `file_id`, got `{}`",
                                 value
                             ));
                             Err(())
@@ -220,6 +245,7 @@ impl ArgCoercer {
                         let mut fields = BamlMap::new();
 
                         for f in c.walk_fields() {
+                            scope.push(f.name().to_string());
                             if let Some(v) = obj.get(f.name()) {
                                 if let Ok(v) = self.coerce_arg(ir, f.r#type(), v, scope) {
                                     fields.insert(f.name().to_string(), v);
@@ -231,6 +257,7 @@ impl ArgCoercer {
                                     name
                                 ));
                             }
+                            scope.pop(false);
                         }
                         let is_dynamic = c.item.attributes.get("dynamic_type").is_some();
                         if is_dynamic {
@@ -266,10 +293,12 @@ impl ArgCoercer {
             (FieldType::List(item), _) => match value {
                 BamlValue::List(arr) => {
                     let mut items = Vec::new();
-                    for v in arr {
+                    for (i, v) in arr.iter().enumerate() {
+                        scope.push(i.to_string());
                         if let Ok(v) = self.coerce_arg(ir, item, v, scope) {
                             items.push(v);
                         }
+                        scope.pop(false);
                     }
                     Ok(BamlValue::List(items))
                 }
@@ -399,7 +428,7 @@ fn first_failing_assert_nested<'a>(
 
 #[cfg(test)]
 mod tests {
-    use baml_types::JinjaExpression;
+    use baml_types::{BamlMediaType, JinjaExpression};
 
     use crate::ir::repr::make_test_ir;
 
@@ -439,4 +468,36 @@ mod tests {
         let res = arg_coercer.coerce_arg(&ir, &type_, &value, &mut ScopeStack::new());
         assert!(res.is_err());
     }
+
+    #[test]
+    fn test_uploaded_file_argument() {
+        let ir = make_test_ir("").unwrap();
+        let type_ = FieldType::Primitive(TypeValue::Media(BamlMediaType::Image));
+        let value = BamlValue::Map(
+            [
+                ("file_id".to_string(), BamlValue::String("file-abc".into())),
+                (
+                    "media_type".to_string(),
+                    BamlValue::String("image/png".into()),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let arg_coercer = ArgCoercer {
+            span_path: None,
+            allow_implicit_cast_to_string: true,
+        };
+        let res = arg_coercer
+            .coerce_arg(&ir, &type_, &value, &mut ScopeStack::new())
+            .unwrap();
+        assert_eq!(
+            res,
+            BamlValue::Media(baml_types::BamlMedia::file_ref(
+                BamlMediaType::Image,
+                "file-abc".into(),
+                Some("image/png".into()),
+            ))
+        );
+    }
 }
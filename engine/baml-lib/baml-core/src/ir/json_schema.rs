@@ -1,11 +1,13 @@
 // JSON Schema
 
-use baml_types::TypeValue;
+use std::collections::HashSet;
+
+use baml_types::{LiteralValue, TypeValue};
 use serde_json::json;
 
 use super::{
     repr::{self},
-    Class, Enum, FieldType, FunctionArgs, FunctionNode, IntermediateRepr, Walker,
+    Class, Enum, FieldType, FunctionArgs, FunctionNode, IRHelper, IntermediateRepr, Walker,
 };
 
 pub trait WithJsonSchema {
@@ -117,11 +119,10 @@ impl WithJsonSchema for Walker<'_, &Enum> {
     fn json_schema(&self) -> serde_json::Value {
         json!({
                 "title": self.elem().name,
+                "type": "string",
                 "enum": self.elem().values
                     .iter()
-                    .map(|v| json!({
-                        "const": v.0.elem.0.clone()
-                    }))
+                    .map(|v| v.0.elem.0.clone())
                     .collect::<Vec<_>>(),
 
         })
@@ -156,9 +157,11 @@ impl WithJsonSchema for FieldType {
             FieldType::Class(name) | FieldType::Enum(name) => json!({
                 "$ref": format!("#/definitions/{}", name),
             }),
-            FieldType::Literal(v) => json!({
-                "const": v.to_string(),
-            }),
+            FieldType::Literal(v) => match v {
+                LiteralValue::String(s) => json!({ "const": s }),
+                LiteralValue::Int(i) => json!({ "const": i }),
+                LiteralValue::Bool(b) => json!({ "const": b }),
+            },
             FieldType::Primitive(t) => match t {
                 TypeValue::String => json!({
                     "type": "string",
@@ -192,9 +195,7 @@ impl WithJsonSchema for FieldType {
             }),
             FieldType::Map(_k, v) => json!({
                 "type": "object",
-                "additionalProperties": {
-                    "type": v.json_schema(),
-                }
+                "additionalProperties": v.json_schema(),
             }),
             FieldType::Union(options) => json!({
                 "anyOf": options.iter().map(|t| {
@@ -237,3 +238,114 @@ impl WithJsonSchema for FieldType {
         }
     }
 }
+
+/// The JSON schema of `field_type`, with the classes and enums it uses under
+/// `definitions` so it stands on its own.
+pub fn standalone_json_schema(ir: &IntermediateRepr, field_type: &FieldType) -> serde_json::Value {
+    let mut seen = HashSet::new();
+    let mut definitions = serde_json::Map::new();
+    add_definitions(ir, field_type, &mut seen, &mut definitions);
+
+    let mut schema = field_type.json_schema();
+    if !definitions.is_empty() {
+        if let serde_json::Value::Object(schema) = &mut schema {
+            schema.insert("definitions".into(), definitions.into());
+        }
+    }
+    schema
+}
+
+fn add_definitions(
+    ir: &IntermediateRepr,
+    field_type: &FieldType,
+    seen: &mut HashSet<String>,
+    definitions: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    match field_type {
+        FieldType::Class(name) => {
+            if !seen.insert(name.clone()) {
+                return;
+            }
+            let Ok(class) = ir.find_class(name) else {
+                return;
+            };
+            definitions.insert(name.clone(), class.json_schema());
+            for field in class.elem().static_fields.iter() {
+                add_definitions(ir, &field.elem.r#type.elem, seen, definitions);
+            }
+        }
+        FieldType::Enum(name) => {
+            if seen.insert(name.clone()) {
+                if let Ok(e) = ir.find_enum(name) {
+                    definitions.insert(name.clone(), e.json_schema());
+                }
+            }
+        }
+        FieldType::List(inner) | FieldType::Optional(inner) => {
+            add_definitions(ir, inner, seen, definitions)
+        }
+        FieldType::Map(_, value) => add_definitions(ir, value, seen, definitions),
+        FieldType::Union(items) | FieldType::Tuple(items) => {
+            for item in items {
+                add_definitions(ir, item, seen, definitions);
+            }
+        }
+        FieldType::Constrained { base, .. } => add_definitions(ir, base, seen, definitions),
+        FieldType::Primitive(_) | FieldType::Literal(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::repr::make_test_ir;
+
+    #[test]
+    fn standalone_schema_has_its_definitions() {
+        let ir = make_test_ir(
+            r##"
+            enum Status {
+                Active
+                Inactive
+            }
+
+            class Address {
+                city string
+            }
+
+            class User {
+                name string
+                status Status
+                addresses Address[]
+                kind "admin" | "member"
+                nickname string?
+            }
+
+            class Unused {
+                value int
+            }
+            "##,
+        )
+        .unwrap();
+
+        let schema = standalone_json_schema(&ir, &FieldType::class("User"));
+        assert_eq!(schema["$ref"], "#/definitions/User");
+        let definitions = schema["definitions"].as_object().unwrap();
+        assert_eq!(
+            definitions.keys().collect::<Vec<_>>(),
+            ["User", "Status", "Address"]
+        );
+        assert_eq!(
+            definitions["User"]["required"],
+            json!(["name", "status", "addresses", "kind"])
+        );
+        assert_eq!(definitions["Status"]["enum"], json!(["Active", "Inactive"]));
+        assert_eq!(
+            definitions["User"]["properties"]["kind"]["anyOf"][0]["const"],
+            "admin"
+        );
+
+        let schema = standalone_json_schema(&ir, &FieldType::string());
+        assert_eq!(schema, json!({ "type": "string" }));
+    }
+}
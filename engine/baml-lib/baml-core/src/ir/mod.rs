@@ -5,10 +5,12 @@ pub mod repr;
 mod walker;
 
 pub use ir_helpers::{
-    scope_diagnostics, ArgCoercer, ClassFieldWalker, ClassWalker, ClientWalker, EnumValueWalker,
-    EnumWalker, FunctionWalker, IRHelper, RetryPolicyWalker, TemplateStringWalker, TestCaseWalker,
+    infer_type, scope_diagnostics, ArgCoercer, ClassFieldWalker, ClassWalker, ClientWalker,
+    EnumValueWalker, EnumWalker, FunctionWalker, IRHelper, RetryPolicyWalker, TemplateStringWalker,
+    TestCaseWalker,
 };
 
+pub use json_schema::standalone_json_schema;
 pub(super) use repr::IntermediateRepr;
 
 // Add aliases for the IR types
@@ -1,7 +1,11 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
-use baml_types::{Constraint, ConstraintLevel, FieldType, StringOr, UnresolvedValue};
+use baml_types::{
+    BamlValue, BoolStrings, ClientKind, Constraint, ConstraintLevel, EnumMatching, FieldType,
+    PiiKind, StreamingBehavior, StringOr, UnresolvedValue,
+};
 use either::Either;
 use indexmap::{IndexMap, IndexSet};
 use internal_baml_parser_database::{
@@ -9,7 +13,8 @@ use internal_baml_parser_database::{
         ClassWalker, ClientWalker, ConfigurationWalker, EnumValueWalker, EnumWalker, FieldWalker,
         FunctionWalker, TemplateStringWalker, Walker as AstWalker,
     },
-    Attributes, ParserDatabase, PromptAst, RetryPolicyStrategy,
+    Attributes, ParserDatabase, PromptAst, RetryHedge, RetryPolicyStrategy, TraceCapture,
+    VotingStrategy,
 };
 use internal_baml_schema_ast::ast::{SubType, ValExpId};
 
@@ -34,8 +39,37 @@ pub struct IntermediateRepr {
     clients: Vec<Node<Client>>,
     retry_policies: Vec<Node<RetryPolicy>>,
     template_strings: Vec<Node<TemplateString>>,
+    profiles: Vec<Node<Profile>>,
 
     configuration: Configuration,
+
+    /// Name to position lookup tables for the vectors above. Rebuilt by
+    /// [`IntermediateRepr::reindex`] whenever those vectors are reordered.
+    index: IrIndex,
+}
+
+/// O(1) name lookups into the node vectors of an [`IntermediateRepr`].
+#[derive(Debug, Default)]
+struct IrIndex {
+    enums: HashMap<String, usize>,
+    classes: HashMap<String, usize>,
+    functions: HashMap<String, usize>,
+    clients: HashMap<String, usize>,
+    retry_policies: HashMap<String, usize>,
+    template_strings: HashMap<String, usize>,
+}
+
+impl IrIndex {
+    fn positions<'a, T: 'a>(
+        nodes: &'a [Node<T>],
+        name: impl Fn(&'a T) -> &'a str,
+    ) -> HashMap<String, usize> {
+        nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (name(&node.elem).to_string(), idx))
+            .collect()
+    }
 }
 
 /// A generic walker. Only walkers instantiated with a concrete ID type (`I`) are useful.
@@ -57,10 +91,25 @@ impl IntermediateRepr {
             clients: vec![],
             retry_policies: vec![],
             template_strings: vec![],
+            profiles: vec![],
             configuration: Configuration::new(),
+            index: IrIndex::default(),
         }
     }
 
+    /// Rebuild the name lookup tables. Must be called after any of the node
+    /// vectors are mutated or reordered.
+    fn reindex(&mut self) {
+        self.index = IrIndex {
+            enums: IrIndex::positions(&self.enums, |e| e.name.as_str()),
+            classes: IrIndex::positions(&self.classes, |c| c.name.as_str()),
+            functions: IrIndex::positions(&self.functions, |f| f.name()),
+            clients: IrIndex::positions(&self.clients, |c| c.name.as_str()),
+            retry_policies: IrIndex::positions(&self.retry_policies, |r| r.name.0.as_str()),
+            template_strings: IrIndex::positions(&self.template_strings, |t| t.name.as_str()),
+        };
+    }
+
     pub fn configuration(&self) -> &Configuration {
         &self.configuration
     }
@@ -90,6 +139,71 @@ impl IntermediateRepr {
         env_vars
     }
 
+    /// Switches every client to its options for the profile, and returns the
+    /// environment variables the profile overrides.
+    pub fn apply_profile(&mut self, name: &str) -> Result<IndexMap<String, String>> {
+        let Some(profile) = self.profiles.iter().find(|p| p.elem.name == name) else {
+            anyhow::bail!(
+                "Unknown profile `{name}`. Available profiles: [{}]",
+                self.profiles
+                    .iter()
+                    .map(|p| p.elem.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        };
+        let env_overrides = profile.elem.env_overrides.clone();
+        for client in &mut self.clients {
+            if let Some(options) = client.elem.profile_options.swap_remove(name) {
+                client.elem.options = options;
+            }
+        }
+        Ok(env_overrides)
+    }
+
+    /// Drops the functions, clients and tests whose `@@if` doesn't hold for
+    /// `env_vars`. Fails if a function that's kept uses a client that isn't.
+    pub fn apply_conditions(&mut self, env_vars: &HashMap<String, String>) -> Result<()> {
+        let holds = |condition: &Option<Condition>| {
+            condition
+                .as_ref()
+                .map_or(true, |condition| condition.holds(env_vars))
+        };
+
+        let excluded_clients = self
+            .clients
+            .iter()
+            .filter(|client| !holds(&client.elem.condition))
+            .filter_map(|client| {
+                let condition = client.elem.condition.clone()?;
+                Some((client.elem.name.clone(), condition))
+            })
+            .collect::<HashMap<_, _>>();
+        self.clients.retain(|client| holds(&client.elem.condition));
+        self.functions
+            .retain(|function| holds(&function.elem.condition));
+
+        for function in &mut self.functions {
+            function
+                .elem
+                .tests
+                .retain(|test| holds(&test.elem.condition));
+            for config in &function.elem.configs {
+                if let ClientSpec::Named(client) = &config.client {
+                    if let Some(condition) = excluded_clients.get(client) {
+                        anyhow::bail!(
+                            "Function `{}` uses client `{client}`, which is excluded by its `{condition}`",
+                            function.elem.name
+                        );
+                    }
+                }
+            }
+        }
+
+        self.reindex();
+        Ok(())
+    }
+
     /// Returns a list of all the recursive cycles in the IR.
     ///
     /// Each cycle is represented as a set of strings, where each string is the
@@ -98,6 +212,54 @@ impl IntermediateRepr {
         &self.finite_recursive_cycles
     }
 
+    /// Look up an enum by name in constant time.
+    pub fn enum_by_name(&self, name: &str) -> Option<Walker<'_, &Node<Enum>>> {
+        self.index.enums.get(name).map(|&idx| Walker {
+            db: self,
+            item: &self.enums[idx],
+        })
+    }
+
+    /// Look up a class by name in constant time.
+    pub fn class_by_name(&self, name: &str) -> Option<Walker<'_, &Node<Class>>> {
+        self.index.classes.get(name).map(|&idx| Walker {
+            db: self,
+            item: &self.classes[idx],
+        })
+    }
+
+    /// Look up a function by name in constant time.
+    pub fn function_by_name(&self, name: &str) -> Option<Walker<'_, &Node<Function>>> {
+        self.index.functions.get(name).map(|&idx| Walker {
+            db: self,
+            item: &self.functions[idx],
+        })
+    }
+
+    /// Look up a client by name in constant time.
+    pub fn client_by_name(&self, name: &str) -> Option<Walker<'_, &Node<Client>>> {
+        self.index.clients.get(name).map(|&idx| Walker {
+            db: self,
+            item: &self.clients[idx],
+        })
+    }
+
+    /// Look up a retry policy by name in constant time.
+    pub fn retry_policy_by_name(&self, name: &str) -> Option<Walker<'_, &Node<RetryPolicy>>> {
+        self.index.retry_policies.get(name).map(|&idx| Walker {
+            db: self,
+            item: &self.retry_policies[idx],
+        })
+    }
+
+    /// Look up a template string by name in constant time.
+    pub fn template_string_by_name(&self, name: &str) -> Option<Walker<'_, &Node<TemplateString>>> {
+        self.index.template_strings.get(name).map(|&idx| Walker {
+            db: self,
+            item: &self.template_strings[idx],
+        })
+    }
+
     pub fn walk_enums(&self) -> impl ExactSizeIterator<Item = Walker<'_, &Node<Enum>>> {
         self.enums.iter().map(|e| Walker { db: self, item: e })
     }
@@ -184,7 +346,12 @@ impl IntermediateRepr {
                 .walk_templates()
                 .map(|e| e.node(db))
                 .collect::<Result<Vec<_>>>()?,
+            profiles: db
+                .walk_profiles()
+                .map(|e| WithRepr::<Profile>::node(&e, db))
+                .collect::<Result<Vec<_>>>()?,
             configuration,
+            index: IrIndex::default(),
         };
 
         // Sort each item by name.
@@ -195,6 +362,7 @@ impl IntermediateRepr {
         repr.clients.sort_by(|a, b| a.elem.name.cmp(&b.elem.name));
         repr.retry_policies
             .sort_by(|a, b| a.elem.name.0.cmp(&b.elem.name.0));
+        repr.reindex();
 
         Ok(repr)
     }
@@ -508,7 +676,12 @@ type TemplateStringId = String;
 pub struct TemplateString {
     pub name: TemplateStringId,
     pub params: Vec<Field>,
+    /// The value of each parameter that has a default, keyed by name.
+    pub defaults: IndexMap<String, BamlValue>,
     pub content: String,
+    /// The raw string `content` was read from, used to trace render errors
+    /// back to the file.
+    pub source: Option<ast::RawString>,
 }
 
 impl WithRepr<TemplateString> for TemplateStringWalker<'_> {
@@ -533,12 +706,21 @@ impl WithRepr<TemplateString> for TemplateStringWalker<'_> {
                                 name: id.name().to_string(),
                                 r#type: f,
                                 docstring: None,
+                                bool_strings: BoolStrings::default(),
+                                streaming: StreamingBehavior::default(),
                             })
                             .ok()
                     })
                     .collect::<Vec<_>>()
             }),
+            defaults: self.ast_node().input().map_or(IndexMap::new(), |e| {
+                e.args
+                    .iter()
+                    .filter_map(|(id, arg)| Some((id.name().to_string(), arg.default_value()?)))
+                    .collect()
+            }),
             content: self.template_string().to_string(),
+            source: self.template_raw().cloned(),
         })
     }
 }
@@ -553,6 +735,8 @@ pub struct Enum {
     pub values: Vec<(Node<EnumValue>, Option<Docstring>)>,
     /// Docstring.
     pub docstring: Option<Docstring>,
+    /// How the values are matched when parsing, from `@@match`.
+    pub matching: EnumMatching,
 }
 
 impl WithRepr<EnumValue> for EnumValueWalker<'_> {
@@ -595,6 +779,7 @@ impl WithRepr<Enum> for EnumWalker<'_> {
                 })
                 .collect::<Result<Vec<_>, _>>()?,
             docstring: self.get_documentation().map(Docstring),
+            matching: self.matching().unwrap_or_default(),
         })
     }
 }
@@ -607,6 +792,11 @@ pub struct Field {
     pub name: String,
     pub r#type: Node<FieldType>,
     pub docstring: Option<Docstring>,
+    /// Which strings bools are read from, from `@bool_strings`.
+    pub bool_strings: BoolStrings,
+    /// How the field shows up in partial results, from `@stream.not_null`
+    /// and `@stream.with_state`.
+    pub streaming: StreamingBehavior,
 }
 
 impl WithRepr<Field> for FieldWalker<'_> {
@@ -637,6 +827,8 @@ impl WithRepr<Field> for FieldWalker<'_> {
                 attributes: self.attributes(db),
             },
             docstring: self.get_documentation().map(Docstring),
+            bool_strings: self.bool_strings().unwrap_or_default(),
+            streaming: self.streaming(),
         })
     }
 }
@@ -783,6 +975,15 @@ pub struct Function {
     pub tests: Vec<Node<TestCase>>,
     pub configs: Vec<FunctionConfig>,
     pub default_config: String,
+    /// The function's `@@if`, see [`IntermediateRepr::apply_conditions`].
+    pub condition: Option<Condition>,
+    /// The function's `@@confidence`. Only functions returning an enum have
+    /// one.
+    pub confidence: Option<Confidence>,
+    /// The function's `@@self_consistency`.
+    pub self_consistency: Option<SelfConsistency>,
+    /// The function's `@@trace`.
+    pub trace: Option<TraceSettings>,
 }
 
 #[derive(Debug)]
@@ -790,7 +991,12 @@ pub struct FunctionConfig {
     pub name: String,
     pub prompt_template: String,
     pub prompt_span: ast::Span,
+    /// The raw string `prompt_template` was read from, used to trace render
+    /// errors back to the file.
+    pub prompt_source: Option<ast::RawString>,
     pub client: ClientSpec,
+    /// What to mask in the rendered prompt. Empty if nothing.
+    pub mask_pii: Vec<PiiKind>,
 }
 
 // impl std::fmt::Display for ClientSpec {
@@ -879,12 +1085,18 @@ impl WithRepr<Function> for FunctionWalker<'_> {
                 name: "default_config".to_string(),
                 prompt_template: self.jinja_prompt().to_string(),
                 prompt_span: self.ast_function().span().clone(),
+                prompt_source: self.metadata().prompt.clone(),
                 client: match self.client_spec() {
                     Ok(spec) => spec,
                     Err(e) => anyhow::bail!("{}", e.message()),
                 },
+                mask_pii: self.metadata().mask_pii.clone(),
             }],
             default_config: "default_config".to_string(),
+            condition: self.condition().map(Condition::from),
+            confidence: self.confidence().map(Confidence::from),
+            self_consistency: self.self_consistency().map(SelfConsistency::from),
+            trace: self.trace().map(TraceSettings::from),
             tests: self
                 .walk_tests()
                 .map(|e| e.node(db))
@@ -899,8 +1111,12 @@ type ClientId = String;
 pub struct Client {
     pub name: ClientId,
     pub provider: ClientProvider,
+    pub kind: ClientKind,
     pub retry_policy_id: Option<String>,
     pub options: UnresolvedClientProperty<()>,
+    /// `options` with each profile's `client_defaults` filled in.
+    pub profile_options: IndexMap<String, UnresolvedClientProperty<()>>,
+    pub condition: Option<Condition>,
 }
 
 impl WithRepr<Client> for ClientWalker<'_> {
@@ -916,12 +1132,20 @@ impl WithRepr<Client> for ClientWalker<'_> {
         Ok(Client {
             name: self.name().to_string(),
             provider: self.properties().provider.0.clone(),
+            kind: self.kind(),
             options: self.properties().options.without_meta(),
+            profile_options: self
+                .properties()
+                .profile_options
+                .iter()
+                .map(|(name, options)| (name.clone(), options.without_meta()))
+                .collect(),
             retry_policy_id: self
                 .properties()
                 .retry_policy
                 .as_ref()
                 .map(|(id, _)| id.clone()),
+            condition: self.condition().map(Condition::from),
         })
     }
 }
@@ -934,6 +1158,8 @@ pub struct RetryPolicy {
     pub name: RetryPolicyId,
     pub max_retries: u32,
     pub strategy: RetryPolicyStrategy,
+    pub retry_on: Option<Vec<u16>>,
+    pub hedge: Option<RetryHedge>,
     // NB: the parser DB has a notion of "empty options" vs "no options"; we collapse
     // those here into an empty vec
     options: Vec<(String, UnresolvedValue<()>)>,
@@ -953,6 +1179,8 @@ impl WithRepr<RetryPolicy> for ConfigurationWalker<'_> {
             name: RetryPolicyId(self.name().to_string()),
             max_retries: self.retry_policy().max_retries,
             strategy: self.retry_policy().strategy,
+            retry_on: self.retry_policy().retry_on.clone(),
+            hedge: self.retry_policy().hedge,
             options: match &self.retry_policy().options {
                 Some(o) => o
                     .iter()
@@ -964,6 +1192,35 @@ impl WithRepr<RetryPolicy> for ConfigurationWalker<'_> {
     }
 }
 
+/// A `profile` block, applied with [`IntermediateRepr::apply_profile`].
+#[derive(Debug)]
+pub struct Profile {
+    pub name: String,
+    pub env_overrides: IndexMap<String, String>,
+}
+
+impl WithRepr<Profile> for ConfigurationWalker<'_> {
+    fn attributes(&self, _db: &ParserDatabase) -> NodeAttributes {
+        NodeAttributes {
+            meta: IndexMap::new(),
+            constraints: Vec::new(),
+            span: Some(self.span().clone()),
+        }
+    }
+
+    fn repr(&self, _db: &ParserDatabase) -> Result<Profile> {
+        Ok(Profile {
+            name: self.name().to_string(),
+            env_overrides: self
+                .profile()
+                .env_overrides
+                .iter()
+                .map(|(k, (_, v))| (k.clone(), v.clone()))
+                .collect(),
+        })
+    }
+}
+
 #[derive(serde::Serialize, Debug)]
 pub struct TestCaseFunction(String);
 
@@ -979,6 +1236,19 @@ pub struct TestCase {
     pub functions: Vec<Node<TestCaseFunction>>,
     pub args: IndexMap<String, UnresolvedValue<()>>,
     pub constraints: Vec<Constraint>,
+    pub expect: Option<Expectation>,
+    /// The JSONL file of rows the test runs with, resolved against the file
+    /// the test is in.
+    pub dataset: Option<PathBuf>,
+    pub condition: Option<Condition>,
+}
+
+/// `@@expect` on a test: the parsed output must equal `value`.
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    pub value: UnresolvedValue<()>,
+    pub float_tolerance: f64,
+    pub unordered_lists: bool,
 }
 
 impl WithRepr<TestCaseFunction> for (&ConfigurationWalker<'_>, usize) {
@@ -1041,9 +1311,112 @@ impl WithRepr<TestCase> for ConfigurationWalker<'_> {
             .constraints
             .into_iter()
             .collect::<Vec<_>>(),
+            expect: self.test_case().expect.as_ref().map(|e| Expectation {
+                value: e.value.without_meta(),
+                float_tolerance: e.float_tolerance,
+                unordered_lists: e.unordered_lists,
+            }),
+            dataset: self.test_case().dataset.as_ref().map(|(path, span)| {
+                match span.file.path_buf().parent() {
+                    Some(dir) => dir.join(path),
+                    None => PathBuf::from(path),
+                }
+            }),
+            condition: self.condition().map(Condition::from),
         })
     }
 }
+
+/// `@@if(env.FEATURE_X)` or `@@if(env.STAGE, "canary")` on a function,
+/// client or test, see [`IntermediateRepr::apply_conditions`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub env_var: String,
+    /// The value the variable must have. Without one, the variable must be
+    /// set to something other than `""`, `0` or `false`.
+    pub equals: Option<String>,
+}
+
+impl Condition {
+    pub fn holds(&self, env_vars: &HashMap<String, String>) -> bool {
+        let value = env_vars.get(&self.env_var).map(|value| value.trim());
+        match (&self.equals, value) {
+            (Some(expected), Some(value)) => value == expected,
+            (None, Some(value)) => {
+                !(value.is_empty() || value == "0" || value.eq_ignore_ascii_case("false"))
+            }
+            (_, None) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.equals {
+            Some(value) => write!(f, "@@if(env.{}, {value:?})", self.env_var),
+            None => write!(f, "@@if(env.{})", self.env_var),
+        }
+    }
+}
+
+impl From<&internal_baml_parser_database::Condition> for Condition {
+    fn from(condition: &internal_baml_parser_database::Condition) -> Self {
+        Condition {
+            env_var: condition.env_var.clone(),
+            equals: condition.equals.clone(),
+        }
+    }
+}
+
+/// `@@confidence` or `@@confidence(5)` on a function returning an enum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Confidence {
+    /// How many times to call the function when the client doesn't return
+    /// logprobs.
+    pub samples: Option<u32>,
+}
+
+impl From<&internal_baml_parser_database::ConfidenceMode> for Confidence {
+    fn from(confidence: &internal_baml_parser_database::ConfidenceMode) -> Self {
+        Confidence {
+            samples: confidence.samples,
+        }
+    }
+}
+
+/// `@@self_consistency` on a function: the runtime calls it `samples` times
+/// and combines the parsed results with `strategy`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfConsistency {
+    pub samples: u32,
+    pub strategy: VotingStrategy,
+}
+
+impl From<&internal_baml_parser_database::SelfConsistency> for SelfConsistency {
+    fn from(self_consistency: &internal_baml_parser_database::SelfConsistency) -> Self {
+        SelfConsistency {
+            samples: self_consistency.samples,
+            strategy: self_consistency.strategy,
+        }
+    }
+}
+
+/// `@@trace` on a function: the runtime sends a `sample` share of its calls
+/// to logs and trace exporters, recording what `capture` allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSettings {
+    pub sample: f64,
+    pub capture: TraceCapture,
+}
+
+impl From<&internal_baml_parser_database::TraceSettings> for TraceSettings {
+    fn from(trace: &internal_baml_parser_database::TraceSettings) -> Self {
+        TraceSettings {
+            sample: trace.sample,
+            capture: trace.capture,
+        }
+    }
+}
 #[derive(Debug, Clone, Serialize)]
 pub enum Prompt {
     // The prompt stirng, and a list of input replacer keys (raw key w/ magic string, and key to replace with)
@@ -1202,4 +1575,365 @@ mod tests {
         let walker = ir.find_test(&function, "Foo").unwrap();
         assert_eq!(walker.item.1.elem.constraints.len(), 1);
     }
+
+    #[test]
+    fn test_apply_profile() {
+        let mut ir = make_test_ir(
+            r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+                api_key env.OPENAI_API_KEY
+              }
+            }
+
+            profile dev {
+              client_defaults {
+                base_url env.DEV_PROXY_URL
+              }
+              env_overrides {
+                OPENAI_API_KEY "sk-dev"
+              }
+            }
+        "##,
+        )
+        .unwrap();
+
+        let client = ir.find_client("GPT4").unwrap();
+        assert!(!client.required_env_vars().contains("DEV_PROXY_URL"));
+
+        let err = ir.apply_profile("prod").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown profile `prod`. Available profiles: [dev]"
+        );
+
+        let env_overrides = ir.apply_profile("dev").unwrap();
+        assert_eq!(
+            env_overrides.get("OPENAI_API_KEY").map(String::as_str),
+            Some("sk-dev")
+        );
+        let client = ir.find_client("GPT4").unwrap();
+        assert!(client.required_env_vars().contains("DEV_PROXY_URL"));
+    }
+
+    #[test]
+    fn test_apply_conditions() {
+        let source = r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+              }
+            }
+
+            client<llm> Canary {
+              provider openai
+              options {
+                model gpt-4o-mini
+              }
+              @@if(env.STAGE, "canary")
+            }
+
+            function Extract(text: string) -> string {
+              client GPT4
+              prompt #"{{ text }}"#
+            }
+
+            function ExtractV2(text: string) -> string {
+              client GPT4
+              prompt #"Extract: {{ text }}"#
+              @@if(env.EXTRACT_V2)
+            }
+
+            test Stable {
+              functions [Extract]
+              args { text "hi" }
+            }
+
+            test Experimental {
+              functions [Extract]
+              args { text "hi" }
+              @@if(env.EXTRACT_V2)
+            }
+        "##;
+        let env = |vars: &[(&str, &str)]| {
+            vars.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let mut ir = make_test_ir(source).unwrap();
+        ir.apply_conditions(&env(&[("EXTRACT_V2", "false")]))
+            .unwrap();
+        assert!(ir.find_function("ExtractV2").is_err());
+        assert!(ir.find_client("Canary").is_err());
+        let extract = ir.find_function("Extract").unwrap();
+        assert_eq!(
+            extract
+                .walk_tests()
+                .map(|t| t.test_case().name.clone())
+                .collect::<Vec<_>>(),
+            vec!["Stable"]
+        );
+
+        let mut ir = make_test_ir(source).unwrap();
+        ir.apply_conditions(&env(&[("EXTRACT_V2", "1"), ("STAGE", "canary")]))
+            .unwrap();
+        assert!(ir.find_function("ExtractV2").is_ok());
+        assert!(ir.find_client("Canary").is_ok());
+        assert_eq!(ir.find_function("Extract").unwrap().walk_tests().count(), 2);
+    }
+
+    #[test]
+    fn test_confidence() {
+        let source = r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+              }
+            }
+
+            enum Sentiment {
+              Positive
+              Negative
+            }
+
+            function Classify(text: string) -> Sentiment {
+              client GPT4
+              prompt #"{{ text }}"#
+              @@confidence(5)
+            }
+
+            function ClassifyFast(text: string) -> Sentiment {
+              client GPT4
+              prompt #"{{ text }}"#
+              @@confidence
+            }
+        "##;
+        let ir = make_test_ir(source).unwrap();
+        assert_eq!(
+            ir.find_function("Classify").unwrap().confidence(),
+            Some(&Confidence { samples: Some(5) })
+        );
+        assert_eq!(
+            ir.find_function("ClassifyFast").unwrap().confidence(),
+            Some(&Confidence { samples: None })
+        );
+
+        assert!(make_test_ir(
+            r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+              }
+            }
+
+            function Extract(text: string) -> string {
+              client GPT4
+              prompt #"{{ text }}"#
+              @@confidence
+            }
+            "##
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_self_consistency() {
+        let source = r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+              }
+            }
+
+            class Answer {
+              value int
+              reasoning string
+            }
+
+            function Solve(question: string) -> Answer {
+              client GPT4
+              prompt #"{{ question }}"#
+              @@self_consistency
+            }
+
+            function SolveStrict(question: string) -> Answer {
+              client GPT4
+              prompt #"{{ question }}"#
+              @@self_consistency({ samples 3, strategy "most_common" })
+            }
+        "##;
+        let ir = make_test_ir(source).unwrap();
+        assert_eq!(
+            ir.find_function("Solve").unwrap().self_consistency(),
+            Some(&SelfConsistency {
+                samples: 5,
+                strategy: VotingStrategy::Majority,
+            })
+        );
+        assert_eq!(
+            ir.find_function("SolveStrict").unwrap().self_consistency(),
+            Some(&SelfConsistency {
+                samples: 3,
+                strategy: VotingStrategy::MostCommon,
+            })
+        );
+
+        for options in [
+            "{ samples 1 }",
+            "{ strategy \"average\" }",
+            "{ temperature 0.7 }",
+        ] {
+            let source = source.replace("{ samples 3, strategy \"most_common\" }", options);
+            assert!(make_test_ir(&source).is_err(), "{options}");
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let source = r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+              }
+            }
+
+            function Summarize(text: string) -> string {
+              client GPT4
+              prompt #"{{ text }}"#
+              @@trace
+            }
+
+            function ExtractMedicalRecord(text: string) -> string {
+              client GPT4
+              prompt #"{{ text }}"#
+              @@trace({ sample 0.1, capture "metadata_only" })
+            }
+        "##;
+        let ir = make_test_ir(source).unwrap();
+        assert_eq!(
+            ir.find_function("Summarize").unwrap().trace(),
+            Some(&TraceSettings {
+                sample: 1.0,
+                capture: TraceCapture::Full,
+            })
+        );
+        assert_eq!(
+            ir.find_function("ExtractMedicalRecord").unwrap().trace(),
+            Some(&TraceSettings {
+                sample: 0.1,
+                capture: TraceCapture::MetadataOnly,
+            })
+        );
+
+        for options in [
+            "{ sample 1.5 }",
+            "{ sample \"often\" }",
+            "{ capture \"prompts\" }",
+            "{ exporter \"otel\" }",
+        ] {
+            let source = source.replace("{ sample 0.1, capture \"metadata_only\" }", options);
+            assert!(make_test_ir(&source).is_err(), "{options}");
+        }
+    }
+
+    #[test]
+    fn test_client_extends() {
+        let ir = make_test_ir(
+            r##"
+            retry_policy Twice {
+              max_retries 2
+            }
+
+            client<llm> Base {
+              retry_policy Twice
+              options {
+                temperature 0.1
+                api_key env.BASE_API_KEY
+              }
+            }
+
+            client<llm> Prod extends Base {
+              provider openai
+              options {
+                model gpt-4o
+              }
+            }
+
+            client<llm> Staging extends Prod {
+              options {
+                api_key env.STAGING_API_KEY
+              }
+            }
+        "##,
+        )
+        .unwrap();
+
+        let prod = ir.find_client("Prod").unwrap();
+        assert_eq!(prod.retry_policy().as_deref(), Some("Twice"));
+        assert!(prod.required_env_vars().contains("BASE_API_KEY"));
+
+        let staging = ir.find_client("Staging").unwrap();
+        assert_eq!(staging.elem().provider.to_string(), "openai");
+        assert!(staging.required_env_vars().contains("STAGING_API_KEY"));
+        assert!(!staging.required_env_vars().contains("BASE_API_KEY"));
+
+        // Clients without a provider are only there to be extended.
+        assert!(ir.find_client("Base").is_err());
+
+        for clients in [
+            "client<llm> Loop extends Loop {\n provider openai\n }",
+            "client<llm> A extends B {\n provider openai\n }\nclient<llm> B extends A {}",
+            "client<llm> A extends Missing {\n provider openai\n }",
+            "client<llm> Base {}",
+        ] {
+            assert!(make_test_ir(clients).is_err(), "{clients}");
+        }
+    }
+
+    #[test]
+    fn test_name_lookups() {
+        let ir = make_test_ir(
+            r##"
+            class Zed {
+              a int
+            }
+            class Alpha {
+              b string
+            }
+            enum Color {
+              RED
+            }
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+                api_key env.OPENAI_API_KEY
+              }
+            }
+            function Foo(a: int) -> Zed {
+              client GPT4
+              prompt #"{{ a }}"#
+            }
+        "##,
+        )
+        .unwrap();
+
+        assert_eq!(ir.class_by_name("Zed").unwrap().name(), "Zed");
+        assert_eq!(ir.class_by_name("Alpha").unwrap().name(), "Alpha");
+        assert!(ir.class_by_name("Missing").is_none());
+        assert_eq!(ir.enum_by_name("Color").unwrap().name(), "Color");
+        assert_eq!(ir.function_by_name("Foo").unwrap().name(), "Foo");
+        assert_eq!(ir.client_by_name("GPT4").unwrap().name(), "GPT4");
+
+        let err = ir.find_class("Alpah").unwrap_err().to_string();
+        assert!(err.contains("Alpha"), "{err}");
+    }
 }
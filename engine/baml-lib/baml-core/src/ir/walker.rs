@@ -1,5 +1,7 @@
 use anyhow::Result;
-use baml_types::{BamlValue, EvaluationContext, UnresolvedValue};
+use baml_types::{
+    BamlValue, BoolStrings, EnumMatching, EvaluationContext, StreamingBehavior, UnresolvedValue,
+};
 use indexmap::IndexMap;
 
 use internal_baml_diagnostics::Span;
@@ -27,6 +29,8 @@ fn provider_to_env_vars(
         "openai" => vec![(Some("api_key"), "OPENAI_API_KEY")],
         "anthropic" => vec![(Some("api_key"), "ANTHROPIC_API_KEY")],
         "google-ai" => vec![(Some("api_key"), "GOOGLE_API_KEY")],
+        "mistral" => vec![(Some("api_key"), "MISTRAL_API_KEY")],
+        "cohere" => vec![(Some("api_key"), "COHERE_API_KEY")],
         "vertex-ai" => vec![
             (Some("credentials"), "GOOGLE_APPLICATION_CREDENTIALS"),
             (
@@ -35,6 +39,9 @@ fn provider_to_env_vars(
             ),
         ],
         "azure-openai" => vec![(Some("api_key"), "AZURE_OPENAI_API_KEY")],
+        "azure-ai-inference" => vec![(Some("api_key"), "AZURE_AI_INFERENCE_API_KEY")],
+        "x-ai" => vec![(Some("api_key"), "XAI_API_KEY")],
+        "deepseek" => vec![(Some("api_key"), "DEEPSEEK_API_KEY")],
         "openai-generic" => vec![(Some("api_key"), "OPENAI_API_KEY")],
         "ollama" => vec![],
         other => vec![],
@@ -68,17 +75,11 @@ impl<'a> Walker<'a, &'a FunctionNode> {
                     let client: super::ClientWalker<'a> = self.db.find_client(n)?;
                     Ok(client.required_env_vars())
                 }
-                ClientSpec::Shorthand(provider, model) => {
-                    let options = IndexMap::from_iter([(
-                        "model".to_string(),
-                        (
-                            (),
-                            baml_types::UnresolvedValue::String(
-                                baml_types::StringOr::Value(model.clone()),
-                                (),
-                            ),
-                        ),
-                    )]);
+                ClientSpec::Shorthand(provider, model, options) => {
+                    let options = ClientSpec::shorthand_options(model, options)
+                        .into_iter()
+                        .map(|(key, value)| Ok((key, ((), value.to_resolvable()?))))
+                        .collect::<Result<IndexMap<_, _>>>()?;
                     let properties = internal_llm_client::PropertyHandler::<()>::new(options, ());
                     if let Ok(client) = provider.parse_client_property(properties) {
                         Ok(client.required_env_vars())
@@ -129,6 +130,18 @@ impl<'a> Walker<'a, &'a FunctionNode> {
         self.elem().inputs()
     }
 
+    pub fn confidence(&self) -> Option<&'a repr::Confidence> {
+        self.elem().confidence.as_ref()
+    }
+
+    pub fn self_consistency(&self) -> Option<&'a repr::SelfConsistency> {
+        self.elem().self_consistency.as_ref()
+    }
+
+    pub fn trace(&self) -> Option<&'a repr::TraceSettings> {
+        self.elem().trace.as_ref()
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -170,6 +183,10 @@ impl<'a> Walker<'a, &'a Enum> {
         &self.item.elem
     }
 
+    pub fn matching(&self) -> EnumMatching {
+        self.elem().matching
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -346,6 +363,26 @@ impl<'a> Walker<'a, &'a RetryPolicy> {
         &self.elem().strategy
     }
 
+    /// Whether a request that failed with HTTP `status` should be retried.
+    /// Failures without an error status, like connection errors, always are.
+    pub fn retries(&self, status: u16) -> bool {
+        match &self.elem().retry_on {
+            Some(codes) => status < 400 || codes.contains(&status),
+            None => true,
+        }
+    }
+
+    /// How long to wait for an attempt to answer before sending a duplicate
+    /// request, and how many duplicates to send at most.
+    pub fn hedge(&self) -> Option<(std::time::Duration, usize)> {
+        self.elem().hedge.map(|hedge| {
+            (
+                std::time::Duration::from_millis(hedge.after_ms.into()),
+                hedge.max_hedges as usize,
+            )
+        })
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -364,6 +401,11 @@ impl<'a> Walker<'a, &'a TemplateString> {
         &self.item.elem.params
     }
 
+    /// The default value of each parameter that has one.
+    pub fn defaults(&self) -> &'a IndexMap<String, BamlValue> {
+        &self.item.elem.defaults
+    }
+
     pub fn template(&self) -> &str {
         &self.elem().content
     }
@@ -402,6 +444,14 @@ impl<'a> Walker<'a, &'a Field> {
             .transpose()
     }
 
+    pub fn bool_strings(&self) -> BoolStrings {
+        self.elem().bool_strings
+    }
+
+    pub fn streaming(&self) -> StreamingBehavior {
+        self.elem().streaming
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -42,11 +42,40 @@ impl std::fmt::Debug for ValidatedSchema {
 
 /// The most general API for dealing with BAML source code. It accumulates what analysis and
 /// validation information it can, and returns it along with any error and warning diagnostics.
+///
+/// Warnings silenced by `// baml-ignore:` comments or a generator's `allowed_warnings` are
+/// dropped from the returned diagnostics.
 pub fn validate(root_path: &Path, files: Vec<SourceFile>) -> ValidatedSchema {
+    validate_roots(vec![(root_path.to_path_buf(), files)])
+}
+
+/// Like [`validate`], for a schema composed from several `baml_src` directories, e.g. a shared
+/// package of types and the functions of one app. Each root is paired with its files.
+///
+/// Spans keep the path of the file they're in, whichever root it's under. A name defined in two
+/// roots is reported as a conflict naming both. The first root is the primary one: generators'
+/// `output_dir`s are relative to it.
+pub fn validate_roots(roots: Vec<(PathBuf, Vec<SourceFile>)>) -> ValidatedSchema {
+    let mut schema = validate_impl(roots);
+    let allowed = schema.configuration.allowed_warnings();
+    schema
+        .diagnostics
+        .suppress_warnings(&schema.db.ast().suppressions, &allowed);
+    schema
+}
+
+fn validate_impl(roots: Vec<(PathBuf, Vec<SourceFile>)>) -> ValidatedSchema {
+    let root_path = roots
+        .first()
+        .map(|(root, _)| root.clone())
+        .unwrap_or_default();
+    let root_path = root_path.as_path();
     let mut diagnostics = Diagnostics::new(root_path.to_path_buf());
     let mut db = internal_baml_parser_database::ParserDatabase::new();
 
-    {
+    // Roots are added in order, so a conflict is reported on the definition in the later root.
+    for (root, files) in &roots {
+        db.add_root(root);
         let diagnostics = Mutex::new(&mut diagnostics);
         let db = Mutex::new(&mut db);
         files.par_iter().for_each(|file| {
@@ -5,7 +5,7 @@ use std::{
 };
 
 use bstd::ProjectFqn;
-use internal_baml_diagnostics::DatamodelError;
+use internal_baml_diagnostics::{DatamodelError, DiagnosticCode, Severity};
 use internal_baml_schema_ast::ast::{self, WithName, WithSpan};
 use semver::Version;
 use strum::VariantNames;
@@ -59,6 +59,48 @@ fn parse_optional_key<'a>(
     }
 }
 
+/// `allowed_warnings ["BAML1006", ...]` silences warnings across the project.
+fn parse_allowed_warnings(
+    map: &HashMap<&str, &ast::Expression>,
+) -> Result<Vec<DiagnosticCode>, Vec<DatamodelError>> {
+    let expr = match map.get("allowed_warnings") {
+        Some(expr) => expr,
+        None => return Ok(vec![]),
+    };
+
+    let Some((items, _)) = expr.as_array() else {
+        return Err(vec![DatamodelError::new_validation_error(
+            "`allowed_warnings` must be an array of diagnostic codes, e.g. [\"BAML1006\"].",
+            expr.span().clone(),
+        )]);
+    };
+
+    let mut codes = vec![];
+    let mut errors = vec![];
+    for item in items {
+        let code = item
+            .as_string_value()
+            .and_then(|(code, _)| code.parse::<DiagnosticCode>().ok());
+        match code {
+            Some(code) if code.severity() == Severity::Warning => codes.push(code),
+            Some(code) => errors.push(DatamodelError::new_validation_error(
+                &format!("{code} is an error and cannot be allowed. Only warnings can be."),
+                item.span().clone(),
+            )),
+            None => errors.push(DatamodelError::new_validation_error(
+                "Expected a warning code, e.g. \"BAML1006\".",
+                item.span().clone(),
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(codes)
+    } else {
+        Err(errors)
+    }
+}
+
 pub(crate) fn parse_generator(
     ast_generator: &ast::ValueExprBlock,
     baml_src: &Path,
@@ -90,6 +132,7 @@ pub(crate) fn parse_generator(
                         .baml_src(baml_src.to_path_buf())
                         .span(ast_generator.span().clone());
                     parse_cloud_project(ast_generator, &args, &mut cloud_builder)?;
+                    cloud_builder.allowed_warnings(parse_allowed_warnings(&args)?);
                     return match cloud_builder.build() {
                         Ok(generator) => Ok(Generator::BoundaryCloud(generator)),
                         Err(e) => Err(vec![DatamodelError::new_anyhow_error(
@@ -188,6 +231,15 @@ pub(crate) fn parse_generator(
         }
     }
 
+    match parse_allowed_warnings(&args) {
+        Ok(codes) => {
+            builder.allowed_warnings(codes);
+        }
+        Err(errs) => {
+            errors.extend(errs);
+        }
+    }
+
     if !errors.is_empty() {
         return Err(errors);
     }
@@ -274,6 +326,7 @@ fn check_property_allowlist<'ir>(
         "default_client_mode",
         "on_generate",
         "project",
+        "allowed_warnings",
     ];
 
     let mut errors = vec![];
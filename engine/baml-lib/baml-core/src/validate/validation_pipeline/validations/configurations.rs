@@ -1,4 +1,4 @@
-use internal_baml_diagnostics::{DatamodelError, DatamodelWarning};
+use internal_baml_diagnostics::DatamodelWarning;
 
 use crate::validate::validation_pipeline::context::Context;
 
@@ -15,7 +15,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
         let mut function_names = std::collections::HashSet::new();
         for (name, s) in &case.functions {
             if !function_names.insert(name) {
-                ctx.push_error(DatamodelError::new_duplicate_function_errors(
+                ctx.push_warning(DatamodelWarning::new_duplicate_test_function(
                     name,
                     s.clone(),
                 ));
@@ -2,12 +2,16 @@ use std::collections::HashSet;
 
 use crate::validate::validation_pipeline::context::Context;
 
+use baml_types::{ClientKind, TypeValue};
 use either::Either;
-use internal_baml_diagnostics::{DatamodelError, DatamodelWarning, Span};
+use internal_baml_diagnostics::{DatamodelError, Span};
+use internal_llm_client::{ClientProvider, ClientSpec};
 
-use internal_baml_schema_ast::ast::{FieldType, TypeExpId, WithIdentifier, WithName, WithSpan};
+use internal_baml_schema_ast::ast::{
+    FieldArity, FieldType, TypeExpId, WithIdentifier, WithName, WithSpan,
+};
 
-use super::types::validate_type;
+use super::{template_strings::push_template_type_errors, types::validate_type};
 
 pub(super) fn validate(ctx: &mut Context<'_>) {
     let clients = ctx
@@ -19,6 +23,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
     let mut defined_types = internal_baml_jinja_types::PredefinedTypes::default(
         internal_baml_jinja_types::JinjaContext::Prompt,
     );
+    defined_types.set_client_providers(ClientProvider::allowed_providers());
     ctx.db.walk_classes().for_each(|t| {
         t.add_to_types(&mut defined_types);
     });
@@ -79,15 +84,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
                         span,
                     ))
                 } else {
-                    e.errors.iter().for_each(|t| {
-                        let span = t.span();
-                        let span = Span::new(
-                            pspan.file.clone(),
-                            pspan.start + span.start_offset as usize,
-                            pspan.start + span.end_offset as usize,
-                        );
-                        ctx.push_warning(DatamodelWarning::new(t.message().to_string(), span))
-                    })
+                    push_template_type_errors(ctx, &e.errors, pspan);
                 }
             }
         }
@@ -112,6 +109,65 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
                     span,
                 ));
             }
+            if let Some(default) = &arg.1.default {
+                ctx.push_error(DatamodelError::new_validation_error(
+                    "Only template_string parameters can have default values.",
+                    default.span().clone(),
+                ));
+            }
+        }
+
+        if let Some(confidence) = func.confidence() {
+            let returns_enum = func.walk_output_args().all(|args| {
+                matches!(
+                    &args.ast_arg().1.field_type,
+                    FieldType::Symbol(FieldArity::Required, id, _)
+                        if matches!(ctx.db.find_type(id), Some(Either::Right(_)))
+                )
+            });
+            if !returns_enum {
+                ctx.push_error(DatamodelError::new_validation_error(
+                    "`@@confidence` is only allowed on functions that return an enum",
+                    confidence.span.clone(),
+                ));
+            }
+        }
+
+        if let (Some(_), Some(self_consistency)) = (func.confidence(), func.self_consistency()) {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "`@@self_consistency` can't be combined with `@@confidence`, which already samples the function",
+                self_consistency.span.clone(),
+            ));
+        }
+
+        let embedding_client = match func.client_spec() {
+            Ok(ClientSpec::Named(name)) => ctx
+                .db
+                .find_client(&name)
+                .filter(|client| client.kind() == ClientKind::Embedding),
+            _ => None,
+        };
+        if let Some(client) = embedding_client {
+            for args in func.walk_output_args() {
+                let field_type = &args.ast_arg().1.field_type;
+                let returns_vector = matches!(
+                    field_type,
+                    FieldType::List(FieldArity::Required, item, 1, ..)
+                        if matches!(
+                            **item,
+                            FieldType::Primitive(FieldArity::Required, TypeValue::Float, ..)
+                        )
+                );
+                if !returns_vector {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        &format!(
+                            "Functions using the embedding client `{}` must return `float[]`",
+                            client.name()
+                        ),
+                        field_type.span().clone(),
+                    ));
+                }
+            }
         }
 
         // Ensure the client is correct.
@@ -201,15 +257,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
                         span,
                     ))
                 } else {
-                    e.errors.iter().for_each(|t| {
-                        let span = t.span();
-                        let span = Span::new(
-                            pspan.file.clone(),
-                            pspan.start + span.start_offset as usize,
-                            pspan.start + span.end_offset as usize,
-                        );
-                        ctx.push_warning(DatamodelWarning::new(t.message().to_string(), span))
-                    })
+                    push_template_type_errors(ctx, &e.errors, pspan);
                 }
             }
         }
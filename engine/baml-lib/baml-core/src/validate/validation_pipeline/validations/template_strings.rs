@@ -2,8 +2,11 @@ use std::collections::HashSet;
 
 use crate::validate::validation_pipeline::context::Context;
 
+use baml_types::{BamlValue, LiteralValue};
 use either::Either;
-use internal_baml_diagnostics::{DatamodelError, DatamodelWarning, Span};
+use internal_baml_diagnostics::{DatamodelError, DatamodelWarning, DiagnosticCode, Span};
+use internal_baml_jinja_types::{CallError, Type, TypeError};
+use internal_llm_client::ClientProvider;
 
 use internal_baml_schema_ast::ast::{FieldType, TypeExpId, WithIdentifier, WithName, WithSpan};
 
@@ -13,6 +16,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
     let mut defined_types = internal_baml_jinja_types::PredefinedTypes::default(
         internal_baml_jinja_types::JinjaContext::Prompt,
     );
+    defined_types.set_client_providers(ClientProvider::allowed_providers());
     ctx.db.walk_classes().for_each(|t| {
         t.add_to_types(&mut defined_types);
     });
@@ -39,6 +43,33 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
             }
         }
 
+        // Parameters with a default may be left out, so they must come last.
+        let mut after_default = false;
+        for args in template.walk_input_args() {
+            let arg = args.ast_arg().1;
+            match (&arg.default, arg.default_value()) {
+                (Some(default), Some(value)) => {
+                    after_default = true;
+                    if !default_type(&value).is_subtype_of(&ctx.db.to_jinja_type(&arg.field_type)) {
+                        ctx.push_error(DatamodelError::new_validation_error(
+                            &format!(
+                                "The default value {default} doesn't match the type {}",
+                                arg.field_type
+                            ),
+                            default.span().clone(),
+                        ));
+                    }
+                }
+                (None, _) if after_default => {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        "Parameters without a default value must come before the ones with one.",
+                        arg.span().clone(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
         let prompt = match template.template_raw() {
             Some(p) => p,
             None => {
@@ -102,15 +133,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
                         span,
                     ))
                 } else {
-                    e.errors.iter().for_each(|t| {
-                        let span = t.span();
-                        let span = Span::new(
-                            pspan.file.clone(),
-                            pspan.start + span.start_offset as usize,
-                            pspan.start + span.end_offset as usize,
-                        );
-                        ctx.push_warning(DatamodelWarning::new(t.message().to_string(), span))
-                    })
+                    push_template_type_errors(ctx, &e.errors, pspan);
                 }
             }
         }
@@ -118,3 +141,61 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
         defined_types.errors_mut().clear();
     }
 }
+
+/// Reports the problems type checking a template found, relative to the
+/// template's span. Calls to template strings with arguments that don't match
+/// their parameters would fail to render, so those are errors at the call.
+pub(super) fn push_template_type_errors(ctx: &mut Context<'_>, errors: &[TypeError], span: &Span) {
+    for error in errors {
+        let error_span = error.span();
+        let error_span = Span::new(
+            span.file.clone(),
+            span.start + error_span.start_offset as usize,
+            span.start + error_span.end_offset as usize,
+        );
+        let template = error.call_error().and_then(|(name, call_error)| {
+            let template = ctx.db.walk_templates().find(|t| t.name() == name)?;
+            Some((template, call_error))
+        });
+        match template {
+            Some((template, call_error)) => {
+                let code = match call_error {
+                    CallError::WrongArgCount => DiagnosticCode::ArgumentCountMismatch,
+                    CallError::MissingArg => DiagnosticCode::MissingArgument,
+                    CallError::UnknownArg => DiagnosticCode::UnknownArgument,
+                };
+                let related = template.ast_node().identifier().span().clone();
+                ctx.push_error(
+                    DatamodelError::new_template_string_call_error(
+                        code,
+                        error.message(),
+                        error_span,
+                    )
+                    .with_related(
+                        related,
+                        format!("template_string \"{}\" defined here", template.name()),
+                    ),
+                );
+            }
+            None => ctx.push_warning(DatamodelWarning::new_template_type_error(
+                error.message().to_string(),
+                error_span,
+            )),
+        }
+    }
+}
+
+/// The type of a default value, for checking it against its parameter's type.
+fn default_type(value: &BamlValue) -> Type {
+    match value {
+        BamlValue::String(value) => Type::Literal(LiteralValue::String(value.clone())),
+        // Whole numbers are fine for floats too.
+        BamlValue::Int(value) => Type::Both(
+            Box::new(Type::Literal(LiteralValue::Int(*value))),
+            Box::new(Type::Float),
+        ),
+        BamlValue::Float(_) => Type::Float,
+        BamlValue::Bool(value) => Type::Literal(LiteralValue::Bool(*value)),
+        _ => Type::None,
+    }
+}
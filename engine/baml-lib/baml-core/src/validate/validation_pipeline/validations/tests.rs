@@ -81,7 +81,10 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
                                 expr_span.start + tspan.start_offset as usize,
                                 expr_span.start + tspan.end_offset as usize,
                             );
-                            ctx.push_warning(DatamodelWarning::new(t.message().to_string(), span))
+                            ctx.push_warning(DatamodelWarning::new_template_type_error(
+                                t.message().to_string(),
+                                span,
+                            ))
                         })
                     }
                 }
@@ -140,13 +140,7 @@ fn validate_type_constraints(ctx: &mut Context<'_>, field_type: &FieldType) {
         .iter()
         .filter(|attr| ["assert", "check"].contains(&attr.name.name()))
         .collect::<Vec<_>>();
-    for Attribute {
-        arguments,
-        span,
-        name,
-        ..
-    } in constraint_attrs.iter()
-    {
+    for Attribute { arguments, .. } in constraint_attrs.iter() {
         let arg_expressions = arguments
             .arguments
             .iter()
@@ -196,7 +190,7 @@ fn validate_type_constraints(ctx: &mut Context<'_>, field_type: &FieldType) {
                                     span.start + tspan.start_offset as usize,
                                     span.start + tspan.end_offset as usize,
                                 );
-                                ctx.push_warning(DatamodelWarning::new(
+                                ctx.push_warning(DatamodelWarning::new_template_type_error(
                                     t.message().to_string(),
                                     span,
                                 ))
@@ -246,7 +240,7 @@ fn validate_type_constraints(ctx: &mut Context<'_>, field_type: &FieldType) {
                                     span.start + tspan.start_offset as usize,
                                     span.start + tspan.end_offset as usize,
                                 );
-                                ctx.push_warning(DatamodelWarning::new(
+                                ctx.push_warning(DatamodelWarning::new_template_type_error(
                                     t.message().to_string(),
                                     span,
                                 ))
@@ -254,20 +248,10 @@ fn validate_type_constraints(ctx: &mut Context<'_>, field_type: &FieldType) {
                         }
                     }
                 }
-
-                if name.to_string() == "check" {
-                    ctx.push_error(DatamodelError::new_validation_error(
-                        "Check constraints must have a name.",
-                        span.clone(),
-                    ))
-                }
-            }
-            _ => {
-                ctx.push_error(DatamodelError::new_validation_error(
-                        "A constraint must have one Jinja argument such as {{ expr }}, and optionally one String label",
-                        span.clone()
-                    ));
             }
+            // Arguments that don't fit the signature of `@assert` or `@check`
+            // were reported by parser-database.
+            _ => {}
         }
     }
 }
@@ -0,0 +1,58 @@
+/// Which strings a `bool` is read from. Set per field with `@bool_strings`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoolStrings {
+    /// Only `true` and `false`, in any case.
+    #[default]
+    Strict,
+    /// Also `yes`/`no` and `y`/`n`.
+    YesNo,
+    /// Also `yes`/`no`, `y`/`n`, `on`/`off`, `enabled`/`disabled`, and `1`/`0`
+    /// as strings or numbers.
+    Lenient,
+}
+
+impl BoolStrings {
+    /// The names `@bool_strings` accepts, in the order they are documented.
+    pub const MODES: &'static [&'static str] = &["strict", "yes_no", "lenient"];
+
+    pub fn from_mode(mode: &str) -> Option<Self> {
+        Some(match mode {
+            "strict" => BoolStrings::Strict,
+            "yes_no" => BoolStrings::YesNo,
+            "lenient" => BoolStrings::Lenient,
+            _ => return None,
+        })
+    }
+
+    /// The bool `value` stands for beyond `true` and `false`, ignoring case
+    /// and surrounding whitespace.
+    pub fn parse(self, value: &str) -> Option<bool> {
+        let value = value.trim().to_lowercase();
+        match (self, value.as_str()) {
+            (BoolStrings::Strict, _) => None,
+            (_, "yes" | "y") => Some(true),
+            (_, "no" | "n") => Some(false),
+            (BoolStrings::YesNo, _) => None,
+            (BoolStrings::Lenient, "on" | "enabled" | "1") => Some(true),
+            (BoolStrings::Lenient, "off" | "disabled" | "0") => Some(false),
+            (BoolStrings::Lenient, _) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_by_mode() {
+        assert_eq!(BoolStrings::Strict.parse("yes"), None);
+        assert_eq!(BoolStrings::YesNo.parse(" Yes "), Some(true));
+        assert_eq!(BoolStrings::YesNo.parse("N"), Some(false));
+        assert_eq!(BoolStrings::YesNo.parse("on"), None);
+        assert_eq!(BoolStrings::Lenient.parse("Disabled"), Some(false));
+        assert_eq!(BoolStrings::Lenient.parse("1"), Some(true));
+        assert_eq!(BoolStrings::Lenient.parse("maybe"), None);
+    }
+}
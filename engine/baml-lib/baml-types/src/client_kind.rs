@@ -0,0 +1,20 @@
+/// What a client is declared as, `client<llm>` or `client<embedding>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientKind {
+    /// Generates text. Plain `client` blocks are LLM clients.
+    #[default]
+    Llm,
+    /// Turns the rendered prompt into a vector, for functions that return
+    /// `float[]`.
+    Embedding,
+}
+
+impl std::fmt::Display for ClientKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientKind::Llm => write!(f, "llm"),
+            ClientKind::Embedding => write!(f, "embedding"),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/// How the values of an enum are matched against what an LLM returned. Set
+/// per enum with `@@match`, or for every enum of a call with a `TypeBuilder`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum EnumMatching {
+    /// Looks for a value's name, alias or description anywhere in the output,
+    /// then without punctuation, then ignoring case.
+    #[default]
+    Substring,
+    /// The output must be a value's name, alias or description.
+    Exact,
+    /// Like `Exact`, ignoring case.
+    CaseInsensitive,
+    /// The output must be a value's alias, or its name if it has none,
+    /// ignoring case. Descriptions don't match.
+    Alias,
+    /// Like `CaseInsensitive`, also accepting output within `max_distance`
+    /// edits of a single value.
+    Fuzzy { max_distance: usize },
+}
+
+impl EnumMatching {
+    /// The names `@@match` accepts, in the order they are documented.
+    pub const MODES: &'static [&'static str] =
+        &["substring", "exact", "case_insensitive", "alias", "fuzzy"];
+
+    pub const DEFAULT_MAX_DISTANCE: usize = 2;
+
+    /// Parses a mode by name. `max_distance` only applies to `fuzzy`.
+    pub fn from_mode(mode: &str, max_distance: Option<usize>) -> Option<Self> {
+        Some(match mode {
+            "substring" => EnumMatching::Substring,
+            "exact" => EnumMatching::Exact,
+            "case_insensitive" => EnumMatching::CaseInsensitive,
+            "alias" => EnumMatching::Alias,
+            "fuzzy" => EnumMatching::Fuzzy {
+                max_distance: max_distance.unwrap_or(Self::DEFAULT_MAX_DISTANCE),
+            },
+            _ => return None,
+        })
+    }
+}
@@ -1,7 +1,13 @@
+mod bool_strings;
+mod client_kind;
 mod constraint;
+mod enum_matching;
 mod map;
 mod media;
 mod minijinja;
+mod number_locale;
+mod pii;
+mod streaming;
 
 mod baml_value;
 mod field_type;
@@ -9,10 +15,16 @@ mod generator;
 mod value_expr;
 
 pub use baml_value::{BamlValue, BamlValueWithMeta};
+pub use bool_strings::BoolStrings;
+pub use client_kind::ClientKind;
 pub use constraint::*;
+pub use enum_matching::EnumMatching;
 pub use field_type::{FieldType, LiteralValue, TypeValue};
 pub use generator::{GeneratorDefaultClientMode, GeneratorOutputType};
 pub use map::Map as BamlMap;
-pub use media::{BamlMedia, BamlMediaContent, BamlMediaType, MediaBase64, MediaUrl};
+pub use media::{BamlMedia, BamlMediaContent, BamlMediaType, MediaBase64, MediaFileRef, MediaUrl};
 pub use minijinja::JinjaExpression;
+pub use number_locale::NumberLocale;
+pub use pii::PiiKind;
+pub use streaming::{StreamState, StreamingBehavior};
 pub use value_expr::{EvaluationContext, GetEnvVar, ResolvedValue, StringOr, UnresolvedValue};
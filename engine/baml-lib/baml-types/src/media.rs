@@ -39,6 +39,9 @@ pub enum BamlMediaContent {
     File(MediaFile),
     Url(MediaUrl),
     Base64(MediaBase64),
+    /// A file already uploaded to the provider's file API, which is only
+    /// valid for clients of that provider.
+    FileRef(MediaFileRef),
 }
 
 impl BamlMedia {
@@ -83,6 +86,18 @@ impl BamlMedia {
             content: BamlMediaContent::Base64(MediaBase64 { base64 }),
         }
     }
+
+    pub fn file_ref(
+        media_type: BamlMediaType,
+        file_id: String,
+        mime_type: Option<String>,
+    ) -> BamlMedia {
+        Self {
+            media_type,
+            mime_type,
+            content: BamlMediaContent::FileRef(MediaFileRef { file_id }),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -128,6 +143,19 @@ impl fmt::Display for MediaUrl {
     }
 }
 
+/// The id the provider returned for an uploaded file, e.g. `file-abc123` for
+/// OpenAI or the file URI for Google AI.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct MediaFileRef {
+    pub file_id: String,
+}
+
+impl fmt::Display for MediaFileRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file_id)
+    }
+}
+
 #[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub struct MediaBase64 {
     pub base64: String,
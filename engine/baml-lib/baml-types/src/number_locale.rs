@@ -0,0 +1,25 @@
+/// Which separators numbers written as strings use, like `"1,234.56"`. Set
+/// for every number of a call with a `TypeBuilder`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberLocale {
+    /// `1,234.56`: commas group thousands and a point marks decimals.
+    #[default]
+    DecimalPoint,
+    /// `1.234,56`: points group thousands and a comma marks decimals.
+    DecimalComma,
+    /// Either, judging by each number's last separator. `1,234` and `1.234`
+    /// are read as thousands, `1,5` and `1.5` as decimals.
+    Auto,
+}
+
+impl NumberLocale {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "decimal_point" => NumberLocale::DecimalPoint,
+            "decimal_comma" => NumberLocale::DecimalComma,
+            "auto" => NumberLocale::Auto,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,27 @@
+/// Personal data a function's `mask_pii` masks in its prompts.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::Display,
+    strum::EnumString,
+    strum::VariantArray,
+    strum::VariantNames,
+    serde::Serialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    CreditCard,
+}
+
+impl PiiKind {
+    pub fn all() -> &'static [PiiKind] {
+        <Self as strum::VariantArray>::VARIANTS
+    }
+}
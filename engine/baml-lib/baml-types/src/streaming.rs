@@ -0,0 +1,41 @@
+/// How a class field shows up in partial results while a function streams.
+/// Set per field with `@stream.not_null` and `@stream.with_state`. Final
+/// results aren't affected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct StreamingBehavior {
+    /// The class is left out of partial results until the field has a value,
+    /// so the field is never null in them.
+    pub not_null: bool,
+    /// Partial results hold the field as `{"value": ..., "state": ...}`, see
+    /// [`StreamState`].
+    pub with_state: bool,
+}
+
+impl StreamingBehavior {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How far along a `@stream.with_state` field is in a partial result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamState {
+    /// The field hasn't started yet.
+    Pending,
+    /// The field is the last one the model started, so its value may still
+    /// grow.
+    Incomplete,
+    /// The model moved on to a later field.
+    Complete,
+}
+
+impl StreamState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamState::Pending => "pending",
+            StreamState::Incomplete => "incomplete",
+            StreamState::Complete => "complete",
+        }
+    }
+}
@@ -456,6 +456,7 @@ impl crate::BamlMedia {
             crate::BamlMediaContent::File(f) => ("file", f.path()?.to_string_lossy().to_string()),
             crate::BamlMediaContent::Url(u) => ("url", u.url.clone()),
             crate::BamlMediaContent::Base64(b) => ("base64", b.base64.clone()),
+            crate::BamlMediaContent::FileRef(r) => ("file_id", r.file_id.clone()),
         };
         index_map.insert(
             key.to_string(),
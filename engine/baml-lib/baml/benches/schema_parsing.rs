@@ -0,0 +1,107 @@
+//! Schema parsing and validation benchmarks.
+//!
+//! Run with `cargo bench -p baml-lib`.
+
+use std::path::{Path, PathBuf};
+
+use baml_lib::{
+    internal_baml_core::ir::repr::IntermediateRepr, validate, Configuration, SourceFile,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Build a synthetic schema with `n` enums, classes and functions that
+/// exercises cross references, unions, maps and optional fields.
+fn large_schema(n: usize) -> String {
+    let mut schema = String::from(
+        r##"
+client<llm> GPT4 {
+  provider openai
+  options {
+    model gpt-4o
+    api_key env.OPENAI_API_KEY
+  }
+}
+"##,
+    );
+    for i in 0..n {
+        schema.push_str(&format!(
+            r##"
+enum Status{i} {{
+  ACTIVE @description("currently active")
+  INACTIVE
+  PENDING @alias("waiting")
+}}
+
+class Item{i} {{
+  id int
+  name string @description("The display name")
+  tags string[]
+  status Status{i}
+  scores map<string, float>
+  parent Item{prev}?
+  value int | string | null
+}}
+
+function Extract{i}(input: string, hint: Item{i}?) -> Item{i}[] {{
+  client GPT4
+  prompt #"
+    Extract items from the text below.
+
+    {{{{ ctx.output_format }}}}
+
+    {{{{ _.role("user") }}}}
+    {{{{ input }}}}
+  "#
+}}
+"##,
+            prev = if i == 0 { 0 } else { i - 1 },
+        ));
+    }
+    schema
+}
+
+fn source_files(schema: &str) -> Vec<SourceFile> {
+    vec![SourceFile::from((
+        PathBuf::from("baml_src/main.baml"),
+        schema.to_string(),
+    ))]
+}
+
+fn bench_validate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("validate");
+    for n in [10, 100, 500] {
+        let schema = large_schema(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &schema, |b, schema| {
+            b.iter(|| {
+                let validated = validate(Path::new("baml_src"), source_files(schema));
+                assert!(!validated.diagnostics.has_errors());
+                black_box(validated)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_build_ir(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_ir");
+    for n in [10, 100, 500] {
+        let validated = validate(Path::new("baml_src"), source_files(&large_schema(n)));
+        assert!(!validated.diagnostics.has_errors());
+        group.bench_with_input(
+            BenchmarkId::from_parameter(n),
+            &validated,
+            |b, validated| {
+                b.iter(|| {
+                    black_box(
+                        IntermediateRepr::from_parser_database(&validated.db, Configuration::new())
+                            .unwrap(),
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_validate, bench_build_ir);
+criterion_main!(benches);
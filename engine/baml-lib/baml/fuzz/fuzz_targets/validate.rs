@@ -0,0 +1,20 @@
+//! Feed arbitrary text through schema parsing and validation.
+//!
+//! Run with `cargo +nightly fuzz run validate -- -dict=fuzz/baml.dict` from
+//! `baml-lib/baml`. The dictionary steers libFuzzer towards BAML syntax so it
+//! gets past the parser and into validation much sooner.
+
+#![no_main]
+
+use std::path::{Path, PathBuf};
+
+use baml_lib::{validate, SourceFile};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|schema: &str| {
+    let files = vec![SourceFile::from((
+        PathBuf::from("baml_src/fuzz.baml"),
+        schema.to_string(),
+    ))];
+    let _ = validate(Path::new("baml_src"), files);
+});
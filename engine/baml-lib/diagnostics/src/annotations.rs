@@ -0,0 +1,34 @@
+use crate::Span;
+
+/// A secondary location that helps explain a diagnostic, e.g. the first
+/// definition of a duplicated name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedInformation {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A single textual replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// A suggested change that resolves a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub message: String,
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    /// A fix that replaces the text covered by `span` with `replacement`.
+    pub fn replace(span: Span, replacement: impl Into<String>) -> Fix {
+        let replacement = replacement.into();
+        Fix {
+            message: format!("Replace with `{replacement}`"),
+            edits: vec![TextEdit { span, replacement }],
+        }
+    }
+}
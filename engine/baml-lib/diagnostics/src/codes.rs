@@ -0,0 +1,233 @@
+//! Stable identifiers for every kind of diagnostic.
+//!
+//! Codes are part of the public interface: they are printed next to every
+//! error and warning, emitted in the JSON report, and used by editors to link
+//! to documentation. Never renumber or reuse a code; retire it instead.
+
+use std::{fmt, str::FromStr};
+
+use crate::Severity;
+
+macro_rules! diagnostic_codes {
+    ($($variant:ident = $code:literal: $explanation:literal,)*) => {
+        /// The kind of a diagnostic. See [`DiagnosticCode::explanation`].
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum DiagnosticCode {
+            $($variant,)*
+        }
+
+        impl DiagnosticCode {
+            pub const ALL: &'static [DiagnosticCode] = &[$(DiagnosticCode::$variant,)*];
+
+            /// The code as printed, e.g. `BAML0003`.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(DiagnosticCode::$variant => $code,)*
+                }
+            }
+
+            /// A longer description of what causes this diagnostic and how
+            /// to resolve it.
+            pub fn explanation(&self) -> &'static str {
+                match self {
+                    $(DiagnosticCode::$variant => $explanation,)*
+                }
+            }
+        }
+    };
+}
+
+diagnostic_codes! {
+    // Errors: BAML0001 - BAML0999.
+    Generic = "BAML0001":
+        "An error without a more specific code. The message describes what went wrong.",
+    SyntaxError = "BAML0002":
+        "The parser encountered a token it did not expect. Check for missing braces, stray \
+         characters, or a keyword used in the wrong place.",
+    ValidationError = "BAML0003":
+        "The BAML source is syntactically valid but breaks a semantic rule, such as a \
+         missing required field, an invalid attribute, or a template that fails to parse.",
+    InvalidLiteral = "BAML0004":
+        "A literal value could not be parsed as the type it was declared with.",
+    InvalidValue = "BAML0005":
+        "A value does not have the shape required here, e.g. a string where a number is \
+         expected.",
+    TypeMismatch = "BAML0006":
+        "A value has a different type than the property it is assigned to. Check the \
+         documentation of the block for the expected type.",
+    TypeNotFound = "BAML0010":
+        "A type is referenced that is not a primitive and is not declared as a class, enum \
+         or type alias anywhere in baml_src. Check the spelling or declare the type.",
+    NameNotFound = "BAML0011":
+        "A name is referenced that is not declared anywhere in baml_src.",
+    ClientNotFound = "BAML0012":
+        "A function or test refers to a client that is not declared. Declare it with a \
+         `client<llm>` block or use the `provider/model` shorthand.",
+    TypeNotUsedInPrompt = "BAML0013":
+        "A prompt refers to a type that is not part of the function's output.",
+    UnknownProperty = "BAML0014":
+        "A block contains a property it does not support. The message lists the closest \
+         supported properties.",
+    UnknownAttribute = "BAML0015":
+        "An attribute is used that BAML does not define. Attributes start with `@` for \
+         fields and `@@` for blocks.",
+    UnknownArgument = "BAML0016":
+        "An argument is passed that the attribute or function does not accept.",
+    UnusedArgument = "BAML0017":
+        "Retired: unused attribute arguments are now reported as the warning BAML1008.",
+    DuplicateDefinition = "BAML0020":
+        "Two top-level declarations share a name. Classes, enums, functions, clients and \
+         template strings share one namespace.",
+    DuplicateField = "BAML0021":
+        "A class declares the same field twice.",
+    DuplicateEnumValue = "BAML0022":
+        "An enum declares the same value twice.",
+    DuplicateTest = "BAML0023":
+        "Two tests with the same name are declared for the same function.",
+    DuplicateConfigKey = "BAML0024":
+        "A configuration block sets the same key twice.",
+    DuplicateArgument = "BAML0025":
+        "The same argument is passed more than once.",
+    DuplicateAttribute = "BAML0026":
+        "An attribute that may only appear once is repeated.",
+    DuplicateTestFunction = "BAML0027":
+        "Retired: duplicate functions in a test are now reported as the warning BAML1007.",
+    DuplicateDatabaseName = "BAML0028":
+        "Two declarations map to the same database name.",
+    MissingArgument = "BAML0030":
+        "A required argument of an attribute, function or generator is missing.",
+    ArgumentCountMismatch = "BAML0031":
+        "A function or type is given a different number of arguments than it accepts.",
+    MissingRequiredProperty = "BAML0032":
+        "A block is missing a property it requires.",
+    MissingPropertyValue = "BAML0033":
+        "A property is declared without a value.",
+    InvalidAttribute = "BAML0034":
+        "An attribute's arguments are not valid for where it is used.",
+    InvalidClient = "BAML0040":
+        "A client<llm> block is misconfigured. Check the provider name and its options.",
+    InvalidBlock = "BAML0041":
+        "A top-level block is malformed, e.g. a field without a name or type, or a \
+         function without a return type.",
+    InvalidField = "BAML0042":
+        "A field is not valid in its container, e.g. its name is reserved in one of the \
+         generated clients.",
+    InvalidEnum = "BAML0043":
+        "An enum or one of its values is malformed.",
+    InvalidName = "BAML0044":
+        "A name does not follow BAML's naming rules. Classes, enums and functions must \
+         start with an uppercase letter.",
+    ReservedName = "BAML0045":
+        "A declaration uses a name that is reserved for a built-in type.",
+    InvalidFunctionSyntax = "BAML0046":
+        "A function declaration does not follow `function Name(params) -> ReturnType { ... }`.",
+    InvalidMapKey = "BAML0047":
+        "Map keys must be `string`, an enum, or a union of string literals.",
+    InvalidDatasource = "BAML0048":
+        "A datasource block is misconfigured.",
+    InvalidNativeType = "BAML0049":
+        "A native type annotation is not supported or has invalid arguments.",
+    UnsupportedList = "BAML0050":
+        "A list field is used where lists are not supported.",
+
+    // Warnings: BAML1001 - BAML1999.
+    GenericWarning = "BAML1001":
+        "A warning without a more specific code. The message describes the problem.",
+    FieldWarning = "BAML1002":
+        "A field is valid but likely to behave unexpectedly.",
+    TypeNotFoundWarning = "BAML1003":
+        "A type is referenced that does not exist. This is a warning, not an error, \
+         because it does not prevent code generation.",
+    TypeNotUsedInPromptWarning = "BAML1004":
+        "A prompt refers to a type that is not part of the function's output, so the \
+         model will never see its definition.",
+    UnusedPromptVariable = "BAML1005":
+        "A variable is declared but never referenced by the prompt.",
+    TemplateTypeError = "BAML1006":
+        "Type checking of a Jinja template found a problem, such as an undefined variable \
+         or an argument of the wrong type. The template may still render, but likely \
+         not as intended.",
+    DuplicateTestFunctionWarning = "BAML1007":
+        "A test lists the same function more than once. The duplicate has no effect.",
+    UnusedArgumentWarning = "BAML1008":
+        "An attribute is passed an argument it does not use. The argument has no effect.",
+    InvalidSuppression = "BAML1009":
+        "A `// baml-ignore:` comment names a code that does not exist or that cannot be \
+         suppressed. Only warnings (BAML1000 and above) can be suppressed.",
+    UnknownModel = "BAML1010":
+        "A client names a model its provider is not known to serve. It may be misspelled, \
+         or newer than this version of BAML.",
+}
+
+impl DiagnosticCode {
+    /// Codes below BAML1000 are errors, the rest are warnings.
+    pub fn severity(&self) -> Severity {
+        if self.as_str() < "BAML1000" {
+            Severity::Error
+        } else {
+            Severity::Warning
+        }
+    }
+
+    /// Where to read more about this code.
+    pub fn docs_url(&self) -> String {
+        format!(
+            "https://docs.boundaryml.com/ref/diagnostics/{}",
+            self.as_str().to_lowercase()
+        )
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DiagnosticCode {
+    type Err = anyhow::Error;
+
+    /// Parses a code, ignoring case, e.g. `BAML0003` or `baml0003`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DiagnosticCode::ALL
+            .iter()
+            .find(|code| code.as_str().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Unknown diagnostic code: {s}"))
+    }
+}
+
+/// The explanation for a diagnostic code, e.g. `explain("BAML0010")`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    code.parse::<DiagnosticCode>()
+        .ok()
+        .map(|code| code.explanation())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_are_unique_and_well_formed() {
+        let mut seen = HashSet::new();
+        for code in DiagnosticCode::ALL {
+            let s = code.as_str();
+            assert!(seen.insert(s), "{s} is used twice");
+            assert_eq!(s.len(), 8, "{s}");
+            assert!(s.starts_with("BAML") && s[4..].chars().all(|c| c.is_ascii_digit()));
+            assert!(!code.explanation().is_empty());
+            assert_eq!(s.parse::<DiagnosticCode>().unwrap(), *code);
+        }
+    }
+
+    #[test]
+    fn explain_by_code() {
+        assert_eq!(
+            explain("baml0010"),
+            Some(DiagnosticCode::TypeNotFound.explanation())
+        );
+        assert_eq!(explain("BAML9999"), None);
+    }
+}
@@ -1,7 +1,7 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use super::DatamodelError;
-use crate::{warning::DatamodelWarning, SourceFile, Span};
+use crate::{warning::DatamodelWarning, DiagnosticCode, SourceFile, SourcePath, Span, Suppression};
 
 /// Represents a list of validation or parser errors and warnings.
 ///
@@ -36,7 +36,7 @@ impl std::error::Error for Diagnostics {}
 impl Diagnostics {
     pub fn new(root_path: PathBuf) -> Diagnostics {
         Diagnostics {
-            root_path,
+            root_path: SourcePath::new(root_path).into_path_buf(),
             current_file: None,
             errors: Vec::new(),
             warnings: Vec::new(),
@@ -113,6 +113,15 @@ impl Diagnostics {
         String::from_utf8_lossy(&message).into_owned()
     }
 
+    /// Drop warnings silenced by a `// baml-ignore:` comment or by the
+    /// project-level `allowed_warnings` list. Errors are never dropped.
+    pub fn suppress_warnings(&mut self, suppressions: &[Suppression], allowed: &[DiagnosticCode]) {
+        self.warnings.retain(|warning| {
+            !allowed.contains(&warning.code())
+                && !suppressions.iter().any(|s| s.suppresses(warning))
+        });
+    }
+
     pub fn push(&mut self, mut other: Diagnostics) {
         self.errors.append(&mut other.errors);
         self.warnings.append(&mut other.warnings);
@@ -130,7 +139,7 @@ impl Diagnostics {
                     .get(&err.span().end)
                     .unwrap_or(&err.span().end);
                 let new_span = Span::new(err.span().file.clone(), new_start, new_end);
-                DatamodelError::new(err.message().to_string(), new_span)
+                err.clone().with_span(new_span)
             })
             .collect();
 
@@ -145,7 +154,7 @@ impl Diagnostics {
                     .get(&warn.span().end)
                     .unwrap_or(&warn.span().end);
                 let new_span = Span::new(warn.span().file.clone(), new_start, new_end);
-                DatamodelWarning::new(warn.message().to_string(), new_span)
+                warn.clone().with_span(new_span)
             })
             .collect();
     }
@@ -1,6 +1,8 @@
 use colored::{ColoredString, Colorize};
 
 use crate::{
+    annotations::{Fix, RelatedInformation},
+    codes::DiagnosticCode,
     pretty_print::{pretty_print, DiagnosticColorer},
     Span,
 };
@@ -9,8 +11,11 @@ use std::{borrow::Cow, ops::Index};
 
 #[derive(Debug, Clone)]
 pub struct DatamodelError {
+    code: DiagnosticCode,
     span: Span,
     message: Cow<'static, str>,
+    related: Vec<RelatedInformation>,
+    fixes: Vec<Fix>,
 }
 
 /// Sorts a collection of strings based on their similarity to a given name.
@@ -64,17 +69,56 @@ where
 }
 
 impl DatamodelError {
-    pub(crate) fn new(message: impl Into<Cow<'static, str>>, span: Span) -> Self {
+    pub(crate) fn new(
+        code: DiagnosticCode,
+        message: impl Into<Cow<'static, str>>,
+        span: Span,
+    ) -> Self {
         let message = message.into();
-        DatamodelError { message, span }
+        DatamodelError {
+            code,
+            message,
+            span,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    /// Point at another location that helps explain this error.
+    pub fn with_related(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.related.push(RelatedInformation {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
+    /// Suggest replacing a misspelled name with each of `candidates`. Only
+    /// applies when the span covers exactly the misspelled name, so that a
+    /// fix never rewrites more than the user typed.
+    fn with_rename_fixes(mut self, name: &str, candidates: &[&str]) -> Self {
+        if self.span.file.as_str().get(self.span.start..self.span.end) == Some(name) {
+            self.fixes.extend(
+                candidates
+                    .iter()
+                    .take(3)
+                    .map(|c| Fix::replace(self.span.clone(), *c)),
+            );
+        }
+        self
     }
 
     pub fn new_anyhow_error(error: anyhow::Error, span: Span) -> Self {
-        Self::new(format!("{error:#}"), span)
+        Self::new(DiagnosticCode::Generic, format!("{error:#}"), span)
     }
 
     pub fn new_static(message: &'static str, span: Span) -> Self {
-        Self::new(message, span)
+        Self::new(DiagnosticCode::Generic, message, span)
     }
 
     pub fn new_literal_parser_error(
@@ -83,13 +127,28 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         Self::new(
+            DiagnosticCode::InvalidLiteral,
             format!("\"{raw_value}\" is not a valid value for {literal_type}."),
             span,
         )
     }
 
     pub fn new_argument_not_found_error(argument_name: &str, span: Span) -> DatamodelError {
-        Self::new(format!("Argument \"{argument_name}\" is missing."), span)
+        Self::new(
+            DiagnosticCode::MissingArgument,
+            format!("Argument \"{argument_name}\" is missing."),
+            span,
+        )
+    }
+
+    /// A call to a template_string in a prompt that doesn't match its
+    /// parameters, e.g. with an argument it doesn't have.
+    pub fn new_template_string_call_error(
+        code: DiagnosticCode,
+        message: &str,
+        span: Span,
+    ) -> DatamodelError {
+        Self::new(code, message.to_string(), span)
     }
 
     pub fn new_argument_count_mismatch_error(
@@ -99,11 +158,11 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg = format!("Function \"{function_name}\" takes {required_count} arguments, but received {given_count}.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::ArgumentCountMismatch, msg, span)
     }
 
     pub fn new_client_error(message: impl Into<Cow<'static, str>>, span: Span) -> DatamodelError {
-        Self::new(message, span)
+        Self::new(DiagnosticCode::InvalidClient, message, span)
     }
 
     pub fn new_attribute_argument_not_found_error(
@@ -112,6 +171,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         Self::new(
+            DiagnosticCode::MissingArgument,
             format!("Argument \"{argument_name}\" is missing in attribute \"@{attribute_name}\"."),
             span,
         )
@@ -123,6 +183,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         Self::new(
+            DiagnosticCode::MissingArgument,
             format!(
                 "Argument \"{argument_name}\" is missing in generator block \"{generator_name}\"."
             ),
@@ -136,6 +197,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         Self::new(
+            DiagnosticCode::InvalidAttribute,
             format!("Error parsing attribute \"{attribute_name}\": {message}"),
             span,
         )
@@ -143,7 +205,7 @@ impl DatamodelError {
 
     pub fn new_duplicate_attribute_error(attribute_name: &str, span: Span) -> DatamodelError {
         let msg = format!("Attribute \"@{attribute_name}\" can only be defined once.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateAttribute, msg, span)
     }
 
     pub fn new_incompatible_native_type(
@@ -155,7 +217,7 @@ impl DatamodelError {
         let msg = format!(
             "Native type {native_type} is not compatible with declared field type {field_type}, expected field type {expected_types}.",
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::InvalidNativeType, msg, span)
     }
 
     pub fn new_invalid_native_type_argument(
@@ -166,7 +228,7 @@ impl DatamodelError {
     ) -> DatamodelError {
         let msg =
             format!("Invalid argument for type {native_type}: {got}. Allowed values: {expected}.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::InvalidNativeType, msg, span)
     }
 
     pub fn new_invalid_prefix_for_native_types(
@@ -176,22 +238,22 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg =  format!("The prefix {given_prefix} is invalid. It must be equal to the name of an existing datasource e.g. {expected_prefix}. Did you mean to use {suggestion}?");
-        DatamodelError::new(msg, span)
+        Self::new(DiagnosticCode::InvalidNativeType, msg, span)
     }
 
     pub fn new_native_types_not_supported(connector_name: String, span: Span) -> DatamodelError {
         let msg = format!("Native types are not supported with {connector_name} connector");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::InvalidNativeType, msg, span)
     }
 
     pub fn new_reserved_scalar_type_error(type_name: &str, span: Span) -> DatamodelError {
         let msg = format!("\"{type_name}\" is a reserved scalar type name and cannot be used.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::ReservedName, msg, span)
     }
 
     pub fn new_duplicate_enum_database_name_error(span: Span) -> DatamodelError {
         let msg = "An enum with the same database name is already defined.";
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateDatabaseName, msg, span)
     }
 
     pub fn new_duplicate_model_database_name_error(
@@ -200,7 +262,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg = format!("The model with database name \"{model_database_name}\" could not be defined because another model or view with this name exists: \"{existing_model_name}\"");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateDatabaseName, msg, span)
     }
 
     pub fn new_duplicate_view_database_name_error(
@@ -209,7 +271,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg = format!("The view with database name \"{model_database_name}\" could not be defined because another model or view with this name exists: \"{existing_model_name}\"");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateDatabaseName, msg, span)
     }
 
     pub fn new_duplicate_test_error(
@@ -219,7 +281,7 @@ impl DatamodelError {
     ) -> DatamodelError {
         let msg =
             format!("Test \"{test_name}\" is already defined for function \"{function_name}\".");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateTest, msg, span)
     }
 
     pub fn new_duplicate_top_error(
@@ -231,7 +293,22 @@ impl DatamodelError {
         let msg = format!(
             "The {top_type} \"{name}\" cannot be defined because a {existing_top_type} with that name already exists.",
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateDefinition, msg, span)
+    }
+
+    /// A name defined in more than one of the `baml_src` directories composed
+    /// into one schema.
+    pub fn new_duplicate_root_definition_error(
+        name: &str,
+        top_type: &str,
+        existing_root: &str,
+        root: &str,
+        span: Span,
+    ) -> DatamodelError {
+        let msg = format!(
+            "The {top_type} \"{name}\" is defined in both {existing_root} and {root}. Names must be unique across all baml_src directories.",
+        );
+        Self::new(DiagnosticCode::DuplicateDefinition, msg, span)
     }
 
     pub fn new_duplicate_config_key_error(
@@ -240,32 +317,25 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg = format!("Key \"{key_name}\" is already defined in {conf_block_name}.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateConfigKey, msg, span)
     }
 
     pub fn new_duplicate_argument_error(arg_name: &str, span: Span) -> DatamodelError {
         Self::new(
+            DiagnosticCode::DuplicateArgument,
             format!("Argument \"{arg_name}\" is already specified."),
             span,
         )
     }
 
-    pub fn new_unused_argument_error(span: Span) -> DatamodelError {
-        Self::new("No such argument.", span)
-    }
-
     pub fn new_duplicate_default_argument_error(arg_name: &str, span: Span) -> DatamodelError {
         let msg = format!("Argument \"{arg_name}\" is already specified as unnamed argument.");
-        Self::new(msg, span)
-    }
-
-    pub fn new_duplicate_function_errors(func_name: &str, span: Span) -> DatamodelError {
-        let msg = format!("Function \"{func_name}\" is already specified for this test case.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateArgument, msg, span)
     }
 
     pub fn new_invalid_function_syntax_error(func_name: &str, span: Span) -> DatamodelError {
         Self::new(
+            DiagnosticCode::InvalidFunctionSyntax,
             format!("Invalid syntax for function \"{func_name}\". Use:\nfunction {func_name}(params...) -> ReturnType {{ ... }}"),
             span,
         )
@@ -277,7 +347,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg = format!("Value \"{value_name}\" is already defined on enum \"{enum_name}\".",);
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateEnumValue, msg, span)
     }
 
     pub fn new_composite_type_duplicate_field_error(
@@ -289,7 +359,7 @@ impl DatamodelError {
             "Field \"{}\" is already defined on {} \"{}\".",
             field_name, "composite type", type_name
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateField, msg, span)
     }
 
     pub fn new_duplicate_field_error(
@@ -300,7 +370,7 @@ impl DatamodelError {
     ) -> DatamodelError {
         let msg =
             format!("Field \"{field_name}\" is already defined on {container} \"{model_name}\".",);
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::DuplicateField, msg, span)
     }
 
     pub fn new_scalar_list_fields_are_not_supported(
@@ -310,7 +380,7 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         let msg = format!("Field \"{field_name}\" in {container} \"{container_name}\" can't be a list. The current connector does not support lists of primitive types.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::UnsupportedList, msg, span)
     }
 
     pub fn new_model_validation_error(
@@ -320,17 +390,23 @@ impl DatamodelError {
         span: Span,
     ) -> DatamodelError {
         Self::new(
+            DiagnosticCode::InvalidBlock,
             format!("Error validating {block_type} \"{model_name}\": {message}"),
             span,
         )
     }
 
     pub fn new_name_error(_type: &str, message: &str, span: Span) -> DatamodelError {
-        Self::new(format!("Invalid name for `{_type}`: {message}"), span)
+        Self::new(
+            DiagnosticCode::InvalidName,
+            format!("Invalid name for `{_type}`: {message}"),
+            span,
+        )
     }
 
     pub fn new_enum_validation_error(message: &str, enum_name: &str, span: Span) -> DatamodelError {
         Self::new(
+            DiagnosticCode::InvalidEnum,
             format!("Error validating enum `{enum_name}`: {message}"),
             span,
         )
@@ -346,7 +422,7 @@ impl DatamodelError {
             "Error validating field `{}` in {} `{}`: {}",
             field, "composite type", composite_type_name, message
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::InvalidField, msg, span)
     }
 
     pub fn new_field_validation_error(
@@ -359,25 +435,30 @@ impl DatamodelError {
         let msg = format!(
             "Error validating field `{field}` in {container_type} `{container_name}`: {message}",
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::InvalidField, msg, span)
     }
 
     pub fn new_source_validation_error(message: &str, source: &str, span: Span) -> DatamodelError {
         Self::new(
+            DiagnosticCode::InvalidDatasource,
             format!("Error validating datasource `{source}`: {message}"),
             span,
         )
     }
 
     pub fn new_validation_error(message: &str, span: Span) -> DatamodelError {
-        Self::new(format!("Error validating: {message}"), span)
+        Self::new(
+            DiagnosticCode::ValidationError,
+            format!("Error validating: {message}"),
+            span,
+        )
     }
 
     pub fn new_legacy_parser_error(
         message: impl Into<Cow<'static, str>>,
         span: Span,
     ) -> DatamodelError {
-        Self::new(message.into(), span)
+        Self::new(DiagnosticCode::Generic, message.into(), span)
     }
 
     pub fn new_optional_argument_count_mismatch(
@@ -390,11 +471,12 @@ impl DatamodelError {
             "Native type {native_type} takes {optional_count} optional arguments, but received {given_count}.",
         );
 
-        DatamodelError::new(msg, span)
+        Self::new(DiagnosticCode::ArgumentCountMismatch, msg, span)
     }
 
     pub fn new_parser_error(expected_str: String, span: Span) -> DatamodelError {
         Self::new(
+            DiagnosticCode::SyntaxError,
             format!("Unexpected token. Expected one of: {expected_str}"),
             span,
         )
@@ -404,7 +486,7 @@ impl DatamodelError {
         message: impl Into<Cow<'static, str>>,
         span: Span,
     ) -> DatamodelError {
-        Self::new(message.into(), span)
+        Self::new(DiagnosticCode::Generic, message.into(), span)
     }
 
     pub fn not_found_error(
@@ -445,7 +527,11 @@ impl DatamodelError {
             )
         };
 
-        Self::new(format!("{type_name} {name} not found.{suggestions}"), span)
+        Self::new(
+            DiagnosticCode::NameNotFound,
+            format!("{type_name} {name} not found.{suggestions}"),
+            span,
+        )
     }
 
     pub fn type_not_used_in_prompt_error(
@@ -496,7 +582,11 @@ impl DatamodelError {
             )
         };
 
-        Self::new(format!("{prefix}{suggestions}"), span)
+        Self::new(
+            DiagnosticCode::TypeNotUsedInPrompt,
+            format!("{prefix}{suggestions}"),
+            span,
+        )
     }
 
     pub fn new_client_not_found_error(
@@ -525,7 +615,8 @@ impl DatamodelError {
             )
         };
 
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::ClientNotFound, msg, span)
+            .with_rename_fixes(client_name, &close_names)
     }
 
     pub fn new_type_not_found_error(
@@ -552,11 +643,16 @@ impl DatamodelError {
             )
         };
 
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::TypeNotFound, msg, span)
+            .with_rename_fixes(type_name, &close_names)
     }
 
     pub fn new_attribute_not_known_error(attribute_name: &str, span: Span) -> DatamodelError {
-        Self::new(format!("Attribute not known: \"@{attribute_name}\"."), span)
+        Self::new(
+            DiagnosticCode::UnknownAttribute,
+            format!("Attribute not known: \"@{attribute_name}\"."),
+            span,
+        )
     }
 
     pub fn new_property_not_known_error<I, T>(
@@ -571,7 +667,7 @@ impl DatamodelError {
     {
         let close_names = sort_by_match(property_name, &alternatives, None);
 
-        Self::new(match close_names.len() {
+        Self::new(DiagnosticCode::UnknownProperty, match close_names.len() {
             0 => format!("Property not known: \"{property_name}\".",),
             1 =>
                 format!(
@@ -587,12 +683,16 @@ impl DatamodelError {
     }
 
     pub fn new_argument_not_known_error(property_name: &str, span: Span) -> DatamodelError {
-        Self::new(format!("Argument not known: \"{property_name}\"."), span)
+        Self::new(
+            DiagnosticCode::UnknownArgument,
+            format!("Argument not known: \"{property_name}\"."),
+            span,
+        )
     }
 
     pub fn new_value_parser_error(expected_type: &str, raw: &str, span: Span) -> DatamodelError {
         let msg = format!("Expected {expected_type}, but found {raw}.");
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::InvalidValue, msg, span)
     }
 
     pub fn new_type_mismatch_error(
@@ -604,7 +704,7 @@ impl DatamodelError {
         let msg = format!(
             "Expected a {expected_type} value, but received {received_type} value `{raw}`."
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::TypeMismatch, msg, span)
     }
 
     pub fn new_missing_required_property_error(
@@ -614,7 +714,7 @@ impl DatamodelError {
     ) -> DatamodelError {
         let msg =
             format!("The required property \"{property_name}\" on \"{object_name}\" is missing.",);
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::MissingRequiredProperty, msg, span)
     }
 
     pub fn new_config_property_missing_value_error(
@@ -626,12 +726,13 @@ impl DatamodelError {
         let msg = format!(
             "Property {property_name} in {config_kind} {config_name} needs to be assigned a value"
         );
-        Self::new(msg, span)
+        Self::new(DiagnosticCode::MissingPropertyValue, msg, span)
     }
 
     pub fn new_type_not_allowed_as_map_key_error(span: Span) -> DatamodelError {
-        Self::new_validation_error(
-            "Maps may only have strings, enums or literal strings as keys",
+        Self::new(
+            DiagnosticCode::InvalidMapKey,
+            "Error validating: Maps may only have strings, enums or literal strings as keys",
             span,
         )
     }
@@ -644,10 +745,29 @@ impl DatamodelError {
         &self.message
     }
 
+    pub fn code(&self) -> DiagnosticCode {
+        self.code
+    }
+
+    pub fn related(&self) -> &[RelatedInformation] {
+        &self.related
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        &self.fixes
+    }
+
+    /// The same error, moved to `span`.
+    pub(crate) fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
     pub fn pretty_print(&self, f: &mut dyn std::io::Write) -> std::io::Result<()> {
         pretty_print(
             f,
             self.span(),
+            self.code,
             self.message.as_ref(),
             &DatamodelErrorColorer {},
         )
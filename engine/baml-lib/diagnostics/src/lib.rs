@@ -1,12 +1,25 @@
+mod annotations;
+mod codes;
 mod collection;
 mod error;
 mod pretty_print;
+mod report;
 mod source_file;
+mod source_path;
 mod span;
+mod suppression;
 mod warning;
 
+pub use annotations::{Fix, RelatedInformation, TextEdit};
+pub use codes::{explain, DiagnosticCode};
 pub use collection::Diagnostics;
 pub use error::DatamodelError;
+pub use report::{
+    DiagnosticsReport, ReportDiagnostic, ReportEdit, ReportFix, ReportPosition, ReportRelated,
+    ReportSpan, Severity,
+};
 pub use source_file::SourceFile;
+pub use source_path::SourcePath;
 pub use span::Span;
+pub use suppression::{parse_suppression_comment, Suppression};
 pub use warning::DatamodelWarning;
@@ -1,4 +1,4 @@
-use crate::Span;
+use crate::{DiagnosticCode, Span};
 use colored::{ColoredString, Colorize};
 
 pub trait DiagnosticColorer {
@@ -12,6 +12,7 @@ pub trait DiagnosticColorer {
 pub(crate) fn pretty_print(
     f: &mut dyn std::io::Write,
     span: &Span,
+    code: DiagnosticCode,
     description: &str,
     colorer: &'static dyn DiagnosticColorer,
 ) -> std::io::Result<()> {
@@ -46,7 +47,9 @@ pub(crate) fn pretty_print(
     writeln!(
         f,
         "{}: {}",
-        colorer.primary_color(colorer.title()).bold(),
+        colorer
+            .primary_color(&format!("{}[{}]", colorer.title(), code))
+            .bold(),
         description.bold()
     )?;
     writeln!(f, "  {arrow}  {file_path}")?;
@@ -0,0 +1,200 @@
+//! A stable, serializable form of [`Diagnostics`] for editors and tooling.
+//!
+//! The human-readable output of `pretty_print` may change at any time; the
+//! shape of these types is versioned via [`DiagnosticsReport::VERSION`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    annotations::{Fix, RelatedInformation, TextEdit},
+    DatamodelError, DatamodelWarning, Diagnostics, Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A position in a source file. `offset` is in bytes; `line` and `column`
+/// are zero-based, with `column` counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportPosition {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSpan {
+    pub file: String,
+    pub start: ReportPosition,
+    pub end: ReportPosition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportRelated {
+    pub span: ReportSpan,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportEdit {
+    pub span: ReportSpan,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportFix {
+    pub message: String,
+    pub edits: Vec<ReportEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportDiagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    /// Documentation for `code`.
+    pub code_url: Option<String>,
+    pub message: String,
+    pub span: ReportSpan,
+    pub related: Vec<ReportRelated>,
+    pub fixes: Vec<ReportFix>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub version: u32,
+    pub error_count: usize,
+    pub warning_count: usize,
+    /// Errors first, then warnings, each in the order they were reported.
+    pub diagnostics: Vec<ReportDiagnostic>,
+}
+
+impl DiagnosticsReport {
+    pub const VERSION: u32 = 1;
+}
+
+impl From<&Span> for ReportSpan {
+    fn from(span: &Span) -> Self {
+        let ((start_line, start_column), (end_line, end_column)) = span.line_and_column();
+        ReportSpan {
+            file: span.file.path(),
+            start: ReportPosition {
+                offset: span.start,
+                line: start_line,
+                column: start_column,
+            },
+            end: ReportPosition {
+                offset: span.end,
+                line: end_line,
+                column: end_column,
+            },
+        }
+    }
+}
+
+impl From<&RelatedInformation> for ReportRelated {
+    fn from(related: &RelatedInformation) -> Self {
+        ReportRelated {
+            span: (&related.span).into(),
+            message: related.message.clone(),
+        }
+    }
+}
+
+impl From<&TextEdit> for ReportEdit {
+    fn from(edit: &TextEdit) -> Self {
+        ReportEdit {
+            span: (&edit.span).into(),
+            replacement: edit.replacement.clone(),
+        }
+    }
+}
+
+impl From<&Fix> for ReportFix {
+    fn from(fix: &Fix) -> Self {
+        ReportFix {
+            message: fix.message.clone(),
+            edits: fix.edits.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&DatamodelError> for ReportDiagnostic {
+    fn from(error: &DatamodelError) -> Self {
+        ReportDiagnostic {
+            severity: Severity::Error,
+            code: Some(error.code().to_string()),
+            code_url: Some(error.code().docs_url()),
+            message: error.message().to_string(),
+            span: error.span().into(),
+            related: error.related().iter().map(Into::into).collect(),
+            fixes: error.fixes().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&DatamodelWarning> for ReportDiagnostic {
+    fn from(warning: &DatamodelWarning) -> Self {
+        ReportDiagnostic {
+            severity: Severity::Warning,
+            code: Some(warning.code().to_string()),
+            code_url: Some(warning.code().docs_url()),
+            message: warning.message().to_string(),
+            span: warning.span().into(),
+            related: warning.related().iter().map(Into::into).collect(),
+            fixes: warning.fixes().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&Diagnostics> for DiagnosticsReport {
+    fn from(diagnostics: &Diagnostics) -> Self {
+        DiagnosticsReport {
+            version: Self::VERSION,
+            error_count: diagnostics.errors().len(),
+            warning_count: diagnostics.warnings().len(),
+            diagnostics: diagnostics
+                .errors()
+                .iter()
+                .map(ReportDiagnostic::from)
+                .chain(diagnostics.warnings().iter().map(ReportDiagnostic::from))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SourceFile;
+
+    #[test]
+    fn type_not_found_report() {
+        let contents = "class Foo {\n  bar Bax\n}\nclass Bar {}";
+        let file: SourceFile = ("main.baml".into(), contents).into();
+        let start = contents.find("Bax").unwrap();
+        let mut diagnostics = Diagnostics::new("baml_src".into());
+        diagnostics.push_error(DatamodelError::new_type_not_found_error(
+            "Bax",
+            vec!["Foo".to_string(), "Bar".to_string()],
+            Span::new(file, start, start + 3),
+        ));
+
+        let report = DiagnosticsReport::from(&diagnostics);
+        assert_eq!(report.error_count, 1);
+        let diagnostic = &report.diagnostics[0];
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code.as_deref(), Some("BAML0010"));
+        assert_eq!(diagnostic.span.start.line, 1);
+        assert_eq!(diagnostic.span.start.column, 6);
+        assert_eq!(diagnostic.span.start.offset, start);
+        assert_eq!(diagnostic.fixes[0].edits[0].replacement, "Bar");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["diagnostics"][0]["severity"], "error");
+        assert_eq!(json["diagnostics"][0]["span"]["file"], "main.baml");
+    }
+}
@@ -1,9 +1,11 @@
 use std::{fmt, path::PathBuf, sync::Arc};
 
+use crate::SourcePath;
+
 /// A Prisma schema document.
 #[derive(Clone)]
 pub struct SourceFile {
-    path: PathBuf,
+    path: SourcePath,
     contents: Contents,
 }
 
@@ -18,14 +20,14 @@ impl Eq for SourceFile {}
 impl SourceFile {
     pub fn new_static(path: PathBuf, content: &'static str) -> Self {
         Self {
-            path,
+            path: SourcePath::new(path),
             contents: Contents::Static(content),
         }
     }
 
     pub fn new_allocated(path: PathBuf, s: Arc<str>) -> Self {
         Self {
-            path,
+            path: SourcePath::new(path),
             contents: Contents::Allocated(s),
         }
     }
@@ -38,10 +40,14 @@ impl SourceFile {
     }
 
     pub fn path(&self) -> String {
-        self.path.to_string_lossy().to_string()
+        self.path.display()
     }
 
     pub fn path_buf(&self) -> &PathBuf {
+        self.path.as_path_buf()
+    }
+
+    pub fn source_path(&self) -> &SourcePath {
         &self.path
     }
 }
@@ -0,0 +1,205 @@
+use std::{
+    ffi::OsString,
+    fmt,
+    path::{Component, Path, PathBuf, Prefix},
+};
+
+/// A normalized path to a BAML source file or directory.
+///
+/// Paths reach us from many places (CLI arguments, `canonicalize`, file
+/// watchers, editors) and the same file can be spelled several ways,
+/// especially on Windows where `\\?\C:\baml_src` and `C:\baml_src` name the
+/// same directory. Normalizing once here means path comparisons, relative
+/// path computation and span reporting all agree.
+///
+/// Normalization is purely lexical: it never touches the filesystem, so it
+/// works in wasm and preserves non-UTF-8 segments as-is.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourcePath {
+    path: PathBuf,
+}
+
+impl SourcePath {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: normalize(path.as_ref()),
+        }
+    }
+
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn as_path_buf(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        self.path
+    }
+
+    /// A human readable form of the path. Segments that are not valid UTF-8
+    /// are replaced with U+FFFD; use [`SourcePath::as_path`] for anything that
+    /// touches the filesystem.
+    pub fn display(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+
+    /// This path relative to `root`, if it lies under it.
+    pub fn relative_to(&self, root: &SourcePath) -> Option<&Path> {
+        self.path.strip_prefix(&root.path).ok()
+    }
+
+    /// The path with `/` separators, for output that must be identical
+    /// across platforms (e.g. the file map embedded in generated clients).
+    pub fn to_slash_string(&self) -> String {
+        self.path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .fold(String::new(), |mut acc, c| {
+                if !acc.is_empty() && !acc.ends_with('/') {
+                    acc.push('/');
+                }
+                acc.push_str(&c.replace('\\', "/"));
+                acc
+            })
+    }
+}
+
+impl fmt::Debug for SourcePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.path, f)
+    }
+}
+
+impl fmt::Display for SourcePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.path.display().fmt(f)
+    }
+}
+
+impl AsRef<Path> for SourcePath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl From<PathBuf> for SourcePath {
+    fn from(path: PathBuf) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<&Path> for SourcePath {
+    fn from(path: &Path) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<&str> for SourcePath {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+/// Strip Windows verbatim prefixes and resolve `.`/`..` lexically.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => match prefix.kind() {
+                // \\?\C:\ -> C:\
+                Prefix::VerbatimDisk(disk) => {
+                    out.push(format!("{}:", disk as char));
+                }
+                // \\?\UNC\server\share -> \\server\share
+                Prefix::VerbatimUNC(server, share) => {
+                    let mut unc = OsString::from(r"\\");
+                    unc.push(server);
+                    unc.push(r"\");
+                    unc.push(share);
+                    out.push(unc);
+                }
+                _ => out.push(prefix.as_os_str()),
+            },
+            Component::RootDir => out.push(component.as_os_str()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let can_pop = matches!(out.components().next_back(), Some(Component::Normal(_)));
+                if can_pop {
+                    out.pop();
+                } else if !out.has_root() {
+                    // A leading `..` on a relative path is meaningful.
+                    out.push(component.as_os_str());
+                }
+            }
+            Component::Normal(segment) => out.push(segment),
+        }
+    }
+    if out.as_os_str().is_empty() {
+        out.push(".");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_dot_segments() {
+        assert_eq!(
+            SourcePath::new("baml_src/./clients/../main.baml").as_path(),
+            Path::new("baml_src/main.baml")
+        );
+        assert_eq!(
+            SourcePath::new("../baml_src").as_path(),
+            Path::new("../baml_src")
+        );
+        assert_eq!(
+            SourcePath::new("/../baml_src").as_path(),
+            Path::new("/baml_src")
+        );
+        assert_eq!(SourcePath::new("./").as_path(), Path::new("."));
+    }
+
+    #[test]
+    fn relative_to_root() {
+        let root = SourcePath::new("/project/./baml_src");
+        let file = SourcePath::new("/project/baml_src/clients/gpt.baml");
+        assert_eq!(file.relative_to(&root), Some(Path::new("clients/gpt.baml")));
+        assert_eq!(root.relative_to(&file), None);
+        assert_eq!(
+            SourcePath::new("baml_src/clients/gpt.baml").to_slash_string(),
+            "baml_src/clients/gpt.baml"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn preserves_non_utf8_segments() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let raw = Path::new(OsStr::from_bytes(b"/baml_src/caf\xe9/main.baml"));
+        let path = SourcePath::new(raw);
+        assert_eq!(path.as_path(), raw);
+        assert_eq!(
+            path.relative_to(&SourcePath::new("/baml_src")),
+            Some(Path::new(OsStr::from_bytes(b"caf\xe9/main.baml")))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn strips_verbatim_prefixes() {
+        assert_eq!(
+            SourcePath::new(r"\\?\C:\project\baml_src"),
+            SourcePath::new(r"C:\project\baml_src")
+        );
+        assert_eq!(
+            SourcePath::new(r"\\?\UNC\server\share\baml_src"),
+            SourcePath::new(r"\\server\share\baml_src")
+        );
+    }
+}
@@ -33,36 +33,14 @@ impl Span {
         self.file == other.file && (self.contains(other.start) || self.contains(other.end))
     }
 
+    /// Zero-based `(line, column)` of the start and end of the span. Columns
+    /// count characters, not bytes.
     pub fn line_and_column(&self) -> ((usize, usize), (usize, usize)) {
         let contents = self.file.as_str();
-        let mut line = 0;
-        let mut column = 0;
-
-        let mut start = None;
-        let mut end = None;
-
-        for (idx, c) in contents.chars().enumerate() {
-            if idx == self.start {
-                start = Some((line, column));
-            }
-            if idx == self.end {
-                end = Some((line, column));
-                break;
-            }
-
-            if c == '\n' {
-                line += 1;
-                column = 0;
-            } else {
-                column += 1;
-            }
-        }
-
-        match (start, end) {
-            (Some(start), Some(end)) => (start, end),
-            (Some(start), None) => (start, (line, column)),
-            _ => ((0, 0), (0, 0)),
-        }
+        (
+            line_and_column(contents, self.start),
+            line_and_column(contents, self.end),
+        )
     }
 
     /// Create a fake span. Useful when generating test data that requires
@@ -82,3 +60,59 @@ impl From<(SourceFile, pest::Span<'_>)> for Span {
         }
     }
 }
+
+/// Zero-based `(line, column)` of the byte `offset` in `contents`. Offsets
+/// past the end or inside a multi-byte character are clamped to the nearest
+/// preceding character boundary.
+fn line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(contents.len());
+    while !contents.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &contents[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    (
+        before.matches('\n').count(),
+        before[line_start..].chars().count(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(contents: &str, start: usize, end: usize) -> Span {
+        let file: SourceFile = ("test.baml".into(), contents).into();
+        Span::new(file, start, end)
+    }
+
+    #[test]
+    fn line_and_column_ascii() {
+        let contents = "class Foo {\n  bar string\n}";
+        let start = contents.find("bar").unwrap();
+        assert_eq!(
+            span(contents, start, start + 3).line_and_column(),
+            ((1, 2), (1, 5))
+        );
+        assert_eq!(
+            span(contents, 0, contents.len()).line_and_column(),
+            ((0, 0), (2, 1))
+        );
+    }
+
+    #[test]
+    fn line_and_column_uses_byte_offsets() {
+        // Each emoji is 4 bytes but a single column.
+        let contents = "// 🌅🌞\nclass Foo {}";
+        let start = contents.find("Foo").unwrap();
+        assert_eq!(
+            span(contents, start, start + 3).line_and_column(),
+            ((1, 6), (1, 9))
+        );
+        let sun = contents.find('🌞').unwrap();
+        assert_eq!(
+            span(contents, sun, sun + 1).line_and_column(),
+            ((0, 4), (0, 4))
+        );
+    }
+}
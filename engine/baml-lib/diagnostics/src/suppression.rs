@@ -0,0 +1,81 @@
+use crate::{DatamodelWarning, DiagnosticCode, Severity, Span};
+
+const SUPPRESSION_PREFIX: &str = "baml-ignore:";
+
+/// A `// baml-ignore: BAML1006` comment, resolved to the part of the source
+/// it applies to.
+///
+/// A comment on its own line applies to the item directly below it (a field,
+/// property or whole block). A trailing comment applies to the item it
+/// trails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub codes: Vec<DiagnosticCode>,
+    pub target: Span,
+}
+
+impl Suppression {
+    pub fn suppresses(&self, warning: &DatamodelWarning) -> bool {
+        let span = warning.span();
+        self.codes.contains(&warning.code())
+            && span.file == self.target.file
+            && self.target.start <= span.start
+            && span.end <= self.target.end
+    }
+}
+
+/// Parses the text of a `//` comment (without the slashes).
+///
+/// Returns `None` if this is not a suppression comment, and otherwise the
+/// codes it names, or a message describing why it is invalid.
+pub fn parse_suppression_comment(comment: &str) -> Option<Result<Vec<DiagnosticCode>, String>> {
+    let codes = comment.trim().strip_prefix(SUPPRESSION_PREFIX)?;
+
+    let codes = codes
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|code| !code.is_empty())
+        .map(|code| match code.parse::<DiagnosticCode>() {
+            Ok(parsed) if parsed.severity() == Severity::Warning => Ok(parsed),
+            Ok(_) => Err(format!(
+                "{code} is an error and cannot be suppressed. Only warnings can be ignored."
+            )),
+            Err(_) => Err(format!("Unknown diagnostic code `{code}`.")),
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    Some(match codes {
+        Ok(codes) if codes.is_empty() => {
+            Err("Expected at least one code, e.g. `// baml-ignore: BAML1006`.".to_string())
+        }
+        other => other,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_codes() {
+        assert_eq!(parse_suppression_comment(" just a comment"), None);
+        assert_eq!(
+            parse_suppression_comment(" baml-ignore: BAML1006, baml1005"),
+            Some(Ok(vec![
+                DiagnosticCode::TemplateTypeError,
+                DiagnosticCode::UnusedPromptVariable
+            ]))
+        );
+        assert!(matches!(
+            parse_suppression_comment(" baml-ignore: BAML0010"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_suppression_comment(" baml-ignore: NOPE"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_suppression_comment(" baml-ignore:"),
+            Some(Err(_))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
 use crate::{
+    annotations::{Fix, RelatedInformation},
+    codes::DiagnosticCode,
     error::sort_by_match,
     pretty_print::{pretty_print, DiagnosticColorer},
     Span,
@@ -10,15 +12,42 @@ use colored::{ColoredString, Colorize};
 /// For fancy printing, please use the `pretty_print_error` function.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct DatamodelWarning {
+    code: DiagnosticCode,
     message: String,
     span: Span,
+    related: Vec<RelatedInformation>,
+    fixes: Vec<Fix>,
 }
 
 impl DatamodelWarning {
     /// You should avoid using this constructor directly when possible, and define warnings as public methods of this class.
     /// The constructor is only left public for supporting connector-specific warnings (which should not live in the core).
     pub fn new(message: String, span: Span) -> DatamodelWarning {
-        DatamodelWarning { message, span }
+        Self::new_with_code(DiagnosticCode::GenericWarning, message, span)
+    }
+
+    fn new_with_code(code: DiagnosticCode, message: String, span: Span) -> DatamodelWarning {
+        DatamodelWarning {
+            code,
+            message,
+            span,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    /// Point at another location that helps explain this warning.
+    pub fn with_related(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.related.push(RelatedInformation {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
     }
 
     pub fn new_field_validation(
@@ -32,7 +61,7 @@ impl DatamodelWarning {
             field, "model", model, message
         );
 
-        Self::new(msg, span)
+        Self::new_with_code(DiagnosticCode::FieldWarning, msg, span)
     }
 
     pub fn new_type_not_found_error(
@@ -59,7 +88,7 @@ impl DatamodelWarning {
             )
         };
 
-        Self::new(msg, span)
+        Self::new_with_code(DiagnosticCode::TypeNotFoundWarning, msg, span)
     }
 
     pub fn type_not_used_in_prompt_error(
@@ -140,11 +169,68 @@ impl DatamodelWarning {
             )
         };
 
-        Self::new(format!("{prefix}{suggestions}"), span)
+        Self::new_with_code(
+            DiagnosticCode::TypeNotUsedInPromptWarning,
+            format!("{prefix}{suggestions}"),
+            span,
+        )
+    }
+
+    /// A problem found while type checking a Jinja template.
+    pub fn new_template_type_error(message: String, span: Span) -> DatamodelWarning {
+        Self::new_with_code(DiagnosticCode::TemplateTypeError, message, span)
+    }
+
+    pub fn new_duplicate_test_function(func_name: &str, span: Span) -> DatamodelWarning {
+        Self::new_with_code(
+            DiagnosticCode::DuplicateTestFunctionWarning,
+            format!("Function \"{func_name}\" is already specified for this test case."),
+            span,
+        )
+    }
+
+    pub fn new_unused_argument(span: Span) -> DatamodelWarning {
+        Self::new_with_code(
+            DiagnosticCode::UnusedArgumentWarning,
+            "Unused argument. It has no effect and can be removed.".to_string(),
+            span,
+        )
+    }
+
+    pub fn new_invalid_suppression(message: &str, span: Span) -> DatamodelWarning {
+        Self::new_with_code(
+            DiagnosticCode::InvalidSuppression,
+            message.to_string(),
+            span,
+        )
+    }
+
+    pub fn new_unknown_model(
+        model: &str,
+        provider: &str,
+        known_models: &[&str],
+        span: Span,
+    ) -> DatamodelWarning {
+        let known_models = known_models.to_vec();
+        let close_models = sort_by_match(model, &known_models, Some(1));
+        let message = match close_models.first() {
+            Some(close) => {
+                format!("`{model}` is not a known {provider} model. Did you mean `{close}`?")
+            }
+            None => format!(
+                "`{model}` is not a known {provider} model. Known models: {}",
+                known_models.join(", ")
+            ),
+        };
+        Self::new_with_code(DiagnosticCode::UnknownModel, message, span)
     }
 
     pub fn prompt_variable_unused(message: &str, span: Span) -> DatamodelWarning {
-        Self::new(message.to_string(), span)
+        Self::new_with_code(
+            DiagnosticCode::UnusedPromptVariable,
+            message.to_string(),
+            span,
+        )
     }
 
     /// The user-facing warning message.
@@ -152,15 +238,34 @@ impl DatamodelWarning {
         &self.message
     }
 
+    pub fn code(&self) -> DiagnosticCode {
+        self.code
+    }
+
     /// The source span the warning applies to.
     pub fn span(&self) -> &Span {
         &self.span
     }
 
+    pub fn related(&self) -> &[RelatedInformation] {
+        &self.related
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        &self.fixes
+    }
+
+    /// The same warning, moved to `span`.
+    pub(crate) fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
     pub fn pretty_print(&self, f: &mut dyn std::io::Write) -> std::io::Result<()> {
         pretty_print(
             f,
             self.span(),
+            self.code,
             self.message.as_ref(),
             &DatamodelWarningColorer {},
         )
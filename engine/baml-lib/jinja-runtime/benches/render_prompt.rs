@@ -0,0 +1,102 @@
+//! Prompt rendering benchmarks.
+//!
+//! Run with `cargo bench -p internal-baml-jinja`.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use baml_types::{BamlMap, BamlValue, FieldType};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use internal_baml_core::{
+    internal_baml_diagnostics::SourceFile, ir::repr::IntermediateRepr, validate,
+};
+use internal_baml_jinja::{
+    render_prompt, types::OutputFormatContent, RenderContext, RenderContext_Client,
+};
+
+const TEMPLATE: &str = r#"
+{{ _.role("system") }}
+You are a careful assistant. Answer using the schema below.
+
+{{ ctx.output_format }}
+
+{% for message in history %}
+{{ _.role(message.role) }}
+{{ message.content }}
+{% endfor %}
+
+{{ _.role("user") }}
+{{ question }}
+"#;
+
+fn ir() -> IntermediateRepr {
+    let path = PathBuf::from("baml_src");
+    let schema = validate(
+        &path,
+        vec![SourceFile::from((
+            path.join("main.baml"),
+            "class Answer {\n  text string\n  confidence float\n}\n".to_string(),
+        ))],
+    );
+    IntermediateRepr::from_parser_database(&schema.db, schema.configuration).unwrap()
+}
+
+fn args(turns: usize) -> BamlValue {
+    let history = (0..turns)
+        .map(|i| {
+            BamlValue::Map(BamlMap::from_iter([
+                (
+                    "role".to_string(),
+                    BamlValue::String(if i % 2 == 0 { "user" } else { "assistant" }.to_string()),
+                ),
+                (
+                    "content".to_string(),
+                    BamlValue::String(format!("Message number {i} in the conversation.")),
+                ),
+            ]))
+        })
+        .collect();
+    BamlValue::Map(BamlMap::from_iter([
+        ("history".to_string(), BamlValue::List(history)),
+        (
+            "question".to_string(),
+            BamlValue::String("What did we talk about?".to_string()),
+        ),
+    ]))
+}
+
+fn render_context() -> RenderContext {
+    RenderContext {
+        client: RenderContext_Client {
+            name: "gpt4".to_string(),
+            provider: "openai".to_string(),
+            default_role: "system".to_string(),
+            allowed_roles: vec![
+                "system".to_string(),
+                "user".to_string(),
+                "assistant".to_string(),
+            ],
+        },
+        output_format: OutputFormatContent::target(FieldType::Class("Answer".to_string())).build(),
+        tags: HashMap::new(),
+    }
+}
+
+fn bench_render(c: &mut Criterion) {
+    let ir = ir();
+    let env_vars = HashMap::new();
+    let mut group = c.benchmark_group("render_prompt");
+    for turns in [1, 10, 100] {
+        let args = args(turns);
+        group.bench_with_input(BenchmarkId::from_parameter(turns), &args, |b, args| {
+            b.iter(|| {
+                black_box(
+                    render_prompt(TEMPLATE, args, render_context(), &[], &ir, &env_vars).unwrap(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_render);
+criterion_main!(benches);
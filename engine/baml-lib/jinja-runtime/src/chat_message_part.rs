@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
 use baml_types::{BamlMedia, BamlMediaContent};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum ChatMessagePart {
     // raw user-provided text
     Text(String),
@@ -65,6 +65,9 @@ impl std::fmt::Display for ChatMessagePart {
                 BamlMediaContent::Base64(_) => {
                     write!(f, "<{}_placeholder base64>", media.media_type)
                 }
+                BamlMediaContent::FileRef(file_ref) => {
+                    write!(f, "<{}_placeholder: {}>", media.media_type, file_ref)
+                }
                 BamlMediaContent::File(file) => write!(
                     f,
                     "<{}_placeholder: {}>",
@@ -1,12 +1,15 @@
 use baml_types::{BamlMedia, BamlValue, EvaluationContext};
 use colored::*;
+use indexmap::IndexMap;
 mod chat_message_part;
 
 mod output_format;
 use internal_baml_core::ir::jinja_helpers::get_env;
 use internal_baml_core::ir::repr::IntermediateRepr;
+use internal_baml_core::{ast::RawString, internal_baml_diagnostics::Span};
 pub use output_format::types;
 mod baml_value_to_jinja_value;
+mod source_map;
 
 use minijinja::{self, value::Kwargs};
 use minijinja::{context, ErrorKind};
@@ -18,6 +21,7 @@ use std::collections::HashMap;
 use crate::baml_value_to_jinja_value::IntoMiniJinjaValue;
 pub use crate::chat_message_part::ChatMessagePart;
 use crate::output_format::OutputFormat;
+use crate::source_map::TemplateSourceMap;
 
 #[allow(non_camel_case_types)]
 #[derive(Clone, Debug, Serialize)]
@@ -38,21 +42,101 @@ pub struct RenderContext {
 pub struct TemplateStringMacro {
     pub name: String,
     pub args: Vec<(String, String)>,
+    /// The default value of each argument that has one.
+    pub defaults: IndexMap<String, BamlValue>,
     pub template: String,
+    /// Where `template` was read from, if anywhere.
+    pub source: Option<RawString>,
 }
 
+/// A prompt failed to render.
+#[derive(Debug)]
+pub struct PromptRenderError {
+    pub message: String,
+    /// The part of the `.baml` source the error was raised in, when it can
+    /// be traced back.
+    pub span: Option<Span>,
+}
+
+impl std::fmt::Display for PromptRenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error occurred while rendering prompt: {}", self.message)?;
+        if let Some(span) = &self.span {
+            let ((line, column), _) = span.line_and_column();
+            write!(
+                f,
+                "\n  --> {}:{}:{}",
+                span.file.path(),
+                line + 1,
+                column + 1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PromptRenderError {}
+
 const MAGIC_CHAT_ROLE_DELIMITER: &str = "BAML_CHAT_ROLE_MAGIC_STRING_DELIMITER";
 const MAGIC_MEDIA_DELIMITER: &str = "BAML_MEDIA_MAGIC_STRING_DELIMITER";
+/// Wrap the output of template strings that set a role, see
+/// [`close_role_scopes`].
+const ROLE_SCOPE_START: &str = ":baml-start-role-scope:";
+const ROLE_SCOPE_END: &str = ":baml-end-role-scope:";
+
+/// `value` as a Jinja literal, for the default value of a macro argument.
+fn jinja_literal(value: &BamlValue) -> String {
+    match value {
+        BamlValue::String(value) => json!(value).to_string(),
+        BamlValue::Int(value) => value.to_string(),
+        // `{:?}` keeps the `.0` of whole numbers, so they stay floats.
+        BamlValue::Float(value) => format!("{value:?}"),
+        BamlValue::Bool(value) => value.to_string(),
+        _ => "none".to_string(),
+    }
+}
 
-fn render_minijinja(
+/// Dedents and trims the prompt and prepends the template string macros,
+/// recording where each part came from.
+fn build_template<'a>(
     template: &str,
-    args: &minijinja::Value,
-    mut ctx: RenderContext,
-    template_string_macros: &[TemplateStringMacro],
-    default_role: String,
-    allowed_roles: Vec<String>,
-) -> Result<RenderedPrompt, minijinja::Error> {
-    let mut env = get_env();
+    source: Option<&'a RawString>,
+    template_string_macros: &'a [TemplateStringMacro],
+) -> (String, TemplateSourceMap<'a>) {
+    let mut source_map = TemplateSourceMap::default();
+    let mut combined = String::new();
+
+    // inject macros
+    for tsm in template_string_macros {
+        combined += &format!(
+            "{{% macro {name}({template_args}) %}}",
+            name = tsm.name,
+            template_args = tsm
+                .args
+                .iter()
+                .map(|(name, _)| match tsm.defaults.get(name) {
+                    Some(default) => format!("{name}={}", jinja_literal(default)),
+                    None => name.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        // Roles set in a template string end with it.
+        let sets_role = tsm.template.contains("_.role") || tsm.template.contains("_.chat");
+        if sets_role {
+            combined += &role_delimited(ROLE_SCOPE_START);
+        }
+        source_map.push(
+            combined.len()..combined.len() + tsm.template.len(),
+            0,
+            tsm.source.as_ref(),
+        );
+        combined += &tsm.template;
+        if sets_role {
+            combined += &role_delimited(ROLE_SCOPE_END);
+        }
+        combined += "{% endmacro %}\n";
+    }
 
     // dedent
     let whitespace_length = template
@@ -61,38 +145,41 @@ fn render_minijinja(
         .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
         .min()
         .unwrap_or(0);
-    let template = template
+    let dedented = template
         .split('\n')
         .map(|line| line.chars().skip(whitespace_length).collect::<String>())
         .collect::<Vec<String>>()
         .join("\n");
 
     // trim
-    let template = template.trim();
-    log::debug!("Rendering template: \n{}\n------\n", template);
-    // let args_dict = minijinja::Value::from_serializable(args);
+    let trimmed = dedented.trim();
+    log::debug!("Rendering template: \n{}\n------\n", trimmed);
+
+    // Offsets only line up with the source if dedenting was a no-op, which
+    // it is for prompts read from a raw string.
+    if whitespace_length == 0 {
+        let leading = dedented.len() - dedented.trim_start().len();
+        source_map.push(
+            combined.len()..combined.len() + trimmed.len(),
+            leading,
+            source,
+        );
+    }
+    combined += trimmed;
 
-    // inject macros
-    let template = template_string_macros
-        .iter()
-        .map(|tsm| {
-            format!(
-                "{{% macro {name}({template_args}) %}}{template}{{% endmacro %}}",
-                name = tsm.name,
-                template_args = tsm
-                    .args
-                    .iter()
-                    .map(|(name, _)| name.as_str())
-                    .collect::<Vec<_>>()
-                    .join(", "),
-                template = tsm.template,
-            )
-        })
-        .chain(std::iter::once(template.to_string()))
-        .collect::<Vec<_>>()
-        .join("\n");
+    (combined, source_map)
+}
 
-    env.add_template("prompt", &template)?;
+fn render_minijinja(
+    template: &str,
+    args: &minijinja::Value,
+    mut ctx: RenderContext,
+    default_role: String,
+    allowed_roles: Vec<String>,
+) -> Result<RenderedPrompt, minijinja::Error> {
+    let mut env = get_env();
+
+    env.add_template("prompt", template)?;
     let client = ctx.client.clone();
     let tags = std::mem::take(&mut ctx.tags);
     let formatter = OutputFormat::new(ctx);
@@ -168,7 +255,7 @@ fn render_minijinja(
 
     let tmpl = env.get_template("prompt")?;
 
-    let rendered = tmpl.render(args)?;
+    let rendered = close_role_scopes(&tmpl.render(args)?, &default_role);
 
     if !rendered.contains(MAGIC_CHAT_ROLE_DELIMITER) && !rendered.contains(MAGIC_MEDIA_DELIMITER) {
         return Ok(RenderedPrompt::Completion(rendered));
@@ -257,7 +344,54 @@ fn render_minijinja(
     Ok(RenderedPrompt::Chat(chat_messages))
 }
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
+fn role_delimited(chunk: &str) -> String {
+    format!("{MAGIC_CHAT_ROLE_DELIMITER}{chunk}{MAGIC_CHAT_ROLE_DELIMITER}")
+}
+
+/// Removes the markers around the output of template strings that set a role,
+/// switching back to the role from before the template string where it
+/// changed. This way a template string can add e.g. a system message to the
+/// middle of a user message.
+fn close_role_scopes(rendered: &str, default_role: &str) -> String {
+    if !rendered.contains(ROLE_SCOPE_START) {
+        return rendered.to_string();
+    }
+
+    let mut result = String::new();
+    // The role directive in effect, and the one in effect where each open
+    // scope started.
+    let mut current = None;
+    let mut scopes = vec![];
+    for (i, chunk) in rendered.split(MAGIC_CHAT_ROLE_DELIMITER).enumerate() {
+        // Splitting leaves directives at the odd indices.
+        if i % 2 == 0 {
+            result += chunk;
+        } else if chunk == ROLE_SCOPE_START {
+            scopes.push(current);
+        } else if chunk == ROLE_SCOPE_END {
+            let Some(outer) = scopes.pop() else {
+                continue;
+            };
+            if current != outer {
+                let directive = match outer {
+                    Some(directive) => directive.to_string(),
+                    None => format!(
+                        ":baml-start-baml:{}:baml-end-baml:",
+                        json!({ "role": default_role, "__baml_allow_dupe_role__": false })
+                    ),
+                };
+                result += &role_delimited(&directive);
+                current = outer;
+            }
+        } else {
+            result += &role_delimited(chunk);
+            current = Some(chunk);
+        }
+    }
+    result
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct RenderedChatMessage {
     pub role: String,
     pub allow_duplicate_role: bool,
@@ -287,7 +421,7 @@ impl ImageBase64 {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum RenderedPrompt {
     Completion(String),
     Chat(Vec<RenderedChatMessage>),
@@ -409,6 +543,29 @@ pub fn render_prompt(
     template_string_macros: &[TemplateStringMacro],
     ir: &IntermediateRepr,
     env_vars: &HashMap<String, String>,
+) -> anyhow::Result<RenderedPrompt> {
+    render_prompt_with_source(
+        template,
+        None,
+        args,
+        ctx,
+        template_string_macros,
+        ir,
+        env_vars,
+    )
+}
+
+/// Like [`render_prompt`], but render errors carry the span in `source`
+/// (or in one of the macros' sources) they were raised at. `template` must
+/// be `source.value()`.
+pub fn render_prompt_with_source(
+    template: &str,
+    source: Option<&RawString>,
+    args: &BamlValue,
+    ctx: RenderContext,
+    template_string_macros: &[TemplateStringMacro],
+    ir: &IntermediateRepr,
+    env_vars: &HashMap<String, String>,
 ) -> anyhow::Result<RenderedPrompt> {
     if !matches!(args, BamlValue::Map(_)) {
         anyhow::bail!("args must be a map");
@@ -417,11 +574,11 @@ pub fn render_prompt(
     let minijinja_args: minijinja::Value = args.clone().to_minijinja_value(ir, &eval_ctx);
     let default_role = ctx.client.default_role.clone();
     let allowed_roles = ctx.client.allowed_roles.clone();
+    let (template, source_map) = build_template(template, source, template_string_macros);
     let rendered = render_minijinja(
-        template,
+        &template,
         &minijinja_args,
         ctx,
-        template_string_macros,
         default_role,
         allowed_roles,
     );
@@ -429,6 +586,8 @@ pub fn render_prompt(
     match rendered {
         Ok(r) => Ok(r),
         Err(err) => {
+            let span = source_map.error_span(&template, &err);
+
             let mut minijinja_err = "".to_string();
             minijinja_err += &format!("{err:#}");
 
@@ -438,7 +597,11 @@ pub fn render_prompt(
                 err = next_err;
             }
 
-            anyhow::bail!("Error occurred while rendering prompt: {minijinja_err}");
+            Err(PromptRenderError {
+                message: minijinja_err,
+                span,
+            }
+            .into())
         }
     }
 }
@@ -1312,6 +1475,183 @@ mod render_tests {
         Ok(())
     }
 
+    #[test]
+    fn render_template_string_defaults() -> anyhow::Result<()> {
+        setup_logging();
+
+        let ir = make_test_ir(
+            r##"
+            template_string Greeting(name: string, tone: string = "friendly", loud: bool = true) #"
+                Greet {{ name }} in a {{ tone }} tone{% if loud %}!{% endif %}
+            "#
+            "##,
+        )?;
+        let macros = ir
+            .walk_template_strings()
+            .map(|t| TemplateStringMacro {
+                name: t.name().into(),
+                args: t
+                    .inputs()
+                    .iter()
+                    .map(|i| (i.name.clone(), i.r#type.elem.to_string()))
+                    .collect(),
+                defaults: t.defaults().clone(),
+                template: t.template().into(),
+                source: None,
+            })
+            .collect::<Vec<_>>();
+
+        let rendered = render_prompt(
+            "{{ Greeting(\"Ada\") }}\n{{ Greeting(\"Bob\", tone=\"formal\", loud=false) }}",
+            &BamlValue::Map(BamlMap::new()),
+            RenderContext {
+                client: RenderContext_Client {
+                    name: "gpt4".to_string(),
+                    provider: "openai".to_string(),
+                    default_role: "system".to_string(),
+                    allowed_roles: vec!["system".to_string()],
+                },
+                output_format: OutputFormatContent::new_string(),
+                tags: HashMap::new(),
+            },
+            &macros,
+            &ir,
+            &HashMap::new(),
+        )?;
+
+        let RenderedPrompt::Completion(rendered) = rendered else {
+            anyhow::bail!("Expected a completion prompt, got {rendered:?}");
+        };
+        assert!(rendered.contains("Greet Ada in a friendly tone!"));
+        assert!(rendered.contains("Greet Bob in a formal tone"));
+        assert!(!rendered.contains("formal tone!"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_template_string_roles() -> anyhow::Result<()> {
+        setup_logging();
+
+        let ir = make_test_ir(
+            r##"
+            template_string Rules #"
+                {{ _.role("system") }}
+                Answer in one word.
+            "#
+
+            template_string Question(text: string) #"
+                Q: {{ text }}
+            "#
+            "##,
+        )?;
+        let macros = ir
+            .walk_template_strings()
+            .map(|t| TemplateStringMacro {
+                name: t.name().into(),
+                args: t
+                    .inputs()
+                    .iter()
+                    .map(|i| (i.name.clone(), i.r#type.elem.to_string()))
+                    .collect(),
+                defaults: t.defaults().clone(),
+                template: t.template().into(),
+                source: None,
+            })
+            .collect::<Vec<_>>();
+
+        let rendered = render_prompt(
+            "{{ _.role(\"user\") }}\nHi!\n{{ Rules() }}\n{{ Question(\"Why?\") }}\n{{ Rules() }}",
+            &BamlValue::Map(BamlMap::new()),
+            RenderContext {
+                client: RenderContext_Client {
+                    name: "gpt4".to_string(),
+                    provider: "openai".to_string(),
+                    default_role: "system".to_string(),
+                    allowed_roles: vec!["system".to_string(), "user".to_string()],
+                },
+                output_format: OutputFormatContent::new_string(),
+                tags: HashMap::new(),
+            },
+            &macros,
+            &ir,
+            &HashMap::new(),
+        )?;
+
+        let message = |role: &str, text: &str| RenderedChatMessage {
+            role: role.to_string(),
+            allow_duplicate_role: false,
+            parts: vec![ChatMessagePart::Text(text.to_string())],
+        };
+        assert_eq!(
+            rendered,
+            RenderedPrompt::Chat(vec![
+                message("user", "Hi!"),
+                message("system", "Answer in one word."),
+                message("user", "Q: Why?"),
+                message("system", "Answer in one word."),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_error_points_at_source() -> anyhow::Result<()> {
+        use internal_baml_core::ir::IRHelper;
+
+        setup_logging();
+        let args: BamlValue = BamlValue::Map(BamlMap::from([(
+            "name".to_string(),
+            BamlValue::String("world".to_string()),
+        )]));
+
+        let source = r##"
+            function Greet(name: string) -> string {
+                client "openai/gpt-4o"
+                prompt #"
+                    Hello,
+
+                    {{ name | nosuchfilter }}!
+                "#
+            }
+            "##;
+        let ir = make_test_ir(source)?;
+        let function = ir.find_function("Greet")?;
+        let config = &function.elem().configs[0];
+
+        let rendered = render_prompt_with_source(
+            &config.prompt_template,
+            config.prompt_source.as_ref(),
+            &args,
+            RenderContext {
+                client: RenderContext_Client {
+                    name: "gpt4".to_string(),
+                    provider: "openai".to_string(),
+                    default_role: "system".to_string(),
+                    allowed_roles: vec!["system".to_string()],
+                },
+                output_format: OutputFormatContent::new_string(),
+                tags: HashMap::new(),
+            },
+            &[],
+            &ir,
+            &HashMap::new(),
+        );
+
+        let Err(err) = rendered else {
+            anyhow::bail!("Expected template rendering to fail, but it succeeded");
+        };
+        let err = err
+            .downcast_ref::<PromptRenderError>()
+            .expect("a PromptRenderError");
+        let span = err.span.as_ref().expect("a span in fake_file.baml");
+        assert!(source[span.start..span.end].contains("nosuchfilter"));
+        assert!(err.to_string().contains("fake_file.baml:7"));
+
+        Ok(())
+    }
+
     #[test]
     fn render_class_with_aliases() -> anyhow::Result<()> {
         setup_logging();
@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use baml_types::{Constraint, FieldType, TypeValue};
+use baml_types::{
+    BoolStrings, Constraint, EnumMatching, FieldType, NumberLocale, StreamingBehavior, TypeValue,
+};
 use indexmap::{IndexMap, IndexSet};
 
 #[derive(Debug)]
@@ -41,6 +43,7 @@ pub struct Enum {
     // name and description
     pub values: Vec<(Name, Option<String>)>,
     pub constraints: Vec<Constraint>,
+    pub matching: EnumMatching,
 }
 
 /// The components of a Class needed to render `OutputFormatContent`.
@@ -59,6 +62,12 @@ pub struct OutputFormatContent {
     pub classes: Arc<IndexMap<String, Class>>,
     recursive_classes: Arc<IndexSet<String>>,
     pub target: FieldType,
+    /// How numbers written as strings are read.
+    pub number_locale: NumberLocale,
+    /// Keyed by class and field, for fields with `@bool_strings`.
+    bool_strings: Arc<IndexMap<(String, String), BoolStrings>>,
+    /// Keyed by class and field, for fields with `@stream.*` attributes.
+    streaming: Arc<IndexMap<(String, String), StreamingBehavior>>,
 }
 
 /// Builder for [`OutputFormatContent`].
@@ -68,6 +77,9 @@ pub struct Builder {
     /// Order matters for this one.
     recursive_classes: IndexSet<String>,
     target: FieldType,
+    number_locale: NumberLocale,
+    bool_strings: IndexMap<(String, String), BoolStrings>,
+    streaming: IndexMap<(String, String), StreamingBehavior>,
 }
 
 impl Builder {
@@ -77,6 +89,9 @@ impl Builder {
             classes: vec![],
             recursive_classes: IndexSet::new(),
             target,
+            number_locale: NumberLocale::default(),
+            bool_strings: IndexMap::new(),
+            streaming: IndexMap::new(),
         }
     }
 
@@ -100,6 +115,25 @@ impl Builder {
         self
     }
 
+    pub fn number_locale(mut self, number_locale: NumberLocale) -> Self {
+        self.number_locale = number_locale;
+        self
+    }
+
+    /// Which strings the bools of each `(class, field)` are read from.
+    /// Fields left out are [`BoolStrings::Strict`].
+    pub fn bool_strings(mut self, bool_strings: IndexMap<(String, String), BoolStrings>) -> Self {
+        self.bool_strings = bool_strings;
+        self
+    }
+
+    /// How each `(class, field)` shows up in partial results. Fields left
+    /// out show up as they are.
+    pub fn streaming(mut self, streaming: IndexMap<(String, String), StreamingBehavior>) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
     pub fn build(self) -> OutputFormatContent {
         OutputFormatContent {
             enums: Arc::new(
@@ -116,6 +150,9 @@ impl Builder {
             ),
             recursive_classes: Arc::new(self.recursive_classes.into_iter().collect()),
             target: self.target,
+            number_locale: self.number_locale,
+            bool_strings: Arc::new(self.bool_strings),
+            streaming: Arc::new(self.streaming),
         }
     }
 }
@@ -141,6 +178,18 @@ pub(crate) enum MapStyle {
     ObjectLiteral,
 }
 
+/// The syntax the schema asks the answer to be written in.
+#[derive(Clone, Copy, PartialEq, Eq, strum::EnumString, strum::VariantNames)]
+pub(crate) enum Format {
+    #[strum(serialize = "json")]
+    Json,
+
+    /// Elements named after the fields, for models that are better at
+    /// writing XML, like Claude.
+    #[strum(serialize = "xml")]
+    Xml,
+}
+
 pub(crate) struct RenderOptions {
     prefix: RenderSetting<String>,
     pub(crate) or_splitter: String,
@@ -148,6 +197,7 @@ pub(crate) struct RenderOptions {
     hoisted_class_prefix: RenderSetting<String>,
     always_hoist_enums: RenderSetting<bool>,
     map_style: MapStyle,
+    format: Format,
 }
 
 impl Default for RenderOptions {
@@ -159,6 +209,7 @@ impl Default for RenderOptions {
             hoisted_class_prefix: RenderSetting::Auto,
             always_hoist_enums: RenderSetting::Auto,
             map_style: MapStyle::TypeParameters,
+            format: Format::Json,
         }
     }
 }
@@ -174,6 +225,7 @@ impl RenderOptions {
         always_hoist_enums: Option<bool>,
         map_style: Option<MapStyle>,
         hoisted_class_prefix: Option<Option<String>>,
+        format: Option<Format>,
     ) -> Self {
         Self {
             prefix: prefix.map_or(RenderSetting::Auto, |p| {
@@ -189,6 +241,7 @@ impl RenderOptions {
             hoisted_class_prefix: hoisted_class_prefix.map_or(RenderSetting::Auto, |p| {
                 p.map_or(RenderSetting::Never, RenderSetting::Always)
             }),
+            format: format.unwrap_or(Format::Json),
         }
     }
 
@@ -270,6 +323,34 @@ impl std::fmt::Display for ClassRender {
     }
 }
 
+impl ClassRender {
+    /// The fields as elements, e.g. `<name>string</name>`, without an element
+    /// for the class itself.
+    fn to_xml(&self) -> String {
+        self.values
+            .iter()
+            .map(|value| {
+                let element = xml_element(&value.name, &value.r#type);
+                match &value.description {
+                    Some(desc) => format!("<!-- {} -->\n{element}", desc.replace('\n', "\n     ")),
+                    None => element,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `content` in an element named `tag`, on lines of its own if it's elements
+/// or more than a line.
+fn xml_element(tag: &str, content: &str) -> String {
+    if content.contains('\n') || content.starts_with('<') {
+        format!("<{tag}>\n  {}\n</{tag}>", content.replace('\n', "\n  "))
+    } else {
+        format!("<{tag}>{content}</{tag}>")
+    }
+}
+
 struct MapRender<'s> {
     style: &'s MapStyle,
     key_type: String,
@@ -310,6 +391,10 @@ impl OutputFormatContent {
             options: &RenderOptions,
             output_format_content: &OutputFormatContent,
         ) -> Option<String> {
+            let syntax = match options.format {
+                Format::Json => "JSON",
+                Format::Xml => "XML",
+            };
             match ft {
                 FieldType::Primitive(TypeValue::String) => None,
                 FieldType::Primitive(p) => Some(format!(
@@ -331,16 +416,17 @@ impl OutputFormatContent {
                         "\n"
                     };
 
-                    Some(format!("Answer in JSON using this {type_prefix}:{end}"))
+                    Some(format!("Answer in {syntax} using this {type_prefix}:{end}"))
                 }
-                FieldType::List(_) => Some(String::from(
-                    "Answer with a JSON Array using this schema:\n",
-                )),
+                FieldType::List(_) => Some(String::from(match options.format {
+                    Format::Json => "Answer with a JSON Array using this schema:\n",
+                    Format::Xml => "Answer in XML with an element per item, using this schema:\n",
+                })),
                 FieldType::Union(_) => {
-                    Some(String::from("Answer in JSON using any of these schemas:\n"))
+                    Some(format!("Answer in {syntax} using any of these schemas:\n"))
                 }
-                FieldType::Optional(_) => Some(String::from("Answer in JSON using this schema:\n")),
-                FieldType::Map(_, _) => Some(String::from("Answer in JSON using this schema:\n")),
+                FieldType::Optional(_) => Some(format!("Answer in {syntax} using this schema:\n")),
+                FieldType::Map(_, _) => Some(format!("Answer in {syntax} using this schema:\n")),
                 FieldType::Tuple(_) => None,
                 FieldType::Constrained { base, .. } => {
                     auto_prefix(base, options, output_format_content)
@@ -355,6 +441,13 @@ impl OutputFormatContent {
         }
     }
 
+    /// The element `cls` is written in, in XML.
+    fn class_tag<'a>(&'a self, cls: &'a str) -> &'a str {
+        self.classes
+            .get(cls)
+            .map_or(cls, |class| class.name.rendered_name())
+    }
+
     fn enum_to_string(&self, enm: &Enum, options: &RenderOptions) -> String {
         EnumRender {
             name: enm.name.rendered_name().to_string(),
@@ -460,7 +553,7 @@ impl OutputFormatContent {
                     ));
                 };
 
-                ClassRender {
+                let render = ClassRender {
                     name: class.name.rendered_name().to_string(),
                     values: class
                         .fields
@@ -478,8 +571,20 @@ impl OutputFormatContent {
                             })
                         })
                         .collect::<Result<_, minijinja::Error>>()?,
+                };
+                match options.format {
+                    Format::Json => render.to_string(),
+                    Format::Xml => render.to_xml(),
                 }
-                .to_string()
+            }
+            FieldType::List(inner) if options.format == Format::Xml => {
+                let inner_str =
+                    self.render_possibly_recursive_type(options, inner, render_state, false)?;
+                let tag = match inner.as_ref() {
+                    FieldType::Class(cls) => self.class_tag(cls),
+                    _ => "item",
+                };
+                format!("{}\n...", xml_element(tag, &inner_str))
             }
             FieldType::List(inner) => {
                 let is_recursive = match inner.as_ref() {
@@ -507,7 +612,19 @@ impl OutputFormatContent {
             }
             FieldType::Union(items) => items
                 .iter()
-                .map(|t| self.render_possibly_recursive_type(options, t, render_state, false))
+                .map(|t| {
+                    let rendered =
+                        self.render_possibly_recursive_type(options, t, render_state, false)?;
+                    // Classes are told apart by their element in XML.
+                    Ok(match (options.format, t) {
+                        (Format::Xml, FieldType::Class(cls))
+                            if !self.recursive_classes.contains(cls) =>
+                        {
+                            xml_element(self.class_tag(cls), &rendered)
+                        }
+                        _ => rendered,
+                    })
+                })
                 .collect::<Result<Vec<_>, minijinja::Error>>()?
                 .join(&options.or_splitter),
             FieldType::Optional(inner) => {
@@ -525,6 +642,14 @@ impl OutputFormatContent {
                     "Tuple type is not supported in outputs",
                 ))
             }
+            FieldType::Map(_, value_type) if options.format == Format::Xml => {
+                let value_str =
+                    self.render_possibly_recursive_type(options, value_type, render_state, false)?;
+                format!(
+                    "<!-- an element per key, named after it -->\n{}\n...",
+                    xml_element("key", &value_str)
+                )
+            }
             FieldType::Map(key_type, value_type) => MapRender {
                 style: &options.map_style,
                 // TODO: Key can't be recursive because we only support strings
@@ -571,6 +696,8 @@ impl OutputFormatContent {
         if let FieldType::Class(class) = &self.target {
             if self.recursive_classes.contains(class) {
                 message = Some(class.to_owned());
+            } else if options.format == Format::Xml {
+                message = message.map(|m| xml_element(self.class_tag(class), &m));
             }
         }
 
@@ -593,11 +720,18 @@ impl OutputFormatContent {
                 false,
             )?;
 
-            class_definitions.push(match &options.hoisted_class_prefix {
-                RenderSetting::Always(prefix) if !prefix.is_empty() => {
+            class_definitions.push(match (&options.hoisted_class_prefix, options.format) {
+                (RenderSetting::Always(prefix), Format::Json) if !prefix.is_empty() => {
                     format!("{prefix} {class_name} {schema}")
                 }
-                _ => format!("{class_name} {schema}"),
+                (_, Format::Json) => format!("{class_name} {schema}"),
+                (RenderSetting::Always(prefix), Format::Xml) if !prefix.is_empty() => {
+                    format!(
+                        "{prefix} {class_name}:\n{}",
+                        xml_element(class_name, &schema)
+                    )
+                }
+                (_, Format::Xml) => xml_element(class_name, &schema),
             });
         }
 
@@ -657,6 +791,22 @@ impl OutputFormatContent {
             .get(name)
             .ok_or_else(|| anyhow::anyhow!("Class {} not found", name))
     }
+
+    /// Which strings the bools of `field` of `class` are read from.
+    pub fn bool_strings(&self, class: &str, field: &str) -> BoolStrings {
+        self.bool_strings
+            .get(&(class.to_string(), field.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// How `field` of `class` shows up in partial results.
+    pub fn streaming(&self, class: &str, field: &str) -> StreamingBehavior {
+        self.streaming
+            .get(&(class.to_string(), field.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -706,6 +856,7 @@ mod tests {
                 (Name::new("Blue".to_string()), None),
             ],
             constraints: Vec::new(),
+            matching: Default::default(),
         }];
 
         let content = OutputFormatContent::target(FieldType::Enum("Color".to_string()))
@@ -2214,4 +2365,64 @@ Answer in JSON using this schema:
             ))
         );
     }
+
+    #[test]
+    fn render_class_as_xml() {
+        let classes = vec![
+            Class {
+                name: Name::new("Person".to_string()),
+                fields: vec![
+                    (
+                        Name::new("name".to_string()),
+                        FieldType::string(),
+                        Some("The person's name".to_string()),
+                    ),
+                    (
+                        Name::new("hobbies".to_string()),
+                        FieldType::list(FieldType::string()),
+                        None,
+                    ),
+                    (
+                        Name::new("address".to_string()),
+                        FieldType::optional(FieldType::class("Address")),
+                        None,
+                    ),
+                ],
+                constraints: Vec::new(),
+            },
+            Class {
+                name: Name::new("Address".to_string()),
+                fields: vec![(Name::new("street".to_string()), FieldType::string(), None)],
+                constraints: Vec::new(),
+            },
+        ];
+
+        let content = OutputFormatContent::target(FieldType::class("Person"))
+            .classes(classes)
+            .build();
+        let rendered = content
+            .render(RenderOptions {
+                format: Format::Xml,
+                ..Default::default()
+            })
+            .unwrap();
+        #[rustfmt::skip]
+        assert_eq!(
+            rendered,
+            Some(String::from(
+r#"Answer in XML using this schema:
+<Person>
+  <!-- The person's name -->
+  <name>string</name>
+  <hobbies>
+    <item>string</item>
+    ...
+  </hobbies>
+  <address>
+    <street>string</street> or null
+  </address>
+</Person>"#
+            ))
+        );
+    }
 }
@@ -0,0 +1,70 @@
+use std::ops::Range;
+
+use internal_baml_core::{ast::RawString, internal_baml_diagnostics::Span};
+
+/// Records which parts of the template handed to minijinja were copied from
+/// which raw strings, so render errors can point at the `.baml` file.
+#[derive(Default)]
+pub(crate) struct TemplateSourceMap<'a> {
+    segments: Vec<Segment<'a>>,
+}
+
+struct Segment<'a> {
+    /// Byte range in the combined template.
+    range: Range<usize>,
+    /// Where `range.start` is in `source.value()`.
+    source_offset: usize,
+    source: &'a RawString,
+}
+
+impl<'a> TemplateSourceMap<'a> {
+    pub(crate) fn push(
+        &mut self,
+        range: Range<usize>,
+        source_offset: usize,
+        source: Option<&'a RawString>,
+    ) {
+        if let Some(source) = source {
+            self.segments.push(Segment {
+                range,
+                source_offset,
+                source,
+            });
+        }
+    }
+
+    /// The file span of a byte range in the combined template, if it was
+    /// copied from a raw string.
+    pub(crate) fn span(&self, range: Range<usize>) -> Option<Span> {
+        let segment = self
+            .segments
+            .iter()
+            .find(|s| s.range.start <= range.start && range.start <= s.range.end)?;
+        let start = range.start - segment.range.start + segment.source_offset;
+        let end = range.end.clamp(range.start, segment.range.end) - segment.range.start
+            + segment.source_offset;
+        Some(segment.source.source_span(start..end))
+    }
+
+    /// The file span of a minijinja error in `template`. Errors without a
+    /// byte range fall back to the whole line they were reported on.
+    pub(crate) fn error_span(&self, template: &str, err: &minijinja::Error) -> Option<Span> {
+        if let Some(range) = err.range() {
+            return self.span(range);
+        }
+
+        let line = err.line()?.checked_sub(1)?;
+        let start = template
+            .split_inclusive('\n')
+            .take(line)
+            .map(str::len)
+            .sum::<usize>();
+        if start >= template.len() {
+            return None;
+        }
+        let end = template[start..]
+            .find('\n')
+            .map_or(template.len(), |i| start + i);
+        self.span(start..end)
+    }
+}
@@ -136,8 +136,11 @@ fn tracker_visit_expr(
                 ast::BinOpKind::Pow => Type::Number,
                 ast::BinOpKind::FloorDiv => Type::Number,
                 ast::BinOpKind::Rem => Type::Number,
-                ast::BinOpKind::Eq => Type::Bool,
-                ast::BinOpKind::Ne => Type::Bool,
+                ast::BinOpKind::Eq | ast::BinOpKind::Ne => {
+                    check_string_comparison(&expr.left, &lhs, &rhs, expr.span(), state);
+                    check_string_comparison(&expr.right, &rhs, &lhs, expr.span(), state);
+                    Type::Bool
+                }
                 ast::BinOpKind::Lt => Type::Bool,
                 ast::BinOpKind::Gt => Type::Bool,
                 ast::BinOpKind::In => Type::Bool,
@@ -379,6 +382,36 @@ fn tracker_visit_expr(
     }
 }
 
+/// Reports comparing `expr`, which is one of a few strings, against a string
+/// it can never be.
+fn check_string_comparison(
+    expr: &ast::Expr,
+    t: &Type,
+    other: &Type,
+    span: minijinja::machinery::Span,
+    state: &mut ScopeTracker,
+) {
+    let (Type::Union(options), Type::Literal(LiteralValue::String(value))) = (t, other) else {
+        return;
+    };
+    let options = options
+        .iter()
+        .map(|option| match option {
+            Type::Literal(LiteralValue::String(option)) => Some(option.as_str()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    if let Some(options) = options {
+        if !options.contains(&value.as_str()) {
+            state
+                .impossible_comparisons
+                .push(TypeError::new_impossible_comparison(
+                    expr, value, &options, span,
+                ));
+        }
+    }
+}
+
 fn infer_const_type(v: &minijinja::value::Value) -> Type {
     match v.kind() {
         minijinja::value::ValueKind::Undefined => Type::Undefined,
@@ -436,9 +469,19 @@ pub fn evaluate_type(expr: &ast::Expr, types: &PredefinedTypes) -> Result<Type,
     let mut state = ScopeTracker::new();
     let result = tracker_visit_expr(expr, &mut state, types);
 
+    state.errors.extend(state.impossible_comparisons);
     if state.errors.is_empty() {
         Ok(result)
     } else {
         Err(state.errors)
     }
 }
+
+/// Type errors that make the condition of an `if` always true or always
+/// false. Other errors aren't reported, as conditions often test variables
+/// that may not be defined.
+pub fn evaluate_condition(expr: &ast::Expr, types: &PredefinedTypes) -> Vec<TypeError> {
+    let mut state = ScopeTracker::new();
+    tracker_visit_expr(expr, &mut state, types);
+    state.impossible_comparisons
+}
@@ -25,6 +25,16 @@ pub use self::expr::evaluate_type;
 pub struct TypeError {
     message: String,
     span: Span,
+    call: Option<(String, CallError)>,
+}
+
+/// How a call passed the wrong arguments to a function, see
+/// [`TypeError::call_error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallError {
+    WrongArgCount,
+    MissingArg,
+    UnknownArg,
 }
 
 // Implementing the Display trait for TypeError.
@@ -83,6 +93,11 @@ impl TypeError {
         self.span
     }
 
+    /// The function a call passed the wrong arguments to, and how.
+    pub fn call_error(&self) -> Option<(&str, CallError)> {
+        self.call.as_ref().map(|(func, error)| (func.as_str(), *error))
+    }
+
     fn new_unresolved_variable(name: &str, span: Span, options: Vec<String>) -> Self {
         let mut close_names = sort_by_match(name, &options, Some(3));
         close_names.sort();
@@ -105,7 +120,11 @@ impl TypeError {
             )
         };
 
-        Self { message, span }
+        Self {
+            message,
+            span,
+            call: None,
+        }
     }
 
     fn new_wrong_arg_type(
@@ -125,6 +144,7 @@ impl TypeError {
                 got.name()
             ),
             span,
+            call: None,
         }
     }
 
@@ -132,6 +152,7 @@ impl TypeError {
         Self {
             message: format!("Function '{func}' expects argument '{name}'"),
             span,
+            call: Some((func.to_string(), CallError::MissingArg)),
         }
     }
 
@@ -141,6 +162,7 @@ impl TypeError {
                 "Function '{func}' expects {expected} arguments, but got {got}"
             ),
             span,
+            call: Some((func.to_string(), CallError::WrongArgCount)),
         }
     }
 
@@ -167,7 +189,11 @@ impl TypeError {
             )
         };
 
-        Self { message, span }
+        Self {
+            message,
+            span,
+            call: Some((func.to_string(), CallError::UnknownArg)),
+        }
     }
 
     fn new_invalid_filter(name: &str, span: Span, valid_filters: &Vec<&str>) -> Self {
@@ -192,7 +218,7 @@ impl TypeError {
             )
         };
 
-        Self { message: format!("{message}\n\nSee: https://docs.rs/minijinja/latest/minijinja/filters/index.html#functions for the compelete list"), span }
+        Self { message: format!("{message}\n\nSee: https://docs.rs/minijinja/latest/minijinja/filters/index.html#functions for the compelete list"), span, call: None }
     }
 
     fn new_invalid_type(expr: &Expr, got: &Type, expected: &str, span: Span) -> Self {
@@ -208,6 +234,23 @@ impl TypeError {
                 expected
             ),
             span,
+            call: None,
+        }
+    }
+
+    fn new_impossible_comparison(expr: &Expr, value: &str, options: &[&str], span: Span) -> Self {
+        let expr = pretty_print::pretty_print(expr);
+        let options = options.to_vec();
+        let message = match sort_by_match(value, &options, Some(1)).first() {
+            Some(close) => {
+                format!("'{expr}' is never \"{value}\". Did you mean \"{close}\"?")
+            }
+            None => format!("'{expr}' is never \"{value}\""),
+        };
+        Self {
+            message,
+            span,
+            call: None,
         }
     }
 
@@ -226,6 +269,7 @@ impl TypeError {
                 property
             ),
             span,
+            call: None,
         }
     }
 
@@ -240,6 +284,7 @@ impl TypeError {
                 "class {class_name} ({variable_name}) does not have a property '{property}'"
             ),
             span,
+            call: None,
         }
     }
 
@@ -247,16 +292,22 @@ impl TypeError {
         Self {
             message: format!("Class '{class}' is not defined"),
             span: Span::default(),
+            call: None,
         }
     }
 }
 
 struct ScopeTracker {
     errors: Vec<TypeError>,
+    /// Comparisons that are always true or always false.
+    impossible_comparisons: Vec<TypeError>,
 }
 
 impl ScopeTracker {
     fn new() -> Self {
-        Self { errors: Vec::new() }
+        Self {
+            errors: Vec::new(),
+            impossible_comparisons: Vec::new(),
+        }
     }
 }
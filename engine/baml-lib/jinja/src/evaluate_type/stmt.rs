@@ -2,7 +2,11 @@ use minijinja::machinery::ast::{self, Stmt};
 
 use crate::evaluate_type::types::Type;
 
-use super::{expr::evaluate_type, types::PredefinedTypes, TypeError};
+use super::{
+    expr::{evaluate_condition, evaluate_type},
+    types::PredefinedTypes,
+    TypeError,
+};
 
 fn track_walk(node: &ast::Stmt<'_>, state: &mut PredefinedTypes) {
     match node {
@@ -49,6 +53,7 @@ fn track_walk(node: &ast::Stmt<'_>, state: &mut PredefinedTypes) {
                             state.errors_mut().push(TypeError {
                                 message: format!("Expected {} items", items.len()),
                                 span: list.span(),
+                                call: None,
                             });
                             list.items.iter().for_each(|x| {
                                 if let ast::Expr::Var(var) = x {
@@ -63,6 +68,7 @@ fn track_walk(node: &ast::Stmt<'_>, state: &mut PredefinedTypes) {
                                     state.errors_mut().push(TypeError {
                                         message: "Expected variable".to_string(),
                                         span: list.span(),
+                                        call: None,
                                     });
                                 }
                             });
@@ -74,6 +80,7 @@ fn track_walk(node: &ast::Stmt<'_>, state: &mut PredefinedTypes) {
                     state.errors_mut().push(TypeError {
                         message: "Not a sequence".to_string(),
                         span: stmt.span(),
+                        call: None,
                     });
                 }
             }
@@ -90,7 +97,8 @@ fn track_walk(node: &ast::Stmt<'_>, state: &mut PredefinedTypes) {
             state.end_scope();
         }
         ast::Stmt::IfCond(stmt) => {
-            let _expr_type = evaluate_type(&stmt.expr, state);
+            let errors = evaluate_condition(&stmt.expr, state);
+            state.errors_mut().extend(errors);
 
             // Record variables in each branch and their types (fuse them if they are the same)
             state.start_branch();
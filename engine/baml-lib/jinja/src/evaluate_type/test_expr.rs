@@ -1,10 +1,12 @@
+use std::collections::HashSet;
+
 use baml_types::LiteralValue;
 use minijinja::machinery::parse_expr;
 
 use crate::evaluate_type::{
     expr::evaluate_type,
     types::{PredefinedTypes, Type},
-    JinjaContext,
+    CallError, JinjaContext,
 };
 
 macro_rules! assert_evaluates_to {
@@ -230,6 +232,60 @@ fn test_call_function() {
         assert_evaluates_to!("TakesLiteralFoo('Foo')", &types),
         Type::Float
     );
+
+    types.add_function_with_defaults(
+        "Greeting",
+        Type::String,
+        vec![("name".into(), Type::String), ("tone".into(), Type::String)],
+        HashSet::from(["tone".to_string()]),
+    );
+    assert_eq!(
+        assert_evaluates_to!("Greeting('Ada')", &types),
+        Type::String
+    );
+    assert_eq!(
+        assert_evaluates_to!("Greeting('Ada', tone='formal')", &types),
+        Type::String
+    );
+    assert_eq!(
+        assert_fails_to!("Greeting(tone='formal')", &types),
+        vec!["Function 'Greeting' expects argument 'name'"]
+    );
+}
+
+#[test]
+fn test_call_errors() {
+    let mut types = PredefinedTypes::default(JinjaContext::Prompt);
+    types.add_function(
+        "Greeting",
+        Type::String,
+        vec![("name".into(), Type::String), ("tone".into(), Type::String)],
+    );
+
+    let call_errors = |expr: &str| {
+        evaluate_type(&parse_expr(expr).unwrap(), &types)
+            .unwrap_err()
+            .iter()
+            .map(|err| {
+                err.call_error()
+                    .map(|(func, error)| (func.to_string(), error))
+            })
+            .collect::<Vec<_>>()
+    };
+    let greeting = |error| Some(("Greeting".to_string(), error));
+
+    assert_eq!(
+        call_errors("Greeting('Ada')"),
+        vec![greeting(CallError::WrongArgCount)]
+    );
+    assert_eq!(
+        call_errors("Greeting('Ada', mood='formal')"),
+        vec![
+            greeting(CallError::MissingArg),
+            greeting(CallError::UnknownArg)
+        ]
+    );
+    assert_eq!(call_errors("Greeting('Ada', 1)"), vec![None]);
 }
 
 #[test]
@@ -275,6 +331,33 @@ fn test_output_format() {
     );
 }
 
+#[test]
+fn test_client_provider() {
+    let mut types = PredefinedTypes::default(JinjaContext::Prompt);
+    assert_eq!(
+        assert_evaluates_to!("ctx.client.provider == 'antropic'", &types),
+        Type::Bool
+    );
+
+    types.set_client_providers(&["openai", "anthropic"]);
+    assert_eq!(
+        assert_evaluates_to!("ctx.client.provider == 'anthropic'", &types),
+        Type::Bool
+    );
+    assert_eq!(
+        assert_evaluates_to!("ctx.client.allowed_roles", &types),
+        Type::List(Box::new(Type::String))
+    );
+    assert_eq!(
+        assert_fails_to!("ctx.client.provider == 'antropic'", &types),
+        vec!["'ctx.client.provider' is never \"antropic\". Did you mean \"anthropic\"?"]
+    );
+    assert_eq!(
+        assert_fails_to!("'antropic' != ctx.client.provider", &types),
+        vec!["'ctx.client.provider' is never \"antropic\". Did you mean \"anthropic\"?"]
+    );
+}
+
 #[test]
 fn sum_filter() {
     let types = PredefinedTypes::default(JinjaContext::Prompt);
@@ -269,3 +269,21 @@ fn if_else() {
         types
     );
 }
+
+#[test]
+fn if_client_provider() {
+    let mut types = PredefinedTypes::default(JinjaContext::Prompt);
+    types.set_client_providers(&["openai", "anthropic"]);
+    assert_fails_to!(
+        r#"
+{% if maybe_undefined is defined and ctx.client.provider == 'anthropic' -%}
+  <Message>{{ ctx.client.name }}</Message>
+{%- elif ctx.client.provider == 'opneai' -%}
+  {{ ctx.client.default_role }}
+{%- endif %}
+        "#
+        .trim(),
+        types,
+        vec![r#"'ctx.client.provider' is never "opneai". Did you mean "openai"?"#]
+    );
+}
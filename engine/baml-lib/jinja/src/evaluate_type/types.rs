@@ -218,6 +218,8 @@ enum Scope {
 #[derive(Debug)]
 pub struct PredefinedTypes {
     functions: HashMap<String, (Type, Vec<(String, Type)>)>,
+    /// Arguments of `functions` that have a default value.
+    function_defaults: HashMap<String, HashSet<String>>,
     classes: HashMap<String, HashMap<String, Type>>,
     // Variable name <--> Definition
     variables: HashMap<String, Type>,
@@ -282,12 +284,15 @@ impl PredefinedTypes {
                     ),
                 ),
             ]),
+            function_defaults: HashMap::new(),
             classes: HashMap::from([
                 (
                     "baml::Client".into(),
                     HashMap::from([
                         ("name".into(), Type::String),
                         ("provider".into(), Type::String),
+                        ("default_role".into(), Type::String),
+                        ("allowed_roles".into(), Type::List(Box::new(Type::String))),
                     ]),
                 ),
                 (
@@ -443,6 +448,34 @@ impl PredefinedTypes {
 
     pub fn add_function(&mut self, name: &str, ret: Type, args: Vec<(String, Type)>) {
         self.functions.insert(name.to_string(), (ret, args));
+        self.function_defaults.remove(name);
+    }
+
+    /// Like [`Self::add_function`], but the `defaults` arguments have a
+    /// default value, so calls may leave them out.
+    pub fn add_function_with_defaults(
+        &mut self,
+        name: &str,
+        ret: Type,
+        args: Vec<(String, Type)>,
+        defaults: HashSet<String>,
+    ) {
+        self.add_function(name, ret, args);
+        self.function_defaults.insert(name.to_string(), defaults);
+    }
+
+    /// Types `ctx.client.provider` as one of `providers`, so comparing it
+    /// against any other string is reported.
+    pub fn set_client_providers(&mut self, providers: &[&str]) {
+        let provider = Type::Union(
+            providers
+                .iter()
+                .map(|p| Type::Literal(LiteralValue::String(p.to_string())))
+                .collect(),
+        );
+        if let Some(client) = self.classes.get_mut("baml::Client") {
+            client.insert("provider".into(), provider);
+        }
     }
 
     pub fn add_class(&mut self, name: &str, fields: HashMap<String, Type>) {
@@ -512,12 +545,13 @@ impl PredefinedTypes {
             );
         }
         let (ret, args) = val.unwrap();
+        let defaults = self.function_defaults.get(func);
         let mut errors = Vec::new();
 
         // Check how many args are required.
         let mut optional_args = vec![];
         for (name, t) in args.iter().rev() {
-            if !t.is_optional() {
+            if !t.is_optional() && !defaults.is_some_and(|defaults| defaults.contains(name)) {
                 break;
             }
             optional_args.push(name);
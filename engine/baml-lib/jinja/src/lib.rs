@@ -1,7 +1,7 @@
 mod evaluate_type;
 
 use evaluate_type::get_variable_types;
-pub use evaluate_type::{JinjaContext, PredefinedTypes, Type, TypeError};
+pub use evaluate_type::{CallError, JinjaContext, PredefinedTypes, Type, TypeError};
 
 #[derive(Debug)]
 pub struct ValidationError {
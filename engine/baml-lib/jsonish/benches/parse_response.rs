@@ -0,0 +1,98 @@
+//! Benchmarks for parsing and coercing LLM output with jsonish.
+//!
+//! Run with `cargo bench -p jsonish`.
+
+use baml_types::FieldType;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use internal_baml_jinja::types::{Class, Enum, Name, OutputFormatContent};
+
+fn output_format() -> OutputFormatContent {
+    let status = Enum {
+        name: Name::new("Status".to_string()),
+        values: vec![
+            (Name::new("ACTIVE".to_string()), None),
+            (Name::new("INACTIVE".to_string()), None),
+        ],
+        constraints: vec![],
+        matching: Default::default(),
+    };
+    let item = Class {
+        name: Name::new("Item".to_string()),
+        fields: vec![
+            (Name::new("id".to_string()), FieldType::int(), None),
+            (Name::new("name".to_string()), FieldType::string(), None),
+            (
+                Name::new("tags".to_string()),
+                FieldType::List(Box::new(FieldType::string())),
+                None,
+            ),
+            (
+                Name::new("status".to_string()),
+                FieldType::Enum("Status".to_string()),
+                None,
+            ),
+            (
+                Name::new("score".to_string()),
+                FieldType::optional(FieldType::float()),
+                None,
+            ),
+        ],
+        constraints: vec![],
+    };
+    OutputFormatContent::target(target())
+        .enums(vec![status])
+        .classes(vec![item])
+        .build()
+}
+
+fn target() -> FieldType {
+    FieldType::List(Box::new(FieldType::Class("Item".to_string())))
+}
+
+fn items_json(n: usize) -> String {
+    let items = (0..n)
+        .map(|i| {
+            format!(
+                r#"{{"id": {i}, "name": "item number {i}", "tags": ["a", "b", "c"], "status": "ACTIVE", "score": {i}.5}}"#
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", items.join(",\n"))
+}
+
+/// Representative model outputs of increasing messiness.
+fn fixtures(n: usize) -> Vec<(&'static str, String)> {
+    let json = items_json(n);
+    vec![
+        ("valid_json", json.clone()),
+        (
+            "markdown",
+            format!("Here are the items you asked for:\n\n```json\n{json}\n```\n\nLet me know!"),
+        ),
+        (
+            "unquoted_keys_trailing_commas",
+            json.replace("\"id\"", "id")
+                .replace("\"status\": \"ACTIVE\"", "status: ACTIVE,"),
+        ),
+        // Truncated mid-stream, as seen by partial parsing.
+        ("truncated", json[..json.len() * 2 / 3].to_string()),
+    ]
+}
+
+fn bench_from_str(c: &mut Criterion) {
+    let of = output_format();
+    let target = target();
+    for n in [10, 100] {
+        let mut group = c.benchmark_group(format!("from_str/{n}_items"));
+        for (name, raw) in fixtures(n) {
+            let allow_partials = name == "truncated";
+            group.bench_with_input(BenchmarkId::from_parameter(name), &raw, |b, raw| {
+                b.iter(|| black_box(jsonish::from_str(&of, &target, raw, allow_partials)))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_from_str);
+criterion_main!(benches);
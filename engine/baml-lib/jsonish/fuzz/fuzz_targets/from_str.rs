@@ -0,0 +1,14 @@
+//! Feed arbitrary text to `jsonish::from_str` against a generated schema.
+//!
+//! Run with `cargo +nightly fuzz run from_str` from `baml-lib/jsonish`.
+
+#![no_main]
+
+use jsonish_fuzz::Schema;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Schema, bool, &str)| {
+    let (schema, allow_partials, raw) = input;
+    let (of, target) = schema.build();
+    let _ = jsonish::from_str(&of, &target, raw, allow_partials);
+});
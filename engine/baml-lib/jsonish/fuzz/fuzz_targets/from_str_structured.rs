@@ -0,0 +1,28 @@
+//! Feed valid JSON, damaged by a sequence of LLM-style mutations, to
+//! `jsonish::from_str` against a generated schema.
+//!
+//! Run with `cargo +nightly fuzz run from_str_structured` from
+//! `baml-lib/jsonish`.
+
+#![no_main]
+
+use jsonish_fuzz::{Document, Mutation, Schema};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Schema, Document, Vec<Mutation>, bool)| {
+    let (schema, document, mutations, pretty) = input;
+    let (of, target) = schema.build();
+
+    let json = document.to_json();
+    let raw = if pretty {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
+    }
+    .expect("serializing a serde_json::Value cannot fail");
+
+    // Bounded so that repeated `Repeat`s can't blow up the input size.
+    let raw = mutations.iter().take(8).fold(raw, |raw, m| m.apply(raw));
+    let _ = jsonish::from_str(&of, &target, &raw, true);
+    let _ = jsonish::from_str(&of, &target, &raw, false);
+});
@@ -0,0 +1,228 @@
+//! Input generators shared by the jsonish fuzz targets.
+//!
+//! `Schema` produces an `OutputFormatContent` and target `FieldType` the same
+//! shape the IR would hand to `jsonish::from_str`, and `Document` produces a
+//! valid JSON value that is then mangled the way LLMs tend to mangle JSON.
+
+use arbitrary::Arbitrary;
+use baml_types::{FieldType, LiteralValue, TypeValue};
+use internal_baml_jinja::types::{Class, Enum, Name, OutputFormatContent};
+
+const MAX_ENUMS: usize = 4;
+const MAX_CLASSES: usize = 4;
+const MAX_MEMBERS: usize = 8;
+
+#[derive(Debug, Arbitrary)]
+pub enum TypeDef {
+    String,
+    Int,
+    Float,
+    Bool,
+    Null,
+    LiteralString(String),
+    LiteralInt(i64),
+    LiteralBool(bool),
+    Enum(u8),
+    Class(u8),
+    List(Box<TypeDef>),
+    Map(Box<TypeDef>),
+    Union(Vec<TypeDef>),
+    Optional(Box<TypeDef>),
+}
+
+impl TypeDef {
+    /// Lower to a `FieldType`. Class references are restricted to the first
+    /// `classes` classes so that the generated schema is never recursive.
+    fn to_field_type(&self, enums: usize, classes: usize) -> FieldType {
+        match self {
+            TypeDef::String => FieldType::string(),
+            TypeDef::Int => FieldType::int(),
+            TypeDef::Float => FieldType::float(),
+            TypeDef::Bool => FieldType::bool(),
+            TypeDef::Null => FieldType::Primitive(TypeValue::Null),
+            TypeDef::LiteralString(s) => FieldType::Literal(LiteralValue::String(s.clone())),
+            TypeDef::LiteralInt(i) => FieldType::Literal(LiteralValue::Int(*i)),
+            TypeDef::LiteralBool(b) => FieldType::Literal(LiteralValue::Bool(*b)),
+            TypeDef::Enum(i) if enums > 0 => FieldType::Enum(enum_name(*i as usize % enums)),
+            TypeDef::Class(i) if classes > 0 => FieldType::Class(class_name(*i as usize % classes)),
+            TypeDef::Enum(_) | TypeDef::Class(_) => FieldType::string(),
+            TypeDef::List(inner) => FieldType::List(Box::new(inner.to_field_type(enums, classes))),
+            TypeDef::Map(value) => FieldType::Map(
+                Box::new(FieldType::string()),
+                Box::new(value.to_field_type(enums, classes)),
+            ),
+            TypeDef::Union(options) if !options.is_empty() => FieldType::Union(
+                options
+                    .iter()
+                    .take(MAX_MEMBERS)
+                    .map(|t| t.to_field_type(enums, classes))
+                    .collect(),
+            ),
+            TypeDef::Union(_) => FieldType::string(),
+            TypeDef::Optional(inner) => FieldType::optional(inner.to_field_type(enums, classes)),
+        }
+    }
+}
+
+#[derive(Debug, Arbitrary)]
+pub struct EnumDef {
+    values: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, Arbitrary)]
+pub struct ClassDef {
+    fields: Vec<(String, Option<String>, TypeDef)>,
+}
+
+#[derive(Debug, Arbitrary)]
+pub struct Schema {
+    enums: Vec<EnumDef>,
+    classes: Vec<ClassDef>,
+    target: TypeDef,
+}
+
+impl Schema {
+    pub fn build(&self) -> (OutputFormatContent, FieldType) {
+        let enums = self
+            .enums
+            .iter()
+            .take(MAX_ENUMS)
+            .enumerate()
+            .map(|(i, e)| Enum {
+                name: Name::new(enum_name(i)),
+                values: e
+                    .values
+                    .iter()
+                    .take(MAX_MEMBERS)
+                    .map(|(name, alias)| (Name::new_with_alias(name.clone(), alias.clone()), None))
+                    .collect(),
+                constraints: vec![],
+                matching: Default::default(),
+            })
+            .collect::<Vec<_>>();
+        let enum_count = enums.len();
+
+        let classes = self
+            .classes
+            .iter()
+            .take(MAX_CLASSES)
+            .enumerate()
+            .map(|(i, c)| Class {
+                name: Name::new(class_name(i)),
+                fields: c
+                    .fields
+                    .iter()
+                    .take(MAX_MEMBERS)
+                    .map(|(name, alias, t)| {
+                        (
+                            Name::new_with_alias(name.clone(), alias.clone()),
+                            t.to_field_type(enum_count, i),
+                            None,
+                        )
+                    })
+                    .collect(),
+                constraints: vec![],
+            })
+            .collect::<Vec<_>>();
+        let class_count = classes.len();
+
+        let target = self.target.to_field_type(enum_count, class_count);
+        let of = OutputFormatContent::target(target.clone())
+            .enums(enums)
+            .classes(classes)
+            .build();
+        (of, target)
+    }
+}
+
+fn enum_name(i: usize) -> String {
+    format!("Enum{i}")
+}
+
+fn class_name(i: usize) -> String {
+    format!("Class{i}")
+}
+
+/// A well-formed JSON document.
+#[derive(Debug, Arbitrary)]
+pub enum Document {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Document>),
+    Object(Vec<(String, Document)>),
+}
+
+impl Document {
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Document::Null => serde_json::Value::Null,
+            Document::Bool(b) => (*b).into(),
+            Document::Int(i) => (*i).into(),
+            Document::Float(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Document::String(s) => s.as_str().into(),
+            Document::Array(items) => items.iter().map(Document::to_json).collect(),
+            Document::Object(fields) => fields
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_json()))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+}
+
+/// Damage commonly seen in model output.
+#[derive(Debug, Arbitrary)]
+pub enum Mutation {
+    /// Cut the response short, as a partial stream would.
+    Truncate(u16),
+    /// Drop every double quote.
+    StripQuotes,
+    /// Swap double quotes for single quotes.
+    SingleQuotes,
+    /// Add a trailing comma before every closing bracket.
+    TrailingCommas,
+    /// Wrap the response in a fenced markdown block.
+    Markdown(String),
+    /// Surround the response with prose.
+    Prose(String, String),
+    /// Insert a comment at the given offset.
+    Comment(u16, bool),
+    /// Emit the document twice.
+    Repeat,
+}
+
+impl Mutation {
+    pub fn apply(&self, raw: String) -> String {
+        match self {
+            Mutation::Truncate(at) => {
+                let at = char_boundary(&raw, *at as usize);
+                raw[..at].to_string()
+            }
+            Mutation::StripQuotes => raw.replace('"', ""),
+            Mutation::SingleQuotes => raw.replace('"', "'"),
+            Mutation::TrailingCommas => raw.replace('}', ",}").replace(']', ",]"),
+            Mutation::Markdown(tag) => format!("```{tag}\n{raw}\n```"),
+            Mutation::Prose(before, after) => format!("{before}\n{raw}\n{after}"),
+            Mutation::Comment(at, block) => {
+                let at = char_boundary(&raw, *at as usize);
+                let comment = if *block { "/* note */" } else { "// note\n" };
+                format!("{}{comment}{}", &raw[..at], &raw[at..])
+            }
+            Mutation::Repeat => format!("{raw}\n{raw}"),
+        }
+    }
+}
+
+/// The largest char boundary in `s` that is `<= at % (s.len() + 1)`.
+fn char_boundary(s: &str, at: usize) -> usize {
+    let mut at = at % (s.len() + 1);
+    while !s.is_char_boundary(at) {
+        at -= 1;
+    }
+    at
+}
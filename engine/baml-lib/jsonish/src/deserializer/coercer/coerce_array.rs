@@ -6,7 +6,9 @@ use crate::deserializer::{
     types::BamlValueWithFlags,
 };
 
-use super::{ParsingContext, ParsingError, TypeCoercer};
+use super::{
+    coerce_markdown_table::coerce_markdown_table, ParsingContext, ParsingError, TypeCoercer,
+};
 
 pub(super) fn coerce_array(
     ctx: &ParsingContext,
@@ -27,6 +29,12 @@ pub(super) fn coerce_array(
         _ => unreachable!(),
     };
 
+    if let Some(crate::jsonish::Value::String(s)) = value {
+        if let Some(table) = coerce_markdown_table(ctx, list_target, s) {
+            return table;
+        }
+    }
+
     let mut items = vec![];
     let mut flags = DeserializerConditions::new();
 
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use internal_baml_core::ir::FieldType;
+use internal_baml_jinja::types::Class;
+
+use crate::deserializer::{
+    deserialize_flags::{DeserializerConditions, Flag},
+    types::BamlValueWithFlags,
+};
+
+use super::{ParsingContext, ParsingError, TypeCoercer};
+
+/// Reads a list of classes off the first markdown table in `raw`, one item
+/// per row, with columns matched to fields by their (aliased) names.
+///
+/// Returns `None` if `list_target` isn't a list of classes or `raw` has no
+/// table.
+pub(super) fn coerce_markdown_table(
+    ctx: &ParsingContext,
+    list_target: &FieldType,
+    raw: &str,
+) -> Option<Result<BamlValueWithFlags, ParsingError>> {
+    let FieldType::List(inner) = list_target else {
+        return None;
+    };
+    let FieldType::Class(name) = inner.as_ref() else {
+        return None;
+    };
+    let (header, rows) = parse_table(raw)?;
+
+    log::debug!(
+        "scope: {scope} :: coercing to: {name} (current: markdown table)",
+        name = list_target.to_string(),
+        scope = ctx.display_scope(),
+    );
+
+    let class = match ctx.of.find_class(name) {
+        Ok(class) => class,
+        Err(e) => return Some(Err(ctx.error_internal(e.to_string()))),
+    };
+    let columns = header
+        .iter()
+        .map(|column| match find_field(class, column) {
+            Some(field) => (field.to_string(), field != column.as_str()),
+            None => (column.to_string(), false),
+        })
+        .collect::<Vec<_>>();
+
+    let mut items = vec![];
+    let mut flags = DeserializerConditions::new();
+    flags.add_flag(Flag::ListFromMarkdownTable(header.clone()));
+    for (i, row) in rows.into_iter().enumerate() {
+        let object = crate::jsonish::Value::Object(
+            columns
+                .iter()
+                .zip(row)
+                .map(|((key, _), cell)| {
+                    let value = match cell.is_empty() {
+                        true => crate::jsonish::Value::Null,
+                        false => crate::jsonish::Value::String(cell),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        );
+        match inner.coerce(&ctx.enter_scope(&format!("{i}")), inner, Some(&object)) {
+            Ok(mut v) => {
+                for ((field, renamed), column) in columns.iter().zip(&header) {
+                    if *renamed {
+                        v.add_flag(Flag::ColumnToField(column.clone(), field.clone()));
+                    }
+                }
+                items.push(v);
+            }
+            Err(e) => flags.add_flag(Flag::ArrayItemParseError(i, e)),
+        }
+    }
+
+    Some(Ok(BamlValueWithFlags::List(flags, items)))
+}
+
+/// The rendered name of the field `column` is for. Headers are often written
+/// in title case or with spaces, e.g. `First Name` for `first_name`.
+fn find_field<'a>(class: &'a Class, column: &str) -> Option<&'a str> {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    let column = normalize(column);
+    class
+        .fields
+        .iter()
+        .find(|(name, ..)| {
+            normalize(name.rendered_name()) == column || normalize(name.real_name()) == column
+        })
+        .map(|(name, ..)| name.rendered_name().trim())
+}
+
+/// The header and rows of the first table in `raw`, i.e. the first line with
+/// pipes that's followed by a delimiter row like `|---|:---:|`, and the lines
+/// with pipes right after it.
+fn parse_table(raw: &str) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    let lines = raw.lines().map(str::trim).collect::<Vec<_>>();
+    let start = lines.windows(2).position(|pair| {
+        pair[0].contains('|')
+            && is_delimiter_row(pair[1])
+            && split_row(pair[0]).len() == split_row(pair[1]).len()
+    })?;
+    let header = split_row(lines[start]);
+    let rows = lines[start + 2..]
+        .iter()
+        .take_while(|line| line.contains('|'))
+        .map(|line| split_row(line))
+        .collect();
+    Some((header, rows))
+}
+
+fn is_delimiter_row(line: &str) -> bool {
+    line.contains('-')
+        && split_row(line).iter().all(|cell| {
+            let cell = cell.strip_prefix(':').unwrap_or(cell);
+            let cell = cell.strip_suffix(':').unwrap_or(cell);
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+/// The trimmed cells of a row. Leading and trailing pipes are optional, and
+/// `\|` is a pipe within a cell.
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+    let mut cells = vec![];
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => cell.push(chars.next().unwrap_or('|')),
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table() {
+        let (header, rows) = parse_table(
+            "Here you go:\n\n| Name | Age |\n|:-----|----:|\n| Jo | 42 |\n| A \\| B |  |\n\nDone.",
+        )
+        .unwrap();
+        assert_eq!(header, vec!["Name", "Age"]);
+        assert_eq!(rows, vec![vec!["Jo", "42"], vec!["A | B", ""]]);
+
+        let (header, rows) = parse_table("a | b\n--- | ---\n1 | 2").unwrap();
+        assert_eq!(header, vec!["a", "b"]);
+        assert_eq!(rows, vec![vec!["1", "2"]]);
+
+        assert!(parse_table("| a | b |\n| 1 | 2 |").is_none());
+        assert!(parse_table("a - b\n---").is_none());
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use baml_types::BamlMediaType;
+use baml_types::{BamlMediaType, NumberLocale};
 use internal_baml_core::ir::{FieldType, TypeValue};
 
 use crate::deserializer::{
@@ -103,11 +103,12 @@ pub(super) fn coerce_int(
                 let s = s.trim();
                 // Trim trailing commas
                 let s = s.trim_end_matches(',');
-                if let Ok(n) = s.parse::<i64>() {
+                let locale = ctx.of.number_locale;
+                if let Some(n) = parse_plain::<i64>(s, locale) {
                     Ok(BamlValueWithFlags::Int(n.into()))
-                } else if let Ok(n) = s.parse::<u64>() {
+                } else if let Some(n) = parse_plain::<u64>(s, locale) {
                     Ok(BamlValueWithFlags::Int((n as i64).into()))
-                } else if let Ok(n) = s.parse::<f64>() {
+                } else if let Some(n) = parse_plain::<f64>(s, locale) {
                     Ok(BamlValueWithFlags::Int(
                         ((n.round() as i64), Flag::FloatToInt(n)).into(),
                     ))
@@ -115,9 +116,12 @@ pub(super) fn coerce_int(
                     Ok(BamlValueWithFlags::Int(
                         ((frac.round() as i64), Flag::FloatToInt(frac)).into(),
                     ))
-                } else if let Some(frac) = float_from_comma_separated(s) {
+                } else if let Some((frac, unit)) = float_from_comma_separated(s, locale) {
+                    let flags = std::iter::once(Flag::FloatToInt(frac))
+                        .chain(unit.map(Flag::StrippedUnit))
+                        .collect::<Vec<_>>();
                     Ok(BamlValueWithFlags::Int(
-                        ((frac.round() as i64), Flag::FloatToInt(frac)).into(),
+                        ((frac.round() as i64), flags.as_slice()).into(),
                     ))
                 } else {
                     Err(ctx.error_unexpected_type(target, value))
@@ -149,22 +153,68 @@ fn float_from_maybe_fraction(value: &str) -> Option<f64> {
     }
 }
 
-fn float_from_comma_separated(value: &str) -> Option<f64> {
-    let re = Regex::new(r"([-+]?)\$?(?:\d+(?:,\d+)*(?:\.\d+)?|\d+\.\d+|\d+|\.\d+)(?:e[-+]?\d+)?")
-        .unwrap();
-    let matches: Vec<_> = re.find_iter(value).collect();
+/// Parses `value` as Rust does, unless decimal commas make that wrong: then
+/// `1.234` is a thousand.
+fn parse_plain<T: std::str::FromStr>(value: &str, locale: NumberLocale) -> Option<T> {
+    match locale {
+        NumberLocale::DecimalComma if value.contains('.') => None,
+        _ => value.parse().ok(),
+    }
+}
 
-    if matches.len() != 1 {
-        return None;
+/// Which separators `value` most likely uses. With both, the last one marks
+/// decimals. A lone comma marks decimals unless three digits follow it, and
+/// a lone point always does.
+fn guess_locale(value: &str) -> NumberLocale {
+    match (value.rfind(','), value.rfind('.')) {
+        (Some(comma), Some(point)) if comma > point => NumberLocale::DecimalComma,
+        (Some(_), Some(_)) => NumberLocale::DecimalPoint,
+        (Some(comma), None) => {
+            let digits_after = value[comma + 1..]
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            if value.matches(',').count() == 1 && digits_after != 3 {
+                NumberLocale::DecimalComma
+            } else {
+                NumberLocale::DecimalPoint
+            }
+        }
+        (None, Some(_)) if value.matches('.').count() > 1 => NumberLocale::DecimalComma,
+        _ => NumberLocale::DecimalPoint,
     }
+}
+
+/// Reads the one number in `value`, with its thousands separators and a
+/// currency symbol or percent sign around it, like `$1,234.56` or `12,5 %`.
+/// Returns the symbol too, if there was one.
+fn float_from_comma_separated(value: &str, locale: NumberLocale) -> Option<(f64, Option<String>)> {
+    let (thousands, decimal) = match locale {
+        NumberLocale::Auto => return float_from_comma_separated(value, guess_locale(value)),
+        NumberLocale::DecimalPoint => (',', '.'),
+        NumberLocale::DecimalComma => ('.', ','),
+    };
+    let (t, d) = (
+        regex::escape(&thousands.to_string()),
+        regex::escape(&decimal.to_string()),
+    );
+    let re = Regex::new(&format!(
+        r"([-+]?)(\p{{Sc}})?\s?((?:\d+(?:{t}\d+)*(?:{d}\d+)?|\d+{d}\d+|\d+|{d}\d+)(?:e[-+]?\d+)?)(\s?[%\p{{Sc}}])?"
+    ))
+    .unwrap();
+    let matches: Vec<_> = re.captures_iter(value).collect();
 
-    let number_str = matches[0].as_str();
-    let without_commas = number_str.replace(",", "");
-    // Remove all Unicode currency symbols
-    let re_currency = Regex::new(r"\p{Sc}").unwrap();
-    let without_currency = re_currency.replace_all(&without_commas, "");
+    let [captures] = matches.as_slice() else {
+        return None;
+    };
 
-    without_currency.parse::<f64>().ok()
+    let number = captures[3].replace(thousands, "").replace(decimal, ".");
+    let number = format!("{}{}", &captures[1], number).parse::<f64>().ok()?;
+    let unit = captures
+        .get(2)
+        .or(captures.get(4))
+        .map(|unit| unit.as_str().trim().to_string());
+    Some((number, unit))
 }
 
 fn coerce_float(
@@ -189,16 +239,18 @@ fn coerce_float(
                 let s = s.trim();
                 // Trim trailing commas
                 let s = s.trim_end_matches(',');
-                if let Ok(n) = s.parse::<f64>() {
+                let locale = ctx.of.number_locale;
+                if let Some(n) = parse_plain::<f64>(s, locale) {
                     Ok(BamlValueWithFlags::Float(n.into()))
-                } else if let Ok(n) = s.parse::<i64>() {
+                } else if let Some(n) = parse_plain::<i64>(s, locale) {
                     Ok(BamlValueWithFlags::Float((n as f64).into()))
-                } else if let Ok(n) = s.parse::<u64>() {
+                } else if let Some(n) = parse_plain::<u64>(s, locale) {
                     Ok(BamlValueWithFlags::Float((n as f64).into()))
                 } else if let Some(frac) = float_from_maybe_fraction(s) {
                     Ok(BamlValueWithFlags::Float(frac.into()))
-                } else if let Some(frac) = float_from_comma_separated(s) {
-                    Ok(BamlValueWithFlags::Float(frac.into()))
+                } else if let Some((frac, unit)) = float_from_comma_separated(s, locale) {
+                    let flags = unit.map(Flag::StrippedUnit).into_iter().collect::<Vec<_>>();
+                    Ok(BamlValueWithFlags::Float((frac, flags.as_slice()).into()))
                 } else {
                     Err(ctx.error_unexpected_type(target, value))
                 }
@@ -231,6 +283,11 @@ pub(super) fn coerce_bool(
                     (false, Flag::StringToBool(s.clone())).into(),
                 )),
                 _ => {
+                    if let Some(b) = ctx.bool_strings.parse(s) {
+                        return Ok(BamlValueWithFlags::Bool(
+                            (b, Flag::WordToBool(s.clone())).into(),
+                        ));
+                    }
                     match super::match_string::match_string(
                         ctx,
                         target,
@@ -256,6 +313,12 @@ pub(super) fn coerce_bool(
                     }
                 }
             },
+            crate::jsonish::Value::Number(n) => match ctx.bool_strings.parse(&n.to_string()) {
+                Some(b) => Ok(BamlValueWithFlags::Bool(
+                    (b, Flag::WordToBool(n.to_string())).into(),
+                )),
+                None => Err(ctx.error_unexpected_type(target, value)),
+            },
             crate::jsonish::Value::Array(items) => {
                 coerce_array_to_singular(ctx, target, &items.iter().collect::<Vec<_>>(), &|value| {
                     coerce_bool(ctx, target, Some(value))
@@ -338,7 +401,8 @@ mod tests {
         ];
 
         for &(input, expected) in &test_cases {
-            let result = float_from_comma_separated(input);
+            let result = float_from_comma_separated(input, NumberLocale::DecimalPoint)
+                .map(|(number, _)| number);
             assert_eq!(
                 result, expected,
                 "Failed to parse '{}'. Expected {:?}, got {:?}",
@@ -346,4 +410,57 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_float_from_comma_separated_locales() {
+        let test_cases = vec![
+            (
+                "1.234,56",
+                NumberLocale::DecimalComma,
+                Some((1234.56, None)),
+            ),
+            ("1.234", NumberLocale::DecimalComma, Some((1234.0, None))),
+            (
+                "€1.234.567,89",
+                NumberLocale::DecimalComma,
+                Some((1234567.89, Some("€"))),
+            ),
+            (
+                "-1.234,56 €",
+                NumberLocale::DecimalComma,
+                Some((-1234.56, Some("€"))),
+            ),
+            (
+                "12,5 %",
+                NumberLocale::DecimalComma,
+                Some((12.5, Some("%"))),
+            ),
+            ("1,234.56", NumberLocale::DecimalComma, None),
+            (
+                "$1,234.56",
+                NumberLocale::DecimalPoint,
+                Some((1234.56, Some("$"))),
+            ),
+            ("1.234,56", NumberLocale::Auto, Some((1234.56, None))),
+            ("1,234.56", NumberLocale::Auto, Some((1234.56, None))),
+            ("1.234.567", NumberLocale::Auto, Some((1234567.0, None))),
+            ("1,234", NumberLocale::Auto, Some((1234.0, None))),
+            ("3,14", NumberLocale::Auto, Some((3.14, None))),
+            (
+                "The total is €1.234,56 today",
+                NumberLocale::Auto,
+                Some((1234.56, Some("€"))),
+            ),
+        ];
+
+        for (input, locale, expected) in test_cases {
+            let result = float_from_comma_separated(input, locale);
+            let expected = expected.map(|(number, unit)| (number, unit.map(String::from)));
+            assert_eq!(
+                result, expected,
+                "Failed to parse '{}' with {:?}. Expected {:?}, got {:?}",
+                input, locale, expected, result
+            );
+        }
+    }
 }
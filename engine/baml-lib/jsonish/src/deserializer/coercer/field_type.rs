@@ -10,8 +10,8 @@ use crate::deserializer::{
 
 use super::{
     array_helper, coerce_array::coerce_array, coerce_map::coerce_map,
-    coerce_optional::coerce_optional, coerce_union::coerce_union, ir_ref::IrRef, ParsingContext,
-    ParsingError,
+    coerce_markdown_table::coerce_markdown_table, coerce_optional::coerce_optional,
+    coerce_union::coerce_union, ir_ref::IrRef, ParsingContext, ParsingError,
 };
 
 impl TypeCoercer for FieldType {
@@ -35,6 +35,15 @@ impl TypeCoercer for FieldType {
                         target,
                         Some(&crate::jsonish::Value::String(primitive.clone())),
                     )
+                } else if let Some(table) = coerce_markdown_table(ctx, self, primitive) {
+                    // A table isn't JSON, so none of the candidates are read
+                    // from it.
+                    let parsed = candidates
+                        .iter()
+                        .map(|val| self.coerce(ctx, target, Some(val)))
+                        .chain(std::iter::once(table))
+                        .collect::<Vec<_>>();
+                    array_helper::pick_best(ctx, target, &parsed)
                 } else {
                     array_helper::coerce_array_to_singular(
                         ctx,
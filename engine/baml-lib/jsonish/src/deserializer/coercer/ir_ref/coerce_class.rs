@@ -1,5 +1,5 @@
 use anyhow::Result;
-use baml_types::{BamlMap, Constraint};
+use baml_types::{BamlMap, Constraint, StreamState};
 use internal_baml_core::ir::FieldType;
 use internal_baml_jinja::types::{Class, Name};
 
@@ -74,6 +74,9 @@ impl TypeCoercer for Class {
         let mut flags = DeserializerConditions::new();
 
         let mut completed_cls = Vec::new();
+        // The fields the value had a key for, in order. Partial results use
+        // them to tell how far along each `@stream.with_state` field is.
+        let mut seen_fields = Vec::new();
 
         // There are a few possible approaches here:
         match value {
@@ -90,9 +93,14 @@ impl TypeCoercer for Class {
                         .iter()
                         .find(|(name, ..)| name.rendered_name().trim() == key)
                     {
-                        let scope = ctx.enter_scope(field.0.real_name());
+                        let scope = ctx.enter_field_scope(
+                            field.0.real_name(),
+                            self.name.real_name(),
+                            field.0.real_name(),
+                        );
                         let parsed = field.1.coerce(&scope, &field.1, Some(v));
                         update_map(&mut required_values, &mut optional_values, field, parsed);
+                        seen_fields.push(field.0.real_name());
                         found_keys = true;
                     } else {
                         extra_keys.push((key, v));
@@ -102,7 +110,11 @@ impl TypeCoercer for Class {
                 if !found_keys && !extra_keys.is_empty() && self.fields.len() == 1 {
                     // Try to coerce the object into the single field
                     let field = &self.fields[0];
-                    let scope = ctx.enter_scope(&format!("<implied:{}>", field.0.real_name()));
+                    let scope = ctx.enter_field_scope(
+                        &format!("<implied:{}>", field.0.real_name()),
+                        self.name.real_name(),
+                        field.0.real_name(),
+                    );
                     let parsed = field
                         .1
                         .coerce(
@@ -116,6 +128,7 @@ impl TypeCoercer for Class {
                         });
 
                     if let Ok(parsed_value) = parsed {
+                        seen_fields.push(field.0.real_name());
                         update_map(
                             &mut required_values,
                             &mut optional_values,
@@ -136,7 +149,11 @@ impl TypeCoercer for Class {
             Some(crate::jsonish::Value::Array(items)) => {
                 if self.fields.len() == 1 {
                     let field = &self.fields[0];
-                    let scope = ctx.enter_scope(&format!("<implied:{}>", field.0.real_name()));
+                    let scope = ctx.enter_field_scope(
+                        &format!("<implied:{}>", field.0.real_name()),
+                        self.name.real_name(),
+                        field.0.real_name(),
+                    );
                     let parsed = match field.1.coerce(&scope, &field.1, value) {
                         Ok(mut v) => {
                             v.add_flag(Flag::ImpliedKey(field.0.real_name().into()));
@@ -145,6 +162,7 @@ impl TypeCoercer for Class {
                         Err(e) => Err(e),
                     };
                     update_map(&mut required_values, &mut optional_values, field, parsed);
+                    seen_fields.push(field.0.real_name());
                 }
 
                 // Coerce the each item into the class if possible
@@ -163,7 +181,11 @@ impl TypeCoercer for Class {
                 // If the class has a single field, then we can try to coerce it directly
                 if self.fields.len() == 1 {
                     let field = &self.fields[0];
-                    let scope = ctx.enter_scope(&format!("<implied:{}>", field.0.real_name()));
+                    let scope = ctx.enter_field_scope(
+                        &format!("<implied:{}>", field.0.real_name()),
+                        self.name.real_name(),
+                        field.0.real_name(),
+                    );
                     let parsed = match field.1.coerce(&scope, &field.1, Some(x)) {
                         Ok(mut v) => {
                             v.add_flag(Flag::ImpliedKey(field.0.real_name().into()));
@@ -173,6 +195,7 @@ impl TypeCoercer for Class {
                         Err(e) => Err(e),
                     };
                     update_map(&mut required_values, &mut optional_values, field, parsed);
+                    seen_fields.push(field.0.real_name());
                 }
             }
         }
@@ -205,10 +228,17 @@ impl TypeCoercer for Class {
                         }
                     }
                 } else if let Some(v) = required_values.get(field_name.real_name()) {
+                    // Fields with `@stream.not_null` hold back the class
+                    // instead of showing up as null.
+                    let null_while_streaming = ctx.allow_partials
+                        && !ctx
+                            .of
+                            .streaming(self.name.real_name(), field_name.real_name())
+                            .not_null;
                     let next = match v {
                         Some(Ok(_)) => None,
                         Some(Err(e)) => t.default_value(Some(e)).or_else(|| {
-                            if ctx.allow_partials {
+                            if null_while_streaming {
                                 Some(BamlValueWithFlags::Null(
                                     DeserializerConditions::new()
                                         .with_flag(Flag::OptionalDefaultFromNoValue),
@@ -218,7 +248,7 @@ impl TypeCoercer for Class {
                             }
                         }),
                         None => t.default_value(None).or_else(|| {
-                            if ctx.allow_partials {
+                            if null_while_streaming {
                                 Some(BamlValueWithFlags::Null(
                                     DeserializerConditions::new()
                                         .with_flag(Flag::OptionalDefaultFromNoValue),
@@ -328,7 +358,14 @@ impl TypeCoercer for Class {
                     flags,
                     ordered_valid_fields.clone(),
                 ))
-                .and_then(|value| apply_constraints(target, vec![], value, constraints.clone()));
+                .and_then(|value| apply_constraints(target, vec![], value, constraints.clone()))
+                .map(|value| {
+                    if ctx.allow_partials {
+                        with_stream_states(self, ctx, value, &seen_fields)
+                    } else {
+                        value
+                    }
+                });
 
                 completed_cls.insert(0, completed_instance);
             }
@@ -340,6 +377,47 @@ impl TypeCoercer for Class {
     }
 }
 
+/// Replaces the value of each `@stream.with_state` field of the partial
+/// `value` of `class` with `{"value": ..., "state": ...}`. Fields whose JSON
+/// token is still open are `incomplete`.
+fn with_stream_states(
+    class: &Class,
+    ctx: &ParsingContext,
+    mut value: BamlValueWithFlags,
+    seen_fields: &[&str],
+) -> BamlValueWithFlags {
+    let BamlValueWithFlags::Class(_, _, fields) = &mut value else {
+        return value;
+    };
+    for (name, field) in fields.iter_mut() {
+        if !ctx.of.streaming(class.name.real_name(), name).with_state {
+            continue;
+        }
+        let state = if !seen_fields.contains(&name.as_str()) {
+            StreamState::Pending
+        } else if !field.is_closed() {
+            StreamState::Incomplete
+        } else {
+            StreamState::Complete
+        };
+        let inner = std::mem::replace(field, BamlValueWithFlags::Null(Default::default()));
+        *field = BamlValueWithFlags::Map(
+            DeserializerConditions::new().with_flag(Flag::StreamState(state)),
+            BamlMap::from([
+                ("value".to_string(), (DeserializerConditions::new(), inner)),
+                (
+                    "state".to_string(),
+                    (
+                        DeserializerConditions::new(),
+                        BamlValueWithFlags::String(state.as_str().to_string().into()),
+                    ),
+                ),
+            ]),
+        );
+    }
+    value
+}
+
 pub fn apply_constraints(
     class_type: &FieldType,
     scope: Vec<String>,
@@ -1,11 +1,12 @@
 use anyhow::Result;
-use baml_types::FieldType;
+use baml_types::{EnumMatching, FieldType};
 use internal_baml_jinja::types::Enum;
 
 use crate::deserializer::{
     coercer::{
-        ir_ref::coerce_class::apply_constraints, match_string::match_string, ParsingError,
-        TypeCoercer,
+        ir_ref::coerce_class::apply_constraints,
+        match_string::{match_string, match_whole_string},
+        ParsingError, TypeCoercer,
     },
     types::BamlValueWithFlags,
 };
@@ -31,6 +32,14 @@ fn enum_match_candidates(enm: &Enum) -> Vec<(&str, Vec<String>)> {
         .collect()
 }
 
+/// Aliases (or names) alone, for `@@match(alias)`.
+fn enum_alias_candidates(enm: &Enum) -> Vec<(&str, Vec<String>)> {
+    enm.values
+        .iter()
+        .map(|(name, _)| (name.real_name(), vec![name.rendered_name().into()]))
+        .collect()
+}
+
 impl TypeCoercer for Enum {
     fn coerce(
         &self,
@@ -50,7 +59,28 @@ impl TypeCoercer for Enum {
             .find_enum(self.name.real_name())
             .map_or(vec![], |class| class.constraints.clone());
 
-        let variant_match = match_string(ctx, target, value, &enum_match_candidates(self))?;
+        let variant_match = match self.matching {
+            EnumMatching::Substring => {
+                match_string(ctx, target, value, &enum_match_candidates(self))?
+            }
+            EnumMatching::Exact => {
+                match_whole_string(ctx, target, value, &enum_match_candidates(self), true, 0)?
+            }
+            EnumMatching::CaseInsensitive => {
+                match_whole_string(ctx, target, value, &enum_match_candidates(self), false, 0)?
+            }
+            EnumMatching::Alias => {
+                match_whole_string(ctx, target, value, &enum_alias_candidates(self), false, 0)?
+            }
+            EnumMatching::Fuzzy { max_distance } => match_whole_string(
+                ctx,
+                target,
+                value,
+                &enum_match_candidates(self),
+                false,
+                max_distance,
+            )?,
+        };
         let enum_match = apply_constraints(
             target,
             vec![],
@@ -92,6 +92,89 @@ pub(super) fn match_string(
     Err(parsing_context.error_unexpected_type(target, &value))
 }
 
+/// Whole string match of different possible values against an input string,
+/// for enums whose `@@match` rules out the heuristics of [`match_string`].
+///
+/// Case is ignored unless `case_sensitive`. Up to `max_distance` edits are
+/// allowed, as long as a single candidate is closest.
+pub(super) fn match_whole_string(
+    parsing_context: &ParsingContext,
+    target: &FieldType,
+    value: Option<&jsonish::Value>,
+    // List of (name, [aliases]) tuples.
+    candidates: &[(&str, Vec<String>)],
+    case_sensitive: bool,
+    max_distance: usize,
+) -> Result<ValueWithFlags<String>, ParsingError> {
+    // Get rid of nulls.
+    let value = match value {
+        None | Some(jsonish::Value::Null) => {
+            return Err(parsing_context.error_unexpected_null(target));
+        }
+        Some(v) => v,
+    };
+
+    // The output may have been parsed several ways, e.g. `"High"` with and
+    // without its quotes, so any of them may match.
+    let inputs: Vec<&str> = match value {
+        jsonish::Value::String(s) => vec![s.as_str()],
+        jsonish::Value::AnyOf(variants, s) => variants
+            .iter()
+            .filter_map(|v| match v {
+                jsonish::Value::String(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .chain(std::iter::once(s.as_str()))
+            .collect(),
+        v => return Err(parsing_context.error_unexpected_type(target, v)),
+    };
+
+    let normalize = |s: &str| match case_sensitive {
+        true => s.trim().to_string(),
+        false => s.trim().to_lowercase(),
+    };
+
+    // (edits, [variants]) of the closest candidates so far.
+    let mut closest: Option<(usize, Vec<&str>)> = None;
+    for input in inputs.iter().map(|input| normalize(input)) {
+        for (variant, valid_values) in candidates {
+            for valid_value in valid_values.iter().map(|v| normalize(v)) {
+                let edits = match max_distance {
+                    0 if valid_value == input => 0,
+                    0 => continue,
+                    _ => strsim::levenshtein(&valid_value, &input),
+                };
+                if edits > max_distance {
+                    continue;
+                }
+                match &mut closest {
+                    Some((closest_edits, _)) if edits > *closest_edits => {}
+                    Some((closest_edits, variants)) if edits == *closest_edits => {
+                        if !variants.contains(variant) {
+                            variants.push(variant);
+                        }
+                    }
+                    _ => closest = Some((edits, vec![variant])),
+                }
+            }
+        }
+    }
+
+    match closest {
+        None => Err(parsing_context.error_unexpected_type(target, &value)),
+        Some((_, variants)) if variants.len() > 1 => {
+            Err(parsing_context.error_too_many_matches(target, variants))
+        }
+        Some((edits, variants)) => {
+            let mut flags = DeserializerConditions::new();
+            if edits > 0 {
+                flags.add_flag(Flag::FuzzyMatch(value.to_string(), edits));
+            }
+            Ok((variants[0].to_string(), flags).into())
+        }
+    }
+}
+
 fn strip_punctuation(s: &str) -> String {
     s.chars()
         .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
@@ -2,6 +2,7 @@ mod array_helper;
 mod coerce_array;
 mod coerce_literal;
 mod coerce_map;
+mod coerce_markdown_table;
 mod coerce_optional;
 mod coerce_primitive;
 mod coerce_union;
@@ -13,7 +14,7 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
 
-use baml_types::{BamlValue, Constraint, JinjaExpression};
+use baml_types::{BamlValue, BoolStrings, Constraint, JinjaExpression};
 use internal_baml_jinja::types::OutputFormatContent;
 
 use internal_baml_core::ir::{jinja_helpers::evaluate_predicate, FieldType};
@@ -27,6 +28,9 @@ pub struct ParsingContext<'a> {
     visited: HashSet<(String, jsonish::Value)>,
     pub of: &'a OutputFormatContent,
     pub allow_partials: bool,
+    /// Which strings bools are read from, per the `@bool_strings` of the
+    /// field being parsed.
+    pub bool_strings: BoolStrings,
 }
 
 impl ParsingContext<'_> {
@@ -43,6 +47,7 @@ impl ParsingContext<'_> {
             visited: HashSet::new(),
             of,
             allow_partials,
+            bool_strings: BoolStrings::default(),
         }
     }
 
@@ -54,9 +59,22 @@ impl ParsingContext<'_> {
             visited: self.visited.clone(),
             of: self.of,
             allow_partials: self.allow_partials,
+            bool_strings: self.bool_strings,
         }
     }
 
+    /// Like `enter_scope`, into `field` of `class`.
+    pub(crate) fn enter_field_scope(
+        &self,
+        scope: &str,
+        class: &str,
+        field: &str,
+    ) -> ParsingContext {
+        let mut ctx = self.enter_scope(scope);
+        ctx.bool_strings = self.of.bool_strings(class, field);
+        ctx
+    }
+
     // TODO: This function and `enter_scope` are clonning both the scope vector
     // and visited hash set each time. Maybe it can be optimized with interior
     // mutability or something.
@@ -71,6 +89,7 @@ impl ParsingContext<'_> {
             visited: new_visited,
             of: self.of,
             allow_partials: self.allow_partials,
+            bool_strings: self.bool_strings,
         }
     }
 
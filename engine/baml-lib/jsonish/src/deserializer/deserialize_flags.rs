@@ -1,11 +1,16 @@
 use super::{coercer::ParsingError, types::BamlValueWithFlags};
-use baml_types::{Constraint, ConstraintLevel, JinjaExpression};
+use baml_types::{Constraint, ConstraintLevel, JinjaExpression, StreamState};
 
 #[derive(Debug, Clone)]
 pub enum Flag {
     // SingleFromMultiple,
     ObjectFromMarkdown(i32),
     ObjectFromFixedJson(Vec<crate::jsonish::Fixes>),
+    /// The header of the markdown table a list of classes was read from.
+    ListFromMarkdownTable(Vec<String>),
+    /// `(column, field)`, for a table column whose header only matches its
+    /// field's name ignoring case and punctuation.
+    ColumnToField(String, String),
 
     DefaultButHadUnparseableValue(ParsingError),
     ObjectToString(crate::jsonish::Value),
@@ -14,6 +19,8 @@ pub enum Flag {
     ExtraKey(String, crate::jsonish::Value),
     StrippedNonAlphaNumeric(String),
     SubstringMatch(String),
+    /// `(value, edits)`
+    FuzzyMatch(String, usize),
     SingleToArray,
     ArrayItemParseError(usize, ParsingError),
     MapKeyParseError(usize, ParsingError),
@@ -36,8 +43,13 @@ pub enum Flag {
 
     // String -> X convertions.
     StringToBool(String),
+    /// A word like `yes` or `off`, or `1` or `0`, read as a bool as its
+    /// field's `@bool_strings` allows.
+    WordToBool(String),
     StringToNull(String),
     StringToChar(String),
+    /// A currency symbol or percent sign read off a number.
+    StrippedUnit(String),
 
     // Number -> X convertions.
     FloatToInt(f64),
@@ -47,6 +59,10 @@ pub enum Flag {
 
     /// Constraint results (only contains checks)
     ConstraintResults(Vec<(String, JinjaExpression, bool)>),
+
+    /// On the `{"value": ..., "state": ...}` a `@stream.with_state` field
+    /// is streamed as.
+    StreamState(StreamState),
 }
 
 #[derive(Clone)]
@@ -61,6 +77,8 @@ impl DeserializerConditions {
             .filter_map(|c| match c {
                 Flag::ObjectFromMarkdown(_) => None,
                 Flag::ObjectFromFixedJson(_) => None,
+                Flag::ListFromMarkdownTable(_) => None,
+                Flag::ColumnToField(_, _) => None,
                 Flag::ArrayItemParseError(_idx, e) => {
                     // TODO: should idx be recorded?
                     Some(e.clone())
@@ -71,6 +89,7 @@ impl DeserializerConditions {
                 Flag::ExtraKey(_, _) => None,
                 Flag::StrippedNonAlphaNumeric(_) => None,
                 Flag::SubstringMatch(_) => None,
+                Flag::FuzzyMatch(_, _) => None,
                 Flag::SingleToArray => None,
                 Flag::MapKeyParseError(_idx, e) => {
                     // Some(format!("Error parsing key {} in map: {}", idx, e))
@@ -89,13 +108,16 @@ impl DeserializerConditions {
                 Flag::DefaultButHadValue(_) => None,
                 Flag::OptionalDefaultFromNoValue => None,
                 Flag::StringToBool(_) => None,
+                Flag::WordToBool(_) => None,
                 Flag::StringToNull(_) => None,
                 Flag::StringToChar(_) => None,
                 Flag::FloatToInt(_) => None,
+                Flag::StrippedUnit(_) => None,
                 Flag::NoFields(_) => None,
                 Flag::UnionMatch(_idx, _) => None,
                 Flag::DefaultButHadUnparseableValue(e) => Some(e.clone()),
                 Flag::ConstraintResults(_) => None,
+                Flag::StreamState(_) => None,
             })
             .collect::<Vec<_>>()
     }
@@ -151,6 +173,12 @@ impl std::fmt::Display for Flag {
             Flag::ObjectFromMarkdown(_) => {
                 write!(f, "Object from markdown")?;
             }
+            Flag::ListFromMarkdownTable(header) => {
+                write!(f, "List from markdown table: {}", header.join(" | "))?;
+            }
+            Flag::ColumnToField(column, field) => {
+                write!(f, "Column {} to field {}", column, field)?;
+            }
             Flag::ImpliedKey(key) => {
                 write!(f, "Implied key: {}", key)?;
             }
@@ -206,6 +234,9 @@ impl std::fmt::Display for Flag {
             Flag::SubstringMatch(value) => {
                 write!(f, "Substring match: {}", value)?;
             }
+            Flag::FuzzyMatch(value, edits) => {
+                write!(f, "Fuzzy match: {} ({} edits)", value, edits)?;
+            }
             Flag::FirstMatch(idx, values) => {
                 writeln!(f, "Picked item {}:", idx)?;
                 for (idx, value) in values.iter().enumerate() {
@@ -229,6 +260,9 @@ impl std::fmt::Display for Flag {
             Flag::StringToBool(value) => {
                 write!(f, "String to bool: {}", value)?;
             }
+            Flag::WordToBool(value) => {
+                write!(f, "Word to bool: {}", value)?;
+            }
             Flag::StringToNull(value) => {
                 write!(f, "String to null: {}", value)?;
             }
@@ -238,6 +272,9 @@ impl std::fmt::Display for Flag {
             Flag::FloatToInt(value) => {
                 write!(f, "Float to int: {}", value)?;
             }
+            Flag::StrippedUnit(unit) => {
+                write!(f, "Stripped unit: {}", unit)?;
+            }
             Flag::NoFields(value) => {
                 write!(f, "No fields: ")?;
                 if let Some(value) = value {
@@ -256,6 +293,9 @@ impl std::fmt::Display for Flag {
                     )?;
                 }
             }
+            Flag::StreamState(state) => {
+                write!(f, "Stream state: {}", state.as_str())?;
+            }
         }
         Ok(())
     }
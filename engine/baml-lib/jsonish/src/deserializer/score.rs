@@ -40,6 +40,8 @@ impl WithScore for Flag {
             Flag::DefaultButHadValue(_) => 110,
             Flag::ObjectFromFixedJson(_) => 0,
             Flag::ObjectFromMarkdown(s) => *s,
+            Flag::ListFromMarkdownTable(_) => 1,
+            Flag::ColumnToField(_, _) => 1,
             Flag::DefaultButHadUnparseableValue(_) => 2,
             Flag::ObjectToMap(_) => 1,
             Flag::ObjectToString(_) => 2,
@@ -47,6 +49,7 @@ impl WithScore for Flag {
             Flag::ExtraKey(_, _) => 1,
             Flag::StrippedNonAlphaNumeric(_) => 3,
             Flag::SubstringMatch(_) => 2,
+            Flag::FuzzyMatch(_, edits) => 1 + *edits as i32,
             Flag::ImpliedKey(_) => 2,
             Flag::JsonToString(_) => 2,
             Flag::SingleToArray => 1,
@@ -62,12 +65,15 @@ impl WithScore for Flag {
                 values.iter().map(|(_, count)| *count as i32).sum::<i32>()
             }
             Flag::StringToBool(_) => 1,
+            Flag::WordToBool(_) => 2,
             Flag::StringToNull(_) => 1,
             Flag::StringToChar(_) => 1,
             Flag::FloatToInt(_) => 1,
+            Flag::StrippedUnit(_) => 1,
             Flag::NoFields(_) => 1,
             // No scores for contraints
             Flag::ConstraintResults(_) => 0,
+            Flag::StreamState(_) => 0,
         }
     }
 }
@@ -1,9 +1,13 @@
 use std::collections::HashSet;
 
-use baml_types::{BamlMap, BamlMedia, BamlValue, BamlValueWithMeta, Constraint, JinjaExpression};
+use baml_types::{
+    BamlMap, BamlMedia, BamlValue, BamlValueWithMeta, Constraint, JinjaExpression, StreamState,
+};
 use serde_json::json;
 use strsim::jaro;
 
+use crate::jsonish::Fixes;
+
 use super::{
     coercer::ParsingError,
     deserialize_flags::{DeserializerConditions, Flag},
@@ -71,6 +75,89 @@ impl BamlValueWithFlags {
             BamlValueWithFlags::Media(v) => &v.flags,
         }
     }
+
+    /// Whether the JSON token of the value was closed, i.e. the model
+    /// finished writing it. Only values of partial results can be open.
+    pub fn is_closed(&self) -> bool {
+        !self.conditions().flags().iter().any(|flag| {
+            matches!(flag, Flag::ObjectFromFixedJson(fixes) if fixes.contains(&Fixes::Unclosed))
+        })
+    }
+
+    /// How far along the value is in a partial result.
+    pub fn stream_state(&self) -> StreamState {
+        let flags = self.conditions().flags();
+        if let Some(state) = flags.iter().find_map(|flag| match flag {
+            Flag::StreamState(state) => Some(*state),
+            _ => None,
+        }) {
+            return state;
+        }
+        if flags.iter().any(|flag| {
+            matches!(
+                flag,
+                Flag::DefaultFromNoValue | Flag::OptionalDefaultFromNoValue
+            )
+        }) {
+            StreamState::Pending
+        } else if self.is_closed() {
+            StreamState::Complete
+        } else {
+            StreamState::Incomplete
+        }
+    }
+
+    /// The [`StreamState`] of every value nested in this one, by path: the
+    /// field names, map keys and list indices to it joined by dots, e.g.
+    /// `vertices.1.name`.
+    pub fn stream_states(&self) -> BamlMap<String, StreamState> {
+        let mut states = BamlMap::new();
+        self.collect_stream_states("", &mut states);
+        states
+    }
+
+    fn collect_stream_states(&self, path: &str, states: &mut BamlMap<String, StreamState>) {
+        let children: Vec<(String, &BamlValueWithFlags)> = match self {
+            BamlValueWithFlags::List(_, items) => items
+                .iter()
+                .enumerate()
+                .map(|(idx, item)| (idx.to_string(), item))
+                .collect(),
+            BamlValueWithFlags::Map(_, entries) => entries
+                .iter()
+                .map(|(key, (_, value))| (key.clone(), value))
+                .collect(),
+            BamlValueWithFlags::Class(_, _, fields) => fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value))
+                .collect(),
+            _ => return,
+        };
+        for (key, child) in children {
+            let path = match path {
+                "" => key,
+                _ => format!("{path}.{key}"),
+            };
+            states.insert(path.clone(), child.stream_state());
+            // A `@stream.with_state` field carries its state next to its
+            // value, which takes its place.
+            let child = match child {
+                BamlValueWithFlags::Map(conditions, entries)
+                    if conditions
+                        .flags()
+                        .iter()
+                        .any(|flag| matches!(flag, Flag::StreamState(_))) =>
+                {
+                    match entries.get("value") {
+                        Some((_, value)) => value,
+                        None => continue,
+                    }
+                }
+                child => child,
+            };
+            child.collect_stream_states(&path, states);
+        }
+    }
 }
 
 trait ParsingErrorToUiJson {
@@ -4,7 +4,7 @@ use crate::jsonish::{
     parser::{
         fixing_parser,
         markdown_parser::{self, MarkdownResult},
-        multi_json_parser,
+        multi_json_parser, xml_parser, yaml_parser,
     },
     value::Fixes,
     Value,
@@ -102,20 +102,51 @@ pub fn parse(str: &str, mut options: ParseOptions) -> Result<Value> {
         }
     }
 
+    // Prose is valid YAML too, so YAML and XML are only ever more candidates,
+    // after the JSON found in the text. Text that starts like JSON is left to
+    // those.
+    let looks_like_json = str.trim_start().starts_with(['{', '[']);
+    let yaml = match options.allow_yaml && !looks_like_json {
+        true => match yaml_parser::parse(str) {
+            Ok(v) => Some(Value::FixedJson(v.into(), vec![Fixes::ParsedYaml])),
+            Err(e) => {
+                log::debug!("Error parsing YAML: {:?}", e);
+                None
+            }
+        },
+        false => None,
+    };
+    let xml = match options.allow_xml && !looks_like_json {
+        true => match xml_parser::parse(str) {
+            Ok(items) => items
+                .into_iter()
+                .map(|v| Value::FixedJson(v.into(), vec![Fixes::ParsedXml]))
+                .collect(),
+            Err(e) => {
+                log::debug!("Error parsing XML: {:?}", e);
+                vec![]
+            }
+        },
+        false => vec![],
+    };
+
     if options.all_finding_all_json_objects {
         match multi_json_parser::parse(str, &options) {
             Ok(items) => match items.len() {
                 0 => {}
                 1 => {
                     return Ok(Value::AnyOf(
-                        vec![Value::FixedJson(
+                        std::iter::once(Value::FixedJson(
                             items
                                 .into_iter()
                                 .next()
                                 .ok_or_else(|| anyhow::anyhow!("Expected 1 item"))?
                                 .into(),
                             vec![Fixes::GreppedForJSON],
-                        )],
+                        ))
+                        .chain(yaml)
+                        .chain(xml)
+                        .collect(),
                         str.to_string(),
                     ))
                 }
@@ -125,6 +156,8 @@ pub fn parse(str: &str, mut options: ParseOptions) -> Result<Value> {
                         .into_iter()
                         .chain(std::iter::once(items_clone))
                         .map(|v| Value::FixedJson(v.into(), vec![Fixes::GreppedForJSON]))
+                        .chain(yaml)
+                        .chain(xml)
                         .collect::<Vec<_>>();
                     return Ok(Value::AnyOf(items, str.to_string()));
                 }
@@ -135,6 +168,8 @@ pub fn parse(str: &str, mut options: ParseOptions) -> Result<Value> {
         }
     }
 
+    let mut candidates = vec![];
+
     if options.allow_fixes {
         match fixing_parser::parse(str, &options) {
             Ok(items) => {
@@ -144,10 +179,7 @@ pub fn parse(str: &str, mut options: ParseOptions) -> Result<Value> {
                         let (v, fixes) = items.into_iter().next().ok_or_else(|| {
                             anyhow::anyhow!("Expected 1 item when performing fixes")
                         })?;
-                        return Ok(Value::AnyOf(
-                            vec![Value::FixedJson(v.into(), fixes)],
-                            str.to_string(),
-                        ));
+                        candidates.push(Value::FixedJson(v.into(), fixes));
                     }
                     _ => {
                         // In the case of multiple JSON objects:
@@ -165,11 +197,7 @@ pub fn parse(str: &str, mut options: ParseOptions) -> Result<Value> {
 
                         let items_clone = Value::Array(items.clone());
 
-                        let items = items
-                            .into_iter()
-                            .chain(std::iter::once(items_clone))
-                            .collect::<Vec<_>>();
-                        return Ok(Value::AnyOf(items, str.to_string()));
+                        candidates.extend(items.into_iter().chain(std::iter::once(items_clone)));
                     }
                 }
             }
@@ -179,6 +207,13 @@ pub fn parse(str: &str, mut options: ParseOptions) -> Result<Value> {
         }
     }
 
+    candidates.extend(yaml);
+    candidates.extend(xml);
+
+    if !candidates.is_empty() {
+        return Ok(Value::AnyOf(candidates, str.to_string()));
+    }
+
     if options.allow_as_string {
         return Ok(Value::String(str.to_string()));
     }
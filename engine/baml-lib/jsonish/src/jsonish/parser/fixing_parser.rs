@@ -46,6 +46,7 @@ pub fn parse(str: &str, _options: &ParseOptions) -> Result<Vec<(Value, Vec<Fixes
 
     // If we still have a collection open, close it
     while !state.collection_stack.is_empty() {
+        state.mark_unclosed();
         state.complete_collection();
     }
 
@@ -104,13 +105,15 @@ mod tests {
         let vals = parse("[12", &opts).unwrap();
         dbg!(&vals);
 
+        assert_eq!(vals[0].1, [Fixes::Unclosed]);
         match vals[0].0.clone() {
             Value::Array(xs) => {
                 assert_eq!(xs.len(), 1);
                 match &xs[0] {
-                    Value::Number(n) => {
+                    Value::FixedJson(n, fixes) => {
                         dbg!(&n);
-                        assert_eq!(n, &serde_json::Number::from(12));
+                        assert_eq!(**n, Value::Number(serde_json::Number::from(12)));
+                        assert_eq!(fixes, &[Fixes::Unclosed]);
                     }
                     _ => panic!("Expected number"),
                 }
@@ -127,12 +130,14 @@ mod tests {
         match &vals[0].0 {
             Value::Object(fields) => {
                 assert_eq!(fields.len(), 2);
+                // `b` is still open, so it may still grow.
                 match (&fields[0], &fields[1]) {
-                    ((key_a, Value::Number(a)), (key_b, Value::Number(b))) => {
+                    ((key_a, Value::Number(a)), (key_b, Value::FixedJson(b, fixes))) => {
                         assert_eq!(key_a.as_str(), "a");
                         assert_eq!(key_b.as_str(), "b");
                         assert_eq!(a, &serde_json::Number::from(11));
-                        assert_eq!(b, &serde_json::Number::from(22));
+                        assert_eq!(**b, Value::Number(serde_json::Number::from(22)));
+                        assert_eq!(fixes, &[Fixes::Unclosed]);
                     }
                     _ => panic!("Expected two numbers."),
                 }
@@ -149,12 +154,14 @@ mod tests {
         match &vals[0].0 {
             Value::Object(fields) => {
                 assert_eq!(fields.len(), 2);
+                // `b` is still open, so it may still grow.
                 match (&fields[0], &fields[1]) {
-                    ((key_a, Value::Number(a)), (key_b, Value::Number(b))) => {
+                    ((key_a, Value::Number(a)), (key_b, Value::FixedJson(b, fixes))) => {
                         assert_eq!(key_a.as_str(), "a");
                         assert_eq!(key_b.as_str(), "b");
                         assert_eq!(a, &serde_json::Number::from(11));
-                        assert_eq!(b, &serde_json::Number::from(22));
+                        assert_eq!(**b, Value::Number(serde_json::Number::from(22)));
+                        assert_eq!(fixes, &[Fixes::Unclosed]);
                     }
                     _ => panic!("Expected two numbers."),
                 }
@@ -162,4 +169,12 @@ mod tests {
             _ => panic!("Expected object"),
         }
     }
+
+    #[test]
+    fn test_deeply_nested_input_is_rejected() {
+        let opts = ParseOptions::default();
+        assert!(parse(&"[".repeat(10_000), &opts).is_err());
+        assert!(parse(&"{\"a\":".repeat(10_000), &opts).is_err());
+        assert!(parse(&"[".repeat(100), &opts).is_ok());
+    }
 }
@@ -5,6 +5,11 @@ use anyhow::Result;
 
 use super::json_collection::JsonCollection;
 
+/// Maximum nesting of objects and arrays. Matches serde_json's recursion
+/// limit; anything deeper is not a plausible model response and would
+/// otherwise overflow the stack when the value is coerced or dropped.
+const MAX_DEPTH: usize = 128;
+
 pub struct JsonParseState {
     pub collection_stack: Vec<(JsonCollection, Vec<Fixes>)>,
 
@@ -32,6 +37,11 @@ impl JsonParseState {
             Some(value) => value,
             None => return,
         };
+        // Nested values keep their fixes so coercion can flag them.
+        let fixed = |value: Value, fixes: Vec<Fixes>| match fixes.is_empty() {
+            true => value,
+            false => Value::FixedJson(value.into(), fixes),
+        };
 
         if let Some((last, _fixes)) = self.collection_stack.last_mut() {
             match last {
@@ -43,17 +53,18 @@ impl JsonParseState {
                             _ => keys.push(value.to_string()),
                         }
                     } else {
-                        values.push(value);
+                        values.push(fixed(value, fixes));
                     }
                 }
                 JsonCollection::Array(values) => {
-                    values.push(value);
+                    values.push(fixed(value, fixes));
                 }
                 _ => {
-                    // TODO: this should never happen as we should only be pushing objects and arrays
-                    panic!(
-                        "Unexpected value: {:?} in collection stack: {:?}",
-                        value, last
+                    // Only objects and arrays should ever hold nested values.
+                    log::debug!(
+                        "Dropping unexpected value: {:?} in collection stack: {:?}",
+                        value,
+                        last
                     );
                 }
             }
@@ -82,12 +93,30 @@ impl JsonParseState {
                 s.push(token);
             }
             JsonCollection::Object(_, _) | JsonCollection::Array(_) => {
-                panic!("Unexpected token: {:?} in: {:?}", token, last);
+                return Err(anyhow::anyhow!(
+                    "Unexpected token: {:?} in: {:?}",
+                    token,
+                    last
+                ));
             }
         }
         Ok(0)
     }
 
+    /// Marks the collection currently being built as cut off by the end of
+    /// the input.
+    pub fn mark_unclosed(&mut self) {
+        if let Some((_, fixes)) = self.collection_stack.last_mut() {
+            fixes.push(Fixes::Unclosed);
+        }
+    }
+
+    /// The collection enclosing the one currently being built, if any.
+    fn parent_collection(&self) -> Option<&JsonCollection> {
+        let idx = self.collection_stack.len().checked_sub(2)?;
+        self.collection_stack.get(idx).map(|(c, _)| c)
+    }
+
     fn is_string_complete(&self) -> bool {
         let Some((JsonCollection::UnquotedString(v), _)) = self.collection_stack.last() else {
             return false;
@@ -110,23 +139,17 @@ impl JsonParseState {
         &mut self,
         mut next: Peekable<impl Iterator<Item = (usize, char)>>,
     ) -> Option<usize> {
-        let pos = if self.collection_stack.len() >= 2 {
-            self.collection_stack
-                .get(self.collection_stack.len() - 2)
-                .map(|(c, _)| match c {
-                    JsonCollection::Object(keys, values) => {
-                        if keys.len() == values.len() {
-                            2
-                        } else {
-                            3
-                        }
-                    }
-                    JsonCollection::Array(_) => 4,
-                    _ => 1,
-                })
-                .unwrap()
-        } else {
-            0
+        let pos = match self.parent_collection() {
+            Some(JsonCollection::Object(keys, values)) => {
+                if keys.len() == values.len() {
+                    2
+                } else {
+                    3
+                }
+            }
+            Some(JsonCollection::Array(_)) => 4,
+            Some(_) => 1,
+            None => 0,
         };
         match pos {
             0 => {
@@ -142,6 +165,7 @@ impl JsonParseState {
                         }
                     }
                 }
+                self.mark_unclosed();
                 Some(counter)
             }
             1 => None,
@@ -157,6 +181,7 @@ impl JsonParseState {
                         }
                     }
                 }
+                self.mark_unclosed();
                 Some(counter)
             }
             3 => {
@@ -251,6 +276,7 @@ impl JsonParseState {
                         }
                     }
                 }
+                self.mark_unclosed();
                 Some(counter)
             }
             4 => {
@@ -266,6 +292,7 @@ impl JsonParseState {
                         }
                     }
                 }
+                self.mark_unclosed();
                 counter += 1; // Indicate that we called next() one time after the final `Some`.
                 Some(counter)
             }
@@ -279,24 +306,17 @@ impl JsonParseState {
         closing_char: char,
     ) -> bool {
         let (has_some_object, in_object_key, in_object_value, in_array) =
-            if self.collection_stack.len() >= 2 {
-                self.collection_stack
-                    .get(self.collection_stack.len() - 2)
-                    .map(|(c, _)| match c {
-                        JsonCollection::Object(keys, values) => {
-                            if keys.len() == values.len() {
-                                (true, false, false)
-                            } else {
-                                (false, true, true)
-                            }
-                        }
-                        JsonCollection::Array(_) => (false, false, true),
-                        _ => (false, false, false),
-                    })
-                    .map(|(a, b, c)| (true, a, b, c))
-                    .unwrap()
-            } else {
-                (false, false, false, false)
+            match self.parent_collection() {
+                Some(JsonCollection::Object(keys, values)) => {
+                    if keys.len() == values.len() {
+                        (true, true, false, false)
+                    } else {
+                        (true, false, true, true)
+                    }
+                }
+                Some(JsonCollection::Array(_)) => (true, false, false, true),
+                Some(_) => (true, false, false, false),
+                None => (false, false, false, false),
             };
 
         if let Some((idx, next_char)) = next.peek() {
@@ -617,6 +637,13 @@ impl JsonParseState {
         token: char,
         mut next: Peekable<impl Iterator<Item = (usize, char)>>,
     ) -> Result<usize> {
+        if matches!(token, '{' | '[') && self.collection_stack.len() >= MAX_DEPTH {
+            return Err(anyhow::anyhow!(
+                "JSON nesting exceeds the maximum depth of {}",
+                MAX_DEPTH
+            ));
+        }
+
         match token {
             '{' => {
                 self.collection_stack
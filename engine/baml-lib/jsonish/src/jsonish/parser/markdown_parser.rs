@@ -5,6 +5,8 @@ use crate::jsonish::{
 
 use super::ParseOptions;
 use anyhow::Result;
+use regex::Regex;
+use std::sync::OnceLock;
 
 #[derive(Debug)]
 pub enum MarkdownResult {
@@ -12,16 +14,21 @@ pub enum MarkdownResult {
     String(String),
 }
 
+// Markdown fences (```<tag><EOF|newline>). Compiled once: this parser runs
+// recursively on every nested candidate of every response.
+static MD_TAG_START: OnceLock<Regex> = OnceLock::new();
+static MD_TAG_END: OnceLock<Regex> = OnceLock::new();
+
 pub fn parse(str: &str, options: &ParseOptions) -> Result<Vec<MarkdownResult>> {
     let mut values = vec![];
 
     let mut remaining = str;
-    // Find regex for markdown blocks (```<tag><EOF|newline>)
 
-    let md_tag_start = regex::Regex::new(r"```([a-zA-Z0-9 ]+)(?:\n|$)")
-        .map_err(|e| anyhow::Error::from(e).context("Failed to build regex for md-tag-start"))?;
-    let md_tag_end = regex::Regex::new(r"```(?:\n|$)")
-        .map_err(|e| anyhow::Error::from(e).context("Failed to build regex for md-tag-end"))?;
+    let md_tag_start = MD_TAG_START.get_or_init(|| {
+        Regex::new(r"```([a-zA-Z0-9 ]+)(?:\n|$)").expect("md-tag-start regex is valid")
+    });
+    let md_tag_end =
+        MD_TAG_END.get_or_init(|| Regex::new(r"```(?:\n|$)").expect("md-tag-end regex is valid"));
 
     let mut should_loop = true;
 
@@ -2,6 +2,8 @@ mod entry;
 mod fixing_parser;
 mod markdown_parser;
 mod multi_json_parser;
+mod xml_parser;
+mod yaml_parser;
 
 pub use entry::parse;
 
@@ -10,6 +12,8 @@ pub struct ParseOptions {
     all_finding_all_json_objects: bool,
     allow_markdown_json: bool,
     allow_fixes: bool,
+    allow_yaml: bool,
+    allow_xml: bool,
     allow_as_string: bool,
     depth: usize,
 }
@@ -20,6 +24,8 @@ impl Default for ParseOptions {
             all_finding_all_json_objects: true,
             allow_markdown_json: true,
             allow_fixes: true,
+            allow_yaml: true,
+            allow_xml: true,
             allow_as_string: true,
             depth: 0,
         }
@@ -33,6 +39,20 @@ pub(super) enum ParsingMode {
 }
 
 impl ParseOptions {
+    /// Only accepts valid JSON, without looking for it in markdown or other
+    /// text, fixing it, reading it as YAML or XML, or falling back to a string.
+    pub fn json_only() -> Self {
+        Self {
+            all_finding_all_json_objects: false,
+            allow_markdown_json: false,
+            allow_fixes: false,
+            allow_yaml: false,
+            allow_xml: false,
+            allow_as_string: false,
+            depth: 0,
+        }
+    }
+
     pub(super) fn next_from_mode(&self, curr_mode: ParsingMode) -> Self {
         let mut new = *self;
         match curr_mode {
@@ -0,0 +1,429 @@
+use std::ops::Range;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use crate::jsonish::{value::Fixes, Value};
+
+/// Parses the XML elements in `str`, as Claude-style models often answer
+/// with tagged output. Text around the elements is skipped, and elements the
+/// input ends in are closed, so streamed output parses too.
+///
+/// Returns the value of each top-level element and, when there are several,
+/// all of them as a list.
+pub fn parse(str: &str) -> Result<Vec<Value>> {
+    let roots = elements(str);
+    let mut items = roots.iter().map(|e| e.value(str)).collect::<Vec<_>>();
+    match items.len() {
+        0 => anyhow::bail!("No XML elements"),
+        1 => Ok(items),
+        _ => {
+            items.push(Value::Array(items.clone()));
+            Ok(items)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    /// The text directly in the element, decoded.
+    text: String,
+    /// Where the content of the element is in the input.
+    content: Range<usize>,
+    closed: bool,
+}
+
+impl Element {
+    /// Attributes and child elements are fields. Children with the same name
+    /// are a list of them.
+    fn value(&self, input: &str) -> Value {
+        let text = self.text.trim();
+        let value = if self.attributes.is_empty() && self.children.is_empty() {
+            match text {
+                // `<middle_name />` or `<middle_name>null</middle_name>`.
+                "" | "null" => Value::AnyOf(
+                    vec![Value::Null, Value::String(text.to_string())],
+                    text.to_string(),
+                ),
+                _ => Value::String(text.to_string()),
+            }
+        } else {
+            let children = self
+                .children
+                .iter()
+                .map(|child| (child.name.as_str(), child.value(input)))
+                .collect::<Vec<_>>();
+
+            let mut fields = IndexMap::<&str, Vec<Value>>::new();
+            for (name, value) in &self.attributes {
+                fields
+                    .entry(name.as_str())
+                    .or_default()
+                    .push(Value::String(value.clone()));
+            }
+            for (name, value) in &children {
+                fields.entry(*name).or_default().push(value.clone());
+            }
+            let object = Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, mut values)| {
+                        let value = match values.len() {
+                            1 => values.remove(0),
+                            _ => Value::Array(values),
+                        };
+                        (name.to_string(), value)
+                    })
+                    .collect(),
+            );
+
+            let mut candidates = vec![object];
+            if self.attributes.is_empty() {
+                // `<hobbies><hobby>chess</hobby></hobbies>` is a list, whatever
+                // its items are called.
+                if children.iter().all(|(name, _)| *name == children[0].0) {
+                    candidates.push(Value::Array(
+                        children.iter().map(|(_, value)| value.clone()).collect(),
+                    ));
+                }
+                // `<answer><Person>...</Person></answer>` is the person.
+                if children.len() == 1 && text.is_empty() {
+                    candidates.push(children[0].1.clone());
+                }
+            } else if self.children.is_empty() && !text.is_empty() {
+                candidates.push(Value::String(text.to_string()));
+            }
+            Value::AnyOf(candidates, input[self.content.clone()].trim().to_string())
+        };
+
+        match self.closed {
+            true => value,
+            false => Value::FixedJson(value.into(), vec![Fixes::Unclosed]),
+        }
+    }
+}
+
+/// The top-level elements of `input`.
+fn elements(input: &str) -> Vec<Element> {
+    let mut roots = vec![];
+    let mut stack: Vec<Element> = vec![];
+
+    fn attach(element: Element, stack: &mut [Element], roots: &mut Vec<Element>) {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(element),
+            None => roots.push(element),
+        }
+    }
+
+    let mut i = 0;
+    while i < input.len() {
+        let tail = &input[i..];
+        let lt = tail.find('<').unwrap_or(tail.len());
+        if let Some(top) = stack.last_mut() {
+            top.text.push_str(&decode(&tail[..lt]));
+        }
+        i += lt;
+        let tail = &input[i..];
+        if tail.is_empty() {
+            break;
+        }
+
+        if let Some(comment) = tail.strip_prefix("<!--") {
+            i += 4 + comment.find("-->").map_or(comment.len(), |end| end + 3);
+        } else if let Some(cdata) = tail.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>");
+            if let Some(top) = stack.last_mut() {
+                top.text.push_str(&cdata[..end.unwrap_or(cdata.len())]);
+            }
+            i += 9 + end.map_or(cdata.len(), |end| end + 3);
+        } else if tail.starts_with("<?") || tail.starts_with("<!") {
+            i += tail.find('>').map_or(tail.len(), |end| end + 1);
+        } else if let Some(close) = tail.strip_prefix("</") {
+            let Some(end) = close.find('>') else {
+                // The input ended in the tag.
+                break;
+            };
+            let name = close[..end].trim();
+            // Elements left open in the one being closed end with it, like
+            // `<br>` in HTML. Closing tags of no open element are skipped.
+            if let Some(depth) = stack.iter().rposition(|e| e.name == name) {
+                while stack.len() > depth {
+                    let mut element = stack.pop().expect("depth < stack.len()");
+                    element.content.end = i;
+                    element.closed = true;
+                    attach(element, &mut stack, &mut roots);
+                }
+            }
+            i += 2 + end + 1;
+        } else {
+            match open_tag(tail) {
+                Tag::Open {
+                    name,
+                    attributes,
+                    self_closing,
+                    len,
+                } => {
+                    let element = Element {
+                        name,
+                        attributes,
+                        children: vec![],
+                        text: String::new(),
+                        content: i + len..i + len,
+                        closed: self_closing,
+                    };
+                    if self_closing {
+                        attach(element, &mut stack, &mut roots);
+                    } else {
+                        stack.push(element);
+                    }
+                    i += len;
+                }
+                Tag::Incomplete => break,
+                Tag::NotATag => {
+                    if let Some(top) = stack.last_mut() {
+                        top.text.push('<');
+                    }
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    while let Some(mut element) = stack.pop() {
+        element.content.end = input.len();
+        attach(element, &mut stack, &mut roots);
+    }
+    roots
+}
+
+enum Tag {
+    Open {
+        name: String,
+        attributes: Vec<(String, String)>,
+        self_closing: bool,
+        /// The length of the tag in the input.
+        len: usize,
+    },
+    /// The input ended in the tag.
+    Incomplete,
+    /// The `<` is just text, e.g. in `1 < 2`.
+    NotATag,
+}
+
+/// Reads the opening tag `tail` starts with, e.g. `<person name="Jo">`.
+fn open_tag(tail: &str) -> Tag {
+    let bytes = tail.as_bytes();
+    let is_name_byte = |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.' | b':');
+    let skip_whitespace = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+
+    if !bytes
+        .get(1)
+        .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+    {
+        return Tag::NotATag;
+    }
+    let mut i = 1;
+    while i < bytes.len() && is_name_byte(bytes[i]) {
+        i += 1;
+    }
+    let name = tail[1..i].to_string();
+    if bytes
+        .get(i)
+        .is_some_and(|b| !b.is_ascii_whitespace() && !matches!(b, b'>' | b'/'))
+    {
+        return Tag::NotATag;
+    }
+
+    let mut attributes = vec![];
+    loop {
+        i = skip_whitespace(i);
+        match bytes.get(i) {
+            None => return Tag::Incomplete,
+            Some(b'>') => {
+                return Tag::Open {
+                    name,
+                    attributes,
+                    self_closing: false,
+                    len: i + 1,
+                }
+            }
+            Some(b'/') => {
+                return match bytes.get(i + 1) {
+                    None => Tag::Incomplete,
+                    Some(b'>') => Tag::Open {
+                        name,
+                        attributes,
+                        self_closing: true,
+                        len: i + 2,
+                    },
+                    Some(_) => Tag::NotATag,
+                }
+            }
+            Some(_) => {}
+        }
+
+        let start = i;
+        while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && !matches!(bytes[i], b'=' | b'>' | b'/')
+        {
+            i += 1;
+        }
+        let key = tail[start..i].to_string();
+        i = skip_whitespace(i);
+        if bytes.get(i) != Some(&b'=') {
+            // `<input disabled>`
+            attributes.push((key, String::new()));
+            continue;
+        }
+        i = skip_whitespace(i + 1);
+        let value = match bytes.get(i) {
+            None => return Tag::Incomplete,
+            Some(&quote @ (b'"' | b'\'')) => {
+                let Some(end) = tail[i + 1..].find(quote as char) else {
+                    return Tag::Incomplete;
+                };
+                let value = &tail[i + 1..i + 1 + end];
+                i += end + 2;
+                value
+            }
+            Some(_) => {
+                let start = i;
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                    i += 1;
+                }
+                &tail[start..i]
+            }
+        };
+        attributes.push((key, decode(value)));
+    }
+}
+
+/// Replaces the character references in `text`, e.g. `&lt;` with `<`.
+/// Unknown ones are kept as they are.
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let reference = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                code => {
+                    let code = match code.strip_prefix("#x").or(code.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => code.strip_prefix('#').and_then(|d| d.parse().ok()),
+                    };
+                    code.and_then(char::from_u32)?
+                }
+            };
+            Some((c, end))
+        });
+        match reference {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_element() {
+        let input = r#"Sure!
+<person id="7">
+  <name>Jo &amp; Sam</name>
+  <!-- a comment -->
+  <hobby>chess</hobby>
+  <hobby><![CDATA[<b>running</b>]]></hobby>
+</person>
+That's all."#;
+        let values = parse(input).unwrap();
+        assert_eq!(values.len(), 1);
+        let Value::AnyOf(candidates, raw) = &values[0] else {
+            panic!("{:?}", values[0]);
+        };
+        assert!(raw.starts_with("<name>"), "{raw}");
+        assert_eq!(
+            candidates,
+            &[Value::Object(vec![
+                ("id".to_string(), string("7")),
+                ("name".to_string(), string("Jo & Sam")),
+                (
+                    "hobby".to_string(),
+                    Value::Array(vec![string("chess"), string("<b>running</b>")])
+                ),
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_list_and_wrapper() {
+        let values = parse("<answer><items><item>a</item><item>b</item></items></answer>").unwrap();
+        let Value::AnyOf(candidates, _) = &values[0] else {
+            panic!("{:?}", values[0]);
+        };
+        // The object, the list of its one child, and the child itself.
+        assert_eq!(candidates.len(), 3);
+        let Value::AnyOf(items, _) = &candidates[2] else {
+            panic!("{:?}", candidates[2]);
+        };
+        assert!(items.contains(&Value::Array(vec![string("a"), string("b")])));
+    }
+
+    #[test]
+    fn test_unclosed() {
+        let values = parse("<person><name>Jo</name><bio>Likes 1 < 2 and").unwrap();
+        let Value::FixedJson(person, fixes) = &values[0] else {
+            panic!("{:?}", values[0]);
+        };
+        assert_eq!(fixes, &[Fixes::Unclosed]);
+        let Value::AnyOf(candidates, _) = person.as_ref() else {
+            panic!("{:?}", person);
+        };
+        let Value::Object(fields) = &candidates[0] else {
+            panic!("{:?}", candidates[0]);
+        };
+        assert_eq!(fields[0], ("name".to_string(), string("Jo")));
+        assert_eq!(
+            fields[1],
+            (
+                "bio".to_string(),
+                Value::FixedJson(string("Likes 1 < 2 and").into(), vec![Fixes::Unclosed])
+            )
+        );
+    }
+
+    #[test]
+    fn test_no_elements() {
+        assert!(parse("Just a sentence, 1 < 2.").is_err());
+        assert!(parse("<").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+
+use crate::jsonish::Value;
+
+/// Parses `str` as a YAML mapping or sequence, as smaller models often
+/// answer in YAML instead of JSON. Scalars are left to the other parsers,
+/// as any text is a valid YAML string.
+pub fn parse(str: &str) -> Result<Value> {
+    let value: serde_yaml::Value = serde_yaml::from_str(str)?;
+    match value {
+        serde_yaml::Value::Mapping(_) | serde_yaml::Value::Sequence(_) => Ok(from_yaml(value)),
+        _ => anyhow::bail!("Not a YAML mapping or sequence"),
+    }
+}
+
+fn from_yaml(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Boolean(b),
+        serde_yaml::Value::Number(n) => {
+            let number = if let Some(n) = n.as_i64() {
+                Some(n.into())
+            } else if let Some(n) = n.as_u64() {
+                Some(n.into())
+            } else {
+                n.as_f64().and_then(serde_json::Number::from_f64)
+            };
+            // `.nan` and `.inf` have no JSON number.
+            number.map_or_else(|| Value::String(n.to_string()), Value::Number)
+        }
+        serde_yaml::Value::String(s) => Value::String(s),
+        serde_yaml::Value::Sequence(items) => {
+            Value::Array(items.into_iter().map(from_yaml).collect())
+        }
+        serde_yaml::Value::Mapping(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (key(k), from_yaml(v)))
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => from_yaml(tagged.value),
+    }
+}
+
+/// Keys like `1:` or `true:` are numbers and booleans in YAML, but field
+/// names here.
+fn key(key: serde_yaml::Value) -> String {
+    match from_yaml(key) {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping() {
+        let value = parse("name: Jo\nage: 42\ntags:\n  - a\n  - b\n1: one").unwrap();
+        assert_eq!(
+            value,
+            Value::Object(vec![
+                ("name".to_string(), Value::String("Jo".to_string())),
+                ("age".to_string(), Value::Number(42.into())),
+                (
+                    "tags".to_string(),
+                    Value::Array(vec![
+                        Value::String("a".to_string()),
+                        Value::String("b".to_string()),
+                    ])
+                ),
+                ("1".to_string(), Value::String("one".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_scalar() {
+        assert!(parse("Just a sentence.").is_err());
+        assert!(parse("42").is_err());
+    }
+}
@@ -9,6 +9,13 @@ use baml_types::BamlMap;
 pub enum Fixes {
     GreppedForJSON,
     InferredArray,
+    /// The input ended before the value did, e.g. a string without its
+    /// closing quote, so a streamed value may still grow.
+    Unclosed,
+    /// The value was written as YAML rather than JSON.
+    ParsedYaml,
+    /// The value was written as XML elements rather than JSON.
+    ParsedXml,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -150,35 +157,28 @@ impl std::fmt::Display for Value {
     }
 }
 
+impl From<serde_json::Value> for Value {
+    /// Moves the parsed tree over node by node, so strings and containers are
+    /// reused rather than re-deserialized at every level of nesting.
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Number(n) => Value::Number(n),
+            serde_json::Value::Bool(b) => Value::Boolean(b),
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Object(o) => {
+                Value::Object(o.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+            serde_json::Value::Array(a) => Value::Array(a.into_iter().map(Value::from).collect()),
+        }
+    }
+}
+
 impl<'de> serde::Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        match value {
-            serde_json::Value::String(s) => Ok(Value::String(s)),
-            serde_json::Value::Number(n) => Ok(Value::Number(n)),
-            serde_json::Value::Bool(b) => Ok(Value::Boolean(b)),
-            serde_json::Value::Null => Ok(Value::Null),
-            serde_json::Value::Object(o) => {
-                let mut map = Vec::new();
-                for (k, v) in o {
-                    let parsed_value =
-                        serde_json::from_value(v).map_err(serde::de::Error::custom)?;
-                    map.push((k, parsed_value));
-                }
-                Ok(Value::Object(map))
-            }
-            serde_json::Value::Array(a) => {
-                let mut vec = Vec::new();
-                for v in a {
-                    let parsed_value =
-                        serde_json::from_value(v).map_err(serde::de::Error::custom)?;
-                    vec.push(parsed_value);
-                }
-                Ok(Value::Array(vec))
-            }
-        }
+        serde_json::Value::deserialize(deserializer).map(Value::from)
     }
 }
@@ -20,13 +20,46 @@ pub fn from_str(
     target: &FieldType,
     raw_string: &str,
     allow_partials: bool,
+) -> Result<BamlValueWithFlags> {
+    parse_with_options(
+        of,
+        target,
+        raw_string,
+        allow_partials,
+        jsonish::ParseOptions::default(),
+    )
+}
+
+/// Like [`from_str`], for complete output the provider guarantees is valid
+/// JSON, e.g. with OpenAI's structured outputs. It's read as JSON only, and
+/// fails if it isn't.
+pub fn from_json_str(
+    of: &OutputFormatContent,
+    target: &FieldType,
+    raw_string: &str,
+) -> Result<BamlValueWithFlags> {
+    parse_with_options(
+        of,
+        target,
+        raw_string,
+        false,
+        jsonish::ParseOptions::json_only(),
+    )
+}
+
+fn parse_with_options(
+    of: &OutputFormatContent,
+    target: &FieldType,
+    raw_string: &str,
+    allow_partials: bool,
+    options: jsonish::ParseOptions,
 ) -> Result<BamlValueWithFlags> {
     if matches!(target, FieldType::Primitive(TypeValue::String)) {
         return Ok(BamlValueWithFlags::String(raw_string.to_string().into()));
     }
 
     // When the schema is just a string, i should really just return the raw_string w/o parsing it.
-    let mut value = jsonish::parse(raw_string, jsonish::ParseOptions::default())?;
+    let mut value = jsonish::parse(raw_string, options)?;
     // let schema = deserializer::schema::from_jsonish_value(&value, None);
 
     // See Note [Streaming Number Invalidation]
@@ -13,16 +13,19 @@ mod test_enum;
 mod test_lists;
 mod test_literals;
 mod test_maps;
+mod test_markdown_table;
 mod test_partials;
 mod test_unions;
+mod test_xml;
+mod test_yaml;
 
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use std::{
     collections::{HashMap, HashSet},
     path::PathBuf,
 };
 
-use baml_types::{BamlValue, EvaluationContext};
+use baml_types::{BamlValue, BoolStrings, EvaluationContext};
 use internal_baml_core::{
     internal_baml_diagnostics::SourceFile,
     ir::{repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, IRHelper, TypeValue},
@@ -30,7 +33,7 @@ use internal_baml_core::{
 };
 use serde_json::json;
 
-use crate::from_str;
+use crate::{from_json_str, from_str};
 
 fn load_test_ir(file_content: &str) -> IntermediateRepr {
     let mut schema = validate(
@@ -57,10 +60,26 @@ fn render_output_format(
 ) -> Result<OutputFormatContent> {
     let (enums, classes, recursive_classes) = relevant_data_models(ir, output, env_values)?;
 
+    let mut bool_strings = IndexMap::new();
+    let mut streaming = IndexMap::new();
+    for class in &classes {
+        for field in ir.find_class(class.name.real_name())?.walk_fields() {
+            let key = (class.name.real_name().to_string(), field.name().to_string());
+            if field.bool_strings() != BoolStrings::Strict {
+                bool_strings.insert(key.clone(), field.bool_strings());
+            }
+            if !field.streaming().is_default() {
+                streaming.insert(key, field.streaming());
+            }
+        }
+    }
+
     Ok(OutputFormatContent::target(output.clone())
         .enums(enums)
         .classes(classes)
         .recursive_classes(recursive_classes)
+        .bool_strings(bool_strings)
+        .streaming(streaming)
         .build())
 }
 
@@ -152,10 +171,12 @@ fn relevant_data_models<'a>(
                         .filter_map(|v| v.transpose())
                         .collect::<Result<Vec<_>>>()?;
 
+                    let walker = walker?;
                     enums.push(Enum {
-                        name: Name::new_with_alias(enm.to_string(), walker?.alias(env_values)?),
+                        name: Name::new_with_alias(enm.to_string(), walker.alias(env_values)?),
                         values,
                         constraints,
+                        matching: walker.matching(),
                     });
                 }
             }
@@ -790,3 +811,57 @@ fn partial_int_not_deleted() {
     // Note: This happens to parse as a List, but Null also seems appropriate.
     assert_eq!(baml_value, BamlValue::List(vec![]));
 }
+
+#[test]
+fn decimal_comma_number() {
+    let target = FieldType::Primitive(TypeValue::Float);
+    let output_format = OutputFormatContent::target(target.clone())
+        .number_locale(baml_types::NumberLocale::DecimalComma)
+        .build();
+    let res = from_str(&output_format, &target, r#""1.234,56 €""#, false).expect("Can parse");
+    assert!(res
+        .conditions()
+        .flags()
+        .iter()
+        .any(|flag| matches!(flag, crate::deserializer::deserialize_flags::Flag::StrippedUnit(unit) if unit == "€")));
+    let baml_value: BamlValue = res.into();
+    assert_eq!(baml_value, BamlValue::Float(1234.56));
+}
+
+#[test]
+fn bool_strings_flag_the_word() {
+    let ir = load_test_ir(
+        r#"
+class Answer {
+  ok bool @bool_strings(yes_no)
+}
+"#,
+    );
+    let target = FieldType::class("Answer");
+    let output_format = render_output_format(&ir, &target, &Default::default()).unwrap();
+    let res = from_str(&output_format, &target, r#"{"ok": "Yes"}"#, false).expect("Can parse");
+    let crate::BamlValueWithFlags::Class(_, _, fields) = &res else {
+        panic!("Expected a class, got {res:?}");
+    };
+    assert!(fields["ok"]
+        .conditions()
+        .flags()
+        .iter()
+        .any(|flag| matches!(flag, crate::deserializer::deserialize_flags::Flag::WordToBool(word) if word == "Yes")));
+}
+
+#[test]
+fn json_only_reads_json_as_is() {
+    let target = FieldType::List(Box::new(FieldType::Primitive(TypeValue::Int)));
+    let output_format = OutputFormatContent::target(target.clone()).build();
+    let res = from_json_str(&output_format, &target, "[1, 2]").expect("Can parse");
+    let baml_value: BamlValue = res.into();
+    assert_eq!(
+        baml_value,
+        BamlValue::List(vec![BamlValue::Int(1), BamlValue::Int(2)])
+    );
+
+    // `from_str` would find the list in the markdown or fix it.
+    assert!(from_json_str(&output_format, &target, "```json\n[1, 2]\n```").is_err());
+    assert!(from_json_str(&output_format, &target, "[1, 2").is_err());
+}
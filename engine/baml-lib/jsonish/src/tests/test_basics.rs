@@ -73,6 +73,36 @@ test_deserializer!(
     false
 );
 
+const BOOL_STRINGS_FILE: &str = r#"
+class Answers {
+  strict bool
+  yes_no bool @bool_strings(yes_no)
+  lenient bool[] @bool_strings(lenient)
+}
+"#;
+
+test_deserializer!(
+    test_bool_strings,
+    BOOL_STRINGS_FILE,
+    r#"{"strict": "true", "yes_no": "Yes", "lenient": ["off", 1, "enabled", "N"]}"#,
+    FieldType::class("Answers"),
+    {"strict": true, "yes_no": true, "lenient": [false, true, true, false]}
+);
+
+test_failing_deserializer!(
+    test_bool_strings_off_by_default,
+    BOOL_STRINGS_FILE,
+    r#"{"strict": "yes", "yes_no": "no", "lenient": []}"#,
+    FieldType::class("Answers")
+);
+
+test_failing_deserializer!(
+    test_bool_strings_yes_no_is_not_lenient,
+    BOOL_STRINGS_FILE,
+    r#"{"strict": false, "yes_no": "on", "lenient": []}"#,
+    FieldType::class("Answers")
+);
+
 test_failing_deserializer!(
     test_ambiguous_bool,
     EMPTY_FILE,
@@ -293,3 +293,91 @@ test_failing_deserializer!(
     "The answer is not car or car-2!",
     FieldType::Enum("Car".to_string())
 );
+
+const EXACT_ENUM_FILE: &str = r#"
+enum Priority {
+    HIGH @alias("urgent")
+    LOW @description("Can wait")
+    @@match(exact)
+}
+"#;
+
+test_deserializer!(
+    test_exact_match,
+    EXACT_ENUM_FILE,
+    r#""urgent""#,
+    FieldType::Enum("Priority".to_string()),
+    "HIGH"
+);
+
+test_deserializer!(
+    test_exact_match_description,
+    EXACT_ENUM_FILE,
+    "Can wait",
+    FieldType::Enum("Priority".to_string()),
+    "LOW"
+);
+
+test_failing_deserializer!(
+    test_exact_match_wrong_case,
+    EXACT_ENUM_FILE,
+    "Urgent",
+    FieldType::Enum("Priority".to_string())
+);
+
+test_failing_deserializer!(
+    test_exact_match_no_substring,
+    EXACT_ENUM_FILE,
+    "The priority is urgent",
+    FieldType::Enum("Priority".to_string())
+);
+
+test_failing_deserializer!(
+    test_alias_match_ignores_description,
+    r#"
+enum Priority {
+    HIGH @alias("urgent")
+    LOW @description("Can wait")
+    @@match(alias)
+}
+"#,
+    "Can wait",
+    FieldType::Enum("Priority".to_string())
+);
+
+const FUZZY_ENUM_FILE: &str = r#"
+enum Sentiment {
+    POSITIVE
+    NEGATIVE
+    NEUTRAL
+    @@match(fuzzy, { max_distance 2 })
+}
+"#;
+
+test_deserializer!(
+    test_fuzzy_match_typo,
+    FUZZY_ENUM_FILE,
+    "Postive",
+    FieldType::Enum("Sentiment".to_string()),
+    "POSITIVE"
+);
+
+test_failing_deserializer!(
+    test_fuzzy_match_too_far,
+    FUZZY_ENUM_FILE,
+    "Happy",
+    FieldType::Enum("Sentiment".to_string())
+);
+
+test_failing_deserializer!(
+    test_fuzzy_match_ambiguous,
+    r#"
+enum Size {
+    SMALL
+    SMALLER
+    @@match(fuzzy, { max_distance 1 })
+}
+"#,
+    "SMALLE",
+    FieldType::Enum("Size".to_string())
+);
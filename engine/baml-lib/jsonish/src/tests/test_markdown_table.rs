@@ -0,0 +1,69 @@
+use super::*;
+
+const PERSON_FILE: &str = r#"
+enum Role {
+  ADMIN
+  MEMBER
+}
+
+class Person {
+  first_name string
+  age int
+  role Role
+  email string? @alias("e-mail")
+}
+"#;
+
+test_deserializer!(
+    test_list_from_table,
+    PERSON_FILE,
+    r#"| first_name | age | role | e-mail |
+|---|---|---|---|
+| Jo | 42 | ADMIN | jo@example.com |
+| Sam | 7 | MEMBER | sam@example.com |
+"#,
+    FieldType::List(FieldType::Class("Person".to_string()).into()),
+    [
+        {"first_name": "Jo", "age": 42, "role": "ADMIN", "email": "jo@example.com"},
+        {"first_name": "Sam", "age": 7, "role": "MEMBER", "email": "sam@example.com"}
+    ]
+);
+
+test_deserializer!(
+    test_list_from_table_with_text,
+    PERSON_FILE,
+    r#"Here are the people I found:
+
+| First Name | Age | Role | E-mail | Notes |
+|:-----------|----:|------|--------|-------|
+| Jo | 42 | admin |  | Likes chess |
+| Sam | 7 | member | sam@example.com | |
+
+Let me know if you need more."#,
+    FieldType::List(FieldType::Class("Person".to_string()).into()),
+    [
+        {"first_name": "Jo", "age": 42, "role": "ADMIN", "email": null},
+        {"first_name": "Sam", "age": 7, "role": "MEMBER", "email": "sam@example.com"}
+    ]
+);
+
+test_deserializer!(
+    test_list_from_table_skips_bad_rows,
+    PERSON_FILE,
+    r#"first_name | age | role
+--- | --- | ---
+Jo | 42 | ADMIN
+Sam | seven | MEMBER"#,
+    FieldType::List(FieldType::Class("Person".to_string()).into()),
+    [
+        {"first_name": "Jo", "age": 42, "role": "ADMIN", "email": null}
+    ]
+);
+
+test_deserializer!(
+    test_string_from_table_is_unchanged,
+    PERSON_FILE,
+    "| first_name | age |\n|---|---|\n| Jo | 42 |",
+    FieldType::Primitive(TypeValue::String),
+    "| first_name | age |\n|---|---|\n| Jo | 42 |"
+);
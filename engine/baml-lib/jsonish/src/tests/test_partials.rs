@@ -357,3 +357,87 @@ test_partial_deserializer!(
     ]
   }
 );
+
+const STREAMING_BAML_FILE: &str = r#"
+class Vertex {
+  id string @stream.not_null
+  name string
+}
+
+class Graph {
+  vertices Vertex[]
+  summary string @stream.with_state
+  status string @stream.with_state
+}
+"#;
+
+test_partial_deserializer!(
+  test_partial_stream_not_null,
+  STREAMING_BAML_FILE,
+  r#"{"vertices": [{"id": "a", "name": "A"}, {"name": "B"#,
+  FieldType::Class("Graph".to_string()),
+  {
+    "vertices": [{"id": "a", "name": "A"}],
+    "summary": {"value": null, "state": "pending"},
+    "status": {"value": null, "state": "pending"}
+  }
+);
+
+test_partial_deserializer!(
+  test_partial_stream_with_state,
+  STREAMING_BAML_FILE,
+  r#"{"vertices": [], "summary": "Two", "status": "dra"#,
+  FieldType::Class("Graph".to_string()),
+  {
+    "vertices": [],
+    "summary": {"value": "Two", "state": "complete"},
+    "status": {"value": "dra", "state": "incomplete"}
+  }
+);
+
+#[test_log::test]
+fn test_partial_stream_states() {
+    let ir = load_test_ir(STREAMING_BAML_FILE);
+    let target = FieldType::Class("Graph".to_string());
+    let output_format = render_output_format(&ir, &target, &Default::default()).unwrap();
+    let value = from_str(
+        &output_format,
+        &target,
+        r#"{"vertices": [{"id": "a", "name": "A"}, {"id": "b", "name": "Bo"#,
+        true,
+    )
+    .unwrap();
+
+    let states = value
+        .stream_states()
+        .into_iter()
+        .map(|(path, state)| (path, state.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        states,
+        [
+            ("vertices", "incomplete"),
+            ("vertices.0", "complete"),
+            ("vertices.0.id", "complete"),
+            ("vertices.0.name", "complete"),
+            ("vertices.1", "incomplete"),
+            ("vertices.1.id", "complete"),
+            ("vertices.1.name", "incomplete"),
+            ("summary", "pending"),
+            ("status", "pending"),
+        ]
+        .map(|(path, state)| (path.to_string(), state))
+    );
+}
+
+test_deserializer!(
+  test_stream_attributes_leave_final_results_alone,
+  STREAMING_BAML_FILE,
+  r#"{"vertices": [{"id": "a", "name": "A"}], "summary": "One", "status": "done"}"#,
+  FieldType::Class("Graph".to_string()),
+  {
+    "vertices": [{"id": "a", "name": "A"}],
+    "summary": "One",
+    "status": "done"
+  }
+);
@@ -0,0 +1,75 @@
+use super::*;
+
+const PERSON_FILE: &str = r#"
+enum Role {
+  ADMIN
+  MEMBER
+}
+
+class Person {
+  name string
+  age int
+  role Role
+  hobbies string[]
+}
+"#;
+
+test_deserializer!(
+    test_class_from_xml,
+    PERSON_FILE,
+    r#"<Person role="admin">
+  <name>Jo &amp; Co</name>
+  <age>42</age>
+  <hobbies>
+    <item>chess</item>
+    <item>running</item>
+  </hobbies>
+</Person>"#,
+    FieldType::Class("Person".to_string()),
+    {"name": "Jo & Co", "age": 42, "role": "ADMIN", "hobbies": ["chess", "running"]}
+);
+
+test_deserializer!(
+    test_class_from_xml_in_answer_tag,
+    PERSON_FILE,
+    r#"<thinking>The user wants Jo's profile.</thinking>
+<answer>
+  <Person>
+    <name>Jo</name>
+    <age>42</age>
+    <role>MEMBER</role>
+    <hobbies>chess</hobbies>
+    <hobbies>running</hobbies>
+  </Person>
+</answer>"#,
+    FieldType::Class("Person".to_string()),
+    {"name": "Jo", "age": 42, "role": "MEMBER", "hobbies": ["chess", "running"]}
+);
+
+test_deserializer!(
+    test_list_from_xml,
+    PERSON_FILE,
+    r#"Here are the people:
+<Person name="Jo" age="42" role="ADMIN"><hobbies>chess</hobbies></Person>
+<Person name="Sam" age="7" role="MEMBER" />
+"#,
+    FieldType::List(FieldType::Class("Person".to_string()).into()),
+    [
+        {"name": "Jo", "age": 42, "role": "ADMIN", "hobbies": ["chess"]},
+        {"name": "Sam", "age": 7, "role": "MEMBER", "hobbies": []}
+    ]
+);
+
+test_deserializer!(
+    test_class_from_xml_in_markdown,
+    PERSON_FILE,
+    r#"```xml
+<Person>
+  <name><![CDATA[Jo <3]]></name>
+  <age>42</age>
+  <role>ADMIN</role>
+</Person>
+```"#,
+    FieldType::Class("Person".to_string()),
+    {"name": "Jo <3", "age": 42, "role": "ADMIN", "hobbies": []}
+);
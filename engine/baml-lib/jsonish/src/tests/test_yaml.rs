@@ -0,0 +1,71 @@
+use super::*;
+
+const PERSON_FILE: &str = r#"
+enum Role {
+  ADMIN
+  MEMBER
+}
+
+class Person {
+  name string
+  age int
+  role Role
+  hobbies string[]
+}
+"#;
+
+test_deserializer!(
+    test_class_from_yaml,
+    PERSON_FILE,
+    r#"name: Jo
+age: 42
+role: admin
+hobbies:
+  - chess
+  - running
+"#,
+    FieldType::Class("Person".to_string()),
+    {"name": "Jo", "age": 42, "role": "ADMIN", "hobbies": ["chess", "running"]}
+);
+
+test_deserializer!(
+    test_class_from_yaml_with_text,
+    PERSON_FILE,
+    r#"Here is the person:
+```yaml
+name: "Jo: the second"
+age: '42'
+role: MEMBER
+hobbies: [chess, running]
+```"#,
+    FieldType::Class("Person".to_string()),
+    {"name": "Jo: the second", "age": 42, "role": "MEMBER", "hobbies": ["chess", "running"]}
+);
+
+test_deserializer!(
+    test_list_from_yaml,
+    PERSON_FILE,
+    r#"- name: Jo
+  age: 42
+  role: ADMIN
+  hobbies: [chess]
+- name: Sam
+  age: 7
+  role: MEMBER
+  hobbies:
+    - lego
+"#,
+    FieldType::List(FieldType::Class("Person".to_string()).into()),
+    [
+        {"name": "Jo", "age": 42, "role": "ADMIN", "hobbies": ["chess"]},
+        {"name": "Sam", "age": 7, "role": "MEMBER", "hobbies": ["lego"]}
+    ]
+);
+
+test_deserializer!(
+    test_string_from_yaml_is_unchanged,
+    PERSON_FILE,
+    "name: Jo\nage: 42",
+    FieldType::Primitive(TypeValue::String),
+    "name: Jo\nage: 42"
+);
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::{AllowedRoleMetadata, FinishReasonFilter, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
+use crate::{AllowedRoleMetadata, FinishReasonFilter, HttpOptions, ImagePreprocessingOptions, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
 use anyhow::Result;
 
 use baml_types::{EvaluationContext, StringOr, UnresolvedValue};
@@ -18,6 +18,40 @@ pub struct UnresolvedAnthropic<Meta> {
     headers: IndexMap<String, StringOr>,
     properties: IndexMap<String, (Meta, UnresolvedValue<Meta>)>,
     finish_reason_filter: UnresolvedFinishReasonFilter,
+    image_preprocessing: Option<ImagePreprocessingOptions>,
+    http: HttpOptions,
+    capture_raw_response: bool,
+    user_id_tag: Option<StringOr>,
+    thinking_budget: Option<u64>,
+    cache_policy: CachePolicy,
+}
+
+/// Where Anthropic clients add prompt cache breakpoints on their own, set
+/// with the `cache_policy` option. `cache_control` set with `_.role()` is
+/// always sent as well.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Only where the prompt sets `cache_control`.
+    #[default]
+    None,
+    /// At the end of the system prompt.
+    System,
+    /// At the end of the system prompt and of the last message, so a call
+    /// that repeats the whole prompt and adds to it is cached too.
+    Prompt,
+}
+
+impl CachePolicy {
+    pub const NAMES: &'static [&'static str] = &["none", "system", "prompt"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "system" => Some(Self::System),
+            "prompt" => Some(Self::Prompt),
+            _ => None,
+        }
+    }
 }
 
 impl<Meta> UnresolvedAnthropic<Meta> {
@@ -39,6 +73,12 @@ impl<Meta> UnresolvedAnthropic<Meta> {
                 .map(|(k, (_, v))| (k.clone(), ((), v.without_meta())))
                 .collect(),
             finish_reason_filter: self.finish_reason_filter.clone(),
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            user_id_tag: self.user_id_tag.clone(),
+            thinking_budget: self.thinking_budget,
+            cache_policy: self.cache_policy,
         }
     }
 }
@@ -53,6 +93,17 @@ pub struct ResolvedAnthropic {
     pub properties: IndexMap<String, serde_json::Value>,
     pub proxy_url: Option<String>,
     pub finish_reason_filter: FinishReasonFilter,
+    pub image_preprocessing: Option<ImagePreprocessingOptions>,
+    pub http: HttpOptions,
+    /// Set with `capture_raw_response true`: responses keep the provider's
+    /// JSON body.
+    pub capture_raw_response: bool,
+    /// The runtime tag forwarded to the provider as the end-user id.
+    pub user_id_tag: Option<String>,
+    /// Tokens Claude may spend thinking before it answers, if extended
+    /// thinking is on.
+    pub thinking_budget: Option<u64>,
+    pub cache_policy: CachePolicy,
 }
 
 impl ResolvedAnthropic {
@@ -115,9 +166,24 @@ impl<Meta: Clone> UnresolvedAnthropic<Meta> {
                 .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
                 .collect::<Result<IndexMap<_, _>>>()?;
 
-            properties
+            // max_tokens includes the thinking budget, so leave the usual
+            // 4096 tokens for the answer.
+            let max_tokens = properties
                 .entry("max_tokens".to_string())
-                .or_insert(serde_json::json!(4096));
+                .or_insert(serde_json::json!(4096 + self.thinking_budget.unwrap_or(0)))
+                .as_u64();
+
+            if let Some(budget_tokens) = self.thinking_budget {
+                if max_tokens.is_some_and(|max_tokens| max_tokens <= budget_tokens) {
+                    anyhow::bail!(
+                        "max_tokens must be greater than thinking.budget_tokens ({budget_tokens})"
+                    );
+                }
+                properties.insert(
+                    "thinking".to_string(),
+                    serde_json::json!({ "type": "enabled", "budget_tokens": budget_tokens }),
+                );
+            }
 
             properties
         };
@@ -132,6 +198,12 @@ impl<Meta: Clone> UnresolvedAnthropic<Meta> {
             properties,
             proxy_url: super::helpers::get_proxy_url(ctx),
             finish_reason_filter: self.finish_reason_filter.resolve(ctx)?,
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            user_id_tag: self.user_id_tag.as_ref().map(|t| t.resolve(ctx)).transpose()?,
+            thinking_budget: self.thinking_budget,
+            cache_policy: self.cache_policy,
         })
     }
 
@@ -148,6 +220,12 @@ impl<Meta: Clone> UnresolvedAnthropic<Meta> {
         let supported_request_modes = properties.ensure_supported_request_modes();
         let headers = properties.ensure_headers().unwrap_or_default();
         let finish_reason_filter = properties.ensure_finish_reason_filter();
+        let image_preprocessing = properties.ensure_image_preprocessing();
+        let http = properties.ensure_http_options();
+        let capture_raw_response = properties.ensure_capture_raw_response();
+        let user_id_tag = properties.ensure_user_id_tag();
+        let thinking_budget = properties.ensure_thinking_budget();
+        let cache_policy = properties.ensure_cache_policy();
         let (properties, errors) = properties.finalize();
         if !errors.is_empty() {
             return Err(errors);
@@ -162,6 +240,12 @@ impl<Meta: Clone> UnresolvedAnthropic<Meta> {
             headers,
             properties,
             finish_reason_filter,
+            image_preprocessing,
+            http,
+            capture_raw_response,
+            user_id_tag,
+            thinking_budget,
+            cache_policy,
         })
     }
 }
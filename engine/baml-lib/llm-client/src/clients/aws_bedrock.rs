@@ -1,14 +1,15 @@
 use std::collections::HashSet;
 
-use crate::{AllowedRoleMetadata, FinishReasonFilter, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
+use crate::{AllowedRoleMetadata, FinishReasonFilter, ImagePreprocessingOptions, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
 use anyhow::Result;
 
-use baml_types::{EvaluationContext, StringOr};
+use baml_types::{EvaluationContext, StringOr, UnresolvedValue};
+use indexmap::IndexMap;
 
 use super::helpers::{Error, PropertyHandler};
 
-#[derive(Debug, Clone)]
-pub struct UnresolvedAwsBedrock {
+#[derive(Debug)]
+pub struct UnresolvedAwsBedrock<Meta> {
     model: Option<StringOr>,
     region: StringOr,
     access_key_id: StringOr,
@@ -18,6 +19,9 @@ pub struct UnresolvedAwsBedrock {
     supported_request_modes: SupportedRequestModes,
     inference_config: Option<UnresolvedInferenceConfiguration>,
     finish_reason_filter: UnresolvedFinishReasonFilter,
+    image_preprocessing: Option<ImagePreprocessingOptions>,
+    additional_model_request_fields: IndexMap<String, (Meta, UnresolvedValue<Meta>)>,
+    guardrail_config: Option<UnresolvedGuardrailConfiguration>,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +62,54 @@ pub struct InferenceConfiguration {
     pub stop_sequences: Option<Vec<String>>,
 }
 
+#[derive(Debug, Clone)]
+struct UnresolvedGuardrailConfiguration {
+    identifier: StringOr,
+    version: StringOr,
+    trace: Option<StringOr>,
+}
+
+impl UnresolvedGuardrailConfiguration {
+    pub fn resolve(&self, ctx: &EvaluationContext<'_>) -> Result<GuardrailConfiguration> {
+        let trace = self.trace.as_ref().map(|t| t.resolve(ctx)).transpose()?;
+        if let Some(trace) = &trace {
+            if !GuardrailConfiguration::TRACES.contains(&trace.as_str()) {
+                anyhow::bail!(
+                    "guardrail_config trace must be one of {}, got: {trace}",
+                    GuardrailConfiguration::TRACES.join(", ")
+                );
+            }
+        }
+        Ok(GuardrailConfiguration {
+            identifier: self.identifier.resolve(ctx)?,
+            version: self.version.resolve(ctx)?,
+            trace,
+        })
+    }
+
+    pub fn required_env_vars(&self) -> HashSet<String> {
+        let mut env_vars = self.identifier.required_env_vars();
+        env_vars.extend(self.version.required_env_vars());
+        if let Some(trace) = &self.trace {
+            env_vars.extend(trace.required_env_vars());
+        }
+        env_vars
+    }
+}
+
+/// The guardrail to apply to requests, from `guardrail_config`.
+#[derive(Debug)]
+pub struct GuardrailConfiguration {
+    pub identifier: String,
+    pub version: String,
+    /// `enabled` or `disabled`.
+    pub trace: Option<String>,
+}
+
+impl GuardrailConfiguration {
+    pub const TRACES: &'static [&'static str] = &["enabled", "disabled"];
+}
+
 pub struct ResolvedAwsBedrock {
     pub model: String,
     pub region: Option<String>,
@@ -68,6 +120,11 @@ pub struct ResolvedAwsBedrock {
     pub allowed_role_metadata: AllowedRoleMetadata,
     pub supported_request_modes: SupportedRequestModes,
     pub finish_reason_filter: FinishReasonFilter,
+    pub image_preprocessing: Option<ImagePreprocessingOptions>,
+    /// Provider-specific fields of the model, like `top_k`, passed through
+    /// as `additionalModelRequestFields`.
+    pub additional_model_request_fields: IndexMap<String, serde_json::Value>,
+    pub guardrail_config: Option<GuardrailConfiguration>,
 }
 
 impl ResolvedAwsBedrock {
@@ -90,7 +147,28 @@ impl ResolvedAwsBedrock {
     }
 }
 
-impl UnresolvedAwsBedrock {
+impl<Meta: Clone> UnresolvedAwsBedrock<Meta> {
+    pub fn without_meta(&self) -> UnresolvedAwsBedrock<()> {
+        UnresolvedAwsBedrock {
+            model: self.model.clone(),
+            region: self.region.clone(),
+            access_key_id: self.access_key_id.clone(),
+            secret_access_key: self.secret_access_key.clone(),
+            role_selection: self.role_selection.clone(),
+            allowed_role_metadata: self.allowed_role_metadata.clone(),
+            supported_request_modes: self.supported_request_modes.clone(),
+            inference_config: self.inference_config.clone(),
+            finish_reason_filter: self.finish_reason_filter.clone(),
+            image_preprocessing: self.image_preprocessing.clone(),
+            additional_model_request_fields: self
+                .additional_model_request_fields
+                .iter()
+                .map(|(k, (_, v))| (k.clone(), ((), v.without_meta())))
+                .collect(),
+            guardrail_config: self.guardrail_config.clone(),
+        }
+    }
+
     pub fn required_env_vars(&self) -> HashSet<String> {
         let mut env_vars = HashSet::new();
         if let Some(m) = self.model.as_ref() {
@@ -105,6 +183,14 @@ impl UnresolvedAwsBedrock {
         if let Some(c) = self.inference_config.as_ref() {
             env_vars.extend(c.required_env_vars())
         }
+        env_vars.extend(
+            self.additional_model_request_fields
+                .values()
+                .flat_map(|(_, v)| v.required_env_vars()),
+        );
+        if let Some(g) = self.guardrail_config.as_ref() {
+            env_vars.extend(g.required_env_vars())
+        }
         env_vars
     }
 
@@ -129,12 +215,21 @@ impl UnresolvedAwsBedrock {
                 .map(|c| c.resolve(ctx))
                 .transpose()?,
             finish_reason_filter: self.finish_reason_filter.resolve(ctx)?,
+            image_preprocessing: self.image_preprocessing.clone(),
+            additional_model_request_fields: self
+                .additional_model_request_fields
+                .iter()
+                .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
+                .collect::<Result<IndexMap<_, _>>>()?,
+            guardrail_config: self
+                .guardrail_config
+                .as_ref()
+                .map(|g| g.resolve(ctx))
+                .transpose()?,
         })
     }
 
-    pub fn create_from<Meta: Clone>(
-        mut properties: PropertyHandler<Meta>,
-    ) -> Result<Self, Vec<Error<Meta>>> {
+    pub fn create_from(mut properties: PropertyHandler<Meta>) -> Result<Self, Vec<Error<Meta>>> {
         let model = {
             // Add AWS Bedrock-specific validation logic here
             let model_id = properties.ensure_string("model_id", false);
@@ -233,6 +328,53 @@ impl UnresolvedAwsBedrock {
             Some(inference_config)
         };
         let finish_reason_filter = properties.ensure_finish_reason_filter();
+        let image_preprocessing = properties.ensure_image_preprocessing();
+        let additional_model_request_fields = properties
+            .ensure_map("additional_model_request_fields", false)
+            .map(|(_, map, _)| map)
+            .unwrap_or_default();
+        let guardrail_config = properties
+            .ensure_map("guardrail_config", false)
+            .and_then(|(key_span, map, _)| {
+                let mut identifier = None;
+                let mut version = None;
+                let mut trace = None;
+                for (k, (key_span, v)) in map.into_iter() {
+                    let slot = match k.as_str() {
+                        "guardrail_identifier" => &mut identifier,
+                        "guardrail_version" => &mut version,
+                        "trace" => &mut trace,
+                        _ => {
+                            properties.push_error(
+                                format!("unknown guardrail_config key: {k}"),
+                                key_span,
+                            );
+                            continue;
+                        }
+                    };
+                    match v.into_str() {
+                        Ok((s, _)) => *slot = Some(s),
+                        Err(e) => properties.push_error(
+                            format!("{k} must be a string: got {}", e.r#type()),
+                            e.meta().clone(),
+                        ),
+                    }
+                }
+                match (identifier, version) {
+                    (Some(identifier), Some(version)) => Some(UnresolvedGuardrailConfiguration {
+                        identifier,
+                        version,
+                        trace,
+                    }),
+                    _ => {
+                        properties.push_error(
+                            "guardrail_config requires guardrail_identifier and guardrail_version",
+                            key_span,
+                        );
+                        None
+                    }
+                }
+            });
 
         // TODO: Handle inference_configuration
         let errors = properties.finalize_empty();
@@ -250,6 +392,9 @@ impl UnresolvedAwsBedrock {
             supported_request_modes,
             inference_config,
             finish_reason_filter,
+            image_preprocessing,
+            additional_model_request_fields,
+            guardrail_config,
         })
     }
 }
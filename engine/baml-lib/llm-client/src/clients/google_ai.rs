@@ -3,13 +3,15 @@ use std::collections::HashSet;
 use crate::{AllowedRoleMetadata, SupportedRequestModes, UnresolvedAllowedRoleMetadata};
 use anyhow::Result;
 use crate::{
-    FinishReasonFilter, RolesSelection, UnresolvedFinishReasonFilter, UnresolvedRolesSelection
+    CandidateSelection, FinishReasonFilter, HttpOptions, ImagePreprocessingOptions, RolesSelection, UnresolvedFinishReasonFilter, UnresolvedRolesSelection
 };
 
 use baml_types::{EvaluationContext, StringOr, UnresolvedValue};
 use indexmap::IndexMap;
 
-use super::helpers::{Error, PropertyHandler, UnresolvedUrl};
+use super::helpers::{
+    request_gemini_logprobs, Error, PropertyHandler, UnresolvedGeminiTools, UnresolvedUrl,
+};
 
 #[derive(Debug)]
 pub struct UnresolvedGoogleAI<Meta> {
@@ -21,6 +23,12 @@ pub struct UnresolvedGoogleAI<Meta> {
     allowed_metadata: UnresolvedAllowedRoleMetadata,
     supported_request_modes: SupportedRequestModes,
     finish_reason_filter: UnresolvedFinishReasonFilter,
+    image_preprocessing: Option<ImagePreprocessingOptions>,
+    http: HttpOptions,
+    capture_raw_response: bool,
+    /// Built-in tools, sent as the request's `tools`.
+    tools: UnresolvedGeminiTools<Meta>,
+    candidate_selection: CandidateSelection,
     properties: IndexMap<String, (Meta, UnresolvedValue<Meta>)>,
 }
 
@@ -44,6 +52,11 @@ impl<Meta> UnresolvedGoogleAI<Meta> {
                 .map(|(k, (_, v))| (k.clone(), ((), v.without_meta())))
                 .collect::<IndexMap<_, _>>(),
             finish_reason_filter: self.finish_reason_filter.clone(),
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            tools: self.tools.without_meta(),
+            candidate_selection: self.candidate_selection,
         }
     }
 }
@@ -59,6 +72,12 @@ pub struct ResolvedGoogleAI {
     pub properties: IndexMap<String, serde_json::Value>,
     pub proxy_url: Option<String>,
     pub finish_reason_filter: FinishReasonFilter,
+    pub image_preprocessing: Option<ImagePreprocessingOptions>,
+    pub http: HttpOptions,
+    /// Set with `capture_raw_response true`: responses keep the provider's
+    /// JSON body.
+    pub capture_raw_response: bool,
+    pub candidate_selection: CandidateSelection,
 }
 
 impl ResolvedGoogleAI {
@@ -92,6 +111,7 @@ impl<Meta: Clone> UnresolvedGoogleAI<Meta> {
         env_vars.extend(self.role_selection.required_env_vars());
         env_vars.extend(self.allowed_metadata.required_env_vars());
         env_vars.extend(self.supported_request_modes.required_env_vars());
+        env_vars.extend(self.tools.required_env_vars());
         env_vars.extend(
             self.properties
                 .values()
@@ -119,6 +139,18 @@ impl<Meta: Clone> UnresolvedGoogleAI<Meta> {
             .map(|(k, v)| Ok((k.clone(), v.resolve(ctx)?)))
             .collect::<Result<IndexMap<_, _>>>()?;
 
+        let mut properties = self
+            .properties
+            .iter()
+            .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
+            .collect::<Result<IndexMap<_, _>>>()?;
+        if let Some(tools) = self.tools.resolve(ctx)? {
+            properties.insert("tools".to_string(), tools);
+        }
+        if self.candidate_selection == CandidateSelection::HighestLogprob {
+            request_gemini_logprobs(&mut properties);
+        }
+
         Ok(ResolvedGoogleAI {
             role_selection,
             api_key,
@@ -127,13 +159,13 @@ impl<Meta: Clone> UnresolvedGoogleAI<Meta> {
             headers,
             allowed_metadata: self.allowed_metadata.resolve(ctx)?,
             supported_request_modes: self.supported_request_modes.clone(),
-            properties: self
-                .properties
-                .iter()
-                .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
-                .collect::<Result<IndexMap<_, _>>>()?,
+            properties,
             proxy_url: super::helpers::get_proxy_url(ctx),
             finish_reason_filter: self.finish_reason_filter.resolve(ctx)?,
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            candidate_selection: self.candidate_selection,
         })
     }
 
@@ -153,6 +185,11 @@ impl<Meta: Clone> UnresolvedGoogleAI<Meta> {
         let supported_request_modes = properties.ensure_supported_request_modes();
         let headers = properties.ensure_headers().unwrap_or_default();
         let finish_reason_filter = properties.ensure_finish_reason_filter();
+        let image_preprocessing = properties.ensure_image_preprocessing();
+        let http = properties.ensure_http_options();
+        let capture_raw_response = properties.ensure_capture_raw_response();
+        let tools = properties.ensure_gemini_tools();
+        let candidate_selection = properties.ensure_candidate_selection();
         let (properties, errors) = properties.finalize();
 
         if !errors.is_empty() {
@@ -169,6 +206,11 @@ impl<Meta: Clone> UnresolvedGoogleAI<Meta> {
             supported_request_modes,
             properties,
             finish_reason_filter,
+            image_preprocessing,
+            http,
+            capture_raw_response,
+            tools,
+            candidate_selection,
         })
     }
 }
@@ -3,9 +3,11 @@ use std::{borrow::Cow, collections::HashSet};
 use baml_types::{GetEnvVar, StringOr, UnresolvedValue};
 use indexmap::IndexMap;
 
+use super::anthropic::CachePolicy;
+use super::round_robin::HealthCheck;
 use crate::{
-    SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter,
-    UnresolvedRolesSelection,
+    CandidateSelection, HttpOptions, ImagePreprocessingOptions, RateLimit, SupportedRequestModes,
+    UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection,
 };
 
 #[derive(Debug, Clone)]
@@ -30,6 +32,62 @@ impl UnresolvedUrl {
     }
 }
 
+/// Asks Gemini for the mean logprob of each candidate, which
+/// `candidate_selection highest_logprob` compares.
+pub(crate) fn request_gemini_logprobs(properties: &mut IndexMap<String, serde_json::Value>) {
+    if let serde_json::Value::Object(config) = properties
+        .entry("generationConfig".to_string())
+        .or_insert_with(|| serde_json::json!({}))
+    {
+        config
+            .entry("responseLogprobs")
+            .or_insert(serde_json::json!(true));
+    }
+}
+
+/// The built-in tools Gemini models can use.
+pub const GEMINI_TOOLS: &[&str] = &["google_search", "google_search_retrieval", "code_execution"];
+
+/// `tools { google_search {} code_execution {} }` on google-ai and vertex
+/// clients, keyed by tool with the tool's options.
+#[derive(Debug)]
+pub struct UnresolvedGeminiTools<Meta>(IndexMap<String, UnresolvedValue<Meta>>);
+
+impl<Meta: Clone> UnresolvedGeminiTools<Meta> {
+    pub fn without_meta(&self) -> UnresolvedGeminiTools<()> {
+        UnresolvedGeminiTools(
+            self.0
+                .iter()
+                .map(|(k, v)| (k.clone(), v.without_meta()))
+                .collect(),
+        )
+    }
+
+    pub fn required_env_vars(&self) -> HashSet<String> {
+        self.0
+            .values()
+            .flat_map(|v| v.required_env_vars())
+            .collect()
+    }
+
+    /// The `tools` field of the request body, `None` if no tools are enabled.
+    pub fn resolve(&self, ctx: &impl GetEnvVar) -> anyhow::Result<Option<serde_json::Value>> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        let tools = self
+            .0
+            .iter()
+            .map(|(name, options)| {
+                let mut tool = serde_json::Map::new();
+                tool.insert(name.clone(), options.resolve_serde(ctx)?);
+                Ok(serde_json::Value::Object(tool))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Some(serde_json::Value::Array(tools)))
+    }
+}
+
 pub struct Error<Meta> {
     pub message: String,
     pub span: Meta,
@@ -293,6 +351,335 @@ impl<Meta: Clone> PropertyHandler<Meta> {
         }
     }
 
+    /// `user_id_tag` names the runtime tag to forward to the provider as the
+    /// end-user id, e.g. OpenAI's `user`.
+    pub fn ensure_user_id_tag(&mut self) -> Option<StringOr> {
+        self.ensure_string("user_id_tag", false).map(|(_, v, _)| v)
+    }
+
+    /// `capture_raw_response true` keeps the provider's JSON body on each
+    /// response, next to the text BAML parses.
+    pub fn ensure_capture_raw_response(&mut self) -> bool {
+        self.ensure_bool("capture_raw_response", false)
+            .is_some_and(|(_, value, _)| value)
+    }
+
+    /// `logprobs true` asks for the log probability of each output token, and
+    /// `top_logprobs` for that many of the most likely alternatives at each
+    /// position. Returns the number of alternatives if logprobs are on.
+    pub fn ensure_logprobs(&mut self) -> Option<u8> {
+        let logprobs = self.ensure_bool("logprobs", false);
+        let top_logprobs = self.ensure_int("top_logprobs", false);
+
+        let top_logprobs = match top_logprobs {
+            Some((_, value, span)) => match u8::try_from(value) {
+                Ok(value) if value <= 20 => Some((value, span)),
+                _ => {
+                    self.push_error("top_logprobs must be between 0 and 20", span);
+                    None
+                }
+            },
+            None => None,
+        };
+        match (logprobs, top_logprobs) {
+            (Some((_, true, _)), top_logprobs) => Some(top_logprobs.map_or(0, |(n, _)| n)),
+            (_, Some((_, span))) => {
+                self.push_error("top_logprobs requires logprobs true", span);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// `tools true` asks for the function's output as a tool call instead of
+    /// in the message text.
+    pub fn ensure_output_tool(&mut self) -> bool {
+        self.ensure_bool("tools", false)
+            .is_some_and(|(_, tools, _)| tools)
+    }
+
+    /// `candidate_selection` picks between the candidates of clients that ask
+    /// for more than one.
+    pub fn ensure_candidate_selection(&mut self) -> CandidateSelection {
+        let Some((_, value, span)) = self.ensure_string("candidate_selection", false) else {
+            return CandidateSelection::default();
+        };
+        let selection = match &value {
+            StringOr::Value(name) => CandidateSelection::from_name(name),
+            _ => None,
+        };
+        selection.unwrap_or_else(|| {
+            self.push_error(
+                format!(
+                    "candidate_selection must be one of: {}",
+                    CandidateSelection::NAMES.join(", ")
+                ),
+                span,
+            );
+            CandidateSelection::default()
+        })
+    }
+
+    /// `thinking { budget_tokens 2048 }` turns on extended thinking for
+    /// Anthropic models. Anthropic requires a budget of at least 1024 tokens.
+    pub fn ensure_thinking_budget(&mut self) -> Option<u64> {
+        let (_, options, span) = self.ensure_map("thinking", false)?;
+
+        let mut options = PropertyHandler::new(options, span);
+        let budget_tokens =
+            options
+                .ensure_int("budget_tokens", true)
+                .and_then(|(_, value, span)| match u64::try_from(value) {
+                    Ok(value) if value >= 1024 => Some(value),
+                    _ => {
+                        options.push_error("budget_tokens must be at least 1024", span);
+                        None
+                    }
+                });
+        self.errors.extend(options.finalize_empty());
+        budget_tokens
+    }
+
+    /// `cache_policy` is where Anthropic clients add prompt cache
+    /// breakpoints.
+    pub fn ensure_cache_policy(&mut self) -> CachePolicy {
+        let Some((_, value, span)) = self.ensure_string("cache_policy", false) else {
+            return CachePolicy::default();
+        };
+        let policy = match &value {
+            StringOr::Value(name) => CachePolicy::from_name(name),
+            _ => None,
+        };
+        policy.unwrap_or_else(|| {
+            self.push_error(
+                format!(
+                    "cache_policy must be one of: {}",
+                    CachePolicy::NAMES.join(", ")
+                ),
+                span,
+            );
+            CachePolicy::default()
+        })
+    }
+
+    /// `image_preprocessing` is either `true`, to use the provider's
+    /// defaults, or a map of `max_dimension`, `jpeg_quality` and
+    /// `convert_heic` overrides.
+    pub fn ensure_image_preprocessing(&mut self) -> Option<ImagePreprocessingOptions> {
+        let (key_span, value) = self.ensure_any("image_preprocessing")?;
+        if let Some(enabled) = value.as_bool() {
+            return enabled.then(ImagePreprocessingOptions::default);
+        }
+        let Ok((options, span)) = value.into_map() else {
+            self.push_error(
+                "image_preprocessing must be true, false, or a map of max_dimension, jpeg_quality and convert_heic",
+                key_span,
+            );
+            return None;
+        };
+
+        let mut options = PropertyHandler::new(options, span);
+        let max_dimension =
+            options
+                .ensure_int("max_dimension", false)
+                .and_then(|(_, value, span)| match u32::try_from(value) {
+                    Ok(value) if value > 0 => Some(value),
+                    _ => {
+                        options.push_error("max_dimension must be a positive integer", span);
+                        None
+                    }
+                });
+        let jpeg_quality = options.ensure_int("jpeg_quality", false).and_then(
+            |(_, value, span)| match u8::try_from(value) {
+                Ok(value) if (1..=100).contains(&value) => Some(value),
+                _ => {
+                    options.push_error("jpeg_quality must be between 1 and 100", span);
+                    None
+                }
+            },
+        );
+        let convert_heic = options
+            .ensure_bool("convert_heic", false)
+            .map(|(_, value, _)| value);
+        self.errors.extend(options.finalize_empty());
+
+        Some(ImagePreprocessingOptions {
+            max_dimension,
+            jpeg_quality,
+            convert_heic,
+        })
+    }
+
+    /// `http` is a map of `compress_requests`, `keep_alive_interval_ms`,
+    /// `pool_idle_timeout_ms`, `request_timeout_ms`, `connect_timeout_ms`,
+    /// `requests_per_minute` and `burst`.
+    pub fn ensure_http_options(&mut self) -> HttpOptions {
+        let Some((_, options, span)) = self.ensure_map("http", false) else {
+            return HttpOptions::default();
+        };
+
+        let mut options = PropertyHandler::new(options, span);
+        let compress_requests = options
+            .ensure_bool("compress_requests", false)
+            .is_some_and(|(_, value, _)| value);
+        let ensure_ms = |options: &mut PropertyHandler<Meta>, key: &str| {
+            options
+                .ensure_int(key, false)
+                .and_then(|(_, value, span)| match u64::try_from(value) {
+                    Ok(value) => Some(value),
+                    Err(_) => {
+                        options.push_error(format!("{key} must not be negative"), span);
+                        None
+                    }
+                })
+        };
+        let keep_alive_interval_ms = ensure_ms(&mut options, "keep_alive_interval_ms");
+        let pool_idle_timeout_ms = ensure_ms(&mut options, "pool_idle_timeout_ms");
+        let ensure_timeout = |options: &mut PropertyHandler<Meta>, key: &str| {
+            options
+                .ensure_int(key, false)
+                .and_then(|(_, value, span)| match u64::try_from(value) {
+                    Ok(value) if value > 0 => Some(value),
+                    _ => {
+                        options.push_error(format!("{key} must be positive"), span);
+                        None
+                    }
+                })
+        };
+        let request_timeout_ms = ensure_timeout(&mut options, "request_timeout_ms");
+        let connect_timeout_ms = ensure_timeout(&mut options, "connect_timeout_ms");
+        let ensure_positive = |options: &mut PropertyHandler<Meta>, key: &str| {
+            options
+                .ensure_int(key, false)
+                .and_then(|(_, value, span)| match u32::try_from(value) {
+                    Ok(value) if value > 0 => Some((value, span)),
+                    _ => {
+                        options.push_error(format!("{key} must be positive"), span);
+                        None
+                    }
+                })
+        };
+        let requests_per_minute = ensure_positive(&mut options, "requests_per_minute");
+        let burst = ensure_positive(&mut options, "burst");
+        let rate_limit = match (requests_per_minute, burst) {
+            (Some((requests_per_minute, _)), burst) => Some(RateLimit {
+                requests_per_minute,
+                // A second's worth of requests.
+                burst: burst.map_or((requests_per_minute / 60).max(1), |(burst, _)| burst),
+            }),
+            (None, Some((_, span))) => {
+                options.push_error("burst needs requests_per_minute", span);
+                None
+            }
+            (None, None) => None,
+        };
+        self.errors.extend(options.finalize_empty());
+
+        HttpOptions {
+            compress_requests,
+            keep_alive_interval_ms,
+            pool_idle_timeout_ms,
+            request_timeout_ms,
+            connect_timeout_ms,
+            rate_limit,
+        }
+    }
+
+    /// `weights [3, 1]`: one positive integer per client of the strategy.
+    pub fn ensure_weights(&mut self, strategy_len: Option<usize>) -> Option<Vec<u32>> {
+        let (_, values, span) = self.ensure_array("weights", false)?;
+        if let Some(len) = strategy_len.filter(|len| *len != values.len()) {
+            self.push_error(
+                format!(
+                    "weights must have one entry per client in strategy: expected {len}, got {}",
+                    values.len()
+                ),
+                span,
+            );
+            return None;
+        }
+        let weights = values
+            .iter()
+            .map(
+                |value| match value.as_numeric().and_then(|n| n.parse::<u32>().ok()) {
+                    Some(weight) if weight > 0 => Some(weight),
+                    _ => {
+                        self.push_error("weights must be positive integers", value.meta().clone());
+                        None
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+        weights.into_iter().collect()
+    }
+
+    /// `health_check` is either `true`, for the defaults, or a map of `window`
+    /// and `max_error_rate` overrides.
+    pub fn ensure_health_check(&mut self) -> Option<HealthCheck> {
+        let (key_span, value) = self.ensure_any("health_check")?;
+        if let Some(enabled) = value.as_bool() {
+            return enabled.then(HealthCheck::default);
+        }
+        let Ok((options, span)) = value.into_map() else {
+            self.push_error(
+                "health_check must be true, false, or a map of window and max_error_rate",
+                key_span,
+            );
+            return None;
+        };
+
+        let mut options = PropertyHandler::new(options, span);
+        let mut health_check = HealthCheck::default();
+        if let Some((_, window, span)) = options.ensure_int("window", false) {
+            match usize::try_from(window) {
+                Ok(window) if window > 0 => health_check.window = window,
+                _ => options.push_error("window must be a positive integer", span),
+            }
+        }
+        if let Some((key_span, value)) = options.ensure_any("max_error_rate") {
+            match value.as_numeric().and_then(|n| n.parse::<f64>().ok()) {
+                Some(rate) if (0.0..=1.0).contains(&rate) => health_check.max_error_rate = rate,
+                _ => {
+                    options.push_error("max_error_rate must be a number between 0 and 1", key_span)
+                }
+            }
+        }
+        let errors = options.finalize_empty();
+        let valid = errors.is_empty();
+        self.errors.extend(errors);
+
+        valid.then_some(health_check)
+    }
+
+    pub fn ensure_gemini_tools(&mut self) -> UnresolvedGeminiTools<Meta> {
+        let Some((_, tools, _)) = self.ensure_map("tools", false) else {
+            return UnresolvedGeminiTools(IndexMap::new());
+        };
+
+        let mut valid = IndexMap::new();
+        for (name, (key_span, options)) in tools {
+            if !GEMINI_TOOLS.contains(&name.as_str()) {
+                self.push_error(
+                    format!(
+                        "Unknown tool: {name}. Supported tools are: {}",
+                        GEMINI_TOOLS.join(", ")
+                    ),
+                    key_span,
+                );
+                continue;
+            }
+            if options.as_map().is_none() {
+                self.push_error(
+                    format!("{name} must be a map of options, e.g. {name} {{}}"),
+                    options.meta().clone(),
+                );
+                continue;
+            }
+            valid.insert(name, options);
+        }
+        UnresolvedGeminiTools(valid)
+    }
+
     pub fn ensure_any(&mut self, key: &str) -> Option<(Meta, UnresolvedValue<Meta>)> {
         self.options.shift_remove(key)
     }
@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+
+use crate::{
+    AllowedRoleMetadata, FinishReasonFilter, HttpOptions, ImagePreprocessingOptions,
+    RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata,
+    UnresolvedFinishReasonFilter, UnresolvedRolesSelection,
+};
+use anyhow::Result;
+
+use baml_types::{EvaluationContext, StringOr, UnresolvedValue};
+use indexmap::IndexMap;
+
+use super::helpers::{Error, PropertyHandler, UnresolvedUrl};
+
+#[derive(Debug)]
+pub struct UnresolvedMistral<Meta> {
+    base_url: UnresolvedUrl,
+    api_key: StringOr,
+    role_selection: UnresolvedRolesSelection,
+    allowed_metadata: UnresolvedAllowedRoleMetadata,
+    supported_request_modes: SupportedRequestModes,
+    headers: IndexMap<String, StringOr>,
+    properties: IndexMap<String, (Meta, UnresolvedValue<Meta>)>,
+    finish_reason_filter: UnresolvedFinishReasonFilter,
+    image_preprocessing: Option<ImagePreprocessingOptions>,
+    http: HttpOptions,
+    capture_raw_response: bool,
+    safe_prompt: Option<bool>,
+    tools: bool,
+}
+
+impl<Meta> UnresolvedMistral<Meta> {
+    pub fn without_meta(&self) -> UnresolvedMistral<()> {
+        UnresolvedMistral {
+            base_url: self.base_url.clone(),
+            api_key: self.api_key.clone(),
+            role_selection: self.role_selection.clone(),
+            allowed_metadata: self.allowed_metadata.clone(),
+            supported_request_modes: self.supported_request_modes.clone(),
+            headers: self
+                .headers
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            properties: self
+                .properties
+                .iter()
+                .map(|(k, (_, v))| (k.clone(), ((), v.without_meta())))
+                .collect(),
+            finish_reason_filter: self.finish_reason_filter.clone(),
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            safe_prompt: self.safe_prompt,
+            tools: self.tools,
+        }
+    }
+}
+
+pub struct ResolvedMistral {
+    pub base_url: String,
+    pub api_key: String,
+    role_selection: RolesSelection,
+    pub allowed_metadata: AllowedRoleMetadata,
+    pub supported_request_modes: SupportedRequestModes,
+    pub headers: IndexMap<String, String>,
+    pub properties: IndexMap<String, serde_json::Value>,
+    pub proxy_url: Option<String>,
+    pub finish_reason_filter: FinishReasonFilter,
+    pub image_preprocessing: Option<ImagePreprocessingOptions>,
+    pub http: HttpOptions,
+    /// Set with `capture_raw_response true`: responses keep the provider's
+    /// JSON body.
+    pub capture_raw_response: bool,
+    /// Set with `tools true`: calls ask for the function's output as a tool
+    /// call with the output type as its parameters.
+    pub tools: bool,
+}
+
+impl ResolvedMistral {
+    pub fn allowed_roles(&self) -> Vec<String> {
+        self.role_selection.allowed_or_else(|| {
+            vec![
+                "system".to_string(),
+                "user".to_string(),
+                "assistant".to_string(),
+            ]
+        })
+    }
+
+    pub fn default_role(&self) -> String {
+        self.role_selection.default_or_else(|| {
+            let allowed_roles = self.allowed_roles();
+            if allowed_roles.contains(&"user".to_string()) {
+                "user".to_string()
+            } else {
+                allowed_roles
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| "user".to_string())
+            }
+        })
+    }
+
+    pub fn supports_streaming(&self) -> bool {
+        self.supported_request_modes.stream.unwrap_or(true)
+    }
+}
+
+impl<Meta: Clone> UnresolvedMistral<Meta> {
+    pub fn required_env_vars(&self) -> HashSet<String> {
+        let mut env_vars = HashSet::new();
+        env_vars.extend(self.base_url.required_env_vars());
+        env_vars.extend(self.api_key.required_env_vars());
+        env_vars.extend(self.role_selection.required_env_vars());
+        env_vars.extend(self.allowed_metadata.required_env_vars());
+        env_vars.extend(self.supported_request_modes.required_env_vars());
+        env_vars.extend(self.headers.values().flat_map(StringOr::required_env_vars));
+        env_vars.extend(
+            self.properties
+                .values()
+                .flat_map(|(_, v)| v.required_env_vars()),
+        );
+        env_vars
+    }
+
+    pub fn resolve(&self, ctx: &EvaluationContext<'_>) -> Result<ResolvedMistral> {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), v.resolve(ctx)?)))
+            .collect::<Result<IndexMap<_, _>>>()?;
+
+        let mut properties = self
+            .properties
+            .iter()
+            .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
+            .collect::<Result<IndexMap<_, _>>>()?;
+        if let Some(safe_prompt) = self.safe_prompt {
+            properties.insert("safe_prompt".to_string(), safe_prompt.into());
+        }
+
+        Ok(ResolvedMistral {
+            base_url: self.base_url.resolve(ctx)?,
+            api_key: self.api_key.resolve(ctx)?,
+            role_selection: self.role_selection.resolve(ctx)?,
+            allowed_metadata: self.allowed_metadata.resolve(ctx)?,
+            supported_request_modes: self.supported_request_modes.clone(),
+            headers,
+            properties,
+            proxy_url: super::helpers::get_proxy_url(ctx),
+            finish_reason_filter: self.finish_reason_filter.resolve(ctx)?,
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            tools: self.tools,
+        })
+    }
+
+    pub fn create_from(mut properties: PropertyHandler<Meta>) -> Result<Self, Vec<Error<Meta>>> {
+        let base_url = properties
+            .ensure_base_url_with_default(UnresolvedUrl::new_static("https://api.mistral.ai/v1"));
+        let api_key = properties
+            .ensure_api_key()
+            .unwrap_or_else(|| StringOr::EnvVar("MISTRAL_API_KEY".to_string()));
+
+        let role_selection = properties.ensure_roles_selection();
+        let allowed_metadata = properties.ensure_allowed_metadata();
+        let supported_request_modes = properties.ensure_supported_request_modes();
+        let headers = properties.ensure_headers().unwrap_or_default();
+        let finish_reason_filter = properties.ensure_finish_reason_filter();
+        let image_preprocessing = properties.ensure_image_preprocessing();
+        let http = properties.ensure_http_options();
+        let capture_raw_response = properties.ensure_capture_raw_response();
+        let safe_prompt = properties
+            .ensure_bool("safe_prompt", false)
+            .map(|(_, safe_prompt, _)| safe_prompt);
+        let tools = properties.ensure_output_tool();
+        let (properties, errors) = properties.finalize();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Self {
+            base_url,
+            api_key,
+            role_selection,
+            allowed_metadata,
+            supported_request_modes,
+            headers,
+            properties,
+            finish_reason_filter,
+            image_preprocessing,
+            http,
+            capture_raw_response,
+            safe_prompt,
+            tools,
+        })
+    }
+}
@@ -9,8 +9,10 @@ mod helpers;
 
 pub mod anthropic;
 pub mod aws_bedrock;
+pub mod cohere;
 pub mod fallback;
 pub mod google_ai;
+pub mod mistral;
 pub mod openai;
 pub mod round_robin;
 pub mod vertex;
@@ -19,9 +21,11 @@ pub mod vertex;
 pub enum UnresolvedClientProperty<Meta> {
     OpenAI(openai::UnresolvedOpenAI<Meta>),
     Anthropic(anthropic::UnresolvedAnthropic<Meta>),
-    AWSBedrock(aws_bedrock::UnresolvedAwsBedrock),
+    AWSBedrock(aws_bedrock::UnresolvedAwsBedrock<Meta>),
     Vertex(vertex::UnresolvedVertex<Meta>),
     GoogleAI(google_ai::UnresolvedGoogleAI<Meta>),
+    Mistral(mistral::UnresolvedMistral<Meta>),
+    Cohere(cohere::UnresolvedCohere<Meta>),
     RoundRobin(round_robin::UnresolvedRoundRobin<Meta>),
     Fallback(fallback::UnresolvedFallback<Meta>),
 }
@@ -32,6 +36,8 @@ pub enum ResolvedClientProperty {
     AWSBedrock(aws_bedrock::ResolvedAwsBedrock),
     Vertex(vertex::ResolvedVertex),
     GoogleAI(google_ai::ResolvedGoogleAI),
+    Mistral(mistral::ResolvedMistral),
+    Cohere(cohere::ResolvedCohere),
     RoundRobin(round_robin::ResolvedRoundRobin),
     Fallback(fallback::ResolvedFallback),
 }
@@ -46,6 +52,8 @@ impl ResolvedClientProperty {
             ResolvedClientProperty::AWSBedrock(_) => "aws-bedrock",
             ResolvedClientProperty::Vertex(_) => "vertex",
             ResolvedClientProperty::GoogleAI(_) => "google-ai",
+            ResolvedClientProperty::Mistral(_) => "mistral",
+            ResolvedClientProperty::Cohere(_) => "cohere",
         }
     }
 }
@@ -58,6 +66,8 @@ impl<Meta: Clone> UnresolvedClientProperty<Meta> {
             UnresolvedClientProperty::AWSBedrock(a) => a.required_env_vars(),
             UnresolvedClientProperty::Vertex(v) => v.required_env_vars(),
             UnresolvedClientProperty::GoogleAI(g) => g.required_env_vars(),
+            UnresolvedClientProperty::Mistral(m) => m.required_env_vars(),
+            UnresolvedClientProperty::Cohere(c) => c.required_env_vars(),
             UnresolvedClientProperty::RoundRobin(r) => r.required_env_vars(),
             UnresolvedClientProperty::Fallback(f) => f.required_env_vars(),
         }
@@ -84,6 +94,12 @@ impl<Meta: Clone> UnresolvedClientProperty<Meta> {
             UnresolvedClientProperty::GoogleAI(g) => {
                 g.resolve(ctx).map(ResolvedClientProperty::GoogleAI)
             }
+            UnresolvedClientProperty::Mistral(m) => {
+                m.resolve(ctx).map(ResolvedClientProperty::Mistral)
+            }
+            UnresolvedClientProperty::Cohere(c) => {
+                c.resolve(ctx).map(ResolvedClientProperty::Cohere)
+            }
             UnresolvedClientProperty::RoundRobin(r) => {
                 r.resolve(ctx).map(ResolvedClientProperty::RoundRobin)
             }
@@ -102,7 +118,7 @@ impl<Meta: Clone> UnresolvedClientProperty<Meta> {
                 UnresolvedClientProperty::Anthropic(a.without_meta())
             }
             UnresolvedClientProperty::AWSBedrock(a) => {
-                UnresolvedClientProperty::AWSBedrock(a.clone())
+                UnresolvedClientProperty::AWSBedrock(a.without_meta())
             }
             UnresolvedClientProperty::Vertex(v) => {
                 UnresolvedClientProperty::Vertex(v.without_meta())
@@ -110,6 +126,12 @@ impl<Meta: Clone> UnresolvedClientProperty<Meta> {
             UnresolvedClientProperty::GoogleAI(g) => {
                 UnresolvedClientProperty::GoogleAI(g.without_meta())
             }
+            UnresolvedClientProperty::Mistral(m) => {
+                UnresolvedClientProperty::Mistral(m.without_meta())
+            }
+            UnresolvedClientProperty::Cohere(c) => {
+                UnresolvedClientProperty::Cohere(c.without_meta())
+            }
             UnresolvedClientProperty::RoundRobin(r) => {
                 UnresolvedClientProperty::RoundRobin(r.without_meta())
             }
@@ -143,11 +165,57 @@ impl crate::ClientProvider {
             crate::ClientProvider::Vertex => {
                 UnresolvedClientProperty::Vertex(vertex::UnresolvedVertex::create_from(properties)?)
             }
+            crate::ClientProvider::Mistral => UnresolvedClientProperty::Mistral(
+                mistral::UnresolvedMistral::create_from(properties)?,
+            ),
+            crate::ClientProvider::Cohere => {
+                UnresolvedClientProperty::Cohere(cohere::UnresolvedCohere::create_from(properties)?)
+            }
             crate::ClientProvider::Strategy(s) => s.create_from(properties)?,
         })
     }
+
+    /// Parses the options of a `client<embedding>`. These are the options of
+    /// the provider's LLM clients, less the ones about generating text.
+    pub fn parse_embedding_property<Meta: Clone>(
+        &self,
+        mut properties: PropertyHandler<Meta>,
+    ) -> Result<UnresolvedClientProperty<Meta>, Vec<helpers::Error<Meta>>> {
+        if !self.supports_embeddings() {
+            properties.push_option_error(format!(
+                "Embedding clients don't support the {self} provider. Supported providers are: {}",
+                crate::ClientProvider::embedding_providers().join(", ")
+            ));
+            return Err(properties.finalize().1);
+        }
+        for key in LLM_ONLY_OPTIONS {
+            if let Some((key_span, _)) = properties.ensure_any(key) {
+                properties.push_error(
+                    format!("{key} doesn't apply to embedding clients"),
+                    key_span,
+                );
+            }
+        }
+        self.parse_client_property(properties)
+    }
 }
 
+/// Options that only apply to clients generating text.
+const LLM_ONLY_OPTIONS: &[&str] = &[
+    "allowed_roles",
+    "default_role",
+    "allowed_role_metadata",
+    "supports_streaming",
+    "finish_reason_allow_list",
+    "finish_reason_deny_list",
+    "candidate_selection",
+    "logprobs",
+    "top_logprobs",
+    "tools",
+    "structured_output",
+    "image_preprocessing",
+];
+
 impl crate::OpenAIClientProviderVariant {
     fn create_from<Meta: Clone>(
         &self,
@@ -163,6 +231,15 @@ impl crate::OpenAIClientProviderVariant {
             crate::OpenAIClientProviderVariant::Azure => {
                 openai::UnresolvedOpenAI::create_azure(properties)
             }
+            crate::OpenAIClientProviderVariant::AzureAiInference => {
+                openai::UnresolvedOpenAI::create_azure_ai_inference(properties)
+            }
+            crate::OpenAIClientProviderVariant::XAi => {
+                openai::UnresolvedOpenAI::create_xai(properties)
+            }
+            crate::OpenAIClientProviderVariant::DeepSeek => {
+                openai::UnresolvedOpenAI::create_deepseek(properties)
+            }
             crate::OpenAIClientProviderVariant::Generic => {
                 openai::UnresolvedOpenAI::create_generic(properties)
             }
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use crate::{AllowedRoleMetadata, FinishReasonFilter, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
+use crate::{AllowedRoleMetadata, CandidateSelection, FinishReasonFilter, HttpOptions, ImagePreprocessingOptions, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
 use anyhow::Result;
 
 use baml_types::{GetEnvVar, StringOr, UnresolvedValue};
@@ -19,6 +19,14 @@ pub struct UnresolvedOpenAI<Meta> {
     properties: IndexMap<String, (Meta, UnresolvedValue<Meta>)>,
     query_params: IndexMap<String, StringOr>,
     finish_reason_filter: UnresolvedFinishReasonFilter,
+    image_preprocessing: Option<ImagePreprocessingOptions>,
+    http: HttpOptions,
+    capture_raw_response: bool,
+    user_id_tag: Option<StringOr>,
+    top_logprobs: Option<u8>,
+    candidate_selection: CandidateSelection,
+    tools: bool,
+    structured_output: bool,
 }
 
 impl<Meta> UnresolvedOpenAI<Meta> {
@@ -45,6 +53,14 @@ impl<Meta> UnresolvedOpenAI<Meta> {
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
             finish_reason_filter: self.finish_reason_filter.clone(),
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            user_id_tag: self.user_id_tag.clone(),
+            top_logprobs: self.top_logprobs,
+            candidate_selection: self.candidate_selection,
+            tools: self.tools,
+            structured_output: self.structured_output,
         }
     }
 }
@@ -60,6 +76,23 @@ pub struct ResolvedOpenAI {
     pub query_params: IndexMap<String, String>,
     pub proxy_url: Option<String>,
     pub finish_reason_filter: FinishReasonFilter,
+    pub image_preprocessing: Option<ImagePreprocessingOptions>,
+    pub http: HttpOptions,
+    /// Set with `capture_raw_response true`: responses keep the provider's
+    /// JSON body.
+    pub capture_raw_response: bool,
+    /// The runtime tag forwarded to the provider as the end-user id.
+    pub user_id_tag: Option<String>,
+    /// Set when logprobs are on, to how many alternatives are returned for
+    /// each token.
+    pub top_logprobs: Option<u8>,
+    pub candidate_selection: CandidateSelection,
+    /// Set with `tools true`: calls ask for the function's output as a tool
+    /// call with the output type as its parameters.
+    pub tools: bool,
+    /// Set with `structured_output true`: calls ask for JSON matching a
+    /// schema of the output type, with `response_format`.
+    pub structured_output: bool,
 }
 
 impl ResolvedOpenAI {
@@ -182,6 +215,13 @@ impl<Meta: Clone> UnresolvedOpenAI<Meta> {
                     .entry("max_tokens".into())
                     .or_insert(serde_json::json!(4096));
             }
+
+            if let Some(top_logprobs) = self.top_logprobs {
+                properties.insert("logprobs".into(), serde_json::json!(true));
+                if top_logprobs > 0 {
+                    properties.insert("top_logprobs".into(), serde_json::json!(top_logprobs));
+                }
+            }
             properties
         };
 
@@ -202,6 +242,14 @@ impl<Meta: Clone> UnresolvedOpenAI<Meta> {
             query_params,
             proxy_url: super::helpers::get_proxy_url(ctx),
             finish_reason_filter: self.finish_reason_filter.resolve(ctx)?,
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            user_id_tag: self.user_id_tag.as_ref().map(|t| t.resolve(ctx)).transpose()?,
+            top_logprobs: self.top_logprobs,
+            candidate_selection: self.candidate_selection,
+            tools: self.tools,
+            structured_output: self.structured_output,
         })
     }
 
@@ -282,6 +330,82 @@ impl<Meta: Clone> UnresolvedOpenAI<Meta> {
         Ok(instance)
     }
 
+    /// Azure AI Inference serves models deployed to Azure AI Foundry or as
+    /// serverless endpoints. Unlike Azure OpenAI, the key goes in the
+    /// `Authorization` header, and every request needs an `api-version`.
+    pub fn create_azure_ai_inference(
+        mut properties: PropertyHandler<Meta>,
+    ) -> Result<Self, Vec<Error<Meta>>> {
+        let base_url = properties.ensure_base_url(true);
+
+        let api_key = properties
+            .ensure_api_key()
+            .unwrap_or_else(|| StringOr::EnvVar("AZURE_AI_INFERENCE_API_KEY".to_string()));
+
+        let api_version = properties
+            .ensure_string("api_version", false)
+            .map(|(_, v, _)| v)
+            .unwrap_or_else(|| StringOr::Value("2024-05-01-preview".to_string()));
+
+        // What the endpoint does with parameters its model doesn't know.
+        let extra_parameters = properties
+            .ensure_string("extra_parameters", false)
+            .and_then(|(key_span, v, _)| match &v {
+                StringOr::Value(mode) if !["pass-through", "drop", "error"].contains(&mode.as_str()) => {
+                    properties.push_error(
+                        format!(
+                            "extra_parameters must be one of \"pass-through\", \"drop\" or \"error\". Got: {mode}"
+                        ),
+                        key_span,
+                    );
+                    None
+                }
+                _ => Some(v),
+            });
+
+        let mut instance = Self::create_common(
+            properties,
+            base_url.map(|url| either::Either::Left(url.1)),
+            Some(api_key),
+        )?;
+        instance
+            .query_params
+            .insert("api-version".to_string(), api_version);
+        if let Some(extra_parameters) = extra_parameters {
+            instance
+                .headers
+                .entry("extra-parameters".to_string())
+                .or_insert(extra_parameters);
+        }
+
+        Ok(instance)
+    }
+
+    pub fn create_xai(properties: PropertyHandler<Meta>) -> Result<Self, Vec<Error<Meta>>> {
+        Self::create_preset(properties, "https://api.x.ai/v1", "XAI_API_KEY")
+    }
+
+    pub fn create_deepseek(properties: PropertyHandler<Meta>) -> Result<Self, Vec<Error<Meta>>> {
+        Self::create_preset(properties, "https://api.deepseek.com", "DEEPSEEK_API_KEY")
+    }
+
+    /// For OpenAI-compatible APIs that only differ by URL and key.
+    fn create_preset(
+        mut properties: PropertyHandler<Meta>,
+        base_url: &'static str,
+        api_key_env_var: &str,
+    ) -> Result<Self, Vec<Error<Meta>>> {
+        let base_url = properties.ensure_base_url_with_default(UnresolvedUrl::new_static(base_url));
+
+        let api_key = Some(
+            properties
+                .ensure_api_key()
+                .unwrap_or_else(|| StringOr::EnvVar(api_key_env_var.to_string())),
+        );
+
+        Self::create_common(properties, Some(either::Either::Left(base_url)), api_key)
+    }
+
     pub fn create_generic(mut properties: PropertyHandler<Meta>) -> Result<Self, Vec<Error<Meta>>> {
         let base_url = properties.ensure_base_url(true);
 
@@ -319,6 +443,28 @@ impl<Meta: Clone> UnresolvedOpenAI<Meta> {
         let supported_request_modes = properties.ensure_supported_request_modes();
         let headers = properties.ensure_headers().unwrap_or_default();
         let finish_reason_filter = properties.ensure_finish_reason_filter();
+        let image_preprocessing = properties.ensure_image_preprocessing();
+        let http = properties.ensure_http_options();
+        let capture_raw_response = properties.ensure_capture_raw_response();
+        let user_id_tag = properties.ensure_user_id_tag();
+        let mut top_logprobs = properties.ensure_logprobs();
+        let candidate_selection = properties.ensure_candidate_selection();
+        let tools = properties.ensure_output_tool();
+        let structured_output = match properties.ensure_bool("structured_output", false) {
+            Some((key_span, true, _)) if tools => {
+                properties.push_error(
+                    "structured_output and tools can't both be true",
+                    key_span,
+                );
+                false
+            }
+            Some((_, structured_output, _)) => structured_output,
+            None => false,
+        };
+        // Candidates are compared by their logprobs, so ask for them.
+        if candidate_selection == CandidateSelection::HighestLogprob {
+            top_logprobs.get_or_insert(0);
+        }
         let (properties, errors) = properties.finalize();
 
         if !errors.is_empty() {
@@ -335,6 +481,14 @@ impl<Meta: Clone> UnresolvedOpenAI<Meta> {
             properties,
             query_params: IndexMap::new(),
             finish_reason_filter,
+            image_preprocessing,
+            http,
+            capture_raw_response,
+            user_id_tag,
+            top_logprobs,
+            candidate_selection,
+            tools,
+            structured_output,
         })
     }
 }
@@ -11,11 +11,36 @@ use super::helpers::{Error, PropertyHandler};
 pub struct UnresolvedRoundRobin<Meta> {
     pub strategy: Vec<(either::Either<StringOr, ClientSpec>, Meta)>,
     start_index: Option<i32>,
+    weights: Option<Vec<u32>>,
+    health_check: Option<HealthCheck>,
 }
 
 pub struct ResolvedRoundRobin {
     pub strategy: Vec<ClientSpec>,
     pub start_index: Option<i32>,
+    /// How many turns each client of `strategy` gets per rotation, one each
+    /// if unset.
+    pub weights: Option<Vec<u32>>,
+    pub health_check: Option<HealthCheck>,
+}
+
+/// `health_check { window 20, max_error_rate 0.5 }`: clients that failed
+/// more than `max_error_rate` of their requests among the strategy's last
+/// `window` requests are skipped, until enough newer requests push those
+/// failures out of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthCheck {
+    pub window: usize,
+    pub max_error_rate: f64,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            max_error_rate: 0.5,
+        }
+    }
 }
 
 impl<Meta: Clone> UnresolvedRoundRobin<Meta> {
@@ -23,6 +48,8 @@ impl<Meta: Clone> UnresolvedRoundRobin<Meta> {
         UnresolvedRoundRobin {
             strategy: self.strategy.iter().map(|(s, _)| (s.clone(), ())).collect(),
             start_index: self.start_index,
+            weights: self.weights.clone(),
+            health_check: self.health_check,
         }
     }
 
@@ -49,12 +76,16 @@ impl<Meta: Clone> UnresolvedRoundRobin<Meta> {
         Ok(ResolvedRoundRobin {
             strategy,
             start_index: self.start_index,
+            weights: self.weights.clone(),
+            health_check: self.health_check,
         })
     }
 
     pub fn create_from(mut properties: PropertyHandler<Meta>) -> Result<Self, Vec<Error<Meta>>> {
         let strategy = properties.ensure_strategy();
         let start_index = properties.ensure_int("start", false).map(|(_, v, _)| v);
+        let weights = properties.ensure_weights(strategy.as_ref().map(Vec::len));
+        let health_check = properties.ensure_health_check();
         let errors = properties.finalize_empty();
 
         if !errors.is_empty() {
@@ -66,6 +97,8 @@ impl<Meta: Clone> UnresolvedRoundRobin<Meta> {
         Ok(Self {
             strategy,
             start_index,
+            weights,
+            health_check,
         })
     }
 }
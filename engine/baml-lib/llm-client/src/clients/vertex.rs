@@ -1,13 +1,15 @@
 use std::collections::HashSet;
 
-use crate::{AllowedRoleMetadata, FinishReasonFilter, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
+use crate::{AllowedRoleMetadata, CandidateSelection, FinishReasonFilter, HttpOptions, ImagePreprocessingOptions, RolesSelection, SupportedRequestModes, UnresolvedAllowedRoleMetadata, UnresolvedFinishReasonFilter, UnresolvedRolesSelection};
 use anyhow::{Context, Result};
 
 use baml_types::{GetEnvVar, StringOr, UnresolvedValue};
 use indexmap::IndexMap;
 use serde::Deserialize;
 
-use super::helpers::{Error, PropertyHandler, UnresolvedUrl};
+use super::helpers::{
+    request_gemini_logprobs, Error, PropertyHandler, UnresolvedGeminiTools, UnresolvedUrl,
+};
 
 #[derive(Debug)]
 enum UnresolvedServiceAccountDetails<Meta> {
@@ -112,10 +114,66 @@ impl<Meta> UnresolvedServiceAccountDetails<Meta> {
     }
 }
 
+/// The locations Vertex AI serves Gemini models from, listed in errors when a
+/// model isn't available where a client asked for it.
+/// https://cloud.google.com/vertex-ai/generative-ai/docs/learn/locations
+pub const VERTEX_LOCATIONS: &[&str] = &[
+    "global",
+    "us-central1",
+    "us-east1",
+    "us-east4",
+    "us-east5",
+    "us-south1",
+    "us-west1",
+    "us-west4",
+    "northamerica-northeast1",
+    "southamerica-east1",
+    "europe-central2",
+    "europe-north1",
+    "europe-southwest1",
+    "europe-west1",
+    "europe-west2",
+    "europe-west3",
+    "europe-west4",
+    "europe-west6",
+    "europe-west8",
+    "europe-west9",
+    "asia-east1",
+    "asia-east2",
+    "asia-northeast1",
+    "asia-northeast3",
+    "asia-south1",
+    "asia-southeast1",
+    "australia-southeast1",
+    "me-central1",
+    "me-central2",
+    "me-west1",
+];
+
+/// A location to send requests to, and the URL for it.
+#[derive(Debug, Clone)]
+pub struct VertexEndpoint {
+    /// `None` if the client was given a `base_url`.
+    pub location: Option<String>,
+    pub base_url: String,
+}
+
+/// The models URL for `location`. `global` has no regional host.
+fn location_base_url(location: &str, project_id: &str) -> String {
+    let host = match location {
+        "global" => "aiplatform.googleapis.com".to_string(),
+        location => format!("{location}-aiplatform.googleapis.com"),
+    };
+    format!("https://{host}/v1/projects/{project_id}/locations/{location}/publishers/google/models")
+}
+
 #[derive(Debug)]
 pub struct UnresolvedVertex<Meta> {
     // Either base_url or location
     base_url: either::Either<UnresolvedUrl, StringOr>,
+    /// Locations to try, in order, when the model isn't available in
+    /// `location`.
+    fallback_locations: Vec<StringOr>,
     project_id: Option<StringOr>,
     authorization: UnresolvedServiceAccountDetails<Meta>,
     model: StringOr,
@@ -124,11 +182,18 @@ pub struct UnresolvedVertex<Meta> {
     allowed_role_metadata: UnresolvedAllowedRoleMetadata,
     supported_request_modes: SupportedRequestModes,
     finish_reason_filter: UnresolvedFinishReasonFilter,
+    image_preprocessing: Option<ImagePreprocessingOptions>,
+    http: HttpOptions,
+    capture_raw_response: bool,
+    /// Built-in tools, sent as the request's `tools`.
+    tools: UnresolvedGeminiTools<Meta>,
+    candidate_selection: CandidateSelection,
     properties: IndexMap<String, (Meta, UnresolvedValue<Meta>)>,
 }
 
 pub struct ResolvedVertex {
-    pub base_url: String,
+    /// The configured location first, then the fallback locations.
+    pub endpoints: Vec<VertexEndpoint>,
     pub authorization: ResolvedServiceAccountDetails,
     pub model: String,
     pub headers: IndexMap<String, String>,
@@ -138,6 +203,12 @@ pub struct ResolvedVertex {
     pub properties: IndexMap<String, serde_json::Value>,
     pub proxy_url: Option<String>,
     pub finish_reason_filter: FinishReasonFilter,
+    pub image_preprocessing: Option<ImagePreprocessingOptions>,
+    pub http: HttpOptions,
+    /// Set with `capture_raw_response true`: responses keep the provider's
+    /// JSON body.
+    pub capture_raw_response: bool,
+    pub candidate_selection: CandidateSelection,
 }
 
 impl ResolvedVertex {
@@ -166,6 +237,11 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
             either::Either::Left(ref base_url) => env_vars.extend(base_url.required_env_vars()),
             either::Either::Right(ref location) => env_vars.extend(location.required_env_vars()),
         }
+        env_vars.extend(
+            self.fallback_locations
+                .iter()
+                .flat_map(StringOr::required_env_vars),
+        );
         if let Some(ref project_id) = self.project_id {
             env_vars.extend(project_id.required_env_vars());
         }
@@ -175,6 +251,7 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
         env_vars.extend(self.role_selection.required_env_vars());
         env_vars.extend(self.allowed_role_metadata.required_env_vars());
         env_vars.extend(self.supported_request_modes.required_env_vars());
+        env_vars.extend(self.tools.required_env_vars());
         env_vars.extend(
             self.properties
                 .values()
@@ -187,6 +264,7 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
     pub fn without_meta(&self) -> UnresolvedVertex<()> {
         UnresolvedVertex {
             base_url: self.base_url.clone(),
+            fallback_locations: self.fallback_locations.clone(),
             project_id: self.project_id.clone(),
             authorization: self.authorization.without_meta(),
             model: self.model.clone(),
@@ -200,6 +278,11 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
                 .map(|(k, (_, v))| (k.clone(), ((), v.without_meta())))
                 .collect(),
             finish_reason_filter: self.finish_reason_filter.clone(),
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            tools: self.tools.without_meta(),
+            candidate_selection: self.candidate_selection,
         }
     }
 
@@ -207,8 +290,11 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
         // Validate auth options - only one should be provided
         let authorization = self.authorization.resolve(ctx)?;
 
-        let base_url = match self.base_url.as_ref() {
-            either::Either::Left(url) => url.resolve(ctx),
+        let endpoints = match self.base_url.as_ref() {
+            either::Either::Left(url) => vec![VertexEndpoint {
+                location: None,
+                base_url: url.resolve(ctx)?,
+            }],
             either::Either::Right(location) => {
                 let project_id = match self.project_id.as_ref() {
                     Some(project_id) => project_id.resolve(ctx)?,
@@ -224,12 +310,22 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
                     },
                 };
 
-                let location = location.resolve(ctx)?;
-                Ok(format!(
-                    "https://{location}-aiplatform.googleapis.com/v1/projects/{project_id}/locations/{location}/publishers/google/models"
-                ))
+                let mut locations = vec![location.resolve(ctx)?];
+                for fallback in &self.fallback_locations {
+                    let fallback = fallback.resolve(ctx)?;
+                    if !locations.contains(&fallback) {
+                        locations.push(fallback);
+                    }
+                }
+                locations
+                    .into_iter()
+                    .map(|location| VertexEndpoint {
+                        base_url: location_base_url(&location, &project_id),
+                        location: Some(location),
+                    })
+                    .collect()
             }
-        }?;
+        };
 
         let model = self.model.resolve(ctx)?;
 
@@ -241,21 +337,33 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
             .map(|(k, v)| Ok((k.clone(), v.resolve(ctx)?)))
             .collect::<Result<IndexMap<_, _>>>()?;
 
+        let mut properties = self
+            .properties
+            .iter()
+            .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
+            .collect::<Result<IndexMap<_, _>>>()?;
+        if let Some(tools) = self.tools.resolve(ctx)? {
+            properties.insert("tools".to_string(), tools);
+        }
+        if self.candidate_selection == CandidateSelection::HighestLogprob {
+            request_gemini_logprobs(&mut properties);
+        }
+
         Ok(ResolvedVertex {
-            base_url,
+            endpoints,
             authorization,
             model,
             headers,
             role_selection,
             allowed_metadata: self.allowed_role_metadata.resolve(ctx)?,
             supported_request_modes: self.supported_request_modes.clone(),
-            properties: self
-                .properties
-                .iter()
-                .map(|(k, (_, v))| Ok((k.clone(), v.resolve_serde::<serde_json::Value>(ctx)?)))
-                .collect::<Result<IndexMap<_, _>>>()?,
+            properties,
             proxy_url: super::helpers::get_proxy_url(ctx),
             finish_reason_filter: self.finish_reason_filter.resolve(ctx)?,
+            image_preprocessing: self.image_preprocessing.clone(),
+            http: self.http.clone(),
+            capture_raw_response: self.capture_raw_response,
+            candidate_selection: self.candidate_selection,
         })
     }
 
@@ -339,6 +447,34 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
             }
         };
 
+        let fallback_locations = properties
+            .ensure_array("fallback_locations", false)
+            .map(|(key_span, locations, _)| {
+                if matches!(base_url, Some(either::Either::Left(_))) {
+                    properties.push_error(
+                        "fallback_locations can only be used with location, not base_url",
+                        key_span,
+                    );
+                }
+                locations
+                    .into_iter()
+                    .filter_map(|location| match location.as_str() {
+                        Some(location) => Some(location.clone()),
+                        None => {
+                            properties.push_error(
+                                format!(
+                                    "values in fallback_locations must be strings. Got: {}",
+                                    location.r#type()
+                                ),
+                                location.meta().clone(),
+                            );
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let project_id = properties
             .ensure_string("project_id", false)
             .map(|(_, v, _)| v);
@@ -348,6 +484,11 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
         let supported_request_modes = properties.ensure_supported_request_modes();
         let headers = properties.ensure_headers().unwrap_or_default();
         let finish_reason_filter = properties.ensure_finish_reason_filter();
+        let image_preprocessing = properties.ensure_image_preprocessing();
+        let http = properties.ensure_http_options();
+        let capture_raw_response = properties.ensure_capture_raw_response();
+        let tools = properties.ensure_gemini_tools();
+        let candidate_selection = properties.ensure_candidate_selection();
 
         let (properties, errors) = properties.finalize();
         if !errors.is_empty() {
@@ -360,6 +501,7 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
 
         Ok(Self {
             base_url,
+            fallback_locations,
             project_id,
             authorization,
             model,
@@ -369,6 +511,11 @@ impl<Meta: Clone> UnresolvedVertex<Meta> {
             supported_request_modes,
             properties,
             finish_reason_filter,
+            image_preprocessing,
+            http,
+            capture_raw_response,
+            tools,
+            candidate_selection,
         })
     }
 }
@@ -1,32 +1,82 @@
 use anyhow::Result;
 use std::collections::HashSet;
 
-use baml_types::{GetEnvVar, StringOr};
+use baml_types::{BamlMap, BamlValue, GetEnvVar, StringOr};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize)]
 pub enum ClientSpec {
     Named(String),
-    /// Shorthand for "<provider>/<model>"
-    Shorthand(ClientProvider, String),
+    /// Shorthand for "<provider>/<model>", optionally followed by inline
+    /// options like "?temperature=0&max_tokens=256".
+    Shorthand(ClientProvider, String, BamlMap<String, String>),
 }
 
 impl ClientSpec {
     pub fn as_str(&self) -> String {
-        match self {
-            ClientSpec::Named(n) => n.clone(),
-            ClientSpec::Shorthand(provider, model) => format!("{provider}/{model}"),
-        }
+        self.to_string()
     }
 
     pub fn new_from_id(arg: &str) -> Result<Self, anyhow::Error> {
-        if arg.contains("/") {
-            let (provider, model) = arg.split_once("/").unwrap();
-            Ok(ClientSpec::Shorthand(provider.parse()?, model.to_string()))
-        } else {
-            Ok(ClientSpec::Named(arg.into()))
+        let Some((provider, model)) = arg.split_once("/") else {
+            return Ok(ClientSpec::Named(arg.into()));
+        };
+        let (model, options) = match model.split_once('?') {
+            Some((model, query)) => (model, parse_inline_options(query)?),
+            None => (model, BamlMap::new()),
+        };
+        Ok(ClientSpec::Shorthand(
+            provider.parse()?,
+            model.to_string(),
+            options,
+        ))
+    }
+
+    /// The options of a shorthand client: its `model`, then the inline
+    /// options. `true`/`false` and numbers become bools and numbers.
+    pub fn shorthand_options(
+        model: &str,
+        inline_options: &BamlMap<String, String>,
+    ) -> BamlMap<String, BamlValue> {
+        std::iter::once(("model".to_string(), BamlValue::String(model.to_string())))
+            .chain(
+                inline_options
+                    .iter()
+                    .map(|(key, value)| (key.clone(), inline_option_value(value))),
+            )
+            .collect()
+    }
+}
+
+fn parse_inline_options(query: &str) -> Result<BamlMap<String, String>> {
+    let mut options = BamlMap::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let Some((key, value)) = pair.split_once('=') else {
+            anyhow::bail!("Expected `key=value` in client options, got `{pair}`");
+        };
+        if key.is_empty() {
+            anyhow::bail!("Missing option name in `{pair}`");
+        }
+        if key == "model" {
+            anyhow::bail!("Set the model before the `?`, not as an option");
+        }
+        if options.insert(key.to_string(), value.to_string()).is_some() {
+            anyhow::bail!("Option `{key}` is set more than once");
         }
     }
+    Ok(options)
+}
+
+fn inline_option_value(value: &str) -> BamlValue {
+    if let Ok(b) = value.parse::<bool>() {
+        BamlValue::Bool(b)
+    } else if let Ok(i) = value.parse::<i64>() {
+        BamlValue::Int(i)
+    } else if let Some(f) = value.parse::<f64>().ok().filter(|f| f.is_finite()) {
+        BamlValue::Float(f)
+    } else {
+        BamlValue::String(value.to_string())
+    }
 }
 
 /// The provider for the client, e.g. baml-openai-chat
@@ -42,6 +92,10 @@ pub enum ClientProvider {
     GoogleAi,
     /// The Vertex client provider variant
     Vertex,
+    /// The Mistral AI client provider variant
+    Mistral,
+    /// The Cohere client provider variant
+    Cohere,
     /// The strategy client provider variant
     Strategy(StrategyClientProvider),
 }
@@ -55,6 +109,13 @@ pub enum OpenAIClientProviderVariant {
     Ollama,
     /// The Azure client provider variant
     Azure,
+    /// The Azure AI Inference client provider variant, for models served
+    /// from Azure AI Foundry and serverless endpoints
+    AzureAiInference,
+    /// The xAI client provider variant, for Grok models
+    XAi,
+    /// The DeepSeek client provider variant
+    DeepSeek,
     /// The generic client provider variant
     Generic,
 }
@@ -76,6 +137,8 @@ impl std::fmt::Display for ClientProvider {
             ClientProvider::AwsBedrock => write!(f, "aws-bedrock"),
             ClientProvider::GoogleAi => write!(f, "google-ai"),
             ClientProvider::Vertex => write!(f, "vertex-ai"),
+            ClientProvider::Mistral => write!(f, "mistral"),
+            ClientProvider::Cohere => write!(f, "cohere"),
             ClientProvider::Strategy(variant) => write!(f, "{variant}"),
         }
     }
@@ -87,6 +150,9 @@ impl std::fmt::Display for OpenAIClientProviderVariant {
             OpenAIClientProviderVariant::Base => write!(f, "openai"),
             OpenAIClientProviderVariant::Ollama => write!(f, "ollama"),
             OpenAIClientProviderVariant::Azure => write!(f, "azure-openai"),
+            OpenAIClientProviderVariant::AzureAiInference => write!(f, "azure-ai-inference"),
+            OpenAIClientProviderVariant::XAi => write!(f, "x-ai"),
+            OpenAIClientProviderVariant::DeepSeek => write!(f, "deepseek"),
             OpenAIClientProviderVariant::Generic => write!(f, "openai-generic"),
         }
     }
@@ -111,6 +177,13 @@ impl std::str::FromStr for ClientProvider {
             "openai-generic" => Ok(ClientProvider::OpenAI(OpenAIClientProviderVariant::Generic)),
             "azure-openai" => Ok(ClientProvider::OpenAI(OpenAIClientProviderVariant::Azure)),
             "baml-azure-chat" => Ok(ClientProvider::OpenAI(OpenAIClientProviderVariant::Azure)),
+            "azure-ai-inference" => Ok(ClientProvider::OpenAI(
+                OpenAIClientProviderVariant::AzureAiInference,
+            )),
+            "x-ai" => Ok(ClientProvider::OpenAI(OpenAIClientProviderVariant::XAi)),
+            "deepseek" => Ok(ClientProvider::OpenAI(
+                OpenAIClientProviderVariant::DeepSeek,
+            )),
             "baml-ollama-chat" => Ok(ClientProvider::OpenAI(OpenAIClientProviderVariant::Ollama)),
             "ollama" => Ok(ClientProvider::OpenAI(OpenAIClientProviderVariant::Ollama)),
             "anthropic" => Ok(ClientProvider::Anthropic),
@@ -118,6 +191,8 @@ impl std::str::FromStr for ClientProvider {
             "aws-bedrock" => Ok(ClientProvider::AwsBedrock),
             "google-ai" => Ok(ClientProvider::GoogleAi),
             "vertex-ai" => Ok(ClientProvider::Vertex),
+            "mistral" => Ok(ClientProvider::Mistral),
+            "cohere" => Ok(ClientProvider::Cohere),
             "fallback" => Ok(ClientProvider::Strategy(StrategyClientProvider::Fallback)),
             "baml-fallback" => Ok(ClientProvider::Strategy(StrategyClientProvider::Fallback)),
             "round-robin" => Ok(ClientProvider::Strategy(StrategyClientProvider::RoundRobin)),
@@ -135,6 +210,9 @@ impl std::str::FromStr for OpenAIClientProviderVariant {
             "openai" => Ok(OpenAIClientProviderVariant::Base),
             "ollama" => Ok(OpenAIClientProviderVariant::Ollama),
             "azure-openai" => Ok(OpenAIClientProviderVariant::Azure),
+            "azure-ai-inference" => Ok(OpenAIClientProviderVariant::AzureAiInference),
+            "x-ai" => Ok(OpenAIClientProviderVariant::XAi),
+            "deepseek" => Ok(OpenAIClientProviderVariant::DeepSeek),
             "openai-generic" => Ok(OpenAIClientProviderVariant::Generic),
             _ => Err(anyhow::anyhow!(
                 "Invalid OpenAI client provider variant: {}",
@@ -165,6 +243,9 @@ impl ClientProvider {
             "openai",
             "openai-generic",
             "azure-openai",
+            "azure-ai-inference",
+            "x-ai",
+            "deepseek",
             "anthropic",
             "ollama",
             "round-robin",
@@ -172,15 +253,63 @@ impl ClientProvider {
             "google-ai",
             "vertex-ai",
             "aws-bedrock",
+            "mistral",
+            "cohere",
+        ]
+    }
+
+    /// The providers a `client<embedding>` can use.
+    pub fn embedding_providers() -> &'static [&'static str] {
+        &[
+            "openai",
+            "openai-generic",
+            "azure-openai",
+            "azure-ai-inference",
+            "ollama",
+            "vertex-ai",
         ]
     }
+
+    /// The models a provider is known to serve, for providers with a short,
+    /// stable list. Other models get a warning, as they are likely typos.
+    pub fn known_models(&self) -> Option<&'static [&'static str]> {
+        match self {
+            ClientProvider::OpenAI(OpenAIClientProviderVariant::XAi) => Some(&[
+                "grok-4",
+                "grok-4-0709",
+                "grok-4-fast-reasoning",
+                "grok-4-fast-non-reasoning",
+                "grok-code-fast-1",
+                "grok-3",
+                "grok-3-mini",
+                "grok-3-fast",
+                "grok-3-mini-fast",
+                "grok-2-1212",
+                "grok-2-vision-1212",
+            ]),
+            ClientProvider::OpenAI(OpenAIClientProviderVariant::DeepSeek) => {
+                Some(&["deepseek-chat", "deepseek-reasoner"])
+            }
+            _ => None,
+        }
+    }
+
+    pub fn supports_embeddings(&self) -> bool {
+        matches!(self, ClientProvider::OpenAI(_) | ClientProvider::Vertex)
+    }
 }
 
 impl std::fmt::Display for ClientSpec {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ClientSpec::Named(n) => write!(f, "{n}"),
-            ClientSpec::Shorthand(provider, model) => write!(f, "{provider}/{model}"),
+            ClientSpec::Shorthand(provider, model, options) => {
+                write!(f, "{provider}/{model}")?;
+                for (i, (key, value)) in options.iter().enumerate() {
+                    write!(f, "{}{key}={value}", if i == 0 { '?' } else { '&' })?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -197,6 +326,74 @@ impl SupportedRequestModes {
     }
 }
 
+/// How one response is picked when a client asks for several candidates,
+/// with `n` for OpenAI or `candidateCount` for Gemini. Set with the client's
+/// `candidate_selection` option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CandidateSelection {
+    /// The first candidate that parses.
+    #[default]
+    FirstParseSuccess,
+    /// A candidate with the parsed value most candidates agree on.
+    MajorityVote,
+    /// The candidate that parses with the highest mean token logprob.
+    HighestLogprob,
+}
+
+impl CandidateSelection {
+    pub const NAMES: &'static [&'static str] =
+        &["first_parse_success", "majority_vote", "highest_logprob"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "first_parse_success" => Some(Self::FirstParseSuccess),
+            "majority_vote" => Some(Self::MajorityVote),
+            "highest_logprob" => Some(Self::HighestLogprob),
+            _ => None,
+        }
+    }
+}
+
+/// The client's `image_preprocessing` option. Unset fields fall back to
+/// defaults for the client's provider.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImagePreprocessingOptions {
+    /// Images are downscaled so neither side is longer than this.
+    pub max_dimension: Option<u32>,
+    /// 1-100, for images re-encoded as JPEG.
+    pub jpeg_quality: Option<u8>,
+    /// Convert HEIC/HEIF images to JPEG.
+    pub convert_heic: Option<bool>,
+}
+
+/// The client's `http` option, tuning its connections and requests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpOptions {
+    /// Gzip request bodies, for servers that accept `Content-Encoding: gzip`.
+    pub compress_requests: bool,
+    /// How often to ping HTTP/2 connections; 0 turns pings off. Defaults to
+    /// 10 seconds.
+    pub keep_alive_interval_ms: Option<u64>,
+    /// How long idle connections are kept for reuse. By default they aren't
+    /// reused at all.
+    pub pool_idle_timeout_ms: Option<u64>,
+    /// How long a call may take, or a stream may take to start. Unlimited by
+    /// default.
+    pub request_timeout_ms: Option<u64>,
+    /// How long connecting may take. Defaults to 10 seconds.
+    pub connect_timeout_ms: Option<u64>,
+    /// How many requests the client may send. Unlimited by default.
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// A token bucket, refilled at `requests_per_minute` up to `burst` requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_minute: u32,
+    /// How many requests may be sent at once after a quiet spell.
+    pub burst: u32,
+}
+
 #[derive(Clone, Debug)]
 pub enum UnresolvedFinishReasonFilter {
     All,
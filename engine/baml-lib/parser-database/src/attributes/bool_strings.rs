@@ -0,0 +1,54 @@
+use baml_types::{BoolStrings, TypeValue};
+use internal_baml_diagnostics::DatamodelError;
+use internal_baml_schema_ast::ast::FieldType;
+
+use crate::context::Context;
+
+/// Parses `@bool_strings(lenient)` on a class field of type `field_type`.
+pub(super) fn visit_bool_strings_attribute(
+    ctx: &mut Context<'_>,
+    field_type: Option<&FieldType>,
+) -> Option<BoolStrings> {
+    // The arguments were checked against the attribute's signature already.
+    let arguments = ctx.get_all_args();
+    let (_, mode) = arguments.first()?;
+    let (mode, mode_span) = mode.as_string_value()?;
+
+    let Some(bool_strings) = BoolStrings::from_mode(mode) else {
+        ctx.push_error(DatamodelError::new_validation_error(
+            &format!(
+                "Unknown bool_strings mode: {mode}. Options are {}",
+                BoolStrings::MODES
+                    .iter()
+                    .map(|mode| format!("`{mode}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            mode_span.clone(),
+        ));
+        return None;
+    };
+
+    if !field_type.is_some_and(has_bool) {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "`@bool_strings` only applies to fields with a `bool` in their type",
+            mode_span.clone(),
+        ));
+        return None;
+    }
+    Some(bool_strings)
+}
+
+/// Whether `field_type` is a bool, or a list, map value or union with one.
+/// Bools inside other classes follow their own field's `@bool_strings`.
+fn has_bool(field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Primitive(_, TypeValue::Bool, ..) => true,
+        FieldType::List(_, inner, ..) => has_bool(inner),
+        FieldType::Map(_, kv, ..) => has_bool(&kv.1),
+        FieldType::Tuple(_, items, ..) | FieldType::Union(_, items, ..) => {
+            items.iter().any(has_bool)
+        }
+        FieldType::Primitive(..) | FieldType::Symbol(..) | FieldType::Literal(..) => false,
+    }
+}
@@ -0,0 +1,36 @@
+use internal_baml_diagnostics::{DatamodelError, Diagnostics};
+use internal_baml_schema_ast::ast::{Attribute, Expression, Identifier};
+
+use super::signatures::bind_arguments;
+use crate::types::Condition;
+
+/// Parses `@@if(env.FEATURE_X)` or `@@if(env.STAGE, "canary")` on a
+/// function, client or test.
+pub fn attribute_as_condition(
+    attribute: &Attribute,
+    diagnostics: &mut Diagnostics,
+) -> Option<Condition> {
+    let arguments = bind_arguments(attribute, true, diagnostics)?;
+    let Some(Expression::Identifier(Identifier::ENV(env_var, _))) = arguments.get("env_var") else {
+        return None;
+    };
+    let equals = match arguments.get("equals") {
+        None => None,
+        Some(value) => match value.as_constant_value() {
+            Some((value, _)) => Some(value.to_string()),
+            None => {
+                diagnostics.push_error(DatamodelError::new_validation_error(
+                    "The value to compare the environment variable to must be a string",
+                    value.span().clone(),
+                ));
+                return None;
+            }
+        },
+    };
+
+    Some(Condition {
+        env_var: env_var.clone(),
+        equals,
+        span: attribute.span.clone(),
+    })
+}
@@ -0,0 +1,32 @@
+use internal_baml_diagnostics::{DatamodelError, Diagnostics};
+use internal_baml_schema_ast::ast::{Attribute, Expression};
+
+use super::signatures::bind_arguments;
+use crate::types::ConfidenceMode;
+
+/// Parses `@@confidence` or `@@confidence(5)` on a function.
+pub fn attribute_as_confidence(
+    attribute: &Attribute,
+    diagnostics: &mut Diagnostics,
+) -> Option<ConfidenceMode> {
+    let arguments = bind_arguments(attribute, true, diagnostics)?;
+
+    let samples = match arguments.get("samples") {
+        Some(Expression::NumericValue(value, span)) => match value.parse::<u32>() {
+            Ok(samples) if samples >= 2 => Some(samples),
+            _ => {
+                diagnostics.push_error(DatamodelError::new_validation_error(
+                    "The number of samples must be a whole number of at least 2",
+                    span.clone(),
+                ));
+                return None;
+            }
+        },
+        _ => None,
+    };
+
+    Some(ConfidenceMode {
+        samples,
+        span: attribute.span.clone(),
+    })
+}
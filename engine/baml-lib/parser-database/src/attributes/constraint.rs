@@ -1,68 +1,34 @@
 use baml_types::{Constraint, ConstraintLevel};
-use internal_baml_diagnostics::{DatamodelError, Span};
-use internal_baml_schema_ast::ast::{Argument, Attribute, Expression};
+use internal_baml_diagnostics::{DatamodelError, Diagnostics, Span};
+use internal_baml_schema_ast::ast::{Attribute, Expression, WithName};
 
+use super::signatures::bind_arguments;
 use crate::{context::Context, types::Attributes};
 
 /// Interpret an attribute as a constraint, the whole constraint's span,
 /// and the span of the constraint's jinja expression.
 pub fn attribute_as_constraint(
     attribute: &Attribute,
-) -> (Option<(Constraint, Span, Span)>, Vec<DatamodelError>) {
-    let span = attribute.span.clone();
-    let mut datamodel_errors = Vec::new();
-    let attribute_name = attribute.name.to_string();
-    let arguments: Vec<Expression> = attribute
-        .arguments
-        .arguments
-        .iter()
-        .map(|Argument { value, .. }| value)
-        .cloned()
-        .collect();
-
-    let level = match attribute_name.as_str() {
+    diagnostics: &mut Diagnostics,
+) -> Option<(Constraint, Span, Span)> {
+    let level = match attribute.name.name() {
         "assert" => ConstraintLevel::Assert,
         "check" => ConstraintLevel::Check,
-        _ => {
-            return (None, datamodel_errors);
-        }
+        _ => return None,
     };
+    let arguments = bind_arguments(attribute, true, diagnostics)?;
 
-    let (label, expression, expr_span) = match arguments.as_slice() {
-        [Expression::JinjaExpressionValue(expression, expr_span)] => {
-            if level == ConstraintLevel::Check {
-                datamodel_errors.push(DatamodelError::new_attribute_validation_error(
-                    "Checks must specify a label.",
-                    attribute_name.as_str(),
-                    span.clone(),
-                ));
-            }
-            (None, expression.clone(), expr_span.clone())
-        }
-        [Expression::Identifier(label), Expression::JinjaExpressionValue(expression, expr_span)] => {
-            (
-                Some(label.to_string()),
-                expression.clone(),
-                expr_span.clone(),
-            )
-        }
-        _ => {
-            datamodel_errors.push(
-                DatamodelError::new_attribute_validation_error(
-                    "Checks and asserts may have either a label and an expression, or a lone expression.",
-                    attribute_name.as_str(),
-                    span
-                )
-            );
-            return (None, datamodel_errors);
-        }
+    let label = arguments.get("label").map(ToString::to_string);
+    let Some(Expression::JinjaExpressionValue(expression, expr_span)) = arguments.get("expression")
+    else {
+        return None;
     };
     let constraint = Constraint {
         label,
-        expression,
+        expression: expression.clone(),
         level,
     };
-    (Some((constraint, span, expr_span)), datamodel_errors)
+    Some((constraint, attribute.span.clone(), expr_span.clone()))
 }
 
 pub(super) fn visit_constraint_attributes(
@@ -90,30 +56,13 @@ pub(super) fn visit_constraint_attributes(
         }
     };
 
+    // The arguments were checked against the attribute's signature already.
     let (label, expression) = match arguments.as_slice() {
-        [Expression::JinjaExpressionValue(expression, _)] => {
-            if level == ConstraintLevel::Check {
-                ctx.push_error(DatamodelError::new_attribute_validation_error(
-                    "Checks must specify a label.",
-                    attribute_name.as_str(),
-                    span,
-                ));
-            }
-            (None, expression.clone())
-        }
+        [Expression::JinjaExpressionValue(expression, _)] => (None, expression.clone()),
         [Expression::Identifier(label), Expression::JinjaExpressionValue(expression, _)] => {
             (Some(label.to_string()), expression.clone())
         }
-        _ => {
-            ctx.push_error(
-                DatamodelError::new_attribute_validation_error(
-                    "Checks and asserts may have either a label and an expression, or a lone expression.",
-                    attribute_name.as_str(),
-                    span
-                )
-            );
-            return;
-        }
+        _ => return,
     };
 
     attributes.constraints.push(Constraint {
@@ -0,0 +1,61 @@
+use internal_baml_diagnostics::{DatamodelError, Diagnostics};
+use internal_baml_schema_ast::ast::Attribute;
+
+use super::signatures::bind_arguments;
+use crate::types::Expectation;
+
+/// Parses `@@expect(value)` or `@@expect(value, { float_tolerance 0.01,
+/// unordered_lists true })` on a test block.
+pub fn attribute_as_expectation(
+    attribute: &Attribute,
+    diagnostics: &mut Diagnostics,
+) -> Option<Expectation> {
+    let arguments = bind_arguments(attribute, true, diagnostics)?;
+    let value = arguments.get("value")?;
+    let options = arguments.get("options");
+
+    let mut expectation = Expectation {
+        value: value.to_unresolved_value(diagnostics)?,
+        float_tolerance: 0.0,
+        unordered_lists: false,
+        span: attribute.span.clone(),
+    };
+
+    let Some(options) = options else {
+        return Some(expectation);
+    };
+    let Some(options) = options.to_unresolved_value(diagnostics) else {
+        return Some(expectation);
+    };
+    let Some(options) = options.as_map() else {
+        diagnostics.push_error(DatamodelError::new_validation_error(
+            "Expectation options must be a map, like { float_tolerance 0.01 }",
+            options.meta().clone(),
+        ));
+        return None;
+    };
+    for (key, (key_span, option)) in options {
+        match key.as_str() {
+            "float_tolerance" => match option.as_numeric().and_then(|n| n.parse::<f64>().ok()) {
+                Some(tolerance) if tolerance >= 0.0 => expectation.float_tolerance = tolerance,
+                _ => diagnostics.push_error(DatamodelError::new_validation_error(
+                    "`float_tolerance` must be a non-negative number",
+                    option.meta().clone(),
+                )),
+            },
+            "unordered_lists" => match option.as_bool() {
+                Some(unordered) => expectation.unordered_lists = unordered,
+                None => diagnostics.push_error(DatamodelError::new_validation_error(
+                    "`unordered_lists` must be true or false",
+                    option.meta().clone(),
+                )),
+            },
+            _ => diagnostics.push_error(DatamodelError::new_property_not_known_error(
+                key,
+                key_span.clone(),
+                ["float_tolerance", "unordered_lists"].to_vec(),
+            )),
+        }
+    }
+    Some(expectation)
+}
@@ -0,0 +1,56 @@
+use baml_types::EnumMatching;
+use internal_baml_diagnostics::DatamodelError;
+
+use crate::context::Context;
+
+/// Parses `@@match(exact)` or `@@match(fuzzy, { max_distance 2 })` on an
+/// enum.
+pub(super) fn visit_match_attribute(ctx: &mut Context<'_>) -> Option<EnumMatching> {
+    // The arguments were checked against the attribute's signature already.
+    let arguments = ctx.get_all_args();
+    let (_, mode) = arguments.first()?;
+    let (mode, mode_span) = mode.as_string_value()?;
+
+    let mut max_distance = None;
+    if let Some((_, options)) = arguments.get(1) {
+        let options = options.to_unresolved_value(ctx.diagnostics)?;
+        for (key, (key_span, option)) in options.as_map()? {
+            match key.as_str() {
+                "max_distance" if mode == "fuzzy" => {
+                    match option.as_numeric().and_then(|n| n.parse::<usize>().ok()) {
+                        Some(distance) if distance >= 1 => max_distance = Some(distance),
+                        _ => ctx.push_error(DatamodelError::new_validation_error(
+                            "`max_distance` must be a whole number of at least 1",
+                            option.meta().clone(),
+                        )),
+                    }
+                }
+                "max_distance" => ctx.push_error(DatamodelError::new_validation_error(
+                    "`max_distance` only applies to `fuzzy` matching",
+                    key_span.clone(),
+                )),
+                _ => ctx.push_error(DatamodelError::new_property_not_known_error(
+                    key,
+                    key_span.clone(),
+                    ["max_distance"].to_vec(),
+                )),
+            }
+        }
+    }
+
+    let matching = EnumMatching::from_mode(mode, max_distance);
+    if matching.is_none() {
+        ctx.push_error(DatamodelError::new_validation_error(
+            &format!(
+                "Unknown match mode: {mode}. Options are {}",
+                EnumMatching::MODES
+                    .iter()
+                    .map(|mode| format!("`{mode}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            mode_span.clone(),
+        ));
+    }
+    matching
+}
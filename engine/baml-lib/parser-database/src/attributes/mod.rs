@@ -2,9 +2,18 @@ use internal_baml_diagnostics::Span;
 use internal_baml_schema_ast::ast::{Top, TopId, TypeExpId, TypeExpressionBlock};
 
 mod alias;
+mod bool_strings;
+pub mod condition;
+pub mod confidence;
 pub mod constraint;
 mod description;
+pub mod expect;
+mod matching;
+pub mod self_consistency;
+pub(crate) mod signatures;
+mod streaming;
 mod to_string_attribute;
+pub mod trace;
 use crate::interner::StringId;
 use crate::{context::Context, types::ClassAttributes, types::EnumAttributes};
 use baml_types::{Constraint, UnresolvedValue};
@@ -79,6 +88,27 @@ pub(super) fn resolve_attributes(ctx: &mut Context<'_>) {
             (TopId::Enum(enum_id), Top::Enum(ast_enum)) => {
                 resolve_type_exp_block_attributes(enum_id, ast_enum, ctx, SubType::Enum)
             }
+            (_, Top::Function(ast_function)) => {
+                let args = ast_function
+                    .input()
+                    .into_iter()
+                    .flat_map(|input| &input.args);
+                for (_, arg) in args {
+                    signatures::validate_type_attributes(&arg.field_type, ctx.diagnostics);
+                }
+                if let Some(output) = ast_function.output() {
+                    signatures::validate_type_attributes(&output.field_type, ctx.diagnostics);
+                }
+            }
+            (_, Top::TemplateString(template_string)) => {
+                let args = template_string
+                    .input()
+                    .into_iter()
+                    .flat_map(|input| &input.args);
+                for (_, arg) in args {
+                    signatures::validate_type_attributes(&arg.field_type, ctx.diagnostics);
+                }
+            }
             _ => (),
         }
     }
@@ -106,6 +136,10 @@ fn resolve_type_exp_block_attributes<'db>(
             // Now validate the enum attributes.
             ctx.assert_all_attributes_processed(type_id.into());
             enum_attributes.serilizer = to_string_attribute::visit(ctx, &span, true);
+            if ctx.visit_optional_single_attr("match") && ctx.validate_attribute_signature(true) {
+                enum_attributes.matching = matching::visit_match_attribute(ctx);
+                ctx.validate_visited_arguments();
+            }
             ctx.validate_visited_attributes();
 
             ctx.types.enum_attributes.insert(type_id, enum_attributes);
@@ -114,10 +148,31 @@ fn resolve_type_exp_block_attributes<'db>(
             let mut class_attributes = ClassAttributes::default();
 
             for (field_idx, field) in ast_typexpr.iter_fields() {
+                if let Some(field_type) = &field.expr {
+                    signatures::validate_type_attributes(field_type, ctx.diagnostics);
+                }
                 ctx.assert_all_attributes_processed((type_id, field_idx).into());
                 if let Some(attrs) = to_string_attribute::visit(ctx, &field.span, false) {
                     class_attributes.field_serilizers.insert(field_idx, attrs);
                 }
+                if ctx.visit_optional_single_attr("bool_strings")
+                    && ctx.validate_attribute_signature(false)
+                {
+                    if let Some(bool_strings) =
+                        bool_strings::visit_bool_strings_attribute(ctx, field.expr.as_ref())
+                    {
+                        class_attributes
+                            .field_bool_strings
+                            .insert(field_idx, bool_strings);
+                    }
+                    ctx.validate_visited_arguments();
+                }
+                let streaming = streaming::visit_streaming_attributes(ctx, field.expr.as_ref());
+                if !streaming.is_default() {
+                    class_attributes
+                        .field_streaming
+                        .insert(field_idx, streaming);
+                }
                 ctx.validate_visited_attributes();
             }
 
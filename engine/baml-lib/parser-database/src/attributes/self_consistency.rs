@@ -0,0 +1,61 @@
+use baml_types::StringOr;
+use internal_baml_diagnostics::{DatamodelError, Diagnostics};
+use internal_baml_schema_ast::ast::Attribute;
+
+use super::signatures::bind_arguments;
+use crate::types::{SelfConsistency, VotingStrategy};
+
+/// Parses `@@self_consistency` or `@@self_consistency({ samples 5, strategy
+/// "majority" })` on a function.
+pub fn attribute_as_self_consistency(
+    attribute: &Attribute,
+    diagnostics: &mut Diagnostics,
+) -> Option<SelfConsistency> {
+    let mut self_consistency = SelfConsistency {
+        samples: 5,
+        strategy: VotingStrategy::Majority,
+        span: attribute.span.clone(),
+    };
+
+    let arguments = bind_arguments(attribute, true, diagnostics)?;
+    let Some(options) = arguments.get("options") else {
+        return Some(self_consistency);
+    };
+    let options = options.to_unresolved_value(diagnostics)?;
+    let Some(options) = options.as_map() else {
+        diagnostics.push_error(DatamodelError::new_validation_error(
+            "Self-consistency options must be a map, like { samples 5 }",
+            options.meta().clone(),
+        ));
+        return None;
+    };
+    for (key, (key_span, option)) in options {
+        match key.as_str() {
+            "samples" => match option.as_numeric().and_then(|n| n.parse::<u32>().ok()) {
+                Some(samples) if samples >= 2 => self_consistency.samples = samples,
+                _ => diagnostics.push_error(DatamodelError::new_validation_error(
+                    "`samples` must be a whole number of at least 2",
+                    option.meta().clone(),
+                )),
+            },
+            "strategy" => match option.as_str() {
+                Some(StringOr::Value(strategy)) if strategy == "majority" => {
+                    self_consistency.strategy = VotingStrategy::Majority
+                }
+                Some(StringOr::Value(strategy)) if strategy == "most_common" => {
+                    self_consistency.strategy = VotingStrategy::MostCommon
+                }
+                _ => diagnostics.push_error(DatamodelError::new_validation_error(
+                    "`strategy` must be \"majority\" or \"most_common\"",
+                    option.meta().clone(),
+                )),
+            },
+            _ => diagnostics.push_error(DatamodelError::new_property_not_known_error(
+                key,
+                key_span.clone(),
+                ["samples", "strategy"].to_vec(),
+            )),
+        }
+    }
+    Some(self_consistency)
+}
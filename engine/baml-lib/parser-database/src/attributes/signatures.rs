@@ -0,0 +1,349 @@
+use internal_baml_diagnostics::{DatamodelError, Diagnostics, Span};
+use internal_baml_schema_ast::ast::{Attribute, Expression, FieldType, Identifier, WithName};
+
+/// The kind of value an attribute argument accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArgumentKind {
+    /// A quoted or raw string, an unquoted string, or an `env.NAME` reference.
+    String,
+    /// A number, like `5`.
+    Number,
+    /// A bare name, like the label of a `@check`.
+    Identifier,
+    /// An `env.NAME` reference.
+    EnvVar,
+    /// A Jinja expression, like `{{ this > 0 }}`.
+    Jinja,
+    /// A map, like `{ samples 5 }`.
+    Map,
+    /// Any value that isn't a Jinja expression.
+    Value,
+}
+
+impl ArgumentKind {
+    fn accepts(self, expression: &Expression) -> bool {
+        match (self, expression) {
+            (ArgumentKind::String, Expression::StringValue(..))
+            | (ArgumentKind::String, Expression::RawStringValue(..))
+            | (ArgumentKind::String, Expression::Identifier(..)) => true,
+            (ArgumentKind::Number, Expression::NumericValue(..)) => true,
+            (ArgumentKind::Identifier, Expression::Identifier(Identifier::Local(..))) => true,
+            (ArgumentKind::EnvVar, Expression::Identifier(Identifier::ENV(..))) => true,
+            (ArgumentKind::Jinja, Expression::JinjaExpressionValue(..)) => true,
+            (ArgumentKind::Map, Expression::Map(..)) => true,
+            (ArgumentKind::Value, Expression::JinjaExpressionValue(..)) => false,
+            (ArgumentKind::Value, _) => true,
+            _ => false,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ArgumentKind::String => "a string",
+            ArgumentKind::Number => "a number",
+            ArgumentKind::Identifier => "a name, like `too_long`",
+            ArgumentKind::EnvVar => "an environment variable, like `env.FEATURE_X`",
+            ArgumentKind::Jinja => "a Jinja expression, like `{{ this > 0 }}`",
+            ArgumentKind::Map => "a map, like `{ samples 5 }`",
+            ArgumentKind::Value => "a value, not a Jinja expression",
+        }
+    }
+}
+
+/// One positional argument of an attribute.
+#[derive(Debug)]
+pub(crate) struct ArgumentSignature {
+    pub(crate) name: &'static str,
+    pub(crate) kind: ArgumentKind,
+    pub(crate) required: bool,
+}
+
+/// The arguments an attribute takes, in order. Optional arguments are filled
+/// from left to right with whatever is left over once every required argument
+/// has one, so `@assert({{ expr }})` and `@assert(label, {{ expr }})` share a
+/// signature.
+#[derive(Debug)]
+pub(crate) struct AttributeSignature {
+    pub(crate) name: &'static str,
+    pub(crate) arguments: &'static [ArgumentSignature],
+    /// An example use, without the leading `@` or `@@`.
+    pub(crate) usage: &'static str,
+}
+
+const fn required(name: &'static str, kind: ArgumentKind) -> ArgumentSignature {
+    ArgumentSignature {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, kind: ArgumentKind) -> ArgumentSignature {
+    ArgumentSignature {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+/// Every attribute parser-database knows about.
+pub(crate) const SIGNATURES: &[AttributeSignature] = &[
+    AttributeSignature {
+        name: "alias",
+        arguments: &[required("name", ArgumentKind::String)],
+        usage: "alias(\"name\")",
+    },
+    AttributeSignature {
+        name: "description",
+        arguments: &[required("description", ArgumentKind::String)],
+        usage: "description(\"what this is\")",
+    },
+    AttributeSignature {
+        name: "skip",
+        arguments: &[],
+        usage: "skip",
+    },
+    AttributeSignature {
+        name: "dynamic",
+        arguments: &[],
+        usage: "dynamic",
+    },
+    AttributeSignature {
+        name: "assert",
+        arguments: &[
+            optional("label", ArgumentKind::Identifier),
+            required("expression", ArgumentKind::Jinja),
+        ],
+        usage: "assert({{ this > 0 }})",
+    },
+    AttributeSignature {
+        name: "check",
+        arguments: &[
+            required("label", ArgumentKind::Identifier),
+            required("expression", ArgumentKind::Jinja),
+        ],
+        usage: "check(too_long, {{ this|length < 10 }})",
+    },
+    AttributeSignature {
+        name: "if",
+        arguments: &[
+            required("env_var", ArgumentKind::EnvVar),
+            optional("equals", ArgumentKind::Value),
+        ],
+        usage: "if(env.STAGE, \"canary\")",
+    },
+    AttributeSignature {
+        name: "match",
+        arguments: &[
+            required("mode", ArgumentKind::String),
+            optional("options", ArgumentKind::Map),
+        ],
+        usage: "match(fuzzy, { max_distance 2 })",
+    },
+    AttributeSignature {
+        name: "bool_strings",
+        arguments: &[required("mode", ArgumentKind::String)],
+        usage: "bool_strings(lenient)",
+    },
+    AttributeSignature {
+        name: "stream.not_null",
+        arguments: &[],
+        usage: "stream.not_null",
+    },
+    AttributeSignature {
+        name: "stream.with_state",
+        arguments: &[],
+        usage: "stream.with_state",
+    },
+    AttributeSignature {
+        name: "confidence",
+        arguments: &[optional("samples", ArgumentKind::Number)],
+        usage: "confidence(5)",
+    },
+    AttributeSignature {
+        name: "self_consistency",
+        arguments: &[optional("options", ArgumentKind::Map)],
+        usage: "self_consistency({ samples 5, strategy \"majority\" })",
+    },
+    AttributeSignature {
+        name: "trace",
+        arguments: &[optional("options", ArgumentKind::Map)],
+        usage: "trace({ sample 0.1, capture \"metadata_only\" })",
+    },
+    AttributeSignature {
+        name: "expect",
+        arguments: &[
+            required("value", ArgumentKind::Value),
+            optional("options", ArgumentKind::Map),
+        ],
+        usage: "expect(value, { float_tolerance 0.01 })",
+    },
+];
+
+/// Looks up the signature of an attribute by name.
+pub(crate) fn signature(name: &str) -> Option<&'static AttributeSignature> {
+    SIGNATURES.iter().find(|signature| signature.name == name)
+}
+
+/// The arguments of an attribute, bound to the parameters of its signature.
+#[derive(Debug)]
+pub(crate) struct AttributeArguments<'a> {
+    signature: &'static AttributeSignature,
+    values: Vec<Option<&'a Expression>>,
+}
+
+impl<'a> AttributeArguments<'a> {
+    /// The argument given for the parameter `name`, if any.
+    pub(crate) fn get(&self, name: &str) -> Option<&'a Expression> {
+        self.signature
+            .arguments
+            .iter()
+            .position(|argument| argument.name == name)
+            .and_then(|idx| self.values[idx])
+    }
+}
+
+/// Checks the arguments of an attribute against its signature, and binds them
+/// to its parameters. Every mismatch is reported, on the argument at fault when
+/// there is one, and `None` is returned.
+pub(crate) fn bind_arguments<'a>(
+    attribute: &'a Attribute,
+    as_block: bool,
+    diagnostics: &mut Diagnostics,
+) -> Option<AttributeArguments<'a>> {
+    let attribute_name = format!(
+        "{}{}",
+        if as_block { "@@" } else { "@" },
+        attribute.name.name()
+    );
+    let Some(signature) = signature(attribute.name.name()) else {
+        diagnostics.push_error(DatamodelError::new_attribute_not_known_error(
+            attribute.name.name(),
+            attribute.span.clone(),
+        ));
+        return None;
+    };
+
+    let arguments = &attribute.arguments.arguments;
+    let max_count = signature.arguments.len();
+    let required_count = signature.arguments.iter().filter(|a| a.required).count();
+
+    if arguments.len() > max_count {
+        let surplus = &arguments[max_count..];
+        let span = Span::new(
+            surplus[0].span.file.clone(),
+            surplus[0].span.start,
+            surplus[surplus.len() - 1].span.end,
+        );
+        let message = match max_count {
+            0 => format!("takes no arguments, but received {}.", arguments.len()),
+            1 => format!(
+                "takes at most 1 argument, but received {}.",
+                arguments.len()
+            ),
+            _ => format!(
+                "takes at most {max_count} arguments, but received {}.",
+                arguments.len()
+            ),
+        };
+        diagnostics.push_error(DatamodelError::new_attribute_validation_error(
+            &message,
+            &attribute_name,
+            span,
+        ));
+        return None;
+    }
+
+    if arguments.len() < required_count {
+        // Match what was given against the required parameters in order, so
+        // that `@check({{ expr }})` is missing its label, not its expression.
+        let mut given = arguments.iter().peekable();
+        let missing = signature
+            .arguments
+            .iter()
+            .filter(|param| param.required)
+            .filter(|param| {
+                if given
+                    .peek()
+                    .is_some_and(|arg| param.kind.accepts(&arg.value))
+                {
+                    given.next();
+                    false
+                } else {
+                    true
+                }
+            })
+            .map(|param| format!("`{}`", param.name))
+            .collect::<Vec<_>>();
+        let message = format!(
+            "missing {} {}, as in `{}{}`.",
+            if missing.len() == 1 {
+                "argument"
+            } else {
+                "arguments"
+            },
+            missing.join(" and "),
+            if as_block { "@@" } else { "@" },
+            signature.usage
+        );
+        diagnostics.push_error(DatamodelError::new_attribute_validation_error(
+            &message,
+            &attribute_name,
+            attribute.span.clone(),
+        ));
+        return None;
+    }
+
+    let mut optional_count = arguments.len() - required_count;
+    let mut given = arguments.iter();
+    let mut values = Vec::with_capacity(max_count);
+    let mut valid = true;
+    for param in signature.arguments {
+        if !param.required {
+            if optional_count == 0 {
+                values.push(None);
+                continue;
+            }
+            optional_count -= 1;
+        }
+        let Some(arg) = given.next() else {
+            values.push(None);
+            continue;
+        };
+        if !param.kind.accepts(&arg.value) {
+            diagnostics.push_error(DatamodelError::new_attribute_validation_error(
+                &format!("`{}` must be {}.", param.name, param.kind.description()),
+                &attribute_name,
+                arg.span.clone(),
+            ));
+            valid = false;
+        }
+        values.push(Some(&arg.value));
+    }
+
+    valid.then_some(AttributeArguments { signature, values })
+}
+
+/// Checks the attributes attached to a type, e.g. `@check` on a field's type,
+/// recursing into the types it's made of.
+pub(crate) fn validate_type_attributes(field_type: &FieldType, diagnostics: &mut Diagnostics) {
+    for attribute in field_type.attributes() {
+        if signature(attribute.name.name()).is_some() {
+            bind_arguments(attribute, false, diagnostics);
+        }
+    }
+
+    match field_type {
+        FieldType::Symbol(..) | FieldType::Primitive(..) | FieldType::Literal(..) => {}
+        FieldType::List(_, inner, ..) => validate_type_attributes(inner, diagnostics),
+        FieldType::Tuple(_, items, ..) | FieldType::Union(_, items, ..) => {
+            for item in items {
+                validate_type_attributes(item, diagnostics);
+            }
+        }
+        FieldType::Map(_, kv, ..) => {
+            validate_type_attributes(&kv.0, diagnostics);
+            validate_type_attributes(&kv.1, diagnostics);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use baml_types::StreamingBehavior;
+use internal_baml_diagnostics::DatamodelError;
+use internal_baml_schema_ast::ast::FieldType;
+
+use crate::context::Context;
+
+/// Parses `@stream.not_null` and `@stream.with_state` on a class field of type
+/// `field_type`.
+pub(super) fn visit_streaming_attributes(
+    ctx: &mut Context<'_>,
+    field_type: Option<&FieldType>,
+) -> StreamingBehavior {
+    let mut streaming = StreamingBehavior::default();
+
+    if ctx.visit_optional_single_attr("stream.not_null") && ctx.validate_attribute_signature(false)
+    {
+        if field_type.is_some_and(FieldType::is_optional) {
+            // Optional fields may never get a value, which would hold back
+            // the whole class until the stream ends.
+            ctx.push_error(DatamodelError::new_validation_error(
+                "`@stream.not_null` doesn't apply to optional fields",
+                ctx.current_attribute().span.clone(),
+            ));
+        } else {
+            streaming.not_null = true;
+        }
+        ctx.validate_visited_arguments();
+    }
+
+    if ctx.visit_optional_single_attr("stream.with_state")
+        && ctx.validate_attribute_signature(false)
+    {
+        streaming.with_state = true;
+        ctx.validate_visited_arguments();
+    }
+
+    streaming
+}
@@ -13,31 +13,36 @@ pub(super) fn visit(ctx: &mut Context<'_>, span: &Span, as_block: bool) -> Optio
 
     let mut attributes = Attributes::default();
     // @alias or @@alias
-    if ctx.visit_optional_single_attr("alias") {
+    if ctx.visit_optional_single_attr("alias") && ctx.validate_attribute_signature(as_block) {
         visit_alias_attribute(&mut attributes, ctx);
         modified = true;
         ctx.validate_visited_arguments();
     }
 
-    if ctx.visit_optional_single_attr("description") {
+    if ctx.visit_optional_single_attr("description") && ctx.validate_attribute_signature(as_block) {
         visit_description_attribute(&mut attributes, ctx);
         modified = true;
         ctx.validate_visited_arguments();
     }
 
-    if ctx.visit_optional_single_attr("skip") {
+    if ctx.visit_optional_single_attr("skip") && ctx.validate_attribute_signature(as_block) {
         attributes.set_skip();
         modified = true;
         ctx.validate_visited_arguments();
     }
 
     if let Some((attribute_name, span)) = ctx.visit_repeated_attr_from_names(&["assert", "check"]) {
-        visit_constraint_attributes(attribute_name, span, &mut attributes, ctx);
-        modified = true;
-        ctx.validate_visited_arguments();
+        if ctx.validate_attribute_signature(as_block) {
+            visit_constraint_attributes(attribute_name, span, &mut attributes, ctx);
+            modified = true;
+            ctx.validate_visited_arguments();
+        }
     }
 
-    if as_block && ctx.visit_optional_single_attr("dynamic") {
+    if as_block
+        && ctx.visit_optional_single_attr("dynamic")
+        && ctx.validate_attribute_signature(as_block)
+    {
         attributes.set_dynamic_type();
         modified = true;
         ctx.validate_visited_arguments();
@@ -0,0 +1,64 @@
+use baml_types::StringOr;
+use internal_baml_diagnostics::{DatamodelError, Diagnostics};
+use internal_baml_schema_ast::ast::Attribute;
+
+use super::signatures::bind_arguments;
+use crate::types::{TraceCapture, TraceSettings};
+
+/// Parses `@@trace` or `@@trace({ sample 0.1, capture "metadata_only" })` on a
+/// function.
+pub fn attribute_as_trace(
+    attribute: &Attribute,
+    diagnostics: &mut Diagnostics,
+) -> Option<TraceSettings> {
+    let mut trace = TraceSettings {
+        sample: 1.0,
+        capture: TraceCapture::Full,
+        span: attribute.span.clone(),
+    };
+
+    let arguments = bind_arguments(attribute, true, diagnostics)?;
+    let Some(options) = arguments.get("options") else {
+        return Some(trace);
+    };
+    let options = options.to_unresolved_value(diagnostics)?;
+    let Some(options) = options.as_map() else {
+        diagnostics.push_error(DatamodelError::new_validation_error(
+            "Trace options must be a map, like { sample 0.1 }",
+            options.meta().clone(),
+        ));
+        return None;
+    };
+    for (key, (key_span, option)) in options {
+        match key.as_str() {
+            "sample" => match option.as_numeric().and_then(|n| n.parse::<f64>().ok()) {
+                Some(sample) if (0.0..=1.0).contains(&sample) => trace.sample = sample,
+                _ => diagnostics.push_error(DatamodelError::new_validation_error(
+                    "`sample` must be a number between 0 and 1",
+                    option.meta().clone(),
+                )),
+            },
+            "capture" => match option.as_str() {
+                Some(StringOr::Value(capture)) if capture == "full" => {
+                    trace.capture = TraceCapture::Full
+                }
+                Some(StringOr::Value(capture)) if capture == "hashed" => {
+                    trace.capture = TraceCapture::Hashed
+                }
+                Some(StringOr::Value(capture)) if capture == "metadata_only" => {
+                    trace.capture = TraceCapture::MetadataOnly
+                }
+                _ => diagnostics.push_error(DatamodelError::new_validation_error(
+                    "`capture` must be \"full\", \"hashed\" or \"metadata_only\"",
+                    option.meta().clone(),
+                )),
+            },
+            _ => diagnostics.push_error(DatamodelError::new_property_not_known_error(
+                key,
+                key_span.clone(),
+                ["sample", "capture"].to_vec(),
+            )),
+        }
+    }
+    Some(trace)
+}
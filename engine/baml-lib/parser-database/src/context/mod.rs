@@ -1,9 +1,11 @@
+use std::path::PathBuf;
+
 use internal_baml_diagnostics::{DatamodelWarning, Span};
 use internal_baml_schema_ast::ast::{Argument, ArgumentId, Attribute};
 
 use crate::{
-    ast, ast::WithName, interner::StringInterner, names::Names, types::Types, DatamodelError,
-    Diagnostics, StringId,
+    ast, ast::WithName, attributes::signatures::bind_arguments, interner::StringInterner,
+    names::Names, types::Types, DatamodelError, Diagnostics, StringId,
 };
 
 use self::attributes::AttributesValidationState;
@@ -28,6 +30,7 @@ pub(crate) struct Context<'db> {
     pub(crate) names: &'db mut Names,
     pub(crate) types: &'db mut Types,
     pub(crate) diagnostics: &'db mut Diagnostics,
+    pub(crate) roots: &'db [PathBuf],
     attributes: AttributesValidationState, // state machine for attribute validation
 }
 
@@ -37,6 +40,7 @@ impl<'db> Context<'db> {
         interner: &'db mut StringInterner,
         names: &'db mut Names,
         types: &'db mut Types,
+        roots: &'db [PathBuf],
         diagnostics: &'db mut Diagnostics,
     ) -> Self {
         Context {
@@ -45,6 +49,7 @@ impl<'db> Context<'db> {
             names,
             types,
             diagnostics,
+            roots,
             attributes: Default::default(),
         }
     }
@@ -180,6 +185,20 @@ impl<'db> Context<'db> {
         self.set_attribute(first_idx, first)
     }
 
+    /// Checks the arguments of the current attribute against its signature, see
+    /// `attributes::signatures`. On a mismatch, the errors are reported and the arguments are
+    /// discarded, so the attribute must not be visited any further.
+    #[must_use]
+    pub(crate) fn validate_attribute_signature(&mut self, as_block: bool) -> bool {
+        let attribute = self.current_attribute();
+        if bind_arguments(attribute, as_block, self.diagnostics).is_some() {
+            true
+        } else {
+            self.discard_arguments();
+            false
+        }
+    }
+
     /// Gets the argument with the given name in the current attribute, or if it is not found, the
     /// first unnamed argument.
     ///
@@ -223,7 +242,7 @@ impl<'db> Context<'db> {
         let diagnostics = &mut self.diagnostics;
         while let Some(arg_idx) = self.attributes.args.pop_front() {
             let arg = &attr.arguments[arg_idx];
-            diagnostics.push_error(DatamodelError::new_unused_argument_error(arg.span.clone()));
+            diagnostics.push_warning(DatamodelWarning::new_unused_argument(arg.span.clone()));
         }
 
         self.discard_arguments();
@@ -36,20 +36,25 @@ mod names;
 mod tarjan;
 mod types;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 pub use coerce_expression::{coerce, coerce_array, coerce_opt};
 use either::Either;
 pub use internal_baml_schema_ast::ast;
-use internal_baml_schema_ast::ast::SchemaAst;
+use internal_baml_schema_ast::ast::{SchemaAst, WithSpan};
 pub use tarjan::Tarjan;
 pub use types::{
-    Attributes, ClientProperties, ContantDelayStrategy, ExponentialBackoffStrategy, PrinterType,
-    PromptAst, PromptVariable, RetryPolicy, RetryPolicyStrategy, StaticType,
+    Attributes, ClientProperties, Condition, ConfidenceMode, ContantDelayStrategy,
+    ExponentialBackoffStrategy, PrinterType, PromptAst, PromptVariable, RetryHedge, RetryJitter,
+    RetryPolicy, RetryPolicyStrategy, SelfConsistency, StaticType, TraceCapture, TraceSettings,
+    VotingStrategy,
 };
 
 use self::{context::Context, interner::StringId, types::Types};
-use internal_baml_diagnostics::{DatamodelError, Diagnostics};
+use internal_baml_diagnostics::{DatamodelError, Diagnostics, SourceFile, SourcePath};
 use names::Names;
 
 /// ParserDatabase is a container for a Schema AST, together with information
@@ -76,6 +81,7 @@ pub struct ParserDatabase {
     interner: interner::StringInterner,
     names: Names,
     types: Types,
+    roots: Vec<PathBuf>,
 }
 
 impl Default for ParserDatabase {
@@ -88,16 +94,31 @@ impl ParserDatabase {
     /// Create a new, empty ParserDatabase.
     pub fn new() -> Self {
         ParserDatabase {
-            ast: ast::SchemaAst { tops: vec![] },
+            ast: ast::SchemaAst::new(),
             interner: Default::default(),
             names: Default::default(),
             types: Default::default(),
+            roots: Vec::new(),
         }
     }
 
+    /// Registers a `baml_src` directory the ASTs come from. When definitions
+    /// from more than one root are composed, a name defined in two roots is
+    /// reported as a conflict between them.
+    pub fn add_root(&mut self, root: impl AsRef<Path>) {
+        self.roots.push(SourcePath::new(root).into_path_buf());
+    }
+
+    /// The registered root `file` is in, the innermost one if roots are
+    /// nested.
+    pub fn root_of(&self, file: &SourceFile) -> Option<&Path> {
+        root_of(&self.roots, file)
+    }
+
     /// See the docs on [ParserDatabase](/struct.ParserDatabase.html).
     pub fn add_ast(&mut self, ast: SchemaAst) {
         self.ast.tops.extend(ast.tops);
+        self.ast.suppressions.extend(ast.suppressions);
     }
 
     /// See the docs on [ParserDatabase](/struct.ParserDatabase.html).
@@ -107,6 +128,7 @@ impl ParserDatabase {
             &mut self.interner,
             &mut self.names,
             &mut self.types,
+            &self.roots,
             diag,
         );
 
@@ -159,7 +181,14 @@ impl ParserDatabase {
                         |dep| match self.find_type_by_str(dep) {
                             Some(Either::Left(cls)) => Some(cls.id),
                             Some(Either::Right(_)) => None,
-                            None => panic!("Unknown class `{dep}`"),
+                            None => {
+                                diag.push_error(DatamodelError::new_type_not_found_error(
+                                    dep,
+                                    self.valid_type_names(),
+                                    self.ast[*id].span().clone(),
+                                ));
+                                None
+                            }
                         },
                     ));
                 (*id, deps)
@@ -181,34 +210,37 @@ impl ParserDatabase {
             .iter()
             .map(|(&k, func)| {
                 let (input, output) = &func.dependencies;
-                let input_deps = input
-                    .iter()
-                    .filter_map(|f| match self.find_type_by_str(f) {
-                        Some(Either::Left(walker)) => Some(walker.dependencies().iter().cloned()),
-                        Some(Either::Right(_)) => None,
-                        _ => panic!("Unknown class `{}`", f),
-                    })
-                    .flatten()
-                    .collect::<HashSet<_>>();
-
-                let output_deps = output
-                    .iter()
-                    .filter_map(|f| match self.find_type_by_str(f) {
-                        Some(Either::Left(walker)) => Some(walker.dependencies().iter().cloned()),
-                        Some(Either::Right(_)) => None,
-                        _ => panic!("Unknown class `{}`", f),
-                    })
-                    .flatten()
-                    .collect::<HashSet<_>>();
+                let mut expand = |deps: &HashSet<String>| {
+                    deps.iter()
+                        .filter_map(|f| match self.find_type_by_str(f) {
+                            Some(Either::Left(walker)) => {
+                                Some(walker.dependencies().iter().cloned())
+                            }
+                            Some(Either::Right(_)) => None,
+                            None => {
+                                diag.push_error(DatamodelError::new_type_not_found_error(
+                                    f,
+                                    self.valid_type_names(),
+                                    self.ast[k].span().clone(),
+                                ));
+                                None
+                            }
+                        })
+                        .flatten()
+                        .collect::<HashSet<_>>()
+                };
+                let input_deps = expand(input);
+                let output_deps = expand(output);
 
                 (k, (input_deps, output_deps))
             })
             .collect::<Vec<_>>();
 
         for (id, (input, output)) in extends {
-            let val = self.types.function.get_mut(&id).unwrap();
-            val.dependencies.0.extend(input);
-            val.dependencies.1.extend(output);
+            if let Some(val) = self.types.function.get_mut(&id) {
+                val.dependencies.0.extend(input);
+                val.dependencies.1.extend(output);
+            }
         }
     }
 
@@ -222,6 +254,15 @@ impl ParserDatabase {
     }
 }
 
+/// The innermost of `roots` that contains `file`.
+pub(crate) fn root_of<'a>(roots: &'a [PathBuf], file: &SourceFile) -> Option<&'a Path> {
+    roots
+        .iter()
+        .filter(|root| file.path_buf().starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .map(PathBuf::as_path)
+}
+
 impl std::fmt::Debug for ParserDatabase {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("ParserDatabase { ... }")
@@ -479,4 +520,39 @@ mod test {
             &[&["RecMap"]],
         )
     }
+
+    #[test]
+    fn duplicate_across_roots_names_both() {
+        let mut db = ParserDatabase::new();
+        let mut diag = Diagnostics::new(PathBuf::from("app/baml_src"));
+        for (root, file, baml) in [
+            (
+                "shared/baml_src",
+                "shared/baml_src/resume.baml",
+                "class Resume {\n  name string\n}",
+            ),
+            (
+                "app/baml_src",
+                "app/baml_src/types.baml",
+                "class Resume {\n  email string\n}",
+            ),
+        ] {
+            db.add_root(root);
+            let source = SourceFile::new_static(PathBuf::from(file), baml);
+            let (ast, _) = parse_schema(Path::new(root), &source).unwrap();
+            db.add_ast(ast);
+        }
+
+        let errors = db.validate(&mut diag).unwrap_err();
+        let error = &errors.errors()[0];
+        assert_eq!(
+            error.message(),
+            "The class \"Resume\" is defined in both shared/baml_src and app/baml_src. Names must be unique across all baml_src directories."
+        );
+        assert_eq!(error.span().file.path(), "app/baml_src/types.baml");
+        assert_eq!(
+            db.root_of(&error.span().file),
+            Some(Path::new("app/baml_src"))
+        );
+    }
 }
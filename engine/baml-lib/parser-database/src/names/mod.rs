@@ -1,5 +1,7 @@
 mod validate_reserved_names;
 
+use std::path::PathBuf;
+
 use crate::{
     ast::{self, TopId, WithAttributes, WithName, WithSpan},
     coerce, coerce_array, Context, DatamodelError, StaticType, StringId,
@@ -20,6 +22,8 @@ pub(super) struct Names {
     pub(super) tops: HashMap<StringId, TopId>,
     /// Generators have their own namespace.
     pub(super) generators: HashMap<StringId, TopId>,
+    /// Profiles have their own namespace.
+    pub(super) profiles: HashMap<StringId, TopId>,
     /// Tests have their own namespace.
     pub(super) tests: HashMap<StringId, HashMap<StringId, TopId>>,
     pub(super) model_fields: HashMap<(ast::TypeExpId, StringId), ast::FieldId>,
@@ -31,6 +35,7 @@ pub(super) struct Names {
 ///
 /// - Model, enum and type alias names
 /// - Generators
+/// - Profiles
 /// - Model fields for each model
 pub(super) fn resolve_names(ctx: &mut Context<'_>) {
     let mut tmp_names: HashSet<&str> = HashSet::default(); // throwaway container for duplicate checking
@@ -71,17 +76,22 @@ pub(super) fn resolve_names(ctx: &mut Context<'_>) {
                     validate_attribute_identifiers(field, ctx);
 
                     let field_name_id = ctx.interner.intern(field.name());
-                    if names
+                    if let Some(existing) = names
                         .model_fields
                         .insert((model_id, field_name_id), field_id)
-                        .is_some()
                     {
-                        ctx.push_error(DatamodelError::new_duplicate_field_error(
-                            ast_class.identifier().name(),
-                            field.name(),
-                            "class",
-                            field.identifier().span().clone(),
-                        ))
+                        ctx.push_error(
+                            DatamodelError::new_duplicate_field_error(
+                                ast_class.identifier().name(),
+                                field.name(),
+                                "class",
+                                field.identifier().span().clone(),
+                            )
+                            .with_related(
+                                ast_class[existing].identifier().span().clone(),
+                                format!("field \"{}\" first defined here", field.name()),
+                            ),
+                        )
                     }
                 }
 
@@ -140,6 +150,15 @@ pub(super) fn resolve_names(ctx: &mut Context<'_>) {
                 Some(either::Left(&mut names.generators))
             }
 
+            // Aliases are checked for duplicates across all `models` blocks.
+            (_, ast::Top::Models(_)) => None,
+
+            (_, ast::Top::Profile(profile)) => {
+                validate_profile_name(profile, ctx.diagnostics);
+                check_for_duplicate_properties(top, profile.fields(), &mut tmp_names, ctx);
+                Some(either::Left(&mut names.profiles))
+            }
+
             (ast::TopId::TestCase(testcase_id), ast::Top::TestCase(testcase)) => {
                 validate_test(testcase, ctx.diagnostics);
                 check_for_duplicate_properties(top, testcase.fields(), &mut tmp_names, ctx);
@@ -204,17 +223,45 @@ fn insert_name(
     if let Some(existing) = namespace.insert(name, top_id) {
         let current_type = top.get_type();
         if current_type != "impl<llm>" && current_type != "impl<?>" {
-            ctx.push_error(duplicate_top_error(&ctx.ast[existing], top));
+            ctx.push_error(duplicate_top_error(&ctx.ast[existing], top, ctx.roots));
         }
     }
 }
 
-fn duplicate_top_error(existing: &ast::Top, duplicate: &ast::Top) -> DatamodelError {
-    DatamodelError::new_duplicate_top_error(
-        duplicate.name(),
-        duplicate.get_type(),
-        existing.get_type(),
-        duplicate.identifier().span().clone(),
+fn duplicate_top_error(
+    existing: &ast::Top,
+    duplicate: &ast::Top,
+    roots: &[PathBuf],
+) -> DatamodelError {
+    let span = duplicate.identifier().span().clone();
+    let existing_span = existing.identifier().span();
+    let error = match (
+        crate::root_of(roots, &existing_span.file),
+        crate::root_of(roots, &span.file),
+    ) {
+        (Some(existing_root), Some(root)) if existing_root != root => {
+            DatamodelError::new_duplicate_root_definition_error(
+                duplicate.name(),
+                duplicate.get_type(),
+                &existing_root.display().to_string(),
+                &root.display().to_string(),
+                span,
+            )
+        }
+        _ => DatamodelError::new_duplicate_top_error(
+            duplicate.name(),
+            duplicate.get_type(),
+            existing.get_type(),
+            span,
+        ),
+    };
+    error.with_related(
+        existing_span.clone(),
+        format!(
+            "{} \"{}\" first defined here",
+            existing.get_type(),
+            existing.name()
+        ),
     )
 }
 
@@ -16,6 +16,13 @@ pub(crate) fn validate_generator_name(
     validate_name("generator", ast_gen.identifier(), diagnostics, false);
 }
 
+pub(crate) fn validate_profile_name(
+    ast_profile: &ast::ValueExprBlock,
+    diagnostics: &mut Diagnostics,
+) {
+    validate_name("profile", ast_profile.identifier(), diagnostics, false);
+}
+
 pub(crate) fn validate_client_name(
     ast_client: &ast::ValueExprBlock,
     diagnostics: &mut Diagnostics,
@@ -117,7 +124,7 @@ fn validate_name(
                 span.clone(),
             ))
         }
-        ast::Identifier::Local(val, span) => if require_upper_case && !val.chars().next().unwrap().is_uppercase() {
+        ast::Identifier::Local(val, span) => if require_upper_case && !val.starts_with(char::is_uppercase) {
             Err(DatamodelError::new_name_error(
                 _type,
                 "Must start with an uppercase letter.",
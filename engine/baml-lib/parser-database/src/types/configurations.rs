@@ -1,5 +1,6 @@
 use baml_types::Constraint;
 use baml_types::UnresolvedValue;
+use indexmap::IndexMap;
 use internal_baml_diagnostics::{DatamodelError, DatamodelWarning, Span};
 use internal_baml_schema_ast::ast::{
     Attribute, ValExpId, ValueExprBlock, WithIdentifier, WithName, WithSpan,
@@ -8,10 +9,12 @@ use regex::Regex;
 use std::{collections::HashSet, ops::Deref};
 
 use crate::attributes::constraint::attribute_as_constraint;
+use crate::attributes::expect::attribute_as_expectation;
 use crate::{coerce, coerce_array, coerce_expression::coerce_map, context::Context};
 
 use super::{
-    Attributes, ContantDelayStrategy, ExponentialBackoffStrategy, RetryPolicy, RetryPolicyStrategy,
+    Attributes, ContantDelayStrategy, ExponentialBackoffStrategy, Profile, RetryHedge, RetryJitter,
+    RetryPolicy, RetryPolicyStrategy,
 };
 
 fn dedent(s: &str) -> String {
@@ -44,8 +47,14 @@ pub(crate) fn visit_retry_policy<'db>(
     let mut max_reties = None;
 
     let mut strategy = Some(RetryPolicyStrategy::ConstantDelay(
-        super::ContantDelayStrategy { delay_ms: 200 },
+        super::ContantDelayStrategy {
+            delay_ms: 200,
+            jitter: RetryJitter::None,
+        },
     ));
+    let mut retry_on = None;
+    let mut hedge_after_ms = None;
+    let mut max_hedges = None;
     let mut options = None;
 
     config
@@ -71,6 +80,34 @@ pub(crate) fn visit_retry_policy<'db>(
                     }
                 }
             }
+            ("retry_on", Some(val)) => {
+                if let Some(codes) = coerce_array(val, &coerce::integer, ctx.diagnostics) {
+                    if codes.iter().all(|code| (400..600).contains(code)) {
+                        retry_on = Some(codes.into_iter().map(|code| code as u16).collect());
+                    } else {
+                        ctx.push_error(DatamodelError::new_validation_error(
+                            "`retry_on` must be a list of HTTP error status codes, like [429, 503]",
+                            val.span().clone(),
+                        ));
+                    }
+                }
+            }
+            ("hedge_after_ms", Some(val)) => match coerce::integer(val, ctx.diagnostics) {
+                Some(ms) if ms > 0 => hedge_after_ms = Some(ms as u32),
+                Some(_) => ctx.push_error(DatamodelError::new_validation_error(
+                    "`hedge_after_ms` must be positive",
+                    val.span().clone(),
+                )),
+                None => {}
+            },
+            ("max_hedges", Some(val)) => match coerce::integer(val, ctx.diagnostics) {
+                Some(count) if count > 0 => max_hedges = Some((count as u32, val.span())),
+                Some(_) => ctx.push_error(DatamodelError::new_validation_error(
+                    "`max_hedges` must be positive",
+                    val.span().clone(),
+                )),
+                None => {}
+            },
             ("options", Some(val)) => match val.to_unresolved_value(ctx.diagnostics) {
                 Some(UnresolvedValue::<Span>::Map(kv, _)) => options = Some(kv),
                 Some(other) => {
@@ -84,9 +121,31 @@ pub(crate) fn visit_retry_policy<'db>(
             (name, Some(_)) => ctx.push_error(DatamodelError::new_property_not_known_error(
                 name,
                 f.identifier().span().clone(),
-                ["max_retries", "strategy", "options"].to_vec(),
+                [
+                    "max_retries",
+                    "strategy",
+                    "retry_on",
+                    "hedge_after_ms",
+                    "max_hedges",
+                    "options",
+                ]
+                .to_vec(),
             )),
         });
+    let hedge = match (hedge_after_ms, max_hedges) {
+        (Some(after_ms), max_hedges) => Some(RetryHedge {
+            after_ms,
+            max_hedges: max_hedges.map_or(1, |(count, _)| count),
+        }),
+        (None, Some((_, span))) => {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "`max_hedges` only applies with `hedge_after_ms`",
+                span.clone(),
+            ));
+            None
+        }
+        (None, None) => None,
+    };
     match (max_reties, strategy) {
         (Some(max_retries), Some(strategy)) => {
             ctx.types.retry_policies.insert(
@@ -94,6 +153,8 @@ pub(crate) fn visit_retry_policy<'db>(
                 RetryPolicy {
                     max_retries,
                     strategy,
+                    retry_on,
+                    hedge,
                     options,
                 },
             );
@@ -112,6 +173,115 @@ pub(crate) fn visit_retry_policy<'db>(
     }
 }
 
+/// Collects the aliases of a `models` block. There may be several, e.g. one
+/// per file, but each alias is defined once.
+pub(crate) fn visit_models<'db>(config: &'db ValueExprBlock, ctx: &mut Context<'db>) {
+    for (_idx, f) in config.iter_fields() {
+        let Some(val) = &f.expr else {
+            ctx.push_error(DatamodelError::new_config_property_missing_value_error(
+                f.name(),
+                config.name(),
+                "models",
+                f.identifier().span().clone(),
+            ));
+            continue;
+        };
+        let Some(value) = val.to_unresolved_value(ctx.diagnostics) else {
+            continue;
+        };
+        let model = match value.as_static_str() {
+            Ok(model) => model.to_string(),
+            Err(err) => {
+                ctx.push_error(DatamodelError::new_validation_error(
+                    &format!("Model alias `{}` must be a model name: {err}", f.name()),
+                    value.meta().clone(),
+                ));
+                continue;
+            }
+        };
+        if ctx.types.model_aliases.contains_key(f.name()) {
+            ctx.push_error(DatamodelError::new_validation_error(
+                &format!("Model alias `{}` is already defined", f.name()),
+                f.identifier().span().clone(),
+            ));
+            continue;
+        }
+        ctx.types
+            .model_aliases
+            .insert(f.name().to_string(), (f.identifier().span().clone(), model));
+    }
+}
+
+pub(crate) fn visit_profile<'db>(
+    idx: ValExpId,
+    config: &'db ValueExprBlock,
+    ctx: &mut Context<'db>,
+) {
+    let mut client_defaults = IndexMap::new();
+    let mut env_overrides = IndexMap::new();
+
+    config
+        .iter_fields()
+        .for_each(|(_idx, f)| match (f.name(), &f.expr) {
+            (name, None) => {
+                ctx.push_error(DatamodelError::new_config_property_missing_value_error(
+                    name,
+                    config.name(),
+                    "profile",
+                    f.identifier().span().clone(),
+                ))
+            }
+            ("client_defaults", Some(val)) => match val.to_unresolved_value(ctx.diagnostics) {
+                Some(UnresolvedValue::<Span>::Map(mut kv, _)) => {
+                    super::resolve_model_alias(&mut kv, ctx);
+                    client_defaults = kv;
+                }
+                Some(other) => {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        "`client_defaults` must be a map",
+                        other.meta().clone(),
+                    ));
+                }
+                None => {}
+            },
+            ("env_overrides", Some(val)) => match val.to_unresolved_value(ctx.diagnostics) {
+                Some(UnresolvedValue::<Span>::Map(kv, _)) => {
+                    for (key, (_, value)) in kv {
+                        match value.as_static_str() {
+                            Ok(s) => {
+                                env_overrides.insert(key, (value.meta().clone(), s.to_string()));
+                            }
+                            Err(err) => ctx.push_error(DatamodelError::new_validation_error(
+                                &format!("`env_overrides.{key}` must be a string: {err}"),
+                                value.meta().clone(),
+                            )),
+                        }
+                    }
+                }
+                Some(other) => {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        "`env_overrides` must be a map",
+                        other.meta().clone(),
+                    ));
+                }
+                None => {}
+            },
+            (name, Some(_)) => ctx.push_error(DatamodelError::new_property_not_known_error(
+                name,
+                f.identifier().span().clone(),
+                ["client_defaults", "env_overrides"].to_vec(),
+            )),
+        });
+
+    ctx.types.profiles.insert(
+        idx,
+        Profile {
+            client_defaults,
+            env_overrides,
+        },
+    );
+}
+
 fn visit_strategy(
     field_span: &Span,
     val: Vec<((&str, &Span), &internal_baml_schema_ast::ast::Expression)>,
@@ -121,6 +291,7 @@ fn visit_strategy(
     let mut delay_ms = None;
     let mut max_delay_ms = None;
     let mut multiplier = None;
+    let mut jitter = RetryJitter::None;
 
     val.iter()
         .for_each(|(name_and_span, val)| match name_and_span.0 {
@@ -144,6 +315,21 @@ fn visit_strategy(
                     multiplier = Some((_val, val.span()))
                 }
             }
+            "jitter" => match coerce::string_with_span(val, diagnostics) {
+                Some(("none", _)) => jitter = RetryJitter::None,
+                Some(("full", _)) => jitter = RetryJitter::Full,
+                Some(("equal", _)) => jitter = RetryJitter::Equal,
+                Some((name, span)) => diagnostics.push_error(
+                    internal_baml_diagnostics::DatamodelError::new_validation_error(
+                        &format!(
+                            "Unknown jitter: {}. Options are `none`, `full` or `equal`",
+                            name
+                        ),
+                        span.clone(),
+                    ),
+                ),
+                None => {}
+            },
             _ => {}
         });
 
@@ -167,6 +353,7 @@ fn visit_strategy(
             }
             Some(RetryPolicyStrategy::ConstantDelay(ContantDelayStrategy {
                 delay_ms: delay_ms.unwrap_or(200) as u32,
+                jitter,
             }))
         }
         Some(("exponential_backoff", _)) => Some(RetryPolicyStrategy::ExponentialBackoff(
@@ -174,6 +361,7 @@ fn visit_strategy(
                 delay_ms: delay_ms.unwrap_or(200) as u32,
                 multiplier: multiplier.map(|(v, _)| v as f32).unwrap_or(1.5),
                 max_delay_ms: max_delay_ms.map(|(v, _)| v as u32).unwrap_or(10000),
+                jitter,
             },
         )),
         Some((name, span)) => {
@@ -205,6 +393,7 @@ pub(crate) fn visit_test_case<'db>(
 ) {
     let mut functions = None;
     let mut args = None;
+    let mut dataset = None;
 
     config
         .iter_fields()
@@ -243,6 +432,11 @@ pub(crate) fn visit_test_case<'db>(
                     );
                 }
             }
+            ("dataset", Some(val)) => {
+                if let Some((path, span)) = coerce::string_with_span(val, ctx.diagnostics) {
+                    dataset = Some((path.to_string(), span.clone()));
+                }
+            }
             ("args", Some(val)) => match val.to_unresolved_value(ctx.diagnostics) {
                 Some(UnresolvedValue::<Span>::Map(kv, span)) => args = Some((span, kv)),
                 Some(other) => {
@@ -263,15 +457,32 @@ pub(crate) fn visit_test_case<'db>(
     let constraints: Vec<(Constraint, Span, Span)> = config
         .attributes
         .iter()
-        .filter_map(|attribute| {
-            let (maybe_constraint, errors) = attribute_as_constraint(attribute);
-            for error in errors {
-                ctx.push_error(error);
-            }
-            maybe_constraint
-        })
+        .filter_map(|attribute| attribute_as_constraint(attribute, ctx.diagnostics))
         .collect();
 
+    let mut expect = None;
+    for attribute in config
+        .attributes
+        .iter()
+        .filter(|a| a.name.to_string() == "expect")
+    {
+        if expect.is_some() {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "Tests may have at most one `@@expect`",
+                attribute.span.clone(),
+            ));
+        } else {
+            expect = attribute_as_expectation(attribute, ctx.diagnostics);
+        }
+    }
+
+    // With a dataset, `args` are optional and shared by every row.
+    let args = match (args, &dataset) {
+        (Some(args), _) => Some(args),
+        (None, Some((_, span))) => Some((span.clone(), IndexMap::new())),
+        (None, None) => None,
+    };
+
     match (functions, args) {
         (None, _) => ctx.push_error(DatamodelError::new_validation_error(
             "Missing `functions` property",
@@ -287,8 +498,10 @@ pub(crate) fn visit_test_case<'db>(
                 super::TestCase {
                     functions,
                     args,
-                    args_field_span: args_field_span.clone(),
+                    args_field_span,
                     constraints,
+                    expect,
+                    dataset,
                 },
             );
         }
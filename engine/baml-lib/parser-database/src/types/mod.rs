@@ -2,15 +2,22 @@ use ouroboros::self_referencing;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::ops::Deref;
+use std::path::PathBuf;
 
-use crate::coerce;
+use crate::attributes::condition::attribute_as_condition;
+use crate::attributes::confidence::attribute_as_confidence;
+use crate::attributes::self_consistency::attribute_as_self_consistency;
+use crate::attributes::trace::attribute_as_trace;
 use crate::types::configurations::visit_test_case;
+use crate::{coerce, coerce_array, coerce_opt};
 use crate::{context::Context, DatamodelError};
 
+use baml_types::ClientKind;
 use baml_types::Constraint;
+use baml_types::PiiKind;
 use baml_types::{StringOr, UnresolvedValue};
 use indexmap::IndexMap;
-use internal_baml_diagnostics::{Diagnostics, Span};
+use internal_baml_diagnostics::{DatamodelWarning, Diagnostics, Span};
 use internal_baml_prompt_parser::ast::{ChatBlock, PrinterBlock, Variable};
 use internal_baml_schema_ast::ast::{
     self, Expression, FieldId, RawString, ValExpId, WithIdentifier, WithName, WithSpan,
@@ -25,9 +32,23 @@ pub use crate::attributes::Attributes;
 pub(crate) use types::EnumAttributes;
 pub(crate) use types::*;
 
-use self::configurations::visit_retry_policy;
+use self::configurations::{visit_models, visit_profile, visit_retry_policy};
 
 pub(super) fn resolve_types(ctx: &mut Context<'_>) {
+    // The model catalog goes first, since profiles and clients refer to it.
+    for (_, top) in ctx.ast.iter_tops() {
+        if let ast::Top::Models(models) = top {
+            visit_models(models, ctx);
+        }
+    }
+
+    // Then profiles, since clients are parsed with their `client_defaults`.
+    for (top_id, top) in ctx.ast.iter_tops() {
+        if let (ast::TopId::Profile(idx), ast::Top::Profile(profile)) = (top_id, top) {
+            visit_profile(idx, profile, ctx);
+        }
+    }
+
     for (top_id, top) in ctx.ast.iter_tops() {
         match (top_id, top) {
             (ast::TopId::Enum(idx), ast::Top::Enum(model)) => visit_enum(idx, model, ctx),
@@ -43,11 +64,16 @@ pub(super) fn resolve_types(ctx: &mut Context<'_>) {
             (_, ast::Top::TemplateString(_)) => unreachable!("TemplateString misconfigured"),
 
             (ast::TopId::Function(idx), ast::Top::Function(function)) => {
-                visit_function(idx, function, ctx)
+                visit_function(idx, function, ctx);
+                visit_condition(idx, function, ctx);
+                visit_confidence(idx, function, ctx);
+                visit_self_consistency(idx, function, ctx);
+                visit_trace(idx, function, ctx);
             }
             (_, ast::Top::Function(_)) => unreachable!("Function misconfigured"),
             (ast::TopId::Client(idx), ast::Top::Client(client)) => {
                 visit_client(idx, client, ctx);
+                visit_condition(idx, client, ctx);
             }
 
             (_, ast::Top::Client(_)) => unreachable!("Client misconfigured"),
@@ -57,6 +83,7 @@ pub(super) fn resolve_types(ctx: &mut Context<'_>) {
             (_, ast::Top::RetryPolicy(_)) => unreachable!("RetryPolicy misconfigured"),
             (ast::TopId::TestCase(idx), ast::Top::TestCase(config)) => {
                 visit_test_case(idx, config, ctx);
+                visit_condition(idx, config, ctx);
             }
             (_, ast::Top::TestCase(_)) => unreachable!("TestCase misconfigured"),
 
@@ -64,6 +91,83 @@ pub(super) fn resolve_types(ctx: &mut Context<'_>) {
         }
     }
 }
+
+fn visit_condition(idx: ast::ValExpId, block: &ast::ValueExprBlock, ctx: &mut Context<'_>) {
+    let mut conditions = block
+        .attributes
+        .iter()
+        .filter(|a| a.name.to_string() == "if");
+    let Some(attribute) = conditions.next() else {
+        return;
+    };
+    for extra in conditions {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "Only one `@@if` is allowed. Make one environment variable for the combination instead",
+            extra.span.clone(),
+        ));
+    }
+    if let Some(condition) = attribute_as_condition(attribute, ctx.diagnostics) {
+        ctx.types.conditions.insert(idx, condition);
+    }
+}
+
+fn visit_confidence(idx: ast::ValExpId, block: &ast::ValueExprBlock, ctx: &mut Context<'_>) {
+    let mut attributes = block
+        .attributes
+        .iter()
+        .filter(|a| a.name.to_string() == "confidence");
+    let Some(attribute) = attributes.next() else {
+        return;
+    };
+    for extra in attributes {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "Only one `@@confidence` is allowed",
+            extra.span.clone(),
+        ));
+    }
+    if let Some(confidence) = attribute_as_confidence(attribute, ctx.diagnostics) {
+        ctx.types.confidence.insert(idx, confidence);
+    }
+}
+
+fn visit_self_consistency(idx: ast::ValExpId, block: &ast::ValueExprBlock, ctx: &mut Context<'_>) {
+    let mut attributes = block
+        .attributes
+        .iter()
+        .filter(|a| a.name.to_string() == "self_consistency");
+    let Some(attribute) = attributes.next() else {
+        return;
+    };
+    for extra in attributes {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "Only one `@@self_consistency` is allowed",
+            extra.span.clone(),
+        ));
+    }
+    if let Some(self_consistency) = attribute_as_self_consistency(attribute, ctx.diagnostics) {
+        ctx.types.self_consistency.insert(idx, self_consistency);
+    }
+}
+
+fn visit_trace(idx: ast::ValExpId, block: &ast::ValueExprBlock, ctx: &mut Context<'_>) {
+    let mut attributes = block
+        .attributes
+        .iter()
+        .filter(|a| a.name.to_string() == "trace");
+    let Some(attribute) = attributes.next() else {
+        return;
+    };
+    for extra in attributes {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "Only one `@@trace` is allowed",
+            extra.span.clone(),
+        ));
+    }
+    if let Some(trace) = attribute_as_trace(attribute, ctx.diagnostics) {
+        ctx.types.trace.insert(idx, trace);
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Variables used inside of raw strings.
 pub enum PromptVariable {
@@ -139,6 +243,9 @@ pub struct ClientProperties {
     pub retry_policy: Option<(String, Span)>,
     /// The options for the client
     pub options: UnresolvedClientProperty<Span>,
+    /// The options with each profile's `client_defaults` filled in, by
+    /// profile name. Only has profiles with `client_defaults`.
+    pub profile_options: IndexMap<String, UnresolvedClientProperty<Span>>,
 }
 
 #[derive(Debug)]
@@ -148,6 +255,92 @@ pub struct TestCase {
     pub args: IndexMap<String, (Span, UnresolvedValue<Span>)>,
     pub args_field_span: Span,
     pub constraints: Vec<(Constraint, Span, Span)>,
+    pub expect: Option<Expectation>,
+    /// A JSONL file of rows to run the test with, relative to the file the
+    /// test is in.
+    pub dataset: Option<(String, Span)>,
+}
+
+/// `@@expect(value, options?)` on a test: the parsed output must equal
+/// `value`.
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    pub value: UnresolvedValue<Span>,
+    /// How far floats may be from the expected value.
+    pub float_tolerance: f64,
+    /// Compare lists as multisets.
+    pub unordered_lists: bool,
+    pub span: Span,
+}
+
+/// `@@if(env.FEATURE_X)` on a function, client or test: it's only part of
+/// the runtime if the environment variable is set, or has the value
+/// `equals`.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    /// The variable's name, without `env.`.
+    pub env_var: String,
+    /// The value the variable must have, if one was given.
+    pub equals: Option<String>,
+    /// The span of the attribute.
+    pub span: Span,
+}
+
+/// `@@confidence` on a function returning an enum: the result says how
+/// confident the model was in each value.
+#[derive(Debug, Clone)]
+pub struct ConfidenceMode {
+    /// How many times to call the function when the client can't return
+    /// logprobs. `None` means logprobs only.
+    pub samples: Option<u32>,
+    /// The span of the attribute.
+    pub span: Span,
+}
+
+/// `@@self_consistency` on a function: it's called several times and the
+/// parsed results are combined into one.
+#[derive(Debug, Clone)]
+pub struct SelfConsistency {
+    /// How many times to call the function.
+    pub samples: u32,
+    /// How the results are combined.
+    pub strategy: VotingStrategy,
+    /// The span of the attribute.
+    pub span: Span,
+}
+
+/// How `@@self_consistency` combines results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VotingStrategy {
+    /// The most common value. Results of the same class are combined field
+    /// by field, so each field is its most common value.
+    Majority,
+    /// The most common value, comparing whole results.
+    MostCommon,
+}
+
+/// `@@trace` on a function: how much of its calls is sent to logs and trace
+/// exporters.
+#[derive(Debug, Clone)]
+pub struct TraceSettings {
+    /// The share of calls that are traced, from 0 to 1.
+    pub sample: f64,
+    /// What a traced call records.
+    pub capture: TraceCapture,
+    /// The span of the attribute.
+    pub span: Span,
+}
+
+/// What `@@trace` records of a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceCapture {
+    /// Arguments, prompts, responses and parsed values.
+    Full,
+    /// A SHA-256 hash in place of each of them, so identical calls can still
+    /// be matched up.
+    Hashed,
+    /// Only the client, model, timing, token usage and whether it failed.
+    MetadataOnly,
 }
 
 #[derive(Debug, Clone)]
@@ -174,6 +367,15 @@ impl PrinterType {
     }
 }
 
+/// An environment profile, selected at runtime with `BAML_PROFILE`.
+#[derive(Debug)]
+pub struct Profile {
+    /// Options for every non-strategy client that doesn't set them itself.
+    pub client_defaults: IndexMap<String, (Span, UnresolvedValue<Span>)>,
+    /// Environment variables to set, overriding the process environment.
+    pub env_overrides: IndexMap<String, (Span, String)>,
+}
+
 /// How to retry a request.
 #[derive(Debug)]
 pub struct RetryPolicy {
@@ -181,6 +383,11 @@ pub struct RetryPolicy {
     pub max_retries: u32,
     /// The strategy to use.
     pub strategy: RetryPolicyStrategy,
+    /// The HTTP status codes to retry. Failures without an error status, like
+    /// connection errors, are always retried. `None` retries every failure.
+    pub retry_on: Option<Vec<u16>>,
+    /// Duplicate requests to send if an attempt is slow to answer.
+    pub hedge: Option<RetryHedge>,
     /// Any additional options.
     pub options: Option<IndexMap<String, (Span, UnresolvedValue<Span>)>>,
 }
@@ -194,11 +401,47 @@ pub enum RetryPolicyStrategy {
     ExponentialBackoff(ExponentialBackoffStrategy),
 }
 
+impl RetryPolicyStrategy {
+    /// How the delays of the strategy are randomized.
+    pub fn jitter(&self) -> RetryJitter {
+        match self {
+            RetryPolicyStrategy::ConstantDelay(strategy) => strategy.jitter,
+            RetryPolicyStrategy::ExponentialBackoff(strategy) => strategy.jitter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+/// How the delay between retries is randomized, so that clients which failed
+/// together don't all retry at once.
+pub enum RetryJitter {
+    /// Wait exactly the delay.
+    #[default]
+    None,
+    /// Wait anywhere between zero and the delay.
+    Full,
+    /// Wait half the delay, plus anywhere up to the other half.
+    Equal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+/// Requests sent to the next client while an attempt is slow to answer, the
+/// first answer winning.
+pub struct RetryHedge {
+    /// How long to wait for an answer before sending each duplicate request.
+    pub after_ms: u32,
+    /// The most duplicate requests to send on top of the first one.
+    pub max_hedges: u32,
+}
+
 #[derive(Debug, Clone, Copy, serde::Serialize)]
 /// The strategy to use for retrying a request.
 pub struct ContantDelayStrategy {
     /// The delay in milliseconds.
     pub delay_ms: u32,
+    /// How the delay is randomized.
+    pub jitter: RetryJitter,
 }
 
 #[derive(Debug, Clone, Copy, serde::Serialize)]
@@ -210,6 +453,8 @@ pub struct ExponentialBackoffStrategy {
     pub multiplier: f32,
     /// The maximum delay in milliseconds.
     pub max_delay_ms: u32,
+    /// How the delay is randomized.
+    pub jitter: RetryJitter,
 }
 
 #[derive(Debug, Clone)]
@@ -217,6 +462,8 @@ pub struct FunctionType {
     pub dependencies: (HashSet<String>, HashSet<String>),
     pub prompt: Option<RawString>,
     pub client: Option<(String, Span)>,
+    /// What `mask_pii` masks in the rendered prompt. Empty if disabled.
+    pub mask_pii: Vec<PiiKind>,
 }
 
 #[derive(Debug, Clone)]
@@ -250,8 +497,18 @@ pub(super) struct Types {
     pub(super) function: HashMap<ast::ValExpId, FunctionType>,
 
     pub(super) client_properties: HashMap<ast::ValExpId, ClientProperties>,
+    /// Clients without a provider that other clients extend. They can't be
+    /// called themselves.
+    pub(super) base_clients: HashSet<ast::ValExpId>,
     pub(super) retry_policies: HashMap<ast::ValExpId, RetryPolicy>,
+    pub(super) profiles: HashMap<ast::ValExpId, Profile>,
+    /// The `models` catalog: each alias, where it's defined and its model.
+    pub(super) model_aliases: IndexMap<String, (Span, String)>,
     pub(super) test_cases: HashMap<ast::ValExpId, TestCase>,
+    pub(super) conditions: HashMap<ast::ValExpId, Condition>,
+    pub(super) confidence: HashMap<ast::ValExpId, ConfidenceMode>,
+    pub(super) self_consistency: HashMap<ast::ValExpId, SelfConsistency>,
+    pub(super) trace: HashMap<ast::ValExpId, TraceSettings>,
     pub(super) template_strings:
         HashMap<either::Either<ast::TemplateStringId, ast::ValExpId>, TemplateStringProperties>,
 }
@@ -261,6 +518,17 @@ fn visit_template_string<'db>(
     template_string: &'db ast::TemplateString,
     ctx: &mut Context<'db>,
 ) {
+    let template = match template_string.value().as_raw_string_value() {
+        Some(v) => v.value().to_string(),
+        None => {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "template_string must be a raw string, e.g. #\"...\"#",
+                template_string.value().span().clone(),
+            ));
+            // Still record the template so walkers can resolve it.
+            String::new()
+        }
+    };
     ctx.types.template_strings.insert(
         either::Left(idx),
         TemplateStringProperties {
@@ -272,11 +540,7 @@ fn visit_template_string<'db>(
                 .iter()
                 .map(|f| f.name().to_string())
                 .collect::<HashSet<_>>(),
-            template: template_string
-                .value()
-                .as_raw_string_value()
-                .map(|v| v.value().to_string())
-                .unwrap(),
+            template,
         },
     );
 }
@@ -373,6 +637,7 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
 
     let mut prompt = None;
     let mut client = None;
+    let mut mask_pii = vec![];
     function
         .iter_fields()
         .for_each(|(_idx, field)| match field.name() {
@@ -389,6 +654,11 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
                     None => None,
                 }
             }
+            "mask_pii" => {
+                if let Some(val) = &field.expr {
+                    mask_pii = visit_mask_pii(val, ctx);
+                }
+            }
             config => ctx.push_error(DatamodelError::new_validation_error(
                 &format!("Unknown field `{}` in function", config),
                 field.span().clone(),
@@ -403,6 +673,7 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
                     dependencies: (input_deps.clone(), output_deps),
                     prompt: Some(prompt.clone()),
                     client: Some(client),
+                    mask_pii,
                 },
             );
 
@@ -436,7 +707,200 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
     }
 }
 
+/// `mask_pii true` masks every kind of PII, or a list like
+/// `mask_pii ["email", "phone"]` picks some.
+fn visit_mask_pii(val: &Expression, ctx: &mut Context<'_>) -> Vec<PiiKind> {
+    if let Some(enabled) = coerce_opt::boolean(val) {
+        return if enabled {
+            PiiKind::all().to_vec()
+        } else {
+            vec![]
+        };
+    }
+    let Some(kinds) = coerce_array(val, &coerce::string_with_span, ctx.diagnostics) else {
+        return vec![];
+    };
+    let mut mask_pii = vec![];
+    for (kind, span) in kinds {
+        match kind.parse::<PiiKind>() {
+            Ok(kind) if !mask_pii.contains(&kind) => mask_pii.push(kind),
+            Ok(_) => {}
+            Err(_) => ctx.push_error(DatamodelError::not_found_error(
+                "PII kind",
+                kind,
+                span.clone(),
+                PiiKind::all().iter().map(|v| v.to_string()).collect(),
+                false,
+            )),
+        }
+    }
+    mask_pii
+}
+
 fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut Context<'db>) {
+    let mut fields = ClientFields::default();
+    // Problems with the fields of the clients this one extends are reported on
+    // those clients.
+    for base in client_bases(client, ctx).into_iter().rev() {
+        let mut ignored = Diagnostics::new(PathBuf::new());
+        fields.extend(client_fields(base, &mut ignored));
+    }
+    fields.extend(client_fields(client, ctx.diagnostics));
+    let ClientFields {
+        provider,
+        retry_policy,
+        mut options,
+    } = fields;
+    if let Some((options, _)) = &mut options {
+        resolve_model_alias(options, ctx);
+    }
+
+    let retry_policy = match retry_policy {
+        Some(retry_policy) => match coerce::string_with_span(retry_policy, ctx.diagnostics) {
+            Some((retry_policy, span)) => Some((retry_policy.to_string(), span.clone())),
+            _ => {
+                // Errors are handled by coerce.
+                None
+            }
+        },
+        None => None,
+    };
+
+    match provider {
+        Some(provider) => {
+            let (options_kv, options_span) = match options {
+                Some((kv, span)) => (kv, span),
+                None => (Default::default(), client.span().clone()),
+            };
+
+            if let Some(known_models) = provider.0.known_models() {
+                let model = options_kv
+                    .get("model")
+                    .and_then(|(_, model)| Some((model.as_static_str().ok()?, model.meta())));
+                if let Some((model, span)) = model {
+                    if !known_models.contains(&model) {
+                        ctx.push_warning(DatamodelWarning::new_unknown_model(
+                            model,
+                            &provider.0.to_string(),
+                            known_models,
+                            span.clone(),
+                        ));
+                    }
+                }
+            }
+
+            let kind = client.client_kind();
+            // Profile defaults are meant for LLM clients.
+            let profile_options = match kind {
+                ClientKind::Llm => {
+                    parse_profile_options(&provider.0, &options_kv, &options_span, ctx)
+                }
+                ClientKind::Embedding => Default::default(),
+            };
+            let properties = PropertyHandler::new(options_kv, options_span);
+            // Parse and cache the result
+            let parsed = match kind {
+                ClientKind::Llm => provider.0.parse_client_property(properties),
+                ClientKind::Embedding => provider.0.parse_embedding_property(properties),
+            };
+            match parsed {
+                Ok(options) => {
+                    ctx.types.client_properties.insert(
+                        idx,
+                        ClientProperties {
+                            provider,
+                            retry_policy,
+                            options,
+                            profile_options,
+                        },
+                    );
+                }
+                Err(errors) => {
+                    for error in errors {
+                        ctx.push_error(DatamodelError::new_client_error(error.message, error.span));
+                    }
+                }
+            }
+        }
+        // Clients other clients extend don't need a provider of their own.
+        None if is_extended(client, ctx) => {
+            ctx.types.base_clients.insert(idx);
+        }
+        None => ctx.push_error(DatamodelError::new_validation_error(
+            "Missing `provider` field in client. e.g. `provider openai`",
+            client.span().clone(),
+        )),
+    }
+}
+
+/// Replaces `model models.<alias>` with the model the `models` catalog gives
+/// the alias.
+fn resolve_model_alias(
+    options: &mut IndexMap<String, (Span, UnresolvedValue<Span>)>,
+    ctx: &mut Context<'_>,
+) {
+    let Some((_, UnresolvedValue::String(StringOr::Value(model), span))) = options.get_mut("model")
+    else {
+        return;
+    };
+    let Some(alias) = model.strip_prefix("models.") else {
+        return;
+    };
+    match ctx.types.model_aliases.get(alias) {
+        Some((_, resolved)) => *model = resolved.clone(),
+        None => ctx.push_error(DatamodelError::not_found_error(
+            "Model alias",
+            alias,
+            span.clone(),
+            ctx.types.model_aliases.keys().cloned().collect(),
+            false,
+        )),
+    }
+}
+
+/// Whether another client extends `client`.
+fn is_extended(client: &ast::ValueExprBlock, ctx: &Context<'_>) -> bool {
+    ctx.ast.iter_tops().any(|(_, top)| match top {
+        ast::Top::Client(other) => other
+            .extends()
+            .is_some_and(|base| base.name() == client.name()),
+        _ => false,
+    })
+}
+
+/// The fields a client block sets itself.
+#[derive(Default)]
+struct ClientFields<'db> {
+    provider: Option<(ClientProvider, Span)>,
+    retry_policy: Option<&'db Expression>,
+    options: Option<(IndexMap<String, (Span, UnresolvedValue<Span>)>, Span)>,
+}
+
+impl<'db> ClientFields<'db> {
+    /// Overrides these fields with the ones `other` sets. Options are merged
+    /// key by key.
+    fn extend(&mut self, other: ClientFields<'db>) {
+        if other.provider.is_some() {
+            self.provider = other.provider;
+        }
+        if other.retry_policy.is_some() {
+            self.retry_policy = other.retry_policy;
+        }
+        match (&mut self.options, other.options) {
+            (Some((options, span)), Some((other_options, other_span))) => {
+                options.extend(other_options);
+                *span = other_span;
+            }
+            (options, other_options @ Some(_)) => *options = other_options,
+            (_, None) => {}
+        }
+    }
+}
+
+fn client_fields<'db>(
+    client: &'db ast::ValueExprBlock,
+    diagnostics: &mut Diagnostics,
+) -> ClientFields<'db> {
     let mut provider = None;
     let mut retry_policy = None;
     let mut options = None;
@@ -447,13 +911,13 @@ fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut
                 match field
                     .expr
                     .as_ref()
-                    .and_then(|e| e.to_unresolved_value(ctx.diagnostics))
+                    .and_then(|e| e.to_unresolved_value(diagnostics))
                 {
                     Some(e) => match e.as_static_str() {
                         Ok(s) => match s.parse::<ClientProvider>() {
                             Ok(p) => provider = Some((p, e.meta().clone())),
                             Err(err) => {
-                                ctx.push_error(DatamodelError::not_found_error(
+                                diagnostics.push_error(DatamodelError::not_found_error(
                                     "client provider",
                                     s,
                                     e.meta().clone(),
@@ -465,12 +929,12 @@ fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut
                                 ));
                             }
                         },
-                        Err(err) => ctx.push_error(DatamodelError::new_validation_error(
+                        Err(err) => diagnostics.push_error(DatamodelError::new_validation_error(
                             &format!("`provider` value error: {err}"),
                             e.meta().clone(),
                         )),
                     },
-                    None => ctx.push_error(DatamodelError::new_validation_error(
+                    None => diagnostics.push_error(DatamodelError::new_validation_error(
                         "Missing `provider` field in client. e.g. `provider \"openai\"`",
                         field.span().clone(),
                     )),
@@ -481,13 +945,13 @@ fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut
                 match field
                     .expr
                     .as_ref()
-                    .and_then(|e| e.to_unresolved_value(ctx.diagnostics))
+                    .and_then(|e| e.to_unresolved_value(diagnostics))
                 {
                     Some(UnresolvedValue::Map(kv, _)) => {
                         options = Some((kv, field.identifier().span().clone()));
                     }
                     Some(v) => {
-                        ctx.push_error(DatamodelError::new_validation_error(
+                        diagnostics.push_error(DatamodelError::new_validation_error(
                             &format!("Expected a key-value pair, but got a: {}", v.r#type()),
                             v.meta().clone(),
                         ));
@@ -495,55 +959,116 @@ fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut
                     None => {}
                 }
             }
-            config => ctx.push_error(DatamodelError::new_validation_error(
+            config => diagnostics.push_error(DatamodelError::new_validation_error(
                 &format!("Unknown field `{}` in client", config),
                 field.span().clone(),
             )),
         });
 
-    let retry_policy = match retry_policy {
-        Some(retry_policy) => match coerce::string_with_span(retry_policy, ctx.diagnostics) {
-            Some((retry_policy, span)) => Some((retry_policy.to_string(), span.clone())),
-            _ => {
-                // Errors are handled by coerce.
-                None
+    ClientFields {
+        provider,
+        retry_policy,
+        options,
+    }
+}
+
+/// The clients `client` extends, nearest first. Only reports problems with
+/// its own `extends`, the clients it extends report theirs.
+fn client_bases<'db>(
+    client: &'db ast::ValueExprBlock,
+    ctx: &mut Context<'db>,
+) -> Vec<&'db ast::ValueExprBlock> {
+    let schema = ctx.ast;
+    let mut bases: Vec<&'db ast::ValueExprBlock> = vec![];
+    let mut current = client;
+    while let Some(base_name) = current.extends() {
+        let base = schema.iter_tops().find_map(|(_, top)| match top {
+            ast::Top::Client(base) if base.name() == base_name.name() => Some(base),
+            _ => None,
+        });
+        match base {
+            Some(base) if std::ptr::eq(base, client) => {
+                ctx.push_error(DatamodelError::new_validation_error(
+                    &format!("Client `{}` extends itself", client.name()),
+                    client.extends().unwrap_or(base_name).span().clone(),
+                ));
+                break;
             }
-        },
-        None => None,
-    };
+            // A cycle the client isn't part of, reported by the clients in it.
+            Some(base) if bases.iter().any(|other| std::ptr::eq(*other, base)) => break,
+            Some(base) => {
+                bases.push(base);
+                current = base;
+            }
+            None => {
+                if std::ptr::eq(current, client) {
+                    ctx.push_error(DatamodelError::not_found_error(
+                        "client",
+                        base_name.name(),
+                        base_name.span().clone(),
+                        schema
+                            .iter_tops()
+                            .filter_map(|(_, top)| match top {
+                                ast::Top::Client(other) => Some(other.name().to_string()),
+                                _ => None,
+                            })
+                            .collect(),
+                        false,
+                    ));
+                }
+                break;
+            }
+        }
+    }
+    bases
+}
 
-    match provider {
-        Some(provider) => {
-            let (options_kv, options_span) = match options {
-                Some((kv, span)) => (kv, span),
-                None => (Default::default(), client.span().clone()),
-            };
+/// Parses the client's options once per profile with `client_defaults`,
+/// filling in the defaults the client doesn't set itself. Strategy clients
+/// don't take defaults.
+fn parse_profile_options(
+    provider: &ClientProvider,
+    options: &IndexMap<String, (Span, UnresolvedValue<Span>)>,
+    options_span: &Span,
+    ctx: &mut Context<'_>,
+) -> IndexMap<String, UnresolvedClientProperty<Span>> {
+    if matches!(provider, ClientProvider::Strategy(_)) {
+        return Default::default();
+    }
 
-            let properties = PropertyHandler::new(options_kv, options_span);
-            // Parse and cache the result
-            match provider.0.parse_client_property(properties) {
-                Ok(options) => {
-                    ctx.types.client_properties.insert(
-                        idx,
-                        ClientProperties {
-                            provider,
-                            retry_policy,
-                            options,
-                        },
-                    );
-                }
-                Err(errors) => {
-                    for error in errors {
-                        ctx.push_error(DatamodelError::new_client_error(error.message, error.span));
-                    }
+    let merged = ctx
+        .ast
+        .iter_tops()
+        .filter_map(|(top_id, top)| {
+            let profile = ctx.types.profiles.get(&top_id.as_profile_id()?)?;
+            if profile.client_defaults.is_empty() {
+                return None;
+            }
+            let mut merged = options.clone();
+            for (key, value) in &profile.client_defaults {
+                merged.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            Some((top.name().to_string(), merged))
+        })
+        .collect::<Vec<_>>();
+
+    let mut profile_options = IndexMap::new();
+    for (name, merged) in merged {
+        match provider.parse_client_property(PropertyHandler::new(merged, options_span.clone())) {
+            Ok(options) => {
+                profile_options.insert(name, options);
+            }
+            Err(errors) => {
+                for error in errors {
+                    ctx.push_error(DatamodelError::new_client_error(
+                        format!("With profile `{name}`: {}", error.message),
+                        error.span,
+                    ));
                 }
             }
         }
-        None => ctx.push_error(DatamodelError::new_validation_error(
-            "Missing `provider` field in client. e.g. `provider openai`",
-            client.span().clone(),
-        )),
     }
+    profile_options
 }
 
 /// Prisma's builtin scalar types.
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use baml_types::{BoolStrings, EnumMatching, StreamingBehavior};
 use internal_baml_schema_ast::ast::FieldId;
 
 use super::Attributes;
@@ -9,6 +10,9 @@ pub struct EnumAttributes {
     pub value_serilizers: HashMap<FieldId, Attributes>,
 
     pub serilizer: Option<Attributes>,
+
+    /// Set by `@@match`.
+    pub matching: Option<EnumMatching>,
 }
 
 #[derive(Debug, Default)]
@@ -16,4 +20,10 @@ pub struct ClassAttributes {
     pub field_serilizers: HashMap<FieldId, Attributes>,
 
     pub serilizer: Option<Attributes>,
+
+    /// Set by `@bool_strings` on a field.
+    pub field_bool_strings: HashMap<FieldId, BoolStrings>,
+
+    /// Set by `@stream.not_null` and `@stream.with_state` on a field.
+    pub field_streaming: HashMap<FieldId, StreamingBehavior>,
 }
@@ -1,9 +1,10 @@
+use baml_types::ClientKind;
 use internal_baml_schema_ast::ast::{Expression, WithSpan};
 use internal_llm_client::ClientProvider;
 
 use crate::{
     ast::{self, WithIdentifier},
-    types::ClientProperties,
+    types::{ClientProperties, Condition},
 };
 
 /// A `function` declaration in the Prisma schema.
@@ -20,11 +21,21 @@ impl<'db> ClientWalker<'db> {
         &self.db.ast[self.id]
     }
 
+    /// Whether this is a `client<llm>` or a `client<embedding>`.
+    pub fn kind(self) -> ClientKind {
+        self.ast_client().client_kind()
+    }
+
     /// The properties of the variant.
     pub fn properties(self) -> &'db ClientProperties {
         &self.db.types.client_properties[&self.id]
     }
 
+    /// The client's `@@if`, if it has one.
+    pub fn condition(self) -> Option<&'db Condition> {
+        self.db.types.conditions.get(&self.id)
+    }
+
     /// The provider for the client, e.g. baml-openai-chat
     pub fn provider(self) -> &'db ClientProvider {
         &self.properties().provider.0
@@ -1,6 +1,6 @@
 use internal_baml_schema_ast::ast::{self, WithIdentifier, WithSpan};
 
-use crate::types::{RetryPolicy, TestCase};
+use crate::types::{Condition, Profile, RetryPolicy, TestCase};
 
 /// A `class` declaration in the Prisma schema.
 pub type ConfigurationWalker<'db> = super::Walker<'db, (ast::ValExpId, &'static str)>;
@@ -17,11 +17,22 @@ impl ConfigurationWalker<'_> {
         &self.db.types.retry_policies[&self.id.0]
     }
 
+    /// Get as a profile configuration.
+    pub fn profile(&self) -> &Profile {
+        assert!(self.id.1 == "profile");
+        &self.db.types.profiles[&self.id.0]
+    }
+
     /// Get as a test case configuration.
     pub fn test_case(&self) -> &TestCase {
         assert!(self.id.1 == "test");
         &self.db.types.test_cases[&self.id.0]
     }
+
+    /// The test's `@@if`, if it has one.
+    pub fn condition(&self) -> Option<&Condition> {
+        self.db.types.conditions.get(&self.id.0)
+    }
 }
 
 impl WithIdentifier for ConfigurationWalker<'_> {
@@ -1,3 +1,5 @@
+use baml_types::EnumMatching;
+
 use crate::{ast, types::Attributes, walkers::Walker};
 
 use internal_baml_schema_ast::ast::{WithDocumentation, WithName, WithSpan};
@@ -18,6 +20,15 @@ impl<'db> EnumWalker<'db> {
             .into_iter()
     }
 
+    /// How the enum's values are matched, from `@@match`.
+    pub fn matching(self) -> Option<EnumMatching> {
+        self.db
+            .types
+            .enum_attributes
+            .get(&self.id)
+            .and_then(|attributes| attributes.matching)
+    }
+
     /// Find a value by name.
     pub fn find_value(&self, name: &str) -> Option<EnumValueWalker<'db>> {
         self.ast_type_block()
@@ -1,3 +1,5 @@
+use baml_types::{BoolStrings, StreamingBehavior};
+
 use crate::types::Attributes;
 
 use super::{ClassWalker, Walker};
@@ -40,6 +42,28 @@ impl<'db> FieldWalker<'db> {
         result
     }
 
+    /// Which strings the field's bools are read from, from `@bool_strings`.
+    pub fn bool_strings(&self) -> Option<BoolStrings> {
+        self.db
+            .types
+            .class_attributes
+            .get(&self.id.0)
+            .and_then(|attributes| attributes.field_bool_strings.get(&self.id.1))
+            .copied()
+    }
+
+    /// How the field shows up in partial results, from `@stream.not_null`
+    /// and `@stream.with_state`.
+    pub fn streaming(&self) -> StreamingBehavior {
+        self.db
+            .types
+            .class_attributes
+            .get(&self.id.0)
+            .and_then(|attributes| attributes.field_streaming.get(&self.id.1))
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// The field's docstring.
     pub fn get_documentation(&self) -> Option<String> {
         self.ast_field()
@@ -5,7 +5,7 @@ use internal_llm_client::ClientSpec;
 
 use crate::{
     ast::{self, WithName},
-    types::FunctionType,
+    types::{Condition, ConfidenceMode, FunctionType, SelfConsistency, TraceSettings},
 };
 
 use super::{ClassWalker, ConfigurationWalker, EnumWalker, Walker};
@@ -107,6 +107,26 @@ impl<'db> FunctionWalker<'db> {
         &self.db.types.function[&self.function_id()]
     }
 
+    /// The function's `@@if`, if it has one.
+    pub fn condition(self) -> Option<&'db Condition> {
+        self.db.types.conditions.get(&self.function_id())
+    }
+
+    /// The function's `@@confidence`, if it has one.
+    pub fn confidence(self) -> Option<&'db ConfidenceMode> {
+        self.db.types.confidence.get(&self.function_id())
+    }
+
+    /// The function's `@@self_consistency`, if it has one.
+    pub fn self_consistency(self) -> Option<&'db SelfConsistency> {
+        self.db.types.self_consistency.get(&self.function_id())
+    }
+
+    /// The function's `@@trace`, if it has one.
+    pub fn trace(self) -> Option<&'db TraceSettings> {
+        self.db.types.trace.get(&self.function_id())
+    }
+
     /// Is this function an old version
     pub fn is_old_function(self) -> bool {
         !self.id.0
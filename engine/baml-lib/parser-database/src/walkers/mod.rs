@@ -100,6 +100,7 @@ impl<'db> crate::ParserDatabase {
     pub fn find_client(&'db self, name: &str) -> Option<ClientWalker<'db>> {
         self.find_top_by_str(name)
             .and_then(|top_id| top_id.as_client_id())
+            .filter(|client_id| !self.types.base_clients.contains(client_id))
             .map(|model_id| self.walk(model_id))
     }
 
@@ -219,6 +220,7 @@ impl<'db> crate::ParserDatabase {
         self.ast()
             .iter_tops()
             .filter_map(|(top_id, _)| top_id.as_client_id())
+            .filter(move |top_id| !self.types.base_clients.contains(top_id))
             .map(move |top_id| Walker {
                 db: self,
                 id: top_id,
@@ -236,6 +238,17 @@ impl<'db> crate::ParserDatabase {
             })
     }
 
+    /// Walk all profiles in the schema.
+    pub fn walk_profiles(&self) -> impl Iterator<Item = ConfigurationWalker<'_>> {
+        self.ast()
+            .iter_tops()
+            .filter_map(|(top_id, _)| top_id.as_profile_id())
+            .map(move |top_id| Walker {
+                db: self,
+                id: (top_id, "profile"),
+            })
+    }
+
     /// Walk all classes in the schema.
     pub fn walk_test_cases(&self) -> impl Iterator<Item = ConfigurationWalker<'_>> {
         self.ast()
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use either::Either;
 use internal_baml_jinja_types::{PredefinedTypes, Type};
 use internal_baml_schema_ast::ast::{
@@ -53,17 +55,21 @@ impl<'db> TemplateStringWalker<'db> {
         let name = self.name();
         let ret_type = Type::String;
         let mut params = vec![];
+        let mut defaults = HashSet::new();
 
         if let Some(p) = self.ast_node().input() {
             p.args.iter().for_each(|(name, t)| {
                 params.push((
                     name.name().to_string(),
                     self.db.to_jinja_type(&t.field_type),
-                ))
+                ));
+                if t.default.is_some() {
+                    defaults.insert(name.name().to_string());
+                }
             });
         }
 
-        types.add_function(name, ret_type, params);
+        types.add_function_with_defaults(name, ret_type, params, defaults);
     }
 }
 
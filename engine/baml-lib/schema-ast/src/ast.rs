@@ -26,6 +26,7 @@ pub use field::{Field, FieldArity, FieldType};
 pub use identifier::{Identifier, RefIdentifier};
 pub use indentation_type::IndentationType;
 pub use internal_baml_diagnostics::Span;
+use internal_baml_diagnostics::Suppression;
 pub use newline_type::NewlineType;
 pub use template_string::TemplateString;
 pub use top::Top;
@@ -46,6 +47,8 @@ pub use value_expression_block::{BlockArg, BlockArgs, ValueExprBlock, ValueExprB
 pub struct SchemaAst {
     /// All models, enums, composite types, datasources, generators and type aliases.
     pub tops: Vec<Top>,
+    /// `// baml-ignore:` comments, resolved to the spans they cover.
+    pub suppressions: Vec<Suppression>,
 }
 
 impl Default for SchemaAst {
@@ -56,7 +59,10 @@ impl Default for SchemaAst {
 
 impl SchemaAst {
     pub fn new() -> Self {
-        SchemaAst { tops: Vec::new() }
+        SchemaAst {
+            tops: Vec::new(),
+            suppressions: Vec::new(),
+        }
     }
 
     /// Iterate over all the top-level items in the schema.
@@ -77,6 +83,17 @@ impl SchemaAst {
             }
         })
     }
+
+    /// Iterate over all the profile blocks in the schema.
+    pub fn profiles(&self) -> impl Iterator<Item = &ValueExprBlock> {
+        self.tops.iter().filter_map(|top| {
+            if let Top::Profile(profile) = top {
+                Some(profile)
+            } else {
+                None
+            }
+        })
+    }
 }
 
 /// An opaque identifier for an enum in a schema AST.
@@ -145,6 +162,12 @@ pub enum TopId {
     // A generator declaration
     Generator(ValExpId),
 
+    // An environment profile
+    Profile(ValExpId),
+
+    // A model catalog
+    Models(ValExpId),
+
     // Template Strings
     TemplateString(TemplateStringId),
 
@@ -186,6 +209,20 @@ impl TopId {
         }
     }
 
+    pub fn as_profile_id(self) -> Option<ValExpId> {
+        match self {
+            TopId::Profile(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn as_models_id(self) -> Option<ValExpId> {
+        match self {
+            TopId::Models(id) => Some(id),
+            _ => None,
+        }
+    }
+
     pub fn as_template_string_id(self) -> Option<TemplateStringId> {
         match self {
             TopId::TemplateString(id) => Some(id),
@@ -219,6 +256,8 @@ impl std::ops::Index<TopId> for SchemaAst {
             TopId::TemplateString(TemplateStringId(idx)) => idx,
             TopId::Client(ValExpId(idx)) => idx,
             TopId::Generator(ValExpId(idx)) => idx,
+            TopId::Profile(ValExpId(idx)) => idx,
+            TopId::Models(ValExpId(idx)) => idx,
             TopId::TestCase(ValExpId(idx)) => idx,
             TopId::RetryPolicy(ValExpId(idx)) => idx,
         };
@@ -235,6 +274,8 @@ fn top_idx_to_top_id(top_idx: usize, top: &Top) -> TopId {
         Top::Client(_) => TopId::Client(ValExpId(top_idx as u32)),
         Top::TemplateString(_) => TopId::TemplateString(TemplateStringId(top_idx as u32)),
         Top::Generator(_) => TopId::Generator(ValExpId(top_idx as u32)),
+        Top::Profile(_) => TopId::Profile(ValExpId(top_idx as u32)),
+        Top::Models(_) => TopId::Models(ValExpId(top_idx as u32)),
         Top::TestCase(_) => TopId::TestCase(ValExpId(top_idx as u32)),
         Top::RetryPolicy(_) => TopId::RetryPolicy(ValExpId(top_idx as u32)),
     }
@@ -82,6 +82,52 @@ impl RawString {
         }
     }
 
+    /// Maps a byte range of [`RawString::value`] back to the file.
+    ///
+    /// Unlike [`RawString::to_raw_span`] this accounts for the blank lines
+    /// dropped before the first line and for lines that were not dedented.
+    pub fn source_span(&self, range: std::ops::Range<usize>) -> Span {
+        Span {
+            file: self.raw_span.file.clone(),
+            start: self.source_offset(range.start),
+            end: self.source_offset(range.end.max(range.start)),
+        }
+    }
+
+    fn source_offset(&self, value_offset: usize) -> usize {
+        let value = self.value();
+        let mut value_offset = value_offset.min(value.len());
+        while !value.is_char_boundary(value_offset) {
+            value_offset -= 1;
+        }
+        let line = value[..value_offset].matches('\n').count();
+        let column = value_offset - value[..value_offset].rfind('\n').map_or(0, |i| i + 1);
+
+        // `dedent` skips leading blank lines and only strips the indent from
+        // lines with content.
+        let content_start = self.raw_span.start + self.inner_span_start;
+        let mut lines = self.raw_value[self.inner_span_start..]
+            .split_inclusive('\n')
+            .scan(content_start, |start, line| {
+                let line_start = *start;
+                *start += line.len();
+                Some((line_start, line.trim_end_matches(['\n', '\r'])))
+            })
+            .skip_while(|(_, line)| line.trim().is_empty());
+
+        match lines.nth(line) {
+            Some((line_start, line)) => {
+                let indent = if line.trim().is_empty() {
+                    0
+                } else {
+                    self.indent
+                };
+                line_start + (indent + column).min(line.len())
+            }
+            None => self.raw_span.end,
+        }
+    }
+
     pub fn assert_eq_up_to_span(&self, other: &RawString) {
         assert_eq!(self.inner_value, other.inner_value);
         assert_eq!(self.raw_value, other.raw_value);
@@ -1,3 +1,5 @@
+use baml_types::ClientKind;
+
 use super::{
     traits::WithSpan, Identifier, Span, TemplateString, TypeExpressionBlock, ValueExprBlock,
     WithIdentifier,
@@ -21,6 +23,12 @@ pub enum Top {
     // Generator
     Generator(ValueExprBlock),
 
+    // Environment profile
+    Profile(ValueExprBlock),
+
+    // Model catalog
+    Models(ValueExprBlock),
+
     TestCase(ValueExprBlock),
 
     RetryPolicy(ValueExprBlock),
@@ -34,9 +42,14 @@ impl Top {
             Top::Enum(_) => "enum",
             Top::Class(_) => "class",
             Top::Function(_) => "function",
+            Top::Client(client) if client.client_kind() == ClientKind::Embedding => {
+                "client<embedding>"
+            }
             Top::Client(_) => "client<llm>",
             Top::TemplateString(_) => "template_string",
             Top::Generator(_) => "generator",
+            Top::Profile(_) => "profile",
+            Top::Models(_) => "models",
             Top::TestCase(_) => "test_case",
             Top::RetryPolicy(_) => "retry_policy",
         }
@@ -56,6 +69,8 @@ impl Top {
             Top::Function(func) => Some(func),
             Top::Client(client) => Some(client),
             Top::Generator(gen) => Some(gen),
+            Top::Profile(profile) => Some(profile),
+            Top::Models(models) => Some(models),
             Top::TestCase(test) => Some(test),
             Top::RetryPolicy(retry) => Some(retry),
             _ => None,
@@ -81,6 +96,8 @@ impl WithIdentifier for Top {
             Top::Client(x) => x.identifier(),
             Top::TemplateString(x) => x.identifier(),
             Top::Generator(x) => x.identifier(),
+            Top::Profile(x) => x.identifier(),
+            Top::Models(x) => x.identifier(),
             Top::TestCase(x) => x.identifier(),
             Top::RetryPolicy(x) => x.identifier(),
         }
@@ -96,6 +113,8 @@ impl WithSpan for Top {
             Top::TemplateString(template) => template.span(),
             Top::Client(client) => client.span(),
             Top::Generator(gen) => gen.span(),
+            Top::Profile(profile) => profile.span(),
+            Top::Models(models) => models.span(),
             Top::TestCase(test) => test.span(),
             Top::RetryPolicy(retry) => retry.span(),
         }
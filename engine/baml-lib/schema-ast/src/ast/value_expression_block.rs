@@ -3,6 +3,7 @@ use super::{
     traits::WithAttributes, Attribute, Comment, Expression, Field, FieldType, Identifier, Span,
     WithDocumentation, WithIdentifier, WithSpan,
 };
+use baml_types::{BamlValue, ClientKind};
 use std::fmt::Display;
 use std::fmt::Formatter;
 
@@ -48,6 +49,10 @@ pub struct BlockArg {
     /// The field's type.
     pub field_type: FieldType,
 
+    /// The value the argument takes when it's left out, e.g. `"friendly"` in
+    /// `tone: string = "friendly"`. Only template strings may have one.
+    pub default: Option<Expression>,
+
     /// The location of this field in the text representation.
     pub(crate) span: Span,
 }
@@ -56,6 +61,29 @@ impl BlockArg {
     pub fn name(&self) -> String {
         self.field_type.name()
     }
+
+    /// The default value, if there is one and it's a string, number, boolean
+    /// or `null`.
+    pub fn default_value(&self) -> Option<BamlValue> {
+        match self.default.as_ref()? {
+            Expression::BoolValue(value, _) => Some(BamlValue::Bool(*value)),
+            Expression::NumericValue(value, _) => match value.parse::<i64>() {
+                Ok(value) => Some(BamlValue::Int(value)),
+                Err(_) => value.parse::<f64>().ok().map(BamlValue::Float),
+            },
+            Expression::StringValue(value, _) => Some(BamlValue::String(value.clone())),
+            Expression::RawStringValue(value) => Some(BamlValue::String(value.value().to_string())),
+            Expression::Identifier(Identifier::Local(value, _) | Identifier::String(value, _)) => {
+                Some(match value.as_str() {
+                    "true" => BamlValue::Bool(true),
+                    "false" => BamlValue::Bool(false),
+                    "null" => BamlValue::Null,
+                    _ => BamlValue::String(value.clone()),
+                })
+            }
+            _ => None,
+        }
+    }
 }
 #[derive(Debug, Clone)]
 pub struct BlockArgs {
@@ -69,6 +97,8 @@ pub enum ValueExprBlockType {
     Function,
     Client,
     Generator,
+    Profile,
+    Models,
     RetryPolicy,
     Test,
 }
@@ -79,6 +109,8 @@ impl Display for ValueExprBlockType {
             ValueExprBlockType::Function => write!(f, "function"),
             ValueExprBlockType::Client => write!(f, "client"),
             ValueExprBlockType::Generator => write!(f, "generator"),
+            ValueExprBlockType::Profile => write!(f, "profile"),
+            ValueExprBlockType::Models => write!(f, "models"),
             ValueExprBlockType::RetryPolicy => write!(f, "retry_policy"),
             ValueExprBlockType::Test => write!(f, "test"),
         }
@@ -114,6 +146,15 @@ pub struct ValueExprBlock {
     ///          ^^^
     /// ```
     pub(crate) name: Identifier,
+    /// The client this one extends.
+    ///
+    /// ```ignore
+    /// client<llm> Prod extends Base {...}
+    ///                          ^^^^
+    /// ```
+    pub(crate) extends: Option<Identifier>,
+    /// For clients, whether they were declared as `client<embedding>`.
+    pub(crate) client_kind: ClientKind,
     /// The fields of the block.
     ///
     /// ```ignore
@@ -172,6 +213,14 @@ impl ValueExprBlock {
         }
     }
 
+    pub fn extends(&self) -> Option<&Identifier> {
+        self.extends.as_ref()
+    }
+
+    pub fn client_kind(&self) -> ClientKind {
+        self.client_kind
+    }
+
     pub fn iter_fields(
         &self,
     ) -> impl ExactSizeIterator<Item = (FieldId, &Field<Expression>)> + Clone {
@@ -191,6 +240,8 @@ impl ValueExprBlock {
             ValueExprBlockType::Function => "function",
             ValueExprBlockType::Client => "client",
             ValueExprBlockType::Generator => "generator",
+            ValueExprBlockType::Profile => "profile",
+            ValueExprBlockType::Models => "models",
             ValueExprBlockType::Test => "test",
         }
     }
@@ -0,0 +1,330 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::Result;
+use baml_types::FieldType;
+use internal_baml_diagnostics::SourceFile;
+
+use crate::parse_schema;
+
+/// Builds BAML source from code, for tools that generate schemas from
+/// something else, like a database or a JSON sample:
+///
+/// ```
+/// # use internal_baml_schema_ast::SchemaBuilder;
+/// # use baml_types::{FieldType, TypeValue};
+/// let mut schema = SchemaBuilder::new();
+/// schema
+///     .class("Person")
+///     .field("name", FieldType::Primitive(TypeValue::String))
+///     .field("age", FieldType::Primitive(TypeValue::Int).as_optional());
+/// assert_eq!(
+///     schema.to_source().unwrap(),
+///     "class Person {\n  name string\n  age int?\n}\n"
+/// );
+/// ```
+///
+/// Declarations are written in the order they were first added.
+#[derive(Debug, Default)]
+pub struct SchemaBuilder {
+    declarations: Vec<Declaration>,
+}
+
+#[derive(Debug)]
+enum Declaration {
+    Class(ClassBuilder),
+    Enum(EnumBuilder),
+}
+
+#[derive(Debug)]
+pub struct ClassBuilder {
+    name: String,
+    dynamic: bool,
+    fields: Vec<FieldBuilder>,
+}
+
+#[derive(Debug)]
+pub struct EnumBuilder {
+    name: String,
+    values: Vec<FieldBuilder>,
+}
+
+/// A class field or an enum value, with its attributes.
+#[derive(Debug)]
+pub struct FieldBuilder {
+    name: String,
+    r#type: Option<FieldType>,
+    alias: Option<String>,
+    description: Option<String>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The class named `name`, added if it doesn't exist yet.
+    pub fn class(&mut self, name: &str) -> &mut ClassBuilder {
+        let idx = self
+            .declarations
+            .iter()
+            .position(|d| matches!(d, Declaration::Class(c) if c.name == name))
+            .unwrap_or_else(|| {
+                self.declarations.push(Declaration::Class(ClassBuilder {
+                    name: name.to_string(),
+                    dynamic: false,
+                    fields: vec![],
+                }));
+                self.declarations.len() - 1
+            });
+        match &mut self.declarations[idx] {
+            Declaration::Class(class) => class,
+            Declaration::Enum(_) => unreachable!(),
+        }
+    }
+
+    /// The enum named `name`, added if it doesn't exist yet.
+    pub fn r#enum(&mut self, name: &str) -> &mut EnumBuilder {
+        let idx = self
+            .declarations
+            .iter()
+            .position(|d| matches!(d, Declaration::Enum(e) if e.name == name))
+            .unwrap_or_else(|| {
+                self.declarations.push(Declaration::Enum(EnumBuilder {
+                    name: name.to_string(),
+                    values: vec![],
+                }));
+                self.declarations.len() - 1
+            });
+        match &mut self.declarations[idx] {
+            Declaration::Enum(enm) => enm,
+            Declaration::Class(_) => unreachable!(),
+        }
+    }
+
+    /// Renders the schema as BAML source, formatted the way `baml-cli init`
+    /// lays out files. Fails if a name isn't a valid BAML identifier, or if
+    /// the result doesn't parse.
+    pub fn to_source(&self) -> Result<String> {
+        let mut source = String::new();
+        for (idx, declaration) in self.declarations.iter().enumerate() {
+            if idx > 0 {
+                source.push('\n');
+            }
+            match declaration {
+                Declaration::Class(class) => {
+                    check_identifier(&class.name)?;
+                    writeln!(source, "class {} {{", class.name)?;
+                    for field in &class.fields {
+                        field.write(&mut source)?;
+                    }
+                    if class.dynamic {
+                        writeln!(source, "  @@dynamic")?;
+                    }
+                    writeln!(source, "}}")?;
+                }
+                Declaration::Enum(enm) => {
+                    check_identifier(&enm.name)?;
+                    writeln!(source, "enum {} {{", enm.name)?;
+                    for value in &enm.values {
+                        value.write(&mut source)?;
+                    }
+                    writeln!(source, "}}")?;
+                }
+            }
+        }
+
+        let path = Path::new("schema.baml");
+        let file = SourceFile::from((path.to_path_buf(), source.clone()));
+        match parse_schema(path, &file) {
+            Ok((_, diagnostics)) if !diagnostics.has_errors() => Ok(source),
+            Ok((_, diagnostics)) | Err(diagnostics) => anyhow::bail!(
+                "Generated invalid BAML:\n{}",
+                diagnostics.to_pretty_string()
+            ),
+        }
+    }
+}
+
+impl ClassBuilder {
+    pub fn field(&mut self, name: &str, r#type: FieldType) -> &mut Self {
+        self.add_field(FieldBuilder::new(name).r#type(r#type))
+    }
+
+    /// Adds `field`, replacing any field of the same name.
+    pub fn add_field(&mut self, field: FieldBuilder) -> &mut Self {
+        match self.fields.iter_mut().find(|f| f.name == field.name) {
+            Some(existing) => *existing = field,
+            None => self.fields.push(field),
+        }
+        self
+    }
+
+    /// Marks the class `@@dynamic`, so a `TypeBuilder` can add to it.
+    pub fn dynamic(&mut self) -> &mut Self {
+        self.dynamic = true;
+        self
+    }
+}
+
+impl EnumBuilder {
+    pub fn value(&mut self, name: &str) -> &mut Self {
+        self.add_value(FieldBuilder::new(name))
+    }
+
+    /// Adds `value`, replacing any value of the same name. Its type, if any,
+    /// is ignored.
+    pub fn add_value(&mut self, value: FieldBuilder) -> &mut Self {
+        let value = FieldBuilder {
+            r#type: None,
+            ..value
+        };
+        match self.values.iter_mut().find(|v| v.name == value.name) {
+            Some(existing) => *existing = value,
+            None => self.values.push(value),
+        }
+        self
+    }
+}
+
+impl FieldBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            r#type: None,
+            alias: None,
+            description: None,
+        }
+    }
+
+    pub fn r#type(self, r#type: FieldType) -> Self {
+        Self {
+            r#type: Some(r#type),
+            ..self
+        }
+    }
+
+    pub fn alias(self, alias: &str) -> Self {
+        Self {
+            alias: Some(alias.to_string()),
+            ..self
+        }
+    }
+
+    pub fn description(self, description: &str) -> Self {
+        Self {
+            description: Some(description.to_string()),
+            ..self
+        }
+    }
+
+    fn write(&self, source: &mut String) -> Result<()> {
+        check_identifier(&self.name)?;
+        write!(source, "  {}", self.name)?;
+        if let Some(r#type) = &self.r#type {
+            write!(source, " {}", r#type)?;
+        }
+        if let Some(alias) = &self.alias {
+            write!(source, " @alias({})", string_value(alias))?;
+        }
+        if let Some(description) = &self.description {
+            write!(source, " @description({})", string_value(description))?;
+        }
+        writeln!(source)?;
+        Ok(())
+    }
+}
+
+/// Whether `name` can be used as the name of a class, enum, field or value.
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn check_identifier(name: &str) -> Result<()> {
+    if !is_valid_identifier(name) {
+        anyhow::bail!(
+            "`{name}` is not a valid BAML name: use letters, digits and underscores, starting with a letter"
+        );
+    }
+    Ok(())
+}
+
+/// A quoted string, or a raw string if `value` can't be quoted.
+fn string_value(value: &str) -> String {
+    if !value.contains(['"', '\n', '\r']) {
+        return format!("\"{value}\"");
+    }
+    let mut hashes = "#".to_string();
+    while value.contains(&format!("\"{hashes}")) {
+        hashes.push('#');
+    }
+    format!("{hashes}\"{value}\"{hashes}")
+}
+
+#[cfg(test)]
+mod tests {
+    use baml_types::TypeValue;
+
+    use super::*;
+
+    #[test]
+    fn test_schema_builder() -> Result<()> {
+        let mut schema = SchemaBuilder::new();
+        schema
+            .r#enum("Role")
+            .value("Admin")
+            .add_value(FieldBuilder::new("Guest").description("Has not \"signed up\" yet"));
+        schema
+            .class("User")
+            .field("id", FieldType::Primitive(TypeValue::Int))
+            .add_field(
+                FieldBuilder::new("full_name")
+                    .r#type(FieldType::Primitive(TypeValue::String))
+                    .alias("full name"),
+            )
+            .field("roles", FieldType::Enum("Role".to_string()).as_list())
+            .dynamic();
+        schema.class("User").field(
+            "tags",
+            FieldType::map(
+                FieldType::Primitive(TypeValue::String),
+                FieldType::Primitive(TypeValue::String),
+            ),
+        );
+
+        assert_eq!(
+            schema.to_source()?,
+            unindent::unindent(
+                r##"
+                enum Role {
+                  Admin
+                  Guest @description(#"Has not "signed up" yet"#)
+                }
+
+                class User {
+                  id int
+                  full_name string @alias("full name")
+                  roles Role[]
+                  tags map<string, string>
+                  @@dynamic
+                }
+                "##
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_builder_rejects_invalid_names() {
+        let mut schema = SchemaBuilder::new();
+        schema
+            .class("User")
+            .field("first name", FieldType::Primitive(TypeValue::String));
+        assert!(schema
+            .to_source()
+            .unwrap_err()
+            .to_string()
+            .contains("`first name`"));
+    }
+}
@@ -16,6 +16,13 @@ use pretty::RcDoc;
 pub struct FormatOptions {
     pub indent_width: isize,
     pub fail_on_unhandled_rule: bool,
+    /// Orders the fields of `client` blocks (provider, retry_policy, options)
+    /// and `function` blocks (client, prompt) the same way everywhere, and
+    /// sorts client options by key, so diffs stay small.
+    pub canonical_order: bool,
+    /// Lines up the trailing comments of adjacent class and enum fields,
+    /// instead of putting each one a space after its field.
+    pub align_trailing_comments: bool,
 }
 
 pub fn format_schema(source: &str, format_options: FormatOptions) -> Result<String> {
@@ -28,9 +35,15 @@ pub fn format_schema(source: &str, format_options: FormatOptions) -> Result<Stri
     let formatter = Formatter {
         indent_width: format_options.indent_width,
         fail_on_unhandled_rule: format_options.fail_on_unhandled_rule,
+        canonical_order: format_options.canonical_order,
+        align_trailing_comments: format_options.align_trailing_comments,
     };
 
     let doc = formatter.schema_to_doc(schema_pair.into_inner())?;
+    render(&doc)
+}
+
+fn render(doc: &RcDoc<'_, ()>) -> Result<String> {
     let mut w = Vec::new();
     doc.render(10, &mut w)
         .map_err(|_| anyhow!("Failed to render doc"))?;
@@ -75,6 +88,8 @@ macro_rules! next_pair {
 struct Formatter {
     indent_width: isize,
     fail_on_unhandled_rule: bool,
+    canonical_order: bool,
+    align_trailing_comments: bool,
 }
 
 impl Formatter {
@@ -86,6 +101,9 @@ impl Formatter {
                 Rule::type_expression_block => {
                     doc = doc.append(self.type_expression_block_to_doc(pair.into_inner())?);
                 }
+                Rule::value_expression_block if self.canonical_order => {
+                    doc = doc.append(self.value_expression_block_to_doc(pair)?);
+                }
                 Rule::EOI => {
                     // skip
                 }
@@ -127,11 +145,31 @@ impl Formatter {
         mut pairs: Pairs<'a, Rule>,
     ) -> Result<RcDoc<'a, ()>> {
         let mut content_docs = vec![];
+        // Fields whose trailing comment gets aligned: their index in
+        // `content_docs`, the run of adjacent fields they're in, and the
+        // field and comment as text.
+        let mut aligned = vec![];
+        let mut run = 0;
 
         for pair in &mut pairs {
+            if pair.as_rule() != Rule::type_expression {
+                run += 1;
+            }
             match pair.as_rule() {
                 Rule::type_expression => {
-                    content_docs.push(self.type_expression_to_doc(pair.into_inner())?);
+                    let comment = trailing_comment(&pair).filter(|_| self.align_trailing_comments);
+                    let doc = self.type_expression_to_doc(pair.into_inner())?;
+                    if let Some(comment) = comment {
+                        let rendered = render(&doc)?;
+                        let field = rendered
+                            .strip_suffix(comment)
+                            .map(str::trim_end)
+                            .filter(|field| !field.contains('\n'));
+                        if let Some(field) = field {
+                            aligned.push((content_docs.len(), run, field.to_string(), comment));
+                        }
+                    }
+                    content_docs.push(doc);
                 }
                 Rule::block_attribute => {
                     content_docs.push(pair_to_doc_text(pair));
@@ -148,6 +186,16 @@ impl Formatter {
             }
         }
 
+        for (index, run, field, comment) in &aligned {
+            let width = aligned
+                .iter()
+                .filter(|(_, other_run, ..)| other_run == run)
+                .map(|(_, _, field, _)| field.chars().count())
+                .max()
+                .unwrap_or_default();
+            content_docs[*index] = RcDoc::text(format!("{field:<width$} {comment}"));
+        }
+
         let doc = if content_docs.len() > 0 {
             content_docs
                 .into_iter()
@@ -269,6 +317,206 @@ impl Formatter {
         Ok(())
     }
 
+    /// A `client` or `function` block with its fields in canonical order.
+    /// Other blocks are kept as they are.
+    fn value_expression_block_to_doc<'a>(&self, pair: Pair<'a, Rule>) -> Result<RcDoc<'a, ()>> {
+        let source = pair.as_str();
+        let start = pair.as_span().start();
+        let mut pairs = pair.clone().into_inner();
+
+        let keyword = next_pair!(pairs, Rule::value_expression_keyword)?;
+        let (field_order, sort_options): (&[&str], bool) =
+            match keyword.into_inner().next().map(|k| k.as_rule()) {
+                Some(Rule::CLIENT_KEYWORD) => (&["provider", "retry_policy", "options"], true),
+                Some(Rule::FUNCTION_KEYWORD) => (&["client", "prompt"], false),
+                _ => return Ok(RcDoc::text(source)),
+            };
+
+        let mut header_end = None;
+        for pair in &mut pairs {
+            match pair.as_rule() {
+                // Comments between the header and the `{` have nowhere to go.
+                Rule::SPACER_TEXT if !pair.as_str().trim().is_empty() => {
+                    return Ok(RcDoc::text(source));
+                }
+                Rule::BLOCK_OPEN => {
+                    header_end = Some(pair.as_span().start() - start);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let header = header_end.ok_or(anyhow!("Expected a {:?}", Rule::BLOCK_OPEN))?;
+        let contents = next_pair!(pairs, Rule::value_expression_contents)?;
+
+        // Comments move with the field after them, and fields keep their
+        // relative order within the same rank.
+        let last_rank = field_order.len() + 1;
+        let mut fields = vec![];
+        let mut comments = vec![];
+        for pair in contents.into_inner() {
+            let (rank, doc) = match pair.as_rule() {
+                Rule::value_expression => {
+                    let name = pair
+                        .clone()
+                        .into_inner()
+                        .next()
+                        .map_or("", |ident| ident.as_str());
+                    let rank = field_order
+                        .iter()
+                        .position(|field| *field == name)
+                        .unwrap_or(field_order.len());
+                    let sort_entries = sort_options && name == "options";
+                    (rank, self.value_expression_to_doc(pair, sort_entries)?)
+                }
+                Rule::comment_block => {
+                    comments.push(comment_block_to_doc(pair));
+                    continue;
+                }
+                Rule::empty_lines => continue,
+                Rule::block_attribute => (last_rank, pair_to_doc_text(pair)),
+                _ => (last_rank, self.unhandled_rule_to_doc(pair)?),
+            };
+            let doc = comments
+                .drain(..)
+                .fold(RcDoc::nil(), |acc, comment| {
+                    acc.append(comment).append(RcDoc::hardline())
+                })
+                .append(doc);
+            fields.push((rank, doc));
+        }
+        fields.extend(comments.into_iter().map(|comment| (last_rank + 1, comment)));
+        fields.sort_by_key(|(rank, _)| *rank);
+
+        Ok(RcDoc::text(source[..header].trim_end())
+            .append(RcDoc::space())
+            .append(self.block_to_doc(fields.into_iter().map(|(_, doc)| doc).collect())))
+    }
+
+    fn value_expression_to_doc<'a>(
+        &self,
+        pair: Pair<'a, Rule>,
+        sort_entries: bool,
+    ) -> Result<RcDoc<'a, ()>> {
+        let column = start_column(&pair);
+        let mut docs = vec![];
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::expression => {
+                    docs.push(self.expression_to_doc(pair, column, 1, sort_entries)?)
+                }
+                Rule::identifier | Rule::field_attribute | Rule::trailing_comment => {
+                    docs.push(pair_to_doc_text(pair))
+                }
+                _ => docs.push(self.unhandled_rule_to_doc(pair)?),
+            }
+        }
+        Ok(RcDoc::intersperse(docs, RcDoc::space()))
+    }
+
+    /// The value of a field or entry that started at `column` and is now
+    /// `depth` levels deep. Maps are rebuilt, sorted by key with
+    /// `sort_entries`; other values are kept as they are, re-indented to
+    /// match.
+    fn expression_to_doc<'a>(
+        &self,
+        pair: Pair<'a, Rule>,
+        column: usize,
+        depth: usize,
+        sort_entries: bool,
+    ) -> Result<RcDoc<'a, ()>> {
+        match pair.clone().into_inner().next() {
+            Some(map) if sort_entries && map.as_rule() == Rule::map_expression => {
+                self.map_expression_to_doc(map, depth + 1)
+            }
+            _ => Ok(RcDoc::text(reindent(
+                pair.as_str().trim(),
+                column,
+                self.indent_width.max(0) as usize * depth,
+            ))),
+        }
+    }
+
+    /// A map whose entries are `depth` levels deep, sorted by key.
+    fn map_expression_to_doc<'a>(
+        &self,
+        pair: Pair<'a, Rule>,
+        depth: usize,
+    ) -> Result<RcDoc<'a, ()>> {
+        let mut entries = vec![];
+        let mut comments = vec![];
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::map_entry => entries.push(self.map_entry_to_doc(pair, depth)?),
+                Rule::comment_block => comments.push(comment_block_to_doc(pair)),
+                Rule::empty_lines => {}
+                _ => comments.push(self.unhandled_rule_to_doc(pair)?),
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(self.block_to_doc(
+            entries
+                .into_iter()
+                .map(|(_, doc)| doc)
+                .chain(comments)
+                .collect(),
+        ))
+    }
+
+    /// The entry with the key it's sorted by.
+    fn map_entry_to_doc<'a>(
+        &self,
+        pair: Pair<'a, Rule>,
+        depth: usize,
+    ) -> Result<(String, RcDoc<'a, ()>)> {
+        let mut key = String::new();
+        let mut column = 0;
+        let mut doc = RcDoc::nil();
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::comment_block => {
+                    doc = doc
+                        .append(comment_block_to_doc(pair))
+                        .append(RcDoc::hardline());
+                }
+                Rule::empty_lines => {}
+                Rule::map_key => {
+                    key = pair.as_str().trim().trim_matches('"').to_string();
+                    column = start_column(&pair);
+                    doc = doc.append(pair_to_doc_text(pair));
+                }
+                Rule::expression => {
+                    doc = doc
+                        .append(RcDoc::space())
+                        .append(self.expression_to_doc(pair, column, depth, true)?);
+                }
+                Rule::ENTRY_CATCH_ALL | Rule::trailing_comment => {
+                    doc = doc.append(RcDoc::space()).append(pair_to_doc_text(pair));
+                }
+                _ => doc = doc.append(self.unhandled_rule_to_doc(pair)?),
+            }
+        }
+        Ok((key, doc))
+    }
+
+    /// `{`, then each of `docs` on its own indented line, then `}`.
+    fn block_to_doc<'a>(&self, docs: Vec<RcDoc<'a, ()>>) -> RcDoc<'a, ()> {
+        if docs.is_empty() {
+            return RcDoc::text("{}");
+        }
+        RcDoc::text("{")
+            .append(
+                docs.into_iter()
+                    .fold(RcDoc::nil(), |acc, doc| {
+                        acc.append(RcDoc::hardline()).append(doc)
+                    })
+                    .nest(self.indent_width),
+            )
+            .append(RcDoc::hardline())
+            .append(RcDoc::text("}"))
+    }
+
     fn unhandled_rule_to_doc<'a>(&self, pair: Pair<'a, Rule>) -> Result<RcDoc<'a, ()>> {
         if self.fail_on_unhandled_rule {
             Err(anyhow!("Unhandled rule: {:?}", pair.as_rule()))
@@ -284,20 +532,75 @@ fn pair_to_doc_text<'a>(pair: Pair<'a, Rule>) -> RcDoc<'a, ()> {
     RcDoc::text(pair.as_str().trim())
 }
 
+/// The comment at the end of a field's line, if it has one.
+fn trailing_comment<'a>(pair: &Pair<'a, Rule>) -> Option<&'a str> {
+    let comment = pair
+        .clone()
+        .into_inner()
+        .flatten()
+        .rev()
+        .find(|p| p.as_rule() == Rule::trailing_comment)?;
+    let line = |pair: &Pair<'_, Rule>| pair.as_span().start_pos().line_col().0;
+    let same_line = line(&comment) == line(pair);
+    let comment = comment.as_str().trim();
+    (same_line && pair.as_str().trim_end().ends_with(comment)).then_some(comment)
+}
+
+fn comment_block_to_doc<'a>(pair: Pair<'a, Rule>) -> RcDoc<'a, ()> {
+    RcDoc::intersperse(
+        pair.as_str()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(RcDoc::text),
+        RcDoc::hardline(),
+    )
+}
+
+/// The 0-based column `pair` starts at.
+fn start_column(pair: &Pair<'_, Rule>) -> usize {
+    pair.as_span().start_pos().line_col().1 - 1
+}
+
+/// Shifts the lines after the first of `text`, which started at `from`
+/// columns, so that it keeps its shape when it starts at `to` columns.
+fn reindent(text: &str, from: usize, to: usize) -> String {
+    let mut lines = text.lines();
+    let mut out = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        out.push('\n');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        out.push_str(&" ".repeat((indent + to).saturating_sub(from)));
+        out.push_str(line.trim_start());
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use unindent::Unindent as _;
 
+    fn test_options() -> FormatOptions {
+        FormatOptions {
+            indent_width: 4,
+            fail_on_unhandled_rule: true,
+            canonical_order: false,
+            align_trailing_comments: false,
+        }
+    }
+
     #[track_caller]
     fn assert_format_eq(schema: &str, expected: &str) -> Result<()> {
-        let formatted = format_schema(
-            &schema.unindent().trim_end(),
-            FormatOptions {
-                indent_width: 4,
-                fail_on_unhandled_rule: true,
-            },
-        )?;
+        assert_format_eq_with(schema, expected, test_options())
+    }
+
+    #[track_caller]
+    fn assert_format_eq_with(schema: &str, expected: &str, options: FormatOptions) -> Result<()> {
+        let formatted = format_schema(&schema.unindent().trim_end(), options)?;
         assert_eq!(expected.unindent().trim_end(), formatted);
         Ok(())
     }
@@ -352,6 +655,132 @@ mod tests {
             "#,
         )?;
 
+        assert_format_eq(
+            r###"
+                class Foo {
+                    field1   string @description(#"""Wrap it in "# and "##"""#)
+                }
+            "###,
+            r###"
+                class Foo {
+                    field1 string @description(#"""Wrap it in "# and "##"""#)
+                }
+            "###,
+        )?;
+
+        Ok(())
+    }
+    #[test]
+    fn test_canonical_order() -> anyhow::Result<()> {
+        assert_format_eq_with(
+            r#"
+                client<llm> MyClient {
+                  options {
+                    temperature 0
+                    model "gpt-4o"
+                    // Shared with the other clients
+                    api_key env.OPENAI_API_KEY
+                    headers {
+                      "X-Team" "search"
+                      "Anthropic-Beta" "none"
+                    }
+                  }
+                  retry_policy MyRetry // Retries twice
+                  provider openai
+                }
+            "#,
+            r#"
+                client<llm> MyClient {
+                    provider openai
+                    retry_policy MyRetry // Retries twice
+                    options {
+                        // Shared with the other clients
+                        api_key env.OPENAI_API_KEY
+                        headers {
+                            "Anthropic-Beta" "none"
+                            "X-Team" "search"
+                        }
+                        model "gpt-4o"
+                        temperature 0
+                    }
+                }
+            "#,
+            FormatOptions {
+                canonical_order: true,
+                ..test_options()
+            },
+        )?;
+
+        assert_format_eq_with(
+            r##"
+                function Summarize(text: string) -> string {
+                  // Keep it short
+                  prompt #"
+                    Summarize {{ text }}
+                  "#
+                  client "openai/gpt-4o"
+                }
+            "##,
+            r##"
+                function Summarize(text: string) -> string {
+                    client "openai/gpt-4o"
+                    // Keep it short
+                    prompt #"
+                      Summarize {{ text }}
+                    "#
+                }
+            "##,
+            FormatOptions {
+                canonical_order: true,
+                ..test_options()
+            },
+        )?;
+
+        Ok(())
+    }
+    #[test]
+    fn test_align_trailing_comments() -> anyhow::Result<()> {
+        let schema = r#"
+            class User {
+                id int // Primary key
+                display_name string   // Shown in the UI
+                tags string[]
+
+                created_at string // ISO 8601
+                updated_at string? // Unset until the first edit
+            }
+        "#;
+
+        assert_format_eq(
+            schema,
+            r#"
+                class User {
+                    id int // Primary key
+                    display_name string // Shown in the UI
+                    tags string[]
+                    created_at string // ISO 8601
+                    updated_at string? // Unset until the first edit
+                }
+            "#,
+        )?;
+
+        assert_format_eq_with(
+            schema,
+            r#"
+                class User {
+                    id int              // Primary key
+                    display_name string // Shown in the UI
+                    tags string[]
+                    created_at string  // ISO 8601
+                    updated_at string? // Unset until the first edit
+                }
+            "#,
+            FormatOptions {
+                align_trailing_comments: true,
+                ..test_options()
+            },
+        )?;
+
         Ok(())
     }
 }
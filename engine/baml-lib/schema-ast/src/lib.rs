@@ -9,9 +9,11 @@ pub use self::parser::parse_schema;
 /// source span information.
 pub mod ast;
 
+mod builder;
 mod formatter;
 mod parser;
 
+pub use builder::{is_valid_identifier, ClassBuilder, EnumBuilder, FieldBuilder, SchemaBuilder};
 pub use formatter::{format_schema, FormatOptions};
 
 /// Transform the input string into a valid (quoted and escaped) PSL string literal.
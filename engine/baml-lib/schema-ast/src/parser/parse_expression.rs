@@ -198,7 +198,8 @@ pub(super) fn parse_raw_string(token: Pair<'_>, diagnostics: &mut Diagnostics) -
             | Rule::raw_string_literal_content_2
             | Rule::raw_string_literal_content_3
             | Rule::raw_string_literal_content_4
-            | Rule::raw_string_literal_content_5 => {
+            | Rule::raw_string_literal_content_5
+            | Rule::raw_string_literal_content_heredoc => {
                 content = Some((
                     current.as_str().to_string(),
                     diagnostics.span(current.as_span()),
@@ -214,7 +215,6 @@ pub(super) fn parse_raw_string(token: Pair<'_>, diagnostics: &mut Diagnostics) -
 }
 
 // NOTE(sam): this doesn't handle unicode escape sequences e.g. \u1234
-// also this has panicks in it (see the hex logic)
 fn unescape_string(val: &str) -> String {
     let mut result = String::with_capacity(val.len());
     let mut chars = val.chars().peekable();
@@ -230,10 +230,17 @@ fn unescape_string(val: &str) -> String {
                 Some('\"') => result.push('\"'),
                 Some('\\') => result.push('\\'),
                 Some('x') => {
-                    let mut hex = String::new();
-                    hex.push(chars.next().unwrap());
-                    hex.push(chars.next().unwrap());
-                    result.push(u8::from_str_radix(&hex, 16).unwrap() as char);
+                    let hex: String = chars.by_ref().take(2).collect();
+                    match u8::from_str_radix(&hex, 16) {
+                        Ok(byte) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+                            result.push(byte as char)
+                        }
+                        // Not a valid `\xNN` escape: keep the source text as written.
+                        _ => {
+                            result.push_str("\\x");
+                            result.push_str(&hex);
+                        }
+                    }
                 }
                 Some(c) => {
                     result.push('\\');
@@ -349,4 +356,56 @@ mod tests {
             _ => panic!("Expected JinjaExpression, got {expr:?}"),
         }
     }
+
+    #[test]
+    fn heredoc_raw_string() {
+        let input =
+            "prompt #\"\"\"\n    Reply with a raw string:\n      #\"{{ answer }}\"#\n  \"\"\"#";
+        let source = SourceFile::new_static("test_file.baml".into(), input);
+        let mut diagnostics = Diagnostics::new("test_file.baml".into());
+        diagnostics.set_source(&source);
+
+        let pair = BAMLParser::parse(Rule::raw_string_literal, &input[7..])
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(pair.as_str(), &input[7..]);
+        let raw = parse_raw_string(pair, &mut diagnostics);
+        assert_eq!(
+            raw.value(),
+            "Reply with a raw string:\n  #\"{{ answer }}\"#"
+        );
+    }
+
+    #[test]
+    fn raw_string_source_span() {
+        let input = "prompt #\"\n\n    Hello\n      {{ name }}\n\n    Bye\n  \"#";
+        let source = SourceFile::new_static("test_file.baml".into(), input);
+        let mut diagnostics = Diagnostics::new("test_file.baml".into());
+        diagnostics.set_source(&source);
+
+        let pair = BAMLParser::parse(Rule::raw_string_literal, &input[7..])
+            .unwrap()
+            .next()
+            .unwrap();
+        let raw = parse_raw_string(pair, &mut diagnostics);
+        assert_eq!(raw.value(), "Hello\n  {{ name }}\n\nBye");
+
+        let name = raw.value().find("name").unwrap();
+        let span = raw.source_span(name..name + 4);
+        assert_eq!(&input[span.start + 7..span.end + 7], "name");
+
+        let bye = raw.value().find("Bye").unwrap();
+        let span = raw.source_span(bye..bye + 3);
+        assert_eq!(&input[span.start + 7..span.end + 7], "Bye");
+    }
+
+    #[test]
+    fn unescape_malformed_hex() {
+        assert_eq!(unescape_string(r"\x41"), "A");
+        assert_eq!(unescape_string(r"\x"), r"\x");
+        assert_eq!(unescape_string(r"\x4"), r"\x4");
+        assert_eq!(unescape_string(r"\xZZ!"), r"\xZZ!");
+        assert_eq!(unescape_string(r"\x+5"), r"\x+5");
+    }
 }
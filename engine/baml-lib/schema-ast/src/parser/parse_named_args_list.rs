@@ -1,8 +1,8 @@
 use internal_baml_diagnostics::Diagnostics;
 
 use super::{
-    helpers::parsing_catch_all, parse_field::parse_field_type_chain,
-    parse_identifier::parse_identifier,
+    helpers::parsing_catch_all, parse_expression::parse_expression,
+    parse_field::parse_field_type_chain, parse_identifier::parse_identifier,
 };
 use crate::{
     assert_correct_parser,
@@ -50,6 +50,20 @@ pub(crate) fn parse_named_argument_list(
                 Rule::field_type | Rule::field_type_chain => {
                     r#type = Some(parse_function_arg(arg, diagnostics)?);
                 }
+                Rule::expression => {
+                    if let (Some(r#type), Some(default)) =
+                        (r#type.as_mut(), parse_expression(arg, diagnostics))
+                    {
+                        let span = default.span().clone();
+                        r#type.default = Some(default);
+                        if r#type.default_value().is_none() {
+                            diagnostics.push_error(DatamodelError::new_validation_error(
+                                "Default values must be a string, number, boolean or null",
+                                span,
+                            ));
+                        }
+                    }
+                }
                 _ => parsing_catch_all(arg, "named_argument_list"),
             }
         }
@@ -91,6 +105,7 @@ pub fn parse_function_arg(
         Some(ftype) => Ok(BlockArg {
             span,
             field_type: ftype,
+            default: None,
         }),
         None => Err(DatamodelError::new_validation_error(
             "Failed to find type",
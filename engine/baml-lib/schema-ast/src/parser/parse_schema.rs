@@ -6,8 +6,11 @@ use super::{
     parse_value_expression_block::parse_value_expression_block, BAMLParser, Rule,
 };
 use crate::ast::*;
-use internal_baml_diagnostics::{DatamodelError, Diagnostics, SourceFile};
-use pest::Parser;
+use internal_baml_diagnostics::{
+    parse_suppression_comment, DatamodelError, DatamodelWarning, Diagnostics, SourceFile,
+    Suppression,
+};
+use pest::{iterators::Pair, Parser};
 
 #[cfg(feature = "debug_parser")]
 fn pretty_print<'a>(pair: pest::iterators::Pair<'a, Rule>, indent_level: usize) {
@@ -50,6 +53,9 @@ pub fn parse_schema(
             #[cfg(feature = "debug_parser")]
             pretty_print(datamodel.clone(), 0);
 
+            let mut suppressions = Vec::new();
+            collect_suppressions(datamodel.clone(), &mut diagnostics, &mut suppressions);
+
             let mut top_level_definitions = Vec::new();
 
             let mut pending_block_comment = None;
@@ -70,7 +76,7 @@ pub fn parse_schema(
                             _ => (), // may need to save other somehow for error propagation
                         }
                     }
-                    Rule::value_expression_block => {
+                    Rule::value_expression_block | Rule::models_block => {
                         let val_expr = parse_value_expression_block(
                             current,
                             pending_block_comment.take(),
@@ -84,6 +90,8 @@ pub fn parse_schema(
                                     ValueExprBlockType::Client => Some(Top::Client(val)),
                                     ValueExprBlockType::RetryPolicy => Some(Top::RetryPolicy(val)),
                                     ValueExprBlockType::Generator => Some(Top::Generator(val)),
+                                    ValueExprBlockType::Profile => Some(Top::Profile(val)),
+                                    ValueExprBlockType::Models => Some(Top::Models(val)),
                                 } {
                                     top_level_definitions.push(top);
                                 }
@@ -136,6 +144,7 @@ pub fn parse_schema(
             Ok((
                 SchemaAst {
                     tops: top_level_definitions,
+                    suppressions,
                 },
                 diagnostics,
             ))
@@ -167,6 +176,69 @@ pub fn parse_schema(
     }
 }
 
+/// Collect `// baml-ignore:` comments. A comment applies to the item that
+/// directly follows it, or, when it trails an item, to that item.
+fn collect_suppressions(
+    pair: Pair<'_, Rule>,
+    diagnostics: &mut Diagnostics,
+    suppressions: &mut Vec<Suppression>,
+) {
+    let parent = pair.as_span();
+    let mut children = pair.into_inner().peekable();
+
+    while let Some(child) = children.next() {
+        match child.as_rule() {
+            Rule::comment_block => {
+                // A blank line detaches the comment from what follows.
+                let target = children
+                    .peek()
+                    .filter(|next| next.as_rule() != Rule::empty_lines)
+                    .map(|next| next.as_span());
+                for comment in child.into_inner() {
+                    push_suppression(comment, target, diagnostics, suppressions);
+                }
+            }
+            Rule::trailing_comment => {
+                for comment in child.into_inner() {
+                    push_suppression(comment, Some(parent), diagnostics, suppressions);
+                }
+            }
+            _ => collect_suppressions(child, diagnostics, suppressions),
+        }
+    }
+}
+
+fn push_suppression(
+    comment: Pair<'_, Rule>,
+    target: Option<pest::Span<'_>>,
+    diagnostics: &mut Diagnostics,
+    suppressions: &mut Vec<Suppression>,
+) {
+    if comment.as_rule() != Rule::comment {
+        return;
+    }
+    let span = comment.as_span();
+    let text = comment
+        .into_inner()
+        .find(|c| c.as_rule() == Rule::doc_content)
+        .map_or("", |c| c.as_str());
+
+    match (parse_suppression_comment(text), target) {
+        (None, _) => {}
+        (Some(Ok(codes)), Some(target)) => suppressions.push(Suppression {
+            codes,
+            target: diagnostics.span(target),
+        }),
+        (Some(Ok(_)), None) => diagnostics.push_warning(DatamodelWarning::new_invalid_suppression(
+            "This comment must be directly above, or at the end of, the item it applies to.",
+            diagnostics.span(span),
+        )),
+        (Some(Err(message)), _) => diagnostics.push_warning(
+            DatamodelWarning::new_invalid_suppression(&message, diagnostics.span(span)),
+        ),
+    }
+}
+
 fn get_expected_from_error(positives: &[Rule]) -> String {
     use std::fmt::Write as _;
     let mut out = String::with_capacity(positives.len() * 6);
@@ -9,6 +9,7 @@ use super::{
 };
 
 use crate::ast::*;
+use baml_types::ClientKind;
 use internal_baml_diagnostics::{DatamodelError, Diagnostics}; // Add this line
 
 pub(crate) fn parse_value_expression_block(
@@ -18,11 +19,13 @@ pub(crate) fn parse_value_expression_block(
 ) -> Result<ValueExprBlock, DatamodelError> {
     let pair_span = pair.as_span();
     let mut name: Option<Identifier> = None;
+    let mut extends: Option<Identifier> = None;
     let mut attributes: Vec<Attribute> = Vec::new();
     let mut input = None;
     let mut output = None;
     let mut fields: Vec<Field<Expression>> = vec![];
     let mut sub_type: Option<ValueExprBlockType> = None;
+    let mut client_kind = ClientKind::Llm;
     let mut has_arrow = false;
 
     for current in pair.into_inner() {
@@ -31,14 +34,43 @@ pub(crate) fn parse_value_expression_block(
                 "function" => sub_type = Some(ValueExprBlockType::Function),
                 "test" => sub_type = Some(ValueExprBlockType::Test),
                 "client" | "client<llm>" => sub_type = Some(ValueExprBlockType::Client),
+                "client<embedding>" => {
+                    sub_type = Some(ValueExprBlockType::Client);
+                    client_kind = ClientKind::Embedding;
+                }
                 "retry_policy" => sub_type = Some(ValueExprBlockType::RetryPolicy),
                 "generator" => sub_type = Some(ValueExprBlockType::Generator),
-                _ => panic!("Unexpected value expression keyword: {}", current.as_str()),
+                "profile" => sub_type = Some(ValueExprBlockType::Profile),
+                other => diagnostics.push_error(DatamodelError::new_validation_error(
+                    &format!("Unexpected value expression keyword: {other}"),
+                    diagnostics.span(current.as_span()),
+                )),
             },
+            // The model catalog has no name of its own.
+            Rule::MODELS_KEYWORD => {
+                sub_type = Some(ValueExprBlockType::Models);
+                name = Some(Identifier::Local(
+                    "models".to_string(),
+                    diagnostics.span(current.as_span()),
+                ));
+            }
             Rule::ARROW => {
                 has_arrow = true;
             }
             Rule::identifier => name = Some(parse_identifier(current, diagnostics)),
+            Rule::extends_clause => {
+                let span = diagnostics.span(current.as_span());
+                if sub_type != Some(ValueExprBlockType::Client) {
+                    diagnostics.push_error(DatamodelError::new_validation_error(
+                        "Only clients may extend another client",
+                        span,
+                    ));
+                }
+                extends = current
+                    .into_inner()
+                    .find(|pair| pair.as_rule() == Rule::identifier)
+                    .map(|pair| parse_identifier(pair, diagnostics));
+            }
             Rule::named_argument_list => match parse_named_argument_list(current, diagnostics) {
                 Ok(arg) => input = Some(arg),
                 Err(err) => diagnostics.push_error(err),
@@ -67,6 +99,8 @@ pub(crate) fn parse_value_expression_block(
                                         ValueExprBlockType::Client => "Client",
                                         ValueExprBlockType::RetryPolicy => "RetryPolicy",
                                         ValueExprBlockType::Generator => "Generator",
+                                        ValueExprBlockType::Profile => "Profile",
+                                        ValueExprBlockType::Models => "Models",
                                     })
                                     .unwrap_or("Other"),
                                 item,
@@ -91,25 +125,39 @@ pub(crate) fn parse_value_expression_block(
                         Rule::block_attribute => {
                             let span = item.as_span();
                             let attribute = parse_attribute(item, false, diagnostics);
-                            let value_is_test = sub_type == Some(ValueExprBlockType::Test);
                             let attribute_name = attribute.name.to_string();
-                            let attribute_is_constraint = &attribute_name == "check" || &attribute_name == "assert";
 
-                            // Only tests may have block attributes, and the only valid block attributes
-                            // are checks/asserts.
-                            if value_is_test && attribute_is_constraint {
-                                // value_expression_block is compatible with the attribute
-                                attributes.push(attribute);
-                            } else if !value_is_test {
-                                diagnostics.push_error(DatamodelError::new_validation_error(
-                                    "Only Tests may contain block-level attributes",
-                                    diagnostics.span(span),
-                                ))
-                            } else {
-                                diagnostics.push_error(DatamodelError::new_validation_error(
-                                    "Tests may only contain 'check' or 'assert' attributes",
+                            // Tests may have checks/asserts/expectations, functions may ask for
+                            // `@@confidence` or `@@self_consistency` and configure `@@trace`, and
+                            // functions, clients and tests may be conditional with `@@if`.
+                            match (&sub_type, attribute_name.as_str()) {
+                                (Some(ValueExprBlockType::Test), "check" | "assert" | "expect" | "if")
+                                | (Some(ValueExprBlockType::Function), "if" | "confidence" | "self_consistency" | "trace")
+                                | (Some(ValueExprBlockType::Client), "if") => {
+                                    attributes.push(attribute);
+                                }
+                                (Some(ValueExprBlockType::Test), _) => {
+                                    diagnostics.push_error(DatamodelError::new_validation_error(
+                                        "Tests may only contain 'check', 'assert', 'expect' or 'if' attributes",
+                                        diagnostics.span(span),
+                                    ))
+                                }
+                                (Some(ValueExprBlockType::Function), _) => {
+                                    diagnostics.push_error(DatamodelError::new_validation_error(
+                                        "Functions may only contain 'if', 'confidence', 'self_consistency' or 'trace' attributes",
+                                        diagnostics.span(span),
+                                    ))
+                                }
+                                (Some(ValueExprBlockType::Client), _) => {
+                                    diagnostics.push_error(DatamodelError::new_validation_error(
+                                        "Clients may only contain an 'if' attribute",
+                                        diagnostics.span(span),
+                                    ))
+                                }
+                                _ => diagnostics.push_error(DatamodelError::new_validation_error(
+                                    "Only Tests, Functions and Clients may contain block-level attributes",
                                     diagnostics.span(span),
-                                ))
+                                )),
                             }
                         }
                         Rule::empty_lines => {}
@@ -134,6 +182,8 @@ pub(crate) fn parse_value_expression_block(
                     (true, true) => {
                         return Ok(ValueExprBlock {
                             name,
+                            extends,
+                            client_kind,
                             input,
                             output,
                             attributes,
@@ -150,6 +200,8 @@ pub(crate) fn parse_value_expression_block(
             } else {
                 return Ok(ValueExprBlock {
                     name,
+                    extends,
+                    client_kind,
                     input,
                     output,
                     attributes,
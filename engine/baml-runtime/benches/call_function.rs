@@ -0,0 +1,124 @@
+//! End-to-end function call benchmarks against a local mock provider.
+//!
+//! The mock speaks the OpenAI chat completions protocol, so every iteration
+//! exercises prompt rendering, the HTTP client, response parsing and tracing
+//! without touching the network.
+//!
+//! Run with `cargo bench -p baml-runtime`.
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use baml_runtime::BamlRuntime;
+use baml_types::{BamlMap, BamlValue};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::json;
+
+const BAML_SRC: &str = r##"
+client<llm> Mock {
+  provider openai-generic
+  options {
+    base_url env.MOCK_BASE_URL
+    api_key "not-a-real-key"
+    model "mock"
+  }
+}
+
+class Item {
+  id int
+  name string
+  tags string[]
+}
+
+function ExtractItems(text: string) -> Item[] {
+  client Mock
+  prompt #"
+    Extract the items from the text.
+
+    {{ ctx.output_format }}
+
+    {{ _.role("user") }}
+    {{ text }}
+  "#
+}
+"##;
+
+fn completion(items: usize) -> serde_json::Value {
+    let content = (0..items)
+        .map(|i| format!(r#"{{"id": {i}, "name": "item {i}", "tags": ["a", "b"]}}"#))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "mock",
+        "choices": [{
+            "index": 0,
+            "message": {
+                "role": "assistant",
+                "content": format!("Sure! Here you go:\n```json\n[{content}]\n```"),
+            },
+            "finish_reason": "stop",
+        }],
+        "usage": { "prompt_tokens": 10, "completion_tokens": 10, "total_tokens": 20 },
+    })
+}
+
+/// Serve a canned chat completion on an ephemeral local port.
+fn spawn_mock_provider(rt: &tokio::runtime::Runtime, items: usize) -> SocketAddr {
+    let listener = rt
+        .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    let body = completion(items);
+    let app = axum::Router::new().route(
+        "/chat/completions",
+        axum::routing::post(move || {
+            let body = body.clone();
+            async move { axum::Json(body) }
+        }),
+    );
+    rt.spawn(async move { axum::serve(listener, app).await.unwrap() });
+    addr
+}
+
+fn bench_call_function(c: &mut Criterion) {
+    // The mock gets its own executor so it never competes with the runtime's
+    // worker threads for the request it is serving.
+    let mock_rt = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("call_function");
+    for items in [1, 50] {
+        let addr = spawn_mock_provider(&mock_rt, items);
+        let base_url = format!("http://{addr}");
+        let runtime = BamlRuntime::from_file_content(
+            "baml_src",
+            &HashMap::from([("main.baml", BAML_SRC)]),
+            HashMap::from([("MOCK_BASE_URL", base_url.as_str())]),
+        )
+        .unwrap();
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("bench".to_string()), None);
+        let params: BamlMap<String, BamlValue> = BamlMap::from_iter([(
+            "text".to_string(),
+            BamlValue::String("Some text with items in it.".to_string()),
+        )]);
+
+        group.bench_with_input(BenchmarkId::from_parameter(items), &params, |b, params| {
+            b.iter(|| {
+                let (result, _) = runtime.call_function_sync(
+                    "ExtractItems".to_string(),
+                    params,
+                    &ctx,
+                    None,
+                    None,
+                    None,
+                );
+                black_box(result.unwrap())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_call_function);
+criterion_main!(benches);
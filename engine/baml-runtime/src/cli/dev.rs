@@ -1,7 +1,7 @@
 use anyhow::Result;
 use notify_debouncer_full::{new_debouncer, notify::*};
 use std::ops::DerefMut;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use crate::{cli::generate::GenerateArgs, BamlRuntime};
@@ -60,14 +60,17 @@ Thanks for trying out BAML!
         // known issues etc of inotify and its ilk
         let mut debouncer = new_debouncer(Duration::from_millis(200), None, tx)?;
 
+        // Watch the normalized path: on Windows the watcher reports events
+        // for `\\?\` and UNC roots in a different spelling than the user gave.
+        let from = BamlRuntime::parse_baml_src_path(&self.from)?;
         debouncer
             .watcher()
-            .watch(self.from.as_path(), RecursiveMode::Recursive)?;
+            .watch(from.as_path(), RecursiveMode::Recursive)?;
 
-        let (server, tcp_listener) = t.block_on(Server::new(self.from.clone(), self.port))?;
+        let (server, tcp_listener) = t.block_on(Server::new(from.clone(), self.port))?;
 
         let _ = GenerateArgs {
-            from: self.from.clone(),
+            from: from.clone(),
             no_version_check: false,
         }
         .run(defaults);
@@ -102,6 +105,13 @@ Thanks for trying out BAML!
             for result in rx {
                 match result {
                     Ok(events) => {
+                        let events = events
+                            .into_iter()
+                            .filter(|e| e.paths.iter().any(|p| is_baml_src_file(p)))
+                            .collect::<Vec<_>>();
+                        if events.is_empty() {
+                            continue;
+                        }
                         log::debug!(
                             "Reloading - {}",
                             match events.len() {
@@ -110,11 +120,11 @@ Thanks for trying out BAML!
                             }
                         );
                         let start = Instant::now();
-                        match BamlRuntime::from_directory(&self.from, std::env::vars().collect()) {
+                        match BamlRuntime::from_directory(&from, std::env::vars().collect()) {
                             Ok(mut new_runtime) => {
                                 let elapsed = start.elapsed();
                                 let _ = GenerateArgs {
-                                    from: self.from.clone(),
+                                    from: from.clone(),
                                     no_version_check: false,
                                 }
                                 .run(defaults);
@@ -140,7 +150,7 @@ Thanks for trying out BAML!
                     Err(errors) => {
                         log::warn!(
                             "Encountered errors while watching {}: {:?}",
-                            self.from.display(),
+                            from.display(),
                             errors
                         );
                     }
@@ -151,3 +161,12 @@ Thanks for trying out BAML!
         Ok(())
     }
 }
+
+/// Editors write swap and backup files next to the sources; only changes to
+/// files the runtime actually loads should trigger a reload.
+pub(super) fn is_baml_src_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("baml" | "json")
+    )
+}
@@ -2,6 +2,7 @@ pub mod dev;
 pub mod generate;
 pub mod init;
 pub mod serve;
+pub mod test;
 
 use internal_baml_core::configuration::GeneratorOutputType;
 
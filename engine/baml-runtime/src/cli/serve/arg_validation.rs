@@ -17,6 +17,7 @@ impl BamlServeValidate for BamlValue {
               }),
               BamlMediaContent::Url(_) => Ok(()),
               BamlMediaContent::Base64(_) => Ok(()),
+              BamlMediaContent::FileRef(_) => Ok(()),
             }
           }
           BamlValue::List(l) => {
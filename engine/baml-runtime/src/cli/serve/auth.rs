@@ -0,0 +1,424 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use axum::http::{HeaderMap, StatusCode};
+use jsonwebtoken::{jwk::JwkSet, DecodingKey, Validation};
+use tokio::sync::RwLock;
+
+use super::config::{AuthConfig, JwtConfig, Limits};
+
+pub(super) const API_KEY_HEADER: &str = "x-baml-api-key";
+pub(super) const HMAC_KEY_ID_HEADER: &str = "x-baml-key-id";
+pub(super) const HMAC_TIMESTAMP_HEADER: &str = "x-baml-timestamp";
+/// Hex encoded HMAC-SHA256 of `{timestamp}.{body}`.
+pub(super) const HMAC_SIGNATURE_HEADER: &str = "x-baml-signature";
+/// How far `x-baml-timestamp` may be from the server's clock.
+const HMAC_MAX_SKEW: Duration = Duration::from_secs(5 * 60);
+const JWKS_TTL: Duration = Duration::from_secs(10 * 60);
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Enforces the `[serve.auth]` section of `baml.toml`.
+pub(super) struct Authenticator {
+    method: Method,
+    rate_limiter: RateLimiter,
+}
+
+enum Method {
+    ApiKey(Vec<Key>),
+    Hmac(Vec<Key>),
+    Jwt {
+        config: JwtConfig,
+        jwks: RwLock<Option<(Instant, JwkSet)>>,
+    },
+}
+
+struct Key {
+    name: String,
+    secret: String,
+    limits: Limits,
+}
+
+/// Who made a request, and what they may do.
+#[derive(Debug)]
+pub(super) struct Principal {
+    pub name: String,
+    limits: Limits,
+}
+
+#[derive(Debug)]
+pub(super) struct AuthError {
+    pub status: StatusCode,
+    pub message: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl AuthError {
+    fn unauthorized(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+}
+
+impl Authenticator {
+    pub(super) fn new(config: AuthConfig) -> Result<Self> {
+        let resolve = |keys: Vec<super::config::KeyConfig>| {
+            keys.into_iter()
+                .map(|key| {
+                    Ok(Key {
+                        secret: key.resolve()?,
+                        name: key.name,
+                        limits: key.limits,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        let method = match config {
+            AuthConfig::ApiKey { keys } => Method::ApiKey(resolve(keys)?),
+            AuthConfig::Hmac { keys } => Method::Hmac(resolve(keys)?),
+            AuthConfig::Jwt(config) => Method::Jwt {
+                config,
+                jwks: RwLock::new(None),
+            },
+        };
+        Ok(Self {
+            method,
+            rate_limiter: RateLimiter::default(),
+        })
+    }
+
+    pub(super) fn mode(&self) -> &'static str {
+        match self.method {
+            Method::ApiKey(_) => "api_key",
+            Method::Hmac(_) => "hmac",
+            Method::Jwt { .. } => "jwt",
+        }
+    }
+
+    /// Identifies the caller. `body` is only read in HMAC mode.
+    pub(super) async fn authenticate(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<Principal, AuthError> {
+        match &self.method {
+            Method::ApiKey(keys) => {
+                let provided = header(headers, API_KEY_HEADER)?;
+                keys.iter()
+                    .find(|key| constant_time_eq(key.secret.as_bytes(), provided.as_bytes()))
+                    .map(Key::principal)
+                    .ok_or_else(|| AuthError::unauthorized(format!("Incorrect {API_KEY_HEADER}")))
+            }
+            Method::Hmac(keys) => {
+                let key_id = header(headers, HMAC_KEY_ID_HEADER)?;
+                let timestamp = header(headers, HMAC_TIMESTAMP_HEADER)?;
+                let signature = header(headers, HMAC_SIGNATURE_HEADER)?;
+                let key = keys
+                    .iter()
+                    .find(|key| key.name == key_id)
+                    .ok_or_else(|| AuthError::unauthorized(format!("Unknown key `{key_id}`")))?;
+                check_timestamp(timestamp, SystemTime::now())?;
+                let expected = hmac_signature(&key.secret, timestamp, body);
+                if constant_time_eq(
+                    expected.as_bytes(),
+                    signature.to_ascii_lowercase().as_bytes(),
+                ) {
+                    Ok(key.principal())
+                } else {
+                    Err(AuthError::unauthorized(format!(
+                        "Incorrect {HMAC_SIGNATURE_HEADER}"
+                    )))
+                }
+            }
+            Method::Jwt { config, jwks } => {
+                let token = header(headers, "authorization")?
+                    .strip_prefix("Bearer ")
+                    .ok_or_else(|| AuthError::unauthorized("Expected a bearer token"))?;
+                self.verify_jwt(config, jwks, token).await
+            }
+        }
+    }
+
+    /// Checks the allowlist and rate limit of `principal` for `function`.
+    pub(super) fn authorize(&self, principal: &Principal, function: &str) -> Result<(), AuthError> {
+        if let Some(functions) = &principal.limits.functions {
+            if !functions.iter().any(|f| f == function) {
+                return Err(AuthError {
+                    status: StatusCode::FORBIDDEN,
+                    message: format!("`{}` may not call {function}", principal.name),
+                    retry_after: None,
+                });
+            }
+        }
+        if let Some(limit) = principal.limits.rate_limit {
+            self.rate_limiter
+                .check(&principal.name, limit, Instant::now())
+                .map_err(|retry_after| AuthError {
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    message: format!(
+                        "`{}` exceeded its limit of {limit} requests per minute",
+                        principal.name
+                    ),
+                    retry_after: Some(retry_after),
+                })?;
+        }
+        Ok(())
+    }
+
+    async fn verify_jwt(
+        &self,
+        config: &JwtConfig,
+        jwks: &RwLock<Option<(Instant, JwkSet)>>,
+        token: &str,
+    ) -> Result<Principal, AuthError> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|e| AuthError::unauthorized(format!("Invalid token: {e}")))?;
+        let kid = header
+            .kid
+            .as_deref()
+            .ok_or_else(|| AuthError::unauthorized("Token has no `kid`"))?;
+
+        let cached = jwks
+            .read()
+            .await
+            .as_ref()
+            .filter(|(fetched, _)| fetched.elapsed() < JWKS_TTL)
+            .and_then(|(_, set)| set.find(kid).cloned());
+        let jwk = match cached {
+            Some(jwk) => jwk,
+            // Unknown keys trigger a refetch, so that rotated keys are picked up.
+            None => {
+                let set = fetch_jwks(&config.jwks_url).await.map_err(|e| AuthError {
+                    status: StatusCode::SERVICE_UNAVAILABLE,
+                    message: format!("{e:#}"),
+                    retry_after: None,
+                })?;
+                let jwk = set.find(kid).cloned();
+                *jwks.write().await = Some((Instant::now(), set));
+                jwk.ok_or_else(|| AuthError::unauthorized(format!("Unknown key `{kid}`")))?
+            }
+        };
+
+        let key = DecodingKey::from_jwk(&jwk)
+            .map_err(|e| AuthError::unauthorized(format!("Unsupported key `{kid}`: {e}")))?;
+        let mut validation = Validation::new(header.alg);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims =
+            jsonwebtoken::decode::<HashMap<String, serde_json::Value>>(token, &key, &validation)
+                .map_err(|e| AuthError::unauthorized(format!("Invalid token: {e}")))?
+                .claims;
+
+        let mut limits = config.limits.clone();
+        if let Some(claim) = &config.functions_claim {
+            let allowed = claims
+                .get(claim)
+                .and_then(|v| v.as_array())
+                .map(|v| {
+                    v.iter()
+                        .filter_map(|f| f.as_str().map(str::to_string))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            limits.functions = Some(match limits.functions {
+                Some(configured) => configured
+                    .into_iter()
+                    .filter(|f| allowed.contains(f))
+                    .collect(),
+                None => allowed,
+            });
+        }
+
+        Ok(Principal {
+            name: claims
+                .get("sub")
+                .and_then(|v| v.as_str())
+                .unwrap_or("anonymous")
+                .to_string(),
+            limits,
+        })
+    }
+}
+
+impl Key {
+    fn principal(&self) -> Principal {
+        Principal {
+            name: self.name.clone(),
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, AuthError> {
+    headers
+        .get(name)
+        .ok_or_else(|| AuthError::unauthorized(format!("Missing {name}")))?
+        .to_str()
+        .map_err(|_| AuthError::unauthorized(format!("Invalid {name}")))
+}
+
+fn check_timestamp(timestamp: &str, now: SystemTime) -> Result<(), AuthError> {
+    let timestamp = timestamp.parse::<u64>().map_err(|_| {
+        AuthError::unauthorized(format!("{HMAC_TIMESTAMP_HEADER} must be unix seconds"))
+    })?;
+    let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if now.abs_diff(timestamp) > HMAC_MAX_SKEW.as_secs() {
+        return Err(AuthError::unauthorized(format!(
+            "{HMAC_TIMESTAMP_HEADER} is too far from the server's clock"
+        )));
+    }
+    Ok(())
+}
+
+fn hmac_signature(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let mut ctx = ring::hmac::Context::with_key(&key);
+    ctx.update(timestamp.as_bytes());
+    ctx.update(b".");
+    ctx.update(body);
+    ctx.sign()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn fetch_jwks(url: &str) -> Result<JwkSet> {
+    reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to fetch JWKS from {url}"))?
+        .json::<JwkSet>()
+        .await
+        .with_context(|| format!("Invalid JWKS at {url}"))
+}
+
+/// Fixed one minute windows per principal.
+#[derive(Default)]
+struct RateLimiter {
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    /// On failure, returns how long until the window resets.
+    fn check(&self, name: &str, limit: u32, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap();
+        let (start, count) = windows.entry(name.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_LIMIT_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= limit {
+            return Err(RATE_LIMIT_WINDOW - now.duration_since(*start));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::serve::config::KeyConfig;
+
+    fn api_key_auth(functions: Option<Vec<&str>>) -> Authenticator {
+        Authenticator::new(AuthConfig::ApiKey {
+            keys: vec![KeyConfig {
+                name: "frontend".to_string(),
+                key: Some("sk-baml-test".to_string()),
+                key_env: None,
+                limits: Limits {
+                    rate_limit: Some(2),
+                    functions: functions.map(|f| f.into_iter().map(String::from).collect()),
+                },
+            }],
+        })
+        .unwrap()
+    }
+
+    fn headers(items: &[(&'static str, &str)]) -> HeaderMap {
+        items
+            .iter()
+            .map(|(k, v)| (axum::http::HeaderName::from_static(k), v.parse().unwrap()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn api_keys() {
+        let auth = api_key_auth(Some(vec!["ExtractResume"]));
+        let principal = auth
+            .authenticate(&headers(&[(API_KEY_HEADER, "sk-baml-test")]), b"")
+            .await
+            .unwrap();
+        assert_eq!(principal.name, "frontend");
+        assert!(auth.authorize(&principal, "ExtractResume").is_ok());
+        assert_eq!(
+            auth.authorize(&principal, "Classify").unwrap_err().status,
+            StatusCode::FORBIDDEN
+        );
+
+        let err = auth
+            .authenticate(&headers(&[(API_KEY_HEADER, "sk-baml-nope")]), b"")
+            .await
+            .unwrap_err();
+        assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+        assert!(auth.authenticate(&HeaderMap::new(), b"").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn hmac_signatures() {
+        let auth = Authenticator::new(AuthConfig::Hmac {
+            keys: vec![KeyConfig {
+                name: "backend".to_string(),
+                key: Some("secret".to_string()),
+                key_env: None,
+                limits: Limits::default(),
+            }],
+        })
+        .unwrap();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+        let body = br#"{"resume": "..."}"#;
+        let signature = hmac_signature("secret", &now, body);
+
+        let request = headers(&[
+            (HMAC_KEY_ID_HEADER, "backend"),
+            (HMAC_TIMESTAMP_HEADER, &now),
+            (HMAC_SIGNATURE_HEADER, &signature),
+        ]);
+        assert!(auth.authenticate(&request, body).await.is_ok());
+        // The signature covers the body.
+        assert!(auth.authenticate(&request, b"{}").await.is_err());
+
+        assert!(check_timestamp("0", SystemTime::now()).is_err());
+    }
+
+    #[test]
+    fn rate_limits_per_window() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.check("a", 2, start).is_ok());
+        assert!(limiter.check("a", 2, start).is_ok());
+        assert!(limiter.check("a", 2, start).is_err());
+        assert!(limiter.check("b", 2, start).is_ok());
+        assert!(limiter.check("a", 2, start + RATE_LIMIT_WINDOW).is_ok());
+    }
+}
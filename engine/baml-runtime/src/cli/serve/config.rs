@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// `baml.toml`, read from the directory containing `baml_src`.
+#[derive(Debug, Default, Deserialize)]
+struct BamlToml {
+    #[serde(default)]
+    serve: ServeConfig,
+}
+
+/// The `[serve]` section of `baml.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ServeConfig {
+    /// Without it, the server falls back to `BAML_PASSWORD`.
+    pub auth: Option<AuthConfig>,
+    /// Serves Prometheus metrics at `/metrics`.
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub(super) enum AuthConfig {
+    /// Clients send one of the keys in `x-baml-api-key`.
+    ApiKey { keys: Vec<KeyConfig> },
+    /// Clients sign `{timestamp}.{body}` with one of the keys, see
+    /// `auth::HMAC_SIGNATURE_HEADER`.
+    Hmac { keys: Vec<KeyConfig> },
+    /// Clients send a bearer token signed by a key in the JWKS.
+    Jwt(JwtConfig),
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct KeyConfig {
+    /// Identifies the key in logs and rate limits, and is the key id for HMAC.
+    pub name: String,
+    /// The key itself. Prefer `key_env` so that it is not committed.
+    pub key: Option<String>,
+    /// An environment variable holding the key.
+    pub key_env: Option<String>,
+    #[serde(flatten)]
+    pub limits: Limits,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct JwtConfig {
+    pub jwks_url: String,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    /// A claim listing the functions the token may call, in addition to
+    /// `functions`.
+    pub functions_claim: Option<String>,
+    /// Applied per `sub` claim.
+    #[serde(flatten)]
+    pub limits: Limits,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(super) struct Limits {
+    /// Requests per minute.
+    pub rate_limit: Option<u32>,
+    /// Functions that may be called. All functions if unset.
+    pub functions: Option<Vec<String>>,
+}
+
+impl KeyConfig {
+    pub(super) fn resolve(&self) -> Result<String> {
+        match (&self.key, &self.key_env) {
+            (Some(key), None) => Ok(key.clone()),
+            (None, Some(var)) => std::env::var(var)
+                .with_context(|| format!("Key `{}` reads `{var}`, which is not set", self.name)),
+            _ => anyhow::bail!(
+                "Key `{}` must set exactly one of `key` and `key_env`",
+                self.name
+            ),
+        }
+    }
+}
+
+impl ServeConfig {
+    pub(super) fn load(src_dir: &Path) -> Result<Self> {
+        let Some(path) = src_dir.parent().map(|dir| dir.join("baml.toml")) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str::<BamlToml>(content)?.serve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_auth_modes() {
+        let config = ServeConfig::parse(
+            r#"
+            [serve.auth]
+            mode = "api_key"
+
+            [[serve.auth.keys]]
+            name = "frontend"
+            key_env = "FRONTEND_KEY"
+            rate_limit = 60
+            functions = ["ExtractResume"]
+            "#,
+        )
+        .unwrap();
+        let Some(AuthConfig::ApiKey { keys }) = config.auth else {
+            panic!("expected api_key auth");
+        };
+        assert_eq!(keys[0].limits.rate_limit, Some(60));
+
+        let config = ServeConfig::parse(
+            r#"
+            [serve.auth]
+            mode = "jwt"
+            jwks_url = "https://example.com/.well-known/jwks.json"
+            audience = "baml"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(config.auth, Some(AuthConfig::Jwt(_))));
+
+        assert!(ServeConfig::parse("").unwrap().auth.is_none());
+        assert!(!ServeConfig::parse("").unwrap().metrics);
+        assert!(
+            ServeConfig::parse("[serve]\nmetrics = true")
+                .unwrap()
+                .metrics
+        );
+        assert!(ServeConfig::parse("[serve.auth]\nmode = \"magic\"").is_err());
+    }
+}
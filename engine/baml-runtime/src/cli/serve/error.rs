@@ -7,6 +7,7 @@ use serde_json::json;
 use crate::{errors::ExposedError, internal::llm_client::LLMResponse};
 
 use super::json_response::Json;
+use super::problem::{FieldError, Problem, INVALID_ARGUMENTS_TYPE};
 
 /// The concrete HTTP error type that we return to our users if something goes wrong.
 /// See https://docs.boundaryml.com/get-started/debugging/exception-handling for
@@ -19,6 +20,10 @@ use super::json_response::Json;
 pub enum BamlError {
     #[serde(rename_all = "snake_case")]
     InvalidArgument { message: String },
+    /// Arguments that do not match the function's parameters, one entry per
+    /// field. Returned as `application/problem+json` (RFC 7807).
+    #[serde(rename_all = "snake_case")]
+    InvalidArguments { errors: Vec<FieldError> },
     #[serde(rename_all = "snake_case")]
     ClientError { message: String },
     #[serde(rename_all = "snake_case")]
@@ -64,10 +69,16 @@ impl BamlError {
                     message: message.to_string(),
                     finish_reason: finish_reason.clone(),
                 },
+                ExposedError::PartialFailure { message, .. } => Self::ClientError {
+                    message: message.to_string(),
+                },
+                ExposedError::Cancelled => Self::InternalError {
+                    message: er.to_string(),
+                },
             }
         } else if let Some(er) = err.downcast_ref::<ScopeStack>() {
-            Self::InvalidArgument {
-                message: format!("{:?}", er),
+            Self::InvalidArguments {
+                errors: er.errors().iter().map(FieldError::from).collect(),
             }
         } else if let Some(er) = err.downcast_ref::<LLMResponse>() {
             match er {
@@ -84,6 +95,7 @@ impl BamlError {
                     | crate::internal::llm_client::ErrorCode::RateLimited
                     | crate::internal::llm_client::ErrorCode::ServerError
                     | crate::internal::llm_client::ErrorCode::ServiceUnavailable
+                    | crate::internal::llm_client::ErrorCode::Timeout
                     | crate::internal::llm_client::ErrorCode::UnsupportedResponse(_) => {
                         Self::ClientError {
                             message: format!("{:?}", err),
@@ -107,9 +119,22 @@ impl BamlError {
 
 impl IntoResponse for BamlError {
     fn into_response(self) -> Response {
+        if let BamlError::InvalidArguments { errors } = self {
+            return Problem {
+                r#type: INVALID_ARGUMENTS_TYPE,
+                title: "Invalid arguments",
+                status: StatusCode::BAD_REQUEST.as_u16(),
+                detail: "The arguments do not match the function's parameters".to_string(),
+                errors,
+            }
+            .into_response();
+        }
+
         (
             match &self {
-                BamlError::InvalidArgument { .. } => StatusCode::BAD_REQUEST,
+                BamlError::InvalidArgument { .. } | BamlError::InvalidArguments { .. } => {
+                    StatusCode::BAD_REQUEST
+                }
                 BamlError::ClientError { .. } => StatusCode::BAD_GATEWAY,
                 BamlError::FinishReasonError { .. } => StatusCode::INTERNAL_SERVER_ERROR, // ??? - FIXME
                 BamlError::ValidationFailure { .. } => StatusCode::INTERNAL_SERVER_ERROR, // ??? - FIXME
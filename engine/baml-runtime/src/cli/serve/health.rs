@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use axum::{
+    extract,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use super::{json_response::Json, Server};
+use crate::ReadinessOptions;
+
+/// The process is up and serving requests.
+pub(super) async fn healthz_handler() -> Response {
+    "ok\n".into_response()
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct ReadyzQuery {
+    /// Also check that every client's host is reachable.
+    #[serde(default)]
+    providers: bool,
+}
+
+/// `200` if the runtime can serve calls, `503` otherwise, with the report of
+/// every check as the body.
+pub(super) async fn readyz_handler(
+    server: Arc<Server>,
+    extract::Query(query): extract::Query<ReadyzQuery>,
+) -> Response {
+    let options = ReadinessOptions {
+        check_providers: query.providers,
+        ..Default::default()
+    };
+    let report = server.b.read().await.readiness(&options).await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
@@ -1,7 +1,11 @@
 mod arg_validation;
+mod auth;
+mod config;
 mod error;
+mod health;
 mod json_response;
 mod ping;
+mod problem;
 use error::BamlError;
 use indexmap::IndexMap;
 use internal_baml_codegen::GeneratorArgs;
@@ -20,7 +24,7 @@ use axum::{
     routing::{any, get, post},
 };
 use axum_extra::{
-    headers::{self, authorization::Basic, Authorization, Header},
+    headers::{self, authorization::Basic, Authorization, Header, HeaderMapExt},
     TypedHeader,
 };
 use baml_types::{BamlValue, GeneratorDefaultClientMode};
@@ -36,10 +40,17 @@ use crate::{
     client_registry::ClientRegistry,
     errors::ExposedError,
     internal::llm_client::{LLMResponse, ResponseBamlValue},
-    BamlRuntime, FunctionResult, RuntimeContextManager,
+    BamlRuntime, FunctionResult, MetricsRegistry, RuntimeContextManager, StreamDeltaEncoder,
+    StreamMode,
 };
 use internal_baml_codegen::openapi::OpenApiSchema;
 
+use auth::Authenticator;
+use config::ServeConfig;
+
+/// Requests are buffered to check their signature when auth is configured.
+const MAX_REQUEST_BODY_BYTES: usize = 32 * 1024 * 1024;
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct ServeArgs {
     #[arg(long, help = "path/to/baml_src", default_value = "./baml_src")]
@@ -59,6 +70,10 @@ pub struct ServeArgs {
 #[derive(Deserialize, Clone, Debug)]
 pub struct BamlOptions {
     pub client_registry: Option<ClientRegistry>,
+    /// Only used by `/stream`. In `delta` mode every event is a list of
+    /// `StreamPatch`es against the previous event instead of the full partial.
+    #[serde(default)]
+    pub stream_mode: StreamMode,
 }
 
 impl ServeArgs {
@@ -127,6 +142,8 @@ pub(super) struct Server {
     src_dir: PathBuf,
     port: u16,
     pub(super) b: Arc<RwLock<BamlRuntime>>,
+    /// From `baml.toml`. Without it, `BAML_PASSWORD` is enforced instead.
+    auth: Option<Authenticator>,
 }
 
 #[derive(Debug)]
@@ -158,9 +175,39 @@ impl Header for XBamlApiKey {
 }
 
 async fn status_handler(
+    server: Arc<Server>,
+    headers: axum::http::HeaderMap,
     basic_creds: Option<TypedHeader<Authorization<Basic>>>,
     baml_api_key: Option<TypedHeader<XBamlApiKey>>,
 ) -> Response {
+    if let Some(auth) = &server.auth {
+        return match auth.authenticate(&headers, b"").await {
+            Ok(principal) => (
+                StatusCode::OK,
+                Json(json!({
+                    "authz": {
+                        "enforcement": "active",
+                        "mode": auth.mode(),
+                        "outcome": "pass",
+                        "principal": principal.name,
+                    },
+                })),
+            ),
+            Err(e) => (
+                e.status,
+                Json(json!({
+                    "authz": {
+                        "enforcement": "active",
+                        "mode": auth.mode(),
+                        "outcome": "fail",
+                        "reason": e.message,
+                    },
+                })),
+            ),
+        }
+        .into_response();
+    }
+
     match Server::enforce_auth(basic_creds.as_deref(), baml_api_key.as_deref()) {
         AuthEnforcementMode::EnforceAndFail(e) => (
             StatusCode::FORBIDDEN,
@@ -208,14 +255,22 @@ impl Server {
                 port
             ))?;
 
+        let config = ServeConfig::load(&src_dir)?;
+        let auth = config
+            .auth
+            .map(Authenticator::new)
+            .transpose()
+            .context("Failed to set up auth from baml.toml")?;
+
+        let mut runtime = BamlRuntime::from_directory(&src_dir, std::env::vars().collect())?;
+        runtime.set_metrics(config.metrics.then(MetricsRegistry::new));
+
         Ok((
             Arc::new(Self {
                 src_dir: src_dir.clone(),
                 port,
-                b: Arc::new(RwLock::new(BamlRuntime::from_directory(
-                    &src_dir,
-                    std::env::vars().collect(),
-                )?)),
+                auth,
+                b: Arc::new(RwLock::new(runtime)),
             }),
             tcp_listener,
         ))
@@ -255,24 +310,69 @@ impl Server {
         AuthEnforcementMode::EnforceAndFail("No authorization metadata".to_owned())
     }
 
-    async fn auth_middleware(
-        basic_auth: Option<TypedHeader<Authorization<Basic>>>,
-        baml_api_key: Option<TypedHeader<XBamlApiKey>>,
-        request: extract::Request,
-        next: Next,
-    ) -> Response {
+    async fn auth_middleware(self: Arc<Self>, request: extract::Request, next: Next) -> Response {
         log::debug!("Handling request for {}", request.uri());
 
         // Skip auth checks for these endpoints.
-        if request.uri() == "/_debug/ping" || request.uri() == "/_debug/status" {
+        if matches!(
+            request.uri().path(),
+            "/_debug/ping" | "/_debug/status" | "/healthz" | "/readyz"
+        ) {
             return next.run(request).await;
         }
-        if let AuthEnforcementMode::EnforceAndFail(e) =
-            Server::enforce_auth(basic_auth.as_deref(), baml_api_key.as_deref())
-        {
-            return (StatusCode::FORBIDDEN, format!("{}\n", e.trim())).into_response();
+
+        let Some(auth) = &self.auth else {
+            let basic_auth = request.headers().typed_get::<Authorization<Basic>>();
+            let baml_api_key = request.headers().typed_get::<XBamlApiKey>();
+            if let AuthEnforcementMode::EnforceAndFail(e) =
+                Server::enforce_auth(basic_auth.as_ref(), baml_api_key.as_ref())
+            {
+                return (StatusCode::FORBIDDEN, format!("{}\n", e.trim())).into_response();
+            }
+            return next.run(request).await;
+        };
+
+        // The body is needed to check HMAC signatures, so buffer it and
+        // rebuild the request afterwards.
+        let (parts, body) = request.into_parts();
+        let body = match axum::body::to_bytes(body, MAX_REQUEST_BODY_BYTES).await {
+            Ok(body) => body,
+            Err(e) => return (StatusCode::BAD_REQUEST, format!("{e}\n")).into_response(),
+        };
+
+        let function = parts
+            .uri
+            .path()
+            .strip_prefix("/call/")
+            .or_else(|| parts.uri.path().strip_prefix("/stream/"));
+        let outcome = match auth.authenticate(&parts.headers, &body).await {
+            Ok(principal) => match function {
+                Some(function) => auth.authorize(&principal, function).map(|_| principal),
+                None => Ok(principal),
+            },
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(principal) => {
+                log::debug!("Authenticated {} as {}", parts.uri, principal.name);
+            }
+            Err(e) => {
+                let mut response = (e.status, format!("{}\n", e.message)).into_response();
+                if let Some(retry_after) = e.retry_after {
+                    if let Ok(value) =
+                        HeaderValue::try_from(retry_after.as_secs().max(1).to_string())
+                    {
+                        response
+                            .headers_mut()
+                            .insert(http::header::RETRY_AFTER, value);
+                    }
+                }
+                return response;
+            }
         }
 
+        let request = extract::Request::from_parts(parts, axum::body::Body::from(body));
+
         // log::info!(
         //     "incoming request triggering middleware, basic auth is {:?} and x-baml-api-key is {:?}",
         //     basic_auth,
@@ -287,7 +387,19 @@ impl Server {
         let app = axum::Router::new();
 
         let app = app.route("/_debug/ping", any(ping::ping_handler));
-        let app = app.route("/_debug/status", any(status_handler));
+        let app = app.route("/healthz", get(health::healthz_handler));
+        let s = self.clone();
+        let app = app.route(
+            "/readyz",
+            get(move |query| health::readyz_handler(s.clone(), query)),
+        );
+        let s = self.clone();
+        let app = app.route(
+            "/_debug/status",
+            any(move |headers, basic_creds, baml_api_key| {
+                status_handler(s.clone(), headers, basic_creds, baml_api_key)
+            }),
+        );
 
         let s = self.clone();
         let app = app.route(
@@ -303,15 +415,21 @@ impl Server {
         let s = self.clone();
         let app = app.route("/docs", get(move || s.clone().docs_handler()));
 
+        let s = self.clone();
+        let app = app.route("/metrics", get(move || s.clone().metrics_handler()));
+
         let s = self.clone();
         let app = app.route(
             "/openapi.json",
             get(move || s.clone().openapi_json_handler()),
         );
 
+        let s = self.clone();
         let service = axum::serve(
             tcp_listener,
-            app.layer(axum::middleware::from_fn(Server::auth_middleware)),
+            app.layer(axum::middleware::from_fn(move |request, next| {
+                s.clone().auth_middleware(request, next)
+            })),
         );
         // TODO: we do not handle this ourselves, because tokio's default
         // handling is pretty good on unix.
@@ -361,7 +479,7 @@ Tip: test that the server is up using `curl http://localhost:{}/_debug/ping`
 
         let locked = self.b.read().await;
         let (result, _trace_id) = locked
-            .call_function(b_fn, &args, &ctx_mgr, None, client_registry.as_ref())
+            .call_function(b_fn, &args, &ctx_mgr, None, client_registry.as_ref(), None)
             .await;
 
         match result {
@@ -430,7 +548,7 @@ Tip: test that the server is up using `curl http://localhost:{}/_debug/ping`
         self.baml_call(b_fn, b_args, b_options).await
     }
 
-    fn baml_stream(
+    async fn baml_stream(
         self: Arc<Self>,
         b_fn: String,
         b_args: serde_json::Value,
@@ -443,101 +561,108 @@ Tip: test that the server is up using `curl http://localhost:{}/_debug/ping`
             Err(e) => return e.into_response(),
         };
 
+        let stream_mode = b_options
+            .as_ref()
+            .map(|options| options.stream_mode)
+            .unwrap_or_default();
         let client_registry = b_options.and_then(|options| options.client_registry);
 
+        let ctx_mgr = RuntimeContextManager::new_from_env_vars(std::env::vars().collect(), None);
+
+        // Start the stream before responding, so that invalid arguments are
+        // reported with a status code instead of as an empty event stream.
+        let result_stream = self.b.read().await.stream_function(
+            b_fn,
+            &args,
+            &ctx_mgr,
+            None,
+            client_registry.as_ref(),
+            None,
+        );
+        let mut result_stream = match result_stream {
+            Ok(result_stream) => result_stream,
+            Err(e) => return BamlError::from_anyhow(e).into_response(),
+        };
+
         tokio::spawn(async move {
-            let ctx_mgr =
-                RuntimeContextManager::new_from_env_vars(std::env::vars().collect(), None);
-
-            let result_stream = self.b.read().await.stream_function(
-                b_fn,
-                &args,
-                &ctx_mgr,
-                None,
-                client_registry.as_ref(),
-            );
+            let (result, _trace_id) = result_stream
+                .run(
+                    Some(move |result| {
+                        // If the receiver is closed (either because it called close or it was dropped),
+                        // we can't really do anything
+                        match sender.send(result) {
+                            Ok(_) => (),
+                            Err(e) => {
+                                log::error!("Error sending result to receiver: {:?}", e);
+                            }
+                        }
+                    }),
+                    &ctx_mgr,
+                    None,
+                    None,
+                )
+                .await;
+
+            match result {
+                Ok(function_result) => match function_result.llm_response() {
+                    LLMResponse::Success(_) => {
+                        match function_result.result_with_constraints_content() {
+                            // Just because the LLM returned 2xx doesn't mean that it returned parse-able content!
+                            Ok(parsed) => {
+                                (StatusCode::OK, Json::<ResponseBamlValue>(parsed.clone()))
+                                    .into_response()
+                            }
 
-            match result_stream {
-                Ok(mut result_stream) => {
-                    let (result, _trace_id) = result_stream
-                        .run(
-                            Some(move |result| {
-                                // If the receiver is closed (either because it called close or it was dropped),
-                                // we can't really do anything
-                                match sender.send(result) {
-                                    Ok(_) => (),
-                                    Err(e) => {
-                                        log::error!("Error sending result to receiver: {:?}", e);
+                            Err(e) => {
+                                log::debug!("Error parsing content: {:?}", e);
+                                if let Some(ExposedError::ValidationError {
+                                    prompt,
+                                    raw_output: raw_response,
+                                    message,
+                                }) = e.downcast_ref::<ExposedError>()
+                                {
+                                    BamlError::ValidationFailure {
+                                        message: message.clone(),
+                                        prompt: prompt.clone(),
+                                        raw_output: raw_response.clone(),
                                     }
-                                }
-                            }),
-                            &ctx_mgr,
-                            None,
-                            None,
-                        )
-                        .await;
-
-                    match result {
-                        Ok(function_result) => match function_result.llm_response() {
-                            LLMResponse::Success(_) => {
-                                match function_result.result_with_constraints_content() {
-                                    // Just because the LLM returned 2xx doesn't mean that it returned parse-able content!
-                                    Ok(parsed) => {
-                                        (StatusCode::OK, Json::<ResponseBamlValue>(parsed.clone()))
-                                            .into_response()
-                                    }
-
-                                    Err(e) => {
-                                        log::debug!("Error parsing content: {:?}", e);
-                                        if let Some(ExposedError::ValidationError {
-                                            prompt,
-                                            raw_output: raw_response,
-                                            message,
-                                        }) = e.downcast_ref::<ExposedError>()
-                                        {
-                                            BamlError::ValidationFailure {
-                                                message: message.clone(),
-                                                prompt: prompt.clone(),
-                                                raw_output: raw_response.clone(),
-                                            }
-                                            .into_response()
-                                        } else {
-                                            BamlError::InternalError {
-                                                message: format!("Error parsing: {:?}", e),
-                                            }
-                                            .into_response()
-                                        }
+                                    .into_response()
+                                } else {
+                                    BamlError::InternalError {
+                                        message: format!("Error parsing: {:?}", e),
                                     }
+                                    .into_response()
                                 }
                             }
-                            LLMResponse::LLMFailure(failure) => {
-                                log::debug!("LLMResponse::LLMFailure: {:?}", failure);
-                                BamlError::ClientError {
-                                    message: format!("{:?}", failure.message),
-                                }
-                                .into_response()
-                            }
-                            LLMResponse::UserFailure(message) => BamlError::InvalidArgument {
-                                message: message.clone(),
-                            }
-                            .into_response(),
-                            LLMResponse::InternalFailure(message) => BamlError::InternalError {
-                                message: message.clone(),
-                            }
-                            .into_response(),
-                        },
-                        Err(e) => BamlError::from_anyhow(e).into_response(),
+                        }
                     }
-                }
-                Err(e) => BamlError::InternalError {
-                    message: format!("Error starting stream: {:?}", e),
-                }
-                .into_response(),
+                    LLMResponse::LLMFailure(failure) => {
+                        log::debug!("LLMResponse::LLMFailure: {:?}", failure);
+                        BamlError::ClientError {
+                            message: format!("{:?}", failure.message),
+                        }
+                        .into_response()
+                    }
+                    LLMResponse::UserFailure(message) => BamlError::InvalidArgument {
+                        message: message.clone(),
+                    }
+                    .into_response(),
+                    LLMResponse::InternalFailure(message) => BamlError::InternalError {
+                        message: message.clone(),
+                    }
+                    .into_response(),
+                },
+                Err(e) => BamlError::from_anyhow(e).into_response(),
             }
         });
 
         // TODO: streaming is broken. the above should return first.
-        let stream = Box::pin(EventStream { receiver }).map(|bv| Event::default().json_data(bv));
+        let delta = match stream_mode {
+            StreamMode::Cumulative => None,
+            StreamMode::Delta => Some(StreamDeltaEncoder::new()),
+        };
+        let stream =
+            Box::pin(EventStream { receiver, delta }).map(|data| Event::default().json_data(data));
 
         Sse::new(stream)
             .keep_alive(KeepAlive::default())
@@ -562,7 +687,7 @@ Tip: test that the server is up using `curl http://localhost:{}/_debug/ping`
                 }
             }
         }
-        self.baml_stream(path, body, b_options)
+        self.baml_stream(path, body, b_options).await
     }
 
     /// Serve an HTML page that loads swagger-ui from local static files.
@@ -612,6 +737,24 @@ Tip: test that the server is up using `curl http://localhost:{}/_debug/ping`
     }
 
     /// Render the openapi spec. This endpoint is used by the swagger ui.
+    async fn metrics_handler(self: Arc<Self>) -> Response {
+        match self.b.read().await.metrics() {
+            Some(metrics) => (
+                [(
+                    http::header::CONTENT_TYPE,
+                    "text/plain; version=0.0.4; charset=utf-8",
+                )],
+                metrics.render(),
+            )
+                .into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                "Metrics are disabled, set `metrics = true` under `[serve]` in baml.toml\n",
+            )
+                .into_response(),
+        }
+    }
+
     async fn openapi_json_handler(self: Arc<Self>) -> Result<String, BamlError> {
         let locked = self.b.read().await;
         let fake_generator = GeneratorArgs::new(
@@ -645,23 +788,39 @@ Tip: test that the server is up using `curl http://localhost:{}/_debug/ping`
 
 struct EventStream {
     receiver: tokio::sync::mpsc::UnboundedReceiver<FunctionResult>,
+    /// Set in delta mode.
+    delta: Option<StreamDeltaEncoder>,
 }
 
 impl Stream for EventStream {
-    type Item = BamlValue;
+    type Item = serde_json::Value;
 
     fn poll_next(
         mut self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        match self.receiver.poll_recv(cx) {
-            Poll::Ready(Some(item)) => match item.result_with_constraints_content() {
-                // TODO: not sure if this is the correct way to implement this.
-                Ok(parsed) => Poll::Ready(Some(parsed.into())),
-                Err(_) => Poll::Pending,
-            },
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
+        loop {
+            match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some(item)) => match item.result_with_constraints_content() {
+                    // TODO: not sure if this is the correct way to implement this.
+                    Ok(parsed) => {
+                        let value = json!(BamlValue::from(parsed));
+                        match self.delta.as_mut() {
+                            None => return Poll::Ready(Some(value)),
+                            Some(delta) => {
+                                let patches = delta.next(value);
+                                // Nothing changed, e.g. only whitespace was streamed.
+                                if !patches.is_empty() {
+                                    return Poll::Ready(Some(json!(patches)));
+                                }
+                            }
+                        }
+                    }
+                    Err(_) => continue,
+                },
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
         }
     }
 }
@@ -0,0 +1,73 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use internal_baml_core::ir::scope_diagnostics::ScopeError;
+use serde::Serialize;
+
+pub(super) const INVALID_ARGUMENTS_TYPE: &str =
+    "https://docs.boundaryml.com/ref/baml-cli/serve#invalid-arguments";
+
+/// An RFC 7807 problem details response.
+#[derive(Debug, Serialize)]
+pub(super) struct Problem {
+    #[serde(rename = "type")]
+    pub r#type: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Extension member listing every invalid field.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    /// A JSON pointer into the request body, e.g. `/resume/experience/0`.
+    pub pointer: String,
+    pub detail: String,
+}
+
+impl From<&ScopeError> for FieldError {
+    fn from(error: &ScopeError) -> Self {
+        FieldError {
+            pointer: error
+                .path
+                .iter()
+                .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+                .collect(),
+            detail: error.message.clone(),
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::BAD_REQUEST);
+        let mut body = serde_json::to_vec_pretty(&self).unwrap_or_default();
+        body.push(b'\n');
+        (
+            status,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/problem+json"),
+            )],
+            body,
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pointers_are_escaped() {
+        let error = ScopeError {
+            path: vec!["resume".into(), "a/b".into(), "0".into()],
+            message: "Expected type string, got `1`".into(),
+        };
+        assert_eq!(FieldError::from(&error).pointer, "/resume/a~1b/0");
+    }
+}
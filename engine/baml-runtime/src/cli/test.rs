@@ -0,0 +1,711 @@
+use anyhow::Result;
+use baml_types::BamlValue;
+use colored::Colorize;
+use indexmap::IndexSet;
+use internal_baml_core::{
+    internal_baml_diagnostics::Span,
+    ir::{repr::IntermediateRepr, FieldType, IRHelper},
+};
+use notify_debouncer_full::{new_debouncer, notify::*, DebounceEventResult};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::dev::is_baml_src_file;
+use crate::{
+    coverage::SchemaCoverage, BamlRuntime, FunctionResult, InternalRuntimeInterface,
+    TestFailReason, TestStatus, UsageTotals,
+};
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct TestArgs {
+    #[arg(long, help = "path/to/baml_src", default_value = "./baml_src")]
+    pub from: PathBuf,
+    #[arg(long, help = "only run tests of functions whose name contains this")]
+    function: Option<String>,
+    #[arg(long, help = "only run tests whose name contains this")]
+    test: Option<String>,
+    #[arg(
+        long,
+        help = "watch baml_src and rerun the tests affected by each change",
+        default_value = "false"
+    )]
+    interactive: bool,
+    #[arg(
+        long,
+        help = "stop once the tests have cost this much, estimated from list prices"
+    )]
+    max_cost_usd: Option<f64>,
+    #[arg(long, help = "stop once the tests have used this many tokens")]
+    max_tokens: Option<u64>,
+    #[arg(
+        long,
+        help = "report which optional fields, union branches and enum values the parsed outputs used",
+        default_value = "false"
+    )]
+    coverage: bool,
+}
+
+/// A test, as `(function, test)`.
+type TestId = (String, String);
+
+/// Case-insensitive substring filters on function and test names.
+#[derive(Clone, Debug, Default)]
+struct TestFilter {
+    function: Option<String>,
+    test: Option<String>,
+}
+
+impl TestFilter {
+    fn matches(&self, (function, test): &TestId) -> bool {
+        let contains = |pattern: &Option<String>, name: &str| {
+            pattern
+                .as_ref()
+                .map_or(true, |p| name.to_lowercase().contains(&p.to_lowercase()))
+        };
+        contains(&self.function, function) && contains(&self.test, test)
+    }
+}
+
+impl std::fmt::Display for TestFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.function, &self.test) {
+            (None, None) => write!(f, "none"),
+            (Some(function), None) => write!(f, "function ~ {function:?}"),
+            (None, Some(test)) => write!(f, "test ~ {test:?}"),
+            (Some(function), Some(test)) => {
+                write!(f, "function ~ {function:?}, test ~ {test:?}")
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct TestSummary {
+    passed: usize,
+    failed: usize,
+    needs_human_eval: usize,
+    /// Tests not run because the budget ran out.
+    skipped: usize,
+    usage: UsageTotals,
+    budget_exceeded: Option<String>,
+    coverage: SchemaCoverage,
+}
+
+impl TestSummary {
+    fn total(&self) -> usize {
+        self.passed + self.failed + self.needs_human_eval + self.skipped
+    }
+}
+
+/// Limits on the LLM usage of one run of tests.
+#[derive(Clone, Copy, Debug, Default)]
+struct Budget {
+    max_cost_usd: Option<f64>,
+    max_tokens: Option<u64>,
+}
+
+impl Budget {
+    /// Why `usage` exceeds the budget, if it does.
+    fn exceeded(&self, usage: &UsageTotals) -> Option<String> {
+        if let Some(max) = self.max_tokens {
+            if usage.total_tokens() > max {
+                return Some(format!(
+                    "used {} tokens, more than --max-tokens {max}",
+                    usage.total_tokens()
+                ));
+            }
+        }
+        if let Some(max) = self.max_cost_usd {
+            if usage.cost_usd > max {
+                return Some(format!(
+                    "spent ${:.4}, more than --max-cost-usd {max}",
+                    usage.cost_usd
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl TestArgs {
+    pub fn run(&self) -> Result<()> {
+        let runtime = BamlRuntime::from_directory(&self.from, std::env::vars().collect())?;
+        let filter = TestFilter {
+            function: self.function.clone(),
+            test: self.test.clone(),
+        };
+
+        if self.interactive {
+            return self.watch(runtime, filter);
+        }
+
+        let tests = select_tests(&runtime, &filter, None);
+        if tests.is_empty() {
+            anyhow::bail!("No tests match the filters ({filter})");
+        }
+        let summary = run_tests(&runtime, &tests, self.budget(), self.coverage);
+        if let Some(reason) = summary.budget_exceeded {
+            anyhow::bail!(
+                "Stopped after {} of {} tests, budget exceeded: {reason}",
+                summary.total() - summary.skipped,
+                summary.total()
+            );
+        }
+        if summary.failed > 0 {
+            anyhow::bail!("{} of {} tests failed", summary.failed, summary.total());
+        }
+        Ok(())
+    }
+
+    fn budget(&self) -> Budget {
+        Budget {
+            max_cost_usd: self.max_cost_usd,
+            max_tokens: self.max_tokens,
+        }
+    }
+
+    fn watch(&self, mut runtime: BamlRuntime, mut filter: TestFilter) -> Result<()> {
+        enum WatchEvent {
+            Files(DebounceEventResult),
+            Command(String),
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let files_tx = tx.clone();
+        let mut debouncer = new_debouncer(Duration::from_millis(200), None, move |result| {
+            let _ = files_tx.send(WatchEvent::Files(result));
+        })?;
+        debouncer
+            .watcher()
+            .watch(self.from.as_path(), RecursiveMode::Recursive)?;
+
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if tx.send(WatchEvent::Command(line)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut last = select_tests(&runtime, &filter, None);
+        run_tests(&runtime, &last, self.budget(), self.coverage);
+        print_menu(&filter);
+
+        for event in rx {
+            let tests = match event {
+                WatchEvent::Command(line) => {
+                    let line = line.trim();
+                    let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+                    let arg = Some(arg.trim().to_string()).filter(|a| !a.is_empty());
+                    match command {
+                        "" => last
+                            .iter()
+                            .filter(|id| filter.matches(id))
+                            .cloned()
+                            .collect(),
+                        "a" => select_tests(&runtime, &filter, None),
+                        "f" => {
+                            filter.function = arg;
+                            select_tests(&runtime, &filter, None)
+                        }
+                        "t" => {
+                            filter.test = arg;
+                            select_tests(&runtime, &filter, None)
+                        }
+                        "c" => {
+                            filter = TestFilter::default();
+                            select_tests(&runtime, &filter, None)
+                        }
+                        "q" => return Ok(()),
+                        _ => {
+                            print_menu(&filter);
+                            continue;
+                        }
+                    }
+                }
+                WatchEvent::Files(Ok(events)) => {
+                    let changed = events
+                        .into_iter()
+                        .flat_map(|e| e.event.paths)
+                        .filter(|p| is_baml_src_file(p))
+                        .collect::<IndexSet<_>>();
+                    if changed.is_empty() {
+                        continue;
+                    }
+                    let new_runtime =
+                        match BamlRuntime::from_directory(&self.from, std::env::vars().collect()) {
+                            Ok(new_runtime) => new_runtime,
+                            Err(e) => {
+                                log::warn!("Failed to reload runtime: {:?}", e);
+                                continue;
+                            }
+                        };
+                    // Dependencies that were just removed count too, e.g. a
+                    // function that moved to another file.
+                    let mut affected = DependencyIndex::new(runtime.inner.ir()).affected(&changed);
+                    affected
+                        .extend(DependencyIndex::new(new_runtime.inner.ir()).affected(&changed));
+                    runtime = new_runtime;
+                    select_tests(&runtime, &filter, Some(&affected))
+                }
+                WatchEvent::Files(Err(errors)) => {
+                    log::warn!(
+                        "Encountered errors while watching {}: {:?}",
+                        self.from.display(),
+                        errors
+                    );
+                    continue;
+                }
+            };
+
+            if tests.is_empty() {
+                println!("No tests affected (filters: {filter})");
+            } else {
+                run_tests(&runtime, &tests, self.budget(), self.coverage);
+                last = tests;
+            }
+            print_menu(&filter);
+        }
+
+        Ok(())
+    }
+}
+
+/// Tests matching `filter`, restricted to those of the `affected` functions
+/// if given.
+fn select_tests(
+    runtime: &BamlRuntime,
+    filter: &TestFilter,
+    affected: Option<&Affected>,
+) -> Vec<TestId> {
+    runtime
+        .inner
+        .ir()
+        .walk_tests()
+        .map(|t| (t.function().name().to_string(), t.name()))
+        .filter(|id| filter.matches(id))
+        .filter(|(function, _)| affected.map_or(true, |a| a.contains(function)))
+        .collect()
+}
+
+/// Runs `tests` in order, each row of a dataset as its own test, stopping
+/// early once they exceed `budget`. Prints the schema coverage of their
+/// outputs if `coverage` is set.
+fn run_tests(
+    runtime: &BamlRuntime,
+    tests: &[TestId],
+    budget: Budget,
+    coverage: bool,
+) -> TestSummary {
+    let ctx = runtime.create_ctx_manager(BamlValue::String("baml-cli".to_string()), None);
+    let mut summary = TestSummary::default();
+    let start = Instant::now();
+
+    let mut cases = Vec::new();
+    for (function, test) in tests {
+        match runtime
+            .async_runtime
+            .block_on(runtime.test_dataset(function, test, &ctx))
+        {
+            Ok(None) => cases.push((function, test, None)),
+            Ok(Some(rows)) => cases.extend(rows.into_iter().map(|row| (function, test, Some(row)))),
+            Err(e) => {
+                summary.failed += 1;
+                println!(" {} {function}::{test}", "FAIL".red().bold());
+                println!("      {:?}", e);
+            }
+        }
+    }
+
+    for (i, (function, test, row)) in cases.iter().enumerate() {
+        let test_start = Instant::now();
+        let (result, _) = match row {
+            Some(row) => runtime.async_runtime.block_on(runtime.run_test_row(
+                function,
+                test,
+                row,
+                &ctx,
+                None::<fn(FunctionResult)>,
+                None,
+            )),
+            None => runtime.async_runtime.block_on(runtime.run_test(
+                function,
+                test,
+                &ctx,
+                None::<fn(FunctionResult)>,
+                None,
+            )),
+        };
+        let elapsed = format!("({}ms)", test_start.elapsed().as_millis()).dimmed();
+        let name = match row {
+            Some(row) => format!("{function}::{test}[{}]", row.name),
+            None => format!("{function}::{test}"),
+        };
+        if let Ok(response) = &result {
+            summary.usage.record(&response.function_response);
+            if let Ok(value) = response.function_response.result_with_constraints_content() {
+                if let Err(e) =
+                    summary
+                        .coverage
+                        .record(runtime.inner.ir(), function, &BamlValue::from(value))
+                {
+                    log::debug!("Failed to record the schema coverage of {name}: {e:#}");
+                }
+            }
+        }
+        match result {
+            Ok(response) => match response.status() {
+                TestStatus::Pass => {
+                    summary.passed += 1;
+                    println!(" {} {name} {elapsed}", "PASS".green().bold());
+                }
+                TestStatus::NeedsHumanEval(checks) => {
+                    summary.needs_human_eval += 1;
+                    println!(" {} {name} {elapsed}", "EVAL".yellow().bold());
+                    println!("      checks need human evaluation: {}", checks.join(", "));
+                }
+                TestStatus::Fail(TestFailReason::TestExpectationFailure(diff)) => {
+                    summary.failed += 1;
+                    println!(" {} {name} {elapsed}", "FAIL".red().bold());
+                    println!("      {}", diff.to_string().replace('\n', "\n      "));
+                }
+                TestStatus::Fail(reason) => {
+                    summary.failed += 1;
+                    println!(" {} {name} {elapsed}", "FAIL".red().bold());
+                    println!("      {:?}", reason);
+                }
+            },
+            Err(e) => {
+                summary.failed += 1;
+                println!(" {} {name} {elapsed}", "FAIL".red().bold());
+                println!("      {:?}", e);
+            }
+        }
+
+        if let Some(reason) = budget.exceeded(&summary.usage) {
+            summary.skipped = cases.len() - i - 1;
+            println!(
+                " {} budget exceeded, {reason}; skipping {} remaining tests",
+                "STOP".red().bold(),
+                summary.skipped
+            );
+            summary.budget_exceeded = Some(reason);
+            break;
+        }
+    }
+
+    println!(
+        "\nTests: {} passed, {} failed, {} need human evaluation, {} skipped, {} total ({:.1}s)",
+        summary.passed,
+        summary.failed,
+        summary.needs_human_eval,
+        summary.skipped,
+        summary.total(),
+        start.elapsed().as_secs_f64()
+    );
+    let usage = &summary.usage;
+    println!(
+        "Usage: {} input tokens ({} cached), {} output tokens, ~${:.4}",
+        usage.input_tokens, usage.cached_input_tokens, usage.output_tokens, usage.cost_usd
+    );
+    if !usage.unpriced_models.is_empty() {
+        println!(
+            "       cost excludes models without a known price: {}",
+            usage
+                .unpriced_models
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if coverage {
+        println!("\nSchema coverage:");
+        for line in summary.coverage.to_string().lines() {
+            println!("  {line}");
+        }
+        for gap in summary.coverage.gaps() {
+            println!("  {} {gap}", "GAP".yellow().bold());
+        }
+    }
+    summary
+}
+
+fn print_menu(filter: &TestFilter) {
+    println!(
+        r#"
+Watch usage (filters: {filter})
+ › a          run all tests
+ › f <name>   filter by function name, or clear it without a name
+ › t <name>   filter by test name, or clear it without a name
+ › c          clear filters
+ › Enter      rerun the last tests
+ › q          quit"#
+    );
+    let _ = std::io::stdout().flush();
+}
+
+/// Functions whose tests must rerun.
+#[derive(Debug, PartialEq)]
+enum Affected {
+    All,
+    Functions(HashSet<String>),
+}
+
+impl Affected {
+    fn contains(&self, function: &str) -> bool {
+        match self {
+            Affected::All => true,
+            Affected::Functions(functions) => functions.contains(function),
+        }
+    }
+
+    fn extend(&mut self, other: Affected) {
+        match (&mut *self, other) {
+            (Affected::All, _) => {}
+            (_, Affected::All) => *self = Affected::All,
+            (Affected::Functions(a), Affected::Functions(b)) => a.extend(b),
+        }
+    }
+}
+
+/// Maps each file in `baml_src` to the functions whose tests depend on it:
+/// the function, its tests, its client and retry policy, and the classes
+/// and enums reachable from its signature.
+struct DependencyIndex {
+    dependents: HashMap<PathBuf, HashSet<String>>,
+    /// Files whose dependents can't be tracked, like template strings, which
+    /// prompts may call by name.
+    untracked: HashSet<PathBuf>,
+}
+
+impl DependencyIndex {
+    fn new(ir: &IntermediateRepr) -> Self {
+        let mut index = Self {
+            dependents: HashMap::new(),
+            untracked: HashSet::new(),
+        };
+
+        for template_string in ir.walk_template_strings() {
+            if let Some(span) = template_string.span() {
+                index
+                    .untracked
+                    .insert(normalize(Path::new(&span.file.path())));
+            }
+        }
+
+        for function in ir.walk_functions() {
+            let mut spans = vec![function.span().cloned()];
+            spans.extend(function.walk_tests().map(|t| t.span().cloned()));
+
+            if let Some(client) = function
+                .client_name()
+                .and_then(|name| ir.find_client(&name).ok())
+            {
+                spans.push(client.span().cloned());
+                if let Some(policy) = client
+                    .retry_policy()
+                    .as_ref()
+                    .and_then(|name| ir.find_retry_policy(name).ok())
+                {
+                    spans.push(policy.span().cloned());
+                }
+            }
+
+            let mut seen = HashSet::new();
+            let types = function
+                .inputs()
+                .iter()
+                .map(|(_, t)| t)
+                .chain(std::iter::once(function.output()));
+            for field_type in types {
+                collect_type_spans(ir, field_type, &mut seen, &mut spans);
+            }
+
+            for span in spans.into_iter().flatten() {
+                index
+                    .dependents
+                    .entry(normalize(Path::new(&span.file.path())))
+                    .or_default()
+                    .insert(function.name().to_string());
+            }
+        }
+
+        index
+    }
+
+    fn affected<'a>(&self, changed: impl IntoIterator<Item = &'a PathBuf>) -> Affected {
+        let mut functions = HashSet::new();
+        for path in changed {
+            let path = normalize(path);
+            if self.untracked.contains(&path)
+                || path.extension().and_then(|ext| ext.to_str()) != Some("baml")
+            {
+                return Affected::All;
+            }
+            if let Some(dependents) = self.dependents.get(&path) {
+                functions.extend(dependents.iter().cloned());
+            }
+        }
+        Affected::Functions(functions)
+    }
+}
+
+fn collect_type_spans(
+    ir: &IntermediateRepr,
+    field_type: &FieldType,
+    seen: &mut HashSet<String>,
+    spans: &mut Vec<Option<Span>>,
+) {
+    match field_type {
+        FieldType::Class(name) => {
+            if !seen.insert(name.clone()) {
+                return;
+            }
+            if let Ok(class) = ir.find_class(name) {
+                spans.push(class.span().cloned());
+                for field in class.walk_fields() {
+                    collect_type_spans(ir, field.r#type(), seen, spans);
+                }
+            }
+        }
+        FieldType::Enum(name) => {
+            if seen.insert(name.clone()) {
+                if let Ok(e) = ir.find_enum(name) {
+                    spans.push(e.span().cloned());
+                }
+            }
+        }
+        FieldType::List(inner) | FieldType::Optional(inner) => {
+            collect_type_spans(ir, inner, seen, spans)
+        }
+        FieldType::Constrained { base, .. } => collect_type_spans(ir, base, seen, spans),
+        FieldType::Map(key, value) => {
+            collect_type_spans(ir, key, seen, spans);
+            collect_type_spans(ir, value, seen, spans);
+        }
+        FieldType::Union(items) | FieldType::Tuple(items) => {
+            for item in items {
+                collect_type_spans(ir, item, seen, spans);
+            }
+        }
+        FieldType::Primitive(_) | FieldType::Literal(_) => {}
+    }
+}
+
+/// Watcher events carry absolute paths, while spans carry the path the file
+/// was loaded from.
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_exceeded() {
+        let usage = UsageTotals {
+            input_tokens: 900,
+            output_tokens: 200,
+            cost_usd: 0.02,
+            ..Default::default()
+        };
+        assert_eq!(Budget::default().exceeded(&usage), None);
+        let budget = Budget {
+            max_cost_usd: Some(0.05),
+            max_tokens: Some(1000),
+        };
+        assert_eq!(
+            budget.exceeded(&usage).as_deref(),
+            Some("used 1100 tokens, more than --max-tokens 1000")
+        );
+        let budget = Budget {
+            max_cost_usd: Some(0.01),
+            max_tokens: None,
+        };
+        assert_eq!(
+            budget.exceeded(&usage).as_deref(),
+            Some("spent $0.0200, more than --max-cost-usd 0.01")
+        );
+    }
+
+    #[test]
+    fn indexes_functions_by_file() {
+        let files = HashMap::from([
+            (
+                "clients.baml",
+                r##"
+                client<llm> GPT4 {
+                  provider openai
+                  options {
+                    model gpt-4o
+                    api_key "sk-test"
+                  }
+                }
+                "##,
+            ),
+            (
+                "resume.baml",
+                r##"
+                class Resume {
+                  name string
+                  role Role
+                }
+
+                function ExtractResume(text: string) -> Resume {
+                  client GPT4
+                  prompt #"{{ text }}"#
+                }
+                "##,
+            ),
+            (
+                "types.baml",
+                r##"
+                enum Role {
+                  Engineer
+                  Manager
+                }
+                "##,
+            ),
+            (
+                "echo.baml",
+                r##"
+                function Echo(input: string) -> string {
+                  client "openai/gpt-4o"
+                  prompt #"{{ input }}"#
+                }
+
+                test Hello {
+                  functions [Echo]
+                  args {
+                    input "hello"
+                  }
+                }
+                "##,
+            ),
+        ]);
+        let runtime =
+            BamlRuntime::from_file_content("baml_src", &files, HashMap::<&str, &str>::new())
+                .unwrap();
+        let index = DependencyIndex::new(runtime.inner.ir());
+        let affected = |path: &str| index.affected([&PathBuf::from(path)]);
+
+        let resume = Affected::Functions(HashSet::from(["ExtractResume".to_string()]));
+        assert_eq!(affected("types.baml"), resume);
+        assert_eq!(affected("clients.baml"), resume);
+        assert_eq!(
+            affected("echo.baml"),
+            Affected::Functions(HashSet::from(["Echo".to_string()]))
+        );
+        assert_eq!(
+            affected("generators.baml"),
+            Affected::Functions(HashSet::new())
+        );
+        assert_eq!(affected("clients.json"), Affected::All);
+    }
+}
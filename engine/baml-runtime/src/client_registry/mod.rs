@@ -2,7 +2,7 @@
 use anyhow::{Context, Result};
 pub use internal_llm_client::ClientProvider;
 use internal_llm_client::{ClientSpec, PropertyHandler, UnresolvedClientProperty};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use baml_types::{BamlMap, BamlValue};
@@ -41,17 +41,24 @@ impl ClientProperty {
         }
     }
 
-    pub fn from_shorthand(provider: &ClientProvider, model: &str) -> Self {
+    pub fn from_shorthand(
+        provider: &ClientProvider,
+        model: &str,
+        options: &BamlMap<String, String>,
+    ) -> Self {
         Self {
-            name: format!("{}/{}", provider, model),
+            name: ClientSpec::Shorthand(provider.clone(), model.to_string(), options.clone())
+                .as_str(),
             provider: provider.clone(),
             retry_policy: None,
-            options: vec![("model".to_string(), BamlValue::String(model.to_string()))]
-                .into_iter()
-                .collect(),
+            options: ClientSpec::shorthand_options(model, options),
         }
     }
 
+    pub fn options(&self) -> &BamlMap<String, BamlValue> {
+        &self.options
+    }
+
     pub fn unresolved_options(&self) -> Result<UnresolvedClientProperty<()>> {
         let property = PropertyHandler::new(
             self.options
@@ -98,10 +105,65 @@ impl ClientRegistry {
         self.clients.insert(client.name.clone(), client);
     }
 
+    /// Removes the client named `name`, returning it if it was registered.
+    /// The primary client is left as is, since it may name a client in
+    /// `baml_src`.
+    pub fn remove_client(&mut self, name: &str) -> Option<ClientProperty> {
+        self.clients.remove(name)
+    }
+
+    pub fn get_client(&self, name: &str) -> Option<&ClientProperty> {
+        self.clients.get(name)
+    }
+
+    /// The registered clients, in no particular order.
+    pub fn iter_clients(&self) -> impl Iterator<Item = &ClientProperty> {
+        self.clients.values()
+    }
+
     pub fn set_primary(&mut self, primary: String) {
         self.primary = Some(primary);
     }
 
+    /// A serializable copy of the registry, e.g. to compare against the next
+    /// version of a hot-reloaded provider config with
+    /// [`ClientRegistrySnapshot::diff`].
+    pub fn snapshot(&self) -> ClientRegistrySnapshot {
+        ClientRegistrySnapshot {
+            clients: self
+                .clients
+                .iter()
+                .map(|(name, client)| {
+                    (
+                        name.clone(),
+                        ClientSnapshot {
+                            provider: client.provider.to_string(),
+                            retry_policy: client.retry_policy.clone(),
+                            options: client.options.clone(),
+                        },
+                    )
+                })
+                .collect(),
+            primary: self.primary.clone(),
+        }
+    }
+
+    /// A registry that makes every function use `client` instead of the one
+    /// in its definition. `client` is either the name of a client in
+    /// `baml_src` or a shorthand like `openai/gpt-4o-mini`.
+    pub fn with_primary_override(client: &str) -> Result<Self> {
+        let mut registry = Self::new();
+        match ClientSpec::new_from_id(client)? {
+            ClientSpec::Named(name) => registry.set_primary(name),
+            ClientSpec::Shorthand(provider, model, options) => {
+                let client = ClientProperty::from_shorthand(&provider, &model, &options);
+                registry.set_primary(client.name.clone());
+                registry.add_client(client);
+            }
+        }
+        Ok(registry)
+    }
+
     pub fn to_clients(
         &self,
         ctx: &RuntimeContext,
@@ -117,6 +179,64 @@ impl ClientRegistry {
     }
 }
 
+/// The state of a [`ClientRegistry`] at the time [`ClientRegistry::snapshot`]
+/// was called. Clients are sorted by name.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClientRegistrySnapshot {
+    pub clients: BTreeMap<String, ClientSnapshot>,
+    pub primary: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClientSnapshot {
+    pub provider: String,
+    pub retry_policy: Option<String>,
+    pub options: BamlMap<String, BamlValue>,
+}
+
+/// What changed between two snapshots of a registry, by client name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ClientRegistryDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Clients whose provider, retry policy or options changed.
+    pub changed: Vec<String>,
+    pub primary_changed: bool,
+}
+
+impl ClientRegistryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.primary_changed
+    }
+}
+
+impl ClientRegistrySnapshot {
+    /// The changes that turn `self` into `newer`.
+    pub fn diff(&self, newer: &ClientRegistrySnapshot) -> ClientRegistryDiff {
+        let mut diff = ClientRegistryDiff {
+            primary_changed: self.primary != newer.primary,
+            ..Default::default()
+        };
+        for (name, client) in &newer.clients {
+            match self.clients.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(old) if old != client => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .clients
+            .keys()
+            .filter(|name| !newer.clients.contains_key(*name))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
 fn deserialize_clients<'de, D>(deserializer: D) -> Result<HashMap<String, ClientProperty>, D::Error>
 where
     D: Deserializer<'de>,
@@ -126,3 +246,94 @@ where
         .map(|client: ClientProperty| (client.name.clone(), client))
         .collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn primary_override() {
+        let registry = ClientRegistry::with_primary_override("GPT4").unwrap();
+        assert_eq!(registry.primary.as_deref(), Some("GPT4"));
+        assert!(registry.clients.is_empty());
+
+        let registry = ClientRegistry::with_primary_override("openai/gpt-4o-mini").unwrap();
+        assert_eq!(registry.primary.as_deref(), Some("openai/gpt-4o-mini"));
+        assert!(registry.clients.contains_key("openai/gpt-4o-mini"));
+
+        assert!(ClientRegistry::with_primary_override("not-a-provider/model").is_err());
+    }
+
+    #[test]
+    fn remove_and_snapshot_diff() {
+        let client = |name: &str, model: &str| {
+            ClientProperty::new(
+                name.to_string(),
+                ClientProvider::from_str("openai").unwrap(),
+                None,
+                BamlMap::from_iter([("model".to_string(), BamlValue::String(model.into()))]),
+            )
+        };
+
+        let mut registry = ClientRegistry::new();
+        registry.add_client(client("Fast", "gpt-4o-mini"));
+        registry.add_client(client("Smart", "gpt-4o"));
+        registry.set_primary("Fast".to_string());
+        let before = registry.snapshot();
+        assert!(before.diff(&registry.snapshot()).is_empty());
+        assert_eq!(
+            serde_json::to_value(&before).unwrap()["clients"]["Fast"],
+            serde_json::json!({
+                "provider": "openai",
+                "retry_policy": null,
+                "options": { "model": "gpt-4o-mini" },
+            })
+        );
+
+        assert_eq!(registry.remove_client("Fast").unwrap().name, "Fast");
+        assert!(registry.remove_client("Fast").is_none());
+        assert!(registry.get_client("Fast").is_none());
+        registry.add_client(client("Smart", "o1"));
+        registry.add_client(client("Cheap", "gpt-3.5-turbo"));
+        registry.set_primary("Smart".to_string());
+        assert_eq!(registry.iter_clients().count(), 2);
+
+        assert_eq!(
+            before.diff(&registry.snapshot()),
+            ClientRegistryDiff {
+                added: vec!["Cheap".to_string()],
+                removed: vec!["Fast".to_string()],
+                changed: vec!["Smart".to_string()],
+                primary_changed: true,
+            }
+        );
+    }
+
+    #[test]
+    fn shorthand_with_inline_options() {
+        let spec = "openai/gpt-4o?temperature=0.5&max_tokens=256&logprobs=false&user=greg";
+        let registry = ClientRegistry::with_primary_override(spec).unwrap();
+        assert_eq!(registry.primary.as_deref(), Some(spec));
+        let client = &registry.clients[spec];
+        assert_eq!(
+            client.options.iter().collect::<Vec<_>>(),
+            vec![
+                (&"model".to_string(), &BamlValue::String("gpt-4o".into())),
+                (&"temperature".to_string(), &BamlValue::Float(0.5)),
+                (&"max_tokens".to_string(), &BamlValue::Int(256)),
+                (&"logprobs".to_string(), &BamlValue::Bool(false)),
+                (&"user".to_string(), &BamlValue::String("greg".into())),
+            ]
+        );
+
+        for bad in [
+            "openai/gpt-4o?temperature",
+            "openai/gpt-4o?model=gpt-4",
+            "openai/gpt-4o?n=1&n=2",
+        ] {
+            assert!(ClientSpec::new_from_id(bad).is_err(), "{bad}");
+        }
+    }
+}
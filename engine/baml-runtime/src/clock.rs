@@ -0,0 +1,131 @@
+//! The time a runtime's calls see: when requests start, how long they take,
+//! how long retries and hedges wait, and when access tokens expire.
+//!
+//! Calls use [`SystemClock`] unless a runtime is given another clock with
+//! [`crate::BamlRuntime::set_time_provider`], e.g. a [`MockClock`] in tests
+//! so that retries don't actually wait.
+
+use std::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use web_time::{Duration, Instant, SystemTime};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type Sleep = Pin<Box<dyn Future<Output = ()>>>;
+
+pub trait TimeProvider: Debug + Send + Sync {
+    /// The wall-clock time.
+    fn now(&self) -> SystemTime;
+
+    /// A monotonic time, to measure durations with.
+    fn instant(&self) -> Instant;
+
+    /// Resolves once `duration` passed.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// The time of the system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl TimeProvider for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// A clock that only moves when told to. Sleeping moves it forward by the
+/// time slept, right away.
+#[derive(Debug)]
+pub struct MockClock {
+    start: SystemTime,
+    instant_start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// A clock that reads `start` until it's moved.
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            start,
+            instant_start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    /// How far the clock moved since it was made.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl TimeProvider for MockClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.instant_start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+/// Measures the time since it started on a [`TimeProvider`].
+#[derive(Debug, Clone)]
+pub struct Stopwatch {
+    clock: Arc<dyn TimeProvider>,
+    start: Instant,
+}
+
+impl Stopwatch {
+    pub fn start(clock: &Arc<dyn TimeProvider>) -> Self {
+        Self {
+            clock: clock.clone(),
+            start: clock.instant(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.instant().saturating_duration_since(self.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_moves_when_sleeping() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock: Arc<dyn TimeProvider> = Arc::new(MockClock::new(start));
+        let stopwatch = Stopwatch::start(&clock);
+
+        futures::executor::block_on(clock.sleep(Duration::from_millis(250)));
+        futures::executor::block_on(clock.sleep(Duration::from_millis(750)));
+
+        assert_eq!(stopwatch.elapsed(), Duration::from_secs(1));
+        assert_eq!(clock.now(), start + Duration::from_secs(1));
+    }
+}
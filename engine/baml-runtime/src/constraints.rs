@@ -1,5 +1,11 @@
-use baml_types::{BamlValue, BamlValueWithMeta, Constraint, ConstraintLevel, ResponseCheck};
-use internal_baml_core::ir::jinja_helpers::{evaluate_predicate, render_expression};
+use baml_types::{
+    BamlValue, BamlValueWithMeta, Constraint, ConstraintLevel, FieldType, ResponseCheck,
+};
+use internal_baml_core::ir::{
+    jinja_helpers::{evaluate_predicate, render_expression},
+    repr::IntermediateRepr,
+    IRHelper,
+};
 use jsonish::BamlValueWithFlags;
 
 use anyhow::Result;
@@ -7,7 +13,8 @@ use indexmap::IndexMap;
 use minijinja;
 use std::{collections::HashMap, fmt};
 
-use crate::internal::llm_client::LLMCompleteResponse;
+use crate::internal::{llm_client::LLMCompleteResponse, prompt_renderer::render_output_format};
+use crate::RuntimeContext;
 
 /// Evaluate a list of constraints to be applied to a `BamlValueWithFlags`, in
 /// the order that the constraints were specified by the user.
@@ -82,6 +89,58 @@ impl TestConstraintsResult {
     }
 }
 
+/// The result of evaluating one constraint against a sample value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleConstraintResult {
+    pub constraint: Constraint,
+    /// Whether the constraint held, or why it couldn't be evaluated.
+    pub result: Result<bool, String>,
+}
+
+/// Evaluate `constraints` against `sample`, a JSON value of the type named
+/// `type_name`, without calling an LLM.
+///
+/// The sample is parsed the way a function returning that type parses its
+/// LLM response, so `this` is the value the constraints would see on a real
+/// call. It's an error if the sample doesn't parse, including when it fails
+/// one of the type's own asserts.
+pub fn evaluate_sample_constraints(
+    ir: &IntermediateRepr,
+    ctx: &RuntimeContext,
+    type_name: &str,
+    sample: &str,
+    constraints: Vec<Constraint>,
+) -> Result<Vec<SampleConstraintResult>> {
+    let field_type = named_type(ir, type_name)?;
+    let output_format = render_output_format(ir, ctx, &field_type)?;
+    let value: BamlValue = jsonish::from_str(&output_format, &field_type, sample, false)?.into();
+    Ok(constraints
+        .into_iter()
+        .map(|constraint| {
+            let result =
+                evaluate_predicate(&value, &constraint.expression).map_err(|e| e.to_string());
+            SampleConstraintResult { constraint, result }
+        })
+        .collect())
+}
+
+/// The class, enum or primitive type called `name`.
+fn named_type(ir: &IntermediateRepr, name: &str) -> Result<FieldType> {
+    if ir.find_class(name).is_ok() {
+        return Ok(FieldType::class(name));
+    }
+    if ir.find_enum(name).is_ok() {
+        return Ok(FieldType::r#enum(name));
+    }
+    match name {
+        "string" => Ok(FieldType::string()),
+        "int" => Ok(FieldType::int()),
+        "float" => Ok(FieldType::float()),
+        "bool" => Ok(FieldType::bool()),
+        _ => anyhow::bail!("Type `{name}` is not a class, an enum, or a primitive type"),
+    }
+}
+
 /// The state that we track as we iterate over constraints in the test block.
 struct Accumulator {
     pub result: TestConstraintsResult,
@@ -335,6 +394,13 @@ mod tests {
                 prompt_tokens: None,
                 output_tokens: None,
                 total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
             },
         }
     }
@@ -462,4 +528,48 @@ mod tests {
         // This test fails because there is a typo: `__` (double underscore).
         assert!(matches!(res, TestConstraintsResult::InternalError { .. }));
     }
+
+    #[test]
+    fn test_sample_constraints() {
+        let ir = internal_baml_core::ir::repr::make_test_ir(
+            r##"
+            class Person {
+                name string
+                age int @assert(positive_age, {{ this >= 0 }})
+            }
+            "##,
+        )
+        .unwrap();
+        let ctx = RuntimeContext::new(
+            Default::default(),
+            HashMap::new(),
+            HashMap::new(),
+            None,
+            Default::default(),
+            Default::default(),
+        );
+        let sample = r#"{"name": "Greg", "age": "42"}"#;
+
+        let results = evaluate_sample_constraints(
+            &ir,
+            &ctx,
+            "Person",
+            sample,
+            vec![
+                mk_check("adult", "this.age >= 18"),
+                mk_assert("short_name", "this.name|length < 3"),
+                mk_check("malformed", "this.name.length()"),
+            ],
+        )
+        .unwrap();
+        let results = results
+            .iter()
+            .map(|r| r.result.as_ref().map_err(|_| ()))
+            .collect::<Vec<_>>();
+        assert_eq!(results, vec![Ok(&true), Ok(&false), Err(())]);
+
+        let failing_assert = r#"{"name": "Greg", "age": -1}"#;
+        assert!(evaluate_sample_constraints(&ir, &ctx, "Person", failing_assert, vec![]).is_err());
+        assert!(evaluate_sample_constraints(&ir, &ctx, "Animal", sample, vec![]).is_err());
+    }
 }
@@ -0,0 +1,361 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use anyhow::Result;
+use baml_types::{BamlValue, FieldType, LiteralValue, TypeValue};
+use internal_baml_core::ir::{repr::IntermediateRepr, IRHelper};
+
+/// How much of a function's output type its parsed results used, summed over
+/// every result recorded with [`SchemaCoverage::record`].
+///
+/// Every optional field, union and enum reachable from the output type is
+/// listed, so parts no result used show up with a count of zero.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaCoverage {
+    /// Keyed by `Class.field`.
+    pub optional_fields: BTreeMap<String, OptionalCoverage>,
+    /// How often each branch of a union was taken, keyed by where the union
+    /// is, like `Resume.contact`, `Resume.links[]` or `ExtractResume()` for
+    /// the output type itself.
+    pub union_branches: BTreeMap<String, BTreeMap<String, usize>>,
+    /// How often each value of an enum appeared, keyed by enum name.
+    pub enum_values: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OptionalCoverage {
+    pub null: usize,
+    pub set: usize,
+}
+
+impl SchemaCoverage {
+    /// Records which parts of the output type of `function` `value` used.
+    pub fn record(
+        &mut self,
+        ir: &IntermediateRepr,
+        function: &str,
+        value: &BamlValue,
+    ) -> Result<()> {
+        let output = ir.find_function(function)?.output().clone();
+        let root = format!("{function}()");
+        declare(ir, &output, &root, &mut HashSet::new(), self);
+        record(ir, &output, value, &root, self);
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &SchemaCoverage) {
+        for (field, coverage) in &other.optional_fields {
+            let entry = self.optional_fields.entry(field.clone()).or_default();
+            entry.null += coverage.null;
+            entry.set += coverage.set;
+        }
+        for (target, other) in [
+            (&mut self.union_branches, &other.union_branches),
+            (&mut self.enum_values, &other.enum_values),
+        ] {
+            for (key, counts) in other {
+                let entry = target.entry(key.clone()).or_default();
+                for (name, count) in counts {
+                    *entry.entry(name.clone()).or_default() += count;
+                }
+            }
+        }
+    }
+
+    /// The parts of the schema no recorded result used.
+    pub fn gaps(&self) -> Vec<String> {
+        let mut gaps = Vec::new();
+        for (field, coverage) in &self.optional_fields {
+            match (coverage.null, coverage.set) {
+                (0, 0) => {}
+                (0, _) => gaps.push(format!("{field} was never null")),
+                (_, 0) => gaps.push(format!("{field} was always null")),
+                _ => {}
+            }
+        }
+        for (location, branches) in &self.union_branches {
+            for (branch, _) in branches.iter().filter(|(_, count)| **count == 0) {
+                gaps.push(format!("{location} was never {branch}"));
+            }
+        }
+        for (name, values) in &self.enum_values {
+            for (value, _) in values.iter().filter(|(_, count)| **count == 0) {
+                gaps.push(format!("{name}.{value} never appeared"));
+            }
+        }
+        gaps
+    }
+}
+
+impl fmt::Display for SchemaCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (field, coverage) in &self.optional_fields {
+            writeln!(f, "{field}: null {}, set {}", coverage.null, coverage.set)?;
+        }
+        for (location, counts) in self.union_branches.iter().chain(&self.enum_values) {
+            let counts = counts
+                .iter()
+                .map(|(name, count)| format!("{name} {count}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "{location}: {counts}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds every optional field, union and enum reachable from `field_type` with
+/// a count of zero.
+fn declare(
+    ir: &IntermediateRepr,
+    field_type: &FieldType,
+    path: &str,
+    seen: &mut HashSet<String>,
+    coverage: &mut SchemaCoverage,
+) {
+    match field_type {
+        FieldType::Constrained { base, .. } => declare(ir, base, path, seen, coverage),
+        FieldType::Optional(inner) => declare(ir, inner, path, seen, coverage),
+        FieldType::Union(branches) => {
+            let counts = coverage.union_branches.entry(path.to_string()).or_default();
+            for branch in branches {
+                counts.entry(branch.to_string()).or_default();
+            }
+            for branch in branches {
+                declare(ir, branch, path, seen, coverage);
+            }
+        }
+        FieldType::List(inner) => declare(ir, inner, &format!("{path}[]"), seen, coverage),
+        FieldType::Map(_, value) => declare(ir, value, &format!("{path}{{}}"), seen, coverage),
+        FieldType::Tuple(items) => {
+            for item in items {
+                declare(ir, item, &format!("{path}[]"), seen, coverage);
+            }
+        }
+        FieldType::Enum(name) => {
+            let Ok(walker) = ir.find_enum(name) else {
+                return;
+            };
+            let values = coverage.enum_values.entry(name.clone()).or_default();
+            for value in walker.walk_values() {
+                values.entry(value.name().to_string()).or_default();
+            }
+        }
+        FieldType::Class(name) => {
+            if !seen.insert(name.clone()) {
+                return;
+            }
+            let Ok(walker) = ir.find_class(name) else {
+                return;
+            };
+            for field in walker.walk_fields() {
+                let path = format!("{name}.{}", field.name());
+                if field.r#type().is_optional() {
+                    coverage.optional_fields.entry(path.clone()).or_default();
+                }
+                declare(ir, field.r#type(), &path, seen, coverage);
+            }
+        }
+        FieldType::Primitive(_) | FieldType::Literal(_) => {}
+    }
+}
+
+fn record(
+    ir: &IntermediateRepr,
+    field_type: &FieldType,
+    value: &BamlValue,
+    path: &str,
+    coverage: &mut SchemaCoverage,
+) {
+    match (field_type, value) {
+        (FieldType::Constrained { base, .. }, _) => record(ir, base, value, path, coverage),
+        (FieldType::Optional(_), BamlValue::Null) => {}
+        (FieldType::Optional(inner), _) => record(ir, inner, value, path, coverage),
+        (FieldType::Union(branches), _) => {
+            let Some(branch) = branches.iter().find(|branch| matches(branch, value)) else {
+                return;
+            };
+            *coverage
+                .union_branches
+                .entry(path.to_string())
+                .or_default()
+                .entry(branch.to_string())
+                .or_default() += 1;
+            record(ir, branch, value, path, coverage);
+        }
+        (FieldType::List(inner), BamlValue::List(items)) => {
+            for item in items {
+                record(ir, inner, item, &format!("{path}[]"), coverage);
+            }
+        }
+        (FieldType::Tuple(types), BamlValue::List(items)) => {
+            for (item_type, item) in types.iter().zip(items) {
+                record(ir, item_type, item, &format!("{path}[]"), coverage);
+            }
+        }
+        (FieldType::Map(_, value_type), BamlValue::Map(entries)) => {
+            for value in entries.values() {
+                record(ir, value_type, value, &format!("{path}{{}}"), coverage);
+            }
+        }
+        (FieldType::Enum(name), BamlValue::Enum(_, value) | BamlValue::String(value)) => {
+            *coverage
+                .enum_values
+                .entry(name.clone())
+                .or_default()
+                .entry(value.clone())
+                .or_default() += 1;
+        }
+        (FieldType::Class(name), BamlValue::Class(_, fields) | BamlValue::Map(fields)) => {
+            let Ok(walker) = ir.find_class(name) else {
+                return;
+            };
+            for field in walker.walk_fields() {
+                let path = format!("{name}.{}", field.name());
+                let value = fields.get(field.name()).unwrap_or(&BamlValue::Null);
+                if field.r#type().is_optional() {
+                    let entry = coverage.optional_fields.entry(path.clone()).or_default();
+                    match value {
+                        BamlValue::Null => entry.null += 1,
+                        _ => entry.set += 1,
+                    }
+                }
+                record(ir, field.r#type(), value, &path, coverage);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `value` has the shape of `field_type`, without checking nested
+/// values.
+fn matches(field_type: &FieldType, value: &BamlValue) -> bool {
+    match (field_type, value) {
+        (FieldType::Constrained { base, .. }, _) => matches(base, value),
+        (FieldType::Optional(_), BamlValue::Null) => true,
+        (FieldType::Optional(inner), _) => matches(inner, value),
+        (FieldType::Union(branches), _) => branches.iter().any(|branch| matches(branch, value)),
+        (FieldType::Primitive(primitive), _) => matches!(
+            (primitive, value),
+            (TypeValue::String, BamlValue::String(_))
+                | (TypeValue::Int, BamlValue::Int(_))
+                | (TypeValue::Float, BamlValue::Float(_) | BamlValue::Int(_))
+                | (TypeValue::Bool, BamlValue::Bool(_))
+                | (TypeValue::Null, BamlValue::Null)
+                | (TypeValue::Media(_), BamlValue::Media(_))
+        ),
+        (FieldType::Literal(literal), _) => match (literal, value) {
+            (LiteralValue::String(l), BamlValue::String(v)) => l == v,
+            (LiteralValue::Int(l), BamlValue::Int(v)) => l == v,
+            (LiteralValue::Bool(l), BamlValue::Bool(v)) => l == v,
+            _ => false,
+        },
+        (FieldType::Enum(name), BamlValue::Enum(value_enum, _)) => name == value_enum,
+        (FieldType::Class(name), BamlValue::Class(value_class, _)) => name == value_class,
+        (FieldType::List(_) | FieldType::Tuple(_), BamlValue::List(_)) => true,
+        (FieldType::Map(..), BamlValue::Map(_)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use internal_baml_core::ir::repr::make_test_ir;
+
+    #[test]
+    fn records_coverage() {
+        let ir = make_test_ir(
+            r##"
+            enum Kind {
+                Person
+                Company
+            }
+
+            class Phone {
+                number string
+            }
+
+            class Contact {
+                kind Kind
+                email string?
+                reach Phone | string
+            }
+
+            client<llm> GPT4 {
+                provider openai
+                options {
+                    model gpt-4o
+                }
+            }
+
+            function Extract(text: string) -> Contact[] {
+                client GPT4
+                prompt #"{{ text }}"#
+            }
+            "##,
+        )
+        .unwrap();
+
+        let contact = |kind: &str, email: Option<&str>, reach: BamlValue| {
+            BamlValue::Class(
+                "Contact".into(),
+                [
+                    (
+                        "kind".to_string(),
+                        BamlValue::Enum("Kind".into(), kind.into()),
+                    ),
+                    (
+                        "email".to_string(),
+                        email.map_or(BamlValue::Null, |e| BamlValue::String(e.into())),
+                    ),
+                    ("reach".to_string(), reach),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+        let phone = BamlValue::Class(
+            "Phone".into(),
+            [("number".to_string(), BamlValue::String("555".into()))]
+                .into_iter()
+                .collect(),
+        );
+
+        let mut coverage = SchemaCoverage::default();
+        coverage
+            .record(
+                &ir,
+                "Extract",
+                &BamlValue::List(vec![contact("Person", None, phone)]),
+            )
+            .unwrap();
+        assert_eq!(
+            coverage.gaps(),
+            vec![
+                "Contact.email was always null",
+                "Contact.reach was never string",
+                "Kind.Company never appeared",
+            ]
+        );
+
+        let mut other = SchemaCoverage::default();
+        other
+            .record(
+                &ir,
+                "Extract",
+                &BamlValue::List(vec![contact(
+                    "Company",
+                    Some("a@b.co"),
+                    BamlValue::String("twitter".into()),
+                )]),
+            )
+            .unwrap();
+        coverage.merge(&other);
+        assert!(coverage.gaps().is_empty());
+        assert_eq!(
+            coverage.optional_fields["Contact.email"],
+            OptionalCoverage { null: 1, set: 1 }
+        );
+    }
+}
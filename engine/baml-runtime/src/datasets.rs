@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use baml_types::{BamlMap, BamlValue};
+use serde::Deserialize;
+
+/// One line of a test's `dataset` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DatasetRow {
+    /// The row's `name`, or its 1-based position in the file.
+    pub name: String,
+    /// Merged over the test's own `args`.
+    pub args: BamlMap<String, BamlValue>,
+    /// Replaces the test's `@@expect` value, keeping its options.
+    pub expect: Option<BamlValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRow {
+    name: Option<String>,
+    #[serde(default)]
+    args: BamlMap<String, BamlValue>,
+    expect: Option<BamlValue>,
+}
+
+/// Parses JSONL rows like `{"name": "short", "args": {...}, "expect": ...}`.
+/// Blank lines are skipped.
+pub fn parse_dataset(content: &str) -> Result<Vec<DatasetRow>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .enumerate()
+        .map(|(position, (line_number, line))| {
+            let row: RawRow = serde_json::from_str(line)
+                .with_context(|| format!("Invalid dataset row on line {}", line_number + 1))?;
+            Ok(DatasetRow {
+                name: row.name.unwrap_or_else(|| (position + 1).to_string()),
+                args: row.args,
+                expect: row.expect,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rows() {
+        let rows = parse_dataset(
+            r#"{"name": "greg", "args": {"text": "Greg, 42"}, "expect": {"age": 42}}
+
+{"args": {"text": "Ellie"}}
+"#,
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "greg");
+        assert_eq!(
+            rows[0].expect,
+            Some(BamlValue::Map(
+                [("age".to_string(), BamlValue::Int(42))]
+                    .into_iter()
+                    .collect()
+            ))
+        );
+        assert_eq!(rows[1].name, "2");
+        assert_eq!(rows[1].expect, None);
+
+        let err = parse_dataset("{\"args\": {}}\n{\"arg\": {}}").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid dataset row on line 2");
+    }
+}
@@ -1,3 +1,6 @@
+use baml_types::BamlValue;
+
+#[derive(Clone)]
 pub enum ExposedError {
     /// Error in parsing post calling the LLM
     ValidationError {
@@ -11,6 +14,17 @@ pub enum ExposedError {
         message: String,
         finish_reason: Option<String>,
     },
+    /// The call was cancelled before it finished
+    Cancelled,
+    /// A streaming call failed after content streamed in, so it didn't fall
+    /// back to other clients
+    PartialFailure {
+        prompt: String,
+        raw_output: String,
+        message: String,
+        /// What the content streamed so far parsed to, if anything
+        partial: Option<BamlValue>,
+    },
 }
 
 impl std::error::Error for ExposedError {}
@@ -44,6 +58,19 @@ impl std::fmt::Display for ExposedError {
                     finish_reason.as_ref().map_or("<none>", |f| f.as_str())
                 )
             }
+            ExposedError::Cancelled => write!(f, "Cancelled"),
+            ExposedError::PartialFailure {
+                prompt,
+                raw_output,
+                message,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Stream failed after partial output: {}\nPrompt: {}\nRaw Response: {}",
+                    message, prompt, raw_output
+                )
+            }
         }
     }
 }
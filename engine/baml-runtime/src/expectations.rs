@@ -0,0 +1,366 @@
+use std::fmt;
+
+use baml_types::{BamlMap, BamlValue};
+
+/// How [`diff_expectation`] compares values, from the `@@expect` options.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExpectOptions {
+    /// How far floats may be from the expected value.
+    pub float_tolerance: f64,
+    /// Compare lists as multisets.
+    pub unordered_lists: bool,
+}
+
+/// Where the parsed output of a test differs from its `@@expect` value.
+/// Empty if they match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExpectationDiff {
+    pub mismatches: Vec<Mismatch>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    /// Like `.kids[1].name`; unordered list items are `[]`.
+    pub path: String,
+    pub kind: MismatchKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MismatchKind {
+    Changed {
+        expected: BamlValue,
+        actual: BamlValue,
+    },
+    /// Expected, but not in the output.
+    Missing(BamlValue),
+    /// In the output, but not expected.
+    Unexpected(BamlValue),
+}
+
+impl ExpectationDiff {
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Deep-compares `actual` to `expected`.
+///
+/// Classes and maps compare by key, where a missing key equals `null`.
+/// Enums equal strings of their value name, and ints equal floats within
+/// the tolerance.
+pub fn diff_expectation(
+    expected: &BamlValue,
+    actual: &BamlValue,
+    options: ExpectOptions,
+) -> ExpectationDiff {
+    let mut mismatches = Vec::new();
+    diff_value("", expected, actual, options, &mut mismatches);
+    ExpectationDiff { mismatches }
+}
+
+fn diff_value(
+    path: &str,
+    expected: &BamlValue,
+    actual: &BamlValue,
+    options: ExpectOptions,
+    out: &mut Vec<Mismatch>,
+) {
+    let changed = match (expected, actual) {
+        (
+            BamlValue::Map(e) | BamlValue::Class(_, e),
+            BamlValue::Map(a) | BamlValue::Class(_, a),
+        ) => {
+            diff_fields(path, e, a, options, out);
+            false
+        }
+        (BamlValue::List(e), BamlValue::List(a)) => {
+            if options.unordered_lists {
+                diff_unordered(path, e, a, options, out);
+            } else {
+                diff_ordered(path, e, a, options, out);
+            }
+            false
+        }
+        (e, a) => match (as_number(e), as_number(a)) {
+            (Some(e), Some(a)) => (e - a).abs() > options.float_tolerance,
+            _ => match (as_str(e), as_str(a)) {
+                (Some(e), Some(a)) => e != a,
+                _ => e != a,
+            },
+        },
+    };
+    if changed {
+        out.push(Mismatch {
+            path: display_path(path),
+            kind: MismatchKind::Changed {
+                expected: expected.clone(),
+                actual: actual.clone(),
+            },
+        });
+    }
+}
+
+fn diff_fields(
+    path: &str,
+    expected: &BamlMap<String, BamlValue>,
+    actual: &BamlMap<String, BamlValue>,
+    options: ExpectOptions,
+    out: &mut Vec<Mismatch>,
+) {
+    for (key, e) in expected {
+        let field_path = format!("{path}.{key}");
+        match actual.get(key) {
+            Some(a) => diff_value(&field_path, e, a, options, out),
+            None if matches!(e, BamlValue::Null) => {}
+            None => out.push(Mismatch {
+                path: field_path,
+                kind: MismatchKind::Missing(e.clone()),
+            }),
+        }
+    }
+    for (key, a) in actual {
+        if !expected.contains_key(key) && !matches!(a, BamlValue::Null) {
+            out.push(Mismatch {
+                path: format!("{path}.{key}"),
+                kind: MismatchKind::Unexpected(a.clone()),
+            });
+        }
+    }
+}
+
+fn diff_ordered(
+    path: &str,
+    expected: &[BamlValue],
+    actual: &[BamlValue],
+    options: ExpectOptions,
+    out: &mut Vec<Mismatch>,
+) {
+    for (i, e) in expected.iter().enumerate() {
+        let item_path = format!("{path}[{i}]");
+        match actual.get(i) {
+            Some(a) => diff_value(&item_path, e, a, options, out),
+            None => out.push(Mismatch {
+                path: item_path,
+                kind: MismatchKind::Missing(e.clone()),
+            }),
+        }
+    }
+    for (i, a) in actual.iter().enumerate().skip(expected.len()) {
+        out.push(Mismatch {
+            path: format!("{path}[{i}]"),
+            kind: MismatchKind::Unexpected(a.clone()),
+        });
+    }
+}
+
+/// Matches every expected item to the first unmatched actual item equal to
+/// it; whatever is left over on either side is a mismatch.
+fn diff_unordered(
+    path: &str,
+    expected: &[BamlValue],
+    actual: &[BamlValue],
+    options: ExpectOptions,
+    out: &mut Vec<Mismatch>,
+) {
+    let item_path = format!("{path}[]");
+    let mut matched = vec![false; actual.len()];
+    for e in expected {
+        let found = (0..actual.len())
+            .find(|&i| !matched[i] && diff_expectation(e, &actual[i], options).is_empty());
+        match found {
+            Some(i) => matched[i] = true,
+            None => out.push(Mismatch {
+                path: item_path.clone(),
+                kind: MismatchKind::Missing(e.clone()),
+            }),
+        }
+    }
+    for (a, _) in actual.iter().zip(&matched).filter(|(_, m)| !**m) {
+        out.push(Mismatch {
+            path: item_path.clone(),
+            kind: MismatchKind::Unexpected(a.clone()),
+        });
+    }
+}
+
+fn as_number(value: &BamlValue) -> Option<f64> {
+    match value {
+        BamlValue::Int(i) => Some(*i as f64),
+        BamlValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn as_str(value: &BamlValue) -> Option<&str> {
+    match value {
+        BamlValue::String(s) | BamlValue::Enum(_, s) => Some(s),
+        _ => None,
+    }
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        ".".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn display_value(value: &BamlValue) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("{value:?}"))
+}
+
+impl fmt::Display for ExpectationDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "Output matches @@expect");
+        }
+        write!(
+            f,
+            "Output differs from @@expect in {} place{} (- expected, + actual):",
+            self.mismatches.len(),
+            if self.mismatches.len() == 1 { "" } else { "s" }
+        )?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  {}", mismatch.path)?;
+            match &mismatch.kind {
+                MismatchKind::Changed { expected, actual } => write!(
+                    f,
+                    "\n    - {}\n    + {}",
+                    display_value(expected),
+                    display_value(actual)
+                )?,
+                MismatchKind::Missing(expected) => {
+                    write!(f, "\n    - {}", display_value(expected))?
+                }
+                MismatchKind::Unexpected(actual) => write!(f, "\n    + {}", display_value(actual))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(fields: &[(&str, BamlValue)]) -> BamlValue {
+        BamlValue::Map(
+            fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    }
+
+    fn strings(items: &[&str]) -> BamlValue {
+        BamlValue::List(
+            items
+                .iter()
+                .map(|s| BamlValue::String(s.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn matches_classes_with_tolerance() {
+        let expected = map(&[
+            ("name", BamlValue::String("Greg".into())),
+            ("score", BamlValue::Float(0.9)),
+            ("role", BamlValue::String("Engineer".into())),
+            ("nickname", BamlValue::Null),
+        ]);
+        let actual = BamlValue::Class(
+            "Person".into(),
+            [
+                ("name".to_string(), BamlValue::String("Greg".into())),
+                ("score".to_string(), BamlValue::Float(0.91)),
+                (
+                    "role".to_string(),
+                    BamlValue::Enum("Role".into(), "Engineer".into()),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let options = ExpectOptions {
+            float_tolerance: 0.05,
+            ..Default::default()
+        };
+        assert!(diff_expectation(&expected, &actual, options).is_empty());
+
+        let diff = diff_expectation(&expected, &actual, ExpectOptions::default());
+        assert_eq!(
+            diff.mismatches,
+            vec![Mismatch {
+                path: ".score".into(),
+                kind: MismatchKind::Changed {
+                    expected: BamlValue::Float(0.9),
+                    actual: BamlValue::Float(0.91),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn compares_lists_in_order_or_as_multisets() {
+        let expected = map(&[("tags", strings(&["rust", "go", "go"]))]);
+        let actual = map(&[("tags", strings(&["go", "rust", "zig"]))]);
+
+        let unordered = ExpectOptions {
+            unordered_lists: true,
+            ..Default::default()
+        };
+        let diff = diff_expectation(&expected, &actual, unordered);
+        assert_eq!(
+            diff.mismatches,
+            vec![
+                Mismatch {
+                    path: ".tags[]".into(),
+                    kind: MismatchKind::Missing(BamlValue::String("go".into())),
+                },
+                Mismatch {
+                    path: ".tags[]".into(),
+                    kind: MismatchKind::Unexpected(BamlValue::String("zig".into())),
+                },
+            ]
+        );
+
+        let diff = diff_expectation(&expected, &actual, ExpectOptions::default());
+        assert_eq!(diff.mismatches.len(), 3);
+        assert_eq!(
+            diff.to_string(),
+            r#"Output differs from @@expect in 3 places (- expected, + actual):
+  .tags[0]
+    - "rust"
+    + "go"
+  .tags[1]
+    - "go"
+    + "rust"
+  .tags[2]
+    - "go"
+    + "zig""#
+        );
+    }
+
+    #[test]
+    fn reports_missing_and_unexpected_fields() {
+        let expected = map(&[("a", BamlValue::Int(1)), ("b", BamlValue::Int(2))]);
+        let actual = map(&[("a", BamlValue::Float(1.0)), ("c", BamlValue::Int(3))]);
+        let diff = diff_expectation(&expected, &actual, ExpectOptions::default());
+        assert_eq!(
+            diff.mismatches,
+            vec![
+                Mismatch {
+                    path: ".b".into(),
+                    kind: MismatchKind::Missing(BamlValue::Int(2)),
+                },
+                Mismatch {
+                    path: ".c".into(),
+                    kind: MismatchKind::Unexpected(BamlValue::Int(3)),
+                },
+            ]
+        );
+    }
+}
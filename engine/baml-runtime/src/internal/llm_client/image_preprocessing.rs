@@ -0,0 +1,191 @@
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use baml_types::{BamlMedia, BamlMediaContent, BamlMediaType};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageFormat};
+use internal_llm_client::ImagePreprocessingOptions;
+
+/// OpenAI fits images into 2048x2048 before tokenizing them.
+pub const OPENAI_MAX_DIMENSION: u32 = 2048;
+/// Anthropic downscales images with a side over 1568px, which only adds
+/// latency. Also used for Bedrock, which mostly serves Claude.
+pub const ANTHROPIC_MAX_DIMENSION: u32 = 1568;
+/// Gemini fits images into 3072x3072.
+pub const GEMINI_MAX_DIMENSION: u32 = 3072;
+/// Pixtral models take images up to 1024x1024 and downscale larger ones.
+pub const MISTRAL_MAX_DIMENSION: u32 = 1024;
+
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// How a client resizes and re-encodes images before sending them, from its
+/// `image_preprocessing` option.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImagePreprocessing {
+    pub max_dimension: u32,
+    pub jpeg_quality: u8,
+    pub convert_heic: bool,
+}
+
+impl ImagePreprocessing {
+    /// `None` if the client doesn't preprocess images.
+    pub fn from_options(
+        options: Option<&ImagePreprocessingOptions>,
+        default_max_dimension: u32,
+    ) -> Option<Self> {
+        options.map(|options| Self {
+            max_dimension: options.max_dimension.unwrap_or(default_max_dimension),
+            jpeg_quality: options.jpeg_quality.unwrap_or(DEFAULT_JPEG_QUALITY),
+            convert_heic: options.convert_heic.unwrap_or(true),
+        })
+    }
+
+    /// Only base64 images are processed; everything else, e.g. URLs the
+    /// provider fetches itself, is returned as is.
+    pub fn apply(&self, media: BamlMedia) -> Result<BamlMedia> {
+        if media.media_type != BamlMediaType::Image {
+            return Ok(media);
+        }
+        let (BamlMediaContent::Base64(media_b64), Some(mime_type)) =
+            (&media.content, media.mime_type.as_deref())
+        else {
+            return Ok(media);
+        };
+        let bytes = BASE64_STANDARD
+            .decode(&media_b64.base64)
+            .context("Failed to decode image as base64")?;
+        match self.process(&bytes, mime_type)? {
+            Some((bytes, mime_type)) => Ok(BamlMedia::base64(
+                BamlMediaType::Image,
+                BASE64_STANDARD.encode(bytes),
+                Some(mime_type),
+            )),
+            None => Ok(media),
+        }
+    }
+
+    /// The re-encoded image and its mime type, or `None` to send it as is.
+    fn process(&self, bytes: &[u8], mime_type: &str) -> Result<Option<(Vec<u8>, String)>> {
+        let (image, converted) = match mime_type {
+            "image/heic" | "image/heif" => {
+                if !self.convert_heic {
+                    return Ok(None);
+                }
+                match decode_heic(bytes)? {
+                    Some(image) => (image, true),
+                    None => return Ok(None),
+                }
+            }
+            "image/jpeg" | "image/png" | "image/webp" => {
+                let format = ImageFormat::from_mime_type(mime_type)
+                    .context(format!("Unsupported image type {mime_type}"))?;
+                let image = image::load_from_memory_with_format(bytes, format)
+                    .context(format!("Failed to decode {mime_type} image"))?;
+                (image, false)
+            }
+            // GIFs may be animated, and we can't decode anything else.
+            _ => return Ok(None),
+        };
+
+        let fits = image.width().max(image.height()) <= self.max_dimension;
+        if fits && !converted {
+            return Ok(None);
+        }
+        let image = if fits {
+            image
+        } else {
+            image.resize(self.max_dimension, self.max_dimension, FilterType::Lanczos3)
+        };
+
+        let mut encoded = Vec::new();
+        if image.color().has_alpha() && !converted {
+            // JPEG would drop the transparency.
+            image
+                .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+                .context("Failed to encode image as PNG")?;
+            Ok(Some((encoded, "image/png".to_string())))
+        } else {
+            image
+                .to_rgb8()
+                .write_with_encoder(JpegEncoder::new_with_quality(
+                    &mut encoded,
+                    self.jpeg_quality,
+                ))
+                .context("Failed to encode image as JPEG")?;
+            Ok(Some((encoded, "image/jpeg".to_string())))
+        }
+    }
+}
+
+#[cfg(feature = "heic")]
+fn decode_heic(bytes: &[u8]) -> Result<Option<DynamicImage>> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let context = HeifContext::read_from_bytes(bytes).context("Failed to read HEIC image")?;
+    let handle = context.primary_image_handle()?;
+    let decoded = LibHeif::new().decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)?;
+    let plane = decoded
+        .planes()
+        .interleaved
+        .context("Decoded HEIC image has no RGB plane")?;
+
+    // Rows may be padded past `width * 3` bytes.
+    let row = plane.width as usize * 3;
+    let mut pixels = Vec::with_capacity(row * plane.height as usize);
+    for y in 0..plane.height as usize {
+        pixels.extend_from_slice(&plane.data[y * plane.stride..][..row]);
+    }
+    let image = image::RgbImage::from_raw(plane.width, plane.height, pixels)
+        .context("Decoded HEIC image has the wrong size")?;
+    Ok(Some(DynamicImage::ImageRgb8(image)))
+}
+
+#[cfg(not(feature = "heic"))]
+fn decode_heic(_bytes: &[u8]) -> Result<Option<DynamicImage>> {
+    log_once::warn_once!(
+        "Sending HEIC images unconverted, converting them needs baml-runtime's `heic` feature"
+    );
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> BamlMedia {
+        let mut bytes = Vec::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        BamlMedia::base64(
+            BamlMediaType::Image,
+            BASE64_STANDARD.encode(bytes),
+            Some("image/png".to_string()),
+        )
+    }
+
+    #[test]
+    fn downscales_large_images() {
+        let preprocessing = ImagePreprocessing::from_options(
+            Some(&ImagePreprocessingOptions {
+                max_dimension: Some(20),
+                ..Default::default()
+            }),
+            OPENAI_MAX_DIMENSION,
+        )
+        .unwrap();
+        assert_eq!(preprocessing.jpeg_quality, DEFAULT_JPEG_QUALITY);
+
+        let small = png(20, 10);
+        assert_eq!(preprocessing.apply(small.clone()).unwrap(), small);
+
+        let resized = preprocessing.apply(png(100, 50)).unwrap();
+        assert_eq!(resized.mime_type.as_deref(), Some("image/jpeg"));
+        let BamlMediaContent::Base64(media_b64) = &resized.content else {
+            panic!("expected base64, got {resized:?}");
+        };
+        let image =
+            image::load_from_memory(&BASE64_STANDARD.decode(&media_b64.base64).unwrap()).unwrap();
+        assert_eq!((image.width(), image.height()), (20, 10));
+    }
+}
@@ -1,9 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
 use colored::*;
+pub mod image_preprocessing;
 pub mod llm_provider;
 pub mod orchestrator;
+pub(crate) mod output_tool;
 pub mod primitive;
+mod rate_limit;
 
 pub mod retry_policy;
 mod strategy;
@@ -12,6 +15,7 @@ pub mod traits;
 use anyhow::Result;
 
 use baml_types::{BamlMap, BamlValueWithMeta, JinjaExpression, ResponseCheck};
+use image_preprocessing::ImagePreprocessing;
 use internal_baml_core::ir::ClientWalker;
 use internal_baml_jinja::RenderedPrompt;
 use internal_llm_client::AllowedRoleMetadata;
@@ -76,6 +80,8 @@ pub struct ModelFeatures {
     pub anthropic_system_constraints: bool,
     pub resolve_media_urls: ResolveMediaUrls,
     pub allowed_metadata: AllowedRoleMetadata,
+    /// `None` sends images as they are.
+    pub image_preprocessing: Option<ImagePreprocessing>,
 }
 
 #[derive(Debug)]
@@ -85,7 +91,7 @@ pub struct RetryLLMResponse {
     pub failed: Vec<LLMResponse>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LLMResponse {
     /// BAML was able to successfully make the HTTP request and got a 2xx
     /// response from the model provider
@@ -103,6 +109,25 @@ pub enum LLMResponse {
 
 impl Error for LLMResponse {}
 
+/// A single item of a provider response stream.
+///
+/// Providers accumulate the response text into one buffer as chunks arrive
+/// and only hand out the newly received text, so consumers don't pay for a
+/// full copy of the response on every chunk.
+#[derive(Debug, Clone)]
+pub enum LLMStreamEvent {
+    /// Text appended to the response since the previous event, along with the
+    /// response metadata as currently known.
+    Delta {
+        text: String,
+        model: String,
+        metadata: LLMCompleteResponseMetadata,
+    },
+    /// The fully accumulated response, or the failure that ended the stream.
+    /// Always the last event of a stream.
+    Done(LLMResponse),
+}
+
 impl crate::tracing::Visualize for LLMResponse {
     fn visualize(&self, max_chunk_size: usize) -> String {
         match self {
@@ -149,13 +174,16 @@ impl LLMResponse {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMErrorResponse {
     pub client: String,
     pub model: Option<String>,
     pub prompt: RenderedPrompt,
     pub request_options: BamlMap<String, serde_json::Value>,
-    #[cfg_attr(target_arch = "wasm32", serde(skip_serializing))]
+    #[cfg_attr(
+        target_arch = "wasm32",
+        serde(skip, default = "web_time::SystemTime::now")
+    )]
     pub start_time: web_time::SystemTime,
     pub latency: web_time::Duration,
 
@@ -164,7 +192,7 @@ pub struct LLMErrorResponse {
     pub code: ErrorCode,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ErrorCode {
     InvalidAuthentication, // 401
     NotSupported,          // 403
@@ -172,6 +200,10 @@ pub enum ErrorCode {
     ServerError,           // 500
     ServiceUnavailable,    // 503
 
+    // No response within the client's `request_timeout_ms`, or no connection
+    // within its `connect_timeout_ms`
+    Timeout,
+
     // We failed to parse the response
     UnsupportedResponse(u16),
 
@@ -187,6 +219,7 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::RateLimited => f.write_str("RateLimited (429)"),
             ErrorCode::ServerError => f.write_str("ServerError (500)"),
             ErrorCode::ServiceUnavailable => f.write_str("ServiceUnavailable (503)"),
+            ErrorCode::Timeout => f.write_str("Timeout"),
             ErrorCode::UnsupportedResponse(code) => write!(f, "BadResponse {code}"),
             ErrorCode::Other(code) => write!(f, "Unspecified error code: {code}"),
         }
@@ -216,6 +249,20 @@ impl ErrorCode {
         }
     }
 
+    /// A stable, snake_case name, e.g. for metric labels.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidAuthentication => "invalid_authentication",
+            ErrorCode::NotSupported => "not_supported",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::ServerError => "server_error",
+            ErrorCode::ServiceUnavailable => "service_unavailable",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::UnsupportedResponse(_) => "unsupported_response",
+            ErrorCode::Other(_) => "other",
+        }
+    }
+
     pub fn to_u16(&self) -> u16 {
         match self {
             ErrorCode::InvalidAuthentication => 401,
@@ -223,32 +270,95 @@ impl ErrorCode {
             ErrorCode::RateLimited => 429,
             ErrorCode::ServerError => 500,
             ErrorCode::ServiceUnavailable => 503,
+            ErrorCode::Timeout => 408,
             ErrorCode::UnsupportedResponse(code) => *code,
             ErrorCode::Other(code) => *code,
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LLMCompleteResponse {
     pub client: String,
     pub model: String,
     pub prompt: RenderedPrompt,
     pub request_options: BamlMap<String, serde_json::Value>,
     pub content: String,
-    #[cfg_attr(target_arch = "wasm32", serde(skip_serializing))]
+    #[cfg_attr(
+        target_arch = "wasm32",
+        serde(skip, default = "web_time::SystemTime::now")
+    )]
     pub start_time: web_time::SystemTime,
     pub latency: web_time::Duration,
     pub metadata: LLMCompleteResponseMetadata,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LLMCompleteResponseMetadata {
     pub baml_is_complete: bool,
     pub finish_reason: Option<String>,
     pub prompt_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
     pub total_tokens: Option<u64>,
+    /// Input tokens the provider served from its prompt cache.
+    pub cached_input_tokens: Option<u64>,
+    /// Set when a Gemini model grounded its answer with Google Search.
+    pub grounding: Option<GroundingMetadata>,
+    /// What an Anthropic model wrote while thinking, if extended thinking was
+    /// on, or a DeepSeek or xAI reasoning model's `reasoning_content`. It's
+    /// not part of `content`, so it's never parsed.
+    pub thinking: Option<String>,
+    /// The log probability of each output token, if the client asked for
+    /// them with `logprobs true`.
+    pub logprobs: Option<Vec<TokenLogprob>>,
+    /// Every response, when the client asked for more than one with `n` or
+    /// `candidateCount`. `content` is the one its `candidate_selection`
+    /// picked.
+    pub candidates: Option<Vec<ResponseCandidate>>,
+    /// Set when the provider was asked for JSON matching the function's
+    /// output type, with `structured_output true`, so `content` is parsed as
+    /// is.
+    #[serde(default)]
+    pub structured_output: bool,
+    /// The provider's JSON body, for clients with `capture_raw_response
+    /// true`. Only kept for calls that aren't streamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_response: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCandidate {
+    pub content: String,
+    pub finish_reason: Option<String>,
+    /// The mean logprob of the candidate's tokens, if the provider returned
+    /// them.
+    pub logprob: Option<f64>,
+    /// The log probability of each token, with `logprobs true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<Vec<TokenLogprob>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// The most likely tokens at this position, with `top_logprobs`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_logprobs: Vec<TokenLogprob>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroundingMetadata {
+    /// The queries the model searched for.
+    pub search_queries: Vec<String>,
+    /// The sources the answer is based on.
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    pub uri: String,
+    pub title: Option<String>,
 }
 
 // This is how the response gets logged if you print the result to the console.
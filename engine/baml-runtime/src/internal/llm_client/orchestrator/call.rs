@@ -1,6 +1,12 @@
 use anyhow::Result;
 use baml_types::BamlValue;
+use futures::{
+    future::{select, Either},
+    stream::FuturesUnordered,
+    StreamExt,
+};
 use internal_baml_core::ir::repr::IntermediateRepr;
+use internal_baml_jinja::RenderedPrompt;
 use jsonish::BamlValueWithFlags;
 use web_time::Duration;
 
@@ -13,16 +19,23 @@ use crate::{
         },
         prompt_renderer::PromptRenderer,
     },
-    RuntimeContext,
+    pii::{MaskedSpan, PiiTokenMap},
+    DryRunRequest, RuntimeContext,
+};
+
+use super::{
+    candidates, hedging, next_after_failure, OrchestrationScope, OrchestratorNodeIterator,
 };
 
-use super::{OrchestrationScope, OrchestratorNodeIterator};
+/// An attempt at calling a node: its index, its masked prompt, unless it
+/// couldn't be rendered, and the response.
+type Attempt = (usize, Option<(RenderedPrompt, PiiTokenMap)>, LLMResponse);
 
 pub async fn orchestrate(
     iter: OrchestratorNodeIterator,
     ir: &IntermediateRepr,
     ctx: &RuntimeContext,
-    prompt: &PromptRenderer,
+    renderer: &PromptRenderer,
     params: &BamlValue,
     parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
 ) -> (
@@ -33,67 +46,196 @@ pub async fn orchestrate(
         Option<Result<ResponseBamlValue>>,
     )>,
     Duration,
+    Vec<MaskedSpan>,
+    Option<DryRunRequest>,
 ) {
     let mut results = Vec::new();
     let mut total_sleep_duration = std::time::Duration::from_secs(0);
+    let mut pii_audit = vec![];
+    let mut dry_run_request = None;
 
-    for node in iter {
-        let prompt = match node.render_prompt(ir, prompt, ctx, params).await {
-            Ok(p) => p,
+    let mut idx = 0;
+    'nodes: while let Some(node) = iter.get(idx) {
+        let (prompt, pii) = match node.render_prompt(ir, renderer, ctx, params).await {
+            Ok(p) => renderer.mask_pii(p),
             Err(e) => {
                 results.push((
-                    node.scope,
+                    node.scope.clone(),
                     LLMResponse::InternalFailure(e.to_string()),
                     None,
                     None,
                 ));
+                if ctx.dry_run {
+                    break;
+                }
+                idx += 1;
                 continue;
             }
         };
-        let response = node.single_call(ctx, &prompt).await;
-        let parsed_response = match &response {
-            LLMResponse::Success(s) => {
-                if !node
-                    .finish_reason_filter()
-                    .is_allowed(s.metadata.finish_reason.as_ref())
-                {
-                    Some(Err(anyhow::anyhow!(crate::errors::ExposedError::FinishReasonError {
-                        prompt: prompt.to_string(),
-                        raw_output: s.content.clone(),
-                        message: "Finish reason not allowed".to_string(),
-                        finish_reason: s.metadata.finish_reason.clone(),
-                    })))
-                } else {
-                    Some(parse_fn(&s.content))
+
+        if ctx.dry_run {
+            let (response, request) = node.dry_run(ctx, &prompt, false).await;
+            pii_audit = pii.spans().to_vec();
+            results.push((node.scope.clone(), response, None, None));
+            dry_run_request = request;
+            break;
+        }
+
+        let attempts = match hedging(&iter, idx, ir) {
+            Some(hedge) => {
+                hedged_call(&iter, idx, hedge, (prompt, pii), ir, ctx, renderer, params).await
+            }
+            None => {
+                let response = node.single_call(ctx, &prompt).await;
+                vec![(idx, Some((prompt, pii)), response)]
+            }
+        };
+
+        let mut last_idx = idx;
+        let mut next = (idx + 1, None);
+        for (attempt_idx, prompt, mut response) in attempts {
+            let node = &iter[attempt_idx];
+            if attempt_idx >= last_idx {
+                last_idx = attempt_idx;
+                next = next_after_failure(&iter, attempt_idx, &response, ir);
+            }
+            let Some((prompt, pii)) = prompt else {
+                results.push((node.scope.clone(), response, None, None));
+                continue;
+            };
+            node.record_outcome(&response);
+            // Of several candidates, the one the client's `candidate_selection`
+            // picks becomes the response.
+            if let LLMResponse::Success(s) = &mut response {
+                if let Some(all) = &s.metadata.candidates {
+                    let picked = all[candidates::select(
+                        node.candidate_selection(),
+                        all,
+                        node.finish_reason_filter(),
+                        |content| parse_fn(&pii.restore(content)),
+                    )]
+                    .clone();
+                    s.content = picked.content;
+                    s.metadata.finish_reason = picked.finish_reason;
+                    s.metadata.logprobs = picked.logprobs;
                 }
+            }
+            let parsed_response = match &response {
+                LLMResponse::Success(s) => {
+                    if !node
+                        .finish_reason_filter()
+                        .is_allowed(s.metadata.finish_reason.as_ref())
+                    {
+                        Some(Err(anyhow::anyhow!(crate::errors::ExposedError::FinishReasonError {
+                            prompt: prompt.to_string(),
+                            raw_output: s.content.clone(),
+                            message: "Finish reason not allowed".to_string(),
+                            finish_reason: s.metadata.finish_reason.clone(),
+                        })))
+                    } else if s.metadata.structured_output {
+                        Some(renderer.parse_json(&pii.restore(&s.content)))
+                    } else {
+                        Some(parse_fn(&pii.restore(&s.content)))
+                    }
+                },
+                _ => None,
+            };
+            if let (LLMResponse::Success(s), Some(Err(e))) = (&response, &parsed_response) {
+                ctx.hooks.parse_error(s, e);
+            }
+
+            pii_audit = pii.spans().to_vec();
+            let (parsed_response, response_with_constraints) = match parsed_response {
+                Some(Ok(v)) => (Some(Ok(v.clone())), Some(Ok(parsed_value_to_response(&v)))),
+                Some(Err(e)) => (None, Some(Err(e))),
+                None => (None, None),
+            };
+            results.push((
+                node.scope.clone(),
+                response,
+                parsed_response,
+                response_with_constraints,
+            ));
+
+            // Currently, we break out of the loop if an LLM responded, even if we couldn't parse the result.
+            if results
+                .last()
+                .map_or(false, |(_, r, _, _)| matches!(r, LLMResponse::Success(_)))
+            {
+                break 'nodes;
+            }
+        }
+
+        let (next_idx, sleep_duration) = next;
+        if let Some(duration) = sleep_duration {
+            total_sleep_duration += duration;
+            ctx.clock.sleep(duration).await;
+        }
+        idx = next_idx;
+    }
+
+    (results, total_sleep_duration, pii_audit, dry_run_request)
+}
+
+/// Calls the node at `idx`, and while no call has answered within `after`,
+/// sends the same request to each of the next `max_hedges` nodes in turn.
+/// Returns the attempts that finished, in order, up to the first success.
+/// Dropping the others cancels their requests.
+#[allow(clippy::too_many_arguments)]
+async fn hedged_call(
+    iter: &OrchestratorNodeIterator,
+    idx: usize,
+    (after, max_hedges): (Duration, usize),
+    first: (RenderedPrompt, PiiTokenMap),
+    ir: &IntermediateRepr,
+    ctx: &RuntimeContext,
+    renderer: &PromptRenderer,
+    params: &BamlValue,
+) -> Vec<Attempt> {
+    let attempt = |idx: usize, prompt: Option<(RenderedPrompt, PiiTokenMap)>| async move {
+        let node = &iter[idx];
+        let (prompt, pii) = match prompt {
+            Some(prompt) => prompt,
+            None => match node.render_prompt(ir, renderer, ctx, params).await {
+                Ok(p) => renderer.mask_pii(p),
+                Err(e) => return (idx, None, LLMResponse::InternalFailure(e.to_string())),
             },
-            _ => None,
         };
+        let response = node.single_call(ctx, &prompt).await;
+        (idx, Some((prompt, pii)), response)
+    };
 
-        let sleep_duration = node.error_sleep_duration().cloned();
-        let (parsed_response, response_with_constraints) = match parsed_response {
-            Some(Ok(v)) => (Some(Ok(v.clone())), Some(Ok(parsed_value_to_response(&v)))),
-            Some(Err(e)) => (None, Some(Err(e))),
-            None => (None, None),
+    let mut pending = FuturesUnordered::new();
+    pending.push(attempt(idx, Some(first)));
+    let mut next = idx + 1;
+    let mut finished = vec![];
+    loop {
+        let done = if next <= idx + max_hedges {
+            let raced = match select(pending.next(), ctx.clock.sleep(after)).await {
+                Either::Left((done, _)) => Some(done),
+                Either::Right(_) => None,
+            };
+            match raced {
+                Some(done) => done,
+                None => {
+                    pending.push(attempt(next, None));
+                    next += 1;
+                    continue;
+                }
+            }
+        } else {
+            pending.next().await
         };
-        results.push((
-            node.scope,
-            response,
-            parsed_response,
-            response_with_constraints,
-        ));
-
-        // Currently, we break out of the loop if an LLM responded, even if we couldn't parse the result.
-        if results
-            .last()
-            .map_or(false, |(_, r, _, _)| matches!(r, LLMResponse::Success(_)))
-        {
+        let Some(done) = done else {
+            break;
+        };
+        let succeeded = matches!(done.2, LLMResponse::Success(_));
+        finished.push(done);
+        // A failed call leaves the race to the others. Once they all failed,
+        // the next node waits for the retry policy's delay instead.
+        if succeeded || pending.is_empty() {
             break;
-        } else if let Some(duration) = sleep_duration {
-            total_sleep_duration += duration;
-            async_std::task::sleep(duration).await;
         }
     }
-
-    (results, total_sleep_duration)
+    finished
 }
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use baml_types::BamlValue;
+use internal_llm_client::{CandidateSelection, FinishReasonFilter};
+use jsonish::BamlValueWithFlags;
+
+use crate::internal::llm_client::ResponseCandidate;
+
+/// The index of the candidate `selection` picks. Candidates with a finish
+/// reason the client doesn't allow, or that don't parse, are never picked
+/// unless none are left, in which case it's the first one.
+pub(crate) fn select(
+    selection: CandidateSelection,
+    candidates: &[ResponseCandidate],
+    finish_reason_filter: &FinishReasonFilter,
+    parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
+) -> usize {
+    let mut parsed = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| finish_reason_filter.is_allowed(candidate.finish_reason.as_ref()))
+        .filter_map(|(i, candidate)| {
+            parse_fn(&candidate.content)
+                .ok()
+                .map(|value| (i, BamlValue::from(value)))
+        });
+
+    let picked = match selection {
+        CandidateSelection::FirstParseSuccess => parsed.next().map(|(i, _)| i),
+        CandidateSelection::MajorityVote => {
+            // (first candidate with the value, value, votes)
+            let mut votes: Vec<(usize, BamlValue, usize)> = vec![];
+            for (i, value) in parsed {
+                match votes.iter_mut().find(|(_, other, _)| *other == value) {
+                    Some((_, _, count)) => *count += 1,
+                    None => votes.push((i, value, 1)),
+                }
+            }
+            // Ties go to the value that came first.
+            votes
+                .into_iter()
+                .fold(
+                    None,
+                    |best: Option<(usize, usize)>, (i, _, count)| match best {
+                        Some((_, best_count)) if best_count >= count => best,
+                        _ => Some((i, count)),
+                    },
+                )
+                .map(|(i, _)| i)
+        }
+        CandidateSelection::HighestLogprob => parsed
+            .map(|(i, _)| (i, candidates[i].logprob.unwrap_or(f64::NEG_INFINITY)))
+            .fold(
+                None,
+                |best: Option<(usize, f64)>, (i, logprob)| match best {
+                    Some((_, best_logprob)) if best_logprob >= logprob => best,
+                    _ => Some((i, logprob)),
+                },
+            )
+            .map(|(i, _)| i),
+    };
+    picked.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(content: &str, logprob: f64) -> ResponseCandidate {
+        ResponseCandidate {
+            content: content.to_string(),
+            finish_reason: Some("stop".to_string()),
+            logprob: Some(logprob),
+            logprobs: None,
+        }
+    }
+
+    fn parse(content: &str) -> Result<BamlValueWithFlags> {
+        match content.parse::<i64>() {
+            Ok(n) => Ok(BamlValueWithFlags::Int(n.into())),
+            Err(_) => anyhow::bail!("not a number: {content}"),
+        }
+    }
+
+    #[test]
+    fn picks_candidates() {
+        let candidates = vec![
+            candidate("not a number", -0.1),
+            candidate("1", -0.9),
+            candidate("2", -0.2),
+            candidate("2", -0.5),
+            candidate("not a number either", -0.01),
+        ];
+        let pick = |selection| select(selection, &candidates, &FinishReasonFilter::All, parse);
+
+        assert_eq!(pick(CandidateSelection::FirstParseSuccess), 1);
+        assert_eq!(pick(CandidateSelection::MajorityVote), 2);
+        assert_eq!(pick(CandidateSelection::HighestLogprob), 2);
+    }
+
+    #[test]
+    fn falls_back_to_the_first_candidate() {
+        let candidates = vec![candidate("a", -0.1), candidate("b", -0.2)];
+        for selection in [
+            CandidateSelection::FirstParseSuccess,
+            CandidateSelection::MajorityVote,
+            CandidateSelection::HighestLogprob,
+        ] {
+            assert_eq!(
+                select(selection, &candidates, &FinishReasonFilter::All, parse),
+                0
+            );
+        }
+
+        let denied = FinishReasonFilter::DenyList(["stop".to_string()].into_iter().collect());
+        let candidates = vec![candidate("1", -0.1), candidate("2", -0.2)];
+        assert_eq!(
+            select(
+                CandidateSelection::HighestLogprob,
+                &candidates,
+                &denied,
+                parse
+            ),
+            0
+        );
+    }
+}
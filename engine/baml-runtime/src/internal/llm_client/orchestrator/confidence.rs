@@ -0,0 +1,229 @@
+use baml_types::{BamlValue, FieldType};
+use indexmap::IndexMap;
+use internal_baml_core::ir::{repr::IntermediateRepr, IRHelper};
+
+use crate::{internal::llm_client::TokenLogprob, FunctionResult, RuntimeContext};
+
+/// A value of the enum a `@@confidence` function returns, with the strings
+/// the model may answer it with.
+#[derive(Debug)]
+pub(crate) struct Variant {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl Variant {
+    /// Whether the model could be starting to answer this value with `text`.
+    fn starts_with(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        std::iter::once(&self.name)
+            .chain(&self.alias)
+            .any(|answer| answer.to_lowercase().starts_with(&text))
+    }
+}
+
+/// The values of the enum `output` names, including ones added with a
+/// `TypeBuilder`, leaving out skipped ones.
+pub(crate) fn variants(
+    ir: &IntermediateRepr,
+    output: &FieldType,
+    ctx: &RuntimeContext,
+) -> Vec<Variant> {
+    let name = match output {
+        FieldType::Enum(name) => name,
+        FieldType::Constrained { base, .. } => return variants(ir, base, ctx),
+        _ => return vec![],
+    };
+    let eval_ctx = ctx.eval_ctx(false);
+    let mut variants = ir
+        .find_enum(name)
+        .map(|walker| {
+            walker
+                .walk_values()
+                .filter(|value| !value.skip(&eval_ctx).unwrap_or(false))
+                .map(|value| Variant {
+                    name: value.name().to_string(),
+                    alias: value.alias(&eval_ctx).ok().flatten(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if let Some(overrides) = ctx.enum_overrides.get(name) {
+        for (value, attributes) in &overrides.values {
+            variants.retain(|variant| &variant.name != value);
+            if attributes.skip == Some(true) {
+                continue;
+            }
+            variants.push(Variant {
+                name: value.clone(),
+                alias: match &attributes.alias {
+                    Some(BamlValue::String(alias)) => Some(alias.clone()),
+                    _ => None,
+                },
+            });
+        }
+    }
+    variants
+}
+
+/// The enum value a result parsed to.
+pub(crate) fn chosen_value(result: &FunctionResult) -> Option<String> {
+    match BamlValue::from(result.parsed_content().ok()?) {
+        BamlValue::Enum(_, value) | BamlValue::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Scores each value with the probabilities of the token the answer starts
+/// with: the first one that could start the chosen value. Each alternative
+/// in its top logprobs counts towards every value it could start, and the
+/// scores are normalized to sum to 1.
+///
+/// `None` if no token starts the chosen value, e.g. the answer was in a
+/// different case than the logprobs suggest.
+pub(crate) fn from_logprobs(
+    variants: &[Variant],
+    logprobs: &[TokenLogprob],
+    chosen: &str,
+) -> Option<IndexMap<String, f64>> {
+    let chosen = variants.iter().find(|variant| variant.name == chosen)?;
+    let token = logprobs.iter().find(|token| {
+        let text = answer_text(&token.token);
+        !text.is_empty() && chosen.starts_with(text)
+    })?;
+    let alternatives = if token.top_logprobs.is_empty() {
+        std::slice::from_ref(token)
+    } else {
+        token.top_logprobs.as_slice()
+    };
+
+    let mut scores = variants
+        .iter()
+        .map(|variant| (variant.name.clone(), 0.0))
+        .collect::<IndexMap<_, _>>();
+    for alternative in alternatives {
+        let text = answer_text(&alternative.token);
+        if text.is_empty() {
+            continue;
+        }
+        let matching = variants
+            .iter()
+            .filter(|variant| variant.starts_with(text))
+            .collect::<Vec<_>>();
+        let probability = alternative.logprob.exp() / matching.len().max(1) as f64;
+        for variant in matching {
+            scores[variant.name.as_str()] += probability;
+        }
+    }
+    normalize(scores)
+}
+
+/// Scores each value with the share of samples that parsed to it.
+pub(crate) fn from_samples(variants: &[Variant], samples: &[String]) -> IndexMap<String, f64> {
+    let mut scores = variants
+        .iter()
+        .map(|variant| (variant.name.clone(), 0.0))
+        .collect::<IndexMap<_, _>>();
+    for sample in samples {
+        *scores.entry(sample.clone()).or_default() += 1.0;
+    }
+    normalize(scores.clone()).unwrap_or(scores)
+}
+
+fn normalize(mut scores: IndexMap<String, f64>) -> Option<IndexMap<String, f64>> {
+    let total = scores.values().sum::<f64>();
+    if total <= 0.0 {
+        return None;
+    }
+    scores.values_mut().for_each(|score| *score /= total);
+    Some(scores)
+}
+
+/// A token without the whitespace and quotes around an answer.
+fn answer_text(token: &str) -> &str {
+    token
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variants() -> Vec<Variant> {
+        vec![
+            Variant {
+                name: "Positive".into(),
+                alias: None,
+            },
+            Variant {
+                name: "Negative".into(),
+                alias: None,
+            },
+            Variant {
+                name: "Neutral".into(),
+                alias: Some("Mixed".into()),
+            },
+        ]
+    }
+
+    fn token(token: &str, logprob: f64, top_logprobs: Vec<TokenLogprob>) -> TokenLogprob {
+        TokenLogprob {
+            token: token.into(),
+            logprob,
+            top_logprobs,
+        }
+    }
+
+    #[test]
+    fn scores_the_first_answer_token() {
+        let logprobs = vec![
+            token("\"", 0.0, vec![]),
+            token(
+                "Pos",
+                0.7f64.ln(),
+                vec![
+                    token("Pos", 0.7f64.ln(), vec![]),
+                    token(" Neg", 0.2f64.ln(), vec![]),
+                    token("Mixed", 0.1f64.ln(), vec![]),
+                ],
+            ),
+            token("itive", 0.0, vec![]),
+        ];
+        let scores = from_logprobs(&variants(), &logprobs, "Positive").unwrap();
+        assert_eq!(
+            scores.keys().collect::<Vec<_>>(),
+            ["Positive", "Negative", "Neutral"]
+        );
+        assert!((scores["Positive"] - 0.7).abs() < 1e-9);
+        assert!((scores["Negative"] - 0.2).abs() < 1e-9);
+        assert!((scores["Neutral"] - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ambiguous_tokens_are_split() {
+        let logprobs = vec![token(
+            "N",
+            0.5f64.ln(),
+            vec![
+                token("N", 0.5f64.ln(), vec![]),
+                token("Positive", 0.5f64.ln(), vec![]),
+            ],
+        )];
+        let scores = from_logprobs(&variants(), &logprobs, "Negative").unwrap();
+        assert!((scores["Positive"] - 0.5).abs() < 1e-9);
+        assert!((scores["Negative"] - 0.25).abs() < 1e-9);
+        assert!((scores["Neutral"] - 0.25).abs() < 1e-9);
+
+        assert!(from_logprobs(&variants(), &[token("{", 0.0, vec![])], "Negative").is_none());
+    }
+
+    #[test]
+    fn scores_samples() {
+        let samples = ["Positive", "Positive", "Neutral", "Positive"].map(String::from);
+        let scores = from_samples(&variants(), &samples);
+        assert_eq!(scores["Positive"], 0.75);
+        assert_eq!(scores["Negative"], 0.0);
+        assert_eq!(scores["Neutral"], 0.25);
+    }
+}
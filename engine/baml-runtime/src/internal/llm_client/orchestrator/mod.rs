@@ -1,21 +1,24 @@
 mod call;
+mod candidates;
+pub(crate) mod confidence;
+pub(crate) mod self_consistency;
 mod stream;
 
 use web_time::Duration; // Add this line
 
-use crate::RenderCurlSettings;
 use crate::{
     internal::prompt_renderer::PromptRenderer, runtime_interface::InternalClientLookup,
     RuntimeContext,
 };
+use crate::{DryRunRequest, RenderCurlSettings};
 
 use super::traits::{WithClientProperties, WithRenderRawCurl};
-use super::LLMCompleteResponse;
 use super::{
     strategy::roundrobin::RoundRobinStrategy,
     traits::{StreamResponse, WithPrompt, WithSingleCallable, WithStreamable},
     LLMResponse,
 };
+use super::{LLMCompleteResponse, LLMCompleteResponseMetadata};
 
 pub use super::primitive::LLMPrimitiveProvider;
 pub use call::orchestrate as orchestrate_call;
@@ -76,11 +79,90 @@ impl OrchestratorNode {
     }
 
     pub fn error_sleep_duration(&self) -> Option<&Duration> {
-        // in reverse find the first retry scope, and return the delay
-        self.scope.scope.iter().rev().find_map(|scope| match scope {
-            ExecutionScope::Retry(_, _, delay) if !delay.is_zero() => Some(delay),
-            _ => None,
-        })
+        self.scope.error_sleep_duration()
+    }
+
+    /// Tells the round-robin strategies that picked this node whether the
+    /// provider answered, for their health checks. Requests that never reached
+    /// the provider don't count.
+    pub fn record_outcome(&self, response: &LLMResponse) {
+        let succeeded = match response {
+            LLMResponse::Success(_) => true,
+            LLMResponse::LLMFailure(_) => false,
+            LLMResponse::UserFailure(_) | LLMResponse::InternalFailure(_) => return,
+        };
+        for scope in &self.scope.scope {
+            if let ExecutionScope::RoundRobin(strategy, index) = scope {
+                strategy.record_outcome(*index, succeeded);
+            }
+        }
+    }
+
+    /// Builds the request for `prompt` without sending it. Stands in for
+    /// [`WithSingleCallable::single_call`] and [`WithStreamable::stream`] in
+    /// dry runs, with an empty response.
+    pub async fn dry_run(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &RenderedPrompt,
+        stream: bool,
+    ) -> (LLMResponse, Option<DryRunRequest>) {
+        let RenderedPrompt::Chat(messages) = prompt else {
+            return (
+                LLMResponse::InternalFailure(
+                    "Dry runs can't build requests for completion prompts".to_string(),
+                ),
+                None,
+            );
+        };
+        let start_time = ctx.clock.now();
+        let settings = RenderCurlSettings {
+            stream,
+            as_shell_commands: false,
+        };
+        let request = match self.render_raw_curl(ctx, messages, settings).await {
+            Ok(request) => request,
+            Err(e) => {
+                return (
+                    LLMResponse::InternalFailure(format!("Failed to build request: {e:#}")),
+                    None,
+                )
+            }
+        };
+        let request_options = self.provider.request_options().clone();
+        let response = LLMCompleteResponse {
+            client: self.provider.name().to_string(),
+            model: request_options
+                .get("model")
+                .and_then(|model| model.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            prompt: prompt.clone(),
+            request_options,
+            content: String::new(),
+            start_time,
+            latency: Duration::ZERO,
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+        let request = DryRunRequest {
+            client: self.scope.name(),
+            prompt: prompt.clone(),
+            request,
+        };
+        (LLMResponse::Success(response), Some(request))
     }
 }
 
@@ -128,12 +210,34 @@ impl OrchestrationScope {
         }
     }
 
+    /// How long to wait before the next attempt if this one fails.
+    pub fn error_sleep_duration(&self) -> Option<&Duration> {
+        // in reverse find the first retry scope, and return the delay
+        self.scope.iter().rev().find_map(|scope| match scope {
+            ExecutionScope::Retry(_, _, delay) if !delay.is_zero() => Some(delay),
+            _ => None,
+        })
+    }
+
     pub fn direct_client_name(&self) -> Option<&String> {
         match self.scope.last() {
             Some(ExecutionScope::Direct(d)) => Some(d),
             _ => None,
         }
     }
+
+    /// Which retry of the innermost retry policy this is, `0` for the first
+    /// attempt.
+    pub fn retry_index(&self) -> usize {
+        self.scope
+            .iter()
+            .rev()
+            .find_map(|s| match s {
+                ExecutionScope::Retry(_, count, _) => Some(*count),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -149,6 +253,80 @@ pub enum ExecutionScope {
 
 pub type OrchestratorNodeIterator = Vec<OrchestratorNode>;
 
+/// The node to try after the one at `idx` failed with `response`, and how
+/// long to wait first. That's the next node after the retry delay, unless the
+/// innermost retry policy's `retry_on` doesn't cover the error: then the
+/// rest of its retries are skipped, without waiting.
+pub fn next_after_failure(
+    nodes: &[OrchestratorNode],
+    idx: usize,
+    response: &LLMResponse,
+    ir: &IntermediateRepr,
+) -> (usize, Option<Duration>) {
+    let node = &nodes[idx];
+    let next = (idx + 1, node.error_sleep_duration().copied());
+    let LLMResponse::LLMFailure(error) = response else {
+        return next;
+    };
+    let Some((pos, policy)) = node
+        .scope
+        .scope
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(pos, scope)| match scope {
+            ExecutionScope::Retry(policy, ..) => Some((pos, policy)),
+            _ => None,
+        })
+    else {
+        return next;
+    };
+    if ir
+        .retry_policy_by_name(policy)
+        .map_or(true, |p| p.retries(error.code.to_u16()))
+    {
+        return next;
+    }
+
+    // Retries of the same policy share the scopes around it.
+    let prefix = &node.scope.scope[..pos];
+    let same_policy = |other: &OrchestratorNode| {
+        other.scope.scope.len() > pos
+            && other.scope.scope[..pos]
+                .iter()
+                .zip(prefix)
+                .all(|(a, b)| a.to_string() == b.to_string())
+            && matches!(&other.scope.scope[pos], ExecutionScope::Retry(p, ..) if p == policy)
+    };
+    let skipped = nodes[idx + 1..]
+        .iter()
+        .take_while(|other| same_policy(other))
+        .count();
+    (idx + 1 + skipped, None)
+}
+
+/// How long the node at `idx` gets to answer before the next node is sent the
+/// same request, and how many of the following nodes may join the race, from
+/// the innermost retry policy's `hedge_after_ms` and `max_hedges`.
+pub fn hedging(
+    nodes: &[OrchestratorNode],
+    idx: usize,
+    ir: &IntermediateRepr,
+) -> Option<(Duration, usize)> {
+    let policy = nodes[idx]
+        .scope
+        .scope
+        .iter()
+        .rev()
+        .find_map(|scope| match scope {
+            ExecutionScope::Retry(policy, ..) => Some(policy),
+            _ => None,
+        })?;
+    let (after, max_hedges) = ir.retry_policy_by_name(policy)?.hedge()?;
+    let max_hedges = max_hedges.min(nodes.len() - idx - 1);
+    (max_hedges > 0).then_some((after, max_hedges))
+}
+
 #[derive(Default)]
 pub struct OrchestrationState {
     // Number of times a client was used so far
@@ -201,7 +379,9 @@ impl WithSingleCallable for OrchestratorNode {
             })
             .map(|a| a.increment_index())
             .for_each(drop);
-        self.provider.single_call(ctx, prompt).await
+        let response = self.provider.single_call(ctx, prompt).await;
+        ctx.hooks.after_response(&response);
+        response
     }
 }
 
@@ -237,6 +417,10 @@ impl WithClientProperties for OrchestratorNode {
         self.provider.finish_reason_filter()
     }
 
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        self.provider.candidate_selection()
+    }
+
     fn allowed_roles(&self) -> Vec<String> {
         self.provider.allowed_roles()
     }
@@ -0,0 +1,158 @@
+use anyhow::Result;
+use baml_types::BamlValue;
+use internal_baml_core::{
+    internal_baml_parser_database::VotingStrategy, ir::repr::SelfConsistency,
+};
+use jsonish::BamlValueWithFlags;
+
+use crate::FunctionResult;
+
+/// Combines the results of calling a `@@self_consistency` function once per
+/// sample into one. It has every sample's attempts, with the ones that parsed
+/// last, and parses to the vote among them. If none parsed, it's the last
+/// failure.
+pub(crate) fn combine(
+    self_consistency: &SelfConsistency,
+    samples: Vec<FunctionResult>,
+) -> Result<FunctionResult> {
+    let (parsed, failed): (Vec<_>, Vec<_>) = samples
+        .into_iter()
+        .partition(|sample| matches!(sample.parsed(), Some(Ok(_))));
+    let values = parsed
+        .iter()
+        .filter_map(|sample| sample.parsed().as_ref()?.as_ref().ok())
+        .collect::<Vec<_>>();
+    let voted = vote(self_consistency.strategy, &values);
+
+    let pii_audit = parsed
+        .last()
+        .or(failed.last())
+        .map(|sample| sample.pii_audit().to_vec())
+        .unwrap_or_default();
+    let chain = failed
+        .into_iter()
+        .chain(parsed)
+        .flat_map(FunctionResult::into_event_chain)
+        .collect();
+    let result = FunctionResult::new_chain(chain)?.with_pii_audit(pii_audit);
+    Ok(match voted {
+        Some(value) => result.with_parsed(value),
+        None => result,
+    })
+}
+
+/// The most common of `values`. With [`VotingStrategy::Majority`], when the
+/// most common value is a class, each of its fields is the most common value
+/// of that field among the values of the same class.
+///
+/// Ties go to the value that came first.
+fn vote(strategy: VotingStrategy, values: &[&BamlValueWithFlags]) -> Option<BamlValueWithFlags> {
+    let winner = most_common(values)?;
+    match (strategy, winner) {
+        (VotingStrategy::Majority, BamlValueWithFlags::Class(name, conditions, fields)) => {
+            let same_class = values
+                .iter()
+                .filter_map(|value| match value {
+                    BamlValueWithFlags::Class(other, _, fields) if other == name => Some(fields),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let fields = fields
+                .iter()
+                .map(|(key, value)| {
+                    let candidates = same_class
+                        .iter()
+                        .filter_map(|fields| fields.get(key))
+                        .collect::<Vec<_>>();
+                    let value = vote(strategy, &candidates).unwrap_or_else(|| value.clone());
+                    (key.clone(), value)
+                })
+                .collect();
+            Some(BamlValueWithFlags::Class(
+                name.clone(),
+                conditions.clone(),
+                fields,
+            ))
+        }
+        (_, winner) => Some(winner.clone()),
+    }
+}
+
+fn most_common<'a>(values: &[&'a BamlValueWithFlags]) -> Option<&'a BamlValueWithFlags> {
+    // (first value with the same content, content, votes)
+    let mut votes: Vec<(&BamlValueWithFlags, BamlValue, usize)> = vec![];
+    for value in values {
+        let content = BamlValue::from(*value);
+        match votes.iter_mut().find(|(_, other, _)| *other == content) {
+            Some((_, _, count)) => *count += 1,
+            None => votes.push((*value, content, 1)),
+        }
+    }
+    votes
+        .into_iter()
+        .fold(
+            None,
+            |best: Option<(&BamlValueWithFlags, usize)>, (value, _, count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((value, count)),
+            },
+        )
+        .map(|(value, _)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonish::deserializer::deserialize_flags::DeserializerConditions;
+
+    use super::*;
+
+    fn answer(value: i64, unit: &str) -> BamlValueWithFlags {
+        BamlValueWithFlags::Class(
+            "Answer".into(),
+            DeserializerConditions::new(),
+            [
+                ("value".to_string(), BamlValueWithFlags::Int(value.into())),
+                (
+                    "unit".to_string(),
+                    BamlValueWithFlags::String(unit.to_string().into()),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn most_common_value_wins() {
+        let values = [1, 2, 2, 3, 3].map(|n| BamlValueWithFlags::Int(n.into()));
+        let values = values.iter().collect::<Vec<_>>();
+        for strategy in [VotingStrategy::Majority, VotingStrategy::MostCommon] {
+            assert_eq!(
+                vote(strategy, &values).map(BamlValue::from),
+                Some(BamlValue::Int(2))
+            );
+        }
+        assert!(vote(VotingStrategy::Majority, &[]).is_none());
+    }
+
+    #[test]
+    fn majority_merges_classes_field_by_field() {
+        let values = [
+            answer(42, "m"),
+            answer(42, "km"),
+            answer(7, "km"),
+            answer(42, "cm"),
+        ];
+        let values = values.iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            vote(VotingStrategy::Majority, &values).map(BamlValue::from),
+            Some(BamlValue::from(answer(42, "km")))
+        );
+        // No whole answer is more common than another, so the first one wins.
+        assert_eq!(
+            vote(VotingStrategy::MostCommon, &values).map(BamlValue::from),
+            Some(BamlValue::from(answer(42, "m")))
+        );
+    }
+}
@@ -6,24 +6,28 @@ use jsonish::BamlValueWithFlags;
 use web_time::Duration;
 
 use crate::{
+    clock::Stopwatch,
+    errors::ExposedError,
     internal::{
         llm_client::{
             parsed_value_to_response,
             traits::{WithClientProperties, WithPrompt, WithStreamable},
-            LLMErrorResponse, LLMResponse, ResponseBamlValue,
+            LLMCompleteResponse, LLMErrorResponse, LLMResponse, LLMStreamEvent,
+            ResponseBamlValue,
         },
         prompt_renderer::PromptRenderer,
     },
-    FunctionResult, RuntimeContext,
+    pii::MaskedSpan,
+    DryRunRequest, FunctionResult, RuntimeContext,
 };
 
-use super::{OrchestrationScope, OrchestratorNodeIterator};
+use super::{next_after_failure, OrchestrationScope, OrchestratorNodeIterator};
 
 pub async fn orchestrate_stream<F>(
     iter: OrchestratorNodeIterator,
     ir: &IntermediateRepr,
     ctx: &RuntimeContext,
-    prompt: &PromptRenderer,
+    renderer: &PromptRenderer,
     params: &BamlValue,
     partial_parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
     parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
@@ -36,68 +40,131 @@ pub async fn orchestrate_stream<F>(
         Option<Result<ResponseBamlValue>>,
     )>,
     Duration,
+    Vec<MaskedSpan>,
+    Option<DryRunRequest>,
 )
 where
     F: Fn(FunctionResult),
 {
     let mut results = Vec::new();
     let mut total_sleep_duration = std::time::Duration::from_secs(0);
+    let mut pii_audit = vec![];
+    let mut dry_run_request = None;
 
     //advanced curl viewing, use render_raw_curl on each node. TODO
-    for node in iter {
-        let prompt = match node.render_prompt(ir, prompt, ctx, params).await {
-            Ok(p) => p,
+    let mut idx = 0;
+    while let Some(node) = iter.get(idx) {
+        let (prompt, pii) = match node.render_prompt(ir, renderer, ctx, params).await {
+            Ok(p) => renderer.mask_pii(p),
             Err(e) => {
                 results.push((
-                    node.scope,
+                    node.scope.clone(),
                     LLMResponse::InternalFailure(e.to_string()),
                     None,
                     None,
                 ));
+                if ctx.dry_run {
+                    break;
+                }
+                idx += 1;
                 continue;
             }
         };
 
-        let (system_start, instant_start) = (web_time::SystemTime::now(), web_time::Instant::now());
+        if ctx.dry_run {
+            let (response, request) = node.dry_run(ctx, &prompt, true).await;
+            pii_audit = pii.spans().to_vec();
+            results.push((node.scope.clone(), response, None, None));
+            dry_run_request = request;
+            break;
+        }
+
+        let (system_start, instant_start) = (ctx.clock.now(), Stopwatch::start(&ctx.clock));
         let stream_res = node.stream(ctx, &prompt).await;
+        // Only the text deltas travel through the stream; the partial content
+        // handed to `on_event` is accumulated here.
+        let mut content = String::new();
         let final_response = match stream_res {
-            Ok(response) => response
-                .map(|stream_part| {
-                    if let Some(on_event) = on_event.as_ref() {
-                        if let LLMResponse::Success(s) = &stream_part {
-                            let parsed = partial_parse_fn(&s.content);
-                            let (parsed, response_value) = match parsed {
-                                Ok(v) => {
-                                    (Some(Ok(v.clone())), Some(Ok(parsed_value_to_response(&v))))
-                                }
-                                Err(e) => (None, Some(Err(e))),
-                            };
-                            on_event(FunctionResult::new(
-                                node.scope.clone(),
-                                LLMResponse::Success(s.clone()),
-                                parsed,
-                                response_value,
-                            ));
+            Ok(mut response) => {
+                let mut final_response = None;
+                while let Some(event) = response.next().await {
+                    match event {
+                        LLMStreamEvent::Delta {
+                            text,
+                            model,
+                            metadata,
+                        } => {
+                            content.push_str(&text);
+                            if let Some(on_event) = on_event.as_ref() {
+                                let parsed = partial_parse_fn(&pii.restore(&content));
+                                let (parsed, response_value) = match parsed {
+                                    Ok(v) => (
+                                        Some(Ok(v.clone())),
+                                        Some(Ok(parsed_value_to_response(&v))),
+                                    ),
+                                    Err(e) => (None, Some(Err(e))),
+                                };
+                                on_event(FunctionResult::new(
+                                    node.scope.clone(),
+                                    LLMResponse::Success(LLMCompleteResponse {
+                                        client: node.provider.name().into(),
+                                        model,
+                                        prompt: prompt.clone(),
+                                        request_options: node.provider.request_options().clone(),
+                                        content: content.clone(),
+                                        start_time: system_start,
+                                        latency: instant_start.elapsed(),
+                                        metadata,
+                                    }),
+                                    parsed,
+                                    response_value,
+                                ));
+                            }
+                        }
+                        LLMStreamEvent::Done(response) => {
+                            final_response = Some(response);
+                            break;
                         }
                     }
-                    stream_part
-                })
-                .fold(None, |_, current| Some(current))
-                .await
-                .unwrap_or_else(|| {
+                }
+                final_response.unwrap_or_else(|| {
                     LLMResponse::LLMFailure(LLMErrorResponse {
                         client: node.provider.name().into(),
                         model: None,
-                        prompt,
+                        prompt: prompt.clone(),
                         start_time: system_start,
                         latency: instant_start.elapsed(),
                         request_options: node.provider.request_options().clone(),
                         message: "Stream ended without response".to_string(),
                         code: crate::internal::llm_client::ErrorCode::from_u16(2),
                     })
-                }),
+                })
+            }
             Err(response) => response,
         };
+        node.record_outcome(&final_response);
+        ctx.hooks.after_response(&final_response);
+
+        // Once content streamed in, falling back would mix the partial results
+        // of two responses, so the call fails with what it got so far.
+        if !content.is_empty() && !matches!(final_response, LLMResponse::Success(_)) {
+            let content = pii.restore(&content);
+            let partial = partial_parse_fn(&content).ok().map(BamlValue::from);
+            let error = ExposedError::PartialFailure {
+                prompt: prompt.to_string(),
+                message: failure_message(&final_response),
+                raw_output: content,
+                partial,
+            };
+            pii_audit = pii.spans().to_vec();
+            results.push((
+                node.scope.clone(),
+                final_response,
+                None,
+                Some(Err(anyhow::anyhow!(error))),
+            ));
+            break;
+        }
 
         let parsed_response = match &final_response {
             LLMResponse::Success(s) => {
@@ -105,26 +172,37 @@ where
                     .finish_reason_filter()
                     .is_allowed(s.metadata.finish_reason.as_ref())
                 {
-                    Some(Err(anyhow::anyhow!(crate::errors::ExposedError::FinishReasonError {
+                    Some(Err(anyhow::anyhow!(ExposedError::FinishReasonError {
                         prompt: s.prompt.to_string(),
                         raw_output: s.content.clone(),
                         message: "Finish reason not allowed".to_string(),
                         finish_reason: s.metadata.finish_reason.clone(),
                     })))
+                } else if s.metadata.structured_output {
+                    Some(renderer.parse_json(&pii.restore(&s.content)))
                 } else {
-                    Some(parse_fn(&s.content))
+                    Some(parse_fn(&pii.restore(&s.content)))
                 }
             },
             _ => None,
         };
+        if let (LLMResponse::Success(s), Some(Err(e))) = (&final_response, &parsed_response) {
+            ctx.hooks.parse_error(s, e);
+        }
         let (parsed_response, response_value) = match parsed_response {
             Some(Ok(v)) => (Some(Ok(v.clone())), Some(Ok(parsed_value_to_response(&v)))),
             Some(Err(e)) => (None, Some(Err(e))),
             None => (None, None),
         };
         // parsed_response.map(|r| r.and_then(|v| parsed_value_to_response(v)));
-        let sleep_duration = node.error_sleep_duration().cloned();
-        results.push((node.scope, final_response, parsed_response, response_value));
+        pii_audit = pii.spans().to_vec();
+        let (next_idx, sleep_duration) = next_after_failure(&iter, idx, &final_response, ir);
+        results.push((
+            node.scope.clone(),
+            final_response,
+            parsed_response,
+            response_value,
+        ));
 
         // Currently, we break out of the loop if an LLM responded, even if we couldn't parse the result.
         if results
@@ -134,9 +212,20 @@ where
             break;
         } else if let Some(duration) = sleep_duration {
             total_sleep_duration += duration;
-            async_std::task::sleep(duration).await;
+            ctx.clock.sleep(duration).await;
         }
+        idx = next_idx;
     }
 
-    (results, total_sleep_duration)
+    (results, total_sleep_duration, pii_audit, dry_run_request)
+}
+
+fn failure_message(response: &LLMResponse) -> String {
+    match response {
+        LLMResponse::LLMFailure(e) => format!("{} ({})", e.message, e.code),
+        LLMResponse::UserFailure(message) | LLMResponse::InternalFailure(message) => {
+            message.clone()
+        }
+        LLMResponse::Success(_) => String::new(),
+    }
 }
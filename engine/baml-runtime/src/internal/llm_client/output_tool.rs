@@ -0,0 +1,140 @@
+use baml_types::FieldType;
+use internal_baml_core::ir::{repr::IntermediateRepr, standalone_json_schema};
+use serde_json::json;
+
+/// A function's output type as the parameters of a tool, for clients with
+/// `tools true` to ask for the output as a call to it.
+#[derive(Debug, Clone)]
+pub(crate) struct OutputTool {
+    /// The function's name.
+    pub name: String,
+    /// The JSON schema of the tool's parameters.
+    pub parameters: serde_json::Value,
+    /// Tool parameters must be an object, so outputs that aren't a class are
+    /// the `value` of one.
+    wrapped: bool,
+}
+
+impl OutputTool {
+    pub fn new(ir: &IntermediateRepr, name: &str, output: &FieldType) -> Self {
+        let mut schema = standalone_json_schema(ir, output);
+        let definitions = schema
+            .as_object_mut()
+            .and_then(|schema| schema.remove("definitions"));
+
+        let class = class_name(output).and_then(|class| definitions.as_ref()?.get(class));
+        let (mut parameters, wrapped) = match class {
+            Some(class) => (class.clone(), false),
+            None => (
+                json!({
+                    "type": "object",
+                    "properties": { "value": schema },
+                    "required": ["value"],
+                }),
+                true,
+            ),
+        };
+        if let (Some(definitions), serde_json::Value::Object(parameters)) =
+            (definitions, &mut parameters)
+        {
+            parameters.insert("definitions".into(), definitions);
+        }
+        OutputTool {
+            name: name.to_string(),
+            parameters,
+            wrapped,
+        }
+    }
+
+    /// Whether the output is the `value` of the parameters, because it isn't
+    /// a class.
+    pub fn is_wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    /// The output in the `arguments` of a call to the tool, to parse like a
+    /// response.
+    pub fn output(&self, arguments: &str) -> String {
+        if !self.wrapped {
+            return arguments.to_string();
+        }
+        let value = serde_json::from_str::<serde_json::Value>(arguments)
+            .ok()
+            .and_then(|mut arguments| arguments.get_mut("value").map(serde_json::Value::take));
+        match value {
+            Some(serde_json::Value::String(value)) => value,
+            Some(value) => value.to_string(),
+            // Leave anything unexpected to the parser, which is lenient.
+            None => arguments.to_string(),
+        }
+    }
+}
+
+fn class_name(output: &FieldType) -> Option<&str> {
+    match output {
+        FieldType::Class(name) => Some(name),
+        FieldType::Constrained { base, .. } => class_name(base),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use internal_baml_core::ir::repr::make_test_ir;
+
+    use super::*;
+
+    fn ir() -> IntermediateRepr {
+        make_test_ir(
+            r##"
+            enum Category {
+                Bug
+                Feature
+            }
+
+            class Ticket {
+                title string
+                category Category
+            }
+            "##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn classes_are_the_parameters() {
+        let tool = OutputTool::new(&ir(), "ExtractTicket", &FieldType::class("Ticket"));
+        assert_eq!(tool.parameters["type"], "object");
+        assert_eq!(tool.parameters["required"], json!(["title", "category"]));
+        assert_eq!(
+            tool.parameters["properties"]["category"]["$ref"],
+            "#/definitions/Category"
+        );
+        assert!(tool.parameters["definitions"]["Category"].is_object());
+
+        let arguments = r#"{"title": "Crash on save", "category": "Bug"}"#;
+        assert_eq!(tool.output(arguments), arguments);
+    }
+
+    #[test]
+    fn other_outputs_are_wrapped() {
+        let tool = OutputTool::new(
+            &ir(),
+            "ExtractTickets",
+            &FieldType::list(FieldType::class("Ticket")),
+        );
+        assert_eq!(tool.parameters["required"], json!(["value"]));
+        assert_eq!(
+            tool.parameters["properties"]["value"]["items"]["$ref"],
+            "#/definitions/Ticket"
+        );
+        assert_eq!(
+            tool.output(r#"{"value": [{"title": "Crash", "category": "Bug"}]}"#),
+            r#"[{"title":"Crash","category":"Bug"}]"#
+        );
+
+        let tool = OutputTool::new(&ir(), "Classify", &FieldType::r#enum("Category"));
+        assert_eq!(tool.output(r#"{"value": "Feature"}"#), "Feature");
+        assert_eq!(tool.output("Feature"), "Feature");
+    }
+}
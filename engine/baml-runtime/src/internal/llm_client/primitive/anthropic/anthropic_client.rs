@@ -1,3 +1,4 @@
+use crate::internal::llm_client::image_preprocessing::{self, ImagePreprocessing};
 use crate::internal::llm_client::{
     traits::{ToProviderMessage, ToProviderMessageExt, WithClientProperties},
     ResolveMediaUrls,
@@ -13,25 +14,25 @@ use internal_baml_jinja::{
     ChatMessagePart, RenderContext_Client, RenderedChatMessage, RenderedPrompt,
 };
 use internal_llm_client::{
-    anthropic::ResolvedAnthropic, AllowedRoleMetadata, ClientProvider, ResolvedClientProperty,
-    UnresolvedClientProperty,
+    anthropic::{CachePolicy, ResolvedAnthropic},
+    AllowedRoleMetadata, ClientProvider, ResolvedClientProperty, UnresolvedClientProperty,
 };
 
 use crate::{
     client_registry::ClientProperty,
     internal::llm_client::{
         primitive::{
-            anthropic::types::{AnthropicMessageResponse, StopReason},
+            anthropic::types::{AnthropicMessageResponse, ContentType, StopReason},
             request::{make_parsed_request, make_request, RequestBuilder},
         },
         traits::{
-            SseResponseTrait, StreamResponse, WithChat, WithClient, WithNoCompletion,
-            WithRetryPolicy, WithStreamChat,
+            accumulate_stream, SseResponseTrait, StreamResponse, WithChat, WithClient,
+            WithNoCompletion, WithRetryPolicy, WithStreamChat,
         },
         ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
         ModelFeatures,
     },
-    request::create_client,
+    request::{create_client_with_options, json_body},
 };
 use serde_json::json;
 
@@ -89,6 +90,9 @@ impl WithClientProperties for AnthropicClient {
     fn finish_reason_filter(&self) -> &internal_llm_client::FinishReasonFilter {
         &self.properties.finish_reason_filter
     }
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        internal_llm_client::CandidateSelection::default()
+    }
     fn default_role(&self) -> String {
         self.properties.default_role()
     }
@@ -116,129 +120,131 @@ impl SseResponseTrait for AnthropicClient {
         resp: reqwest::Response,
         prompt: &[RenderedChatMessage],
         system_start: web_time::SystemTime,
-        instant_start: web_time::Instant,
+        instant_start: crate::clock::Stopwatch,
     ) -> StreamResponse {
         let prompt = prompt.to_vec();
         let client_name = self.context.name.clone();
         let params = self.properties.properties.clone();
 
-        Ok(Box::pin(
-            resp.bytes_stream()
-                .inspect(|event| log::trace!("anthropic event bytes: {:#?}", event))
-                .eventsource()
-                .map(|event| -> Result<MessageChunk> { Ok(serde_json::from_str(&event?.data)?) })
-                .inspect(|event| log::trace!("anthropic eventsource: {:#?}", event))
-                .scan(
-                    Ok(LLMCompleteResponse {
-                        client: client_name.clone(),
-                        prompt: RenderedPrompt::Chat(prompt.clone()),
-                        content: "".to_string(),
-                        start_time: system_start,
-                        latency: instant_start.elapsed(),
-                        model: "".to_string(),
-                        request_options: params.clone(),
-                        metadata: LLMCompleteResponseMetadata {
-                            baml_is_complete: false,
-                            finish_reason: None,
-                            prompt_tokens: None,
-                            output_tokens: None,
-                            total_tokens: None,
-                        },
-                    }),
-                    move |accumulated: &mut Result<LLMCompleteResponse>, event| {
-                        let Ok(ref mut inner) = accumulated else {
-                            return std::future::ready(None);
-                        };
-                        let event = match event {
-                            Ok(event) => event,
-                            Err(e) => {
-                                return std::future::ready(Some(LLMResponse::LLMFailure(
-                                    LLMErrorResponse {
-                                        client: client_name.clone(),
-                                        model: if inner.model.is_empty() {
-                                            None
-                                        } else {
-                                            Some(inner.model.clone())
-                                        },
-                                        prompt: internal_baml_jinja::RenderedPrompt::Chat(
-                                            prompt.clone(),
-                                        ),
-                                        request_options: params.clone(),
-                                        start_time: system_start,
-                                        latency: instant_start.elapsed(),
-                                        message: format!("Failed to parse event: {:#?}", e),
-                                        code: ErrorCode::UnsupportedResponse(2),
-                                    },
-                                )));
-                            }
-                        };
-                        match event {
-                            MessageChunk::MessageStart(chunk) => {
-                                let body = chunk.message;
-                                inner.model = body.model;
-                                let inner = &mut inner.metadata;
-                                inner.baml_is_complete = matches!(
-                                    body.stop_reason,
-                                    Some(StopReason::StopSequence) | Some(StopReason::EndTurn)
-                                );
-                                inner.finish_reason =
-                                    body.stop_reason.as_ref().map(ToString::to_string);
-                                inner.prompt_tokens = Some(body.usage.input_tokens);
-                                inner.output_tokens = Some(body.usage.output_tokens);
-                                inner.total_tokens =
-                                    Some(body.usage.input_tokens + body.usage.output_tokens);
-                            }
-                            MessageChunk::ContentBlockDelta(event) => {
-                                inner.content += &event.delta.text;
-                            }
-                            MessageChunk::ContentBlockStart(_) => (),
-                            MessageChunk::ContentBlockStop(_) => (),
-                            MessageChunk::Ping => (),
-                            MessageChunk::MessageDelta(body) => {
-                                let inner = &mut inner.metadata;
-
-                                inner.baml_is_complete = matches!(
-                                    body.delta.stop_reason,
-                                    Some(StopReason::StopSequence) | Some(StopReason::EndTurn)
-                                );
-                                inner.finish_reason = body
-                                    .delta
-                                    .stop_reason
-                                    .as_ref()
-                                    .map(|r| serde_json::to_string(r).unwrap_or("".into()));
-                                inner.output_tokens = Some(body.usage.output_tokens);
-                                inner.total_tokens = Some(
-                                    inner.prompt_tokens.unwrap_or(0) + body.usage.output_tokens,
-                                );
-                            }
-                            MessageChunk::MessageStop => (),
-                            MessageChunk::Error(err) => {
-                                return std::future::ready(Some(LLMResponse::LLMFailure(
-                                    LLMErrorResponse {
-                                        client: client_name.clone(),
-                                        model: if inner.model.is_empty() {
-                                            None
-                                        } else {
-                                            Some(inner.model.clone())
-                                        },
-                                        prompt: internal_baml_jinja::RenderedPrompt::Chat(
-                                            prompt.to_vec(),
-                                        ),
-                                        request_options: params.clone(),
-                                        start_time: system_start,
-                                        latency: instant_start.elapsed(),
-                                        message: err.message,
-                                        code: ErrorCode::Other(2),
-                                    },
-                                )));
-                            }
-                        };
-
-                        inner.latency = instant_start.elapsed();
-                        std::future::ready(Some(LLMResponse::Success(inner.clone())))
+        let events = resp
+            .bytes_stream()
+            .inspect(|event| log::trace!("anthropic event bytes: {:#?}", event))
+            .eventsource()
+            .map(|event| -> Result<MessageChunk> { Ok(serde_json::from_str(&event?.data)?) })
+            .inspect(|event| log::trace!("anthropic eventsource: {:#?}", event));
+        let initial = LLMCompleteResponse {
+            client: client_name.clone(),
+            prompt: RenderedPrompt::Chat(prompt.clone()),
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: "".to_string(),
+            request_options: params.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+
+        Ok(Box::pin(accumulate_stream(
+            events,
+            initial,
+            instant_start.clone(),
+            move |inner, event| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                            client: client_name.clone(),
+                            model: if inner.model.is_empty() {
+                                None
+                            } else {
+                                Some(inner.model.clone())
+                            },
+                            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                            request_options: params.clone(),
+                            start_time: system_start,
+                            latency: instant_start.elapsed(),
+                            message: format!("Failed to parse event: {:#?}", e),
+                            code: ErrorCode::UnsupportedResponse(2),
+                        }));
+                    }
+                };
+                match event {
+                    MessageChunk::MessageStart(chunk) => {
+                        let body = chunk.message;
+                        inner.model = body.model;
+                        let inner = &mut inner.metadata;
+                        inner.baml_is_complete = matches!(
+                            body.stop_reason,
+                            Some(StopReason::StopSequence) | Some(StopReason::EndTurn)
+                        );
+                        inner.finish_reason = body.stop_reason.as_ref().map(ToString::to_string);
+                        inner.prompt_tokens = Some(body.usage.input_tokens);
+                        inner.output_tokens = Some(body.usage.output_tokens);
+                        inner.total_tokens =
+                            Some(body.usage.input_tokens + body.usage.output_tokens);
+                        inner.cached_input_tokens = body.usage.cache_read_input_tokens;
+                    }
+                    MessageChunk::ContentBlockDelta(event) => match event.delta._type {
+                        ContentType::ThinkingDelta => {
+                            let thinking = inner.metadata.thinking.get_or_insert_with(String::new);
+                            thinking.push_str(event.delta.thinking.as_deref().unwrap_or_default());
+                        }
+                        ContentType::SignatureDelta => (),
+                        _ => return Ok(Some(event.delta.text)),
                     },
-                ),
-        ))
+                    MessageChunk::ContentBlockStart(_) => (),
+                    MessageChunk::ContentBlockStop(_) => (),
+                    MessageChunk::Ping => (),
+                    MessageChunk::MessageDelta(body) => {
+                        let inner = &mut inner.metadata;
+
+                        inner.baml_is_complete = matches!(
+                            body.delta.stop_reason,
+                            Some(StopReason::StopSequence) | Some(StopReason::EndTurn)
+                        );
+                        inner.finish_reason = body
+                            .delta
+                            .stop_reason
+                            .as_ref()
+                            .map(|r| serde_json::to_string(r).unwrap_or("".into()));
+                        inner.output_tokens = Some(body.usage.output_tokens);
+                        inner.total_tokens =
+                            Some(inner.prompt_tokens.unwrap_or(0) + body.usage.output_tokens);
+                    }
+                    MessageChunk::MessageStop => (),
+                    MessageChunk::Error(err) => {
+                        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                            client: client_name.clone(),
+                            model: if inner.model.is_empty() {
+                                None
+                            } else {
+                                Some(inner.model.clone())
+                            },
+                            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
+                            request_options: params.clone(),
+                            start_time: system_start,
+                            latency: instant_start.elapsed(),
+                            message: err.message,
+                            code: ErrorCode::Other(2),
+                        }));
+                    }
+                };
+
+                Ok(None)
+            },
+        )))
     }
 }
 
@@ -246,11 +252,11 @@ impl SseResponseTrait for AnthropicClient {
 impl WithStreamChat for AnthropicClient {
     async fn stream_chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         prompt: &[RenderedChatMessage],
     ) -> StreamResponse {
         let (response, system_now, instant_now) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
@@ -276,9 +282,13 @@ impl AnthropicClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::ANTHROPIC_MAX_DIMENSION,
+                ),
             },
             retry_policy: client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client_with_options(&properties.http)?,
             properties,
         })
     }
@@ -299,16 +309,73 @@ impl AnthropicClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::ANTHROPIC_MAX_DIMENSION,
+                ),
             },
             retry_policy: client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client_with_options(&properties.http)?,
             properties,
         })
     }
+
+    /// `parts` as content blocks. Anthropic allows only a few cache
+    /// breakpoints per request, so `cache_control` set with `_.role()` goes on
+    /// the message's last block instead of on each of them, whether or not
+    /// it's in `allowed_role_metadata`.
+    fn content_blocks(
+        &self,
+        parts: &[ChatMessagePart],
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>> {
+        let mut blocks = self.parts_to_message(parts)?;
+        let cache_control = parts
+            .iter()
+            .rev()
+            .find_map(|part| part.meta()?.get("cache_control"))
+            .filter(|cache_control| !cache_control.is_null());
+        for block in &mut blocks {
+            block.remove("cache_control");
+        }
+        if let (Some(cache_control), Some(last)) = (cache_control, blocks.last_mut()) {
+            last.insert("cache_control".into(), cache_control.clone());
+        }
+        Ok(blocks)
+    }
+}
+
+/// Adds the cache breakpoints `policy` asks for to a messages request body,
+/// keeping any `cache_control` the prompt already set on those blocks.
+fn add_cache_breakpoints(
+    body: &mut serde_json::Map<String, serde_json::Value>,
+    policy: CachePolicy,
+) {
+    fn mark_last_block(blocks: Option<&mut serde_json::Value>) {
+        if let Some(block) = blocks
+            .and_then(serde_json::Value::as_array_mut)
+            .and_then(|blocks| blocks.last_mut())
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            block
+                .entry("cache_control")
+                .or_insert_with(|| json!({ "type": "ephemeral" }));
+        }
+    }
+
+    if matches!(policy, CachePolicy::System | CachePolicy::Prompt) {
+        mark_last_block(body.get_mut("system"));
+    }
+    if policy == CachePolicy::Prompt {
+        let last_message = body
+            .get_mut("messages")
+            .and_then(serde_json::Value::as_array_mut)
+            .and_then(|messages| messages.last_mut());
+        mark_last_block(last_message.and_then(|message| message.get_mut("content")));
+    }
 }
 
 // how to build the HTTP request for requests
@@ -317,8 +384,13 @@ impl RequestBuilder for AnthropicClient {
         &self.client
     }
 
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        &self.properties.http
+    }
+
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &[RenderedChatMessage]>,
         allow_proxy: bool,
         stream: bool,
@@ -361,7 +433,20 @@ impl RequestBuilder for AnthropicClient {
             body_obj.insert("stream".into(), true.into());
         }
 
-        Ok(req.json(&body))
+        // An explicit `metadata.user_id` in the client options takes precedence.
+        if let Some(user_id) = self
+            .properties
+            .user_id_tag
+            .as_ref()
+            .and_then(|tag| ctx.tag_string(tag))
+        {
+            let metadata = body_obj.entry("metadata").or_insert_with(|| json!({}));
+            if let Some(metadata) = metadata.as_object_mut() {
+                metadata.entry("user_id").or_insert(json!(user_id));
+            }
+        }
+
+        json_body(req, &body, self.properties.http.compress_requests)
     }
 
     fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
@@ -370,19 +455,21 @@ impl RequestBuilder for AnthropicClient {
 }
 
 impl WithChat for AnthropicClient {
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
-        let (response, system_now, instant_now) = match make_parsed_request::<
-            AnthropicMessageResponse,
-        >(
-            self, either::Either::Right(prompt), false
-        )
-        .await
-        {
-            Ok(v) => v,
-            Err(e) => return e,
-        };
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
+        let (response, raw_response, system_now, instant_now) =
+            match make_parsed_request::<AnthropicMessageResponse>(
+                self,
+                ctx,
+                either::Either::Right(prompt),
+                false,
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
 
-        if response.content.len() != 1 {
+        let Some(content) = response.text() else {
             return LLMResponse::LLMFailure(LLMErrorResponse {
                 client: self.context.name.to_string(),
                 model: None,
@@ -391,17 +478,22 @@ impl WithChat for AnthropicClient {
                 request_options: self.properties.properties.clone(),
                 latency: instant_now.elapsed(),
                 message: format!(
-                    "Expected exactly one content block, got {}",
-                    response.content.len()
+                    "Expected a text content block, got {}",
+                    response
+                        .content
+                        .iter()
+                        .map(|c| c.r#type.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
                 ),
                 code: ErrorCode::Other(200),
             });
-        }
+        };
 
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
-            content: response.content[0].text.clone(),
+            content,
             start_time: system_now,
             latency: instant_now.elapsed(),
             request_options: self.properties.properties.clone(),
@@ -418,6 +510,13 @@ impl WithChat for AnthropicClient {
                 prompt_tokens: Some(response.usage.input_tokens),
                 output_tokens: Some(response.usage.output_tokens),
                 total_tokens: Some(response.usage.input_tokens + response.usage.output_tokens),
+                cached_input_tokens: response.usage.cache_read_input_tokens,
+                grounding: None,
+                thinking: response.thinking(),
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: self.properties.capture_raw_response.then_some(raw_response),
             },
         })
     }
@@ -448,6 +547,13 @@ impl ToProviderMessage for AnthropicClient {
                 source.insert("data".into(), data.base64.clone().into());
                 content.insert("source".into(), source.into());
             }
+            BamlMediaContent::FileRef(file_ref) => {
+                content.insert("type".into(), media.media_type.to_string().into());
+                content.insert(
+                    "source".into(),
+                    json!({ "type": "file", "file_id": file_ref.file_id }),
+                );
+            }
             BamlMediaContent::File(_) => {
                 anyhow::bail!(
                     "BAML internal error (Anthropic): file should have been resolved to base64"
@@ -470,7 +576,7 @@ impl ToProviderMessage for AnthropicClient {
         map.insert("role".into(), content.role.clone().into());
         map.insert(
             "content".into(),
-            json!(self.parts_to_message(&content.parts)?),
+            json!(self.content_blocks(&content.parts)?),
         );
         Ok(map)
     }
@@ -483,32 +589,23 @@ impl ToProviderMessageExt for AnthropicClient {
     ) -> Result<serde_json::Map<String, serde_json::Value>> {
         // merge all adjacent roles of the same type
         let mut res = serde_json::Map::new();
-        let (first, others) = chat.split_at(1);
-        if let Some(content) = first.first() {
-            if content.role == "system" {
-                res.insert(
-                    "system".into(),
-                    json!(self.parts_to_message(&content.parts)?),
-                );
-                res.insert(
-                    "messages".into(),
-                    others
-                        .iter()
-                        .map(|c| self.role_to_message(c))
-                        .collect::<Result<Vec<_>>>()?
-                        .into(),
-                );
-                return Ok(res);
+        let messages = match chat.split_first() {
+            Some((system, others)) if system.role == "system" => {
+                res.insert("system".into(), json!(self.content_blocks(&system.parts)?));
+                others
             }
-        }
+            _ => chat,
+        };
 
         res.insert(
             "messages".into(),
-            chat.iter()
+            messages
+                .iter()
                 .map(|c| self.role_to_message(c))
                 .collect::<Result<Vec<_>>>()?
                 .into(),
         );
+        add_cache_breakpoints(&mut res, self.properties.cache_policy);
 
         Ok(res)
     }
@@ -520,3 +617,65 @@ fn convert_completion_prompt_to_body(prompt: &String) -> HashMap<String, serde_j
     map.insert("prompt".into(), json!(prompt));
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body() -> serde_json::Map<String, serde_json::Value> {
+        json!({
+            "system": [{ "type": "text", "text": "You are a contract reviewer." }],
+            "messages": [
+                {
+                    "role": "user",
+                    "content": [
+                        { "type": "text", "text": "Here is the contract." },
+                        { "type": "text", "text": "Is it signed?" },
+                    ],
+                },
+            ],
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn cache_policy_adds_breakpoints() {
+        let ephemeral = json!({ "type": "ephemeral" });
+
+        let mut none = body();
+        add_cache_breakpoints(&mut none, CachePolicy::None);
+        assert_eq!(none, body());
+
+        let mut system = body();
+        add_cache_breakpoints(&mut system, CachePolicy::System);
+        assert_eq!(system["system"][0]["cache_control"], ephemeral);
+        assert!(system["messages"][0]["content"][1]
+            .get("cache_control")
+            .is_none());
+
+        let mut prompt = body();
+        add_cache_breakpoints(&mut prompt, CachePolicy::Prompt);
+        assert_eq!(prompt["system"][0]["cache_control"], ephemeral);
+        assert!(prompt["messages"][0]["content"][0]
+            .get("cache_control")
+            .is_none());
+        assert_eq!(
+            prompt["messages"][0]["content"][1]["cache_control"],
+            ephemeral
+        );
+    }
+
+    #[test]
+    fn cache_policy_keeps_prompt_breakpoints() {
+        let ttl = json!({ "type": "ephemeral", "ttl": "1h" });
+        let mut body = body();
+        body["system"][0]
+            .as_object_mut()
+            .unwrap()
+            .insert("cache_control".into(), ttl.clone());
+        add_cache_breakpoints(&mut body, CachePolicy::System);
+        assert_eq!(body["system"][0]["cache_control"], ttl);
+    }
+}
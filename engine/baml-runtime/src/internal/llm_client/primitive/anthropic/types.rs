@@ -4,13 +4,20 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct AnthropicMessageContent {
     pub r#type: String,
+    #[serde(default)]
     pub text: String,
+    /// Set on `thinking` blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct AnthropicUsage {
     pub input_tokens: u64,
     pub output_tokens: u64,
+    /// Input tokens read from the prompt cache, if prompt caching was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -25,6 +32,29 @@ pub struct AnthropicMessageResponse {
     pub usage: AnthropicUsage,
 }
 
+impl AnthropicMessageResponse {
+    /// The answer, leaving out thinking blocks. `None` if there is no text
+    /// block.
+    pub fn text(&self) -> Option<String> {
+        let blocks = self
+            .content
+            .iter()
+            .filter(|c| c.r#type == "text")
+            .map(|c| c.text.as_str())
+            .collect::<Vec<_>>();
+        (!blocks.is_empty()).then(|| blocks.concat())
+    }
+
+    pub fn thinking(&self) -> Option<String> {
+        let blocks = self
+            .content
+            .iter()
+            .filter_map(|c| c.thinking.as_deref())
+            .collect::<Vec<_>>();
+        (!blocks.is_empty()).then(|| blocks.join("\n\n"))
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, strum_macros::Display, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
@@ -56,6 +86,14 @@ pub enum ContentType {
     ToolUse,
     /// tool_result
     ToolResult,
+    /// thinking
+    Thinking,
+    /// thinking_delta
+    ThinkingDelta,
+    /// signature_delta
+    SignatureDelta,
+    /// redacted_thinking
+    RedactedThinking,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -63,7 +101,8 @@ pub struct TextContentBlock {
     /// The content type. It is always `text`.
     #[serde(rename = "type")]
     pub _type: ContentType,
-    /// The text content.
+    /// The text content. Empty for thinking blocks.
+    #[serde(default)]
     pub text: String,
 }
 
@@ -144,11 +183,16 @@ pub struct MessageDeltaChunk {
 /// The text delta content block.
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
 pub struct TextDeltaContentBlock {
-    /// The content type. It is always `text_delta`.
+    /// The content type: `text_delta`, or `thinking_delta` and
+    /// `signature_delta` with extended thinking.
     #[serde(rename = "type")]
     pub _type: ContentType,
     /// The text delta content.
+    #[serde(default)]
     pub text: String,
+    /// The thinking delta content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
 }
 
 /// The stream stop information.
@@ -181,6 +225,7 @@ mod tests {
             delta: TextDeltaContentBlock {
                 _type: ContentType::TextDelta,
                 text: "Hello".to_string(),
+                thinking: None,
             },
         });
         println!("serialized = {}", serde_json::to_string(&chunk)?);
@@ -190,4 +235,37 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn thinking_is_not_content() -> Result<()> {
+        let response: AnthropicMessageResponse = serde_json::from_str(
+            r#"{
+                "id": "msg_01",
+                "role": "assistant",
+                "type": "message",
+                "model": "claude-3-7-sonnet-20250219",
+                "content": [
+                    {"type": "thinking", "thinking": "The user wants JSON.", "signature": "abc"},
+                    {"type": "redacted_thinking", "data": "xyz"},
+                    {"type": "text", "text": "{\"a\": 1}"}
+                ],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": {"input_tokens": 10, "output_tokens": 120}
+            }"#,
+        )?;
+        assert_eq!(response.text().as_deref(), Some("{\"a\": 1}"));
+        assert_eq!(response.thinking().as_deref(), Some("The user wants JSON."));
+
+        let chunk: MessageChunk = serde_json::from_str(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Hmm"}}"#,
+        )?;
+        let MessageChunk::ContentBlockDelta(chunk) = chunk else {
+            panic!("expected a content block delta");
+        };
+        assert_eq!(chunk.delta._type, ContentType::ThinkingDelta);
+        assert_eq!(chunk.delta.thinking.as_deref(), Some("Hmm"));
+
+        Ok(())
+    }
 }
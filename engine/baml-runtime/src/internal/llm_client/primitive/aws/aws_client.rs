@@ -8,7 +8,7 @@ use aws_sdk_bedrockruntime::{self as bedrock, operation::converse::ConverseOutpu
 use anyhow::{Context, Result};
 use aws_smithy_json::serialize::JsonObjectWriter;
 use aws_smithy_runtime_api::client::result::SdkError;
-use aws_smithy_types::Blob;
+use aws_smithy_types::{Blob, Document, Number};
 use baml_types::{BamlMap, BamlMediaContent};
 use baml_types::{BamlMedia, BamlMediaType};
 use futures::stream;
@@ -20,16 +20,16 @@ use internal_llm_client::{
 };
 use serde::Deserialize;
 use serde_json::Map;
-use web_time::Instant;
-use web_time::SystemTime;
 
 use crate::client_registry::ClientProperty;
+use crate::clock::Stopwatch;
+use crate::internal::llm_client::image_preprocessing::{self, ImagePreprocessing};
 use crate::internal::llm_client::traits::{ToProviderMessageExt, WithClientProperties};
 use crate::internal::llm_client::{
     primitive::request::RequestBuilder,
     traits::{
-        StreamResponse, WithChat, WithClient, WithNoCompletion, WithRenderRawCurl, WithRetryPolicy,
-        WithStreamChat,
+        accumulate_stream, StreamResponse, WithChat, WithClient, WithNoCompletion,
+        WithRenderRawCurl, WithRetryPolicy, WithStreamChat,
     },
     ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
     ModelFeatures, ResolveMediaUrls,
@@ -80,6 +80,10 @@ impl AwsClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_role_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::ANTHROPIC_MAX_DIMENSION,
+                ),
             },
             retry_policy: client.retry_policy.as_ref().map(|s| s.to_string()),
             properties,
@@ -103,6 +107,10 @@ impl AwsClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_role_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::ANTHROPIC_MAX_DIMENSION,
+                ),
             },
             retry_policy: client
                 .elem()
@@ -217,16 +225,78 @@ impl AwsClient {
                 .build()
         });
 
+        let additional_model_request_fields =
+            (!self.properties.additional_model_request_fields.is_empty()).then(|| {
+                Document::Object(
+                    self.properties
+                        .additional_model_request_fields
+                        .iter()
+                        .map(|(k, v)| (k.clone(), to_document(v)))
+                        .collect(),
+                )
+            });
+
+        let guardrail_config = self
+            .properties
+            .guardrail_config
+            .as_ref()
+            .map(|curr| {
+                bedrock::types::GuardrailConfiguration::builder()
+                    .guardrail_identifier(curr.identifier.clone())
+                    .guardrail_version(curr.version.clone())
+                    .set_trace(
+                        curr.trace
+                            .as_deref()
+                            .map(bedrock::types::GuardrailTrace::from),
+                    )
+                    .build()
+            })
+            .transpose()
+            .context("Invalid guardrail_config")?;
+
         bedrock::operation::converse::ConverseInput::builder()
             .set_inference_config(inference_config)
             .set_model_id(Some(self.properties.model.clone()))
             .set_system(system_message)
             .set_messages(Some(converse_messages))
+            .set_additional_model_request_fields(additional_model_request_fields)
+            .set_guardrail_config(guardrail_config)
             .build()
             .context("Failed to convert BAML prompt to AWS Bedrock request")
     }
 }
 
+fn to_document(value: &serde_json::Value) -> Document {
+    match value {
+        serde_json::Value::Null => Document::Null,
+        serde_json::Value::Bool(b) => Document::Bool(*b),
+        serde_json::Value::Number(n) => Document::Number(match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => Number::PosInt(n),
+            (None, Some(n)) => Number::NegInt(n),
+            _ => Number::Float(n.as_f64().unwrap_or_default()),
+        }),
+        serde_json::Value::String(s) => Document::String(s.clone()),
+        serde_json::Value::Array(items) => Document::Array(items.iter().map(to_document).collect()),
+        serde_json::Value::Object(map) => Document::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), to_document(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Converse and ConverseStream take the same guardrail, as different types.
+fn to_stream_guardrail_config(
+    config: bedrock::types::GuardrailConfiguration,
+) -> Option<bedrock::types::GuardrailStreamConfiguration> {
+    bedrock::types::GuardrailStreamConfiguration::builder()
+        .guardrail_identifier(config.guardrail_identifier)
+        .guardrail_version(config.guardrail_version)
+        .set_trace(config.trace)
+        .build()
+        .ok()
+}
+
 fn try_to_json<
     Ser: Fn(
         &mut JsonObjectWriter,
@@ -285,6 +355,9 @@ impl WithClientProperties for AwsClient {
     fn finish_reason_filter(&self) -> &internal_llm_client::FinishReasonFilter {
         &self.properties.finish_reason_filter
     }
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        internal_llm_client::CandidateSelection::default()
+    }
     fn default_role(&self) -> String {
         self.properties.default_role()
     }
@@ -324,7 +397,7 @@ impl WithStreamChat for AwsClient {
                     client,
                     model,
                     prompt,
-                    start_time: SystemTime::now(),
+                    start_time: ctx.clock.now(),
                     request_options,
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
@@ -340,7 +413,7 @@ impl WithStreamChat for AwsClient {
                     client,
                     model,
                     prompt,
-                    start_time: SystemTime::now(),
+                    start_time: ctx.clock.now(),
                     request_options,
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
@@ -354,10 +427,16 @@ impl WithStreamChat for AwsClient {
             .set_model_id(request.model_id)
             .set_inference_config(request.inference_config)
             .set_system(request.system)
-            .set_messages(request.messages);
+            .set_messages(request.messages)
+            .set_additional_model_request_fields(request.additional_model_request_fields)
+            .set_guardrail_config(
+                request
+                    .guardrail_config
+                    .and_then(to_stream_guardrail_config),
+            );
 
-        let system_start = SystemTime::now();
-        let instant_start = Instant::now();
+        let system_start = ctx.clock.now();
+        let instant_start = Stopwatch::start(&ctx.clock);
 
         let response = match request.send().await {
             Ok(resp) => resp,
@@ -400,105 +479,156 @@ impl WithStreamChat for AwsClient {
             }
         };
 
-        let stream = stream::unfold(
-            (
-                Some(LLMCompleteResponse {
-                    client,
-                    prompt,
-                    content: "".to_string(),
-                    start_time: system_start,
-                    latency: instant_start.elapsed(),
-                    model: self.properties.model.clone(),
-                    request_options,
-                    metadata: LLMCompleteResponseMetadata {
-                        baml_is_complete: false,
-                        finish_reason: None,
-                        prompt_tokens: None,
-                        output_tokens: None,
-                        total_tokens: None,
-                    },
-                }),
-                response,
-            ),
-            move |(initial_state, mut response)| {
-                async move {
-                    let mut new_state = initial_state?;
-                    match response.stream.recv().await {
-                        Ok(Some(message)) => {
-                            log::trace!("Received message: {:#?}", message);
-                            match message {
-                                bedrock::types::ConverseStreamOutput::ContentBlockDelta(
-                                    content_block_delta,
-                                ) => {
-                                    if let Some(bedrock::types::ContentBlockDelta::Text(
-                                        ref delta,
-                                    )) = content_block_delta.delta
-                                    {
-                                        new_state.content += delta;
-                                        // TODO- handle
-                                    }
-                                    // TODO- handle
-                                }
-                                bedrock::types::ConverseStreamOutput::ContentBlockStart(_) => {
-                                    // TODO- handle
-                                }
-                                bedrock::types::ConverseStreamOutput::ContentBlockStop(_) => {
-                                    // TODO- handle
-                                }
-                                bedrock::types::ConverseStreamOutput::MessageStart(_) => {
-                                    // TODO- handle
-                                }
-                                bedrock::types::ConverseStreamOutput::MessageStop(stop) => {
-                                    new_state.metadata.baml_is_complete = matches!(
-                                        stop.stop_reason,
-                                        bedrock::types::StopReason::StopSequence
-                                            | bedrock::types::StopReason::EndTurn
-                                    );
-                                    // TODO- handle
-                                }
-                                bedrock::types::ConverseStreamOutput::Metadata(metadata) => {
-                                    if let Some(usage) = metadata.usage() {
-                                        new_state.metadata.prompt_tokens =
-                                            Some(usage.input_tokens() as u64);
-                                        new_state.metadata.output_tokens =
-                                            Some(usage.output_tokens() as u64);
-                                        new_state.metadata.total_tokens =
-                                            Some((usage.total_tokens()) as u64);
-                                    }
-                                }
-                                _ => {
-                                    // TODO- handle
-                                }
-                            }
-                            new_state.latency = instant_start.elapsed();
-                            Some((
-                                LLMResponse::Success(new_state.clone()),
-                                (Some(new_state), response),
-                            ))
+        let events = stream::unfold(response, |mut response| async move {
+            match response.stream.recv().await {
+                Ok(Some(message)) => Some((Ok(message), response)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), response)),
+            }
+        });
+        let initial = LLMCompleteResponse {
+            client,
+            prompt,
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: self.properties.model.clone(),
+            request_options,
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+
+        let mut blocks = StreamedBlocks::default();
+        let stream_start = instant_start.clone();
+        let stream = accumulate_stream(events, initial, stream_start, move |state, message| {
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                        client: state.client.clone(),
+                        model: Some(state.model.clone()),
+                        prompt: state.prompt.clone(),
+                        start_time: state.start_time,
+                        request_options: state.request_options.clone(),
+                        latency: instant_start.elapsed(),
+                        message: format!("Failed to parse event: {:#?}", e),
+                        code: ErrorCode::Other(2),
+                    }))
+                }
+            };
+            log::trace!("Received message: {:#?}", message);
+            match message {
+                bedrock::types::ConverseStreamOutput::ContentBlockDelta(content_block_delta) => {
+                    let index = content_block_delta.content_block_index;
+                    match content_block_delta.delta {
+                        Some(bedrock::types::ContentBlockDelta::Text(delta)) => {
+                            return Ok(blocks.delta(index, &delta));
+                        }
+                        Some(bedrock::types::ContentBlockDelta::ToolUse(delta)) => {
+                            return Ok(blocks.delta(index, &delta.input));
                         }
-                        Ok(None) => None,
-                        Err(e) => Some((
-                            LLMResponse::LLMFailure(LLMErrorResponse {
-                                client: new_state.client,
-                                model: Some(new_state.model),
-                                prompt: new_state.prompt,
-                                start_time: new_state.start_time,
-                                request_options: new_state.request_options,
-                                latency: instant_start.elapsed(),
-                                message: format!("Failed to parse event: {:#?}", e),
-                                code: ErrorCode::Other(2),
-                            }),
-                            (None, response),
-                        )),
+                        _ => {}
                     }
                 }
-            },
-        );
+                bedrock::types::ConverseStreamOutput::ContentBlockStart(start) => {
+                    if let Some(bedrock::types::ContentBlockStart::ToolUse(tool_use)) = start.start
+                    {
+                        log::debug!(
+                            "Bedrock started tool use {} ({})",
+                            tool_use.name,
+                            tool_use.tool_use_id
+                        );
+                        blocks.start_tool_use(start.content_block_index);
+                    }
+                }
+                bedrock::types::ConverseStreamOutput::ContentBlockStop(stop) => {
+                    return Ok(blocks.stop(stop.content_block_index));
+                }
+                bedrock::types::ConverseStreamOutput::MessageStart(_) => {
+                    // TODO- handle
+                }
+                bedrock::types::ConverseStreamOutput::MessageStop(stop) => {
+                    // The input of a tool call is whole once the model stops
+                    // for it.
+                    state.metadata.baml_is_complete = matches!(
+                        stop.stop_reason,
+                        bedrock::types::StopReason::StopSequence
+                            | bedrock::types::StopReason::EndTurn
+                            | bedrock::types::StopReason::ToolUse
+                    );
+                    state.metadata.finish_reason = Some(stop.stop_reason.as_str().into());
+                }
+                bedrock::types::ConverseStreamOutput::Metadata(metadata) => {
+                    if let Some(usage) = metadata.usage() {
+                        state.metadata.prompt_tokens = Some(usage.input_tokens() as u64);
+                        state.metadata.output_tokens = Some(usage.output_tokens() as u64);
+                        state.metadata.total_tokens = Some((usage.total_tokens()) as u64);
+                    }
+                }
+                _ => {
+                    // TODO- handle
+                }
+            }
+            Ok(None)
+        });
 
         Ok(Box::pin(stream))
     }
 }
 
+/// The content blocks of a response as Bedrock streams them. The content is
+/// each block in order, a newline apart, with tool-use blocks standing for the
+/// JSON input of the call.
+#[derive(Default)]
+struct StreamedBlocks {
+    /// The index of the block the content ends with.
+    last: Option<i32>,
+    /// The input of each tool-use block so far, by index.
+    tool_inputs: HashMap<i32, String>,
+}
+
+impl StreamedBlocks {
+    fn start_tool_use(&mut self, index: i32) {
+        self.tool_inputs.insert(index, String::new());
+    }
+
+    /// What `text` in block `index` adds to the content.
+    fn delta(&mut self, index: i32, text: &str) -> Option<String> {
+        if text.is_empty() {
+            return None;
+        }
+        if let Some(input) = self.tool_inputs.get_mut(&index) {
+            input.push_str(text);
+        }
+        let separator = match self.last.replace(index) {
+            Some(last) if last != index => "\n",
+            _ => "",
+        };
+        Some(format!("{separator}{text}"))
+    }
+
+    /// What the end of block `index` adds to the content: a tool call that
+    /// got no input still stands for an empty object.
+    fn stop(&mut self, index: i32) -> Option<String> {
+        match self.tool_inputs.get(&index) {
+            Some(input) if input.is_empty() => self.delta(index, "{}"),
+            _ => None,
+        }
+    }
+}
+
 impl AwsClient {
     fn to_chat_message(&self, text: &str) -> Result<bedrock::types::ContentBlock> {
         Ok(bedrock::types::ContentBlock::Text(text.to_string()))
@@ -525,6 +655,9 @@ impl AwsClient {
                     "BAML internal error (AWSBedrock): media URL should have been resolved to base64"
                 )
             }
+            BamlMediaContent::FileRef(_) => {
+                anyhow::bail!("AWS Bedrock does not support uploaded files, use base64 instead")
+            }
             BamlMediaContent::Base64(b64_media) => Ok(bedrock::types::ContentBlock::Image(
                 bedrock::types::ImageBlock::builder()
                     .set_format(Some(bedrock::types::ImageFormat::from(
@@ -600,7 +733,7 @@ impl AwsClient {
 impl WithChat for AwsClient {
     async fn chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         chat_messages: &[RenderedChatMessage],
     ) -> LLMResponse {
         let client = self.context.name.to_string();
@@ -616,7 +749,7 @@ impl WithChat for AwsClient {
                     client,
                     model,
                     prompt,
-                    start_time: SystemTime::now(),
+                    start_time: ctx.clock.now(),
                     request_options,
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
@@ -625,14 +758,14 @@ impl WithChat for AwsClient {
             }
         };
 
-        let request = match self.build_request(_ctx, chat_messages) {
+        let request = match self.build_request(ctx, chat_messages) {
             Ok(r) => r,
             Err(e) => {
                 return LLMResponse::LLMFailure(LLMErrorResponse {
                     client,
                     model,
                     prompt,
-                    start_time: SystemTime::now(),
+                    start_time: ctx.clock.now(),
                     request_options,
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
@@ -645,10 +778,12 @@ impl WithChat for AwsClient {
             .set_model_id(request.model_id)
             .set_inference_config(request.inference_config)
             .set_system(request.system)
-            .set_messages(request.messages);
+            .set_messages(request.messages)
+            .set_additional_model_request_fields(request.additional_model_request_fields)
+            .set_guardrail_config(request.guardrail_config);
 
-        let system_start = SystemTime::now();
-        let instant_start = Instant::now();
+        let system_start = ctx.clock.now();
+        let instant_start = Stopwatch::start(&ctx.clock);
 
         let response = match request.send().await {
             Ok(resp) => resp,
@@ -695,6 +830,13 @@ impl WithChat for AwsClient {
                         .usage
                         .as_ref()
                         .and_then(|i| i.total_tokens.try_into().ok()),
+                    cached_input_tokens: None,
+                    grounding: None,
+                    thinking: None,
+                    logprobs: None,
+                    candidates: None,
+                    structured_output: false,
+                    raw_response: None,
                 },
             }),
             Err(e) => LLMResponse::LLMFailure(LLMErrorResponse {
@@ -710,3 +852,63 @@ impl WithChat for AwsClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use aws_smithy_types::{Document, Number};
+
+    use super::{to_document, StreamedBlocks};
+
+    fn content(blocks: &mut StreamedBlocks, events: &[(i32, Option<&str>)]) -> String {
+        events
+            .iter()
+            .filter_map(|(index, delta)| match delta {
+                Some(text) => blocks.delta(*index, text),
+                None => blocks.stop(*index),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_every_block() {
+        let mut blocks = StreamedBlocks::default();
+        blocks.start_tool_use(1);
+        let content = content(
+            &mut blocks,
+            &[
+                (0, Some("Let me look")),
+                (0, Some(" that up.")),
+                (0, None),
+                (1, Some(r#"{"city": "#)),
+                (1, Some(r#""Paris"}"#)),
+                (1, None),
+            ],
+        );
+        assert_eq!(content, "Let me look that up.\n{\"city\": \"Paris\"}");
+    }
+
+    #[test]
+    fn tool_calls_without_input_are_empty_objects() {
+        let mut blocks = StreamedBlocks::default();
+        blocks.start_tool_use(0);
+        blocks.start_tool_use(1);
+        let content = content(&mut blocks, &[(0, None), (1, Some("{}")), (1, None)]);
+        assert_eq!(content, "{}\n{}");
+    }
+
+    #[test]
+    fn converts_request_fields_to_documents() {
+        let fields =
+            serde_json::json!({"top_k": 50, "bias": -1, "scale": 0.5, "tags": ["a", null]});
+        let Document::Object(fields) = to_document(&fields) else {
+            panic!("Expected an object");
+        };
+        assert_eq!(fields["top_k"], Document::Number(Number::PosInt(50)));
+        assert_eq!(fields["bias"], Document::Number(Number::NegInt(-1)));
+        assert_eq!(fields["scale"], Document::Number(Number::Float(0.5)));
+        assert_eq!(
+            fields["tags"],
+            Document::Array(vec![Document::String("a".to_string()), Document::Null])
+        );
+    }
+}
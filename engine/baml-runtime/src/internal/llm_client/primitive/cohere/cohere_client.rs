@@ -0,0 +1,489 @@
+use anyhow::Result;
+use baml_types::BamlMap;
+use futures::StreamExt;
+use internal_baml_core::ir::ClientWalker;
+use internal_baml_jinja::{RenderContext_Client, RenderedChatMessage};
+use internal_llm_client::{
+    cohere::ResolvedCohere, AllowedRoleMetadata, ClientProvider, FinishReasonFilter,
+    ResolvedClientProperty, UnresolvedClientProperty,
+};
+use serde_json::json;
+
+use crate::{
+    client_registry::ClientProperty,
+    internal::llm_client::{
+        primitive::request::{make_parsed_request, make_request, RequestBuilder},
+        traits::{
+            accumulate_stream, SseResponseTrait, StreamResponse, WithChat, WithClient,
+            WithClientProperties, WithNoCompletion, WithRetryPolicy, WithStreamChat,
+        },
+        ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
+        ModelFeatures, ResolveMediaUrls,
+    },
+    request::{create_client_with_options, json_body},
+    RuntimeContext,
+};
+
+use super::types::{CohereChatResponse, CohereStreamEvent, CohereUsage};
+
+// Talks to Cohere's v1 chat API, which takes the conversation as a preamble,
+// a chat history and a final message instead of a list of messages.
+pub struct CohereClient {
+    pub name: String,
+    retry_policy: Option<String>,
+    context: RenderContext_Client,
+    features: ModelFeatures,
+    properties: ResolvedCohere,
+
+    // clients
+    client: reqwest::Client,
+}
+
+fn resolve_properties(
+    provider: &ClientProvider,
+    properties: &UnresolvedClientProperty<()>,
+    ctx: &RuntimeContext,
+) -> Result<ResolvedCohere, anyhow::Error> {
+    let properties = properties.resolve(provider, &ctx.eval_ctx(false))?;
+
+    let ResolvedClientProperty::Cohere(props) = properties else {
+        anyhow::bail!(
+            "Invalid client property. Should have been a cohere property but got: {}",
+            properties.name()
+        );
+    };
+
+    Ok(props)
+}
+
+impl WithRetryPolicy for CohereClient {
+    fn retry_policy_name(&self) -> Option<&str> {
+        self.retry_policy.as_deref()
+    }
+}
+
+impl WithClientProperties for CohereClient {
+    fn allowed_metadata(&self) -> &AllowedRoleMetadata {
+        &self.properties.allowed_metadata
+    }
+
+    fn finish_reason_filter(&self) -> &FinishReasonFilter {
+        &self.properties.finish_reason_filter
+    }
+
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        internal_llm_client::CandidateSelection::default()
+    }
+
+    fn allowed_roles(&self) -> Vec<String> {
+        self.properties.allowed_roles()
+    }
+
+    fn default_role(&self) -> String {
+        self.properties.default_role()
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.properties.supports_streaming()
+    }
+}
+
+impl WithClient for CohereClient {
+    fn context(&self) -> &RenderContext_Client {
+        &self.context
+    }
+
+    fn model_features(&self) -> &ModelFeatures {
+        &self.features
+    }
+}
+
+impl WithNoCompletion for CohereClient {}
+
+impl WithChat for CohereClient {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
+        let (response, raw_response, system_start, instant_start) =
+            match make_parsed_request::<CohereChatResponse>(
+                self,
+                ctx,
+                either::Either::Right(prompt),
+                false,
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+
+        let usage = response.usage();
+
+        LLMResponse::Success(LLMCompleteResponse {
+            client: self.context.name.to_string(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
+            content: response.text.clone(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: self.model(),
+            request_options: self.properties.properties.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: response.is_complete(),
+                finish_reason: response.finish_reason.clone(),
+                prompt_tokens: usage.and_then(|u| u.input_tokens),
+                output_tokens: usage.and_then(|u| u.output_tokens),
+                total_tokens: usage.and_then(CohereUsage::total_tokens),
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: self.properties.capture_raw_response.then_some(raw_response),
+            },
+        })
+    }
+}
+
+impl RequestBuilder for CohereClient {
+    fn http_client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        &self.properties.http
+    }
+
+    async fn build_request(
+        &self,
+        _ctx: &RuntimeContext,
+        prompt: either::Either<&String, &[RenderedChatMessage]>,
+        allow_proxy: bool,
+        stream: bool,
+    ) -> Result<reqwest::RequestBuilder> {
+        let destination_url = if allow_proxy {
+            self.properties
+                .proxy_url
+                .as_ref()
+                .unwrap_or(&self.properties.base_url)
+        } else {
+            &self.properties.base_url
+        };
+
+        let mut req = self.client.post(format!("{}/chat", destination_url));
+
+        for (key, value) in &self.properties.headers {
+            req = req.header(key, value);
+        }
+        req = req.bearer_auth(&self.properties.api_key);
+
+        if allow_proxy {
+            req = req.header("baml-original-url", self.properties.base_url.as_str());
+        }
+
+        let mut body = json!(self.properties.properties);
+        let body_obj = body.as_object_mut().unwrap();
+        match prompt {
+            either::Either::Left(_) => {
+                anyhow::bail!("Cohere only supports chat prompts")
+            }
+            either::Either::Right(messages) => {
+                body_obj.extend(chat_to_message(messages)?);
+            }
+        }
+
+        if stream {
+            body_obj.insert("stream".into(), json!(true));
+        }
+
+        json_body(req, &body, self.properties.http.compress_requests)
+    }
+
+    fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
+        &self.properties.properties
+    }
+}
+
+impl SseResponseTrait for CohereClient {
+    fn response_stream(
+        &self,
+        resp: reqwest::Response,
+        prompt: &[RenderedChatMessage],
+        system_start: web_time::SystemTime,
+        instant_start: crate::clock::Stopwatch,
+    ) -> StreamResponse {
+        let prompt = prompt.to_vec();
+        let client_name = self.context.name.clone();
+        let model = self.model();
+        let params = self.properties.properties.clone();
+        let events = stream_events(resp).inspect(|event| log::trace!("{:#?}", event));
+        let initial = LLMCompleteResponse {
+            client: client_name.clone(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: model.clone(),
+            request_options: params.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+        Ok(Box::pin(accumulate_stream(
+            events,
+            initial,
+            instant_start.clone(),
+            move |inner, event| match event {
+                Ok(CohereStreamEvent::TextGeneration { text }) => Ok(Some(text)),
+                Ok(CohereStreamEvent::StreamEnd { response }) => {
+                    inner.metadata.baml_is_complete = response.is_complete();
+                    inner.metadata.finish_reason = response.finish_reason.clone();
+                    if let Some(usage) = response.usage() {
+                        inner.metadata.prompt_tokens = usage.input_tokens;
+                        inner.metadata.output_tokens = usage.output_tokens;
+                        inner.metadata.total_tokens = usage.total_tokens();
+                    }
+                    Ok(None)
+                }
+                Ok(CohereStreamEvent::Other) => Ok(None),
+                Err(e) => Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                    client: client_name.clone(),
+                    model: Some(model.clone()),
+                    prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                    start_time: system_start,
+                    request_options: params.clone(),
+                    latency: instant_start.elapsed(),
+                    message: format!("Failed to parse event: {:#?}", e),
+                    code: ErrorCode::UnsupportedResponse(2),
+                })),
+            },
+        )))
+    }
+}
+
+impl WithStreamChat for CohereClient {
+    async fn stream_chat(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &[RenderedChatMessage],
+    ) -> StreamResponse {
+        let (resp, system_start, instant_start) =
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
+        self.response_stream(resp, prompt, system_start, instant_start)
+    }
+}
+
+impl CohereClient {
+    pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<CohereClient> {
+        let properties = resolve_properties(&client.elem().provider, client.options(), ctx)?;
+        Ok(Self {
+            name: client.name().into(),
+            context: RenderContext_Client {
+                name: client.name().into(),
+                provider: client.elem().provider.to_string(),
+                default_role: properties.default_role(),
+                allowed_roles: properties.allowed_roles(),
+            },
+            features: Self::model_features(&properties),
+            retry_policy: client
+                .elem()
+                .retry_policy_id
+                .as_ref()
+                .map(|s| s.to_string()),
+            client: create_client_with_options(&properties.http)?,
+            properties,
+        })
+    }
+
+    pub fn dynamic_new(client: &ClientProperty, ctx: &RuntimeContext) -> Result<CohereClient> {
+        let properties = resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
+        Ok(Self {
+            name: client.name.clone(),
+            context: RenderContext_Client {
+                name: client.name.clone(),
+                provider: client.provider.to_string(),
+                default_role: properties.default_role(),
+                allowed_roles: properties.allowed_roles(),
+            },
+            features: Self::model_features(&properties),
+            retry_policy: client.retry_policy.clone(),
+            client: create_client_with_options(&properties.http)?,
+            properties,
+        })
+    }
+
+    fn model_features(properties: &ResolvedCohere) -> ModelFeatures {
+        ModelFeatures {
+            chat: true,
+            completion: false,
+            anthropic_system_constraints: false,
+            // Cohere's chat API only takes text.
+            resolve_media_urls: ResolveMediaUrls::Never,
+            allowed_metadata: properties.allowed_metadata.clone(),
+            image_preprocessing: None,
+        }
+    }
+
+    /// Cohere's v1 responses don't say which model answered.
+    fn model(&self) -> String {
+        self.properties
+            .properties
+            .get("model")
+            .and_then(|model| model.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// Leading system messages become the `preamble`, the last message the
+/// `message`, and everything in between the `chat_history`.
+fn chat_to_message(
+    chat: &[RenderedChatMessage],
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let preamble_len = chat
+        .iter()
+        .position(|message| message.role != "system")
+        .unwrap_or(chat.len());
+    let (preamble, conversation) = chat.split_at(preamble_len);
+    let Some((message, history)) = conversation.split_last() else {
+        anyhow::bail!("Cohere needs a user message after the system prompt");
+    };
+    if message.role != "user" {
+        anyhow::bail!(
+            "Cohere needs the prompt to end with a user message, not a {} message",
+            message.role
+        );
+    }
+
+    let mut res = serde_json::Map::new();
+    if !preamble.is_empty() {
+        res.insert(
+            "preamble".into(),
+            json!(preamble
+                .iter()
+                .map(message_text)
+                .collect::<Result<Vec<_>>>()?
+                .join("\n\n")),
+        );
+    }
+    if !history.is_empty() {
+        res.insert(
+            "chat_history".into(),
+            history
+                .iter()
+                .map(|message| {
+                    Ok(json!({
+                        "role": cohere_role(&message.role),
+                        "message": message_text(message)?,
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into(),
+        );
+    }
+    res.insert("message".into(), json!(message_text(message)?));
+    Ok(res)
+}
+
+fn cohere_role(role: &str) -> String {
+    match role {
+        "assistant" => "CHATBOT".to_string(),
+        role => role.to_uppercase(),
+    }
+}
+
+fn message_text(message: &RenderedChatMessage) -> Result<String> {
+    message
+        .parts
+        .iter()
+        .map(|part| match part.as_text() {
+            Some(text) => Ok(text.as_str()),
+            None => anyhow::bail!("Cohere does not support media inputs"),
+        })
+        .collect()
+}
+
+/// Cohere streams v1 chat responses as one JSON event per line, not as
+/// server-sent events.
+fn stream_events(
+    resp: reqwest::Response,
+) -> impl futures::Stream<Item = Result<CohereStreamEvent>> {
+    resp.bytes_stream()
+        .scan(Vec::<u8>::new(), |buffer, chunk| {
+            let events = match chunk {
+                Ok(chunk) => {
+                    buffer.extend_from_slice(&chunk);
+                    let mut events = vec![];
+                    while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                        let line = buffer.drain(..=end).collect::<Vec<_>>();
+                        let line = String::from_utf8_lossy(&line);
+                        if !line.trim().is_empty() {
+                            events.push(
+                                serde_json::from_str::<CohereStreamEvent>(&line)
+                                    .map_err(anyhow::Error::from),
+                            );
+                        }
+                    }
+                    events
+                }
+                Err(e) => vec![Err(e.into())],
+            };
+            std::future::ready(Some(events))
+        })
+        .flat_map(futures::stream::iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use internal_baml_jinja::ChatMessagePart;
+
+    use super::*;
+
+    fn message(role: &str, text: &str) -> RenderedChatMessage {
+        RenderedChatMessage {
+            role: role.to_string(),
+            allow_duplicate_role: false,
+            parts: vec![ChatMessagePart::Text(text.to_string())],
+        }
+    }
+
+    #[test]
+    fn maps_chat_to_preamble_history_and_message() {
+        let body = chat_to_message(&[
+            message("system", "You are a travel agent."),
+            message("user", "I want to go to Lisbon."),
+            message("assistant", "When?"),
+            message("user", "In May."),
+        ])
+        .unwrap();
+        assert_eq!(
+            serde_json::Value::Object(body),
+            json!({
+                "preamble": "You are a travel agent.",
+                "chat_history": [
+                    { "role": "USER", "message": "I want to go to Lisbon." },
+                    { "role": "CHATBOT", "message": "When?" },
+                ],
+                "message": "In May.",
+            })
+        );
+
+        let body = chat_to_message(&[message("user", "Hi")]).unwrap();
+        assert_eq!(serde_json::Value::Object(body), json!({ "message": "Hi" }));
+
+        assert!(chat_to_message(&[message("system", "Be brief.")]).is_err());
+        assert!(chat_to_message(&[message("user", "Hi"), message("assistant", "Hello")]).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+mod cohere_client;
+mod types;
+
+pub use cohere_client::CohereClient;
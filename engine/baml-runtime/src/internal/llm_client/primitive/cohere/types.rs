@@ -0,0 +1,92 @@
+use serde::Deserialize;
+
+// https://docs.cohere.com/v1/reference/chat
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CohereChatResponse {
+    pub text: String,
+    pub finish_reason: Option<String>,
+    pub meta: Option<CohereMeta>,
+}
+
+impl CohereChatResponse {
+    /// `COMPLETE` when the model ended on its own, `STOP_SEQUENCE` when it
+    /// hit one of the request's `stop_sequences`.
+    pub fn is_complete(&self) -> bool {
+        matches!(
+            self.finish_reason.as_deref(),
+            Some("COMPLETE" | "STOP_SEQUENCE")
+        )
+    }
+
+    pub fn usage(&self) -> Option<&CohereUsage> {
+        self.meta.as_ref()?.tokens.as_ref()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CohereMeta {
+    /// Tokens the model actually saw and produced, including the ones of the
+    /// preamble Cohere adds.
+    pub tokens: Option<CohereUsage>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CohereUsage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+impl CohereUsage {
+    pub fn total_tokens(&self) -> Option<u64> {
+        Some(self.input_tokens? + self.output_tokens?)
+    }
+}
+
+/// One line of a streamed chat response.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "event_type", rename_all = "kebab-case")]
+pub enum CohereStreamEvent {
+    TextGeneration {
+        text: String,
+    },
+    StreamEnd {
+        response: CohereChatResponse,
+    },
+    /// The stream's start, and citations, tool calls and search results,
+    /// which BAML doesn't use.
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_stream_events() {
+        let events = [
+            r#"{"is_finished":false,"event_type":"stream-start","generation_id":"a1b2"}"#,
+            r#"{"is_finished":false,"event_type":"text-generation","text":"Hello"}"#,
+            r#"{"is_finished":false,"event_type":"citation-generation","citations":[]}"#,
+            r#"{"is_finished":true,"event_type":"stream-end","finish_reason":"COMPLETE","response":{"response_id":"r1","text":"Hello","generation_id":"a1b2","chat_history":[],"finish_reason":"COMPLETE","meta":{"api_version":{"version":"1"},"billed_units":{"input_tokens":3,"output_tokens":1},"tokens":{"input_tokens":70,"output_tokens":1}}}}"#,
+        ]
+        .map(|event| serde_json::from_str::<CohereStreamEvent>(event).unwrap());
+
+        assert_eq!(
+            events[1],
+            CohereStreamEvent::TextGeneration {
+                text: "Hello".to_string()
+            }
+        );
+        assert_eq!(events[0], CohereStreamEvent::Other);
+        assert_eq!(events[2], CohereStreamEvent::Other);
+        let CohereStreamEvent::StreamEnd { response } = &events[3] else {
+            panic!("expected a stream-end event, got {:?}", events[3]);
+        };
+        assert!(response.is_complete());
+        assert_eq!(
+            response.usage().and_then(CohereUsage::total_tokens),
+            Some(71)
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use anyhow::Result;
+use baml_types::BamlMap;
+use internal_baml_core::ir::ClientWalker;
+use internal_baml_jinja::{
+    CompletionOptions, RenderContext_Client, RenderedChatMessage, RenderedPrompt,
+};
+#[cfg(feature = "provider-vertex")]
+use internal_llm_client::vertex::{ResolvedServiceAccountDetails, ResolvedVertex};
+use internal_llm_client::{
+    openai::ResolvedOpenAI, AllowedRoleMetadata, ClientProvider, FinishReasonFilter,
+    OpenAIClientProviderVariant, ResolvedClientProperty, UnresolvedClientProperty,
+};
+use serde_json::json;
+
+use crate::{
+    internal::llm_client::{
+        primitive::request::{make_parsed_request, RequestBuilder},
+        traits::{
+            StreamResponse, WithClient, WithClientProperties, WithCompletion, WithNoChat,
+            WithRetryPolicy, WithStreamCompletion,
+        },
+        ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
+        ModelFeatures, ResolveMediaUrls,
+    },
+    request::{create_client_with_options, json_body},
+    RuntimeContext,
+};
+
+use super::types::OpenAIEmbeddingResponse;
+#[cfg(feature = "provider-vertex")]
+use super::types::VertexEmbeddingResponse;
+
+/// The API a `client<embedding>` calls, with the options of the provider's
+/// LLM clients.
+enum Backend {
+    /// `POST {base_url}/embeddings`, for every OpenAI-compatible provider.
+    OpenAI(ResolvedOpenAI),
+    /// `POST {endpoint}/{model}:predict`.
+    #[cfg(feature = "provider-vertex")]
+    Vertex(ResolvedVertex),
+}
+
+impl Backend {
+    fn properties(&self) -> &BamlMap<String, serde_json::Value> {
+        match self {
+            Backend::OpenAI(properties) => &properties.properties,
+            #[cfg(feature = "provider-vertex")]
+            Backend::Vertex(properties) => &properties.properties,
+        }
+    }
+
+    fn http(&self) -> &internal_llm_client::HttpOptions {
+        match self {
+            Backend::OpenAI(properties) => &properties.http,
+            #[cfg(feature = "provider-vertex")]
+            Backend::Vertex(properties) => &properties.http,
+        }
+    }
+
+    fn finish_reason_filter(&self) -> &FinishReasonFilter {
+        match self {
+            Backend::OpenAI(properties) => &properties.finish_reason_filter,
+            #[cfg(feature = "provider-vertex")]
+            Backend::Vertex(properties) => &properties.finish_reason_filter,
+        }
+    }
+
+    fn capture_raw_response(&self) -> bool {
+        match self {
+            Backend::OpenAI(properties) => properties.capture_raw_response,
+            #[cfg(feature = "provider-vertex")]
+            Backend::Vertex(properties) => properties.capture_raw_response,
+        }
+    }
+}
+
+/// A `client<embedding>`. It sends the rendered prompt as a single input and
+/// answers with the embedding as a JSON array, which the function's `float[]`
+/// return type parses.
+pub struct EmbeddingClient {
+    pub name: String,
+    provider: ClientProvider,
+    retry_policy: Option<String>,
+    context: RenderContext_Client,
+    features: ModelFeatures,
+    backend: Backend,
+    client: reqwest::Client,
+}
+
+impl WithRetryPolicy for EmbeddingClient {
+    fn retry_policy_name(&self) -> Option<&str> {
+        self.retry_policy.as_deref()
+    }
+}
+
+impl WithClientProperties for EmbeddingClient {
+    fn allowed_metadata(&self) -> &AllowedRoleMetadata {
+        &self.features.allowed_metadata
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn finish_reason_filter(&self) -> &FinishReasonFilter {
+        self.backend.finish_reason_filter()
+    }
+
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        Default::default()
+    }
+
+    fn default_role(&self) -> String {
+        self.context.default_role.clone()
+    }
+
+    fn allowed_roles(&self) -> Vec<String> {
+        self.context.allowed_roles.clone()
+    }
+}
+
+impl WithClient for EmbeddingClient {
+    fn context(&self) -> &RenderContext_Client {
+        &self.context
+    }
+
+    fn model_features(&self) -> &ModelFeatures {
+        &self.features
+    }
+}
+
+impl WithNoChat for EmbeddingClient {}
+
+impl WithCompletion for EmbeddingClient {
+    fn completion_options(&self, _ctx: &RuntimeContext) -> Result<CompletionOptions> {
+        Ok(completion_options())
+    }
+
+    async fn completion(&self, ctx: &RuntimeContext, prompt: &str) -> LLMResponse {
+        let prompt = prompt.to_string();
+        match &self.backend {
+            Backend::OpenAI(_) => {
+                let (response, raw_response, system_start, instant_start) =
+                    match make_parsed_request::<OpenAIEmbeddingResponse>(
+                        self,
+                        ctx,
+                        either::Either::Left(&prompt),
+                        false,
+                    )
+                    .await
+                    {
+                        Ok(v) => v,
+                        Err(e) => return e,
+                    };
+                let Some(embedding) = response.data.first() else {
+                    return self.failure(
+                        &prompt,
+                        (system_start, instant_start),
+                        "Expected one embedding, got none".to_string(),
+                    );
+                };
+                let usage = response.usage.as_ref();
+                self.success(
+                    &prompt,
+                    &embedding.embedding,
+                    response.model.clone(),
+                    (
+                        usage.map(|u| u.prompt_tokens),
+                        usage.map(|u| u.total_tokens),
+                    ),
+                    raw_response,
+                    (system_start, instant_start),
+                )
+            }
+            #[cfg(feature = "provider-vertex")]
+            Backend::Vertex(properties) => {
+                let (response, raw_response, system_start, instant_start) =
+                    match make_parsed_request::<VertexEmbeddingResponse>(
+                        self,
+                        ctx,
+                        either::Either::Left(&prompt),
+                        false,
+                    )
+                    .await
+                    {
+                        Ok(v) => v,
+                        Err(e) => return e,
+                    };
+                let Some(prediction) = response.predictions.first() else {
+                    return self.failure(
+                        &prompt,
+                        (system_start, instant_start),
+                        "Expected one prediction, got none".to_string(),
+                    );
+                };
+                let tokens = prediction
+                    .embeddings
+                    .statistics
+                    .as_ref()
+                    .and_then(|s| s.token_count)
+                    .map(|count| count as u64);
+                self.success(
+                    &prompt,
+                    &prediction.embeddings.values,
+                    properties.model.clone(),
+                    (tokens, tokens),
+                    raw_response,
+                    (system_start, instant_start),
+                )
+            }
+        }
+    }
+}
+
+impl WithStreamCompletion for EmbeddingClient {
+    async fn stream_completion(&self, _: &RuntimeContext, _: &str) -> StreamResponse {
+        Err(LLMResponse::InternalFailure(
+            "Embedding clients don't stream".to_string(),
+        ))
+    }
+}
+
+impl RequestBuilder for EmbeddingClient {
+    fn http_client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        self.backend.http()
+    }
+
+    async fn build_request(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: either::Either<&String, &[RenderedChatMessage]>,
+        allow_proxy: bool,
+        _stream: bool,
+    ) -> Result<reqwest::RequestBuilder> {
+        let input = input_text(prompt);
+        let mut body = json!(self.backend.properties());
+        let body_obj = body.as_object_mut().unwrap();
+
+        let req = match &self.backend {
+            Backend::OpenAI(properties) => {
+                let destination_url = if allow_proxy {
+                    properties
+                        .proxy_url
+                        .as_ref()
+                        .unwrap_or(&properties.base_url)
+                } else {
+                    &properties.base_url
+                };
+                let mut req = self.client.post(format!("{destination_url}/embeddings"));
+                if !properties.query_params.is_empty() {
+                    req = req.query(&properties.query_params);
+                }
+                for (key, value) in &properties.headers {
+                    req = req.header(key, value);
+                }
+                if let Some(key) = &properties.api_key {
+                    req = req.bearer_auth(key);
+                }
+                if allow_proxy {
+                    req = req.header("baml-original-url", properties.base_url.as_str());
+                }
+
+                // Azure clients default `max_tokens` for chat, which the
+                // embeddings endpoint rejects.
+                if matches!(
+                    self.provider,
+                    ClientProvider::OpenAI(OpenAIClientProviderVariant::Azure)
+                ) {
+                    body_obj.remove("max_tokens");
+                }
+                body_obj.insert("input".into(), json!(input));
+                req
+            }
+            #[cfg(feature = "provider-vertex")]
+            Backend::Vertex(properties) => {
+                let url = format!(
+                    "{}/{}:predict",
+                    properties.endpoints[0].base_url, properties.model
+                );
+                let mut req = match (&properties.proxy_url, allow_proxy) {
+                    (Some(proxy_url), true) => self
+                        .client
+                        .post(proxy_url.clone())
+                        .header("baml-original-url", url),
+                    _ => self.client.post(url),
+                };
+                let access_token = match &properties.authorization {
+                    ResolvedServiceAccountDetails::RawAuthorizationHeader(token) => {
+                        token.to_string()
+                    }
+                    ResolvedServiceAccountDetails::Json(token) => {
+                        super::super::vertex::get_access_token(token, ctx.clock.now())
+                            .await
+                            .map_err(|e| e.context("Failed to get access token"))?
+                    }
+                };
+                req = req.header("Authorization", format!("Bearer {access_token}"));
+                for (key, value) in &properties.headers {
+                    req = req.header(key, value);
+                }
+
+                body_obj.insert("instances".into(), json!([{ "content": input }]));
+                req
+            }
+        };
+
+        json_body(req, &body, self.backend.http().compress_requests)
+    }
+
+    fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
+        self.backend.properties()
+    }
+}
+
+impl EmbeddingClient {
+    pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<Self> {
+        let provider = client.elem().provider.clone();
+        let backend = resolve_backend(&provider, client.options(), ctx)?;
+        Ok(Self {
+            name: client.name().into(),
+            context: RenderContext_Client {
+                name: client.name().into(),
+                provider: provider.to_string(),
+                default_role: "user".to_string(),
+                allowed_roles: vec!["user".to_string()],
+            },
+            features: ModelFeatures {
+                chat: false,
+                completion: true,
+                anthropic_system_constraints: false,
+                resolve_media_urls: ResolveMediaUrls::Never,
+                allowed_metadata: AllowedRoleMetadata::None,
+                image_preprocessing: None,
+            },
+            retry_policy: client
+                .elem()
+                .retry_policy_id
+                .as_ref()
+                .map(|s| s.to_string()),
+            client: create_client_with_options(backend.http())?,
+            provider,
+            backend,
+        })
+    }
+
+    fn success(
+        &self,
+        prompt: &str,
+        embedding: &[f64],
+        model: String,
+        (prompt_tokens, total_tokens): (Option<u64>, Option<u64>),
+        raw_response: serde_json::Value,
+        (system_start, instant_start): (web_time::SystemTime, crate::clock::Stopwatch),
+    ) -> LLMResponse {
+        LLMResponse::Success(LLMCompleteResponse {
+            client: self.context.name.clone(),
+            prompt: RenderedPrompt::Completion(prompt.to_string()),
+            content: json!(embedding).to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model,
+            request_options: self.backend.properties().clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: None,
+                prompt_tokens,
+                output_tokens: Some(0),
+                total_tokens,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: self.backend.capture_raw_response().then_some(raw_response),
+            },
+        })
+    }
+
+    fn failure(
+        &self,
+        prompt: &str,
+        (system_start, instant_start): (web_time::SystemTime, crate::clock::Stopwatch),
+        message: String,
+    ) -> LLMResponse {
+        LLMResponse::LLMFailure(LLMErrorResponse {
+            client: self.context.name.clone(),
+            model: None,
+            prompt: RenderedPrompt::Completion(prompt.to_string()),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            request_options: self.backend.properties().clone(),
+            message,
+            code: ErrorCode::Other(200),
+        })
+    }
+}
+
+fn resolve_backend(
+    provider: &ClientProvider,
+    properties: &UnresolvedClientProperty<()>,
+    ctx: &RuntimeContext,
+) -> Result<Backend> {
+    match properties.resolve(provider, &ctx.eval_ctx(false))? {
+        ResolvedClientProperty::OpenAI(properties) => Ok(Backend::OpenAI(properties)),
+        #[cfg(feature = "provider-vertex")]
+        ResolvedClientProperty::Vertex(properties) => Ok(Backend::Vertex(properties)),
+        #[allow(unreachable_patterns)]
+        ResolvedClientProperty::Vertex(_) => Err(super::super::provider_not_compiled(provider)),
+        other => anyhow::bail!(
+            "Embedding clients don't support the {} provider",
+            other.name()
+        ),
+    }
+}
+
+fn completion_options() -> CompletionOptions {
+    CompletionOptions::new("\n".to_string())
+}
+
+/// The text to embed. Chat prompts, e.g. from `{{ _.role(...) }}`, are
+/// joined like completion prompts.
+fn input_text(prompt: either::Either<&String, &[RenderedChatMessage]>) -> String {
+    match prompt {
+        either::Either::Left(text) => text.clone(),
+        either::Either::Right(messages) => {
+            match RenderedPrompt::Chat(messages.to_vec()).as_completion(&completion_options()) {
+                RenderedPrompt::Completion(text) => text,
+                RenderedPrompt::Chat(_) => unreachable!("as_completion returns a completion"),
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+mod embedding_client;
+mod types;
+
+pub use embedding_client::EmbeddingClient;
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+
+/// The response of OpenAI's `/embeddings` endpoint.
+#[derive(Debug, Deserialize)]
+pub struct OpenAIEmbeddingResponse {
+    /// One embedding per input.
+    pub data: Vec<OpenAIEmbedding>,
+    pub model: String,
+    pub usage: Option<OpenAIEmbeddingUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIEmbedding {
+    pub embedding: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenAIEmbeddingUsage {
+    pub prompt_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// The response of a Vertex AI embedding model's `:predict` endpoint.
+#[cfg(feature = "provider-vertex")]
+#[derive(Debug, Deserialize)]
+pub struct VertexEmbeddingResponse {
+    /// One prediction per instance.
+    pub predictions: Vec<VertexPrediction>,
+}
+
+#[cfg(feature = "provider-vertex")]
+#[derive(Debug, Deserialize)]
+pub struct VertexPrediction {
+    pub embeddings: VertexEmbedding,
+}
+
+#[cfg(feature = "provider-vertex")]
+#[derive(Debug, Deserialize)]
+pub struct VertexEmbedding {
+    pub values: Vec<f64>,
+    pub statistics: Option<VertexEmbeddingStatistics>,
+}
+
+#[cfg(feature = "provider-vertex")]
+#[derive(Debug, Deserialize)]
+pub struct VertexEmbeddingStatistics {
+    pub token_count: Option<f64>,
+}
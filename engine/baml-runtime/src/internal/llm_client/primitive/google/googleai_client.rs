@@ -1,4 +1,5 @@
 use crate::client_registry::ClientProperty;
+use crate::internal::llm_client::image_preprocessing::{self, ImagePreprocessing};
 use crate::internal::llm_client::traits::{
     ToProviderMessage, ToProviderMessageExt, WithClientProperties,
 };
@@ -8,16 +9,16 @@ use crate::{
     internal::llm_client::{
         primitive::{
             google::types::{FinishReason, GoogleResponse},
-            request::{make_parsed_request, make_request, RequestBuilder},
+            request::{make_parsed_request, make_request, send_upload, RequestBuilder},
         },
         traits::{
-            SseResponseTrait, StreamResponse, WithChat, WithClient, WithNoCompletion,
+            accumulate_stream, SseResponseTrait, StreamResponse, WithChat, WithClient, WithNoCompletion,
             WithRetryPolicy, WithStreamChat,
         },
         ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
         ModelFeatures,
     },
-    request::create_client,
+    request::{create_client_with_options, json_body},
 };
 use anyhow::{Context, Result};
 use baml_types::{BamlMap, BamlMedia, BamlMediaContent};
@@ -78,6 +79,9 @@ impl WithClientProperties for GoogleAIClient {
     fn finish_reason_filter(&self) -> &internal_llm_client::FinishReasonFilter {
         &self.properties.finish_reason_filter
     }
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        self.properties.candidate_selection
+    }
     fn default_role(&self) -> String {
         self.properties.default_role()
     }
@@ -104,95 +108,98 @@ impl SseResponseTrait for GoogleAIClient {
         resp: reqwest::Response,
         prompt: &[RenderedChatMessage],
         system_start: web_time::SystemTime,
-        instant_start: web_time::Instant,
+        instant_start: crate::clock::Stopwatch,
     ) -> StreamResponse {
         let prompt = prompt.to_vec();
         let client_name = self.context.name.clone();
         let model_id = self.properties.model.clone();
         let params = self.properties.properties.clone();
-        Ok(Box::pin(
-            resp.bytes_stream()
-                .eventsource()
-                .inspect(|event| log::trace!("Received event: {:?}", event))
-                .take_while(|event| {
-                    std::future::ready(event.as_ref().is_ok_and(|e| e.data != "data: \n"))
-                })
-                .map(|event| -> Result<GoogleResponse> {
-                    Ok(serde_json::from_str::<GoogleResponse>(&event?.data)?)
-                })
-                .scan(
-                    Ok(LLMCompleteResponse {
-                        client: client_name.clone(),
-                        prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-                        content: "".to_string(),
-                        start_time: system_start,
-                        latency: instant_start.elapsed(),
-                        model: model_id,
-                        request_options: params.clone(),
-                        metadata: LLMCompleteResponseMetadata {
-                            baml_is_complete: false,
-                            finish_reason: None,
-                            prompt_tokens: None,
-                            output_tokens: None,
-                            total_tokens: None,
-                        },
-                    }),
-                    move |accumulated: &mut Result<LLMCompleteResponse>, event| {
-                        let Ok(ref mut inner) = accumulated else {
-                            // halt the stream: the last stream event failed to parse
-                            return std::future::ready(None);
-                        };
-                        let event = match event {
-                            Ok(event) => event,
-                            Err(e) => {
-                                return std::future::ready(Some(LLMResponse::LLMFailure(
-                                    LLMErrorResponse {
-                                        client: client_name.clone(),
-                                        model: if inner.model.is_empty() {
-                                            None
-                                        } else {
-                                            Some(inner.model.clone())
-                                        },
-                                        prompt: internal_baml_jinja::RenderedPrompt::Chat(
-                                            prompt.clone(),
-                                        ),
-                                        start_time: system_start,
-                                        request_options: params.clone(),
-                                        latency: instant_start.elapsed(),
-                                        message: format!("Failed to parse event: {:#?}", e),
-                                        code: ErrorCode::UnsupportedResponse(2),
-                                    },
-                                )));
-                            }
-                        };
-
-                        if let Some(choice) = event.candidates.get(0) {
-                            if let Some(content) = choice.content.as_ref().and_then(|c| c.parts.get(0)) {
-                                inner.content += &content.text;
-                            }
-                            if let Some(FinishReason::Stop) = choice.finish_reason.as_ref() {
-                                inner.metadata.baml_is_complete = true;
-                                inner.metadata.finish_reason = Some(FinishReason::Stop.to_string());
-                            }
-                        }
-                        inner.latency = instant_start.elapsed();
-
-                        std::future::ready(Some(LLMResponse::Success(inner.clone())))
-                    },
-                ),
-        ))
+        let events = resp
+            .bytes_stream()
+            .eventsource()
+            .inspect(|event| log::trace!("Received event: {:?}", event))
+            .take_while(|event| {
+                std::future::ready(event.as_ref().is_ok_and(|e| e.data != "data: \n"))
+            })
+            .map(|event| -> Result<GoogleResponse> {
+                Ok(serde_json::from_str::<GoogleResponse>(&event?.data)?)
+            });
+        let initial = LLMCompleteResponse {
+            client: client_name.clone(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: model_id,
+            request_options: params.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+        Ok(Box::pin(accumulate_stream(
+            events,
+            initial,
+            instant_start.clone(),
+            move |inner, event| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                            client: client_name.clone(),
+                            model: if inner.model.is_empty() {
+                                None
+                            } else {
+                                Some(inner.model.clone())
+                            },
+                            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                            start_time: system_start,
+                            request_options: params.clone(),
+                            latency: instant_start.elapsed(),
+                            message: format!("Failed to parse event: {:#?}", e),
+                            code: ErrorCode::UnsupportedResponse(2),
+                        }));
+                    }
+                };
+
+                let mut delta = None;
+                if let Some(choice) = event.candidates.get(0) {
+                    if let Some(content) = choice.content.as_ref() {
+                        delta = Some(content.text());
+                    }
+                    if let Some(grounding) = choice.grounding_metadata.as_ref() {
+                        inner.metadata.grounding = Some(grounding.to_response_metadata());
+                    }
+                    if let Some(FinishReason::Stop) = choice.finish_reason.as_ref() {
+                        inner.metadata.baml_is_complete = true;
+                        inner.metadata.finish_reason = Some(FinishReason::Stop.to_string());
+                    }
+                }
+                Ok(delta)
+            },
+        )))
     }
 }
 // makes the request to the google client, on success it triggers the response_stream function to handle continuous rendering with the response object
 impl WithStreamChat for GoogleAIClient {
     async fn stream_chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         prompt: &[RenderedChatMessage],
     ) -> StreamResponse {
         //incomplete, streaming response object is returned
         let (response, system_now, instant_now) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
@@ -201,6 +208,30 @@ impl WithStreamChat for GoogleAIClient {
 }
 
 impl GoogleAIClient {
+    /// Uploads to the Files API and returns the file's URI. Files expire after
+    /// 48 hours.
+    pub(crate) async fn upload_file(&self, bytes: &[u8], mime_type: &str) -> Result<String> {
+        // Uploads go to `/upload/v1beta/files` rather than `/v1beta/files`.
+        let mut url = reqwest::Url::parse(&self.properties.base_url)
+            .with_context(|| format!("Invalid base_url {}", self.properties.base_url))?;
+        url.set_path(&format!("/upload{}/files", url.path().trim_end_matches('/')));
+        let mut req = self
+            .client
+            .post(url)
+            .query(&[("uploadType", "media")])
+            .header(header::CONTENT_TYPE, mime_type)
+            .body(bytes.to_vec());
+        for (key, value) in &self.properties.headers {
+            req = req.header(key, value);
+        }
+        req = req.header("x-goog-api-key", self.properties.api_key.clone());
+        let response = send_upload(req).await?;
+        response["file"]["uri"]
+            .as_str()
+            .map(str::to_string)
+            .with_context(|| format!("File upload response has no uri: {response}"))
+    }
+
     pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<Self> {
         let properties = resolve_properties(&client.elem().provider, &client.options(), ctx)?;
         Ok(Self {
@@ -217,13 +248,17 @@ impl GoogleAIClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::GEMINI_MAX_DIMENSION,
+                ),
             },
             retry_policy: client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client_with_options(&properties.http)?,
             properties,
         })
     }
@@ -245,9 +280,13 @@ impl GoogleAIClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::GEMINI_MAX_DIMENSION,
+                ),
             },
             retry_policy: client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client_with_options(&properties.http)?,
             properties,
         })
     }
@@ -258,8 +297,13 @@ impl RequestBuilder for GoogleAIClient {
         &self.client
     }
 
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        &self.properties.http
+    }
+
     async fn build_request(
         &self,
+        _ctx: &RuntimeContext,
         prompt: either::Either<&String, &[RenderedChatMessage]>,
         allow_proxy: bool,
         stream: bool,
@@ -301,7 +345,7 @@ impl RequestBuilder for GoogleAIClient {
             }
         }
 
-        Ok(req.json(&body))
+        json_body(req, &body, self.properties.http.compress_requests)
     }
 
     fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
@@ -310,17 +354,17 @@ impl RequestBuilder for GoogleAIClient {
 }
 
 impl WithChat for GoogleAIClient {
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
         //non-streaming, complete response is returned
-        let (response, system_now, instant_now) =
-            match make_parsed_request::<GoogleResponse>(self, either::Either::Right(prompt), false)
+        let (response, raw_response, system_now, instant_now) =
+            match make_parsed_request::<GoogleResponse>(self, ctx, either::Either::Right(prompt), false)
                 .await
             {
                 Ok(v) => v,
                 Err(e) => return e,
             };
 
-        if response.candidates.len() != 1 {
+        if response.candidates.is_empty() {
             return LLMResponse::LLMFailure(LLMErrorResponse {
                 client: self.context.name.to_string(),
                 model: None,
@@ -328,10 +372,7 @@ impl WithChat for GoogleAIClient {
                 start_time: system_now,
                 request_options: self.properties.properties.clone(),
                 latency: instant_now.elapsed(),
-                message: format!(
-                    "Expected exactly one content block, got {}",
-                    response.candidates.len()
-                ),
+                message: "No candidates returned".to_string(),
                 code: ErrorCode::Other(200),
             });
         }
@@ -352,7 +393,7 @@ impl WithChat for GoogleAIClient {
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
-            content: content.parts[0].text.clone(),
+            content: content.text(),
             start_time: system_now,
             latency: instant_now.elapsed(),
             request_options: self.properties.properties.clone(),
@@ -369,6 +410,22 @@ impl WithChat for GoogleAIClient {
                 prompt_tokens: response.usage_metadata.prompt_token_count,
                 output_tokens: response.usage_metadata.candidates_token_count,
                 total_tokens: response.usage_metadata.total_token_count,
+                cached_input_tokens: None,
+                grounding: response.candidates[0]
+                    .grounding_metadata
+                    .as_ref()
+                    .map(|g| g.to_response_metadata()),
+                thinking: None,
+                logprobs: None,
+                candidates: (response.candidates.len() > 1).then(|| {
+                    response
+                        .candidates
+                        .iter()
+                        .map(|c| c.to_candidate())
+                        .collect()
+                }),
+                structured_output: false,
+                raw_response: self.properties.capture_raw_response.then_some(raw_response),
             },
         })
     }
@@ -430,6 +487,16 @@ impl ToProviderMessage for GoogleAIClient {
                 );
                 Ok(content)
             }
+            BamlMediaContent::FileRef(file_ref) => {
+                content.insert(
+                    "fileData".into(),
+                    json!({
+                        "mimeType": media.mime_type_as_ok()?,
+                        "fileUri": file_ref.file_id
+                    }),
+                );
+                Ok(content)
+            }
             BamlMediaContent::File(_) => anyhow::bail!(
                 "BAML internal error (google-ai): file should have been resolved to base64"
             ),
@@ -227,6 +227,22 @@ pub struct Candidate {
     // pub citation_metadata: Option<CitationMetadata>,
     pub grounding_metadata: Option<GroundingMetadata>,
     pub finish_message: Option<String>,
+    /// Set when the request asked for `responseLogprobs`.
+    pub avg_logprobs: Option<f64>,
+}
+
+impl Candidate {
+    pub fn to_candidate(&self) -> crate::internal::llm_client::ResponseCandidate {
+        crate::internal::llm_client::ResponseCandidate {
+            content: self.content.as_ref().map(Content::text).unwrap_or_default(),
+            finish_reason: self
+                .finish_reason
+                .as_ref()
+                .map(|r| serde_json::to_string(r).unwrap_or_default()),
+            logprob: self.avg_logprobs,
+            logprobs: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -236,15 +252,38 @@ pub struct Content {
     pub parts: Vec<Part>,
 }
 
+impl Content {
+    /// The text of every text part. Code the model ran with the
+    /// `code_execution` tool comes back in other parts and isn't included.
+    pub fn text(&self) -> String {
+        self.parts.iter().map(|p| p.text.as_str()).collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Part {
+    #[serde(default)]
     pub text: String,
     pub inline_data: Option<Blob>,
     pub file_data: Option<FileData>,
     pub function_call: Option<FunctionCall>,
     pub function_response: Option<FunctionResponse>,
     pub video_metadata: Option<VideoMetadata>,
+    pub executable_code: Option<ExecutableCode>,
+    pub code_execution_result: Option<CodeExecutionResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecutableCode {
+    pub language: String,
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CodeExecutionResult {
+    pub outcome: String,
+    pub output: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -340,15 +379,48 @@ pub struct Date {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GroundingMetadata {
+    #[serde(default)]
     pub web_search_queries: Vec<String>,
-    pub search_entry_point: SearchEntryPoint,
+    pub search_entry_point: Option<SearchEntryPoint>,
+    #[serde(default)]
+    pub grounding_chunks: Vec<GroundingChunk>,
+}
+
+impl GroundingMetadata {
+    pub fn to_response_metadata(&self) -> crate::internal::llm_client::GroundingMetadata {
+        crate::internal::llm_client::GroundingMetadata {
+            search_queries: self.web_search_queries.clone(),
+            citations: self
+                .grounding_chunks
+                .iter()
+                .filter_map(|chunk| chunk.web.as_ref().or(chunk.retrieved_context.as_ref()))
+                .map(|source| crate::internal::llm_client::Citation {
+                    uri: source.uri.clone(),
+                    title: source.title.clone(),
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchEntryPoint {
-    pub rendered_content: String,
-    pub sdk_blob: Vec<u8>,
+    pub rendered_content: Option<String>,
+    pub sdk_blob: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingChunk {
+    pub web: Option<GroundingSource>,
+    pub retrieved_context: Option<GroundingSource>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroundingSource {
+    pub uri: String,
+    pub title: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -432,4 +504,59 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_grounded_code_execution_response() {
+        let data = r#"
+        {
+            "candidates": [
+              {
+                "content": {
+                  "role": "model",
+                  "parts": [
+                    { "executableCode": { "language": "PYTHON", "code": "print(2 ** 10)" } },
+                    { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "1024\n" } },
+                    { "text": "2^10 is 1024, " },
+                    { "text": "according to the docs." }
+                  ]
+                },
+                "finishReason": "STOP",
+                "groundingMetadata": {
+                  "webSearchQueries": ["two to the tenth"],
+                  "searchEntryPoint": { "renderedContent": "<div></div>" },
+                  "groundingChunks": [
+                    { "web": { "uri": "https://example.com/powers", "title": "example.com" } }
+                  ]
+                }
+              }
+            ],
+            "usageMetadata": {
+              "promptTokenCount": 8,
+              "candidatesTokenCount": 21,
+              "totalTokenCount": 29
+            }
+          }
+        "#;
+
+        let response: GoogleResponse = serde_json::from_str(data).unwrap();
+        let candidate = &response.candidates[0];
+        assert_eq!(
+            candidate.content.as_ref().unwrap().text(),
+            "2^10 is 1024, according to the docs."
+        );
+        assert_eq!(
+            candidate
+                .grounding_metadata
+                .as_ref()
+                .unwrap()
+                .to_response_metadata(),
+            crate::internal::llm_client::GroundingMetadata {
+                search_queries: vec!["two to the tenth".to_string()],
+                citations: vec![crate::internal::llm_client::Citation {
+                    uri: "https://example.com/powers".to_string(),
+                    title: Some("example.com".to_string()),
+                }],
+            }
+        );
+    }
 }
@@ -0,0 +1,494 @@
+use anyhow::Result;
+use baml_types::{BamlMap, BamlMedia, BamlMediaContent, BamlMediaType};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use internal_baml_core::ir::ClientWalker;
+use internal_baml_jinja::{RenderContext_Client, RenderedChatMessage};
+use internal_llm_client::{
+    mistral::ResolvedMistral, AllowedRoleMetadata, ClientProvider, FinishReasonFilter,
+    ResolvedClientProperty, UnresolvedClientProperty,
+};
+use serde_json::json;
+
+use crate::{
+    client_registry::ClientProperty,
+    internal::llm_client::{
+        image_preprocessing::{self, ImagePreprocessing},
+        output_tool::OutputTool,
+        primitive::{
+            openai::types::{ChatCompletionResponse, ChatCompletionResponseDelta},
+            request::{make_parsed_request, make_request, RequestBuilder},
+        },
+        traits::{
+            accumulate_stream, SseResponseTrait, StreamResponse, ToProviderMessage,
+            ToProviderMessageExt, WithChat, WithClient, WithClientProperties, WithNoCompletion,
+            WithRetryPolicy, WithStreamChat,
+        },
+        ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
+        ModelFeatures, ResolveMediaUrls,
+    },
+    request::{create_client_with_options, json_body},
+    RuntimeContext,
+};
+
+// Mistral's chat API is OpenAI-shaped, but rejects `user` and
+// `stream_options`, takes `safe_prompt`, and images as a plain URL string.
+pub struct MistralClient {
+    pub name: String,
+    retry_policy: Option<String>,
+    context: RenderContext_Client,
+    features: ModelFeatures,
+    properties: ResolvedMistral,
+
+    // clients
+    client: reqwest::Client,
+}
+
+fn resolve_properties(
+    provider: &ClientProvider,
+    properties: &UnresolvedClientProperty<()>,
+    ctx: &RuntimeContext,
+) -> Result<ResolvedMistral, anyhow::Error> {
+    let properties = properties.resolve(provider, &ctx.eval_ctx(false))?;
+
+    let ResolvedClientProperty::Mistral(props) = properties else {
+        anyhow::bail!(
+            "Invalid client property. Should have been a mistral property but got: {}",
+            properties.name()
+        );
+    };
+
+    Ok(props)
+}
+
+impl WithRetryPolicy for MistralClient {
+    fn retry_policy_name(&self) -> Option<&str> {
+        self.retry_policy.as_deref()
+    }
+}
+
+impl WithClientProperties for MistralClient {
+    fn allowed_metadata(&self) -> &AllowedRoleMetadata {
+        &self.properties.allowed_metadata
+    }
+
+    fn finish_reason_filter(&self) -> &FinishReasonFilter {
+        &self.properties.finish_reason_filter
+    }
+
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        internal_llm_client::CandidateSelection::default()
+    }
+
+    fn allowed_roles(&self) -> Vec<String> {
+        self.properties.allowed_roles()
+    }
+
+    fn default_role(&self) -> String {
+        self.properties.default_role()
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.properties.supports_streaming()
+    }
+}
+
+impl WithClient for MistralClient {
+    fn context(&self) -> &RenderContext_Client {
+        &self.context
+    }
+
+    fn model_features(&self) -> &ModelFeatures {
+        &self.features
+    }
+}
+
+impl WithNoCompletion for MistralClient {}
+
+impl WithChat for MistralClient {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
+        let (response, raw_response, system_start, instant_start) =
+            match make_parsed_request::<ChatCompletionResponse>(
+                self,
+                ctx,
+                either::Either::Right(prompt),
+                false,
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+
+        let Some(choice) = response.choices.first() else {
+            return LLMResponse::LLMFailure(LLMErrorResponse {
+                client: self.context.name.to_string(),
+                model: Some(response.model),
+                prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
+                start_time: system_start,
+                latency: instant_start.elapsed(),
+                request_options: self.properties.properties.clone(),
+                message: "Expected a choices block, got none".to_string(),
+                code: ErrorCode::Other(200),
+            });
+        };
+
+        let usage = response.usage.as_ref();
+        let output_tool = self.output_tool(ctx);
+
+        LLMResponse::Success(LLMCompleteResponse {
+            client: self.context.name.to_string(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
+            content: choice.message.output(output_tool),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: response.model.clone(),
+            request_options: self.properties.properties.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: choice
+                    .finish_reason
+                    .as_ref()
+                    .is_some_and(|f| f == "stop" || (f == "tool_calls" && output_tool.is_some())),
+                finish_reason: choice.finish_reason.clone(),
+                prompt_tokens: usage.map(|u| u.prompt_tokens),
+                output_tokens: usage.map(|u| u.completion_tokens),
+                total_tokens: usage.map(|u| u.total_tokens),
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: (response.choices.len() > 1).then(|| {
+                    response
+                        .choices
+                        .iter()
+                        .map(|c| c.to_candidate(output_tool))
+                        .collect()
+                }),
+                structured_output: false,
+                raw_response: self.properties.capture_raw_response.then_some(raw_response),
+            },
+        })
+    }
+}
+
+impl RequestBuilder for MistralClient {
+    fn http_client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        &self.properties.http
+    }
+
+    async fn build_request(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: either::Either<&String, &[RenderedChatMessage]>,
+        allow_proxy: bool,
+        stream: bool,
+    ) -> Result<reqwest::RequestBuilder> {
+        let destination_url = if allow_proxy {
+            self.properties
+                .proxy_url
+                .as_ref()
+                .unwrap_or(&self.properties.base_url)
+        } else {
+            &self.properties.base_url
+        };
+
+        let mut req = self
+            .client
+            .post(format!("{}/chat/completions", destination_url));
+
+        for (key, value) in &self.properties.headers {
+            req = req.header(key, value);
+        }
+        req = req.bearer_auth(&self.properties.api_key);
+
+        if allow_proxy {
+            req = req.header("baml-original-url", self.properties.base_url.as_str());
+        }
+
+        let mut body = json!(self.properties.properties);
+        let body_obj = body.as_object_mut().unwrap();
+        match prompt {
+            either::Either::Left(_) => {
+                anyhow::bail!("Mistral only supports chat prompts")
+            }
+            either::Either::Right(messages) => {
+                body_obj.extend(self.chat_to_message(messages)?);
+            }
+        }
+
+        // `tools true` asks for the output as a call to a tool that takes it.
+        // Mistral's `tool_choice` can't name a tool, but `any` forces a call
+        // to the only one there is.
+        if let Some(tool) = self.output_tool(ctx).filter(|_| !stream) {
+            body_obj.insert(
+                "tools".into(),
+                json!([{
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "parameters": tool.parameters,
+                    },
+                }]),
+            );
+            body_obj.insert("tool_choice".into(), json!("any"));
+        }
+
+        if stream {
+            body_obj.insert("stream".into(), json!(true));
+        }
+
+        json_body(req, &body, self.properties.http.compress_requests)
+    }
+
+    fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
+        &self.properties.properties
+    }
+}
+
+impl SseResponseTrait for MistralClient {
+    fn response_stream(
+        &self,
+        resp: reqwest::Response,
+        prompt: &[RenderedChatMessage],
+        system_start: web_time::SystemTime,
+        instant_start: crate::clock::Stopwatch,
+    ) -> StreamResponse {
+        let prompt = prompt.to_vec();
+        let client_name = self.context.name.clone();
+        let params = self.properties.properties.clone();
+        let events = resp
+            .bytes_stream()
+            .eventsource()
+            .take_while(|event| {
+                std::future::ready(event.as_ref().is_ok_and(|e| e.data != "[DONE]"))
+            })
+            .map(|event| -> Result<ChatCompletionResponseDelta> {
+                Ok(serde_json::from_str::<ChatCompletionResponseDelta>(
+                    &event?.data,
+                )?)
+            })
+            .inspect(|event| log::trace!("{:#?}", event));
+        let initial = LLMCompleteResponse {
+            client: client_name.clone(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: "".to_string(),
+            request_options: params.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+        Ok(Box::pin(accumulate_stream(
+            events,
+            initial,
+            instant_start.clone(),
+            move |inner, event| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                            client: client_name.clone(),
+                            model: if inner.model.is_empty() {
+                                None
+                            } else {
+                                Some(inner.model.clone())
+                            },
+                            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                            start_time: system_start,
+                            request_options: params.clone(),
+                            latency: instant_start.elapsed(),
+                            message: format!("Failed to parse event: {:#?}", e),
+                            code: ErrorCode::UnsupportedResponse(2),
+                        }));
+                    }
+                };
+                let mut delta = None;
+                if let Some(choice) = event.choices.first() {
+                    delta = choice.delta.content.clone();
+                    inner.model = event.model;
+                    inner.metadata.finish_reason = choice.finish_reason.clone();
+                    inner.metadata.baml_is_complete =
+                        choice.finish_reason.as_ref().is_some_and(|s| s == "stop");
+                }
+                // Mistral sends usage with the last chunk, unprompted.
+                if let Some(usage) = event.usage.as_ref() {
+                    inner.metadata.prompt_tokens = Some(usage.prompt_tokens);
+                    inner.metadata.output_tokens = Some(usage.completion_tokens);
+                    inner.metadata.total_tokens = Some(usage.total_tokens);
+                }
+                Ok(delta)
+            },
+        )))
+    }
+}
+
+impl WithStreamChat for MistralClient {
+    async fn stream_chat(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &[RenderedChatMessage],
+    ) -> StreamResponse {
+        let (resp, system_start, instant_start) =
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
+        self.response_stream(resp, prompt, system_start, instant_start)
+    }
+}
+
+impl MistralClient {
+    pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<MistralClient> {
+        let properties = resolve_properties(&client.elem().provider, client.options(), ctx)?;
+        Ok(Self {
+            name: client.name().into(),
+            context: RenderContext_Client {
+                name: client.name().into(),
+                provider: client.elem().provider.to_string(),
+                default_role: properties.default_role(),
+                allowed_roles: properties.allowed_roles(),
+            },
+            features: Self::model_features(&properties),
+            retry_policy: client
+                .elem()
+                .retry_policy_id
+                .as_ref()
+                .map(|s| s.to_string()),
+            client: create_client_with_options(&properties.http)?,
+            properties,
+        })
+    }
+
+    pub fn dynamic_new(client: &ClientProperty, ctx: &RuntimeContext) -> Result<MistralClient> {
+        let properties = resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
+        Ok(Self {
+            name: client.name.clone(),
+            context: RenderContext_Client {
+                name: client.name.clone(),
+                provider: client.provider.to_string(),
+                default_role: properties.default_role(),
+                allowed_roles: properties.allowed_roles(),
+            },
+            features: Self::model_features(&properties),
+            retry_policy: client.retry_policy.clone(),
+            client: create_client_with_options(&properties.http)?,
+            properties,
+        })
+    }
+
+    fn model_features(properties: &ResolvedMistral) -> ModelFeatures {
+        ModelFeatures {
+            chat: true,
+            completion: false,
+            anthropic_system_constraints: false,
+            // Data URLs need a mime type; Mistral fetches other URLs itself.
+            resolve_media_urls: ResolveMediaUrls::Never,
+            allowed_metadata: properties.allowed_metadata.clone(),
+            image_preprocessing: ImagePreprocessing::from_options(
+                properties.image_preprocessing.as_ref(),
+                image_preprocessing::MISTRAL_MAX_DIMENSION,
+            ),
+        }
+    }
+
+    /// The tool to ask for the output through, with `tools true`.
+    fn output_tool<'a>(&self, ctx: &'a RuntimeContext) -> Option<&'a OutputTool> {
+        self.properties
+            .tools
+            .then_some(ctx.output_tool.as_ref())
+            .flatten()
+    }
+}
+
+impl ToProviderMessage for MistralClient {
+    fn to_chat_message(
+        &self,
+        mut content: serde_json::Map<String, serde_json::Value>,
+        text: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        content.insert("type".into(), json!("text"));
+        content.insert("text".into(), json!(text));
+        Ok(content)
+    }
+
+    fn to_media_message(
+        &self,
+        mut content: serde_json::Map<String, serde_json::Value>,
+        media: &BamlMedia,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        if media.media_type != BamlMediaType::Image {
+            anyhow::bail!(
+                "Mistral does not support {} inputs, only images",
+                media.media_type
+            );
+        }
+        let url = match &media.content {
+            BamlMediaContent::Url(url) => url.url.clone(),
+            BamlMediaContent::Base64(b64_media) => {
+                format!(
+                    "data:{};base64,{}",
+                    media.mime_type_as_ok()?,
+                    b64_media.base64
+                )
+            }
+            BamlMediaContent::FileRef(_) => {
+                anyhow::bail!("Mistral does not support uploaded files")
+            }
+            BamlMediaContent::File(_) => {
+                anyhow::bail!(
+                    "BAML internal error (mistral): file should have been resolved to base64"
+                )
+            }
+        };
+        content.insert("type".into(), json!("image_url"));
+        content.insert("image_url".into(), json!(url));
+        Ok(content)
+    }
+
+    fn role_to_message(
+        &self,
+        content: &RenderedChatMessage,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let mut message = serde_json::Map::new();
+        message.insert("role".into(), json!(content.role));
+        message.insert(
+            "content".into(),
+            json!(self.parts_to_message(&content.parts)?),
+        );
+        Ok(message)
+    }
+}
+
+impl ToProviderMessageExt for MistralClient {
+    fn chat_to_message(
+        &self,
+        chat: &[RenderedChatMessage],
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let mut res = serde_json::Map::new();
+        res.insert(
+            "messages".into(),
+            chat.iter()
+                .map(|c| self.role_to_message(c))
+                .collect::<Result<Vec<_>>>()?
+                .into(),
+        );
+        Ok(res)
+    }
+}
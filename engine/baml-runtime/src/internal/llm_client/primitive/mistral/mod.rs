@@ -0,0 +1,3 @@
+mod mistral_client;
+
+pub use mistral_client::MistralClient;
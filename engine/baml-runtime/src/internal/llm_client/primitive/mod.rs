@@ -1,19 +1,29 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use baml_types::{BamlMap, BamlValue};
+use baml_types::{BamlMap, BamlValue, ClientKind};
 use internal_baml_core::ir::{repr::IntermediateRepr, ClientWalker};
 use internal_llm_client::{AllowedRoleMetadata, ClientProvider, OpenAIClientProviderVariant};
+use std::str::FromStr;
 
 use crate::{
     client_registry::ClientProperty, internal::prompt_renderer::PromptRenderer,
     runtime_interface::InternalClientLookup, RenderCurlSettings, RuntimeContext,
 };
 
-use self::{
-    anthropic::AnthropicClient, aws::AwsClient, google::GoogleAIClient, openai::OpenAIClient,
-    request::RequestBuilder, vertex::VertexClient,
-};
+#[cfg(feature = "provider-anthropic")]
+use self::anthropic::AnthropicClient;
+#[cfg(feature = "provider-aws")]
+use self::aws::AwsClient;
+#[cfg(feature = "provider-cohere")]
+use self::cohere::CohereClient;
+#[cfg(feature = "provider-google")]
+use self::google::GoogleAIClient;
+#[cfg(feature = "provider-mistral")]
+use self::mistral::MistralClient;
+#[cfg(feature = "provider-vertex")]
+use self::vertex::VertexClient;
+use self::{embedding::EmbeddingClient, openai::OpenAIClient, request::RequestBuilder};
 
 use super::{
     orchestrator::{
@@ -27,24 +37,55 @@ use super::{
     LLMResponse,
 };
 
+#[cfg(feature = "provider-anthropic")]
 mod anthropic;
+#[cfg(feature = "provider-aws")]
 mod aws;
+#[cfg(feature = "provider-cohere")]
+mod cohere;
+mod embedding;
+#[cfg(feature = "provider-google")]
 mod google;
+#[cfg(feature = "provider-mistral")]
+mod mistral;
 mod openai;
 pub(super) mod request;
+#[cfg(feature = "provider-vertex")]
 mod vertex;
 
-// use crate::internal::llm_client::traits::ambassador_impl_WithRenderRawCurl;
-// use crate::internal::llm_client::traits::ambassador_impl_WithRetryPolicy;
-use enum_dispatch::enum_dispatch;
+/// Whether this build can construct clients for `provider`. Providers other
+/// than the OpenAI-compatible ones are behind `provider-*` cargo features.
+pub fn is_provider_supported(provider: &ClientProvider) -> bool {
+    match provider {
+        ClientProvider::OpenAI(_) | ClientProvider::Strategy(_) => true,
+        ClientProvider::Anthropic => cfg!(feature = "provider-anthropic"),
+        ClientProvider::AwsBedrock => cfg!(feature = "provider-aws"),
+        ClientProvider::GoogleAi => cfg!(feature = "provider-google"),
+        ClientProvider::Vertex => cfg!(feature = "provider-vertex"),
+        ClientProvider::Mistral => cfg!(feature = "provider-mistral"),
+        ClientProvider::Cohere => cfg!(feature = "provider-cohere"),
+    }
+}
+
+/// The `provider` names this build supports, e.g. for the playground to
+/// grey out clients it can't run.
+pub fn supported_providers() -> Vec<&'static str> {
+    ClientProvider::allowed_providers()
+        .iter()
+        .copied()
+        .filter(|name| {
+            ClientProvider::from_str(name)
+                .map(|p| is_provider_supported(&p))
+                .unwrap_or(false)
+        })
+        .collect()
+}
 
-#[enum_dispatch(WithRetryPolicy)]
-pub enum LLMPrimitive2 {
-    OpenAIClient,
-    AnthropicClient,
-    GoogleAIClient,
-    VertexClient,
-    AwsClient,
+fn provider_not_compiled(provider: &ClientProvider) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The `{provider}` provider is not included in this build of BAML. Supported providers: {}",
+        supported_providers().join(", ")
+    )
 }
 
 // #[derive(Delegate)]
@@ -52,10 +93,20 @@ pub enum LLMPrimitive2 {
 #[derive(derive_more::From)]
 pub enum LLMPrimitiveProvider {
     OpenAI(OpenAIClient),
+    #[cfg(feature = "provider-anthropic")]
     Anthropic(AnthropicClient),
+    #[cfg(feature = "provider-google")]
     Google(GoogleAIClient),
+    #[cfg(feature = "provider-vertex")]
     Vertex(VertexClient),
-    Aws(aws::AwsClient),
+    #[cfg(feature = "provider-aws")]
+    Aws(AwsClient),
+    #[cfg(feature = "provider-mistral")]
+    Mistral(MistralClient),
+    #[cfg(feature = "provider-cohere")]
+    Cohere(CohereClient),
+    /// A `client<embedding>` of any provider that has one.
+    Embedding(EmbeddingClient),
 }
 
 macro_rules! match_llm_provider {
@@ -63,20 +114,38 @@ macro_rules! match_llm_provider {
     ($self:expr, $method:ident, async $(, $args:tt)*) => {
         match $self {
             LLMPrimitiveProvider::OpenAI(client) => client.$method($($args),*).await,
+            #[cfg(feature = "provider-anthropic")]
             LLMPrimitiveProvider::Anthropic(client) => client.$method($($args),*).await,
+            #[cfg(feature = "provider-google")]
             LLMPrimitiveProvider::Google(client) => client.$method($($args),*).await,
+            #[cfg(feature = "provider-aws")]
             LLMPrimitiveProvider::Aws(client) => client.$method($($args),*).await,
+            #[cfg(feature = "provider-vertex")]
             LLMPrimitiveProvider::Vertex(client) => client.$method($($args),*).await,
+            #[cfg(feature = "provider-mistral")]
+            LLMPrimitiveProvider::Mistral(client) => client.$method($($args),*).await,
+            #[cfg(feature = "provider-cohere")]
+            LLMPrimitiveProvider::Cohere(client) => client.$method($($args),*).await,
+            LLMPrimitiveProvider::Embedding(client) => client.$method($($args),*).await,
         }
     };
 
     ($self:expr, $method:ident $(, $args:tt)*) => {
         match $self {
             LLMPrimitiveProvider::OpenAI(client) => client.$method($($args),*),
+            #[cfg(feature = "provider-anthropic")]
             LLMPrimitiveProvider::Anthropic(client) => client.$method($($args),*),
+            #[cfg(feature = "provider-google")]
             LLMPrimitiveProvider::Google(client) => client.$method($($args),*),
+            #[cfg(feature = "provider-aws")]
             LLMPrimitiveProvider::Aws(client) => client.$method($($args),*),
+            #[cfg(feature = "provider-vertex")]
             LLMPrimitiveProvider::Vertex(client) => client.$method($($args),*),
+            #[cfg(feature = "provider-mistral")]
+            LLMPrimitiveProvider::Mistral(client) => client.$method($($args),*),
+            #[cfg(feature = "provider-cohere")]
+            LLMPrimitiveProvider::Cohere(client) => client.$method($($args),*),
+            LLMPrimitiveProvider::Embedding(client) => client.$method($($args),*),
         }
     };
 }
@@ -97,6 +166,9 @@ impl WithClientProperties for LLMPrimitiveProvider {
     fn finish_reason_filter(&self) -> &internal_llm_client::FinishReasonFilter {
         match_llm_provider!(self, finish_reason_filter)
     }
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        match_llm_provider!(self, candidate_selection)
+    }
     fn default_role(&self) -> String {
         match_llm_provider!(self, default_role)
     }
@@ -121,15 +193,39 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMPrimitiveProvider {
                     OpenAIClientProviderVariant::Azure => {
                         OpenAIClient::dynamic_new_azure(value, ctx).map(Into::into)
                     }
+                    OpenAIClientProviderVariant::AzureAiInference => {
+                        OpenAIClient::dynamic_new_azure_ai_inference(value, ctx).map(Into::into)
+                    }
+                    OpenAIClientProviderVariant::XAi => {
+                        OpenAIClient::dynamic_new_xai(value, ctx).map(Into::into)
+                    }
+                    OpenAIClientProviderVariant::DeepSeek => {
+                        OpenAIClient::dynamic_new_deepseek(value, ctx).map(Into::into)
+                    }
                     OpenAIClientProviderVariant::Generic => {
                         OpenAIClient::dynamic_new_generic(value, ctx).map(Into::into)
                     }
                 }
             }
+            #[cfg(feature = "provider-anthropic")]
             ClientProvider::Anthropic => AnthropicClient::dynamic_new(value, ctx).map(Into::into),
+            #[cfg(feature = "provider-aws")]
             ClientProvider::AwsBedrock => AwsClient::dynamic_new(value, ctx).map(Into::into),
+            #[cfg(feature = "provider-google")]
             ClientProvider::GoogleAi => GoogleAIClient::dynamic_new(value, ctx).map(Into::into),
+            #[cfg(feature = "provider-vertex")]
             ClientProvider::Vertex => VertexClient::dynamic_new(value, ctx).map(Into::into),
+            #[cfg(feature = "provider-mistral")]
+            ClientProvider::Mistral => MistralClient::dynamic_new(value, ctx).map(Into::into),
+            #[cfg(feature = "provider-cohere")]
+            ClientProvider::Cohere => CohereClient::dynamic_new(value, ctx).map(Into::into),
+            #[allow(unreachable_patterns)]
+            ClientProvider::Anthropic
+            | ClientProvider::AwsBedrock
+            | ClientProvider::GoogleAi
+            | ClientProvider::Vertex
+            | ClientProvider::Mistral
+            | ClientProvider::Cohere => Err(provider_not_compiled(&value.provider)),
             ClientProvider::Strategy(strategy_client_provider) => {
                 unimplemented!(
                     "Strategy client providers are not supported yet in LLMPrimitiveProvider"
@@ -167,6 +263,9 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for LLMPrimitiveProvider {
     type Error = anyhow::Error;
 
     fn try_from((client, ctx): (&ClientWalker, &RuntimeContext)) -> Result<Self> {
+        if client.elem().kind == ClientKind::Embedding {
+            return EmbeddingClient::new(client, ctx).map(Into::into);
+        }
         match &client.elem().provider {
             ClientProvider::OpenAI(open_aiclient_provider_variant) => {
                 match open_aiclient_provider_variant {
@@ -179,15 +278,39 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for LLMPrimitiveProvider {
                     OpenAIClientProviderVariant::Azure => {
                         OpenAIClient::new_azure(client, ctx).map(Into::into)
                     }
+                    OpenAIClientProviderVariant::AzureAiInference => {
+                        OpenAIClient::new_azure_ai_inference(client, ctx).map(Into::into)
+                    }
+                    OpenAIClientProviderVariant::XAi => {
+                        OpenAIClient::new_xai(client, ctx).map(Into::into)
+                    }
+                    OpenAIClientProviderVariant::DeepSeek => {
+                        OpenAIClient::new_deepseek(client, ctx).map(Into::into)
+                    }
                     OpenAIClientProviderVariant::Generic => {
                         OpenAIClient::new_generic(client, ctx).map(Into::into)
                     }
                 }
             }
+            #[cfg(feature = "provider-anthropic")]
             ClientProvider::Anthropic => AnthropicClient::new(client, ctx).map(Into::into),
+            #[cfg(feature = "provider-aws")]
             ClientProvider::AwsBedrock => AwsClient::new(client, ctx).map(Into::into),
+            #[cfg(feature = "provider-google")]
             ClientProvider::GoogleAi => GoogleAIClient::new(client, ctx).map(Into::into),
+            #[cfg(feature = "provider-vertex")]
             ClientProvider::Vertex => VertexClient::new(client, ctx).map(Into::into),
+            #[cfg(feature = "provider-mistral")]
+            ClientProvider::Mistral => MistralClient::new(client, ctx).map(Into::into),
+            #[cfg(feature = "provider-cohere")]
+            ClientProvider::Cohere => CohereClient::new(client, ctx).map(Into::into),
+            #[allow(unreachable_patterns)]
+            ClientProvider::Anthropic
+            | ClientProvider::AwsBedrock
+            | ClientProvider::GoogleAi
+            | ClientProvider::Vertex
+            | ClientProvider::Mistral
+            | ClientProvider::Cohere => Err(provider_not_compiled(&client.elem().provider)),
             ClientProvider::Strategy(strategy_client_provider) => {
                 unimplemented!(
                     "Strategy client providers are not supported yet in LLMPrimitiveProvider"
@@ -259,10 +382,19 @@ impl std::fmt::Display for LLMPrimitiveProvider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             LLMPrimitiveProvider::OpenAI(_) => write!(f, "OpenAI"),
+            #[cfg(feature = "provider-anthropic")]
             LLMPrimitiveProvider::Anthropic(_) => write!(f, "Anthropic"),
+            #[cfg(feature = "provider-google")]
             LLMPrimitiveProvider::Google(_) => write!(f, "Google"),
+            #[cfg(feature = "provider-aws")]
             LLMPrimitiveProvider::Aws(_) => write!(f, "AWS"),
+            #[cfg(feature = "provider-vertex")]
             LLMPrimitiveProvider::Vertex(_) => write!(f, "Vertex"),
+            #[cfg(feature = "provider-mistral")]
+            LLMPrimitiveProvider::Mistral(_) => write!(f, "Mistral"),
+            #[cfg(feature = "provider-cohere")]
+            LLMPrimitiveProvider::Cohere(_) => write!(f, "Cohere"),
+            LLMPrimitiveProvider::Embedding(_) => write!(f, "Embedding"),
         }
     }
 }
@@ -275,4 +407,96 @@ impl LLMPrimitiveProvider {
     pub fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
         match_llm_provider!(self, request_options)
     }
+
+    /// `None` for providers that go through an SDK instead of plain HTTP.
+    pub(crate) async fn check_reachable(
+        &self,
+        ctx: &RuntimeContext,
+        timeout: web_time::Duration,
+    ) -> Option<Result<()>> {
+        match self {
+            LLMPrimitiveProvider::OpenAI(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            #[cfg(feature = "provider-anthropic")]
+            LLMPrimitiveProvider::Anthropic(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            #[cfg(feature = "provider-google")]
+            LLMPrimitiveProvider::Google(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            #[cfg(feature = "provider-vertex")]
+            LLMPrimitiveProvider::Vertex(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            #[cfg(feature = "provider-mistral")]
+            LLMPrimitiveProvider::Mistral(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            #[cfg(feature = "provider-cohere")]
+            LLMPrimitiveProvider::Cohere(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            LLMPrimitiveProvider::Embedding(client) => {
+                Some(request::check_reachable(client, ctx, timeout).await)
+            }
+            #[cfg(feature = "provider-aws")]
+            LLMPrimitiveProvider::Aws(_) => None,
+        }
+    }
+
+    /// Uploads `bytes` to the provider's file API, returning the id to
+    /// reference it by in later requests.
+    pub(crate) async fn upload_file(&self, bytes: &[u8], mime_type: &str) -> Result<String> {
+        match self {
+            LLMPrimitiveProvider::OpenAI(client) => client.upload_file(bytes, mime_type).await,
+            #[cfg(feature = "provider-google")]
+            LLMPrimitiveProvider::Google(client) => client.upload_file(bytes, mime_type).await,
+            #[allow(unreachable_patterns)]
+            _ => anyhow::bail!("File uploads are not supported for {self} clients"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_providers_follow_features() {
+        let supported = supported_providers();
+        for always in [
+            "openai",
+            "openai-generic",
+            "azure-openai",
+            "azure-ai-inference",
+            "x-ai",
+            "deepseek",
+            "ollama",
+            "fallback",
+            "round-robin",
+        ] {
+            assert!(
+                supported.contains(&always),
+                "{always} should always be supported"
+            );
+        }
+        assert_eq!(
+            supported.contains(&"aws-bedrock"),
+            cfg!(feature = "provider-aws")
+        );
+        assert_eq!(
+            is_provider_supported(&ClientProvider::Vertex),
+            cfg!(feature = "provider-vertex")
+        );
+        assert_eq!(
+            supported.contains(&"mistral"),
+            cfg!(feature = "provider-mistral")
+        );
+        assert_eq!(
+            supported.contains(&"cohere"),
+            cfg!(feature = "provider-cohere")
+        );
+    }
 }
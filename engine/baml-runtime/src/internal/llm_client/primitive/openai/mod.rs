@@ -1,6 +1,6 @@
 mod openai_client;
 mod properties;
 #[allow(dead_code)]
-mod types;
+pub(super) mod types;
 
 pub use openai_client::OpenAIClient;
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 
+use crate::internal::llm_client::image_preprocessing::{self, ImagePreprocessing};
+use crate::internal::llm_client::output_tool::OutputTool;
 use crate::internal::llm_client::ResolveMediaUrls;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use baml_types::{BamlMap, BamlMedia, BamlMediaContent, BamlMediaType};
 use internal_baml_core::ir::ClientWalker;
 use internal_baml_jinja::{ChatMessagePart, RenderContext_Client, RenderedChatMessage};
@@ -14,14 +16,17 @@ use crate::internal::llm_client::{
 };
 
 use super::properties;
-use super::types::{ChatCompletionResponse, ChatCompletionResponseDelta};
+use super::types::{
+    ChatCompletionResponse, ChatCompletionResponseDelta, ChatCompletionResponseMessage,
+    CompletionUsage,
+};
 
 use crate::client_registry::ClientProperty;
 use crate::internal::llm_client::primitive::request::{
-    make_parsed_request, make_request, RequestBuilder,
+    make_parsed_request, make_request, multipart_body, send_upload, RequestBuilder,
 };
 use crate::internal::llm_client::traits::{
-    SseResponseTrait, StreamResponse, ToProviderMessage, ToProviderMessageExt,
+    accumulate_stream, SseResponseTrait, StreamResponse, ToProviderMessage, ToProviderMessageExt,
     WithClientProperties, WithStreamChat,
 };
 use crate::internal::llm_client::{
@@ -29,7 +34,7 @@ use crate::internal::llm_client::{
     LLMResponse, ModelFeatures,
 };
 
-use crate::request::create_client;
+use crate::request::{create_client_with_options, json_body};
 use crate::RuntimeContext;
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
@@ -61,6 +66,10 @@ impl WithClientProperties for OpenAIClient {
         &self.properties.finish_reason_filter
     }
 
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        self.properties.candidate_selection
+    }
+
     fn allowed_roles(&self) -> Vec<String> {
         self.properties.allowed_roles()
     }
@@ -157,10 +166,11 @@ impl WithNoCompletion for OpenAIClient {}
 // }
 
 impl WithChat for OpenAIClient {
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
-        let (response, system_start, instant_start) =
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
+        let (response, raw_response, system_start, instant_start) =
             match make_parsed_request::<ChatCompletionResponse>(
                 self,
+                ctx,
                 either::Either::Right(prompt),
                 false,
             )
@@ -187,23 +197,26 @@ impl WithChat for OpenAIClient {
         }
 
         let usage = response.usage.as_ref();
+        let output_tool = self.output_tool(ctx);
+        let output_schema = self.output_schema(ctx, false);
+        let output = |message: &ChatCompletionResponseMessage| match output_schema {
+            Some(schema) => message.structured_output(schema),
+            None => message.output(output_tool),
+        };
 
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
-            content: response.choices[0]
-                .message
-                .content
-                .as_ref()
-                .map_or("", |s| s.as_str())
-                .to_string(),
+            content: output(&response.choices[0].message),
             start_time: system_start,
             latency: instant_start.elapsed(),
             model: response.model,
             request_options: self.properties.properties.clone(),
             metadata: LLMCompleteResponseMetadata {
                 baml_is_complete: match response.choices.get(0) {
-                    Some(c) => c.finish_reason.as_ref().is_some_and(|f| f == "stop"),
+                    Some(c) => c.finish_reason.as_ref().is_some_and(|f| {
+                        f == "stop" || (f == "tool_calls" && output_tool.is_some())
+                    }),
                     None => false,
                 },
                 finish_reason: match response.choices.get(0) {
@@ -213,6 +226,25 @@ impl WithChat for OpenAIClient {
                 prompt_tokens: usage.map(|u| u.prompt_tokens),
                 output_tokens: usage.map(|u| u.completion_tokens),
                 total_tokens: usage.map(|u| u.total_tokens),
+                cached_input_tokens: usage.and_then(CompletionUsage::cached_tokens),
+                grounding: None,
+                thinking: response.choices[0].message.reasoning_content.clone(),
+                logprobs: response.choices[0]
+                    .logprobs
+                    .as_ref()
+                    .map(|l| l.to_response_metadata()),
+                candidates: (response.choices.len() > 1).then(|| {
+                    response
+                        .choices
+                        .iter()
+                        .map(|c| crate::internal::llm_client::ResponseCandidate {
+                            content: output(&c.message),
+                            ..c.to_candidate(output_tool)
+                        })
+                        .collect()
+                }),
+                structured_output: output_schema.is_some(),
+                raw_response: self.properties.capture_raw_response.then_some(raw_response),
             },
         })
     }
@@ -223,8 +255,13 @@ impl RequestBuilder for OpenAIClient {
         &self.client
     }
 
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        &self.properties.http
+    }
+
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &[RenderedChatMessage]>,
         allow_proxy: bool,
         stream: bool,
@@ -272,9 +309,66 @@ impl RequestBuilder for OpenAIClient {
             }
         }
 
+        // An explicit `user` in the client options takes precedence.
+        if let Some(user_id) = self
+            .properties
+            .user_id_tag
+            .as_ref()
+            .and_then(|tag| ctx.tag_string(tag))
+        {
+            body_obj.entry("user").or_insert(json!(user_id));
+        }
+
+        // `@@confidence` needs the alternatives to the answer's first token.
+        if ctx.request_logprobs && prompt.is_right() && !body_obj.contains_key("logprobs") {
+            body_obj.insert("logprobs".into(), json!(true));
+            body_obj.insert("top_logprobs".into(), json!(20));
+        }
+
+        // `structured_output true` asks for JSON matching the output type.
+        if let Some(schema) = self
+            .output_schema(ctx, stream)
+            .filter(|_| prompt.is_right())
+        {
+            body_obj.insert(
+                "response_format".into(),
+                json!({
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": schema.name,
+                        "schema": schema.parameters,
+                    },
+                }),
+            );
+        }
+
+        // `tools true` asks for the output as a call to a tool that takes it.
+        if let Some(tool) = self
+            .output_tool(ctx)
+            .filter(|_| prompt.is_right() && !stream)
+        {
+            body_obj.insert(
+                "tools".into(),
+                json!([{
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "parameters": tool.parameters,
+                    },
+                }]),
+            );
+            body_obj.insert(
+                "tool_choice".into(),
+                json!({
+                    "type": "function",
+                    "function": { "name": tool.name },
+                }),
+            );
+        }
+
         if stream {
             body_obj.insert("stream".into(), json!(true));
-            if self.provider == "openai" {
+            if ["openai", "x-ai", "deepseek"].contains(&self.provider.as_str()) {
                 body_obj.insert(
                     "stream_options".into(),
                     json!({
@@ -284,7 +378,7 @@ impl RequestBuilder for OpenAIClient {
             }
         }
 
-        Ok(req.json(&body))
+        json_body(req, &body, self.properties.http.compress_requests)
     }
 
     fn request_options(&self) -> &BamlMap<String, serde_json::Value> {
@@ -298,98 +392,112 @@ impl SseResponseTrait for OpenAIClient {
         resp: reqwest::Response,
         prompt: &[RenderedChatMessage],
         system_start: web_time::SystemTime,
-        instant_start: web_time::Instant,
+        instant_start: crate::clock::Stopwatch,
     ) -> StreamResponse {
         let prompt = prompt.to_vec();
         let client_name = self.context.name.clone();
         let params = self.properties.properties.clone();
-        Ok(Box::pin(
-            resp.bytes_stream()
-                .eventsource()
-                .take_while(|event| {
-                    std::future::ready(event.as_ref().is_ok_and(|e| e.data != "[DONE]"))
-                })
-                .map(|event| -> Result<ChatCompletionResponseDelta> {
-                    Ok(serde_json::from_str::<ChatCompletionResponseDelta>(
-                        &event?.data,
-                    )?)
-                })
-                .inspect(|event| log::trace!("{:#?}", event))
-                .scan(
-                    Ok(LLMCompleteResponse {
-                        client: client_name.clone(),
-                        prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-                        content: "".to_string(),
-                        start_time: system_start,
-                        latency: instant_start.elapsed(),
-                        model: "".to_string(),
-                        request_options: params.clone(),
-                        metadata: LLMCompleteResponseMetadata {
-                            baml_is_complete: false,
-                            finish_reason: None,
-                            prompt_tokens: None,
-                            output_tokens: None,
-                            total_tokens: None,
-                        },
-                    }),
-                    move |accumulated: &mut Result<LLMCompleteResponse>, event| {
-                        let Ok(ref mut inner) = accumulated else {
-                            // halt the stream: the last stream event failed to parse
-                            return std::future::ready(None);
-                        };
-                        let event = match event {
-                            Ok(event) => event,
-                            Err(e) => {
-                                return std::future::ready(Some(LLMResponse::LLMFailure(
-                                    LLMErrorResponse {
-                                        client: client_name.clone(),
-                                        model: if inner.model.is_empty() {
-                                            None
-                                        } else {
-                                            Some(inner.model.clone())
-                                        },
-                                        prompt: internal_baml_jinja::RenderedPrompt::Chat(
-                                            prompt.clone(),
-                                        ),
-                                        start_time: system_start,
-                                        request_options: params.clone(),
-                                        latency: instant_start.elapsed(),
-                                        message: format!("Failed to parse event: {:#?}", e),
-                                        code: ErrorCode::UnsupportedResponse(2),
-                                    },
-                                )));
-                            }
-                        };
-                        if let Some(choice) = event.choices.first() {
-                            if let Some(content) = choice.delta.content.as_ref() {
-                                inner.content += content.as_str();
-                            }
-                            inner.model = event.model;
-                            inner.metadata.finish_reason = choice.finish_reason.clone();
-                            inner.metadata.baml_is_complete = choice.finish_reason.as_ref().is_some_and(|s| s == "stop");
-                        }
-                        inner.latency = instant_start.elapsed();
-                        if let Some(usage) = event.usage.as_ref() {
-                            inner.metadata.prompt_tokens = Some(usage.prompt_tokens);
-                            inner.metadata.output_tokens = Some(usage.completion_tokens);
-                            inner.metadata.total_tokens = Some(usage.total_tokens);
-                        }
-
-                        std::future::ready(Some(LLMResponse::Success(inner.clone())))
-                    },
-                ),
-        ))
+        let events = resp
+            .bytes_stream()
+            .eventsource()
+            .take_while(|event| {
+                std::future::ready(event.as_ref().is_ok_and(|e| e.data != "[DONE]"))
+            })
+            .map(|event| -> Result<ChatCompletionResponseDelta> {
+                Ok(serde_json::from_str::<ChatCompletionResponseDelta>(
+                    &event?.data,
+                )?)
+            })
+            .inspect(|event| log::trace!("{:#?}", event));
+        let initial = LLMCompleteResponse {
+            client: client_name.clone(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: "".to_string(),
+            request_options: params.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+        Ok(Box::pin(accumulate_stream(
+            events,
+            initial,
+            instant_start.clone(),
+            move |inner, event| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                            client: client_name.clone(),
+                            model: if inner.model.is_empty() {
+                                None
+                            } else {
+                                Some(inner.model.clone())
+                            },
+                            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                            start_time: system_start,
+                            request_options: params.clone(),
+                            latency: instant_start.elapsed(),
+                            message: format!("Failed to parse event: {:#?}", e),
+                            code: ErrorCode::UnsupportedResponse(2),
+                        }));
+                    }
+                };
+                let mut delta = None;
+                if let Some(choice) = event.choices.first() {
+                    delta = choice.delta.content.clone();
+                    if let Some(reasoning) = choice.delta.reasoning_content.as_deref() {
+                        inner
+                            .metadata
+                            .thinking
+                            .get_or_insert_with(String::new)
+                            .push_str(reasoning);
+                    }
+                    inner.model = event.model;
+                    inner.metadata.finish_reason = choice.finish_reason.clone();
+                    inner.metadata.baml_is_complete =
+                        choice.finish_reason.as_ref().is_some_and(|s| s == "stop");
+                    if let Some(logprobs) = choice.logprobs.as_ref() {
+                        inner
+                            .metadata
+                            .logprobs
+                            .get_or_insert_with(Vec::new)
+                            .extend(logprobs.to_response_metadata());
+                    }
+                }
+                if let Some(usage) = event.usage.as_ref() {
+                    inner.metadata.prompt_tokens = Some(usage.prompt_tokens);
+                    inner.metadata.output_tokens = Some(usage.completion_tokens);
+                    inner.metadata.total_tokens = Some(usage.total_tokens);
+                    inner.metadata.cached_input_tokens = usage.cached_tokens();
+                }
+                Ok(delta)
+            },
+        )))
     }
 }
 
 impl WithStreamChat for OpenAIClient {
     async fn stream_chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         prompt: &[RenderedChatMessage],
     ) -> StreamResponse {
         let (resp, system_start, instant_start) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
@@ -414,10 +522,14 @@ macro_rules! make_openai_client {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::Never,
                 allowed_metadata: $properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    $properties.image_preprocessing.as_ref(),
+                    image_preprocessing::OPENAI_MAX_DIMENSION,
+                ),
             },
+            client: create_client_with_options(&$properties.http)?,
             properties: $properties,
             retry_policy: $client.retry_policy.clone(),
-            client: create_client()?,
         })
     };
     ($client:ident, $properties:ident, $provider:expr) => {
@@ -436,19 +548,72 @@ macro_rules! make_openai_client {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::Never,
                 allowed_metadata: $properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    $properties.image_preprocessing.as_ref(),
+                    image_preprocessing::OPENAI_MAX_DIMENSION,
+                ),
             },
+            client: create_client_with_options(&$properties.http)?,
             properties: $properties,
             retry_policy: $client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
         })
     };
 }
 
 impl OpenAIClient {
+    /// The tool to ask for the output through, with `tools true`.
+    fn output_tool<'a>(&self, ctx: &'a RuntimeContext) -> Option<&'a OutputTool> {
+        self.properties
+            .tools
+            .then_some(ctx.output_tool.as_ref())
+            .flatten()
+    }
+
+    /// The output type to ask for JSON of, with `structured_output true`.
+    /// Streams only ask for classes: other outputs are wrapped in an object,
+    /// which partial results would show.
+    fn output_schema<'a>(&self, ctx: &'a RuntimeContext, stream: bool) -> Option<&'a OutputTool> {
+        self.properties
+            .structured_output
+            .then_some(ctx.output_tool.as_ref())
+            .flatten()
+            .filter(|schema| !stream || !schema.is_wrapped())
+    }
+
+    /// Uploads to the `/files` endpoint and returns the file's id.
+    pub(crate) async fn upload_file(&self, bytes: &[u8], mime_type: &str) -> Result<String> {
+        let (content_type, body) = multipart_body(
+            &[("purpose", "user_data")],
+            "file",
+            &upload_file_name(mime_type),
+            mime_type,
+            bytes,
+        );
+        let mut req = self
+            .client
+            .post(format!("{}/files", self.properties.base_url))
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body);
+        if !self.properties.query_params.is_empty() {
+            req = req.query(&self.properties.query_params);
+        }
+        for (key, value) in &self.properties.headers {
+            req = req.header(key, value);
+        }
+        if let Some(key) = &self.properties.api_key {
+            req = req.bearer_auth(key);
+        }
+        let response = send_upload(req).await?;
+        response["id"]
+            .as_str()
+            .map(str::to_string)
+            .with_context(|| format!("File upload response has no id: {response}"))
+    }
+
     pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
         let properties =
             properties::resolve_properties(&client.elem().provider, client.options(), ctx)?;
@@ -473,6 +638,27 @@ impl OpenAIClient {
         make_openai_client!(client, properties, "azure")
     }
 
+    pub fn new_azure_ai_inference(
+        client: &ClientWalker,
+        ctx: &RuntimeContext,
+    ) -> Result<OpenAIClient> {
+        let properties =
+            properties::resolve_properties(&client.elem().provider, client.options(), ctx)?;
+        make_openai_client!(client, properties, "azure-ai-inference")
+    }
+
+    pub fn new_xai(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
+        let properties =
+            properties::resolve_properties(&client.elem().provider, client.options(), ctx)?;
+        make_openai_client!(client, properties, "x-ai")
+    }
+
+    pub fn new_deepseek(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
+        let properties =
+            properties::resolve_properties(&client.elem().provider, client.options(), ctx)?;
+        make_openai_client!(client, properties, "deepseek")
+    }
+
     pub fn dynamic_new(client: &ClientProperty, ctx: &RuntimeContext) -> Result<OpenAIClient> {
         let properties =
             properties::resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
@@ -505,6 +691,30 @@ impl OpenAIClient {
             properties::resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
         make_openai_client!(client, properties, "azure", dynamic)
     }
+
+    pub fn dynamic_new_azure_ai_inference(
+        client: &ClientProperty,
+        ctx: &RuntimeContext,
+    ) -> Result<OpenAIClient> {
+        let properties =
+            properties::resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
+        make_openai_client!(client, properties, "azure-ai-inference", dynamic)
+    }
+
+    pub fn dynamic_new_xai(client: &ClientProperty, ctx: &RuntimeContext) -> Result<OpenAIClient> {
+        let properties =
+            properties::resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
+        make_openai_client!(client, properties, "x-ai", dynamic)
+    }
+
+    pub fn dynamic_new_deepseek(
+        client: &ClientProperty,
+        ctx: &RuntimeContext,
+    ) -> Result<OpenAIClient> {
+        let properties =
+            properties::resolve_properties(&client.provider, &client.unresolved_options()?, ctx)?;
+        make_openai_client!(client, properties, "deepseek", dynamic)
+    }
 }
 
 impl ToProviderMessage for OpenAIClient {
@@ -547,6 +757,10 @@ impl ToProviderMessage for OpenAIClient {
                     }),
                 );
             }
+            BamlMediaContent::FileRef(file_ref) => {
+                content.insert("type".into(), json!("file"));
+                content.insert("file".into(), json!({ "file_id": file_ref.file_id }));
+            }
             BamlMediaContent::File(_) => {
                 anyhow::bail!(
                     "BAML internal error (openai): file should have been resolved to base64"
@@ -622,3 +836,10 @@ impl ToProviderMessageExt for OpenAIClient {
         Ok(res)
     }
 }
+
+/// Some providers infer the file type from its name, so give it a matching
+/// extension.
+fn upload_file_name(mime_type: &str) -> String {
+    let extension = mime_type.split('/').nth(1).unwrap_or("bin");
+    format!("upload.{extension}")
+}
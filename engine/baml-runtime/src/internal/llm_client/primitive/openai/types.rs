@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::internal::llm_client::output_tool::OutputTool;
+
 pub type CompletionResponse = ChatCompletionGeneric<CompletionChoice>;
 pub type ChatCompletionResponse = ChatCompletionGeneric<ChatCompletionChoice>;
 
@@ -56,6 +58,23 @@ pub struct CompletionUsage {
     pub completion_tokens: u64,
     /// Total number of tokens used in the request (prompt + completion).
     pub total_tokens: u64,
+    /// Breakdown of tokens used in the prompt.
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+impl CompletionUsage {
+    /// Prompt tokens served from the prompt cache.
+    pub fn cached_tokens(&self) -> Option<u64> {
+        self.prompt_tokens_details
+            .as_ref()
+            .and_then(|details| details.cached_tokens)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct PromptTokensDetails {
+    /// Tokens in the prompt that were present in the prompt cache.
+    pub cached_tokens: Option<u64>,
 }
 
 /// A chat completion message generated by the model.
@@ -65,21 +84,63 @@ pub struct ChatCompletionResponseMessage {
     pub content: Option<String>,
 
     /// The tool calls generated by the model, such as function calls.
-    // pub tool_calls: Option<Vec<ChatCompletionMessageToolCall>>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<ChatCompletionMessageToolCall>>,
 
     /// The role of the author of this message.
     pub role: ChatCompletionMessageRole,
+
+    /// What a reasoning model wrote before answering, from DeepSeek and xAI.
+    #[serde(default)]
+    pub reasoning_content: Option<String>,
     // Deprecated and replaced by `tool_calls`.
     // The name and arguments of a function that should be called, as generated by the model.
     // #[deprecated]
     // pub function_call: Option<FunctionCall>,
 }
 
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ChatCompletionMessageToolCall {
+    pub id: Option<String>,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    /// The arguments as a JSON string, which the model may not have made
+    /// valid.
+    pub arguments: String,
+}
+
+impl ChatCompletionResponseMessage {
+    /// The message text, or the output in the arguments of a call to
+    /// `output_tool` if the model made one.
+    pub fn output(&self, output_tool: Option<&OutputTool>) -> String {
+        let tool_call = output_tool.and_then(|tool| {
+            let call = self
+                .tool_calls
+                .iter()
+                .flatten()
+                .find(|call| call.function.name == tool.name)?;
+            Some(tool.output(&call.function.arguments))
+        });
+        tool_call.unwrap_or_else(|| self.content.clone().unwrap_or_default())
+    }
+
+    /// The output in the message text, which is JSON of `schema`'s
+    /// parameters with `structured_output true`.
+    pub fn structured_output(&self, schema: &OutputTool) -> String {
+        schema.output(self.content.as_deref().unwrap_or_default())
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct ChatCompletionChoiceDelta {
     pub index: u64,
     pub finish_reason: Option<String>,
     pub delta: ChatCompletionMessageDelta,
+    pub logprobs: Option<ChatChoiceLogprobs>,
 }
 
 /// Same as ChatCompletionMessage, but received during a response stream.
@@ -89,6 +150,9 @@ pub struct ChatCompletionMessageDelta {
     pub role: Option<ChatCompletionMessageRole>,
     /// The contents of the message
     pub content: Option<String>,
+    /// Reasoning streamed before the content, from DeepSeek and xAI.
+    #[serde(default)]
+    pub reasoning_content: Option<String>,
     // The name of the user in a multi-user chat
     // #[serde(skip_serializing_if = "Option::is_none")]
     // pub name: Option<String>,
@@ -116,6 +180,53 @@ pub struct ChatChoiceLogprobs {
     pub content: Option<Vec<ChatCompletionTokenLogprob>>,
 }
 
+impl ChatCompletionChoice {
+    pub fn to_candidate(
+        &self,
+        output_tool: Option<&OutputTool>,
+    ) -> crate::internal::llm_client::ResponseCandidate {
+        crate::internal::llm_client::ResponseCandidate {
+            content: self.message.output(output_tool),
+            finish_reason: self.finish_reason.clone(),
+            logprob: self
+                .logprobs
+                .as_ref()
+                .and_then(ChatChoiceLogprobs::mean_logprob),
+            logprobs: self
+                .logprobs
+                .as_ref()
+                .map(ChatChoiceLogprobs::to_response_metadata),
+        }
+    }
+}
+
+impl ChatChoiceLogprobs {
+    pub fn mean_logprob(&self) -> Option<f64> {
+        let tokens = self.content.as_ref().filter(|tokens| !tokens.is_empty())?;
+        Some(tokens.iter().map(|token| token.logprob as f64).sum::<f64>() / tokens.len() as f64)
+    }
+
+    pub fn to_response_metadata(&self) -> Vec<crate::internal::llm_client::TokenLogprob> {
+        self.content
+            .iter()
+            .flatten()
+            .map(|token| crate::internal::llm_client::TokenLogprob {
+                token: token.token.clone(),
+                logprob: token.logprob as f64,
+                top_logprobs: token
+                    .top_logprobs
+                    .iter()
+                    .map(|top| crate::internal::llm_client::TokenLogprob {
+                        token: top.token.clone(),
+                        logprob: top.logprob as f64,
+                        top_logprobs: vec![],
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct ChatCompletionTokenLogprob {
     /// The token.
@@ -125,6 +236,7 @@ pub struct ChatCompletionTokenLogprob {
     /// A list of integers representing the UTF-8 bytes representation of the token. Useful in instances where characters are represented by multiple tokens and their byte representations must be combined to generate the correct text representation. Can be `null` if there is no bytes representation for the token.
     pub bytes: Option<Vec<u8>>,
     ///  List of the most likely tokens and their log probability, at this token position. In rare cases, there may be fewer than the number of requested `top_logprobs` returned.
+    #[serde(default)]
     pub top_logprobs: Vec<TopLogprobs>,
 }
 
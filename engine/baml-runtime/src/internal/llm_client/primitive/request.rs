@@ -3,15 +3,24 @@ use std::collections::HashMap;
 use anyhow::{Context, Result};
 use baml_types::BamlMap;
 use internal_baml_jinja::RenderedChatMessage;
+use internal_llm_client::HttpOptions;
 use reqwest::Response;
 use serde::de::DeserializeOwned;
 
-use crate::internal::llm_client::{traits::WithClient, ErrorCode, LLMErrorResponse, LLMResponse};
+use crate::{
+    clock::Stopwatch,
+    internal::llm_client::{
+        rate_limit, traits::WithClient, ErrorCode, LLMErrorResponse, LLMResponse,
+    },
+    transport::ResponseFuture,
+    RuntimeContext,
+};
 
 pub trait RequestBuilder {
     #[allow(async_fn_in_trait)]
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &[RenderedChatMessage]>,
         allow_proxy: bool,
         stream: bool,
@@ -20,6 +29,14 @@ pub trait RequestBuilder {
     fn request_options(&self) -> &BamlMap<String, serde_json::Value>;
 
     fn http_client(&self) -> &reqwest::Client;
+
+    fn http_options(&self) -> &HttpOptions;
+
+    /// Sends `request` with the runtime's transport, see
+    /// `BamlRuntime::set_http_transport`.
+    fn execute(&self, ctx: &RuntimeContext, request: reqwest::Request) -> ResponseFuture {
+        ctx.http_transport.execute(self.http_client(), request)
+    }
 }
 
 fn to_prompt(
@@ -33,13 +50,19 @@ fn to_prompt(
 
 pub async fn make_request(
     client: &(impl WithClient + RequestBuilder),
+    ctx: &RuntimeContext,
     prompt: either::Either<&String, &[RenderedChatMessage]>,
     stream: bool,
-) -> Result<(Response, web_time::SystemTime, web_time::Instant), LLMResponse> {
-    let (system_now, instant_now) = (web_time::SystemTime::now(), web_time::Instant::now());
+) -> Result<(Response, web_time::SystemTime, Stopwatch), LLMResponse> {
+    // Time spent waiting for the rate limit isn't the request's latency.
+    if let Some(limit) = &client.http_options().rate_limit {
+        rate_limit::acquire(ctx, &client.context().name, limit).await;
+    }
+
+    let (system_now, instant_now) = (ctx.clock.now(), Stopwatch::start(&ctx.clock));
 
     let req = match client
-        .build_request(prompt, true, stream)
+        .build_request(ctx, prompt, true, stream)
         .await
         .context("Failed to build request")
     {
@@ -58,7 +81,11 @@ pub async fn make_request(
         }
     };
 
-    let req = match req.build() {
+    let timeout = client
+        .http_options()
+        .request_timeout_ms
+        .map(web_time::Duration::from_millis);
+    let mut req = match req.build() {
         Ok(req) => req,
         Err(e) => {
             return Err(LLMResponse::LLMFailure(LLMErrorResponse {
@@ -74,9 +101,46 @@ pub async fn make_request(
         }
     };
 
-    let response = match client.http_client().execute(req).await {
-        Ok(response) => response,
-        Err(e) => {
+    // Calls must finish reading their response in time too, while streams
+    // only need to start.
+    #[cfg(not(target_arch = "wasm32"))]
+    if !stream {
+        *req.timeout_mut() = timeout;
+    }
+
+    // Clients that set their own `traceparent` header keep it.
+    if let Some(trace_parent) = ctx.trace_parent {
+        if let Ok(value) = reqwest::header::HeaderValue::from_str(&trace_parent.to_string()) {
+            req.headers_mut().entry("traceparent").or_insert(value);
+        }
+    }
+
+    if let Err(e) = ctx.hooks.before_request(&client.context().name, &mut req) {
+        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+            client: client.context().name.to_string(),
+            model: None,
+            prompt: to_prompt(prompt),
+            start_time: system_now,
+            request_options: client.request_options().clone(),
+            latency: instant_now.elapsed(),
+            message: format!("Hook failed: {:#}", e),
+            code: ErrorCode::Other(2),
+        }));
+    }
+
+    if let Some(recorder) = &ctx.http_recorder {
+        recorder.record_request(&req);
+    }
+
+    let response = client.execute(ctx, req);
+    let response = match timeout {
+        Some(timeout) => async_std::future::timeout(timeout, response).await.ok(),
+        None => Some(response.await),
+    };
+    let response = match response {
+        Some(Ok(response)) => response,
+        Some(Err(e)) => {
+            let reqwest_error = e.downcast_ref::<reqwest::Error>();
             return Err(LLMResponse::LLMFailure(LLMErrorResponse {
                 client: client.context().name.to_string(),
                 model: None,
@@ -84,13 +148,37 @@ pub async fn make_request(
                 start_time: system_now,
                 request_options: client.request_options().clone(),
                 latency: instant_now.elapsed(),
-                message: format!("{:?}", e),
-                code: ErrorCode::Other(2),
+                message: match reqwest_error {
+                    Some(e) => format!("{:?}", e),
+                    None => format!("{:#}", e),
+                },
+                code: match reqwest_error.is_some_and(|e| e.is_timeout()) {
+                    true => ErrorCode::Timeout,
+                    false => ErrorCode::Other(2),
+                },
+            }));
+        }
+        None => {
+            return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                client: client.context().name.to_string(),
+                model: None,
+                prompt: to_prompt(prompt),
+                start_time: system_now,
+                request_options: client.request_options().clone(),
+                latency: instant_now.elapsed(),
+                message: format!(
+                    "No response within request_timeout_ms ({}ms)",
+                    timeout.unwrap_or_default().as_millis()
+                ),
+                code: ErrorCode::Timeout,
             }));
         }
     };
 
     let status = response.status();
+    if let Some(recorder) = &ctx.http_recorder {
+        recorder.record_response(Some(status.as_u16()), None);
+    }
     if !status.is_success() {
         let url = response.url().to_string();
         let text = response.text().await.map_or_else(
@@ -103,6 +191,9 @@ pub async fn make_request(
                 }
             },
         );
+        if let Some(recorder) = &ctx.http_recorder {
+            recorder.record_response(None, Some(text.clone()));
+        }
         return Err(LLMResponse::LLMFailure(LLMErrorResponse {
             client: client.context().name.to_string(),
             model: None,
@@ -118,14 +209,93 @@ pub async fn make_request(
     Ok((response, system_now, instant_now))
 }
 
+/// Sends a `HEAD` request to the host `client` talks to. Any HTTP response,
+/// even an error status, means the host is reachable.
+pub async fn check_reachable(
+    client: &impl RequestBuilder,
+    ctx: &RuntimeContext,
+    timeout: web_time::Duration,
+) -> Result<()> {
+    let request = client
+        .build_request(ctx, either::Right(&[]), false, false)
+        .await
+        .context("Failed to build request")?
+        .build()?;
+    let mut url = request.url().clone();
+    url.set_path("");
+    url.set_query(None);
+
+    let probe = client.http_client().head(url.clone());
+    #[cfg(not(target_arch = "wasm32"))]
+    let probe = probe.timeout(timeout);
+    #[cfg(target_arch = "wasm32")]
+    let _ = timeout;
+    probe
+        .send()
+        .await
+        .with_context(|| format!("{url} is not reachable"))?;
+    Ok(())
+}
+
+/// A `multipart/form-data` body with text `fields` and one file, for file
+/// APIs. Returns the content type to send it with.
+pub(crate) fn multipart_body(
+    fields: &[(&str, &str)],
+    file_field: &str,
+    file_name: &str,
+    mime_type: &str,
+    bytes: &[u8],
+) -> (String, Vec<u8>) {
+    let boundary = format!("baml-{}", uuid::Uuid::new_v4().simple());
+    let mut body = Vec::with_capacity(bytes.len() + 512);
+    for (name, value) in fields {
+        body.extend(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{file_field}\"; filename=\"{file_name}\"\r\nContent-Type: {mime_type}\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend(bytes);
+    body.extend(format!("\r\n--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+/// Sends a file upload and returns the JSON the provider responded with.
+pub(crate) async fn send_upload(req: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+    let response = req.send().await.context("Failed to upload file")?;
+    let status = response.status();
+    if !status.is_success() {
+        let url = response.url().to_string();
+        let text = response.text().await.unwrap_or_default();
+        anyhow::bail!("File upload failed ({status}): {url}\n{text}");
+    }
+    response
+        .json()
+        .await
+        .context("Failed to parse file upload response")
+}
+
 pub async fn make_parsed_request<T: DeserializeOwned>(
     client: &(impl WithClient + RequestBuilder),
+    ctx: &RuntimeContext,
     prompt: either::Either<&String, &[RenderedChatMessage]>,
     stream: bool,
-) -> Result<(T, web_time::SystemTime, web_time::Instant), LLMResponse> {
-    let (response, system_now, instant_now) = make_request(client, prompt, stream).await?;
+) -> Result<(T, serde_json::Value, web_time::SystemTime, Stopwatch), LLMResponse> {
+    let (response, system_now, instant_now) = make_request(client, ctx, prompt, stream).await?;
     let j = match response.json::<serde_json::Value>().await {
-        Ok(response) => response,
+        Ok(response) => {
+            if let Some(recorder) = &ctx.http_recorder {
+                recorder.record_response(None, Some(response.to_string()));
+            }
+            response
+        }
         Err(e) => {
             return Err(LLMResponse::LLMFailure(LLMErrorResponse {
                 client: client.context().name.to_string(),
@@ -135,7 +305,10 @@ pub async fn make_parsed_request<T: DeserializeOwned>(
                 request_options: client.request_options().clone(),
                 latency: instant_now.elapsed(),
                 message: e.to_string(),
-                code: ErrorCode::Other(2),
+                code: match e.is_timeout() {
+                    true => ErrorCode::Timeout,
+                    false => ErrorCode::Other(2),
+                },
             }))
         }
     };
@@ -145,7 +318,7 @@ pub async fn make_parsed_request<T: DeserializeOwned>(
         std::any::type_name::<T>(),
         j
     )) {
-        Ok(response) => Ok((response, system_now, instant_now)),
+        Ok(response) => Ok((response, j, system_now, instant_now)),
         Err(e) => Err(LLMResponse::LLMFailure(LLMErrorResponse {
             client: client.context().name.to_string(),
             model: None,
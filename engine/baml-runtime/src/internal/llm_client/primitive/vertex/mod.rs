@@ -1,3 +1,4 @@
 mod types;
 mod vertex_client;
+pub(super) use vertex_client::get_access_token;
 pub use vertex_client::VertexClient;
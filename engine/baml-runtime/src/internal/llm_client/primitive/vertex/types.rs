@@ -238,6 +238,22 @@ pub struct Candidate {
     pub citation_metadata: Option<CitationMetadata>,
     pub grounding_metadata: Option<GroundingMetadata>,
     pub finish_message: Option<String>,
+    /// Set when the request asked for `responseLogprobs`.
+    pub avg_logprobs: Option<f64>,
+}
+
+impl Candidate {
+    pub fn to_candidate(&self) -> crate::internal::llm_client::ResponseCandidate {
+        crate::internal::llm_client::ResponseCandidate {
+            content: self.content.as_ref().map(Content::text).unwrap_or_default(),
+            finish_reason: self
+                .finish_reason
+                .as_ref()
+                .map(|r| serde_json::to_string(r).unwrap_or_default()),
+            logprob: self.avg_logprobs,
+            logprobs: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -247,15 +263,38 @@ pub struct Content {
     pub parts: Vec<Part>,
 }
 
+impl Content {
+    /// The text of every text part. Code the model ran with the
+    /// `code_execution` tool comes back in other parts and isn't included.
+    pub fn text(&self) -> String {
+        self.parts.iter().map(|p| p.text.as_str()).collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Part {
+    #[serde(default)]
     pub text: String,
     pub inline_data: Option<Blob>,
     pub file_data: Option<FileData>,
     pub function_call: Option<FunctionCall>,
     pub function_response: Option<FunctionResponse>,
     pub video_metadata: Option<VideoMetadata>,
+    pub executable_code: Option<ExecutableCode>,
+    pub code_execution_result: Option<CodeExecutionResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecutableCode {
+    pub language: String,
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CodeExecutionResult {
+    pub outcome: String,
+    pub output: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -351,15 +390,48 @@ pub struct Date {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GroundingMetadata {
+    #[serde(default)]
     pub web_search_queries: Vec<String>,
-    pub search_entry_point: SearchEntryPoint,
+    pub search_entry_point: Option<SearchEntryPoint>,
+    #[serde(default)]
+    pub grounding_chunks: Vec<GroundingChunk>,
+}
+
+impl GroundingMetadata {
+    pub fn to_response_metadata(&self) -> crate::internal::llm_client::GroundingMetadata {
+        crate::internal::llm_client::GroundingMetadata {
+            search_queries: self.web_search_queries.clone(),
+            citations: self
+                .grounding_chunks
+                .iter()
+                .filter_map(|chunk| chunk.web.as_ref().or(chunk.retrieved_context.as_ref()))
+                .map(|source| crate::internal::llm_client::Citation {
+                    uri: source.uri.clone(),
+                    title: source.title.clone(),
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchEntryPoint {
-    pub rendered_content: String,
-    pub sdk_blob: Vec<u8>,
+    pub rendered_content: Option<String>,
+    pub sdk_blob: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingChunk {
+    pub web: Option<GroundingSource>,
+    pub retrieved_context: Option<GroundingSource>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroundingSource {
+    pub uri: String,
+    pub title: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
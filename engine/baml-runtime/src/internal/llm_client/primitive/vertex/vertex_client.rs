@@ -1,4 +1,5 @@
 use crate::client_registry::ClientProperty;
+use crate::internal::llm_client::image_preprocessing::{self, ImagePreprocessing};
 use crate::internal::llm_client::traits::{
     ToProviderMessage, ToProviderMessageExt, WithClientProperties,
 };
@@ -13,18 +14,19 @@ use crate::{
             vertex::types::{FinishReason, VertexResponse},
         },
         traits::{
-            SseResponseTrait, StreamResponse, WithChat, WithClient, WithNoCompletion,
-            WithRetryPolicy, WithStreamChat,
+            accumulate_stream, SseResponseTrait, StreamResponse, WithChat, WithClient,
+            WithNoCompletion, WithRetryPolicy, WithStreamChat,
         },
         ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
         ModelFeatures,
     },
-    request::create_client,
+    request::{create_client_with_options, json_body},
 };
 use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
 use futures::StreamExt;
-use internal_llm_client::vertex::{ResolvedServiceAccountDetails, ResolvedVertex, ServiceAccount};
+use internal_llm_client::vertex::{
+    ResolvedServiceAccountDetails, ResolvedVertex, ServiceAccount, VERTEX_LOCATIONS,
+};
 use internal_llm_client::{
     AllowedRoleMetadata, ClientProvider, ResolvedClientProperty, UnresolvedClientProperty,
 };
@@ -44,6 +46,8 @@ use internal_baml_jinja::{RenderContext_Client, RenderedChatMessage};
 
 use serde_json::json;
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub struct VertexClient {
     pub name: String,
@@ -52,6 +56,9 @@ pub struct VertexClient {
     pub context: RenderContext_Client,
     pub features: ModelFeatures,
     properties: ResolvedVertex,
+    /// The endpoint requests go to, moved past locations that don't serve
+    /// the model.
+    endpoint: AtomicUsize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -68,14 +75,17 @@ struct Claims {
 const DEFAULT_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
 
 impl Claims {
-    fn from_service_account(service_account: &ServiceAccount) -> Claims {
-        let now = Utc::now();
+    fn from_service_account(service_account: &ServiceAccount, now: web_time::SystemTime) -> Claims {
+        let now = now
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
         Claims {
             iss: service_account.client_email.clone(),
             scope: DEFAULT_SCOPE.to_string(),
             aud: service_account.token_uri.clone(),
-            exp: (now + Duration::hours(1)).timestamp(),
-            iat: now.timestamp(),
+            exp: now + 60 * 60,
+            iat: now,
         }
     }
 }
@@ -116,6 +126,9 @@ impl WithClientProperties for VertexClient {
     fn finish_reason_filter(&self) -> &internal_llm_client::FinishReasonFilter {
         &self.properties.finish_reason_filter
     }
+    fn candidate_selection(&self) -> internal_llm_client::CandidateSelection {
+        self.properties.candidate_selection
+    }
     fn default_role(&self) -> String {
         self.properties.default_role()
     }
@@ -142,87 +155,86 @@ impl SseResponseTrait for VertexClient {
         resp: reqwest::Response,
         prompt: &[RenderedChatMessage],
         system_start: web_time::SystemTime,
-        instant_start: web_time::Instant,
+        instant_start: crate::clock::Stopwatch,
     ) -> StreamResponse {
         let prompt = prompt.to_vec();
         let client_name = self.context.name.clone();
         let model_id = self.properties.model.clone();
         let params = self.properties.properties.clone();
-        Ok(Box::pin(
-            resp.bytes_stream()
-                .eventsource()
-                .inspect(|event| log::trace!("Received event: {:?}", event))
-                .take_while(|event| {
-                    std::future::ready(event.as_ref().is_ok_and(|e| e.data != "data: \n"))
-                })
-                .map(|event| -> Result<VertexResponse> {
-                    Ok(serde_json::from_str::<VertexResponse>(&event?.data)?)
-                })
-                .scan(
-                    Ok(LLMCompleteResponse {
-                        client: client_name.clone(),
-                        prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-                        content: "".to_string(),
-                        start_time: system_start,
-                        latency: instant_start.elapsed(),
-                        model: model_id,
-                        request_options: params.clone(),
-                        metadata: LLMCompleteResponseMetadata {
-                            baml_is_complete: false,
-                            finish_reason: None,
-                            prompt_tokens: None,
-                            output_tokens: None,
-                            total_tokens: None,
-                        },
-                    }),
-                    move |accumulated: &mut Result<LLMCompleteResponse>, event| {
-                        let Ok(ref mut inner) = accumulated else {
-                            // halt the stream: the last stream event failed to parse
-                            return std::future::ready(None);
-                        };
-                        let event = match event {
-                            Ok(event) => event,
-                            Err(e) => {
-                                return std::future::ready(Some(LLMResponse::LLMFailure(
-                                    LLMErrorResponse {
-                                        client: client_name.clone(),
-                                        model: if inner.model.is_empty() {
-                                            None
-                                        } else {
-                                            Some(inner.model.clone())
-                                        },
-                                        prompt: internal_baml_jinja::RenderedPrompt::Chat(
-                                            prompt.to_vec(),
-                                        ),
-                                        start_time: system_start,
-                                        request_options: params.clone(),
-                                        latency: instant_start.elapsed(),
-                                        message: format!("Failed to parse event: {:#?}", e),
-                                        code: ErrorCode::UnsupportedResponse(2),
-                                    },
-                                )));
-                            }
-                        };
-                        if let Some(choice) = event.candidates.first() {
-                            if let Some(content) = choice
-                                .content
-                                .as_ref()
-                                .and_then(|c| c.parts.first().map(|p| p.text.as_ref()))
-                            {
-                                inner.content += content;
-                            }
-                            if let Some(FinishReason::Stop) = choice.finish_reason.as_ref() {
-                                inner.metadata.baml_is_complete = true;
-                                inner.metadata.finish_reason = Some(FinishReason::Stop.to_string());
-                            }
-                        }
-
-                        inner.latency = instant_start.elapsed();
-
-                        std::future::ready(Some(LLMResponse::Success(inner.clone())))
-                    },
-                ),
-        ))
+        let events = resp
+            .bytes_stream()
+            .eventsource()
+            .inspect(|event| log::trace!("Received event: {:?}", event))
+            .take_while(|event| {
+                std::future::ready(event.as_ref().is_ok_and(|e| e.data != "data: \n"))
+            })
+            .map(|event| -> Result<VertexResponse> {
+                Ok(serde_json::from_str::<VertexResponse>(&event?.data)?)
+            });
+        let initial = LLMCompleteResponse {
+            client: client_name.clone(),
+            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+            content: "".to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            model: model_id,
+            request_options: params.clone(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        };
+        Ok(Box::pin(accumulate_stream(
+            events,
+            initial,
+            instant_start.clone(),
+            move |inner, event| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        return Err(LLMResponse::LLMFailure(LLMErrorResponse {
+                            client: client_name.clone(),
+                            model: if inner.model.is_empty() {
+                                None
+                            } else {
+                                Some(inner.model.clone())
+                            },
+                            prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.to_vec()),
+                            start_time: system_start,
+                            request_options: params.clone(),
+                            latency: instant_start.elapsed(),
+                            message: format!("Failed to parse event: {:#?}", e),
+                            code: ErrorCode::UnsupportedResponse(2),
+                        }));
+                    }
+                };
+
+                let mut delta = None;
+                if let Some(choice) = event.candidates.first() {
+                    if let Some(content) = choice.content.as_ref() {
+                        delta = Some(content.text());
+                    }
+                    if let Some(grounding) = choice.grounding_metadata.as_ref() {
+                        inner.metadata.grounding = Some(grounding.to_response_metadata());
+                    }
+                    if let Some(FinishReason::Stop) = choice.finish_reason.as_ref() {
+                        inner.metadata.baml_is_complete = true;
+                        inner.metadata.finish_reason = Some(FinishReason::Stop.to_string());
+                    }
+                }
+                Ok(delta)
+            },
+        )))
     }
 }
 // makes the request to the google client, on success it triggers the response_stream function to handle continuous rendering with the response object
@@ -233,11 +245,9 @@ impl WithStreamChat for VertexClient {
         prompt: &[RenderedChatMessage],
     ) -> StreamResponse {
         //incomplete, streaming response object is returned
-        let (response, system_now, instant_now) =
-            match make_request(self, either::Either::Right(prompt), true).await {
-                Ok(v) => v,
-                Err(e) => return Err(e),
-            };
+        let (response, system_now, instant_now) = self
+            .with_location_fallback(|| make_request(self, ctx, either::Either::Right(prompt), true))
+            .await?;
         self.response_stream(response, prompt, system_now, instant_now)
     }
 }
@@ -259,14 +269,19 @@ impl VertexClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::EnsureMime,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::GEMINI_MAX_DIMENSION,
+                ),
             },
             retry_policy: client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client_with_options(&properties.http)?,
             properties,
+            endpoint: AtomicUsize::new(0),
         })
     }
 
@@ -287,17 +302,98 @@ impl VertexClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::EnsureMime,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                image_preprocessing: ImagePreprocessing::from_options(
+                    properties.image_preprocessing.as_ref(),
+                    image_preprocessing::GEMINI_MAX_DIMENSION,
+                ),
             },
             retry_policy: client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client_with_options(&properties.http)?,
             properties,
+            endpoint: AtomicUsize::new(0),
         })
     }
 }
 
-async fn get_access_token(service_account: &ServiceAccount) -> Result<String> {
+impl VertexClient {
+    /// Sends `request` to the current endpoint and, while the model isn't
+    /// available there, to the fallback locations after it. Later requests
+    /// start at the location that served the model.
+    async fn with_location_fallback<T, Fut>(
+        &self,
+        request: impl Fn() -> Fut,
+    ) -> Result<T, LLMResponse>
+    where
+        Fut: Future<Output = Result<T, LLMResponse>>,
+    {
+        let endpoints = &self.properties.endpoints;
+        loop {
+            let current = self.endpoint.load(Ordering::Relaxed);
+            let mut failure = match request().await {
+                Err(LLMResponse::LLMFailure(failure)) if is_model_unavailable(&failure) => failure,
+                other => return other,
+            };
+            if endpoints[current].location.is_none() {
+                return Err(LLMResponse::LLMFailure(failure));
+            }
+            if current + 1 < endpoints.len() {
+                log::warn!(
+                    "Vertex model {} is not available in {}, trying {}",
+                    self.properties.model,
+                    location_name(&endpoints[current].location),
+                    location_name(&endpoints[current + 1].location),
+                );
+                let _ = self.endpoint.compare_exchange(
+                    current,
+                    current + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+                continue;
+            }
+
+            let tried = endpoints
+                .iter()
+                .map(|endpoint| location_name(&endpoint.location))
+                .collect::<Vec<_>>()
+                .join(", ");
+            failure.message = format!(
+                "Model {} is not available in {tried}. Set `location` to one that serves it, \
+                 `global`, or list more in `fallback_locations`. Vertex AI serves Gemini models \
+                 in: {}\n\n{}",
+                self.properties.model,
+                VERTEX_LOCATIONS.join(", "),
+                failure.message
+            );
+            return Err(LLMResponse::LLMFailure(failure));
+        }
+    }
+}
+
+fn location_name(location: &Option<String>) -> &str {
+    location.as_deref().unwrap_or_default()
+}
+
+/// Vertex answers 404 for models a location doesn't serve, and 400 for some
+/// models that exist but aren't offered in the region.
+fn is_model_unavailable(failure: &LLMErrorResponse) -> bool {
+    match failure.code {
+        ErrorCode::Other(404) => true,
+        ErrorCode::Other(400) => {
+            let message = failure.message.to_lowercase();
+            message.contains("region") || message.contains("location")
+        }
+        _ => false,
+    }
+}
+
+/// `now` is when the token is issued. It expires an hour later.
+pub(crate) async fn get_access_token(
+    service_account: &ServiceAccount,
+    now: web_time::SystemTime,
+) -> Result<String> {
     // Create the JWT
-    let claims = Claims::from_service_account(service_account);
+    let claims = Claims::from_service_account(service_account, now);
 
     #[cfg(not(target_arch = "wasm32"))]
     let jwt = encode(
@@ -340,8 +436,13 @@ impl RequestBuilder for VertexClient {
         &self.client
     }
 
+    fn http_options(&self) -> &internal_llm_client::HttpOptions {
+        &self.properties.http
+    }
+
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &[RenderedChatMessage]>,
         allow_proxy: bool,
         stream: bool,
@@ -353,7 +454,9 @@ impl RequestBuilder for VertexClient {
             should_stream = "streamGenerateContent?alt=sse";
         }
 
-        let base_url = self.properties.base_url.clone();
+        let base_url = self.properties.endpoints[self.endpoint.load(Ordering::Relaxed)]
+            .base_url
+            .clone();
         let model = self.properties.model.clone();
         let baml_original_url = format!("{}/{}:{}", base_url, model, should_stream);
 
@@ -367,7 +470,7 @@ impl RequestBuilder for VertexClient {
 
         let access_token = match &self.properties.authorization {
             ResolvedServiceAccountDetails::RawAuthorizationHeader(token) => token.to_string(),
-            ResolvedServiceAccountDetails::Json(token) => get_access_token(token)
+            ResolvedServiceAccountDetails::Json(token) => get_access_token(token, ctx.clock.now())
                 .await
                 .context("Failed to get access token")?,
         };
@@ -388,7 +491,7 @@ impl RequestBuilder for VertexClient {
             either::Either::Right(messages) => body_obj.extend(self.chat_to_message(messages)?),
         }
 
-        let req = req.json(&body);
+        let req = json_body(req, &body, self.properties.http.compress_requests)?;
 
         Ok(req)
     }
@@ -399,17 +502,24 @@ impl RequestBuilder for VertexClient {
 }
 
 impl WithChat for VertexClient {
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &[RenderedChatMessage]) -> LLMResponse {
         //non-streaming, complete response is returned
-        let (response, system_now, instant_now) =
-            match make_parsed_request::<VertexResponse>(self, either::Either::Right(prompt), false)
-                .await
-            {
-                Ok(v) => v,
-                Err(e) => return e,
-            };
+        let (response, raw_response, system_now, instant_now) = match self
+            .with_location_fallback(|| {
+                make_parsed_request::<VertexResponse>(
+                    self,
+                    ctx,
+                    either::Either::Right(prompt),
+                    false,
+                )
+            })
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
 
-        if response.candidates.len() != 1 {
+        if response.candidates.is_empty() {
             return LLMResponse::LLMFailure(LLMErrorResponse {
                 client: self.context.name.to_string(),
                 model: None,
@@ -417,19 +527,16 @@ impl WithChat for VertexClient {
                 start_time: system_now,
                 request_options: self.properties.properties.clone(),
                 latency: instant_now.elapsed(),
-                message: format!(
-                    "Expected exactly one content block, got {}",
-                    response.candidates.len()
-                ),
+                message: "No candidates returned".to_string(),
                 code: ErrorCode::Other(200),
             });
         }
 
-        let content = if let Some(content) = response.candidates.first().and_then(|c| {
-            c.content
-                .as_ref()
-                .and_then(|c| c.parts.first().map(|p| p.text.clone()))
-        }) {
+        let content = if let Some(content) = response
+            .candidates
+            .first()
+            .and_then(|c| c.content.as_ref().map(|c| c.text()))
+        {
             content
         } else {
             return LLMResponse::LLMFailure(LLMErrorResponse {
@@ -471,6 +578,22 @@ impl WithChat for VertexClient {
                 prompt_tokens: usage_metadata.prompt_token_count,
                 output_tokens: usage_metadata.candidates_token_count,
                 total_tokens: usage_metadata.total_token_count,
+                cached_input_tokens: None,
+                grounding: response.candidates[0]
+                    .grounding_metadata
+                    .as_ref()
+                    .map(|g| g.to_response_metadata()),
+                thinking: None,
+                logprobs: None,
+                candidates: (response.candidates.len() > 1).then(|| {
+                    response
+                        .candidates
+                        .iter()
+                        .map(|c| c.to_candidate())
+                        .collect()
+                }),
+                structured_output: false,
+                raw_response: self.properties.capture_raw_response.then_some(raw_response),
             },
         })
     }
@@ -515,6 +638,13 @@ impl ToProviderMessage for VertexClient {
                 );
                 Ok(content)
             }
+            BamlMediaContent::FileRef(file_ref) => {
+                content.insert(
+                    "fileData".into(),
+                    json!({"file_uri": file_ref.file_id, "mime_type": media.mime_type}),
+                );
+                Ok(content)
+            }
             BamlMediaContent::Base64(data) => {
                 content.insert(
                     "inlineData".into(),
@@ -0,0 +1,256 @@
+//! The `requests_per_minute` and `burst` of a client's `http` options.
+//!
+//! Each process keeps its own buckets, named after the clients, unless
+//! `BAML_RATE_LIMITER` points at a backend replicas share:
+//! - unset or `memory`: this process only
+//! - `redis://...` or `rediss://...`: needs the `rate-limit-redis` feature
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use anyhow::Result;
+use internal_llm_client::RateLimit;
+use web_time::{Duration, SystemTime};
+
+use crate::RuntimeContext;
+
+pub trait RateLimiter: Send + Sync + Debug {
+    /// Takes a request from the bucket of `client` if there's one left, or
+    /// returns how long until there will be.
+    fn try_acquire(
+        &self,
+        client: &str,
+        limit: &RateLimit,
+        now: SystemTime,
+    ) -> Result<Option<Duration>>;
+}
+
+/// Waits until `client` may send a request, on the runtime's clock.
+///
+/// If the backend can't be reached, a warning is logged and the request is
+/// sent right away.
+pub async fn acquire(ctx: &RuntimeContext, client: &str, limit: &RateLimit) {
+    let limiter = match from_env(ctx) {
+        Ok(limiter) => limiter,
+        Err(e) => {
+            log::warn!("Failed to load the rate limiter of {client}: {e:#}");
+            return;
+        }
+    };
+    loop {
+        match limiter.try_acquire(client, limit, ctx.clock.now()) {
+            Ok(None) => return,
+            Ok(Some(wait)) => ctx.clock.sleep(wait).await,
+            Err(e) => {
+                log::warn!("Failed to rate limit {client}: {e:#}");
+                return;
+            }
+        }
+    }
+}
+
+/// The backend named by `BAML_RATE_LIMITER`. Backends live as long as the
+/// process, so that every runtime and call shares their buckets.
+fn from_env(ctx: &RuntimeContext) -> Result<Arc<dyn RateLimiter>> {
+    static LIMITERS: OnceLock<Mutex<HashMap<String, Arc<dyn RateLimiter>>>> = OnceLock::new();
+
+    let url = ctx
+        .env_vars()
+        .get("BAML_RATE_LIMITER")
+        .filter(|url| !url.is_empty())
+        .map_or("memory", String::as_str);
+    let mut limiters = LIMITERS.get_or_init(Default::default).lock().unwrap();
+    if let Some(limiter) = limiters.get(url) {
+        return Ok(limiter.clone());
+    }
+    let limiter = new_limiter(url)?;
+    limiters.insert(url.to_string(), limiter.clone());
+    Ok(limiter)
+}
+
+fn new_limiter(url: &str) -> Result<Arc<dyn RateLimiter>> {
+    if url == "memory" {
+        return Ok(Arc::new(InMemoryRateLimiter::default()));
+    }
+    if url.starts_with("redis://") || url.starts_with("rediss://") {
+        #[cfg(all(feature = "rate-limit-redis", not(target_arch = "wasm32")))]
+        return Ok(Arc::new(redis_limiter::RedisRateLimiter::new(url)?));
+        #[cfg(not(all(feature = "rate-limit-redis", not(target_arch = "wasm32"))))]
+        anyhow::bail!(
+            "BAML_RATE_LIMITER is a Redis URL, which needs baml-runtime's `rate-limit-redis` feature"
+        );
+    }
+    anyhow::bail!("Invalid BAML_RATE_LIMITER `{url}`. Expected `memory` or a Redis URL")
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryRateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: SystemTime,
+}
+
+impl Bucket {
+    /// Refills the bucket for the time since it was last taken from, then
+    /// takes a token from it.
+    fn take(&mut self, limit: &RateLimit, now: SystemTime) -> Option<Duration> {
+        let per_second = f64::from(limit.requests_per_minute) / 60.0;
+        let elapsed = now.duration_since(self.updated).unwrap_or_default();
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * per_second).min(f64::from(limit.burst));
+        self.updated = self.updated.max(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+        }
+    }
+}
+
+impl RateLimiter for InMemoryRateLimiter {
+    fn try_acquire(
+        &self,
+        client: &str,
+        limit: &RateLimit,
+        now: SystemTime,
+    ) -> Result<Option<Duration>> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        });
+        Ok(bucket.take(limit, now))
+    }
+}
+
+#[cfg(all(feature = "rate-limit-redis", not(target_arch = "wasm32")))]
+mod redis_limiter {
+    use std::sync::Mutex;
+
+    use anyhow::{Context, Result};
+    use internal_llm_client::RateLimit;
+    use web_time::{Duration, SystemTime};
+
+    use super::RateLimiter;
+
+    /// Takes a token from the bucket at `KEYS[1]` and returns 0, or returns
+    /// how many milliseconds until it has one. Buckets are refilled by the
+    /// time of the Redis server, which replicas agree on.
+    const TAKE: &str = r#"
+local capacity = tonumber(ARGV[1])
+local per_ms = tonumber(ARGV[2]) / 60000
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or capacity
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - updated) * per_ms)
+local wait = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+else
+  wait = math.ceil((1 - tokens) / per_ms)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / per_ms) + 1000)
+return wait
+"#;
+
+    /// Buckets are kept under `baml:rate_limit:<client name>`.
+    pub struct RedisRateLimiter {
+        client: redis::Client,
+        script: redis::Script,
+        connection: Mutex<Option<redis::Connection>>,
+    }
+
+    impl std::fmt::Debug for RedisRateLimiter {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("RedisRateLimiter")
+                .field("connection_info", self.client.get_connection_info())
+                .finish()
+        }
+    }
+
+    impl RedisRateLimiter {
+        pub fn new(url: &str) -> Result<Self> {
+            Ok(Self {
+                client: redis::Client::open(url).context("Invalid Redis URL")?,
+                script: redis::Script::new(TAKE),
+                connection: Mutex::new(None),
+            })
+        }
+    }
+
+    impl RateLimiter for RedisRateLimiter {
+        fn try_acquire(
+            &self,
+            client: &str,
+            limit: &RateLimit,
+            _now: SystemTime,
+        ) -> Result<Option<Duration>> {
+            // Connects on first use, and again after a command fails.
+            let mut connection = self.connection.lock().unwrap();
+            if connection.is_none() {
+                *connection = Some(
+                    self.client
+                        .get_connection()
+                        .context("Failed to connect to Redis")?,
+                );
+            }
+            let result = self
+                .script
+                .key(format!("baml:rate_limit:{client}"))
+                .arg(limit.burst)
+                .arg(limit.requests_per_minute)
+                .invoke::<u64>(connection.as_mut().unwrap());
+            if result.is_err() {
+                *connection = None;
+            }
+            Ok(match result? {
+                0 => None,
+                wait => Some(Duration::from_millis(wait)),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_buckets_refill() {
+        let limiter = InMemoryRateLimiter::default();
+        let limit = RateLimit {
+            requests_per_minute: 60,
+            burst: 2,
+        };
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |secs: f64| start + Duration::from_secs_f64(secs);
+
+        assert_eq!(limiter.try_acquire("A", &limit, at(0.0)).unwrap(), None);
+        assert_eq!(limiter.try_acquire("A", &limit, at(0.0)).unwrap(), None);
+        assert_eq!(
+            limiter.try_acquire("A", &limit, at(0.5)).unwrap(),
+            Some(Duration::from_millis(500))
+        );
+        // Other clients have buckets of their own.
+        assert_eq!(limiter.try_acquire("B", &limit, at(0.5)).unwrap(), None);
+        assert_eq!(limiter.try_acquire("A", &limit, at(1.0)).unwrap(), None);
+        // A quiet spell refills up to `burst` only.
+        assert_eq!(limiter.try_acquire("A", &limit, at(60.0)).unwrap(), None);
+        assert_eq!(limiter.try_acquire("A", &limit, at(60.0)).unwrap(), None);
+        assert!(limiter
+            .try_acquire("A", &limit, at(60.0))
+            .unwrap()
+            .is_some());
+    }
+}
@@ -1,5 +1,6 @@
 use internal_baml_core::{
-    internal_baml_parser_database::RetryPolicyStrategy, ir::RetryPolicyWalker,
+    internal_baml_parser_database::{RetryJitter, RetryPolicyStrategy},
+    ir::RetryPolicyWalker,
 };
 
 #[derive(Clone)]
@@ -56,6 +57,60 @@ impl Iterator for CallablePolicy {
         }
         self.counter += 1;
 
-        Some(self.current)
+        // The backoff grows from the delay without jitter, so that a short
+        // random delay doesn't shorten all the ones after it.
+        Some(with_jitter(self.current, self.strategy.jitter()))
+    }
+}
+
+fn with_jitter(delay: std::time::Duration, jitter: RetryJitter) -> std::time::Duration {
+    let ms = delay.as_millis() as u64;
+    let ms = match jitter {
+        RetryJitter::None => ms,
+        RetryJitter::Full => fastrand::u64(0..=ms),
+        RetryJitter::Equal => ms / 2 + fastrand::u64(0..=ms - ms / 2),
+    };
+    std::time::Duration::from_millis(ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use internal_baml_core::internal_baml_parser_database::ExponentialBackoffStrategy;
+
+    use super::*;
+
+    fn policy(jitter: RetryJitter) -> CallablePolicy {
+        CallablePolicy {
+            max_retries: 3,
+            strategy: RetryPolicyStrategy::ExponentialBackoff(ExponentialBackoffStrategy {
+                delay_ms: 100,
+                multiplier: 2.0,
+                max_delay_ms: 1000,
+                jitter,
+            }),
+            current: std::time::Duration::from_millis(100),
+            counter: 0,
+        }
+    }
+
+    fn delays_ms(policy: CallablePolicy) -> Vec<u64> {
+        policy.map(|d| d.as_millis() as u64).collect()
+    }
+
+    #[test]
+    fn test_backoff_without_jitter() {
+        assert_eq!(delays_ms(policy(RetryJitter::None)), [200, 400, 800, 0]);
+    }
+
+    #[test]
+    fn test_backoff_with_jitter() {
+        for _ in 0..100 {
+            let full = delays_ms(policy(RetryJitter::Full));
+            let equal = delays_ms(policy(RetryJitter::Equal));
+            for (idx, max) in [200, 400, 800, 0].into_iter().enumerate() {
+                assert!(full[idx] <= max, "{full:?}");
+                assert!(equal[idx] >= max / 2 && equal[idx] <= max, "{equal:?}");
+            }
+        }
     }
 }
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use anyhow::Result;
 mod fallback;
 pub mod roundrobin;
+pub mod state;
 
 use internal_baml_core::ir::ClientWalker;
 use internal_llm_client::{ClientProvider, StrategyClientProvider};
@@ -1,17 +1,19 @@
 use anyhow::{Context, Result};
 use std::{
+    cmp::Reverse,
+    collections::VecDeque,
     fmt::Debug,
-    {
-        collections::HashMap,
-        sync::{atomic::AtomicUsize, Arc},
-    },
+    sync::Mutex,
+    {collections::HashMap, sync::Arc},
 };
 
 use internal_baml_core::ir::ClientWalker;
 use internal_llm_client::{
+    round_robin::{HealthCheck, ResolvedRoundRobin},
     ClientProvider, ClientSpec, ResolvedClientProperty, UnresolvedClientProperty,
 };
 
+use super::state::{self, StrategyState};
 use crate::{
     client_registry::ClientProperty,
     internal::llm_client::orchestrator::{
@@ -22,7 +24,6 @@ use crate::{
     RuntimeContext,
 };
 use serde::Serialize;
-use serde::Serializer;
 
 #[derive(Debug, Serialize)]
 pub struct RoundRobinStrategy {
@@ -30,25 +31,146 @@ pub struct RoundRobinStrategy {
     pub(super) retry_policy: Option<String>,
     // TODO: We can add conditions to each client
     client_specs: Vec<ClientSpec>,
-    current_index: AtomicUsize,
+    /// The index in `client_specs` of each turn of a rotation.
+    schedule: Vec<usize>,
+    /// Added to the rotation count from `state`.
+    start: usize,
+    /// Shared between replicas if `BAML_STRATEGY_STATE` says so.
+    #[serde(skip)]
+    state: Arc<dyn StrategyState>,
+    /// Kept by each process, even when the rotation is shared.
+    #[serde(skip)]
+    health: Option<Health>,
+}
+
+/// The outcomes of the strategy's last `check.window` requests.
+#[derive(Debug)]
+struct Health {
+    check: HealthCheck,
+    /// The index of the client in `client_specs`, and whether it succeeded,
+    /// newest last.
+    outcomes: Mutex<VecDeque<(usize, bool)>>,
 }
 
-fn serialize_atomic<S>(value: &AtomicUsize, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    serializer.serialize_u64(value.load(std::sync::atomic::Ordering::Relaxed) as u64)
+impl Health {
+    fn new(check: HealthCheck) -> Self {
+        Self {
+            check,
+            outcomes: Mutex::new(VecDeque::with_capacity(check.window)),
+        }
+    }
+
+    fn record(&self, client: usize, succeeded: bool) {
+        let mut outcomes = self.outcomes.lock().unwrap();
+        outcomes.push_back((client, succeeded));
+        while outcomes.len() > self.check.window {
+            outcomes.pop_front();
+        }
+    }
+
+    fn is_healthy(&self, client: usize) -> bool {
+        let outcomes = self.outcomes.lock().unwrap();
+        let (requests, failures) = outcomes.iter().filter(|(c, _)| *c == client).fold(
+            (0, 0),
+            |(requests, failures), (_, succeeded)| {
+                (requests + 1, failures + usize::from(!succeeded))
+            },
+        );
+        failures as f64 <= self.check.max_error_rate * requests as f64
+    }
+}
+
+/// Smooth weighted round-robin, as in nginx: weights `[5, 1, 1]` give the
+/// rotation `[0, 0, 1, 0, 2, 0, 0]` rather than five 0s in a row.
+fn schedule(weights: &[u32]) -> Vec<usize> {
+    let total = weights.iter().map(|w| i64::from(*w)).sum::<i64>();
+    let mut current = vec![0; weights.len()];
+    (0..total)
+        .map(|_| {
+            for (current, weight) in current.iter_mut().zip(weights) {
+                *current += i64::from(*weight);
+            }
+            let next = (0..current.len())
+                .max_by_key(|i| (current[*i], Reverse(*i)))
+                .expect("weights are not empty");
+            current[next] -= total;
+            next
+        })
+        .collect()
 }
 
 impl RoundRobinStrategy {
+    fn new(
+        name: String,
+        retry_policy: Option<String>,
+        props: ResolvedRoundRobin,
+        ctx: &RuntimeContext,
+    ) -> Result<Self> {
+        let state = state::from_env(ctx)
+            .with_context(|| format!("Failed to load the state of round-robin client {name}"))?;
+        let client_specs = props.strategy;
+        let schedule = match &props.weights {
+            Some(weights) => schedule(weights),
+            None => (0..client_specs.len()).collect(),
+        };
+        let start = match props.start_index {
+            Some(start) => start as usize % schedule.len(),
+            // Replicas sharing the rotation count must agree on the offset.
+            None if state.is_shared() => 0,
+            // For VSCode, we don't want a random start point,
+            // as it can make rendering inconsistent
+            None if cfg!(target_arch = "wasm32") => 0,
+            None => fastrand::usize(..schedule.len()),
+        };
+        Ok(Self {
+            name,
+            retry_policy,
+            client_specs,
+            schedule,
+            start,
+            state,
+            health: props.health_check.map(Health::new),
+        })
+    }
+
+    fn state_key(&self) -> String {
+        format!("round_robin:{}:index", self.name)
+    }
+
     pub fn current_index(&self) -> usize {
-        self.current_index
-            .load(std::sync::atomic::Ordering::Relaxed)
+        let count = self.state.get(&self.state_key()).unwrap_or_else(|e| {
+            log::warn!("Failed to read the rotation of {}: {e:#}", self.name);
+            0
+        });
+        self.start + count as usize
     }
 
     pub fn increment_index(&self) {
-        self.current_index
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let Err(e) = self.state.increment(&self.state_key()) {
+            log::warn!("Failed to advance the rotation of {}: {e:#}", self.name);
+        }
+    }
+
+    /// The client whose turn `turn` is, or the next healthy one if it is
+    /// failing. If every client is failing, the rotation carries on as if
+    /// there were no health check.
+    fn client_for_turn(&self, turn: usize) -> usize {
+        let scheduled = self.schedule[turn % self.schedule.len()];
+        let Some(health) = &self.health else {
+            return scheduled;
+        };
+        (0..self.schedule.len())
+            .map(|i| self.schedule[(turn + i) % self.schedule.len()])
+            .find(|client| health.is_healthy(*client))
+            .unwrap_or(scheduled)
+    }
+
+    /// Records how a request to `client_specs[client]` went, for the health
+    /// check.
+    pub fn record_outcome(&self, client: usize, succeeded: bool) {
+        if let Some(health) = &self.health {
+            health.record(client, succeeded);
+        }
     }
 }
 
@@ -56,7 +178,7 @@ fn resolve_strategy(
     provider: &ClientProvider,
     properties: &UnresolvedClientProperty<()>,
     ctx: &RuntimeContext,
-) -> Result<(Vec<ClientSpec>, usize)> {
+) -> Result<ResolvedRoundRobin> {
     let properties = properties.resolve(provider, &ctx.eval_ctx(false))?;
     let ResolvedClientProperty::RoundRobin(props) = properties else {
         anyhow::bail!(
@@ -64,19 +186,7 @@ fn resolve_strategy(
             properties.name()
         );
     };
-    let start = match props.start_index {
-        Some(start) => (start as usize) % props.strategy.len(),
-        None => {
-            if cfg!(target_arch = "wasm32") {
-                // For VSCode, we don't want a random start point,
-                // as it can make rendering inconsistent
-                0
-            } else {
-                fastrand::usize(..props.strategy.len())
-            }
-        }
-    };
-    Ok((props.strategy, start))
+    Ok(props)
 }
 
 impl TryFrom<(&ClientProperty, &RuntimeContext)> for RoundRobinStrategy {
@@ -85,15 +195,9 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for RoundRobinStrategy {
     fn try_from(
         (client, ctx): (&ClientProperty, &RuntimeContext),
     ) -> std::result::Result<Self, Self::Error> {
-        let (strategy, start) =
-            resolve_strategy(&client.provider, &client.unresolved_options()?, ctx)?;
-
-        Ok(RoundRobinStrategy {
-            name: client.name.clone(),
-            retry_policy: client.retry_policy.clone(),
-            client_specs: strategy,
-            current_index: AtomicUsize::new(start),
-        })
+        let props = resolve_strategy(&client.provider, &client.unresolved_options()?, ctx)?;
+
+        RoundRobinStrategy::new(client.name.clone(), client.retry_policy.clone(), props, ctx)
     }
 }
 
@@ -101,13 +205,13 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for RoundRobinStrategy {
     type Error = anyhow::Error;
 
     fn try_from((client, ctx): (&ClientWalker, &RuntimeContext)) -> Result<Self> {
-        let (strategy, start) = resolve_strategy(&client.elem().provider, client.options(), ctx)?;
-        Ok(Self {
-            name: client.item.elem.name.clone(),
-            retry_policy: client.retry_policy().as_ref().map(String::from),
-            client_specs: strategy,
-            current_index: AtomicUsize::new(start),
-        })
+        let props = resolve_strategy(&client.elem().provider, client.options(), ctx)?;
+        Self::new(
+            client.item.elem.name.clone(),
+            client.retry_policy().as_ref().map(String::from),
+            props,
+            ctx,
+        )
     }
 }
 
@@ -120,7 +224,7 @@ impl IterOrchestrator for Arc<RoundRobinStrategy> {
         client_lookup: &'a dyn InternalClientLookup<'a>,
     ) -> Result<OrchestratorNodeIterator> {
         let offset = state.client_to_usage.entry(self.name.clone()).or_insert(0);
-        let next = (self.current_index() + *offset) % self.client_specs.len();
+        let next = self.client_for_turn(self.current_index() + *offset);
 
         // Update the usage count
         *offset += 1;
@@ -136,3 +240,38 @@ impl IterOrchestrator for Arc<RoundRobinStrategy> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_schedule() {
+        assert_eq!(schedule(&[1, 1, 1]), [0, 1, 2]);
+        assert_eq!(schedule(&[3, 1]), [0, 0, 1, 0]);
+        assert_eq!(schedule(&[5, 1, 1]), [0, 0, 1, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn health_window() {
+        let health = Health::new(HealthCheck {
+            window: 4,
+            max_error_rate: 0.5,
+        });
+        health.record(0, false);
+        health.record(1, true);
+        assert!(!health.is_healthy(0));
+        assert!(health.is_healthy(1));
+        // Never tried clients are healthy.
+        assert!(health.is_healthy(2));
+
+        health.record(0, true);
+        assert!(health.is_healthy(0));
+        health.record(0, false);
+        assert!(!health.is_healthy(0));
+
+        // The first failure falls out of the window.
+        health.record(1, true);
+        assert!(health.is_healthy(0));
+    }
+}
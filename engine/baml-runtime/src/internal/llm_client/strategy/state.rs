@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+
+use crate::RuntimeContext;
+
+/// State strategies keep between calls, e.g. which client a round-robin
+/// picks next. Values are counters, which also covers circuit-breaker state
+/// such as consecutive failures or the time a client may be retried.
+///
+/// Keys look like `round_robin:<client name>:index`.
+pub trait StrategyState: Send + Sync + Debug {
+    /// Whether other processes see the same state.
+    fn is_shared(&self) -> bool;
+
+    /// The counter at `key`, `0` if it was never set.
+    fn get(&self, key: &str) -> Result<u64>;
+
+    fn set(&self, key: &str, value: u64) -> Result<()>;
+
+    /// Adds one to the counter at `key` and returns the new value.
+    fn increment(&self, key: &str) -> Result<u64>;
+}
+
+/// The backend named by `BAML_STRATEGY_STATE`:
+/// - unset or `memory`: this process only
+/// - `file://<path>`: a JSON file, locked while it's updated
+/// - `redis://...` or `rediss://...`: needs the `strategy-state-redis` feature
+pub fn from_env(ctx: &RuntimeContext) -> Result<Arc<dyn StrategyState>> {
+    let Some(url) = ctx
+        .env_vars()
+        .get("BAML_STRATEGY_STATE")
+        .filter(|url| !url.is_empty())
+    else {
+        return Ok(Arc::new(InMemoryState::default()));
+    };
+    if url == "memory" {
+        return Ok(Arc::new(InMemoryState::default()));
+    }
+    if let Some(path) = url.strip_prefix("file://") {
+        return Ok(Arc::new(FileState::new(path)));
+    }
+    if url.starts_with("redis://") || url.starts_with("rediss://") {
+        #[cfg(all(feature = "strategy-state-redis", not(target_arch = "wasm32")))]
+        return Ok(Arc::new(redis_state::RedisState::new(url)?));
+        #[cfg(not(all(feature = "strategy-state-redis", not(target_arch = "wasm32"))))]
+        anyhow::bail!(
+            "BAML_STRATEGY_STATE is a Redis URL, which needs baml-runtime's `strategy-state-redis` feature"
+        );
+    }
+    anyhow::bail!(
+        "Invalid BAML_STRATEGY_STATE `{url}`. Expected `memory`, `file://<path>` or a Redis URL"
+    )
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryState {
+    values: Mutex<HashMap<String, u64>>,
+}
+
+impl StrategyState for InMemoryState {
+    fn is_shared(&self) -> bool {
+        false
+    }
+
+    fn get(&self, key: &str) -> Result<u64> {
+        Ok(self.values.lock().unwrap().get(key).copied().unwrap_or(0))
+    }
+
+    fn set(&self, key: &str, value: u64) -> Result<()> {
+        self.values.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn increment(&self, key: &str) -> Result<u64> {
+        let mut values = self.values.lock().unwrap();
+        let value = values.entry(key.to_string()).or_insert(0);
+        *value += 1;
+        Ok(*value)
+    }
+}
+
+/// Counters in a JSON object, for replicas that share a filesystem.
+#[derive(Debug)]
+pub struct FileState {
+    path: PathBuf,
+}
+
+impl FileState {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Runs `f` on the file's counters while holding an exclusive lock on it,
+    /// writing them back if `f` changed them.
+    fn update<T>(&self, f: impl FnOnce(&mut HashMap<String, u64>) -> T) -> Result<T> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock {}", self.path.display()))?;
+
+        let mut values = read_values(&mut file)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        let before = values.clone();
+        let result = f(&mut values);
+        if values != before {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(serde_json::to_string(&values)?.as_bytes())
+                .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        }
+        Ok(result)
+    }
+}
+
+fn read_values(file: &mut File) -> Result<HashMap<String, u64>> {
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    if content.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(serde_json::from_str(&content)?)
+}
+
+impl StrategyState for FileState {
+    fn is_shared(&self) -> bool {
+        true
+    }
+
+    fn get(&self, key: &str) -> Result<u64> {
+        self.update(|values| values.get(key).copied().unwrap_or(0))
+    }
+
+    fn set(&self, key: &str, value: u64) -> Result<()> {
+        self.update(|values| {
+            values.insert(key.to_string(), value);
+        })
+    }
+
+    fn increment(&self, key: &str) -> Result<u64> {
+        self.update(|values| {
+            let value = values.entry(key.to_string()).or_insert(0);
+            *value += 1;
+            *value
+        })
+    }
+}
+
+#[cfg(all(feature = "strategy-state-redis", not(target_arch = "wasm32")))]
+mod redis_state {
+    use std::sync::Mutex;
+
+    use anyhow::{Context, Result};
+    use redis::Commands;
+
+    use super::StrategyState;
+
+    /// Keys are prefixed with `baml:`.
+    pub struct RedisState {
+        client: redis::Client,
+        connection: Mutex<Option<redis::Connection>>,
+    }
+
+    impl std::fmt::Debug for RedisState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("RedisState")
+                .field("connection_info", self.client.get_connection_info())
+                .finish()
+        }
+    }
+
+    impl RedisState {
+        pub fn new(url: &str) -> Result<Self> {
+            Ok(Self {
+                client: redis::Client::open(url).context("Invalid Redis URL")?,
+                connection: Mutex::new(None),
+            })
+        }
+
+        /// Connects on first use, and again after a command fails.
+        fn with_connection<T>(
+            &self,
+            f: impl FnOnce(&mut redis::Connection) -> redis::RedisResult<T>,
+        ) -> Result<T> {
+            let mut connection = self.connection.lock().unwrap();
+            if connection.is_none() {
+                *connection = Some(
+                    self.client
+                        .get_connection()
+                        .context("Failed to connect to Redis")?,
+                );
+            }
+            let result = f(connection.as_mut().unwrap());
+            if result.is_err() {
+                *connection = None;
+            }
+            Ok(result?)
+        }
+    }
+
+    impl StrategyState for RedisState {
+        fn is_shared(&self) -> bool {
+            true
+        }
+
+        fn get(&self, key: &str) -> Result<u64> {
+            self.with_connection(|c| c.get::<_, Option<u64>>(format!("baml:{key}")))
+                .map(|value| value.unwrap_or(0))
+        }
+
+        fn set(&self, key: &str, value: u64) -> Result<()> {
+            self.with_connection(|c| c.set(format!("baml:{key}"), value))
+        }
+
+        fn increment(&self, key: &str) -> Result<u64> {
+            self.with_connection(|c| c.incr(format!("baml:{key}"), 1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_counters(state: &dyn StrategyState) {
+        assert_eq!(state.get("round_robin:A:index").unwrap(), 0);
+        assert_eq!(state.increment("round_robin:A:index").unwrap(), 1);
+        assert_eq!(state.increment("round_robin:A:index").unwrap(), 2);
+        state.set("round_robin:B:index", 7).unwrap();
+        assert_eq!(state.get("round_robin:B:index").unwrap(), 7);
+        assert_eq!(state.get("round_robin:A:index").unwrap(), 2);
+    }
+
+    #[test]
+    fn in_memory_counters() {
+        check_counters(&InMemoryState::default());
+    }
+
+    #[test]
+    fn file_counters_are_shared() {
+        let path =
+            std::env::temp_dir().join(format!("baml-strategy-state-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        check_counters(&FileState::new(&path));
+        // A second handle, like another replica, sees the same counters.
+        assert_eq!(FileState::new(&path).get("round_robin:A:index").unwrap(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
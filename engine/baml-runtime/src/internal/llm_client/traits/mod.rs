@@ -1,8 +1,7 @@
 use std::{collections::HashMap, path::PathBuf, pin::Pin};
 
 use anyhow::{Context, Result};
-use aws_smithy_types::byte_stream::error::Error;
-use internal_llm_client::{AllowedRoleMetadata, FinishReasonFilter};
+use internal_llm_client::{AllowedRoleMetadata, CandidateSelection, FinishReasonFilter};
 use serde_json::{json, Map};
 
 mod chat;
@@ -11,8 +10,14 @@ pub use self::{
     chat::{WithChat, WithStreamChat},
     completion::{WithCompletion, WithNoCompletion, WithStreamCompletion},
 };
-use super::{primitive::request::RequestBuilder, LLMResponse, ModelFeatures};
-use crate::{internal::llm_client::ResolveMediaUrls, RenderCurlSettings};
+use super::{
+    primitive::request::RequestBuilder, LLMCompleteResponse, LLMResponse, LLMStreamEvent,
+    ModelFeatures,
+};
+use crate::{
+    internal::llm_client::{image_preprocessing::ImagePreprocessing, ResolveMediaUrls},
+    RenderCurlSettings,
+};
 use crate::{internal::prompt_renderer::PromptRenderer, RuntimeContext};
 use baml_types::{BamlMedia, BamlMediaContent, BamlMediaType, BamlValue, MediaBase64, MediaUrl};
 use base64::{prelude::BASE64_STANDARD, Engine};
@@ -37,6 +42,8 @@ pub trait WithClientProperties {
     fn allowed_metadata(&self) -> &AllowedRoleMetadata;
     fn supports_streaming(&self) -> bool;
     fn finish_reason_filter(&self) -> &FinishReasonFilter;
+    /// How to pick between the candidates of a response with several.
+    fn candidate_selection(&self) -> CandidateSelection;
     fn default_role(&self) -> String;
     fn allowed_roles(&self) -> Vec<String>;
 }
@@ -154,6 +161,7 @@ where
         if let RenderedPrompt::Chat(chat) = &prompt {
             match process_media_urls(
                 self.model_features().resolve_media_urls,
+                self.model_features().image_preprocessing,
                 true,
                 None,
                 ctx,
@@ -217,7 +225,10 @@ where
     ) -> Result<RenderedPrompt> {
         let features = self.model_features();
 
-        let prompt = renderer.render_prompt(ir, ctx, params, self.context())?;
+        let mut prompt = renderer.render_prompt(ir, ctx, params, self.context())?;
+        if let Some(reask) = &ctx.reask {
+            prompt = reask.apply(prompt);
+        }
 
         let prompt = match prompt {
             RenderedPrompt::Completion(_) => prompt,
@@ -225,7 +236,8 @@ where
                 let chat = merge_messages(&chat);
                 // We never need to resolve media URLs here: webview rendering understands how to handle URLs and file refs
                 let chat =
-                    process_media_urls(ResolveMediaUrls::Never, true, None, ctx, &chat).await?;
+                    process_media_urls(ResolveMediaUrls::Never, None, true, None, ctx, &chat)
+                        .await?;
                 RenderedPrompt::Chat(chat)
             }
         };
@@ -276,6 +288,7 @@ where
     ) -> Result<String> {
         let chat_messages: Vec<RenderedChatMessage> = process_media_urls(
             self.model_features().resolve_media_urls,
+            self.model_features().image_preprocessing,
             true,
             Some(render_settings),
             ctx,
@@ -285,6 +298,7 @@ where
 
         let request_builder = self
             .build_request(
+                ctx,
                 either::Right(&chat_messages),
                 false,
                 render_settings.stream && self.supports_streaming(),
@@ -298,15 +312,14 @@ where
 
         let url_str = url_header_value.to_string();
 
+        let body = crate::request::request_body(&request).unwrap_or_default();
         {
             let headers = request.headers_mut();
             headers.remove("baml-original-url");
+            // The command sends the body uncompressed.
+            headers.remove(reqwest::header::CONTENT_ENCODING);
         }
 
-        let body = request
-            .body()
-            .map(|b| b.as_bytes().unwrap_or_default().to_vec())
-            .unwrap_or_default(); // Add this line to handle the Option
         let request_str = to_curl_command(&url_str, "POST", request.headers(), body);
 
         Ok(request_str)
@@ -320,16 +333,69 @@ pub trait SseResponseTrait {
         resp: reqwest::Response,
         prompt: &[internal_baml_jinja::RenderedChatMessage],
         system_start: web_time::SystemTime,
-        instant_start: web_time::Instant,
+        instant_start: crate::clock::Stopwatch,
     ) -> StreamResponse;
 }
 
 #[cfg(target_arch = "wasm32")]
-pub type StreamResponse = Result<Pin<Box<dyn futures::Stream<Item = LLMResponse>>>, LLMResponse>;
+pub type StreamResponse = Result<Pin<Box<dyn futures::Stream<Item = LLMStreamEvent>>>, LLMResponse>;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub type StreamResponse =
-    Result<Pin<Box<dyn futures::Stream<Item = LLMResponse> + Send + Sync>>, LLMResponse>;
+    Result<Pin<Box<dyn futures::Stream<Item = LLMStreamEvent> + Send + Sync>>, LLMResponse>;
+
+/// Fold a provider's decoded event stream into a single accumulated response.
+///
+/// `apply` merges one provider event into the response (model, metadata, ...)
+/// and returns the text it contributes, if any; that text is appended to the
+/// accumulated content and emitted as an [`LLMStreamEvent::Delta`]. Returning
+/// `Err` ends the stream with that failure. Once `events` is exhausted the
+/// accumulated response is emitted as [`LLMStreamEvent::Done`].
+pub fn accumulate_stream<S, E, F>(
+    events: S,
+    initial: LLMCompleteResponse,
+    instant_start: crate::clock::Stopwatch,
+    apply: F,
+) -> impl futures::Stream<Item = LLMStreamEvent>
+where
+    S: futures::Stream<Item = E>,
+    F: FnMut(&mut LLMCompleteResponse, E) -> Result<Option<String>, LLMResponse>,
+{
+    futures::stream::unfold(
+        (Box::pin(events), Some(initial), apply),
+        move |(mut events, state, mut apply)| {
+            let instant_start = instant_start.clone();
+            async move {
+                // `None` means the stream already emitted its final event.
+                let mut response = state?;
+                loop {
+                    let Some(event) = events.next().await else {
+                        response.latency = instant_start.elapsed();
+                        let done = LLMStreamEvent::Done(LLMResponse::Success(response));
+                        return Some((done, (events, None, apply)));
+                    };
+                    match apply(&mut response, event) {
+                        // Metadata-only events don't produce a delta.
+                        Ok(None) => continue,
+                        Ok(Some(text)) => {
+                            response.content.push_str(&text);
+                            response.latency = instant_start.elapsed();
+                            let delta = LLMStreamEvent::Delta {
+                                text,
+                                model: response.model.clone(),
+                                metadata: response.metadata.clone(),
+                            };
+                            return Some((delta, (events, Some(response), apply)));
+                        }
+                        Err(failure) => {
+                            return Some((LLMStreamEvent::Done(failure), (events, None, apply)));
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
 
 pub trait WithStreamable {
     /// Retries are not supported for streaming calls.
@@ -352,6 +418,7 @@ where
             if let RenderedPrompt::Chat(ref chat) = prompt {
                 match process_media_urls(
                     self.model_features().resolve_media_urls,
+                    self.model_features().image_preprocessing,
                     true,
                     None,
                     ctx,
@@ -378,7 +445,9 @@ where
                     self.stream_chat(ctx, p).await
                 } else {
                     let res = self.chat(ctx, p).await;
-                    Ok(Box::pin(futures::stream::once(async move { res })))
+                    Ok(Box::pin(futures::stream::once(async move {
+                        LLMStreamEvent::Done(res)
+                    })))
                 }
             }
             RenderedPrompt::Completion(p) => {
@@ -386,7 +455,9 @@ where
                     self.stream_completion(ctx, p).await
                 } else {
                     let res = self.completion(ctx, p).await;
-                    Ok(Box::pin(futures::stream::once(async move { res })))
+                    Ok(Box::pin(futures::stream::once(async move {
+                        LLMStreamEvent::Done(res)
+                    })))
                 }
             }
         }
@@ -397,6 +468,7 @@ where
 /// Other formats will be converted into that, depending on what formats are allowed according to supported_media_formats.
 async fn process_media_urls(
     resolve_media_urls: ResolveMediaUrls,
+    image_preprocessing: Option<ImagePreprocessing>,
     resolve_files: bool,
     render_settings: Option<RenderCurlSettings>,
     ctx: &RuntimeContext,
@@ -415,15 +487,21 @@ async fn process_media_urls(
                 let Some(part) = any_part.as_media() else {
                     return Ok::<ChatMessagePart, anyhow::Error>(any_part.clone());
                 };
-                let media = process_media(
+                let mut media = process_media(
                     resolve_media_urls,
                     resolve_files,
                     render_settings,
                     ctx,
                     part,
                 )
-                .await
-                .map(ChatMessagePart::Media)?;
+                .await?;
+                // Shell commands stand in for the media, there's nothing to process.
+                if let Some(preprocessing) = image_preprocessing {
+                    if !render_settings.as_shell_commands {
+                        media = preprocessing.apply(media)?;
+                    }
+                }
+                let media = ChatMessagePart::Media(media);
 
                 if let Some(meta) = any_part.meta() {
                     Ok(media.with_meta(meta.clone()))
@@ -607,9 +685,37 @@ async fn process_media(
                 mime_type,
             ))
         }
+        // Already uploaded, the provider resolves it.
+        BamlMediaContent::FileRef(_) => Ok(part.clone()),
     }
 }
 
+/// The bytes and mime type of a file, URL or base64 `media`, e.g. to upload
+/// it to a provider.
+pub(crate) async fn media_bytes(
+    ctx: &RuntimeContext,
+    media: &BamlMedia,
+) -> Result<(Vec<u8>, String)> {
+    let resolved = process_media(
+        ResolveMediaUrls::Always,
+        true,
+        RenderCurlSettings {
+            stream: false,
+            as_shell_commands: false,
+        },
+        ctx,
+        media,
+    )
+    .await?;
+    let BamlMediaContent::Base64(media_b64) = &resolved.content else {
+        anyhow::bail!("Can't read the contents of an uploaded file");
+    };
+    let bytes = BASE64_STANDARD
+        .decode(&media_b64.base64)
+        .context("Failed to decode media as base64")?;
+    Ok((bytes, resolved.mime_type_as_ok()?))
+}
+
 async fn to_base64_with_inferred_mime_type(
     ctx: &RuntimeContext,
     media_url: &MediaUrl,
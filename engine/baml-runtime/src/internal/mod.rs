@@ -2,5 +2,5 @@ pub mod ir_features;
 pub mod llm_client;
 pub mod prompt_renderer;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "provider-vertex"))]
 pub mod wasm_jwt;
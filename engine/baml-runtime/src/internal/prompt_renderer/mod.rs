@@ -1,10 +1,10 @@
 mod render_output_format;
 use internal_llm_client::ClientSpec;
 use jsonish::BamlValueWithFlags;
-use render_output_format::render_output_format;
+pub(crate) use render_output_format::render_output_format;
 
 use anyhow::Result;
-use baml_types::{BamlValue, FieldType};
+use baml_types::{BamlValue, FieldType, PiiKind};
 use internal_baml_core::{
     error_unsupported,
     ir::{repr::IntermediateRepr, FunctionWalker, IRHelper},
@@ -14,13 +14,17 @@ use internal_baml_jinja::{
     TemplateStringMacro,
 };
 
-use crate::RuntimeContext;
+use crate::{
+    pii::{mask_pii, PiiTokenMap},
+    RuntimeContext,
+};
 
 pub struct PromptRenderer {
     function_name: String,
     client_spec: ClientSpec,
     output_defs: OutputFormatContent,
     output_type: FieldType,
+    mask_pii: Vec<PiiKind>,
 }
 
 impl PromptRenderer {
@@ -37,11 +41,17 @@ impl PromptRenderer {
         Ok(PromptRenderer {
             function_name: function.name().into(),
             client_spec: match &ctx.client_overrides {
+                // The primary may also be a shorthand like `openai/gpt-4o?temperature=0`.
+                Some((Some(client), clients)) if !clients.contains_key(client) => {
+                    ClientSpec::new_from_id(client)
+                        .unwrap_or_else(|_| ClientSpec::Named(client.clone()))
+                }
                 Some((Some(client), _)) => ClientSpec::Named(client.clone()),
                 _ => config.client.clone(),
             },
             output_defs: render_output_format(ir, ctx, &func_v2.output)?,
             output_type: func_v2.output.clone(),
+            mask_pii: config.mask_pii.clone(),
         })
     }
 
@@ -58,6 +68,18 @@ impl PromptRenderer {
         )
     }
 
+    /// Parses a complete response the provider guarantees is JSON, see
+    /// [`jsonish::from_json_str`].
+    pub fn parse_json(&self, raw_string: &str) -> Result<BamlValueWithFlags> {
+        jsonish::from_json_str(&self.output_defs, &self.output_type, raw_string)
+    }
+
+    /// Masks the PII the function's `mask_pii` asks for. The map is empty if
+    /// it asks for none.
+    pub fn mask_pii(&self, prompt: RenderedPrompt) -> (RenderedPrompt, PiiTokenMap) {
+        mask_pii(prompt, &self.mask_pii)
+    }
+
     pub fn render_prompt(
         &self,
         ir: &IntermediateRepr,
@@ -73,8 +95,9 @@ impl PromptRenderer {
             error_unsupported!("function", self.function_name, "no valid prompt found")
         };
 
-        internal_baml_jinja::render_prompt(
+        internal_baml_jinja::render_prompt_with_source(
             &config.prompt_template,
+            config.prompt_source.as_ref(),
             params,
             RenderContext {
                 client: client_ctx.clone(),
@@ -89,7 +112,9 @@ impl PromptRenderer {
                         .iter()
                         .map(|i| (i.name.clone(), i.r#type.elem.to_string()))
                         .collect(),
+                    defaults: t.defaults().clone(),
                     template: t.template().into(),
+                    source: t.elem().source.clone(),
                 })
                 .collect::<Vec<_>>(),
             ir,
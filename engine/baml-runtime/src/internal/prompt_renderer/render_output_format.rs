@@ -1,8 +1,8 @@
 use std::collections::HashSet;
 
 use anyhow::Result;
-use baml_types::BamlValue;
-use indexmap::IndexSet;
+use baml_types::{BamlValue, BoolStrings, StreamingBehavior};
+use indexmap::{IndexMap, IndexSet};
 use internal_baml_core::ir::{
     repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, IRHelper,
 };
@@ -19,14 +19,64 @@ pub fn render_output_format(
     output: &FieldType,
 ) -> Result<OutputFormatContent> {
     let (enums, classes, recursive_classes) = relevant_data_models(ir, output, ctx)?;
+    let bool_strings = field_bool_strings(ir, &classes);
+    let streaming = field_streaming(ir, &classes);
 
     Ok(OutputFormatContent::target(output.clone())
         .enums(enums)
         .classes(classes)
         .recursive_classes(recursive_classes)
+        .number_locale(ctx.number_locale)
+        .bool_strings(bool_strings)
+        .streaming(streaming)
         .build())
 }
 
+/// The `@bool_strings` of the fields of `classes`, for those that have one.
+fn field_bool_strings(
+    ir: &IntermediateRepr,
+    classes: &[Class],
+) -> IndexMap<(String, String), BoolStrings> {
+    let mut bool_strings = IndexMap::new();
+    for class in classes {
+        let Ok(walker) = ir.find_class(class.name.real_name()) else {
+            continue;
+        };
+        for field in walker.walk_fields() {
+            if field.bool_strings() != BoolStrings::Strict {
+                bool_strings.insert(
+                    (walker.name().to_string(), field.name().to_string()),
+                    field.bool_strings(),
+                );
+            }
+        }
+    }
+    bool_strings
+}
+
+/// The `@stream.*` attributes of the fields of `classes`, for those that have
+/// any.
+fn field_streaming(
+    ir: &IntermediateRepr,
+    classes: &[Class],
+) -> IndexMap<(String, String), StreamingBehavior> {
+    let mut streaming = IndexMap::new();
+    for class in classes {
+        let Ok(walker) = ir.find_class(class.name.real_name()) else {
+            continue;
+        };
+        for field in walker.walk_fields() {
+            if !field.streaming().is_default() {
+                streaming.insert(
+                    (walker.name().to_string(), field.name().to_string()),
+                    field.streaming(),
+                );
+            }
+        }
+    }
+    streaming
+}
+
 enum OverridableValue<T> {
     Unset,
     SetEmpty,
@@ -247,6 +297,11 @@ fn relevant_data_models<'a>(
                         .filter_map(|v| v.transpose())
                         .collect::<Result<Vec<_>>>()?;
 
+                    // A `TypeBuilder`'s matching applies to every enum of the call.
+                    let matching = ctx.enum_matching.unwrap_or_else(|| {
+                        walker.as_ref().map(|w| w.matching()).unwrap_or_default()
+                    });
+
                     let mut alias =
                         OverridableValue::<String>::from(overrides.and_then(|o| o.alias.as_ref()));
 
@@ -262,6 +317,7 @@ fn relevant_data_models<'a>(
                         name: Name::new_with_alias(enm.to_string(), alias.value()),
                         values,
                         constraints,
+                        matching,
                     });
                 }
             }
@@ -8,12 +8,18 @@ pub(crate) mod internal;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod cli;
 pub mod client_registry;
+pub mod clock;
 pub mod constraints;
+pub mod coverage;
+pub mod datasets;
 pub mod errors;
+pub mod expectations;
+pub mod pii;
 pub mod request;
 mod runtime;
 pub mod runtime_interface;
 pub mod tracing;
+pub mod transport;
 pub mod type_builder;
 mod types;
 
@@ -25,10 +31,13 @@ use anyhow::Context;
 use anyhow::Result;
 
 use baml_types::BamlMap;
+use baml_types::BamlMedia;
+use baml_types::BamlMediaContent;
 use baml_types::BamlValue;
 use baml_types::Constraint;
 use cfg_if::cfg_if;
 use client_registry::ClientRegistry;
+use clock::{SystemClock, TimeProvider};
 use indexmap::IndexMap;
 use internal_baml_core::configuration::CloudProject;
 use internal_baml_core::configuration::CodegenGenerator;
@@ -37,11 +46,13 @@ use internal_baml_core::configuration::GeneratorOutputType;
 use on_log_event::LogEventCallbackSync;
 use runtime::InternalBamlRuntime;
 use std::sync::OnceLock;
+use transport::{HttpTransport, ReqwestTransport};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use cli::RuntimeCliDefaults;
 pub use runtime_context::BamlSrcReader;
 use runtime_interface::ExperimentalTracingInterface;
+use runtime_interface::InternalClientLookup;
 use runtime_interface::RuntimeConstructor;
 use runtime_interface::RuntimeInterface;
 use tracing::{BamlTracer, TracingSpan};
@@ -60,11 +71,20 @@ pub(crate) use internal_baml_jinja::{ChatMessagePart, RenderedPrompt};
 #[cfg(not(feature = "internal"))]
 pub(crate) use runtime_interface::InternalRuntimeInterface;
 
+pub use internal::llm_client::primitive::{is_provider_supported, supported_providers};
+pub use internal::llm_client::{Citation, GroundingMetadata, ResponseCandidate, TokenLogprob};
 pub use internal_baml_core::internal_baml_diagnostics;
 pub use internal_baml_core::internal_baml_diagnostics::Diagnostics as DiagnosticsError;
+use internal_baml_core::internal_baml_diagnostics::SourcePath;
 pub use internal_baml_core::ir::{scope_diagnostics, FieldType, IRHelper, TypeValue};
+pub use internal_baml_jinja::PromptRenderError;
 
 use crate::constraints::{evaluate_test_constraints, TestConstraintsResult};
+use crate::datasets::{parse_dataset, DatasetRow};
+use crate::expectations::diff_expectation;
+use crate::internal::llm_client::llm_provider::LLMProvider;
+use crate::internal::llm_client::primitive::LLMPrimitiveProvider;
+use crate::internal::llm_client::traits::media_bytes;
 use crate::internal::llm_client::LLMResponse;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -74,6 +94,13 @@ pub struct BamlRuntime {
     pub(crate) inner: InternalBamlRuntime,
     tracer: Arc<BamlTracer>,
     env_vars: HashMap<String, String>,
+    metrics: Option<MetricsRegistry>,
+    usage: UsageTracker,
+    hooks: RuntimeHooks,
+    post_processors: PostProcessors,
+    clock: Arc<dyn TimeProvider>,
+    http_transport: Arc<dyn HttpTransport>,
+    dry_run: bool,
     #[cfg(not(target_arch = "wasm32"))]
     pub async_runtime: Arc<tokio::runtime::Runtime>,
 }
@@ -83,6 +110,94 @@ impl BamlRuntime {
         &self.env_vars
     }
 
+    /// Records every function call made through this runtime into `metrics`,
+    /// or stops recording if `None`.
+    pub fn set_metrics(&mut self, metrics: Option<MetricsRegistry>) {
+        self.metrics = metrics;
+    }
+
+    pub fn metrics(&self) -> Option<&MetricsRegistry> {
+        self.metrics.as_ref()
+    }
+
+    /// Token usage and estimated cost of every call made through this
+    /// runtime so far, per function, client and model.
+    pub fn usage_report(&self) -> UsageReport {
+        self.usage.report()
+    }
+
+    /// Estimates the cost of calls from now on with `prices` instead of the
+    /// list prices.
+    pub fn set_price_table(&self, prices: PriceTable) {
+        self.usage.set_price_table(prices);
+    }
+
+    pub fn usage(&self) -> &UsageTracker {
+        &self.usage
+    }
+
+    /// Runs `hook` around every LLM request of the calls started from now
+    /// on, after the hooks added before it.
+    pub fn add_hook(&self, hook: Arc<dyn RuntimeHook>) {
+        self.hooks.add(hook);
+    }
+
+    pub fn hooks(&self) -> &RuntimeHooks {
+        &self.hooks
+    }
+
+    /// Runs `processor` on the results of `function_name` from now on, after
+    /// they parsed and their checks ran, and after the post-processors added
+    /// before it. Each run is a span in the trace. Streams aren't
+    /// post-processed.
+    pub fn add_post_processor(&self, function_name: &str, processor: Arc<dyn PostProcessor>) {
+        self.post_processors.add(function_name, processor);
+    }
+
+    pub fn post_processors(&self) -> &PostProcessors {
+        &self.post_processors
+    }
+
+    /// Times the calls from now on with `clock`, see [`clock`].
+    pub fn set_time_provider(&mut self, clock: Arc<dyn TimeProvider>) {
+        self.clock = clock;
+    }
+
+    pub fn time_provider(&self) -> &Arc<dyn TimeProvider> {
+        &self.clock
+    }
+
+    /// Sends the LLM requests of the calls from now on with `transport`,
+    /// see [`transport`].
+    pub fn set_http_transport(&mut self, transport: Arc<dyn HttpTransport>) {
+        self.http_transport = transport;
+    }
+
+    pub fn http_transport(&self) -> &Arc<dyn HttpTransport> {
+        &self.http_transport
+    }
+
+    /// In a dry run, calls render their prompt and build the request for
+    /// their first client, then return it in [`FunctionResult::dry_run_request`]
+    /// without sending it. Defaults to `BAML_DRY_RUN`.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn dry_run_from_env(env_vars: &HashMap<String, String>) -> bool {
+        matches!(
+            env_vars
+                .get("BAML_DRY_RUN")
+                .map(|v| v.to_lowercase())
+                .as_deref(),
+            Some("1" | "true")
+        )
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn get_tokio_singleton() -> Result<Arc<tokio::runtime::Runtime>> {
         match TOKIO_SINGLETON.get_or_init(|| tokio::runtime::Runtime::new().map(Arc::new)) {
@@ -92,7 +207,7 @@ impl BamlRuntime {
     }
 
     pub fn parse_baml_src_path(path: impl Into<PathBuf>) -> Result<PathBuf> {
-        let mut path: PathBuf = path.into();
+        let mut path: PathBuf = SourcePath::new(path.into()).into_path_buf();
 
         if !path.exists() {
             anyhow::bail!(
@@ -132,17 +247,68 @@ impl BamlRuntime {
     ) -> Result<Self> {
         let path = Self::parse_baml_src_path(path)?;
 
-        let copy = env_vars
+        let mut copy = env_vars
             .iter()
             .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
             .collect();
-        Ok(BamlRuntime {
-            inner: InternalBamlRuntime::from_directory(&path)?,
-            tracer: BamlTracer::new(None, env_vars.into_iter())?.into(),
+        let mut inner = InternalBamlRuntime::from_directory(&path)?;
+        inner.apply_profile(&mut copy)?;
+        inner.apply_conditions(&copy)?;
+        let tracer = BamlTracer::new(None, env_vars.into_iter())?.with_trace_settings(inner.ir());
+        let runtime = BamlRuntime {
+            inner,
+            tracer: tracer.into(),
+            dry_run: Self::dry_run_from_env(&copy),
             env_vars: copy,
-            #[cfg(not(target_arch = "wasm32"))]
+            metrics: None,
+            usage: UsageTracker::new(),
+            hooks: RuntimeHooks::default(),
+            post_processors: PostProcessors::default(),
+            clock: Arc::new(SystemClock),
+            http_transport: Arc::new(ReqwestTransport),
             async_runtime: Self::get_tokio_singleton()?,
-        })
+        };
+        runtime.log_description_once();
+        Ok(runtime)
+    }
+
+    /// Like [`BamlRuntime::from_directory`], composing several `baml_src`
+    /// directories into one runtime, e.g. a package of shared types and the
+    /// functions of one app. A name defined in two of them is an error.
+    /// Generators are resolved against the first directory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_directories<T: AsRef<str>>(
+        paths: &[&std::path::Path],
+        env_vars: HashMap<T, T>,
+    ) -> Result<Self> {
+        let paths = paths
+            .iter()
+            .map(|path| Self::parse_baml_src_path(*path))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut copy = env_vars
+            .iter()
+            .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+            .collect();
+        let mut inner = InternalBamlRuntime::from_directories(&paths)?;
+        inner.apply_profile(&mut copy)?;
+        inner.apply_conditions(&copy)?;
+        let tracer = BamlTracer::new(None, env_vars.into_iter())?.with_trace_settings(inner.ir());
+        let runtime = BamlRuntime {
+            inner,
+            tracer: tracer.into(),
+            dry_run: Self::dry_run_from_env(&copy),
+            env_vars: copy,
+            metrics: None,
+            usage: UsageTracker::new(),
+            hooks: RuntimeHooks::default(),
+            post_processors: PostProcessors::default(),
+            clock: Arc::new(SystemClock),
+            http_transport: Arc::new(ReqwestTransport),
+            async_runtime: Self::get_tokio_singleton()?,
+        };
+        runtime.log_description_once();
+        Ok(runtime)
     }
 
     pub fn from_file_content<T: AsRef<str>, U: AsRef<str>>(
@@ -150,17 +316,31 @@ impl BamlRuntime {
         files: &HashMap<T, T>,
         env_vars: HashMap<U, U>,
     ) -> Result<Self> {
-        let copy = env_vars
+        let mut copy = env_vars
             .iter()
             .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
             .collect();
-        Ok(BamlRuntime {
-            inner: InternalBamlRuntime::from_file_content(root_path, files)?,
-            tracer: BamlTracer::new(None, env_vars.into_iter())?.into(),
+        let mut inner = InternalBamlRuntime::from_file_content(root_path, files)?;
+        inner.apply_profile(&mut copy)?;
+        inner.apply_conditions(&copy)?;
+        let tracer = BamlTracer::new(None, env_vars.into_iter())?.with_trace_settings(inner.ir());
+        let runtime = BamlRuntime {
+            inner,
+            tracer: tracer.into(),
+            dry_run: Self::dry_run_from_env(&copy),
             env_vars: copy,
+            metrics: None,
+            usage: UsageTracker::new(),
+            hooks: RuntimeHooks::default(),
+            post_processors: PostProcessors::default(),
+            clock: Arc::new(SystemClock),
+            http_transport: Arc::new(ReqwestTransport),
             #[cfg(not(target_arch = "wasm32"))]
             async_runtime: Self::get_tokio_singleton()?,
-        })
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        runtime.log_description_once();
+        Ok(runtime)
     }
 
     #[cfg(feature = "internal")]
@@ -178,9 +358,176 @@ impl BamlRuntime {
             .into_iter()
             .map(|(k, v)| (k.to_string(), v))
             .collect();
-        ctx.upsert_tags(tags);
+        ctx.upsert_internal_tags(tags);
         ctx
     }
+
+    /// The environment variables clients reference and those of them that
+    /// are not set, both sorted.
+    fn env_var_status(&self) -> (Vec<String>, Vec<String>) {
+        let mut required = self
+            .inner
+            .ir()
+            .required_env_vars()
+            .into_iter()
+            .collect::<Vec<_>>();
+        required.sort();
+        let missing = required
+            .iter()
+            .filter(|var| !self.env_vars.contains_key(*var))
+            .cloned()
+            .collect();
+        (required, missing)
+    }
+
+    /// The functions, clients, generators and environment variables this
+    /// runtime was loaded with. Client options are resolved against the
+    /// runtime's environment, with credentials redacted.
+    pub fn describe(&self) -> RuntimeDescription {
+        let ir = self.inner.ir();
+        let ctx = RuntimeContextManager::new_from_env_vars(self.env_vars.clone(), None)
+            .create_ctx_with_default();
+
+        let functions = ir
+            .walk_functions()
+            .map(|function| FunctionDescription {
+                name: function.name().to_string(),
+                client: function.client_name(),
+            })
+            .collect();
+
+        let clients = ir
+            .walk_clients()
+            .map(|client| {
+                let mut description = ClientDescription {
+                    name: client.name().to_string(),
+                    provider: client.elem().provider.to_string(),
+                    retry_policy: client.retry_policy().clone(),
+                    options: Default::default(),
+                    error: None,
+                };
+                if !matches!(
+                    client.elem().provider,
+                    internal_llm_client::ClientProvider::Strategy(_)
+                ) {
+                    match LLMPrimitiveProvider::try_from((&client, &ctx)) {
+                        Ok(provider) => {
+                            description.options = redact_options(provider.request_options())
+                        }
+                        Err(e) => description.error = Some(format!("{e:#}")),
+                    }
+                }
+                description
+            })
+            .collect();
+
+        let generators = ir
+            .configuration()
+            .generators
+            .iter()
+            .map(|generator| match generator {
+                Generator::Codegen(generator) => GeneratorDescription {
+                    name: generator.name.clone(),
+                    output_type: generator.output_type.to_string(),
+                    version: generator.version.clone(),
+                    output_dir: Some(generator.output_dir().display().to_string()),
+                },
+                Generator::BoundaryCloud(project) => GeneratorDescription {
+                    name: project.name.clone(),
+                    output_type: "boundary-cloud".to_string(),
+                    version: project.version.clone(),
+                    output_dir: None,
+                },
+            })
+            .collect();
+
+        let (required_env_vars, missing_env_vars) = self.env_var_status();
+        RuntimeDescription {
+            baml_version: env!("CARGO_PKG_VERSION").to_string(),
+            functions,
+            clients,
+            generators,
+            required_env_vars,
+            missing_env_vars,
+        }
+    }
+
+    /// Logs [`Self::describe`] for the first runtime of the process.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn log_description_once(&self) {
+        static LOGGED: std::sync::Once = std::sync::Once::new();
+        if log::log_enabled!(log::Level::Info) {
+            LOGGED.call_once(|| log::info!("Loaded BAML runtime\n{}", self.describe()));
+        }
+    }
+
+    /// Whether this runtime can serve calls: the IR is loaded, every
+    /// environment variable a client needs is set and, with
+    /// `check_providers`, every client's host answers.
+    pub async fn readiness(&self, options: &ReadinessOptions) -> ReadinessReport {
+        let ir = self.inner.ir();
+        let mut checks = vec![ReadinessCheck::new(
+            "ir",
+            CheckStatus::Pass,
+            Some(format!("{} functions loaded", ir.function_names().count())),
+        )];
+
+        let (_, missing) = self.env_var_status();
+        checks.push(if missing.is_empty() {
+            ReadinessCheck::new("env_vars", CheckStatus::Pass, None)
+        } else {
+            ReadinessCheck::new(
+                "env_vars",
+                CheckStatus::Fail,
+                Some(format!("Missing {}", missing.join(", "))),
+            )
+        });
+
+        if options.check_providers {
+            let ctx = RuntimeContextManager::new_from_env_vars(self.env_vars.clone(), None)
+                .create_ctx_with_default();
+            let probes = ir
+                .walk_clients()
+                .filter(|client| {
+                    !matches!(
+                        client.elem().provider,
+                        internal_llm_client::ClientProvider::Strategy(_)
+                    )
+                })
+                .map(|client| {
+                    let name = format!("client:{}", client.name());
+                    let provider = LLMPrimitiveProvider::try_from((&client, &ctx));
+                    let ctx = &ctx;
+                    async move {
+                        match provider {
+                            Ok(provider) => match provider
+                                .check_reachable(ctx, options.timeout)
+                                .await
+                            {
+                                Some(Ok(())) => ReadinessCheck::new(name, CheckStatus::Pass, None),
+                                Some(Err(e)) => ReadinessCheck::new(
+                                    name,
+                                    CheckStatus::Fail,
+                                    Some(format!("{e:#}")),
+                                ),
+                                None => ReadinessCheck::new(
+                                    name,
+                                    CheckStatus::Skipped,
+                                    Some(format!("{provider} clients can't be checked")),
+                                ),
+                            },
+                            Err(e) => {
+                                ReadinessCheck::new(name, CheckStatus::Fail, Some(format!("{e:#}")))
+                            }
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            checks.extend(futures::future::join_all(probes).await);
+        }
+
+        ReadinessReport::new(checks)
+    }
 }
 
 impl BamlRuntime {
@@ -218,17 +565,109 @@ impl BamlRuntime {
         test_name: &str,
         ctx: &RuntimeContextManager,
         on_event: Option<F>,
+        cb: Option<&ClientRegistry>,
     ) -> (Result<TestResponse>, Option<uuid::Uuid>)
     where
         F: Fn(FunctionResult),
     {
-        let span = self.tracer.start_span(test_name, ctx, &Default::default());
+        self.run_test_impl(function_name, test_name, None, ctx, on_event, cb)
+            .await
+    }
+
+    /// The rows of a test with a `dataset`, `None` for other tests. Read
+    /// with the context's `baml_src` reader, or from disk without one.
+    pub async fn test_dataset(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        ctx: &RuntimeContextManager,
+    ) -> Result<Option<Vec<DatasetRow>>> {
+        let rctx = ctx.create_ctx(None, None)?;
+        let Some(path) = self
+            .inner
+            .get_test_dataset(function_name, test_name, &rctx)?
+        else {
+            return Ok(None);
+        };
+        let path_str = path.to_string_lossy();
+        let bytes = match &*rctx.baml_src {
+            Some(reader) => reader(&path_str).await,
+            #[cfg(not(target_arch = "wasm32"))]
+            None => std::fs::read(&path).map_err(anyhow::Error::from),
+            #[cfg(target_arch = "wasm32")]
+            None => Err(anyhow::anyhow!("No baml_src reader provided")),
+        }
+        .with_context(|| format!("Failed to read dataset {path_str}"))?;
+        let content =
+            String::from_utf8(bytes).with_context(|| format!("Dataset {path_str} is not UTF-8"))?;
+        parse_dataset(&content)
+            .with_context(|| format!("Failed to parse dataset {path_str}"))
+            .map(Some)
+    }
+
+    /// Runs one row of [`Self::test_dataset`], reported as its own test.
+    pub async fn run_test_row<F>(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        row: &DatasetRow,
+        ctx: &RuntimeContextManager,
+        on_event: Option<F>,
+        cb: Option<&ClientRegistry>,
+    ) -> (Result<TestResponse>, Option<uuid::Uuid>)
+    where
+        F: Fn(FunctionResult),
+    {
+        self.run_test_impl(function_name, test_name, Some(row), ctx, on_event, cb)
+            .await
+    }
+
+    async fn run_test_impl<F>(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        row: Option<&DatasetRow>,
+        ctx: &RuntimeContextManager,
+        on_event: Option<F>,
+        cb: Option<&ClientRegistry>,
+    ) -> (Result<TestResponse>, Option<uuid::Uuid>)
+    where
+        F: Fn(FunctionResult),
+    {
+        let span_name = match row {
+            Some(row) => format!("{test_name}[{}]", row.name),
+            None => test_name.to_string(),
+        };
+        let span = self.tracer.start_span(&span_name, ctx, &Default::default());
 
         let run_to_response = || async {
-            let rctx = ctx.create_ctx(None, None)?;
-            let (params, constraints) =
-                self.get_test_params_and_constraints(function_name, test_name, &rctx, true)?;
-            let rctx_stream = ctx.create_ctx(None, None)?;
+            let rctx = ctx.create_ctx(None, cb)?;
+            let (params, constraints) = match row {
+                Some(row) => (
+                    self.inner.get_test_row_params(
+                        function_name,
+                        test_name,
+                        &row.args,
+                        &rctx,
+                        true,
+                    )?,
+                    self.inner
+                        .get_test_constraints(function_name, test_name, &rctx)?,
+                ),
+                None => {
+                    self.get_test_params_and_constraints(function_name, test_name, &rctx, true)?
+                }
+            };
+            let mut expectation =
+                self.inner
+                    .get_test_expectation(function_name, test_name, &rctx)?;
+            if let Some(expected) = row.and_then(|row| row.expect.clone()) {
+                let options = expectation.map(|(_, options)| options).unwrap_or_default();
+                expectation = Some((expected, options));
+            }
+            let mut rctx_stream = ctx.create_ctx(None, cb)?;
+            rctx_stream.clock = self.clock.clone();
+            rctx_stream.http_transport = self.http_transport.clone();
             let mut stream = self.inner.stream_function_impl(
                 function_name.into(),
                 &params,
@@ -237,7 +676,7 @@ impl BamlRuntime {
                 #[cfg(not(target_arch = "wasm32"))]
                 self.async_runtime.clone(),
             )?;
-            let (response_res, span_uuid) = stream.run(on_event, ctx, None, None).await;
+            let (response_res, span_uuid) = stream.run(on_event, ctx, None, cb).await;
             let res = response_res?;
             let (_, llm_resp, _, val) = res
                 .event_chain()
@@ -265,11 +704,20 @@ impl BamlRuntime {
                     _ => TestConstraintsResult::empty(),
                 }
             };
+            let expectation_diff = match (expectation, val) {
+                (Some((expected, options)), Some(Ok(value))) => Some(diff_expectation(
+                    &expected,
+                    &BamlValue::from(value),
+                    options,
+                )),
+                _ => None,
+            };
 
             Ok(TestResponse {
                 function_response: res,
                 function_span: span_uuid,
                 constraints_result: test_constraints_result,
+                expectation_diff,
             })
         };
 
@@ -300,8 +748,9 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
     ) -> (Result<FunctionResult>, Option<uuid::Uuid>) {
-        let fut = self.call_function(function_name, params, ctx, tb, cb);
+        let fut = self.call_function(function_name, params, ctx, tb, cb, collector);
         self.async_runtime.block_on(fut)
     }
 
@@ -312,17 +761,82 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
+    ) -> (Result<FunctionResult>, Option<uuid::Uuid>) {
+        self.call_function_with_reask(function_name, params, ctx, tb, cb, collector, None)
+            .await
+    }
+
+    /// Calls `function_name` again after `previous`, with the previous answer
+    /// and what was wrong with it appended to the prompt, so the LLM can
+    /// correct it. Build `previous` with [`Reask::from_result`] from the
+    /// previous call's result.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn reask(
+        &self,
+        function_name: String,
+        params: &BamlMap<String, BamlValue>,
+        previous: Reask,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
+    ) -> (Result<FunctionResult>, Option<uuid::Uuid>) {
+        self.call_function_with_reask(
+            function_name,
+            params,
+            ctx,
+            tb,
+            cb,
+            collector,
+            Some(Arc::new(previous)),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn call_function_with_reask(
+        &self,
+        function_name: String,
+        params: &BamlMap<String, BamlValue>,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
+        reask: Option<Arc<Reask>>,
     ) -> (Result<FunctionResult>, Option<uuid::Uuid>) {
         log::trace!("Calling function: {}", function_name);
         let span = self.tracer.start_span(&function_name, ctx, params);
+        let collector_call = collector.map(|c| c.start(&function_name));
+        let metrics_call = self.metrics.as_ref().map(|m| m.start(&function_name));
         let response = match ctx.create_ctx(tb, cb) {
-            Ok(rctx) => {
+            Ok(mut rctx) => {
+                rctx.http_recorder = collector_call.as_ref().map(|c| c.http_recorder());
+                rctx.dry_run = self.dry_run;
+                rctx.hooks = self.hooks.clone();
+                rctx.clock = self.clock.clone();
+                rctx.http_transport = self.http_transport.clone();
+                rctx.reask = reask;
                 self.inner
-                    .call_function_impl(function_name, params, rctx)
+                    .call_function_impl(function_name.clone(), params, rctx)
                     .await
             }
             Err(e) => Err(e),
         };
+        if let Some(collector_call) = collector_call {
+            collector_call.finish(&response);
+        }
+        if let Some(metrics_call) = metrics_call {
+            metrics_call.finish(&response);
+        }
+        // Dry runs send no requests, so they use nothing.
+        if !self.dry_run {
+            self.usage.record(&function_name, &response);
+        }
+        let response = match response {
+            Ok(result) => self.post_process(&function_name, ctx, result).await,
+            Err(e) => Err(e),
+        };
 
         let mut target_id = None;
         if let Some(span) = span {
@@ -340,6 +854,78 @@ impl BamlRuntime {
         (response, target_id)
     }
 
+    /// Runs the post-processors of `function_name` on `result`, if it
+    /// parsed, each in a span of its own.
+    async fn post_process(
+        &self,
+        function_name: &str,
+        ctx: &RuntimeContextManager,
+        result: FunctionResult,
+    ) -> Result<FunctionResult> {
+        let processors = self.post_processors.get(function_name);
+        if processors.is_empty() {
+            return Ok(result);
+        }
+        let Some(Ok(value)) = result.result_with_constraints() else {
+            return Ok(result);
+        };
+        let mut value = value.clone();
+        let span_name = format!("{function_name}.post_process");
+        for processor in processors {
+            let params = [("value".to_string(), value.clone().value())]
+                .into_iter()
+                .collect();
+            let span = self.tracer.start_span(&span_name, ctx, &params);
+            let processed = processor.process(value);
+            if let Some(span) = span {
+                let output = processed.as_ref().ok().map(|v| v.clone().value());
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Err(e) = self.tracer.finish_span(span, ctx, output) {
+                    log::debug!("Error during logging: {}", e);
+                }
+                #[cfg(target_arch = "wasm32")]
+                if let Err(e) = self.tracer.finish_span(span, ctx, output).await {
+                    log::debug!("Error during logging: {}", e);
+                }
+            }
+            value =
+                processed.with_context(|| format!("A post-processor of {function_name} failed"))?;
+        }
+        Ok(result.with_result(value))
+    }
+
+    /// Calls `function_name` once per entry of `args`, at most
+    /// `options.max_concurrency` at a time and `options.requests_per_second`
+    /// per second. Returns a result per entry, in order: a failed call
+    /// doesn't stop the others.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn call_function_batch(
+        &self,
+        function_name: String,
+        args: &[BamlMap<String, BamlValue>],
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
+        options: &BatchOptions,
+    ) -> Vec<(Result<FunctionResult>, Option<uuid::Uuid>)> {
+        let permits = tokio::sync::Semaphore::new(options.max_concurrency.max(1));
+        let pacer = Pacer::new(options.requests_per_second);
+        let calls = args.iter().map(|params| {
+            let (function_name, permits, pacer) = (function_name.clone(), &permits, &pacer);
+            async move {
+                let _permit = permits.acquire().await.expect("never closed");
+                let wait = pacer.reserve(self.clock.instant());
+                if !wait.is_zero() {
+                    self.clock.sleep(wait).await;
+                }
+                self.call_function(function_name, params, ctx, tb, cb, collector)
+                    .await
+            }
+        });
+        futures::future::join_all(calls).await
+    }
+
     pub fn stream_function(
         &self,
         function_name: String,
@@ -347,15 +933,90 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
     ) -> Result<FunctionResultStream> {
-        self.inner.stream_function_impl(
+        let rctx = ctx.create_ctx(tb, cb)?;
+        let mut stream = self.inner.stream_function_impl(
             function_name,
             params,
             self.tracer.clone(),
-            ctx.create_ctx(tb, cb)?,
+            rctx,
             #[cfg(not(target_arch = "wasm32"))]
             self.async_runtime.clone(),
-        )
+        )?;
+        stream.collector = collector.cloned();
+        stream.metrics = self.metrics.clone();
+        stream.usage = Some(self.usage.clone());
+        stream.dry_run = self.dry_run;
+        stream.hooks = self.hooks.clone();
+        stream.clock = self.clock.clone();
+        stream.http_transport = self.http_transport.clone();
+        Ok(stream)
+    }
+
+    /// Starts a call of `function_name` that runs one step at a time, see
+    /// [`FunctionCallStepper`]. Collectors and metrics are not supported.
+    pub fn step_function(
+        &self,
+        function_name: String,
+        params: &BamlMap<String, BamlValue>,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+    ) -> Result<FunctionCallStepper> {
+        let mut rctx = ctx.create_ctx(tb, cb)?;
+        rctx.dry_run = self.dry_run;
+        rctx.hooks = self.hooks.clone();
+        rctx.clock = self.clock.clone();
+        rctx.http_transport = self.http_transport.clone();
+        self.inner.step_function_impl(function_name, params, rctx)
+    }
+
+    /// Continues a [`FunctionCallStepper`] from its checkpoint, skipping the
+    /// attempts it already made. Takes the same arguments as the call that
+    /// made the checkpoint.
+    ///
+    /// Fails if the call would use other clients now, e.g. because a
+    /// round-robin client without a `start` index picked another one.
+    pub fn resume_function(
+        &self,
+        checkpoint: CallCheckpoint,
+        params: &BamlMap<String, BamlValue>,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+    ) -> Result<FunctionCallStepper> {
+        let mut stepper =
+            self.step_function(checkpoint.function_name.clone(), params, ctx, tb, cb)?;
+        stepper.restore(checkpoint)?;
+        Ok(stepper)
+    }
+
+    /// Uploads `media` to the provider of `client_name` and returns a
+    /// reference to the uploaded file, which prompts can use like any other
+    /// media. Only OpenAI and Google AI clients support uploads.
+    pub async fn upload_media(
+        &self,
+        client_name: &str,
+        media: &BamlMedia,
+        ctx: &RuntimeContextManager,
+    ) -> Result<BamlMedia> {
+        if let BamlMediaContent::FileRef(_) = &media.content {
+            return Ok(media.clone());
+        }
+        let rctx = ctx.create_ctx(None, None)?;
+        let client_spec = internal_llm_client::ClientSpec::new_from_id(client_name)?;
+        let provider = self.inner.get_llm_provider(&client_spec, &rctx)?;
+        let LLMProvider::Primitive(provider) = provider.as_ref() else {
+            anyhow::bail!("Can't upload files to '{client_name}', pick one of its clients instead");
+        };
+        let (bytes, mime_type) = media_bytes(&rctx, media).await?;
+        let file_id = provider.upload_file(&bytes, &mime_type).await?;
+        Ok(BamlMedia::file_ref(
+            media.media_type,
+            file_id,
+            Some(mime_type),
+        ))
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -611,7 +1272,7 @@ pub fn baml_src_files(dir: &std::path::PathBuf) -> Result<Vec<PathBuf>> {
             };
             VALID_EXTENSIONS.contains(&ext)
         })
-        .map(|e| e.path().to_path_buf())
+        .map(|e| SourcePath::new(e.path()).into_path_buf())
         .collect::<Vec<_>>();
 
     if !src_files
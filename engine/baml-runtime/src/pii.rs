@@ -0,0 +1,243 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use baml_types::PiiKind;
+use internal_baml_jinja::{ChatMessagePart, RenderedPrompt};
+use regex::Regex;
+use serde::Serialize;
+
+static EMAIL: OnceLock<Regex> = OnceLock::new();
+static CREDIT_CARD: OnceLock<Regex> = OnceLock::new();
+static PHONE: OnceLock<Regex> = OnceLock::new();
+
+/// Emails go first and phone numbers last, so a card number is never read
+/// as a phone number.
+const MASK_ORDER: [PiiKind; 3] = [PiiKind::Email, PiiKind::CreditCard, PiiKind::Phone];
+
+fn pattern(kind: PiiKind) -> &'static Regex {
+    match kind {
+        PiiKind::Email => EMAIL.get_or_init(|| {
+            Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+                .unwrap()
+        }),
+        // 13 to 19 digits, optionally grouped with spaces or dashes.
+        PiiKind::CreditCard => {
+            CREDIT_CARD.get_or_init(|| Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap())
+        }
+        // NANP-style numbers, e.g. `+1 (555) 123-4567` or `555.123.4567`.
+        PiiKind::Phone => PHONE.get_or_init(|| {
+            Regex::new(r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\) ?|\b\d{3}[ .-]?)\d{3}[ .-]?\d{4}\b")
+                .unwrap()
+        }),
+    }
+}
+
+/// A value that was masked in a prompt. The value itself is not recorded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaskedSpan {
+    pub kind: PiiKind,
+    /// The chat message the value was in, `0` for completion prompts.
+    pub message: usize,
+    /// The part of the message the value was in.
+    pub part: usize,
+    /// Byte offsets into the part's text before masking.
+    pub start: usize,
+    pub end: usize,
+    /// What the value was replaced with, e.g. `[EMAIL_1]`.
+    pub token: String,
+}
+
+/// What [`mask_pii`] replaced, to put it back into the model's response.
+#[derive(Debug, Clone, Default)]
+pub struct PiiTokenMap {
+    /// Token and the value it replaced, in the order they were first seen.
+    values: Vec<(String, String)>,
+    counts: HashMap<PiiKind, usize>,
+    spans: Vec<MaskedSpan>,
+}
+
+impl PiiTokenMap {
+    pub fn spans(&self) -> &[MaskedSpan] {
+        &self.spans
+    }
+
+    /// Replaces every token in `text` with the value it masked.
+    pub fn restore(&self, text: &str) -> String {
+        self.values
+            .iter()
+            .fold(text.to_string(), |text, (token, value)| {
+                text.replace(token, value)
+            })
+    }
+
+    fn token(&mut self, kind: PiiKind, value: &str) -> String {
+        if let Some((token, _)) = self.values.iter().find(|(_, v)| v == value) {
+            return token.clone();
+        }
+        let count = self.counts.entry(kind).or_default();
+        *count += 1;
+        let token = format!("[{}_{}]", kind.to_string().to_uppercase(), count);
+        self.values.push((token.clone(), value.to_string()));
+        token
+    }
+
+    fn mask_part(
+        &mut self,
+        part: ChatMessagePart,
+        kinds: &[PiiKind],
+        message: usize,
+        index: usize,
+    ) -> ChatMessagePart {
+        match part {
+            ChatMessagePart::Text(text) => {
+                ChatMessagePart::Text(self.mask_text(&text, kinds, message, index))
+            }
+            ChatMessagePart::WithMeta(part, meta) => ChatMessagePart::WithMeta(
+                Box::new(self.mask_part(*part, kinds, message, index)),
+                meta,
+            ),
+            ChatMessagePart::Media(media) => ChatMessagePart::Media(media),
+        }
+    }
+
+    fn mask_text(&mut self, text: &str, kinds: &[PiiKind], message: usize, part: usize) -> String {
+        let mut found: Vec<(usize, usize, PiiKind)> = vec![];
+        for kind in MASK_ORDER.into_iter().filter(|k| kinds.contains(k)) {
+            for m in pattern(kind).find_iter(text) {
+                if kind == PiiKind::CreditCard && !passes_luhn(m.as_str()) {
+                    continue;
+                }
+                if found.iter().any(|(s, e, _)| m.start() < *e && *s < m.end()) {
+                    continue;
+                }
+                found.push((m.start(), m.end(), kind));
+            }
+        }
+        if found.is_empty() {
+            return text.to_string();
+        }
+        found.sort_by_key(|(start, ..)| *start);
+
+        let mut masked = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end, kind) in found {
+            let token = self.token(kind, &text[start..end]);
+            masked.push_str(&text[last..start]);
+            masked.push_str(&token);
+            last = end;
+            self.spans.push(MaskedSpan {
+                kind,
+                message,
+                part,
+                start,
+                end,
+                token,
+            });
+        }
+        masked.push_str(&text[last..]);
+        masked
+    }
+}
+
+/// Replaces the `kinds` of personal data in the text of `prompt` with tokens
+/// like `[EMAIL_1]`. A value that appears twice gets the same token.
+pub fn mask_pii(prompt: RenderedPrompt, kinds: &[PiiKind]) -> (RenderedPrompt, PiiTokenMap) {
+    let mut map = PiiTokenMap::default();
+    if kinds.is_empty() {
+        return (prompt, map);
+    }
+    let prompt = match prompt {
+        RenderedPrompt::Completion(text) => {
+            RenderedPrompt::Completion(map.mask_text(&text, kinds, 0, 0))
+        }
+        RenderedPrompt::Chat(messages) => RenderedPrompt::Chat(
+            messages
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut message)| {
+                    message.parts = std::mem::take(&mut message.parts)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(j, part)| map.mask_part(part, kinds, i, j))
+                        .collect();
+                    message
+                })
+                .collect(),
+        ),
+    };
+    (prompt, map)
+}
+
+fn passes_luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (0, _) => d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use internal_baml_jinja::RenderedChatMessage;
+
+    #[test]
+    fn masks_and_restores_pii() {
+        let prompt = RenderedPrompt::Chat(vec![RenderedChatMessage {
+            role: "user".into(),
+            allow_duplicate_role: false,
+            parts: vec![ChatMessagePart::Text(
+                "Reach greg@example.com or +1 (555) 123-4567. Card 4111 1111 1111 1111, \
+                 not 4111 1111 1111 1112. Again: greg@example.com"
+                    .into(),
+            )],
+        }]);
+
+        let (masked, map) = mask_pii(prompt, PiiKind::all());
+        let RenderedPrompt::Chat(messages) = &masked else {
+            panic!("expected a chat prompt");
+        };
+        assert_eq!(
+            messages[0].parts[0],
+            ChatMessagePart::Text(
+                "Reach [EMAIL_1] or [PHONE_1]. Card [CREDIT_CARD_1], \
+                 not 4111 1111 1111 1112. Again: [EMAIL_1]"
+                    .into()
+            )
+        );
+        assert_eq!(
+            map.spans()
+                .iter()
+                .map(|s| (s.kind, s.start, s.end))
+                .collect::<Vec<_>>(),
+            vec![
+                (PiiKind::Email, 6, 22),
+                (PiiKind::Phone, 26, 43),
+                (PiiKind::CreditCard, 50, 69),
+                (PiiKind::Email, 103, 119),
+            ]
+        );
+
+        assert_eq!(
+            map.restore(r#"{"email": "[EMAIL_1]", "card": "[CREDIT_CARD_1]"}"#),
+            r#"{"email": "greg@example.com", "card": "4111 1111 1111 1111"}"#
+        );
+    }
+
+    #[test]
+    fn only_masks_requested_kinds() {
+        let prompt = RenderedPrompt::Completion("greg@example.com 555-123-4567".into());
+        let (masked, map) = mask_pii(prompt, &[PiiKind::Phone]);
+        assert_eq!(
+            masked,
+            RenderedPrompt::Completion("greg@example.com [PHONE_1]".into())
+        );
+        assert_eq!(map.spans().len(), 1);
+    }
+}
@@ -1,4 +1,8 @@
+use std::io::Read;
+
 use anyhow::{Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use internal_llm_client::HttpOptions;
 use web_time::Duration;
 
 fn builder() -> reqwest::ClientBuilder {
@@ -27,6 +31,71 @@ pub fn create_client() -> Result<reqwest::Client> {
     builder().build().context("Failed to create reqwest client")
 }
 
+/// A client for an LLM provider, tuned by the client's `http` option.
+pub fn create_client_with_options(options: &HttpOptions) -> Result<reqwest::Client> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            // The browser manages connections.
+            let cb = builder();
+        } else {
+            let mut cb = builder();
+            if let Some(timeout) = options.connect_timeout_ms {
+                cb = cb.connect_timeout(Duration::from_millis(timeout));
+            }
+            if let Some(interval) = options.keep_alive_interval_ms {
+                cb = cb.http2_keep_alive_interval(
+                    (interval > 0).then(|| Duration::from_millis(interval)),
+                );
+            }
+            if let Some(timeout) = options.pool_idle_timeout_ms {
+                cb = cb
+                    .pool_max_idle_per_host(usize::MAX)
+                    .pool_idle_timeout(Duration::from_millis(timeout))
+                    // Keep pooled connections warm between calls.
+                    .http2_keep_alive_while_idle(true);
+            }
+        }
+    }
+
+    cb.build().context("Failed to create reqwest client")
+}
+
+/// Sets `body` as the JSON body of `req`, gzipped if `compress`.
+pub(crate) fn json_body(
+    req: reqwest::RequestBuilder,
+    body: &serde_json::Value,
+    compress: bool,
+) -> Result<reqwest::RequestBuilder> {
+    if !compress {
+        return Ok(req.json(body));
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, body)?;
+    let compressed = encoder.finish().context("Failed to gzip request body")?;
+    Ok(req
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(reqwest::header::CONTENT_ENCODING, "gzip")
+        .body(compressed))
+}
+
+/// The body of `request`, decompressed if [`json_body`] gzipped it, for
+/// logs and curl commands.
+pub(crate) fn request_body(request: &reqwest::Request) -> Option<Vec<u8>> {
+    let bytes = request.body()?.as_bytes()?;
+    let gzipped = request
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding == "gzip");
+    if !gzipped {
+        return Some(bytes.to_vec());
+    }
+    let mut decompressed = Vec::new();
+    match GzDecoder::new(bytes).read_to_end(&mut decompressed) {
+        Ok(_) => Some(decompressed),
+        Err(_) => Some(bytes.to_vec()),
+    }
+}
+
 pub(crate) fn create_tracing_client() -> Result<reqwest::Client> {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
@@ -41,3 +110,25 @@ pub(crate) fn create_tracing_client() -> Result<reqwest::Client> {
 
     cb.build().context("Failed to create reqwest client")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gzips_json_bodies() {
+        let body = serde_json::json!({"model": "gpt-4o", "messages": []});
+        let client = reqwest::Client::new();
+        let request = json_body(client.post("http://localhost"), &body, true)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["content-encoding"], "gzip");
+        let compressed = request.body().unwrap().as_bytes().unwrap();
+        assert_ne!(compressed, serde_json::to_vec(&body).unwrap());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&request_body(&request).unwrap()).unwrap(),
+            body
+        );
+    }
+}
@@ -1,7 +1,7 @@
 mod ir_features;
 pub(crate) mod runtime_interface;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
@@ -18,7 +18,7 @@ cfg_if::cfg_if!(
 use internal_baml_core::{
     internal_baml_diagnostics::{Diagnostics, SourceFile},
     ir::repr::IntermediateRepr,
-    validate,
+    validate, validate_roots,
 };
 use std::sync::Arc;
 
@@ -78,4 +78,62 @@ impl InternalBamlRuntime {
             retry_policies: Default::default(),
         })
     }
+
+    /// Composes the `baml_src` directories in `dirs` into one runtime. See
+    /// [`validate_roots`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) fn from_directories(dirs: &[PathBuf]) -> Result<Self> {
+        let roots = dirs
+            .iter()
+            .map(|dir| {
+                let contents = crate::baml_src_files(dir)?
+                    .into_iter()
+                    .map(|path| {
+                        let contents = std::fs::read_to_string(&path)
+                            .with_context(|| format!("Failed to read {}", path.display()))?;
+                        Ok(SourceFile::from((path, contents)))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((dir.clone(), contents))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut schema = validate_roots(roots);
+        schema.diagnostics.to_result()?;
+
+        let ir = IntermediateRepr::from_parser_database(&schema.db, schema.configuration)?;
+
+        Ok(Self {
+            ir: Arc::new(ir),
+            diagnostics: schema.diagnostics,
+            clients: Default::default(),
+            retry_policies: Default::default(),
+        })
+    }
+
+    /// Applies the profile named by `BAML_PROFILE`, if it's set, and adds the
+    /// profile's `env_overrides` to `env_vars`.
+    pub(super) fn apply_profile(&mut self, env_vars: &mut HashMap<String, String>) -> Result<()> {
+        let Some(name) = env_vars
+            .get("BAML_PROFILE")
+            .filter(|name| !name.is_empty())
+            .cloned()
+        else {
+            return Ok(());
+        };
+        let ir = Arc::get_mut(&mut self.ir)
+            .context("Profiles must be applied before the runtime is shared")?;
+        let env_overrides = ir
+            .apply_profile(&name)
+            .context("Failed to apply BAML_PROFILE")?;
+        env_vars.extend(env_overrides);
+        Ok(())
+    }
+
+    /// Drops the functions, clients and tests whose `@@if` doesn't hold for
+    /// `env_vars`, which already have the profile's overrides.
+    pub(super) fn apply_conditions(&mut self, env_vars: &HashMap<String, String>) -> Result<()> {
+        let ir = Arc::get_mut(&mut self.ir)
+            .context("Conditions must be applied before the runtime is shared")?;
+        ir.apply_conditions(env_vars)
+    }
 }
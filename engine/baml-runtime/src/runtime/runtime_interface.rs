@@ -5,13 +5,16 @@ use crate::internal::llm_client::traits::WithClientProperties;
 use crate::internal::llm_client::LLMResponse;
 use crate::{
     client_registry::ClientProperty,
+    expectations::ExpectOptions,
     internal::{
         ir_features::{IrFeatures, WithInternal},
         llm_client::{
             llm_provider::LLMProvider,
             orchestrator::{
-                orchestrate_call, IterOrchestrator, OrchestrationScope, OrchestratorNode,
+                confidence, orchestrate_call, self_consistency, IterOrchestrator,
+                OrchestrationScope, OrchestratorNode,
             },
+            output_tool::OutputTool,
             primitive::LLMPrimitiveProvider,
             retry_policy::CallablePolicy,
             traits::{WithPrompt, WithRenderRawCurl},
@@ -20,8 +23,8 @@ use crate::{
     },
     runtime_interface::{InternalClientLookup, RuntimeConstructor},
     tracing::BamlTracer,
-    FunctionResult, FunctionResultStream, InternalRuntimeInterface, RenderCurlSettings,
-    RuntimeContext, RuntimeInterface,
+    CancelHandle, FunctionCallStepper, FunctionResult, FunctionResultStream,
+    InternalRuntimeInterface, RenderCurlSettings, RuntimeContext, RuntimeInterface,
 };
 use anyhow::{Context, Result};
 use baml_types::{BamlMap, BamlValue, Constraint, EvaluationContext};
@@ -41,12 +44,12 @@ impl<'a> InternalClientLookup<'a> for InternalBamlRuntime {
         ctx: &RuntimeContext,
     ) -> Result<Arc<LLMProvider>> {
         match client_spec {
-            ClientSpec::Shorthand(provider, model) => {
-                let client_property = ClientProperty::from_shorthand(provider, model);
+            ClientSpec::Shorthand(provider, model, options) => {
+                let client_property = ClientProperty::from_shorthand(provider, model, options);
                 // TODO: allow other providers
                 let llm_primitive_provider =
                     LLMPrimitiveProvider::try_from((&client_property, ctx))
-                        .context(format!("Failed to parse client: {}/{}", provider, model))?;
+                        .context(format!("Failed to parse client: {}", client_property.name))?;
 
                 Ok(Arc::new(LLMProvider::Primitive(Arc::new(
                     llm_primitive_provider,
@@ -231,6 +234,17 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
         test_name: &str,
         ctx: &RuntimeContext,
         strict: bool,
+    ) -> Result<BamlMap<String, BamlValue>> {
+        self.get_test_row_params(function_name, test_name, &BamlMap::new(), ctx, strict)
+    }
+
+    fn get_test_row_params(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        row_args: &BamlMap<String, BamlValue>,
+        ctx: &RuntimeContext,
+        strict: bool,
     ) -> Result<BamlMap<String, BamlValue>> {
         let func = self.get_function(function_name, ctx)?;
         let test = self.ir().find_test(&func, test_name)?;
@@ -241,7 +255,7 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
             Ok(params) => {
                 // Collect all errors and return them as a single error.
                 let mut errors = Vec::new();
-                let params = params
+                let mut params = params
                     .into_iter()
                     .map(|(k, v)| match v {
                         Ok(v) => (k, v),
@@ -258,6 +272,7 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
                         errors
                     ));
                 }
+                params.extend(row_args.clone());
 
                 let baml_args = self.ir().check_function_params(
                     &func,
@@ -285,6 +300,41 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
         let walker = self.ir().find_test(&func, test_name)?;
         Ok(walker.item.1.elem.constraints.clone())
     }
+
+    fn get_test_dataset(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        ctx: &RuntimeContext,
+    ) -> Result<Option<std::path::PathBuf>> {
+        let func = self.get_function(function_name, ctx)?;
+        let walker = self.ir().find_test(&func, test_name)?;
+        Ok(walker.item.1.elem.dataset.clone())
+    }
+
+    fn get_test_expectation(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        ctx: &RuntimeContext,
+    ) -> Result<Option<(BamlValue, ExpectOptions)>> {
+        let func = self.get_function(function_name, ctx)?;
+        let walker = self.ir().find_test(&func, test_name)?;
+        let Some(expect) = &walker.item.1.elem.expect else {
+            return Ok(None);
+        };
+        let value = expect
+            .value
+            .resolve_serde::<BamlValue>(&ctx.eval_ctx(true))
+            .context("Unable to resolve @@expect value")?;
+        Ok(Some((
+            value,
+            ExpectOptions {
+                float_tolerance: expect.float_tolerance,
+                unordered_lists: expect.unordered_lists,
+            },
+        )))
+    }
 }
 
 impl RuntimeConstructor for InternalBamlRuntime {
@@ -328,7 +378,7 @@ impl RuntimeInterface for InternalBamlRuntime {
         &self,
         function_name: String,
         params: &BamlMap<String, BamlValue>,
-        ctx: RuntimeContext,
+        mut ctx: RuntimeContext,
     ) -> Result<crate::FunctionResult> {
         let func = match self.get_function(&function_name, &ctx) {
             Ok(func) => func,
@@ -373,17 +423,72 @@ impl RuntimeInterface for InternalBamlRuntime {
         //     }
         // };
 
+        ctx.request_logprobs = func.confidence().is_some();
+        if ctx.class_override.is_empty() && ctx.enum_overrides.is_empty() {
+            ctx.output_tool = Some(OutputTool::new(self.ir(), func.name(), func.output()));
+        }
         let renderer = PromptRenderer::from_function(&func, self.ir(), &ctx)?;
+
+        if let Some(voting) = func.self_consistency().filter(|_| !ctx.dry_run) {
+            let calls = (0..voting.samples).map(|_| async {
+                let orchestrator = self.orchestration_graph(renderer.client_spec(), &ctx)?;
+                let (history, _, pii_audit, _) =
+                    orchestrate_call(orchestrator, self.ir(), &ctx, &renderer, &baml_args, |s| {
+                        renderer.parse(s, false)
+                    })
+                    .await;
+                anyhow::Ok(FunctionResult::new_chain(history)?.with_pii_audit(pii_audit))
+            });
+            let samples = futures::future::join_all(calls)
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>>>()?;
+            return self_consistency::combine(voting, samples);
+        }
+
         let orchestrator = self.orchestration_graph(renderer.client_spec(), &ctx)?;
 
         // Now actually execute the code.
-        let (history, _) =
+        let (history, _, pii_audit, dry_run_request) =
             orchestrate_call(orchestrator, self.ir(), &ctx, &renderer, &baml_args, |s| {
                 renderer.parse(s, false)
             })
             .await;
 
-        FunctionResult::new_chain(history)
+        let result = FunctionResult::new_chain(history)?
+            .with_pii_audit(pii_audit)
+            .with_dry_run_request(dry_run_request);
+
+        let Some(confidence) = func.confidence() else {
+            return Ok(result);
+        };
+        let Some(chosen) = confidence::chosen_value(&result) else {
+            return Ok(result);
+        };
+        let variants = confidence::variants(self.ir(), func.output(), &ctx);
+        let mut scores = result
+            .logprobs()
+            .and_then(|logprobs| confidence::from_logprobs(&variants, logprobs, &chosen));
+        if let (None, Some(samples)) = (&scores, confidence.samples) {
+            // The client can't return logprobs, so ask again and count the answers.
+            let calls = (1..samples).map(|_| async {
+                let orchestrator = self.orchestration_graph(renderer.client_spec(), &ctx)?;
+                let (history, ..) =
+                    orchestrate_call(orchestrator, self.ir(), &ctx, &renderer, &baml_args, |s| {
+                        renderer.parse(s, false)
+                    })
+                    .await;
+                anyhow::Ok(FunctionResult::new_chain(history)?)
+            });
+            let mut answers = vec![chosen];
+            // Samples that failed aren't counted.
+            for sample in futures::future::join_all(calls).await {
+                answers.extend(sample.ok().as_ref().and_then(confidence::chosen_value));
+            }
+            scores = Some(confidence::from_samples(&variants, &answers));
+        }
+
+        Ok(result.with_confidence(scores))
     }
 
     fn stream_function_impl(
@@ -411,6 +516,7 @@ impl RuntimeInterface for InternalBamlRuntime {
         else {
             anyhow::bail!("Expected parameters to be a map for: {}", function_name);
         };
+        let (cancel, cancel_registration) = CancelHandle::new_pair();
         Ok(FunctionResultStream {
             function_name,
             ir: self.ir.clone(),
@@ -418,8 +524,44 @@ impl RuntimeInterface for InternalBamlRuntime {
             orchestrator,
             tracer,
             renderer,
+            collector: None,
+            metrics: None,
+            usage: None,
+            dry_run: false,
+            hooks: Default::default(),
+            clock: ctx.clock.clone(),
+            http_transport: ctx.http_transport.clone(),
+            cancel,
+            cancel_registration,
             #[cfg(not(target_arch = "wasm32"))]
             tokio_runtime,
         })
     }
+
+    fn step_function_impl(
+        &self,
+        function_name: String,
+        params: &BamlMap<String, BamlValue>,
+        ctx: RuntimeContext,
+    ) -> Result<FunctionCallStepper> {
+        let func = self.get_function(&function_name, &ctx)?;
+        let renderer = PromptRenderer::from_function(&func, self.ir(), &ctx)?;
+        let orchestrator = self.orchestration_graph(renderer.client_spec(), &ctx)?;
+        let baml_args = self.ir().check_function_params(
+            &func,
+            params,
+            ArgCoercer {
+                span_path: None,
+                allow_implicit_cast_to_string: false,
+            },
+        )?;
+        Ok(FunctionCallStepper::new(
+            function_name,
+            baml_args,
+            renderer,
+            self.ir.clone(),
+            orchestrator,
+            ctx,
+        ))
+    }
 }
@@ -6,6 +6,7 @@ use internal_baml_jinja::RenderedPrompt;
 use internal_llm_client::{AllowedRoleMetadata, ClientSpec};
 use std::{collections::HashMap, sync::Arc};
 
+use crate::expectations::ExpectOptions;
 use crate::internal::llm_client::llm_provider::LLMProvider;
 use crate::internal::llm_client::orchestrator::{OrchestrationScope, OrchestratorNode};
 use crate::tracing::{BamlTracer, TracingSpan};
@@ -13,7 +14,7 @@ use crate::types::on_log_event::LogEventCallbackSync;
 use crate::{
     internal::{ir_features::IrFeatures, llm_client::retry_policy::CallablePolicy},
     runtime::InternalBamlRuntime,
-    types::FunctionResultStream,
+    types::{FunctionCallStepper, FunctionResultStream},
     FunctionResult, RuntimeContext,
 };
 use crate::{RenderCurlSettings, RuntimeContextManager};
@@ -46,6 +47,13 @@ pub trait RuntimeInterface {
         ctx: RuntimeContext,
         #[cfg(not(target_arch = "wasm32"))] tokio_runtime: Arc<tokio::runtime::Runtime>,
     ) -> Result<FunctionResultStream>;
+
+    fn step_function_impl(
+        &self,
+        function_name: String,
+        params: &BamlMap<String, BamlValue>,
+        ctx: RuntimeContext,
+    ) -> Result<FunctionCallStepper>;
 }
 
 //
@@ -160,10 +168,37 @@ pub trait InternalRuntimeInterface {
         strict: bool,
     ) -> Result<BamlMap<String, BamlValue>>;
 
+    /// Like `get_test_params`, with `row_args` from a dataset row replacing
+    /// the test's own args.
+    fn get_test_row_params(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        row_args: &BamlMap<String, BamlValue>,
+        ctx: &RuntimeContext,
+        strict: bool,
+    ) -> Result<BamlMap<String, BamlValue>>;
+
+    /// The test's `dataset` file, if it has one.
+    fn get_test_dataset(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        ctx: &RuntimeContext,
+    ) -> Result<Option<std::path::PathBuf>>;
+
     fn get_test_constraints(
         &self,
         function_name: &str,
         test_name: &str,
         ctx: &RuntimeContext,
     ) -> Result<Vec<Constraint>>;
+
+    /// The resolved `@@expect` value of a test, if it has one.
+    fn get_test_expectation(
+        &self,
+        function_name: &str,
+        test_name: &str,
+        ctx: &RuntimeContext,
+    ) -> Result<Option<(BamlValue, ExpectOptions)>>;
 }
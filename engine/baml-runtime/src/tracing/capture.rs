@@ -0,0 +1,192 @@
+use colored::Colorize;
+use internal_baml_core::{internal_baml_parser_database::TraceCapture, ir::repr::TraceSettings};
+use sha2::{Digest, Sha256};
+
+use super::api_wrapper::core_types::{
+    IOValue, LLMEventSchema, LogSchema, MetadataType, Template, ValueType,
+};
+use crate::{internal::llm_client::LLMResponse, FunctionResult};
+
+/// What a call's content is replaced with when `@@trace` doesn't capture it.
+const NOT_CAPTURED: &str = "[not captured]";
+
+/// Whether to trace this call of a function with `settings`.
+pub(super) fn sampled(settings: &TraceSettings) -> bool {
+    settings.sample >= 1.0 || fastrand::f64() < settings.sample
+}
+
+/// `text` as `capture` records it: as is, hashed, or not at all.
+pub(super) fn capture_text(capture: TraceCapture, text: &str) -> Option<String> {
+    match capture {
+        TraceCapture::Full => Some(text.to_string()),
+        TraceCapture::Hashed => {
+            let hash = Sha256::digest(text.as_bytes());
+            let hex = hash.iter().map(|b| format!("{b:02x}")).collect::<String>();
+            Some(format!("sha256:{hex}"))
+        }
+        TraceCapture::MetadataOnly => None,
+    }
+}
+
+fn capture_required(capture: TraceCapture, text: &str) -> String {
+    capture_text(capture, text).unwrap_or_else(|| NOT_CAPTURED.to_string())
+}
+
+/// Removes what `capture` doesn't record from an event before it's exported.
+pub(super) fn redact_log_schema(schema: &mut LogSchema, capture: TraceCapture) {
+    if capture == TraceCapture::Full {
+        return;
+    }
+    for io in [&mut schema.io.input, &mut schema.io.output] {
+        *io = io.take().and_then(|value| redact_io_value(value, capture));
+    }
+    if let Some(error) = &mut schema.error {
+        error.message = capture_required(capture, &error.message);
+        error.traceback = None;
+    }
+    match &mut schema.metadata {
+        Some(MetadataType::Single(event)) => redact_llm_event(event, capture),
+        Some(MetadataType::Multi(events)) => {
+            for event in events {
+                redact_llm_event(event, capture);
+            }
+        }
+        None => {}
+    }
+}
+
+fn redact_io_value(mut value: IOValue, capture: TraceCapture) -> Option<IOValue> {
+    value.value = match value.value {
+        ValueType::String(text) => ValueType::String(capture_text(capture, &text)?),
+        ValueType::List(items) => ValueType::List(
+            items
+                .iter()
+                .map(|item| capture_text(capture, item))
+                .collect::<Option<_>>()?,
+        ),
+    };
+    Some(value)
+}
+
+fn redact_llm_event(event: &mut LLMEventSchema, capture: TraceCapture) {
+    let prompt = match &event.input.prompt.template {
+        Template::Single(text) => text.clone(),
+        Template::Multiple(chat) => serde_json::to_string(chat).unwrap_or_default(),
+    };
+    event.input.prompt.template = Template::Single(capture_required(capture, &prompt));
+    event.input.prompt.template_args.clear();
+    if let Some(output) = &mut event.output {
+        output.raw_text = capture_required(capture, &output.raw_text);
+        output.metadata.logprobs = None;
+    }
+    if let Some(error) = &mut event.error {
+        *error = capture_required(capture, error);
+    }
+}
+
+/// The `baml_events` log line of a call whose content isn't captured in
+/// full: the client, timing and outcome, with the prompt and reply as
+/// `capture` records them.
+pub(super) fn visualize(response: &FunctionResult, capture: TraceCapture) -> String {
+    let mut s = vec![];
+    if response.event_chain().len() > 1 {
+        s.push(format!(
+            "{}",
+            format!(
+                "({} other previous tries)",
+                response.event_chain().len() - 1
+            )
+            .yellow()
+        ));
+    }
+    let (prompt, reply) = match response.llm_response() {
+        LLMResponse::Success(r) => {
+            s.push(format!(
+                "{}",
+                format!(
+                    "Client: {} ({}) - {}ms. StopReason: {}. Tokens(in/out): {}/{}",
+                    r.client,
+                    r.model,
+                    r.latency.as_millis(),
+                    r.metadata.finish_reason.as_deref().unwrap_or("unknown"),
+                    r.metadata
+                        .prompt_tokens
+                        .map_or_else(|| "unknown".to_string(), |t| t.to_string()),
+                    r.metadata
+                        .output_tokens
+                        .map_or_else(|| "unknown".to_string(), |t| t.to_string()),
+                )
+                .yellow()
+            ));
+            (r.prompt.to_string(), Some(&r.content))
+        }
+        LLMResponse::LLMFailure(r) => {
+            s.push(format!(
+                "{}",
+                format!(
+                    "Client: {} ({}) - {}ms",
+                    r.client,
+                    r.model.as_deref().unwrap_or("<unknown>"),
+                    r.latency.as_millis(),
+                )
+                .yellow()
+            ));
+            s.push(format!("{}", format!("---ERROR ({})---", r.code).red()));
+            (r.prompt.to_string(), None)
+        }
+        LLMResponse::UserFailure(_) | LLMResponse::InternalFailure(_) => {
+            s.push(format!("{}", "Failed before LLM call".red()));
+            return s.join("\n");
+        }
+    };
+    if capture != TraceCapture::MetadataOnly {
+        s.push(format!("{}", "---PROMPT---".blue()));
+        s.push(format!("{}", capture_required(capture, &prompt).dimmed()));
+        if let Some(reply) = reply {
+            s.push(format!("{}", "---LLM REPLY---".blue()));
+            s.push(format!("{}", capture_required(capture, reply).dimmed()));
+        }
+    }
+    match response.result_with_constraints() {
+        Some(Ok(value)) => s.push(format!(
+            "{}",
+            format!("---Parsed Response ({})---", value.r#type()).blue()
+        )),
+        Some(Err(_)) => s.push(format!(
+            "{}",
+            format!("---Parsed Response ({})---", "Error".red()).blue()
+        )),
+        None => {}
+    }
+    s.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_text() {
+        assert_eq!(
+            capture_text(TraceCapture::Full, "hello").as_deref(),
+            Some("hello")
+        );
+        assert_eq!(
+            capture_text(TraceCapture::Hashed, "hello").as_deref(),
+            Some("sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+        assert_eq!(capture_text(TraceCapture::MetadataOnly, "hello"), None);
+    }
+
+    #[test]
+    fn samples_calls() {
+        let settings = |sample| TraceSettings {
+            sample,
+            capture: TraceCapture::Full,
+        };
+        assert!(sampled(&settings(1.0)));
+        assert!(!sampled(&settings(0.0)));
+        let traced = (0..1000).filter(|_| sampled(&settings(0.5))).count();
+        assert!((300..700).contains(&traced), "{traced}");
+    }
+}
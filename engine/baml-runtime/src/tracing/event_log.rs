@@ -0,0 +1,187 @@
+//! Appends a JSON line per BAML function call to the file at
+//! `BAML_EVENT_LOG`, e.g. to build eval datasets from production traffic.
+
+use std::{collections::HashMap, fs::File, io::Write, sync::Mutex};
+
+use anyhow::{Context, Result};
+use baml_types::BamlValue;
+use internal_baml_core::internal_baml_parser_database::TraceCapture;
+use serde_json::{json, Value};
+
+use super::{capture, to_iso_string, TracingSpan};
+use crate::{internal::llm_client::LLMResponse, FunctionResult, SpanCtx};
+
+pub(super) struct EventLog {
+    file: Mutex<File>,
+}
+
+impl EventLog {
+    /// `None` unless `BAML_EVENT_LOG` is set. The file is created if it
+    /// doesn't exist, and appended to if it does.
+    pub(super) fn from_env_vars(env_vars: &HashMap<String, String>) -> Result<Option<Self>> {
+        let Some(path) = env_vars
+            .get("BAML_EVENT_LOG")
+            .filter(|path| !path.is_empty())
+        else {
+            return Ok(None);
+        };
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open BAML_EVENT_LOG at {path}"))?;
+        Ok(Some(Self {
+            file: Mutex::new(file),
+        }))
+    }
+
+    pub(super) fn record(
+        &self,
+        span: &TracingSpan,
+        event_chain: &[SpanCtx],
+        tags: &HashMap<String, BamlValue>,
+        response: &Result<FunctionResult>,
+        capture: TraceCapture,
+    ) {
+        let entry = entry(
+            span,
+            event_chain,
+            tags,
+            response,
+            capture,
+            web_time::SystemTime::now(),
+        );
+        let mut line = entry.to_string();
+        line.push('\n');
+        // One write per line, so lines of concurrent calls don't interleave.
+        if let Err(e) = self.file.lock().unwrap().write_all(line.as_bytes()) {
+            log::warn!("Failed to write to BAML_EVENT_LOG: {e}");
+        }
+    }
+}
+
+/// `value` as `capture` records it. Hashed values are hashed as JSON.
+fn captured(capture: TraceCapture, value: Value) -> Value {
+    match capture {
+        TraceCapture::Full => value,
+        _ => capture::capture_text(capture, &value.to_string()).map_or(Value::Null, Value::String),
+    }
+}
+
+fn entry(
+    span: &TracingSpan,
+    event_chain: &[SpanCtx],
+    tags: &HashMap<String, BamlValue>,
+    response: &Result<FunctionResult>,
+    capture: TraceCapture,
+    end_time: web_time::SystemTime,
+) -> Value {
+    let latency_ms = end_time
+        .duration_since(span.start_time)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let mut entry = json!({
+        "function": event_chain.last().map(|s| s.name.as_str()),
+        "span_id": event_chain.last().map(|s| s.span_id.to_string()),
+        "root_span_id": event_chain.first().map(|s| s.span_id.to_string()),
+        "start_time": to_iso_string(&span.start_time),
+        "latency_ms": latency_ms,
+        "tags": tags,
+        "args": captured(capture, json!(span.params)),
+    });
+
+    let result = match response {
+        Ok(result) => result,
+        Err(e) => {
+            entry["error"] = captured(capture, json!(format!("{e:#}")));
+            return entry;
+        }
+    };
+    let (mut input_tokens, mut output_tokens) = (None::<u64>, None::<u64>);
+    for (_, response, _, _) in result.event_chain() {
+        if let LLMResponse::Success(response) = response {
+            if let Some(tokens) = response.metadata.prompt_tokens {
+                *input_tokens.get_or_insert(0) += tokens;
+            }
+            if let Some(tokens) = response.metadata.output_tokens {
+                *output_tokens.get_or_insert(0) += tokens;
+            }
+        }
+    }
+    entry["attempts"] = json!(result.event_chain().len());
+    entry["input_tokens"] = json!(input_tokens);
+    entry["output_tokens"] = json!(output_tokens);
+
+    match result.llm_response() {
+        LLMResponse::Success(response) => {
+            entry["client"] = json!(response.client);
+            entry["model"] = json!(response.model);
+            entry["prompt"] = captured(capture, json!(response.prompt));
+            entry["raw_response"] = captured(capture, json!(response.content));
+        }
+        LLMResponse::LLMFailure(error) => {
+            entry["client"] = json!(error.client);
+            entry["model"] = json!(error.model);
+            entry["prompt"] = captured(capture, json!(error.prompt));
+            entry["error"] = captured(capture, json!(error.message));
+        }
+        LLMResponse::UserFailure(message) | LLMResponse::InternalFailure(message) => {
+            entry["error"] = captured(capture, json!(message));
+        }
+    }
+    match result.result_with_constraints() {
+        Some(Ok(value)) => entry["parsed"] = captured(capture, json!(value)),
+        Some(Err(e)) => entry["error"] = captured(capture, json!(format!("{e:#}"))),
+        None => {}
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use web_time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn entry_of_failed_call() {
+        let start_time = web_time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let span = TracingSpan {
+            span_id: uuid::Uuid::nil(),
+            params: [("text".to_string(), BamlValue::String("hi".into()))]
+                .into_iter()
+                .collect(),
+            start_time,
+            trace_parent: None,
+        };
+        let event_chain = [SpanCtx {
+            span_id: uuid::Uuid::nil(),
+            name: "Summarize".to_string(),
+        }];
+        let response = Err(anyhow::anyhow!("no API key"));
+
+        let full = entry(
+            &span,
+            &event_chain,
+            &HashMap::new(),
+            &response,
+            TraceCapture::Full,
+            start_time + Duration::from_millis(1500),
+        );
+        assert_eq!(full["function"], "Summarize");
+        assert_eq!(full["latency_ms"], 1500);
+        assert_eq!(full["args"], json!({"text": "hi"}));
+        assert_eq!(full["error"], "no API key");
+
+        let metadata_only = entry(
+            &span,
+            &event_chain,
+            &HashMap::new(),
+            &response,
+            TraceCapture::MetadataOnly,
+            start_time,
+        );
+        assert_eq!(metadata_only["args"], Value::Null);
+        assert_eq!(metadata_only["error"], Value::Null);
+    }
+}
@@ -1,4 +1,12 @@
 pub mod api_wrapper;
+mod capture;
+#[cfg(not(target_arch = "wasm32"))]
+mod event_log;
+#[cfg(all(feature = "otel", not(target_arch = "wasm32")))]
+mod otel;
+mod trace_parent;
+
+pub use trace_parent::TraceParent;
 
 use crate::on_log_event::LogEventCallbackSync;
 use crate::InnerTraceStats;
@@ -6,6 +14,10 @@ use anyhow::{Context, Result};
 use baml_types::{BamlMap, BamlMediaType, BamlValue};
 use cfg_if::cfg_if;
 use colored::{ColoredString, Colorize};
+use internal_baml_core::{
+    internal_baml_parser_database::TraceCapture,
+    ir::repr::{IntermediateRepr, TraceSettings},
+};
 use internal_baml_jinja::RenderedPrompt;
 use serde::Serialize;
 use std::collections::HashMap;
@@ -14,7 +26,7 @@ use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 use crate::{
-    client_registry::ClientRegistry, internal::llm_client::LLMResponse,
+    client_registry::ClientRegistry, errors::ExposedError, internal::llm_client::LLMResponse,
     tracing::api_wrapper::core_types::Role, type_builder::TypeBuilder, FunctionResult,
     RuntimeContext, RuntimeContextManager, SpanCtx, TestResponse, TraceStats,
 };
@@ -45,12 +57,31 @@ pub struct TracingSpan {
     span_id: Uuid,
     params: BamlMap<String, BamlValue>,
     start_time: web_time::SystemTime,
+    /// The application's trace the span is in, see
+    /// [`RuntimeContextManager::set_trace_parent`].
+    trace_parent: Option<TraceParent>,
+}
+
+impl TracingSpan {
+    /// The application's trace, as a child of this span, to propagate to
+    /// what the span calls. `None` unless a trace parent was set.
+    pub fn trace_parent(&self) -> Option<TraceParent> {
+        self.trace_parent
+            .map(|trace_parent| trace_parent.child(self.span_id))
+    }
 }
 
 pub struct BamlTracer {
     options: APIWrapper,
     tracer: Option<TracerImpl>,
     trace_stats: TraceStats,
+    /// The `@@trace` of each function that has one, by function name.
+    trace_settings: HashMap<String, TraceSettings>,
+    #[cfg(all(feature = "otel", not(target_arch = "wasm32")))]
+    otel: Option<otel::OtelExporter>,
+    /// Set with `BAML_EVENT_LOG`.
+    #[cfg(not(target_arch = "wasm32"))]
+    event_log: Option<event_log::EventLog>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -191,10 +222,19 @@ impl BamlTracer {
         options: Option<APIWrapper>,
         env_vars: impl Iterator<Item = (T, T)>,
     ) -> Result<Self> {
+        let env_vars = env_vars
+            .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+            .collect::<HashMap<_, _>>();
         let options = match options {
             Some(wrapper) => wrapper,
-            None => APIWrapper::from_env_vars(env_vars)?,
+            None => APIWrapper::from_env_vars(env_vars.iter())?,
         };
+        #[cfg(not(all(feature = "otel", not(target_arch = "wasm32"))))]
+        if env_vars.contains_key("BAML_OTEL_EXPORTER_OTLP_ENDPOINT") {
+            log::warn!(
+                "BAML_OTEL_EXPORTER_OTLP_ENDPOINT is set, but exporting spans needs baml-runtime's `otel` feature"
+            );
+        }
 
         let trace_stats = TraceStats::default();
 
@@ -206,10 +246,36 @@ impl BamlTracer {
             },
             options,
             trace_stats,
+            trace_settings: HashMap::new(),
+            #[cfg(all(feature = "otel", not(target_arch = "wasm32")))]
+            otel: otel::OtelExporter::from_env_vars(&env_vars)?,
+            #[cfg(not(target_arch = "wasm32"))]
+            event_log: event_log::EventLog::from_env_vars(&env_vars)?,
         };
         Ok(tracer)
     }
 
+    /// Applies the `@@trace` of the functions in `ir` to their calls.
+    pub(crate) fn with_trace_settings(mut self, ir: &IntermediateRepr) -> Self {
+        self.trace_settings = ir
+            .walk_functions()
+            .filter_map(|f| Some((f.name().to_string(), f.trace()?.clone())))
+            .collect();
+        self
+    }
+
+    /// What to record of a call, or `None` if it isn't sampled.
+    fn capture_for(&self, event_chain: &[SpanCtx]) -> Option<TraceCapture> {
+        let settings = event_chain
+            .last()
+            .and_then(|ctx| self.trace_settings.get(&ctx.name));
+        match settings {
+            Some(settings) if !capture::sampled(settings) => None,
+            Some(settings) => Some(settings.capture),
+            None => Some(TraceCapture::Full),
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn set_log_event_callback(&self, log_event_callback: Option<LogEventCallbackSync>) {
         if let Some(tracer) = &self.tracer {
@@ -221,6 +287,10 @@ impl BamlTracer {
         if let Some(ref tracer) = self.tracer {
             tracer.flush().context("Failed to flush BAML traces")?;
         }
+        #[cfg(all(feature = "otel", not(target_arch = "wasm32")))]
+        if let Some(otel) = &self.otel {
+            otel.flush()?;
+        }
 
         Ok(())
     }
@@ -242,6 +312,7 @@ impl BamlTracer {
             span_id,
             params: params.clone(),
             start_time: web_time::SystemTime::now(),
+            trace_parent: ctx.trace_parent(),
         };
 
         Some(span)
@@ -306,6 +377,11 @@ impl BamlTracer {
             anyhow::bail!("Span ID mismatch: {} != {}", span.span_id, span_id);
         }
 
+        #[cfg(feature = "otel")]
+        if let Some(otel) = &self.otel {
+            otel.export_span(&span, &event_chain, &tags);
+        }
+
         if let Some(tracer) = &self.tracer {
             tracer.submit(response.to_log_schema(&self.options, event_chain, tags, span))?;
             guard.finalize();
@@ -332,25 +408,24 @@ impl BamlTracer {
             anyhow::bail!("Span ID mismatch: {} != {}", span.span_id, span_id);
         }
 
+        let Some(capture) = self.capture_for(&event_chain) else {
+            guard.done();
+            return Ok(None);
+        };
+
         if let Ok(response) = &response {
             let name = event_chain.last().map(|s| s.name.as_str());
             let is_ok = response
                 .result_with_constraints()
                 .as_ref()
                 .is_some_and(|r| r.is_ok());
-            log::log!(
-                target: "baml_events",
-                if is_ok { log::Level::Info } else { log::Level::Warn },
-                "{}{}",
-                name.map(|s| format!("Function {}:\n", s)).unwrap_or_default().purple(),
-                response.visualize(self.options.config.max_log_chunk_chars())
-            );
+            log_simple_event(is_ok, name, response, &self.options, capture);
         }
 
         if let Some(tracer) = &self.tracer {
-            tracer
-                .submit(response.to_log_schema(&self.options, event_chain, tags, span))
-                .await?;
+            let mut log_schema = response.to_log_schema(&self.options, event_chain, tags, span);
+            capture::redact_log_schema(&mut log_schema, capture);
+            tracer.submit(log_schema).await?;
             guard.done();
             Ok(Some(span_id))
         } else {
@@ -382,17 +457,32 @@ impl BamlTracer {
             anyhow::bail!("Span ID mismatch: {} != {}", span.span_id, span_id);
         }
 
+        let Some(capture) = self.capture_for(&event_chain) else {
+            guard.done();
+            return Ok(None);
+        };
+
+        #[cfg(feature = "otel")]
+        if let Some(otel) = &self.otel {
+            otel.export_function(&span, &event_chain, &tags, response, capture);
+        }
+        if let Some(event_log) = &self.event_log {
+            event_log.record(&span, &event_chain, &tags, response, capture);
+        }
+
         let log_json = Self::is_json_logging_enabled();
 
         match response {
             Ok(response) => {
-                self.handle_ok_response(response, log_json, &event_chain, &tags, &span)?
+                self.handle_ok_response(response, log_json, &event_chain, &tags, &span, capture)?
             }
-            Err(e) => self.handle_error_response(e, log_json, &span),
+            Err(e) => self.handle_error_response(e, log_json, &span, capture),
         }
 
         if let Some(tracer) = &self.tracer {
-            tracer.submit(response.to_log_schema(&self.options, event_chain, tags, span))?;
+            let mut log_schema = response.to_log_schema(&self.options, event_chain, tags, span);
+            capture::redact_log_schema(&mut log_schema, capture);
+            tracer.submit(log_schema)?;
             guard.finalize();
             Ok(Some(span_id))
         } else {
@@ -415,6 +505,7 @@ impl BamlTracer {
         event_chain: &[SpanCtx],
         tags: &HashMap<String, BamlValue>,
         span: &TracingSpan,
+        capture: TraceCapture,
     ) -> Result<()> {
         let name = event_chain.last().map(|s| s.name.as_str());
         let is_ok = response
@@ -430,16 +521,22 @@ impl BamlTracer {
         );
 
         if log_json {
-            let log_event = self.build_baml_event_json(response, span);
+            let log_event = self.build_baml_event_json(response, span, capture);
             log_json_event(is_ok, log_event)?;
         } else {
-            log_simple_event(is_ok, name, response, &self.options);
+            log_simple_event(is_ok, name, response, &self.options, capture);
         }
 
         Ok(())
     }
 
-    fn handle_error_response(&self, error: &anyhow::Error, log_json: bool, span: &TracingSpan) {
+    fn handle_error_response(
+        &self,
+        error: &anyhow::Error,
+        log_json: bool,
+        span: &TracingSpan,
+        capture: TraceCapture,
+    ) {
         if log_json {
             let baml_event_json = BamlEventJson {
                 start_time: to_iso_string(&span.start_time),
@@ -455,15 +552,17 @@ impl BamlTracer {
                 tokens: None,
                 parsed_response_type: None,
                 parsed_response: None,
-                error: Some(error.to_string()),
+                error: capture::capture_text(capture, &error.to_string()),
             };
             rust_tracing::event!(
                 target: "baml_events",
                 rust_tracing::Level::ERROR,
                 baml_event = baml_event_json.as_value()
             );
-        } else {
+        } else if let Some(error) = capture::capture_text(capture, &error.to_string()) {
             log::error!("{}", error);
+        } else {
+            log::error!("Function failed");
         }
     }
 
@@ -471,6 +570,26 @@ impl BamlTracer {
         &self,
         response: &FunctionResult,
         span: &TracingSpan,
+        capture: TraceCapture,
+    ) -> BamlEventJson {
+        let mut event = self.build_full_baml_event_json(response, span);
+        for content in [
+            &mut event.prompt,
+            &mut event.llm_reply,
+            &mut event.parsed_response,
+            &mut event.error,
+        ] {
+            *content = content
+                .take()
+                .and_then(|text| capture::capture_text(capture, &text));
+        }
+        event
+    }
+
+    fn build_full_baml_event_json(
+        &self,
+        response: &FunctionResult,
+        span: &TracingSpan,
     ) -> BamlEventJson {
         let last_ctx = response.llm_response();
         let start_time = to_iso_string(&span.start_time);
@@ -569,13 +688,18 @@ fn log_simple_event(
     name: Option<&str>,
     response: &FunctionResult,
     options: &APIWrapper,
+    capture: TraceCapture,
 ) {
+    let visualized = match capture {
+        TraceCapture::Full => response.visualize(options.config.max_log_chunk_chars()),
+        _ => capture::visualize(response, capture),
+    };
     log::log!(
         target: "baml_events",
         if is_ok { log::Level::Info } else { log::Level::Warn },
         "{}{}",
         name.map(|s| format!("Function {}:\n", s)).unwrap_or_default().purple(),
-        response.visualize(options.config.max_log_chunk_chars())
+        visualized
     );
 }
 
@@ -679,6 +803,10 @@ impl From<&BamlValue> for IOValue {
     }
 }
 
+/// The error code of spans whose call was cancelled, e.g. with a stream's
+/// `cancel()`. Other failures use 2.
+const CANCELLED_ERROR_CODE: i32 = 3;
+
 fn error_from_result(result: &FunctionResult) -> Option<api_wrapper::core_types::Error> {
     match result.result_with_constraints() {
         Some(Ok(_)) => None,
@@ -745,7 +873,10 @@ impl<T: ToLogSchema> ToLogSchema for Result<T> {
                     output: None,
                 },
                 error: Some(api_wrapper::core_types::Error {
-                    code: 2,
+                    code: match e.downcast_ref::<ExposedError>() {
+                        Some(ExposedError::Cancelled) => CANCELLED_ERROR_CODE,
+                        _ => 2,
+                    },
                     message: e.to_string(),
                     traceback: None,
                     r#override: None,
@@ -955,6 +1086,12 @@ impl From<&internal_baml_jinja::ChatMessagePart> for ContentPart {
                     (BamlMediaType::Audio, baml_types::BamlMediaContent::Url(data)) => {
                         ContentPart::UrlAudio(data.url.clone())
                     }
+                    (BamlMediaType::Image, baml_types::BamlMediaContent::FileRef(data)) => {
+                        ContentPart::UrlImage(data.file_id.clone())
+                    }
+                    (BamlMediaType::Audio, baml_types::BamlMediaContent::FileRef(data)) => {
+                        ContentPart::UrlAudio(data.file_id.clone())
+                    }
                 }
             }
             internal_baml_jinja::ChatMessagePart::WithMeta(inner, meta) => ContentPart::WithMeta(
@@ -0,0 +1,318 @@
+//! Exports spans over OTLP, to any OpenTelemetry collector, when
+//! `BAML_OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+//!
+//! Span ids are those of the BAML spans, so spans of `@trace`d functions and
+//! the BAML functions they call make up one trace.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use baml_types::BamlValue;
+use internal_baml_core::internal_baml_parser_database::TraceCapture;
+use opentelemetry::{
+    trace::{
+        Span as _, SpanBuilder, SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceFlags,
+        TraceId, TraceState, Tracer as _, TracerProvider as _,
+    },
+    Context as OtelContext, KeyValue, StringValue, Value,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    trace::{Tracer, TracerProvider},
+    Resource,
+};
+use uuid::Uuid;
+
+use super::{capture, trace_parent::span_id_of, TracingSpan};
+use crate::{internal::llm_client::LLMResponse, BamlRuntime, FunctionResult, SpanCtx};
+
+pub(super) struct OtelExporter {
+    provider: TracerProvider,
+    tracer: Tracer,
+}
+
+impl OtelExporter {
+    /// `None` unless `BAML_OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+    ///
+    /// - `BAML_OTEL_EXPORTER_OTLP_PROTOCOL`: `grpc` (default) or
+    ///   `http/protobuf`
+    /// - `BAML_OTEL_SERVICE_NAME`: the `service.name` of the spans, `baml` by
+    ///   default
+    pub(super) fn from_env_vars(env_vars: &HashMap<String, String>) -> Result<Option<Self>> {
+        let Some(endpoint) = env_vars
+            .get("BAML_OTEL_EXPORTER_OTLP_ENDPOINT")
+            .filter(|endpoint| !endpoint.is_empty())
+        else {
+            return Ok(None);
+        };
+        let protocol = env_vars
+            .get("BAML_OTEL_EXPORTER_OTLP_PROTOCOL")
+            .map_or("grpc", String::as_str);
+        let service_name = env_vars
+            .get("BAML_OTEL_SERVICE_NAME")
+            .cloned()
+            .unwrap_or_else(|| "baml".to_string());
+
+        // The exporter sends batches from the runtime's tokio runtime, which
+        // outlives every caller's.
+        let runtime = BamlRuntime::get_tokio_singleton()?;
+        let _guard = runtime.enter();
+        let exporter = match protocol {
+            "grpc" => opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build(),
+            "http/protobuf" => opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+                .build(),
+            other => anyhow::bail!(
+                "BAML_OTEL_EXPORTER_OTLP_PROTOCOL must be grpc or http/protobuf, got {other}"
+            ),
+        }
+        .with_context(|| format!("Failed to create an OTLP exporter for {endpoint}"))?;
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .with_resource(Resource::new([KeyValue::new("service.name", service_name)]))
+            .build();
+        let tracer = provider.tracer("baml");
+        Ok(Some(Self { provider, tracer }))
+    }
+
+    pub(super) fn flush(&self) -> Result<()> {
+        for result in self.provider.force_flush() {
+            result.context("Failed to flush OpenTelemetry spans")?;
+        }
+        Ok(())
+    }
+
+    /// Exports the span of a `@trace`d function.
+    pub(super) fn export_span(
+        &self,
+        span: &TracingSpan,
+        event_chain: &[SpanCtx],
+        tags: &HashMap<String, BamlValue>,
+    ) {
+        let Some(current) = event_chain.last() else {
+            return;
+        };
+        let builder = self
+            .tracer
+            .span_builder(current.name.clone())
+            .with_span_id(span_id(current.span_id))
+            .with_start_time(span.start_time)
+            .with_attributes(tag_attributes(tags));
+        self.end(builder, span, event_chain, web_time::SystemTime::now());
+    }
+
+    /// Exports the span of a BAML function call, with a child span per LLM
+    /// request it made.
+    pub(super) fn export_function(
+        &self,
+        span: &TracingSpan,
+        event_chain: &[SpanCtx],
+        tags: &HashMap<String, BamlValue>,
+        response: &Result<FunctionResult>,
+        capture: TraceCapture,
+    ) {
+        let Some(current) = event_chain.last() else {
+            return;
+        };
+        let end_time = web_time::SystemTime::now();
+        let mut attributes = tag_attributes(tags);
+        attributes.push(KeyValue::new("baml.function.name", current.name.clone()));
+        let status = match response {
+            Ok(result) => {
+                let (mut input_tokens, mut output_tokens) = (0, 0);
+                for (_, response, _, _) in result.event_chain() {
+                    if let LLMResponse::Success(response) = response {
+                        input_tokens += response.metadata.prompt_tokens.unwrap_or_default();
+                        output_tokens += response.metadata.output_tokens.unwrap_or_default();
+                    }
+                }
+                attributes.extend([
+                    KeyValue::new("baml.attempts", result.event_chain().len() as i64),
+                    KeyValue::new("gen_ai.usage.input_tokens", input_tokens as i64),
+                    KeyValue::new("gen_ai.usage.output_tokens", output_tokens as i64),
+                ]);
+                match result.result_with_constraints() {
+                    Some(Ok(_)) => Status::Ok,
+                    Some(Err(e)) => error_status(capture, &e.to_string()),
+                    None => Status::error("No response"),
+                }
+            }
+            Err(e) => error_status(capture, &e.to_string()),
+        };
+        let builder = self
+            .tracer
+            .span_builder(current.name.clone())
+            .with_span_id(span_id(current.span_id))
+            .with_start_time(span.start_time)
+            .with_attributes(attributes)
+            .with_status(status);
+        let cx = self.end(builder, span, event_chain, end_time);
+
+        let Ok(result) = response else {
+            return;
+        };
+        for (scope, response, _, _) in result.event_chain() {
+            let Some((builder, end_time)) = self.request_span(scope.name(), response, capture)
+            else {
+                continue;
+            };
+            self.tracer
+                .build_with_context(builder, &cx)
+                .end_with_timestamp(end_time);
+        }
+    }
+
+    /// Ends a span started with `builder`, as a child of the span before the
+    /// last in `event_chain`, or of the application's span for the first.
+    /// Returns the span's context, for its children.
+    fn end(
+        &self,
+        builder: SpanBuilder,
+        span: &TracingSpan,
+        event_chain: &[SpanCtx],
+        end_time: web_time::SystemTime,
+    ) -> OtelContext {
+        let (trace_id, flags) = match span.trace_parent {
+            Some(trace_parent) => (
+                TraceId::from_bytes(trace_parent.trace_id.to_be_bytes()),
+                TraceFlags::new(trace_parent.flags),
+            ),
+            None => (trace_id(event_chain[0].span_id), TraceFlags::SAMPLED),
+        };
+        let parent_span_id = match (event_chain, span.trace_parent) {
+            ([.., parent, _], _) => Some(span_id(parent.span_id)),
+            (_, Some(trace_parent)) => {
+                Some(SpanId::from_bytes(trace_parent.parent_id.to_be_bytes()))
+            }
+            _ => None,
+        };
+        let parent =
+            match parent_span_id {
+                Some(parent_span_id) => OtelContext::new().with_remote_span_context(
+                    SpanContext::new(trace_id, parent_span_id, flags, true, TraceState::default()),
+                ),
+                None => OtelContext::new(),
+            };
+        let mut span = self
+            .tracer
+            .build_with_context(builder.with_trace_id(trace_id), &parent);
+        span.end_with_timestamp(end_time);
+        OtelContext::new().with_remote_span_context(span.span_context().clone())
+    }
+
+    /// The span of one LLM request, with when it ended. `None` for requests
+    /// that were never sent.
+    fn request_span(
+        &self,
+        scope: String,
+        response: &LLMResponse,
+        capture: TraceCapture,
+    ) -> Option<(SpanBuilder, web_time::SystemTime)> {
+        let mut attributes = vec![KeyValue::new("baml.scope", scope)];
+        let (start_time, latency, status) = match response {
+            LLMResponse::Success(response) => {
+                attributes.extend([
+                    KeyValue::new("baml.client", response.client.clone()),
+                    KeyValue::new("gen_ai.request.model", response.model.clone()),
+                ]);
+                if let Some(tokens) = response.metadata.prompt_tokens {
+                    attributes.push(KeyValue::new("gen_ai.usage.input_tokens", tokens as i64));
+                }
+                if let Some(tokens) = response.metadata.output_tokens {
+                    attributes.push(KeyValue::new("gen_ai.usage.output_tokens", tokens as i64));
+                }
+                if let Some(reason) = &response.metadata.finish_reason {
+                    attributes.push(KeyValue::new(
+                        "gen_ai.response.finish_reasons",
+                        Value::Array(vec![StringValue::from(reason.clone())].into()),
+                    ));
+                }
+                attributes.extend(content_attributes(
+                    capture,
+                    &response.prompt.to_string(),
+                    Some(&response.content),
+                ));
+                (response.start_time, response.latency, Status::Ok)
+            }
+            LLMResponse::LLMFailure(error) => {
+                attributes.push(KeyValue::new("baml.client", error.client.clone()));
+                if let Some(model) = &error.model {
+                    attributes.push(KeyValue::new("gen_ai.request.model", model.clone()));
+                }
+                attributes.extend(content_attributes(capture, &error.prompt.to_string(), None));
+                (
+                    error.start_time,
+                    error.latency,
+                    error_status(capture, &error.message),
+                )
+            }
+            LLMResponse::UserFailure(_) | LLMResponse::InternalFailure(_) => return None,
+        };
+        let builder = self
+            .tracer
+            .span_builder("llm_request")
+            .with_kind(SpanKind::Client)
+            .with_start_time(start_time)
+            .with_attributes(attributes)
+            .with_status(status);
+        Some((builder, start_time + latency))
+    }
+}
+
+/// The trace of a span is named after its root span.
+fn trace_id(root: Uuid) -> TraceId {
+    TraceId::from_bytes(root.as_u128().to_be_bytes())
+}
+
+fn span_id(span: Uuid) -> SpanId {
+    SpanId::from_bytes(span_id_of(span).to_be_bytes())
+}
+
+fn tag_attributes(tags: &HashMap<String, BamlValue>) -> Vec<KeyValue> {
+    tags.iter()
+        .map(|(key, value)| {
+            let value = match value {
+                BamlValue::String(value) => value.clone(),
+                value => serde_json::to_string(value).unwrap_or_default(),
+            };
+            KeyValue::new(format!("baml.tag.{key}"), value)
+        })
+        .collect()
+}
+
+fn content_attributes(
+    capture: TraceCapture,
+    prompt: &str,
+    completion: Option<&str>,
+) -> Vec<KeyValue> {
+    [
+        ("baml.prompt", Some(prompt)),
+        ("baml.completion", completion),
+    ]
+    .into_iter()
+    .filter_map(|(key, text)| Some(KeyValue::new(key, capture::capture_text(capture, text?)?)))
+    .collect()
+}
+
+fn error_status(capture: TraceCapture, message: &str) -> Status {
+    Status::error(capture::capture_text(capture, message).unwrap_or_else(|| "Failed".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_come_from_baml_spans() {
+        let uuid = Uuid::from_u128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        assert_eq!(
+            trace_id(uuid).to_string(),
+            "0123456789abcdeffedcba9876543210"
+        );
+        assert_eq!(span_id(uuid).to_string(), "fedcba9876543210");
+    }
+}
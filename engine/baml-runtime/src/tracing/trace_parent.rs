@@ -0,0 +1,115 @@
+//! The W3C trace context of the application calling BAML, so its calls show
+//! up in the application's distributed trace.
+//!
+//! See <https://www.w3.org/TR/trace-context/#traceparent-header>.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::Result;
+use uuid::Uuid;
+
+/// A `traceparent` header, e.g.
+/// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    pub trace_id: u128,
+    /// The span the next span is a child of.
+    pub parent_id: u64,
+    pub flags: u8,
+}
+
+impl TraceParent {
+    pub fn sampled(&self) -> bool {
+        self.flags & 1 == 1
+    }
+
+    /// The context of the BAML span `span_id`, in the same trace.
+    pub fn child(&self, span_id: Uuid) -> Self {
+        Self {
+            parent_id: span_id_of(span_id),
+            ..*self
+        }
+    }
+}
+
+/// The span id a BAML span has in W3C and OpenTelemetry traces.
+pub(crate) fn span_id_of(span: Uuid) -> u64 {
+    span.as_u128() as u64
+}
+
+impl FromStr for TraceParent {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid traceparent: {s:?}");
+        let parts = s.trim().split('-').collect::<Vec<_>>();
+        let &[version, trace_id, parent_id, flags, ref rest @ ..] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        let is_hex = |part: &str, len: usize| {
+            part.len() == len && part.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        // Later versions may append fields, which are skipped.
+        let version_ok =
+            is_hex(version, 2) && version != "ff" && (version != "00" || rest.is_empty());
+        if !version_ok || !is_hex(trace_id, 32) || !is_hex(parent_id, 16) || !is_hex(flags, 2) {
+            return Err(invalid());
+        }
+        let parsed = Self {
+            trace_id: u128::from_str_radix(trace_id, 16)?,
+            parent_id: u64::from_str_radix(parent_id, 16)?,
+            flags: u8::from_str_radix(flags, 16)?,
+        };
+        if parsed.trace_id == 0 || parsed.parent_id == 0 {
+            return Err(invalid());
+        }
+        Ok(parsed)
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.parent_id, self.flags
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let parent = header.parse::<TraceParent>().unwrap();
+        assert_eq!(parent.trace_id, 0x4bf92f3577b34da6a3ce929d0e0e4736);
+        assert_eq!(parent.parent_id, 0x00f067aa0ba902b7);
+        assert!(parent.sampled());
+        assert_eq!(parent.to_string(), header);
+
+        let span = Uuid::from_u128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        assert_eq!(
+            parent.child(span).to_string(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-fedcba9876543210-01"
+        );
+
+        for invalid in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert!(invalid.parse::<TraceParent>().is_err(), "{invalid}");
+        }
+        assert!(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra"
+                .parse::<TraceParent>()
+                .is_ok()
+        );
+    }
+}
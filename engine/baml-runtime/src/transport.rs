@@ -0,0 +1,265 @@
+//! How a runtime's LLM requests are sent.
+//!
+//! Requests go over the network with the client's `reqwest::Client` unless
+//! a runtime is given another transport with
+//! [`crate::BamlRuntime::set_http_transport`], e.g. a [`MockTransport`] in
+//! tests, which answers with canned responses instead.
+
+use std::{fmt::Debug, future::Future, pin::Pin};
+
+use anyhow::Result;
+use reqwest::{Client, Request, Response};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type ResponseFuture = Pin<Box<dyn Future<Output = Result<Response>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type ResponseFuture = Pin<Box<dyn Future<Output = Result<Response>>>>;
+
+pub trait HttpTransport: Debug + Send + Sync {
+    /// Sends `request`. `client` is the one the LLM client was configured
+    /// with, for transports that send requests over the network.
+    ///
+    /// Errors that are `reqwest::Error`s keep their meaning, e.g. timeouts
+    /// fail calls with `ErrorCode::Timeout`.
+    fn execute(&self, client: &Client, request: Request) -> ResponseFuture;
+}
+
+/// Sends requests over the network.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReqwestTransport;
+
+impl HttpTransport for ReqwestTransport {
+    fn execute(&self, client: &Client, request: Request) -> ResponseFuture {
+        let client = client.clone();
+        Box::pin(async move { Ok(client.execute(request).await?) })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use mock::{MockEvent, MockRequest, MockTransport};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod mock {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    use reqwest::ResponseBuilderExt;
+
+    use super::*;
+
+    /// A request a [`MockTransport`] was sent.
+    #[derive(Debug, Clone)]
+    pub struct MockRequest {
+        pub method: String,
+        pub url: String,
+        pub headers: Vec<(String, String)>,
+        pub body: Option<String>,
+    }
+
+    impl MockRequest {
+        /// The body, parsed as JSON.
+        pub fn json(&self) -> Option<serde_json::Value> {
+            serde_json::from_str(self.body.as_deref()?).ok()
+        }
+    }
+
+    #[derive(Debug)]
+    enum MockResponse {
+        Http {
+            status: u16,
+            content_type: &'static str,
+            body: Vec<u8>,
+        },
+        Error(String),
+    }
+
+    /// Answers requests with the responses queued on it, in order, and
+    /// records the requests. Requests fail once the queue is empty.
+    ///
+    /// Cheap to clone and clones share their queue and requests.
+    #[derive(Debug, Default, Clone)]
+    pub struct MockTransport {
+        responses: Arc<Mutex<VecDeque<MockResponse>>>,
+        requests: Arc<Mutex<Vec<MockRequest>>>,
+    }
+
+    impl MockTransport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        fn push(&self, response: MockResponse) -> &Self {
+            self.responses.lock().unwrap().push_back(response);
+            self
+        }
+
+        /// Responds with `body` as JSON.
+        pub fn push_json(&self, status: u16, body: serde_json::Value) -> &Self {
+            self.push(MockResponse::Http {
+                status,
+                content_type: "application/json",
+                body: body.to_string().into_bytes(),
+            })
+        }
+
+        pub fn push_text(&self, status: u16, body: impl Into<String>) -> &Self {
+            self.push(MockResponse::Http {
+                status,
+                content_type: "text/plain",
+                body: body.into().into_bytes(),
+            })
+        }
+
+        /// Responds with a server-sent event per item of `events`, each as
+        /// its `data`. Named events can be given as `("name", data)`.
+        pub fn push_sse<E: Into<MockEvent>>(&self, events: impl IntoIterator<Item = E>) -> &Self {
+            let body = events
+                .into_iter()
+                .map(|event| event.into().to_string())
+                .collect::<String>();
+            self.push(MockResponse::Http {
+                status: 200,
+                content_type: "text/event-stream",
+                body: body.into_bytes(),
+            })
+        }
+
+        /// Fails the request without a response, as if the host couldn't be
+        /// reached.
+        pub fn push_error(&self, message: impl Into<String>) -> &Self {
+            self.push(MockResponse::Error(message.into()))
+        }
+
+        /// The requests sent so far, in order.
+        pub fn requests(&self) -> Vec<MockRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// A server-sent event of [`MockTransport::push_sse`].
+    #[derive(Debug, Clone)]
+    pub struct MockEvent {
+        pub event: Option<String>,
+        pub data: String,
+    }
+
+    impl std::fmt::Display for MockEvent {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if let Some(event) = &self.event {
+                writeln!(f, "event: {event}")?;
+            }
+            write!(f, "data: {}\n\n", self.data)
+        }
+    }
+
+    impl From<serde_json::Value> for MockEvent {
+        fn from(data: serde_json::Value) -> Self {
+            Self {
+                event: None,
+                data: data.to_string(),
+            }
+        }
+    }
+
+    impl From<&str> for MockEvent {
+        fn from(data: &str) -> Self {
+            Self {
+                event: None,
+                data: data.to_string(),
+            }
+        }
+    }
+
+    impl From<(&str, serde_json::Value)> for MockEvent {
+        fn from((event, data): (&str, serde_json::Value)) -> Self {
+            Self {
+                event: Some(event.to_string()),
+                data: data.to_string(),
+            }
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn execute(&self, _client: &Client, request: Request) -> ResponseFuture {
+            let url = request.url().clone();
+            self.requests.lock().unwrap().push(MockRequest {
+                method: request.method().to_string(),
+                url: url.to_string(),
+                headers: request
+                    .headers()
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.to_string(),
+                            String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                        )
+                    })
+                    .collect(),
+                body: request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .map(|body| String::from_utf8_lossy(body).into_owned()),
+            });
+            let response = self.responses.lock().unwrap().pop_front();
+            Box::pin(async move {
+                let (status, content_type, body) = match response {
+                    Some(MockResponse::Http {
+                        status,
+                        content_type,
+                        body,
+                    }) => (status, content_type, body),
+                    Some(MockResponse::Error(message)) => anyhow::bail!(message),
+                    None => anyhow::bail!("MockTransport has no response left for {url}"),
+                };
+                // Error messages name the URL of the response.
+                let response = http::Response::builder()
+                    .status(status)
+                    .header(http::header::CONTENT_TYPE, content_type)
+                    .url(url)
+                    .body(body)?;
+                Ok(Response::from(response))
+            })
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_transport_answers_in_order() {
+        let transport = MockTransport::new();
+        transport
+            .push_json(200, serde_json::json!({"ok": true}))
+            .push_sse([serde_json::json!({"n": 1}), serde_json::json!({"n": 2})]);
+
+        let client = Client::new();
+        let send = |body: &str| {
+            let request = client
+                .post("https://api.example.com/v1/chat")
+                .body(body.to_string())
+                .build()
+                .unwrap();
+            futures::executor::block_on(transport.execute(&client, request))
+        };
+
+        let response = send("{\"first\": true}").unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.url().as_str(), "https://api.example.com/v1/chat");
+        let response = send("second").unwrap();
+        assert_eq!(
+            futures::executor::block_on(response.text()).unwrap(),
+            "data: {\"n\":1}\n\ndata: {\"n\":2}\n\n"
+        );
+        assert!(send("third").is_err());
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].json(), Some(serde_json::json!({"first": true})));
+        assert_eq!(requests[1].body.as_deref(), Some("second"));
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use baml_types::{BamlValue, Constraint, ConstraintLevel, FieldType};
+use internal_baml_core::{
+    ast::{self, Attribute, Expression, Identifier, Top, WithName},
+    internal_baml_diagnostics::SourceFile,
+    internal_baml_schema_ast::parse_schema,
+};
+
+use super::{TypeBuilder, WithMeta};
+
+/// A class of the snippet, ready to be merged into the builder.
+struct ClassSource {
+    name: String,
+    properties: Vec<(String, FieldType, Vec<(&'static str, BamlValue)>)>,
+}
+
+/// An enum of the snippet, ready to be merged into the builder.
+struct EnumSource {
+    name: String,
+    meta: Vec<(&'static str, BamlValue)>,
+    values: Vec<(String, Vec<(&'static str, BamlValue)>)>,
+}
+
+impl TypeBuilder {
+    /// Parses `source`, a snippet of BAML declaring classes and enums, and
+    /// merges it into the builder as if each property and value had been
+    /// added through [`TypeBuilder::class`] and [`TypeBuilder::r#enum`]:
+    ///
+    /// ```baml
+    /// class Address {
+    ///   street string
+    ///   city string @description("Without the state")
+    /// }
+    ///
+    /// class Person {
+    ///   address Address?
+    /// }
+    /// ```
+    ///
+    /// Classes that already exist in the schema, like `Person` above, must be
+    /// `@@dynamic`, and get the snippet's properties added to them.
+    ///
+    /// A name used as a type is an enum if the snippet or the builder has an
+    /// enum by that name, and a class otherwise, so call
+    /// [`TypeBuilder::r#enum`] first to use an enum of the schema. Nothing is
+    /// added if the snippet has an error.
+    pub fn add_baml(&self, source: &str) -> Result<()> {
+        let path = Path::new("type_builder.baml");
+        let file = SourceFile::from((path.to_path_buf(), source.to_string()));
+        let ast = match parse_schema(path, &file) {
+            Ok((ast, diagnostics)) if !diagnostics.has_errors() => ast,
+            Ok((_, diagnostics)) | Err(diagnostics) => {
+                anyhow::bail!("Failed to parse BAML:\n{}", diagnostics.to_pretty_string())
+            }
+        };
+
+        let mut enum_names = self
+            .enums
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<HashSet<_>>();
+        for (_, top) in ast.iter_tops() {
+            match top {
+                Top::Enum(block) => {
+                    enum_names.insert(block.name().to_string());
+                }
+                Top::Class(_) => {}
+                other => anyhow::bail!(
+                    "Only classes and enums can be added to a TypeBuilder, found {} {}",
+                    other.get_type(),
+                    other.name()
+                ),
+            }
+        }
+
+        let mut classes = vec![];
+        let mut enums = vec![];
+        for (_, top) in ast.iter_tops() {
+            match top {
+                Top::Class(block) => classes.push(class_source(block, &enum_names)?),
+                Top::Enum(block) => enums.push(enum_source(block)?),
+                _ => {}
+            }
+        }
+
+        for class in classes {
+            let builder = self.class(&class.name);
+            let builder = builder.lock().unwrap();
+            for (name, r#type, meta) in class.properties {
+                let property = builder.property(&name);
+                let property = property.lock().unwrap();
+                property.r#type(r#type);
+                for (key, value) in meta {
+                    property.with_meta(key, value);
+                }
+            }
+        }
+        for enm in enums {
+            let builder = self.r#enum(&enm.name);
+            let builder = builder.lock().unwrap();
+            for (key, value) in enm.meta {
+                builder.with_meta(key, value);
+            }
+            for (name, meta) in enm.values {
+                let value = builder.value(&name);
+                let value = value.lock().unwrap();
+                for (key, meta_value) in meta {
+                    value.with_meta(key, meta_value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn class_source(
+    block: &ast::TypeExpressionBlock,
+    enum_names: &HashSet<String>,
+) -> Result<ClassSource> {
+    let name = block.name().to_string();
+    for attribute in &block.attributes {
+        // `@@dynamic` is implied: the class is being extended at runtime.
+        if attribute.name.name() != "dynamic" {
+            anyhow::bail!(
+                "Class {name}: @@{} is not supported by the TypeBuilder",
+                attribute.name.name()
+            );
+        }
+    }
+
+    let properties = block
+        .iter_fields()
+        .map(|(_, field)| {
+            let Some(field_type) = &field.expr else {
+                anyhow::bail!("Class {name}: property {} has no type", field.name());
+            };
+            let r#type = to_field_type(field_type, enum_names)?;
+            let meta = field_meta(&field.attributes)
+                .map_err(|e| e.context(format!("Class {name}: property {}", field.name())))?;
+            Ok((field.name().to_string(), r#type, meta))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(ClassSource { name, properties })
+}
+
+fn enum_source(block: &ast::TypeExpressionBlock) -> Result<EnumSource> {
+    let name = block.name().to_string();
+    let meta = field_meta(&block.attributes)
+        .map_err(|e| e.context(format!("Enum {name}")))?
+        .into_iter()
+        .filter(|(key, _)| *key == "alias")
+        .collect();
+    let values = block
+        .iter_fields()
+        .map(|(_, value)| {
+            let meta = field_meta(&value.attributes)
+                .map_err(|e| e.context(format!("Enum {name}: value {}", value.name())))?;
+            Ok((value.name().to_string(), meta))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(EnumSource { name, meta, values })
+}
+
+/// Reads `@alias`, `@description` and `@skip` into builder metadata.
+fn field_meta(attributes: &[Attribute]) -> Result<Vec<(&'static str, BamlValue)>> {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.name.name() != "dynamic")
+        .map(|attribute| match attribute.name.name() {
+            "alias" => Ok(("alias", BamlValue::String(string_argument(attribute)?))),
+            "description" => Ok((
+                "description",
+                BamlValue::String(string_argument(attribute)?),
+            )),
+            "skip" => Ok(("skip", BamlValue::Bool(true))),
+            other => anyhow::bail!("@{other} is not supported by the TypeBuilder"),
+        })
+        .collect()
+}
+
+fn string_argument(attribute: &Attribute) -> Result<String> {
+    match attribute.arguments.arguments.as_slice() {
+        [argument] => match argument.value.as_string_value() {
+            Some((value, _)) => Ok(value.to_string()),
+            None => anyhow::bail!("@{} must be a string", attribute.name.name()),
+        },
+        _ => anyhow::bail!("@{} takes exactly one string", attribute.name.name()),
+    }
+}
+
+fn to_field_type(field_type: &ast::FieldType, enum_names: &HashSet<String>) -> Result<FieldType> {
+    let with_arity = |r#type: FieldType, arity: &ast::FieldArity| {
+        if arity.is_optional() {
+            r#type.as_optional()
+        } else {
+            r#type
+        }
+    };
+
+    let base = match field_type {
+        ast::FieldType::Primitive(arity, type_value, ..) => {
+            with_arity(FieldType::Primitive(*type_value), arity)
+        }
+        ast::FieldType::Literal(arity, literal, ..) => {
+            with_arity(FieldType::Literal(literal.clone()), arity)
+        }
+        ast::FieldType::Symbol(arity, identifier, ..) => {
+            let name = identifier.name().to_string();
+            let r#type = if enum_names.contains(&name) {
+                FieldType::Enum(name)
+            } else {
+                FieldType::Class(name)
+            };
+            with_arity(r#type, arity)
+        }
+        ast::FieldType::List(arity, inner, dims, ..) => {
+            let mut r#type = to_field_type(inner, enum_names)?.as_list();
+            for _ in 1..*dims {
+                r#type = r#type.as_list();
+            }
+            with_arity(r#type, arity)
+        }
+        ast::FieldType::Map(arity, kv, ..) => with_arity(
+            FieldType::map(
+                to_field_type(&kv.0, enum_names)?,
+                to_field_type(&kv.1, enum_names)?,
+            ),
+            arity,
+        ),
+        ast::FieldType::Union(arity, items, ..) => {
+            let mut items = items
+                .iter()
+                .map(|item| to_field_type(item, enum_names))
+                .collect::<Result<Vec<_>>>()?;
+            if arity.is_optional() {
+                items.push(FieldType::null());
+            }
+            FieldType::Union(items)
+        }
+        ast::FieldType::Tuple(arity, items, ..) => with_arity(
+            FieldType::Tuple(
+                items
+                    .iter()
+                    .map(|item| to_field_type(item, enum_names))
+                    .collect::<Result<_>>()?,
+            ),
+            arity,
+        ),
+    };
+
+    let constraints = field_type
+        .attributes()
+        .iter()
+        .map(constraint)
+        .collect::<Result<Vec<_>>>()?;
+    if constraints.is_empty() {
+        Ok(base)
+    } else {
+        Ok(FieldType::Constrained {
+            base: Box::new(base),
+            constraints,
+        })
+    }
+}
+
+fn constraint(attribute: &Attribute) -> Result<Constraint> {
+    let level = match attribute.name.name() {
+        "assert" => ConstraintLevel::Assert,
+        "check" => ConstraintLevel::Check,
+        other => anyhow::bail!("@{other} is not supported on a type by the TypeBuilder"),
+    };
+    let arguments = attribute
+        .arguments
+        .arguments
+        .iter()
+        .map(|argument| &argument.value)
+        .collect::<Vec<_>>();
+    let (label, expression) = match arguments.as_slice() {
+        [Expression::JinjaExpressionValue(expression, _)] if level == ConstraintLevel::Assert => {
+            (None, expression)
+        }
+        [Expression::Identifier(Identifier::Local(label, _)), Expression::JinjaExpressionValue(expression, _)] => {
+            (Some(label.clone()), expression)
+        }
+        _ => anyhow::bail!(
+            "@{} takes a label and a Jinja expression, like @check(too_long, {{{{ this|length < 10 }}}})",
+            attribute.name.name()
+        ),
+    };
+
+    Ok(Constraint {
+        level,
+        expression: expression.clone(),
+        label,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_baml() {
+        let builder = TypeBuilder::new();
+        builder.r#enum("Role");
+        builder
+            .add_baml(
+                r#"
+                enum Color {
+                  Red @alias("crimson")
+                  Blue
+                }
+
+                class Address {
+                  street string
+                  city string @description("Without the state")
+                }
+
+                class Person {
+                  address Address?
+                  colors Color[]
+                  role Role @alias("job")
+                  age int @check(adult, {{ this >= 18 }})
+                }
+                "#,
+            )
+            .unwrap();
+
+        let (classes, enums) = builder.to_overrides();
+        assert_eq!(
+            enums["Color"].values.keys().collect::<Vec<_>>(),
+            ["Red", "Blue"]
+        );
+        assert_eq!(
+            enums["Color"].values["Red"].alias,
+            Some(BamlValue::String("crimson".to_string()))
+        );
+
+        let person = &classes["Person"].new_fields;
+        assert_eq!(
+            person["address"].0,
+            FieldType::class("Address").as_optional()
+        );
+        assert_eq!(person["colors"].0, FieldType::r#enum("Color").as_list());
+        assert_eq!(person["role"].0, FieldType::r#enum("Role"));
+        assert_eq!(
+            person["role"].1.alias,
+            Some(BamlValue::String("job".to_string()))
+        );
+        assert!(matches!(person["age"].0, FieldType::Constrained { .. }));
+        assert_eq!(
+            classes["Address"].new_fields["city"]
+                .1
+                .meta
+                .get("description"),
+            Some(&BamlValue::String("Without the state".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_add_baml_rejects_functions_and_adds_nothing() {
+        let builder = TypeBuilder::new();
+        let err = builder
+            .add_baml(
+                r##"
+                class Foo {
+                  bar string
+                }
+
+                function Extract(input: string) -> Foo {
+                  client "openai/gpt-4o"
+                  prompt #"{{ input }}"#
+                }
+                "##,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("function Extract"));
+        assert!(builder.to_overrides().0.is_empty());
+    }
+}
@@ -1,10 +1,12 @@
 use std::sync::{Arc, Mutex};
 
-use baml_types::{BamlValue, FieldType};
+use baml_types::{BamlValue, EnumMatching, FieldType, NumberLocale};
 use indexmap::IndexMap;
 
 use crate::runtime_context::{PropertyAttributes, RuntimeClassOverride, RuntimeEnumOverride};
 
+mod from_source;
+
 type MetaData = Arc<Mutex<IndexMap<String, BamlValue>>>;
 
 trait Meta {
@@ -170,6 +172,8 @@ impl std::fmt::Debug for TypeBuilder {
 pub struct TypeBuilder {
     classes: Arc<Mutex<IndexMap<String, Arc<Mutex<ClassBuilder>>>>>,
     enums: Arc<Mutex<IndexMap<String, Arc<Mutex<EnumBuilder>>>>>,
+    enum_matching: Arc<Mutex<Option<EnumMatching>>>,
+    number_locale: Arc<Mutex<NumberLocale>>,
 }
 
 impl Default for TypeBuilder {
@@ -183,9 +187,33 @@ impl TypeBuilder {
         Self {
             classes: Default::default(),
             enums: Default::default(),
+            enum_matching: Default::default(),
+            number_locale: Default::default(),
         }
     }
 
+    /// Matches the values of every enum this way in calls given this builder,
+    /// instead of as their `@@match` says.
+    pub fn set_enum_matching(&self, matching: EnumMatching) -> &Self {
+        *self.enum_matching.lock().unwrap() = Some(matching);
+        self
+    }
+
+    pub fn enum_matching(&self) -> Option<EnumMatching> {
+        *self.enum_matching.lock().unwrap()
+    }
+
+    /// Parses numbers written as strings, like `"1.234,56"`, with these
+    /// separators in calls given this builder.
+    pub fn set_number_locale(&self, locale: NumberLocale) -> &Self {
+        *self.number_locale.lock().unwrap() = locale;
+        self
+    }
+
+    pub fn number_locale(&self) -> NumberLocale {
+        *self.number_locale.lock().unwrap()
+    }
+
     pub fn class(&self, name: &str) -> Arc<Mutex<ClassBuilder>> {
         Arc::clone(
             self.classes
@@ -0,0 +1,262 @@
+use anyhow::Result;
+use jsonish::BamlValueWithFlags;
+use serde::{Serialize, Serializer};
+use web_time::{Duration, SystemTime};
+
+use crate::internal::llm_client::{
+    orchestrator::{ExecutionScope, OrchestrationScope},
+    ErrorCode, LLMResponse, ResponseBamlValue,
+};
+
+/// One request the orchestrator made during a call, see
+/// [`FunctionResult::attempts`](crate::FunctionResult::attempts).
+#[derive(Debug, Clone, Serialize)]
+pub struct Attempt {
+    /// The client the request was for.
+    pub client: String,
+    /// The strategies that picked the client, e.g.
+    /// `Fallback(MyFallback, 1) + ClientB`.
+    pub path: String,
+    /// `None` if the attempt failed before a request was sent.
+    #[serde(rename = "start_time_unix_ms", serialize_with = "serialize_unix_ms")]
+    pub start_time: Option<SystemTime>,
+    #[serde(rename = "latency_ms", serialize_with = "serialize_ms")]
+    pub latency: Option<Duration>,
+    pub outcome: AttemptOutcome,
+    pub error_code: Option<ErrorCode>,
+    /// What the orchestrator did after this attempt.
+    pub next: NextStep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptOutcome {
+    Success,
+    /// The model responded, but the response didn't parse or had a finish
+    /// reason the client doesn't allow.
+    ParseFailure,
+    /// The provider returned an error.
+    LlmFailure,
+    /// The request couldn't be built from the function's arguments.
+    UserFailure,
+    /// The request couldn't be built or sent.
+    InternalFailure,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NextStep {
+    /// The client's retry policy tried again after `delay`.
+    Retry {
+        policy: String,
+        /// `1` for the first retry.
+        retry: usize,
+        #[serde(rename = "delay_ms", serialize_with = "serialize_ms")]
+        delay: Duration,
+    },
+    /// A fallback or round-robin strategy moved on to another client.
+    NextClient,
+    /// This was the last attempt.
+    Stop,
+}
+
+pub(crate) fn from_event_chain(
+    chain: &[(
+        OrchestrationScope,
+        LLMResponse,
+        Option<Result<BamlValueWithFlags>>,
+        Option<Result<ResponseBamlValue>>,
+    )],
+) -> Vec<Attempt> {
+    chain
+        .iter()
+        .enumerate()
+        .map(|(i, (scope, response, _, result))| {
+            let (client, start_time, latency, outcome, error_code) = match response {
+                LLMResponse::Success(s) => (
+                    s.client.clone(),
+                    Some(s.start_time),
+                    Some(s.latency),
+                    match result {
+                        Some(Err(_)) => AttemptOutcome::ParseFailure,
+                        _ => AttemptOutcome::Success,
+                    },
+                    None,
+                ),
+                LLMResponse::LLMFailure(f) => (
+                    f.client.clone(),
+                    Some(f.start_time),
+                    Some(f.latency),
+                    AttemptOutcome::LlmFailure,
+                    Some(f.code.clone()),
+                ),
+                LLMResponse::UserFailure(_) => (
+                    scope.direct_client_name().cloned().unwrap_or_default(),
+                    None,
+                    None,
+                    AttemptOutcome::UserFailure,
+                    None,
+                ),
+                LLMResponse::InternalFailure(_) => (
+                    scope.direct_client_name().cloned().unwrap_or_default(),
+                    None,
+                    None,
+                    AttemptOutcome::InternalFailure,
+                    None,
+                ),
+            };
+            Attempt {
+                client,
+                path: scope.name(),
+                start_time,
+                latency,
+                outcome,
+                error_code,
+                next: match chain.get(i + 1) {
+                    Some((next, ..)) => next_step(scope, next),
+                    None => NextStep::Stop,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Retries wrap the strategies below them, so the outermost scope that
+/// changed between two attempts says which one moved on.
+fn next_step(current: &OrchestrationScope, next: &OrchestrationScope) -> NextStep {
+    let changed = current
+        .scope
+        .iter()
+        .zip(&next.scope)
+        .find(|(a, b)| a.to_string() != b.to_string())
+        .map(|(_, b)| b);
+    match changed {
+        Some(ExecutionScope::Retry(policy, retry, _)) => NextStep::Retry {
+            policy: policy.clone(),
+            retry: *retry,
+            delay: current
+                .error_sleep_duration()
+                .copied()
+                .unwrap_or(Duration::ZERO),
+        },
+        _ => NextStep::NextClient,
+    }
+}
+
+fn serialize_unix_ms<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let ms = time.map(|time| {
+        time.duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_millis() as u64
+    });
+    ms.serialize(serializer)
+}
+
+fn serialize_ms<S: Serializer, D: Into<Option<Duration>> + Copy>(
+    duration: &D,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let duration: Option<Duration> = (*duration).into();
+    duration
+        .map(|duration| duration.as_millis() as u64)
+        .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use baml_types::BamlMap;
+    use internal_baml_jinja::RenderedPrompt;
+
+    use super::*;
+    use crate::internal::llm_client::{
+        LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse,
+    };
+
+    fn scope(retry: usize, fallback: usize, client: &str) -> OrchestrationScope {
+        vec![
+            ExecutionScope::Retry("Twice".into(), retry, Duration::from_millis(200)),
+            ExecutionScope::Fallback("MyFallback".into(), fallback),
+            ExecutionScope::Direct(client.into()),
+        ]
+        .into()
+    }
+
+    fn failure(client: &str) -> LLMResponse {
+        LLMResponse::LLMFailure(LLMErrorResponse {
+            client: client.into(),
+            model: None,
+            prompt: RenderedPrompt::Completion(String::new()),
+            request_options: BamlMap::new(),
+            start_time: web_time::UNIX_EPOCH,
+            latency: Duration::from_millis(30),
+            message: "Too many requests".into(),
+            code: ErrorCode::RateLimited,
+        })
+    }
+
+    #[test]
+    fn attempts_follow_retries_and_fallbacks() {
+        let success = LLMResponse::Success(LLMCompleteResponse {
+            client: "ClientA".into(),
+            model: "gpt-4o".into(),
+            prompt: RenderedPrompt::Completion(String::new()),
+            request_options: BamlMap::new(),
+            content: "not json".into(),
+            start_time: web_time::UNIX_EPOCH,
+            latency: Duration::from_millis(500),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        });
+        let chain = vec![
+            (scope(0, 0, "ClientA"), failure("ClientA"), None, None),
+            (scope(0, 1, "ClientB"), failure("ClientB"), None, None),
+            (
+                scope(1, 0, "ClientA"),
+                success,
+                None,
+                Some(Err(anyhow::anyhow!("Failed to parse"))),
+            ),
+        ];
+
+        let attempts = from_event_chain(&chain);
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|a| (a.client.as_str(), a.outcome, a.next.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("ClientA", AttemptOutcome::LlmFailure, NextStep::NextClient),
+                (
+                    "ClientB",
+                    AttemptOutcome::LlmFailure,
+                    NextStep::Retry {
+                        policy: "Twice".into(),
+                        retry: 1,
+                        delay: Duration::from_millis(200),
+                    }
+                ),
+                ("ClientA", AttemptOutcome::ParseFailure, NextStep::Stop),
+            ]
+        );
+        assert_eq!(attempts[1].path, "Fallback(MyFallback, 1) + ClientB");
+        assert_eq!(
+            serde_json::to_value(&attempts[1]).unwrap()["next"],
+            serde_json::json!({"type": "retry", "policy": "Twice", "retry": 1, "delay_ms": 200})
+        );
+    }
+}
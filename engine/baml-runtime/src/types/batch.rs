@@ -0,0 +1,79 @@
+use std::sync::Mutex;
+
+use web_time::{Duration, Instant};
+
+/// How [`crate::BamlRuntime::call_function_batch`] runs its calls.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Calls in flight at once. At least 1.
+    pub max_concurrency: usize,
+    /// Calls started per second at most, evenly spaced. Unlimited if `None`.
+    pub requests_per_second: Option<f64>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 10,
+            requests_per_second: None,
+        }
+    }
+}
+
+/// Spaces the starts of a batch's calls `interval` apart.
+pub(crate) struct Pacer {
+    interval: Duration,
+    next_start: Mutex<Option<Instant>>,
+}
+
+impl Pacer {
+    pub(crate) fn new(requests_per_second: Option<f64>) -> Self {
+        let interval = requests_per_second
+            .filter(|rps| *rps > 0.0 && rps.is_finite())
+            .map_or(Duration::ZERO, |rps| Duration::from_secs_f64(1.0 / rps));
+        Self {
+            interval,
+            next_start: Mutex::new(None),
+        }
+    }
+
+    /// Reserves the next start slot and returns how long to wait for it.
+    pub(crate) fn reserve(&self, now: Instant) -> Duration {
+        if self.interval.is_zero() {
+            return Duration::ZERO;
+        }
+        let mut next_start = self.next_start.lock().unwrap();
+        let start = match *next_start {
+            Some(next) if next > now => next,
+            _ => now,
+        };
+        *next_start = Some(start + self.interval);
+        start - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_starts_by_rate() {
+        let pacer = Pacer::new(Some(4.0));
+        let now = Instant::now();
+        let waits = (0..3).map(|_| pacer.reserve(now)).collect::<Vec<_>>();
+        assert_eq!(
+            waits,
+            [
+                Duration::ZERO,
+                Duration::from_millis(250),
+                Duration::from_millis(500)
+            ]
+        );
+        // A slot that passed while idle isn't made up for.
+        assert_eq!(pacer.reserve(now + Duration::from_secs(5)), Duration::ZERO);
+
+        let unlimited = Pacer::new(None);
+        assert_eq!(unlimited.reserve(now), Duration::ZERO);
+        assert_eq!(unlimited.reserve(now), Duration::ZERO);
+    }
+}
@@ -0,0 +1,382 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::internal::llm_client::{
+    orchestrator::OrchestrationScope, GroundingMetadata, LLMResponse, ResponseCandidate,
+    TokenLogprob,
+};
+use crate::FunctionResult;
+
+/// Headers that are never recorded, since they carry credentials.
+const REDACTED_HEADERS: &[&str] = &["authorization", "x-api-key", "api-key", "x-goog-api-key"];
+
+/// Gathers what happened during the function calls it is passed to: the
+/// clients tried, retries, timing, token usage and the raw HTTP traffic.
+///
+/// Unlike tracing, nothing is sent anywhere and no global logging needs to be
+/// enabled. A collector is cheap to clone and clones share their logs.
+#[derive(Clone, Default)]
+pub struct Collector {
+    logs: Arc<Mutex<Vec<FunctionLog>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionLog {
+    pub function_name: String,
+    /// Milliseconds since the unix epoch.
+    pub start_time_ms: u64,
+    pub duration_ms: u64,
+    /// One entry per LLM call, including retries and fallbacks, in order.
+    pub calls: Vec<LLMCallLog>,
+    /// The raw HTTP requests made, in order.
+    pub http_requests: Vec<HttpRequestLog>,
+    /// Set when the function failed before or outside of an LLM call.
+    pub error: Option<String>,
+    /// The score of each enum value, for functions with `@@confidence`.
+    pub confidence: Option<IndexMap<String, f64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LLMCallLog {
+    pub client: String,
+    pub model: Option<String>,
+    /// The orchestration path to the client, e.g. `Fallback(MyFallback, 1) + GPT4`.
+    pub scope: String,
+    /// Which retry of the policy this was, `0` for the first attempt.
+    pub retry: usize,
+    pub start_time_ms: u64,
+    pub latency_ms: u64,
+    pub usage: Usage,
+    pub finish_reason: Option<String>,
+    /// The searches and sources behind a grounded Gemini response.
+    pub grounding: Option<GroundingMetadata>,
+    /// What the model wrote while thinking, with Anthropic's extended thinking
+    /// or a DeepSeek or xAI reasoning model.
+    pub thinking: Option<String>,
+    /// Token log probabilities, for clients with `logprobs true`.
+    pub logprobs: Option<Vec<TokenLogprob>>,
+    /// The provider's JSON body, for clients with `capture_raw_response true`.
+    pub raw_response: Option<serde_json::Value>,
+    /// Every candidate, when the client asked for more than one.
+    pub candidates: Option<Vec<ResponseCandidate>>,
+    pub error: Option<String>,
+    /// Whether this call produced the function's result.
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Usage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpRequestLog {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub response_status: Option<u16>,
+    /// Only recorded for non-streaming requests and errors.
+    pub response_body: Option<String>,
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn logs(&self) -> Vec<FunctionLog> {
+        self.logs.lock().unwrap().clone()
+    }
+
+    pub fn last(&self) -> Option<FunctionLog> {
+        self.logs.lock().unwrap().last().cloned()
+    }
+
+    /// Summed over every call of every function collected so far.
+    pub fn usage(&self) -> Usage {
+        let logs = self.logs.lock().unwrap();
+        logs.iter()
+            .map(FunctionLog::usage)
+            .fold(Usage::default(), Usage::add)
+    }
+
+    pub fn clear(&self) {
+        self.logs.lock().unwrap().clear();
+    }
+
+    pub(crate) fn start(&self, function_name: &str) -> CollectorCall {
+        CollectorCall {
+            collector: self.clone(),
+            function_name: function_name.to_string(),
+            start_time: web_time::SystemTime::now(),
+            start: web_time::Instant::now(),
+            http: HttpRecorder::default(),
+        }
+    }
+}
+
+impl FunctionLog {
+    /// The client that produced the result, if any did.
+    pub fn selected_client(&self) -> Option<&str> {
+        self.calls
+            .iter()
+            .find(|call| call.selected)
+            .map(|call| call.client.as_str())
+    }
+
+    pub fn usage(&self) -> Usage {
+        self.calls
+            .iter()
+            .map(|call| call.usage.clone())
+            .fold(Usage::default(), Usage::add)
+    }
+}
+
+impl Usage {
+    fn add(self, other: Usage) -> Usage {
+        fn sum(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+        Usage {
+            input_tokens: sum(self.input_tokens, other.input_tokens),
+            output_tokens: sum(self.output_tokens, other.output_tokens),
+        }
+    }
+}
+
+/// A function call in progress. Its [`HttpRecorder`] is handed to the
+/// `RuntimeContext` so the LLM clients can record their requests.
+pub(crate) struct CollectorCall {
+    collector: Collector,
+    function_name: String,
+    start_time: web_time::SystemTime,
+    start: web_time::Instant,
+    http: HttpRecorder,
+}
+
+impl CollectorCall {
+    pub(crate) fn http_recorder(&self) -> HttpRecorder {
+        self.http.clone()
+    }
+
+    pub(crate) fn finish(self, result: &Result<FunctionResult>) {
+        let confidence = result
+            .as_ref()
+            .ok()
+            .and_then(|result| result.confidence().cloned());
+        let (calls, error) = match result {
+            Ok(result) => {
+                let chain = result.event_chain();
+                let selected = chain.iter().rposition(|(_, response, _, value)| {
+                    matches!(response, LLMResponse::Success(_)) && matches!(value, Some(Ok(_)))
+                });
+                let calls = chain
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (scope, response, ..))| {
+                        LLMCallLog::new(scope, response, selected == Some(i))
+                    })
+                    .collect();
+                (calls, None)
+            }
+            Err(e) => (vec![], Some(format!("{:?}", e))),
+        };
+
+        let log = FunctionLog {
+            function_name: self.function_name,
+            start_time_ms: unix_ms(self.start_time),
+            duration_ms: self.start.elapsed().as_millis() as u64,
+            calls,
+            http_requests: self.http.take(),
+            error,
+            confidence,
+        };
+        self.collector.logs.lock().unwrap().push(log);
+    }
+}
+
+impl LLMCallLog {
+    fn new(scope: &OrchestrationScope, response: &LLMResponse, selected: bool) -> Self {
+        let mut log = LLMCallLog {
+            client: scope.direct_client_name().cloned().unwrap_or_default(),
+            model: None,
+            scope: scope.name(),
+            retry: scope.retry_index(),
+            start_time_ms: 0,
+            latency_ms: 0,
+            usage: Usage::default(),
+            finish_reason: None,
+            grounding: None,
+            thinking: None,
+            logprobs: None,
+            raw_response: None,
+            candidates: None,
+            error: None,
+            selected,
+        };
+        match response {
+            LLMResponse::Success(r) => {
+                log.client = r.client.clone();
+                log.model = Some(r.model.clone());
+                log.start_time_ms = unix_ms(r.start_time);
+                log.latency_ms = r.latency.as_millis() as u64;
+                log.usage = Usage {
+                    input_tokens: r.metadata.prompt_tokens,
+                    output_tokens: r.metadata.output_tokens,
+                };
+                log.finish_reason = r.metadata.finish_reason.clone();
+                log.grounding = r.metadata.grounding.clone();
+                log.thinking = r.metadata.thinking.clone();
+                log.logprobs = r.metadata.logprobs.clone();
+                log.raw_response = r.metadata.raw_response.clone();
+                log.candidates = r.metadata.candidates.clone();
+            }
+            LLMResponse::LLMFailure(e) => {
+                log.client = e.client.clone();
+                log.model = e.model.clone();
+                log.start_time_ms = unix_ms(e.start_time);
+                log.latency_ms = e.latency.as_millis() as u64;
+                log.error = Some(format!("{} {}", e.code, e.message));
+            }
+            LLMResponse::UserFailure(e) | LLMResponse::InternalFailure(e) => {
+                log.error = Some(e.clone());
+            }
+        }
+        log
+    }
+}
+
+fn unix_ms(time: web_time::SystemTime) -> u64 {
+    time.duration_since(web_time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Shared by the clients of a single function call. Attempts are made one
+/// after another, so a response always belongs to the last request.
+#[derive(Clone, Default)]
+pub(crate) struct HttpRecorder {
+    requests: Arc<Mutex<Vec<HttpRequestLog>>>,
+}
+
+impl HttpRecorder {
+    pub(crate) fn record_request(&self, request: &reqwest::Request) {
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                    "<redacted>".to_string()
+                } else {
+                    value.to_str().unwrap_or("<binary>").to_string()
+                };
+                (name.to_string(), value)
+            })
+            .collect();
+        let body = crate::request::request_body(request)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+
+        self.requests.lock().unwrap().push(HttpRequestLog {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers,
+            body,
+            response_status: None,
+            response_body: None,
+        });
+    }
+
+    pub(crate) fn record_response(&self, status: Option<u16>, body: Option<String>) {
+        if let Some(request) = self.requests.lock().unwrap().last_mut() {
+            if status.is_some() {
+                request.response_status = status;
+            }
+            if body.is_some() {
+                request.response_body = body;
+            }
+        }
+    }
+
+    fn take(&self) -> Vec<HttpRequestLog> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(input: Option<u64>, output: Option<u64>, selected: bool) -> LLMCallLog {
+        LLMCallLog {
+            client: if selected { "B" } else { "A" }.to_string(),
+            model: None,
+            scope: String::new(),
+            retry: 0,
+            start_time_ms: 0,
+            latency_ms: 0,
+            usage: Usage {
+                input_tokens: input,
+                output_tokens: output,
+            },
+            finish_reason: None,
+            grounding: None,
+            thinking: None,
+            logprobs: None,
+            raw_response: None,
+            candidates: None,
+            error: None,
+            selected,
+        }
+    }
+
+    #[test]
+    fn sums_usage_and_finds_selected_client() {
+        let collector = Collector::new();
+        collector.logs.lock().unwrap().push(FunctionLog {
+            function_name: "Extract".to_string(),
+            start_time_ms: 0,
+            duration_ms: 0,
+            calls: vec![call(Some(10), None, false), call(Some(12), Some(5), true)],
+            http_requests: vec![],
+            error: None,
+            confidence: None,
+        });
+
+        let log = collector.last().unwrap();
+        assert_eq!(log.selected_client(), Some("B"));
+        let usage = collector.usage();
+        assert_eq!(usage.input_tokens, Some(22));
+        assert_eq!(usage.output_tokens, Some(5));
+
+        collector.clone().clear();
+        assert!(collector.logs().is_empty());
+    }
+
+    #[test]
+    fn redacts_credentials() {
+        let request = reqwest::Client::new()
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth("sk-secret")
+            .body("{}")
+            .build()
+            .unwrap();
+        let recorder = HttpRecorder::default();
+        recorder.record_request(&request);
+        recorder.record_response(Some(200), None);
+
+        let requests = recorder.take();
+        assert_eq!(requests[0].response_status, Some(200));
+        assert_eq!(requests[0].body.as_deref(), Some("{}"));
+        assert!(requests[0]
+            .headers
+            .iter()
+            .all(|(_, value)| !value.contains("sk-secret")));
+    }
+}
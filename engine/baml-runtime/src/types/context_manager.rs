@@ -7,9 +7,12 @@ use anyhow::{Context, Result};
 use baml_types::BamlValue;
 use std::fmt;
 
-use crate::{client_registry::ClientRegistry, type_builder::TypeBuilder, RuntimeContext, SpanCtx};
+use crate::{
+    client_registry::ClientRegistry, tracing::TraceParent, type_builder::TypeBuilder,
+    RuntimeContext, SpanCtx,
+};
 
-use super::runtime_context::BamlSrcReader;
+use super::{runtime_context::BamlSrcReader, tags::validate_user_tags};
 
 type BamlContext = (uuid::Uuid, String, HashMap<String, BamlValue>);
 
@@ -19,6 +22,7 @@ pub struct RuntimeContextManager {
     context: Arc<Mutex<Vec<BamlContext>>>,
     env_vars: HashMap<String, String>,
     global_tags: Arc<Mutex<HashMap<String, BamlValue>>>,
+    trace_parent: Arc<Mutex<Option<TraceParent>>>,
 }
 
 impl fmt::Debug for RuntimeContextManager {
@@ -26,6 +30,7 @@ impl fmt::Debug for RuntimeContextManager {
         f.debug_struct("RuntimeContextManager")
             .field("context", &self.context.lock())
             .field("global_tags", &self.global_tags)
+            .field("trace_parent", &self.trace_parent)
             .finish()
     }
 }
@@ -38,6 +43,7 @@ impl RuntimeContextManager {
             context: Arc::new(Mutex::new(self.context.lock().unwrap().clone())),
             env_vars: self.env_vars.clone(),
             global_tags: Arc::new(Mutex::new(self.global_tags.lock().unwrap().clone())),
+            trace_parent: Arc::new(Mutex::new(self.trace_parent())),
         }
     }
 
@@ -50,10 +56,39 @@ impl RuntimeContextManager {
             context: Default::default(),
             env_vars,
             global_tags: Default::default(),
+            trace_parent: Default::default(),
+        }
+    }
+
+    /// Puts the spans started from now on in the application's trace
+    /// `trace_parent`, e.g. parsed from the `traceparent` header of the
+    /// request the application is handling. LLM requests are sent with
+    /// their function's span as `traceparent`.
+    pub fn set_trace_parent(&self, trace_parent: Option<TraceParent>) {
+        *self.trace_parent.lock().unwrap() = trace_parent;
+    }
+
+    pub fn trace_parent(&self) -> Option<TraceParent> {
+        *self.trace_parent.lock().unwrap()
+    }
+
+    /// Adds tags from user code. Fails if a key is reserved or the tags are
+    /// too large; see [`super::tags`].
+    pub fn upsert_tags(&self, tags: HashMap<String, BamlValue>) -> Result<()> {
+        let mut ctx = self.context.lock().unwrap();
+        if let Some((.., last_tags)) = ctx.last_mut() {
+            validate_user_tags(last_tags, &tags)?;
+            last_tags.extend(tags);
+        } else {
+            let mut global_tags = self.global_tags.lock().unwrap();
+            validate_user_tags(&global_tags, &tags)?;
+            global_tags.extend(tags);
         }
+        Ok(())
     }
 
-    pub fn upsert_tags(&self, tags: HashMap<String, BamlValue>) {
+    /// Adds tags set by BAML itself, which may use reserved keys.
+    pub(crate) fn upsert_internal_tags(&self, tags: HashMap<String, BamlValue>) {
         let mut ctx = self.context.lock().unwrap();
         if let Some((.., last_tags)) = ctx.last_mut() {
             last_tags.extend(tags);
@@ -145,6 +180,14 @@ impl RuntimeContextManager {
         };
 
         ctx.client_overrides = client_overrides;
+        ctx.trace_parent =
+            self.trace_parent()
+                .map(|trace_parent| match self.context.lock().unwrap().last() {
+                    Some((span_id, ..)) => trace_parent.child(*span_id),
+                    None => trace_parent,
+                });
+        ctx.enum_matching = tb.and_then(|tb| tb.enum_matching());
+        ctx.number_locale = tb.map(|tb| tb.number_locale()).unwrap_or_default();
 
         Ok(ctx)
     }
@@ -0,0 +1,201 @@
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+
+use crate::internal::llm_client::{LLMCompleteResponse, LLMResponse};
+use crate::FunctionResult;
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub cached_input: f64,
+    pub output: f64,
+}
+
+impl ModelPrice {
+    /// The cost in USD of a request, where `cached_input` is part of `input`.
+    pub fn cost(&self, input: u64, cached_input: u64, output: u64) -> f64 {
+        ((input - cached_input) as f64 * self.input
+            + cached_input as f64 * self.cached_input
+            + output as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
+const fn price(input: f64, cached_input: f64, output: f64) -> ModelPrice {
+    ModelPrice {
+        input,
+        cached_input,
+        output,
+    }
+}
+
+/// List prices by model name prefix; the longest matching prefix wins, so
+/// `gpt-4o-mini` isn't priced as `gpt-4o`.
+const PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-4o-mini", price(0.15, 0.075, 0.6)),
+    ("gpt-4o", price(2.5, 1.25, 10.0)),
+    ("gpt-4-turbo", price(10.0, 10.0, 30.0)),
+    ("gpt-4", price(30.0, 30.0, 60.0)),
+    ("gpt-3.5-turbo", price(0.5, 0.5, 1.5)),
+    ("o1-mini", price(3.0, 1.5, 12.0)),
+    ("o1", price(15.0, 7.5, 60.0)),
+    ("claude-3-5-sonnet", price(3.0, 0.3, 15.0)),
+    ("claude-3-5-haiku", price(0.8, 0.08, 4.0)),
+    ("claude-3-opus", price(15.0, 1.5, 75.0)),
+    ("claude-3-sonnet", price(3.0, 3.0, 15.0)),
+    ("claude-3-haiku", price(0.25, 0.03, 1.25)),
+    ("gemini-1.5-pro", price(1.25, 0.3125, 5.0)),
+    ("gemini-1.5-flash", price(0.075, 0.01875, 0.3)),
+];
+
+/// The list price of `model`, ignoring any `provider/` or Bedrock
+/// `anthropic.` prefix. `None` for models we don't know.
+pub fn model_price(model: &str) -> Option<ModelPrice> {
+    by_longest_prefix(PRICES.iter().map(|(prefix, price)| (*prefix, price)), model)
+}
+
+fn by_longest_prefix<'a>(
+    prices: impl Iterator<Item = (&'a str, &'a ModelPrice)>,
+    model: &str,
+) -> Option<ModelPrice> {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let model = model.strip_prefix("anthropic.").unwrap_or(model);
+    prices
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+}
+
+/// The prices to estimate cost with: the list prices of [`model_price`],
+/// with overrides for negotiated rates or models it doesn't know, like
+/// fine-tunes or self-hosted models.
+#[derive(Debug, Clone, Default)]
+pub struct PriceTable {
+    overrides: IndexMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prices models starting with `prefix` at `price`. Overrides take
+    /// precedence over list prices, the longest matching prefix first.
+    pub fn set_price(&mut self, prefix: impl Into<String>, price: ModelPrice) {
+        self.overrides.insert(prefix.into(), price);
+    }
+
+    pub fn with_price(mut self, prefix: impl Into<String>, price: ModelPrice) -> Self {
+        self.set_price(prefix, price);
+        self
+    }
+
+    /// The price of `model`. `None` for models neither overridden nor known.
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        by_longest_prefix(
+            self.overrides
+                .iter()
+                .map(|(prefix, price)| (prefix.as_str(), price)),
+            model,
+        )
+        .or_else(|| model_price(model))
+    }
+}
+
+/// Token usage and estimated cost summed over function calls, including
+/// retries and fallbacks.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Part of `input_tokens`.
+    pub cached_input_tokens: u64,
+    /// Only covers models with a known price, see `unpriced_models`.
+    pub cost_usd: f64,
+    pub unpriced_models: IndexSet<String>,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Adds the usage of every request of `result`, at list prices.
+    pub fn record(&mut self, result: &FunctionResult) {
+        self.record_with_prices(result, &PriceTable::default());
+    }
+
+    pub fn record_with_prices(&mut self, result: &FunctionResult, prices: &PriceTable) {
+        for (_, response, _, _) in result.event_chain() {
+            if let LLMResponse::Success(response) = response {
+                self.record_response(response, prices);
+            }
+        }
+    }
+
+    pub(crate) fn record_response(&mut self, response: &LLMCompleteResponse, prices: &PriceTable) {
+        let metadata = &response.metadata;
+        let input = metadata.prompt_tokens.unwrap_or(0);
+        let output = metadata.output_tokens.unwrap_or(0);
+        let cached = metadata.cached_input_tokens.unwrap_or(0).min(input);
+        self.input_tokens += input;
+        self.output_tokens += output;
+        self.cached_input_tokens += cached;
+
+        match prices.price(&response.model) {
+            Some(price) => self.cost_usd += price.cost(input, cached, output),
+            None => {
+                self.unpriced_models.insert(response.model.clone());
+            }
+        }
+    }
+
+    pub(crate) fn add(&mut self, other: &UsageTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.cost_usd += other.cost_usd;
+        self.unpriced_models
+            .extend(other.unpriced_models.iter().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_by_longest_prefix() {
+        assert_eq!(model_price("gpt-4o-mini-2024-07-18").unwrap().input, 0.15);
+        assert_eq!(model_price("gpt-4o-2024-08-06").unwrap().input, 2.5);
+        assert_eq!(model_price("gpt-4").unwrap().input, 30.0);
+        assert_eq!(
+            model_price("anthropic.claude-3-5-sonnet-20240620-v1:0")
+                .unwrap()
+                .output,
+            15.0
+        );
+        assert_eq!(model_price("openai/o1-mini").unwrap().input, 3.0);
+        assert_eq!(model_price("llama3.1"), None);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let prices = PriceTable::new()
+            .with_price("gpt-4o", price(2.0, 1.0, 8.0))
+            .with_price("llama3", price(0.1, 0.1, 0.1));
+        assert_eq!(prices.price("gpt-4o-2024-08-06").unwrap().input, 2.0);
+        // The override is a shorter prefix than the list price, but wins.
+        assert_eq!(prices.price("gpt-4o-mini").unwrap().input, 2.0);
+        assert_eq!(prices.price("gpt-4").unwrap().input, 30.0);
+        assert_eq!(prices.price("ollama/llama3.1").unwrap().output, 0.1);
+        assert_eq!(prices.price("mistral-large"), None);
+    }
+
+    #[test]
+    fn costs_cached_input_separately() {
+        let cost = price(2.0, 1.0, 8.0).cost(1_000_000, 500_000, 250_000);
+        assert!((cost - 3.5).abs() < 1e-9);
+    }
+}
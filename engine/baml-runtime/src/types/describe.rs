@@ -0,0 +1,184 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+/// Option keys with any of these words, e.g. `api_key` or `X-Auth-Token`, are
+/// redacted in [`ClientDescription`].
+const REDACTED_OPTION_WORDS: &[&str] = &[
+    "key",
+    "apikey",
+    "secret",
+    "token",
+    "password",
+    "authorization",
+    "credentials",
+];
+
+/// What a runtime was loaded with, see [`crate::BamlRuntime::describe`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeDescription {
+    pub baml_version: String,
+    pub functions: Vec<FunctionDescription>,
+    pub clients: Vec<ClientDescription>,
+    pub generators: Vec<GeneratorDescription>,
+    /// Environment variables referenced by clients, sorted.
+    pub required_env_vars: Vec<String>,
+    /// The subset of `required_env_vars` that is not set.
+    pub missing_env_vars: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionDescription {
+    pub name: String,
+    pub client: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientDescription {
+    pub name: String,
+    pub provider: String,
+    pub retry_policy: Option<String>,
+    /// The resolved request options, with credentials redacted. Empty for
+    /// strategies and for clients that failed to resolve.
+    pub options: IndexMap<String, serde_json::Value>,
+    /// Why the client could not be resolved, e.g. a missing env var.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratorDescription {
+    pub name: String,
+    /// `python/pydantic`, `typescript`, ..., or `boundary-cloud`.
+    pub output_type: String,
+    pub version: String,
+    pub output_dir: Option<String>,
+}
+
+pub(crate) fn redact_options<'a>(
+    options: impl IntoIterator<Item = (&'a String, &'a serde_json::Value)>,
+) -> IndexMap<String, serde_json::Value> {
+    options
+        .into_iter()
+        .map(|(key, value)| {
+            let lower = key.to_lowercase();
+            let sensitive = lower
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .any(|word| REDACTED_OPTION_WORDS.contains(&word));
+            let value = if sensitive {
+                serde_json::Value::String("<redacted>".to_string())
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+impl fmt::Display for RuntimeDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "BAML runtime v{}", self.baml_version)?;
+        writeln!(f, "Functions ({}):", self.functions.len())?;
+        for function in &self.functions {
+            match &function.client {
+                Some(client) => writeln!(f, "  {} -> {}", function.name, client)?,
+                None => writeln!(f, "  {}", function.name)?,
+            }
+        }
+        writeln!(f, "Clients ({}):", self.clients.len())?;
+        for client in &self.clients {
+            write!(f, "  {} ({})", client.name, client.provider)?;
+            if let Some(policy) = &client.retry_policy {
+                write!(f, " retry={policy}")?;
+            }
+            if !client.options.is_empty() {
+                write!(
+                    f,
+                    " {}",
+                    serde_json::to_string(&client.options).unwrap_or_default()
+                )?;
+            }
+            if let Some(error) = &client.error {
+                write!(f, " error: {error}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "Generators ({}):", self.generators.len())?;
+        for generator in &self.generators {
+            write!(
+                f,
+                "  {} ({} v{})",
+                generator.name, generator.output_type, generator.version
+            )?;
+            if let Some(dir) = &generator.output_dir {
+                write!(f, " -> {dir}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "Env vars: {} required, missing: {}",
+            self.required_env_vars.len(),
+            if self.missing_env_vars.is_empty() {
+                "none".to_string()
+            } else {
+                self.missing_env_vars.join(", ")
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_credentials() {
+        let options = IndexMap::from([
+            ("model".to_string(), json!("gpt-4o")),
+            ("api_key".to_string(), json!("sk-secret")),
+            ("X-Auth-Token".to_string(), json!("abc")),
+            ("max_tokens".to_string(), json!(100)),
+        ]);
+        let redacted = redact_options(&options);
+        assert_eq!(redacted["model"], json!("gpt-4o"));
+        assert_eq!(redacted["api_key"], json!("<redacted>"));
+        assert_eq!(redacted["X-Auth-Token"], json!("<redacted>"));
+        assert_eq!(redacted["max_tokens"], json!(100));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn describes_runtime() {
+        let files = std::collections::HashMap::from([(
+            "main.baml",
+            r##"
+            client<llm> GPT4 {
+              provider openai
+              options {
+                model gpt-4o
+                api_key env.OPENAI_API_KEY
+              }
+            }
+
+            function Echo(input: string) -> string {
+              client GPT4
+              prompt #"{{ input }}"#
+            }
+            "##,
+        )]);
+        let runtime = crate::BamlRuntime::from_file_content(
+            "baml_src",
+            &files,
+            std::collections::HashMap::<&str, &str>::new(),
+        )
+        .unwrap();
+
+        let description = runtime.describe();
+        assert_eq!(description.functions[0].client.as_deref(), Some("GPT4"));
+        assert_eq!(description.clients[0].provider, "openai");
+        assert_eq!(description.missing_env_vars, vec!["OPENAI_API_KEY"]);
+        assert!(description.to_string().contains("missing: OPENAI_API_KEY"));
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::{Arc, RwLock};
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::internal::llm_client::{LLMCompleteResponse, LLMResponse};
+
+/// Runs around every LLM request a [`crate::BamlRuntime`] makes, including
+/// retries and fallbacks, see [`crate::BamlRuntime::add_hook`].
+///
+/// Every method does nothing by default.
+pub trait RuntimeHook: Send + Sync {
+    /// Called with each request before it's sent, e.g. to add headers or
+    /// redact the prompt. An error fails the request without sending it.
+    fn on_before_request(&self, _request: &mut HookRequest) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with each response, or the failure to get one.
+    fn on_after_response(&self, _response: &LLMResponse) {}
+
+    /// Called when a response can't be parsed into the function's output
+    /// type.
+    fn on_parse_error(&self, _response: &LLMCompleteResponse, _error: &anyhow::Error) {}
+}
+
+/// An HTTP request to an LLM, as [`RuntimeHook::on_before_request`] gets it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookRequest {
+    /// The client sending the request. Changing it has no effect.
+    pub client: String,
+    pub url: String,
+    /// Header names are lowercase.
+    pub headers: IndexMap<String, String>,
+    /// The JSON body, `None` if the request has none.
+    pub body: Option<serde_json::Value>,
+}
+
+/// The hooks registered on a runtime, in order. Cheap to clone and clones
+/// share their hooks.
+#[derive(Clone, Default)]
+pub struct RuntimeHooks {
+    hooks: Arc<RwLock<Vec<Arc<dyn RuntimeHook>>>>,
+}
+
+impl RuntimeHooks {
+    pub fn add(&self, hook: Arc<dyn RuntimeHook>) {
+        self.hooks.write().unwrap().push(hook);
+    }
+
+    pub fn clear(&self) {
+        self.hooks.write().unwrap().clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.read().unwrap().is_empty()
+    }
+
+    /// Hooks run without the lock held, so they can register others.
+    fn snapshot(&self) -> Vec<Arc<dyn RuntimeHook>> {
+        self.hooks.read().unwrap().clone()
+    }
+
+    /// Runs the `on_before_request` hooks and applies their changes to
+    /// `request`.
+    pub(crate) fn before_request(
+        &self,
+        client: &str,
+        request: &mut reqwest::Request,
+    ) -> Result<()> {
+        let hooks = self.snapshot();
+        if hooks.is_empty() {
+            return Ok(());
+        }
+        let headers = request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect::<IndexMap<_, _>>();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(bytes).ok());
+        let mut hook_request = HookRequest {
+            client: client.to_string(),
+            url: request.url().to_string(),
+            headers: headers.clone(),
+            body: body.clone(),
+        };
+        for hook in hooks {
+            hook.on_before_request(&mut hook_request)?;
+        }
+
+        if hook_request.url != request.url().as_str() {
+            *request.url_mut() = hook_request
+                .url
+                .parse()
+                .with_context(|| format!("Hook set an invalid URL: {}", hook_request.url))?;
+        }
+        if hook_request.headers != headers {
+            let mut headers = reqwest::header::HeaderMap::new();
+            for (name, value) in &hook_request.headers {
+                headers.insert(
+                    reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                        .with_context(|| format!("Hook set an invalid header name: {name}"))?,
+                    reqwest::header::HeaderValue::from_str(value)
+                        .with_context(|| format!("Hook set an invalid value for header {name}"))?,
+                );
+            }
+            *request.headers_mut() = headers;
+        }
+        if hook_request.body != body {
+            *request.body_mut() = hook_request
+                .body
+                .map(|body| serde_json::to_vec(&body))
+                .transpose()?
+                .map(Into::into);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn after_response(&self, response: &LLMResponse) {
+        for hook in self.snapshot() {
+            hook.on_after_response(response);
+        }
+    }
+
+    pub(crate) fn parse_error(&self, response: &LLMCompleteResponse, error: &anyhow::Error) {
+        for hook in self.snapshot() {
+            hook.on_parse_error(response, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct AddHeader;
+
+    impl RuntimeHook for AddHeader {
+        fn on_before_request(&self, request: &mut HookRequest) -> Result<()> {
+            request
+                .headers
+                .insert("x-request-source".to_string(), "batch".to_string());
+            if let Some(body) = &mut request.body {
+                body["messages"] = serde_json::json!([]);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn applies_request_changes() {
+        let mut request = reqwest::Client::new()
+            .post("https://api.openai.com/v1/chat/completions")
+            .bearer_auth("sk-test")
+            .json(&serde_json::json!({"model": "gpt-4o", "messages": [{"role": "user"}]}))
+            .build()
+            .unwrap();
+
+        let hooks = RuntimeHooks::default();
+        hooks.before_request("GPT4o", &mut request).unwrap();
+        assert!(request.headers().get("x-request-source").is_none());
+
+        hooks.add(Arc::new(AddHeader));
+        hooks.before_request("GPT4o", &mut request).unwrap();
+        assert_eq!(request.headers()["x-request-source"], "batch");
+        assert_eq!(request.headers()["authorization"], "Bearer sk-test");
+        let body: serde_json::Value =
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({"model": "gpt-4o", "messages": []}));
+    }
+}
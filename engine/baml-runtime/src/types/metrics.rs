@@ -0,0 +1,471 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crate::internal::llm_client::LLMResponse;
+use crate::FunctionResult;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Counts calls, errors, retries, latency and token usage per function and
+/// per client, and renders them in the Prometheus text format.
+///
+/// Metrics are off unless a registry is attached with
+/// [`crate::BamlRuntime::set_metrics`]. A registry is cheap to clone and
+/// clones share their counts, so an embedder can keep one to scrape.
+#[derive(Clone, Default)]
+pub struct MetricsRegistry {
+    inner: Arc<Mutex<Metrics>>,
+}
+
+#[derive(Default)]
+struct Metrics {
+    functions: BTreeMap<String, FunctionMetrics>,
+    /// Keyed by function and client.
+    clients: BTreeMap<(String, String), ClientMetrics>,
+}
+
+#[derive(Default)]
+struct FunctionMetrics {
+    calls: u64,
+    errors: BTreeMap<&'static str, u64>,
+    duration: Histogram,
+}
+
+#[derive(Default)]
+struct ClientMetrics {
+    requests: u64,
+    retries: u64,
+    errors: BTreeMap<&'static str, u64>,
+    latency: Histogram,
+    input_tokens: u64,
+    output_tokens: u64,
+    cached_input_tokens: u64,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Per bucket of `LATENCY_BUCKETS`, plus a final `+Inf` bucket.
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything recorded so far, in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        self.inner.lock().unwrap().render()
+    }
+
+    pub fn reset(&self) {
+        *self.inner.lock().unwrap() = Metrics::default();
+    }
+
+    pub(crate) fn start(&self, function_name: &str) -> MetricsCall {
+        MetricsCall {
+            metrics: self.clone(),
+            function_name: function_name.to_string(),
+            start: web_time::Instant::now(),
+        }
+    }
+
+    fn record(
+        &self,
+        function_name: &str,
+        duration: web_time::Duration,
+        result: &Result<FunctionResult>,
+    ) {
+        let mut metrics = self.inner.lock().unwrap();
+
+        let function = metrics
+            .functions
+            .entry(function_name.to_string())
+            .or_default();
+        function.calls += 1;
+        function.duration.observe(duration.as_secs_f64());
+        if let Some(code) = function_error(result) {
+            *function.errors.entry(code).or_default() += 1;
+        }
+
+        let Ok(result) = result else {
+            return;
+        };
+        for (scope, response, _, value) in result.event_chain() {
+            let client_name = match response {
+                LLMResponse::Success(r) => r.client.clone(),
+                LLMResponse::LLMFailure(e) => e.client.clone(),
+                _ => scope.direct_client_name().cloned().unwrap_or_default(),
+            };
+            let client = metrics
+                .clients
+                .entry((function_name.to_string(), client_name))
+                .or_default();
+            client.requests += 1;
+            if scope.retry_index() > 0 {
+                client.retries += 1;
+            }
+            let error = match response {
+                LLMResponse::Success(r) => {
+                    client.latency.observe(r.latency.as_secs_f64());
+                    client.input_tokens += r.metadata.prompt_tokens.unwrap_or(0);
+                    client.output_tokens += r.metadata.output_tokens.unwrap_or(0);
+                    client.cached_input_tokens += r.metadata.cached_input_tokens.unwrap_or(0);
+                    matches!(value, Some(Err(_))).then_some("parse_error")
+                }
+                LLMResponse::LLMFailure(e) => {
+                    client.latency.observe(e.latency.as_secs_f64());
+                    Some(e.code.name())
+                }
+                LLMResponse::UserFailure(_) => Some("user_failure"),
+                LLMResponse::InternalFailure(_) => Some("internal_failure"),
+            };
+            if let Some(code) = error {
+                *client.errors.entry(code).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// Why a function call failed, `None` if it succeeded.
+fn function_error(result: &Result<FunctionResult>) -> Option<&'static str> {
+    let Ok(result) = result else {
+        return Some("internal_failure");
+    };
+    match result.llm_response() {
+        LLMResponse::Success(_) => match result.result_with_constraints() {
+            Some(Ok(_)) => None,
+            _ => Some("parse_error"),
+        },
+        LLMResponse::LLMFailure(e) => Some(e.code.name()),
+        LLMResponse::UserFailure(_) => Some("user_failure"),
+        LLMResponse::InternalFailure(_) => Some("internal_failure"),
+    }
+}
+
+/// A function call in progress, recorded when it finishes.
+pub(crate) struct MetricsCall {
+    metrics: MetricsRegistry,
+    function_name: String,
+    start: web_time::Instant,
+}
+
+impl MetricsCall {
+    pub(crate) fn finish(self, result: &Result<FunctionResult>) {
+        self.metrics
+            .record(&self.function_name, self.start.elapsed(), result);
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        let mut cumulative = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            cumulative += count;
+            let le = LATENCY_BUCKETS
+                .get(i)
+                .map_or("+Inf".to_string(), |bound| bound.to_string());
+            let mut labels = labels.to_vec();
+            labels.push(("le", &le));
+            sample(out, &format!("{name}_bucket"), &labels, cumulative);
+        }
+        sample(out, &format!("{name}_sum"), labels, self.sum);
+        sample(out, &format!("{name}_count"), labels, self.count);
+    }
+}
+
+impl Metrics {
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "baml_function_calls_total",
+            "counter",
+            "Function calls, including failed ones.",
+        );
+        for (function, m) in &self.functions {
+            let function = function.as_str();
+            sample(
+                &mut out,
+                "baml_function_calls_total",
+                &[("function", function)],
+                m.calls,
+            );
+        }
+        header(
+            &mut out,
+            "baml_function_errors_total",
+            "counter",
+            "Failed function calls by error code.",
+        );
+        for (function, m) in &self.functions {
+            let function = function.as_str();
+            for (&code, count) in &m.errors {
+                sample(
+                    &mut out,
+                    "baml_function_errors_total",
+                    &[("function", function), ("code", code)],
+                    count,
+                );
+            }
+        }
+        header(
+            &mut out,
+            "baml_function_duration_seconds",
+            "histogram",
+            "Function call duration, including retries and fallbacks.",
+        );
+        for (function, m) in &self.functions {
+            let function = function.as_str();
+            m.duration.render(
+                &mut out,
+                "baml_function_duration_seconds",
+                &[("function", function)],
+            );
+        }
+
+        header(
+            &mut out,
+            "baml_llm_requests_total",
+            "counter",
+            "Requests made to a client.",
+        );
+        for ((function, client), m) in &self.clients {
+            let (function, client) = (function.as_str(), client.as_str());
+            sample(
+                &mut out,
+                "baml_llm_requests_total",
+                &[("function", function), ("client", client)],
+                m.requests,
+            );
+        }
+        header(
+            &mut out,
+            "baml_llm_retries_total",
+            "counter",
+            "Requests made by a retry policy after the first attempt.",
+        );
+        for ((function, client), m) in &self.clients {
+            let (function, client) = (function.as_str(), client.as_str());
+            sample(
+                &mut out,
+                "baml_llm_retries_total",
+                &[("function", function), ("client", client)],
+                m.retries,
+            );
+        }
+        header(
+            &mut out,
+            "baml_llm_errors_total",
+            "counter",
+            "Failed requests to a client by error code.",
+        );
+        for ((function, client), m) in &self.clients {
+            let (function, client) = (function.as_str(), client.as_str());
+            for (&code, count) in &m.errors {
+                sample(
+                    &mut out,
+                    "baml_llm_errors_total",
+                    &[("function", function), ("client", client), ("code", code)],
+                    count,
+                );
+            }
+        }
+        header(
+            &mut out,
+            "baml_llm_request_duration_seconds",
+            "histogram",
+            "Latency of requests to a client.",
+        );
+        for ((function, client), m) in &self.clients {
+            let (function, client) = (function.as_str(), client.as_str());
+            m.latency.render(
+                &mut out,
+                "baml_llm_request_duration_seconds",
+                &[("function", function), ("client", client)],
+            );
+        }
+        header(
+            &mut out,
+            "baml_llm_tokens_total",
+            "counter",
+            "Tokens reported by the provider.",
+        );
+        for ((function, client), m) in &self.clients {
+            let (function, client) = (function.as_str(), client.as_str());
+            for (kind, count) in [
+                ("input", m.input_tokens),
+                ("output", m.output_tokens),
+                ("cached_input", m.cached_input_tokens),
+            ] {
+                sample(
+                    &mut out,
+                    "baml_llm_tokens_total",
+                    &[("function", function), ("client", client), ("type", kind)],
+                    count,
+                );
+            }
+        }
+        header(
+            &mut out,
+            "baml_llm_prompt_cache_hit_ratio",
+            "gauge",
+            "Share of input tokens served from the provider's prompt cache.",
+        );
+        for ((function, client), m) in &self.clients {
+            let (function, client) = (function.as_str(), client.as_str());
+            if m.input_tokens > 0 {
+                let ratio = m.cached_input_tokens as f64 / m.input_tokens as f64;
+                sample(
+                    &mut out,
+                    "baml_llm_prompt_cache_hit_ratio",
+                    &[("function", function), ("client", client)],
+                    ratio,
+                );
+            }
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+    let _ = writeln!(out, "{name}{{{labels}}} {value}");
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::llm_client::{
+        orchestrator::{ExecutionScope, OrchestrationScope},
+        ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse,
+    };
+    use baml_types::BamlValueWithMeta;
+    use internal_baml_jinja::RenderedPrompt;
+
+    fn scope(retry: usize) -> OrchestrationScope {
+        OrchestrationScope {
+            scope: vec![
+                ExecutionScope::Retry("Policy".to_string(), retry, Default::default()),
+                ExecutionScope::Direct("GPT4".to_string()),
+            ],
+        }
+    }
+
+    fn failure(code: ErrorCode) -> LLMResponse {
+        LLMResponse::LLMFailure(LLMErrorResponse {
+            client: "GPT4".to_string(),
+            model: None,
+            prompt: RenderedPrompt::Completion(String::new()),
+            request_options: Default::default(),
+            start_time: web_time::SystemTime::UNIX_EPOCH,
+            latency: web_time::Duration::from_millis(200),
+            message: String::new(),
+            code,
+        })
+    }
+
+    fn success() -> LLMResponse {
+        LLMResponse::Success(LLMCompleteResponse {
+            client: "GPT4".to_string(),
+            model: "gpt-4o".to_string(),
+            prompt: RenderedPrompt::Completion(String::new()),
+            request_options: Default::default(),
+            content: String::new(),
+            start_time: web_time::SystemTime::UNIX_EPOCH,
+            latency: web_time::Duration::from_millis(3000),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: None,
+                prompt_tokens: Some(100),
+                output_tokens: Some(20),
+                total_tokens: Some(120),
+                cached_input_tokens: Some(25),
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        })
+    }
+
+    #[test]
+    fn records_retries_errors_and_tokens() {
+        let metrics = MetricsRegistry::new();
+        let result = FunctionResult::new_chain(vec![
+            (scope(0), failure(ErrorCode::RateLimited), None, None),
+            (
+                scope(1),
+                success(),
+                None,
+                Some(Ok(BamlValueWithMeta::String("ok".to_string(), vec![]))),
+            ),
+        ]);
+        metrics.record("Extract", web_time::Duration::from_millis(3500), &result);
+        metrics.record(
+            "Extract",
+            web_time::Duration::from_millis(10),
+            &Err(anyhow::anyhow!("bad arguments")),
+        );
+
+        let text = metrics.render();
+        for line in [
+            r#"baml_function_calls_total{function="Extract"} 2"#,
+            r#"baml_function_errors_total{function="Extract",code="internal_failure"} 1"#,
+            r#"baml_function_duration_seconds_bucket{function="Extract",le="0.1"} 1"#,
+            r#"baml_function_duration_seconds_bucket{function="Extract",le="5"} 2"#,
+            r#"baml_llm_requests_total{function="Extract",client="GPT4"} 2"#,
+            r#"baml_llm_retries_total{function="Extract",client="GPT4"} 1"#,
+            r#"baml_llm_errors_total{function="Extract",client="GPT4",code="rate_limited"} 1"#,
+            r#"baml_llm_request_duration_seconds_count{function="Extract",client="GPT4"} 2"#,
+            r#"baml_llm_tokens_total{function="Extract",client="GPT4",type="cached_input"} 25"#,
+            r#"baml_llm_prompt_cache_hit_ratio{function="Extract",client="GPT4"} 0.25"#,
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line} in\n{text}");
+        }
+
+        metrics.clone().reset();
+        assert!(!metrics.render().contains("Extract"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
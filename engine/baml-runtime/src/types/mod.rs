@@ -1,16 +1,48 @@
+mod attempts;
+mod batch;
+mod collector;
 mod context_manager;
+mod cost;
+mod describe;
+mod hooks;
 // mod expression_helper;
+mod metrics;
 pub mod on_log_event;
+mod post_process;
+mod readiness;
+mod reask;
 mod response;
 pub(crate) mod runtime_context;
+mod stepper;
 mod stream;
+mod stream_delta;
+pub mod tags;
 mod trace_stats;
+mod usage;
 
+pub use attempts::{Attempt, AttemptOutcome, NextStep};
+pub use batch::BatchOptions;
+pub(crate) use batch::Pacer;
+pub use collector::{Collector, FunctionLog, HttpRequestLog, LLMCallLog, Usage};
+pub(crate) use collector::{CollectorCall, HttpRecorder};
 pub use context_manager::RuntimeContextManager;
-pub use response::{FunctionResult, TestFailReason, TestResponse, TestStatus};
+pub use cost::{model_price, ModelPrice, PriceTable, UsageTotals};
+pub(crate) use describe::redact_options;
+pub use describe::{
+    ClientDescription, FunctionDescription, GeneratorDescription, RuntimeDescription,
+};
+pub use hooks::{HookRequest, RuntimeHook, RuntimeHooks};
+pub use metrics::{MetricsRegistry, LATENCY_BUCKETS};
+pub use post_process::{PostProcessor, PostProcessors};
+pub use readiness::{CheckStatus, ReadinessCheck, ReadinessOptions, ReadinessReport};
+pub use reask::Reask;
+pub use response::{DryRunRequest, FunctionResult, TestFailReason, TestResponse, TestStatus};
 pub use runtime_context::{RuntimeContext, SpanCtx};
-pub use stream::FunctionResultStream;
+pub use stepper::{CallCheckpoint, CallStep, CheckpointStep, FunctionCallStepper};
+pub use stream::{CancelHandle, FunctionResultStream};
+pub use stream_delta::{StreamDeltaEncoder, StreamMode, StreamPatch};
 pub use trace_stats::{InnerTraceStats, TraceStats};
+pub use usage::{UsageEntry, UsageReport, UsageTracker};
 
 #[derive(Debug, Clone, Copy)]
 pub struct RenderCurlSettings {
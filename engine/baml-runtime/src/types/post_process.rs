@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Result;
+
+use crate::internal::llm_client::ResponseBamlValue;
+
+/// Runs on the result of a function once it parsed and its checks ran, see
+/// [`crate::BamlRuntime::add_post_processor`].
+pub trait PostProcessor: Send + Sync {
+    /// Returns the value the call returns instead. An error fails the call
+    /// with it.
+    fn process(&self, value: ResponseBamlValue) -> Result<ResponseBamlValue>;
+}
+
+/// The post-processors registered on a runtime, by function, in order. Cheap
+/// to clone and clones share their post-processors.
+#[derive(Clone, Default)]
+pub struct PostProcessors {
+    by_function: Arc<RwLock<HashMap<String, Vec<Arc<dyn PostProcessor>>>>>,
+}
+
+impl PostProcessors {
+    pub fn add(&self, function_name: &str, processor: Arc<dyn PostProcessor>) {
+        self.by_function
+            .write()
+            .unwrap()
+            .entry(function_name.to_string())
+            .or_default()
+            .push(processor);
+    }
+
+    pub fn clear(&self) {
+        self.by_function.write().unwrap().clear();
+    }
+
+    /// The post-processors of `function_name`. Returned without the lock
+    /// held, so they can register others.
+    pub(crate) fn get(&self, function_name: &str) -> Vec<Arc<dyn PostProcessor>> {
+        self.by_function
+            .read()
+            .unwrap()
+            .get(function_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use baml_types::{BamlValue, BamlValueWithMeta};
+
+    use super::*;
+
+    struct Append(&'static str);
+
+    impl PostProcessor for Append {
+        fn process(&self, value: ResponseBamlValue) -> Result<ResponseBamlValue> {
+            let BamlValueWithMeta::String(s, meta) = value else {
+                anyhow::bail!("expected a string");
+            };
+            Ok(BamlValueWithMeta::String(s + self.0, meta))
+        }
+    }
+
+    #[test]
+    fn runs_in_order_per_function() {
+        let processors = PostProcessors::default();
+        processors.add("Summarize", Arc::new(Append(" [1]")));
+        processors.add("Classify", Arc::new(Append(" [x]")));
+        processors.add("Summarize", Arc::new(Append(" [2]")));
+
+        let value = ResponseBamlValue::with_default_meta(&BamlValue::String("ok".to_string()));
+        let value = processors
+            .get("Summarize")
+            .iter()
+            .try_fold(value, |value, processor| processor.process(value))
+            .unwrap();
+        assert_eq!(value.value(), BamlValue::String("ok [1] [2]".to_string()));
+        assert!(processors.get("Extract").is_empty());
+
+        processors.clear();
+        assert!(processors.get("Summarize").is_empty());
+    }
+}
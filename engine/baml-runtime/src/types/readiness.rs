@@ -0,0 +1,113 @@
+use serde::Serialize;
+
+/// What [`crate::BamlRuntime::readiness`] checks beyond the IR and
+/// environment variables.
+#[derive(Debug, Clone)]
+pub struct ReadinessOptions {
+    /// Sends a `HEAD` request to the host of every client. Any HTTP response,
+    /// even an error status, counts as reachable.
+    pub check_providers: bool,
+    /// Per provider check.
+    pub timeout: web_time::Duration,
+}
+
+impl Default for ReadinessOptions {
+    fn default() -> Self {
+        Self {
+            check_providers: false,
+            timeout: web_time::Duration::from_secs(5),
+        }
+    }
+}
+
+/// Whether a runtime is ready to serve calls, e.g. for a Kubernetes readiness
+/// probe. Ready if no check failed; skipped checks don't count.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessCheck {
+    /// `ir`, `env_vars` or `client:<name>`.
+    pub name: String,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+impl ReadinessReport {
+    pub(crate) fn new(checks: Vec<ReadinessCheck>) -> Self {
+        Self {
+            ready: checks.iter().all(|c| c.status != CheckStatus::Fail),
+            checks,
+        }
+    }
+}
+
+impl ReadinessCheck {
+    pub(crate) fn new(
+        name: impl Into<String>,
+        status: CheckStatus,
+        detail: Option<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail,
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::BamlRuntime;
+
+    const BAML: &str = r##"
+        client<llm> GPT4 {
+          provider openai
+          options {
+            model gpt-4o
+            api_key env.OPENAI_API_KEY
+          }
+        }
+
+        function Echo(input: string) -> string {
+          client GPT4
+          prompt #"{{ input }}"#
+        }
+    "##;
+
+    fn readiness(env_vars: &[(&str, &str)]) -> ReadinessReport {
+        let files = HashMap::from([("main.baml", BAML)]);
+        let runtime =
+            BamlRuntime::from_file_content("baml_src", &files, env_vars.iter().copied().collect())
+                .unwrap();
+        runtime
+            .async_runtime
+            .block_on(runtime.readiness(&ReadinessOptions::default()))
+    }
+
+    #[test]
+    fn fails_on_missing_env_vars() {
+        let report = readiness(&[]);
+        assert!(!report.ready);
+        let env_vars = report.checks.iter().find(|c| c.name == "env_vars").unwrap();
+        assert_eq!(env_vars.status, CheckStatus::Fail);
+        assert_eq!(env_vars.detail.as_deref(), Some("Missing OPENAI_API_KEY"));
+
+        assert!(readiness(&[("OPENAI_API_KEY", "sk-test")]).ready);
+    }
+}
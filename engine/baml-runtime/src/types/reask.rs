@@ -0,0 +1,143 @@
+use internal_baml_jinja::{ChatMessagePart, RenderedChatMessage, RenderedPrompt};
+
+use crate::{internal::llm_client::LLMResponse, FunctionResult};
+
+/// What a follow-up call of a function is told about a previous call, see
+/// [`crate::BamlRuntime::reask`].
+#[derive(Debug, Clone, Default)]
+pub struct Reask {
+    /// The previous response, as the LLM wrote it.
+    pub raw_output: Option<String>,
+    /// The previous result, as far as it parsed.
+    pub value: Option<serde_json::Value>,
+    /// What was wrong with the previous result.
+    pub errors: Vec<String>,
+    /// What to do differently this time.
+    pub instructions: Option<String>,
+}
+
+impl Reask {
+    /// Takes the response, parsed value, parse error and failed checks of
+    /// `result`.
+    pub fn from_result(result: &FunctionResult, instructions: Option<String>) -> Self {
+        let raw_output = match result.llm_response() {
+            LLMResponse::Success(response) => Some(response.content.clone()),
+            _ => None,
+        };
+        let mut errors = vec![];
+        let value = match result.result_with_constraints() {
+            Some(Ok(value)) => {
+                errors.extend(value.iter().flat_map(|v| v.meta()).filter_map(|check| {
+                    (check.status == "failed")
+                        .then(|| format!("Check `{}` failed: {}", check.name, check.expression))
+                }));
+                serde_json::to_value(value.clone().value()).ok()
+            }
+            Some(Err(e)) => {
+                errors.push(format!("{e:#}"));
+                None
+            }
+            None => None,
+        };
+        Self {
+            raw_output,
+            value,
+            errors,
+            instructions,
+        }
+    }
+
+    /// The previous answer, as the assistant's turn, and the feedback on it.
+    fn messages(&self) -> (Option<String>, String) {
+        let value = self
+            .value
+            .as_ref()
+            .and_then(|value| serde_json::to_string_pretty(value).ok());
+        let answer = self.raw_output.clone().or_else(|| value.clone());
+
+        let mut feedback = vec![];
+        if let (Some(_), Some(value)) = (&self.raw_output, &value) {
+            feedback.push(format!("Your previous answer was parsed as:\n{value}"));
+        }
+        if !self.errors.is_empty() {
+            let errors = self
+                .errors
+                .iter()
+                .map(|e| format!("- {}", e.replace('\n', "\n  ")))
+                .collect::<Vec<_>>()
+                .join("\n");
+            feedback.push(format!(
+                "Your previous answer has these problems:\n{errors}"
+            ));
+        }
+        if let Some(instructions) = &self.instructions {
+            feedback.push(instructions.clone());
+        }
+        feedback.push(
+            "Answer again with the full, corrected answer, in the format asked for above."
+                .to_string(),
+        );
+        (answer, feedback.join("\n\n"))
+    }
+
+    /// Appends the previous answer and the feedback on it to `prompt`.
+    pub(crate) fn apply(&self, prompt: RenderedPrompt) -> RenderedPrompt {
+        let (answer, feedback) = self.messages();
+        match prompt {
+            RenderedPrompt::Chat(mut chat) => {
+                let message = |role: &str, text: String| RenderedChatMessage {
+                    role: role.to_string(),
+                    allow_duplicate_role: false,
+                    parts: vec![ChatMessagePart::Text(text)],
+                };
+                chat.extend(answer.map(|answer| message("assistant", answer)));
+                chat.push(message("user", feedback));
+                RenderedPrompt::Chat(chat)
+            }
+            RenderedPrompt::Completion(mut text) => {
+                for part in answer.into_iter().chain([feedback]) {
+                    text.push_str("\n\n");
+                    text.push_str(&part);
+                }
+                RenderedPrompt::Completion(text)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_answer_and_feedback() {
+        let reask = Reask {
+            raw_output: Some("{\"name\": \"Jo\"}".to_string()),
+            value: Some(serde_json::json!({"name": "Jo"})),
+            errors: vec!["Check `full_name` failed: this.name|length > 3".to_string()],
+            instructions: Some("Use the name from the header.".to_string()),
+        };
+        let prompt = RenderedPrompt::Chat(vec![RenderedChatMessage {
+            role: "system".to_string(),
+            allow_duplicate_role: false,
+            parts: vec![ChatMessagePart::Text("Extract the name.".to_string())],
+        }]);
+
+        let RenderedPrompt::Chat(chat) = reask.apply(prompt) else {
+            panic!("expected a chat prompt");
+        };
+        let roles = chat.iter().map(|m| m.role.as_str()).collect::<Vec<_>>();
+        assert_eq!(roles, ["system", "assistant", "user"]);
+        assert_eq!(
+            chat[2].parts,
+            [ChatMessagePart::Text(
+                "Your previous answer was parsed as:\n{\n  \"name\": \"Jo\"\n}\n\n\
+                 Your previous answer has these problems:\n\
+                 - Check `full_name` failed: this.name|length > 3\n\n\
+                 Use the name from the header.\n\n\
+                 Answer again with the full, corrected answer, in the format asked for above."
+                    .to_string()
+            )]
+        );
+    }
+}
@@ -2,12 +2,21 @@ pub use crate::internal::llm_client::LLMResponse;
 use crate::{
     constraints::TestConstraintsResult,
     errors::ExposedError,
-    internal::llm_client::{orchestrator::OrchestrationScope, ResponseBamlValue},
+    expectations::ExpectationDiff,
+    internal::llm_client::{
+        orchestrator::OrchestrationScope, parsed_value_to_response, ResponseBamlValue, TokenLogprob,
+    },
+    pii::MaskedSpan,
+    types::attempts::{self, Attempt},
+    PriceTable, UsageTotals,
 };
 use anyhow::Result;
 use colored::*;
+use indexmap::IndexMap;
+use internal_baml_jinja::RenderedPrompt;
+use serde::Serialize;
 
-use baml_types::BamlValue;
+use baml_types::{BamlMap, BamlValue, StreamState};
 use jsonish::BamlValueWithFlags;
 
 #[derive(Debug)]
@@ -18,6 +27,19 @@ pub struct FunctionResult {
         Option<Result<BamlValueWithFlags>>,
         Option<Result<ResponseBamlValue>>,
     )>,
+    pii_audit: Vec<MaskedSpan>,
+    dry_run_request: Option<DryRunRequest>,
+    confidence: Option<IndexMap<String, f64>>,
+}
+
+/// The request a dry run built instead of sending it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunRequest {
+    /// The client the request was for, with the strategies that picked it.
+    pub client: String,
+    pub prompt: RenderedPrompt,
+    /// The request as a `curl` command.
+    pub request: String,
 }
 
 impl std::fmt::Display for FunctionResult {
@@ -30,6 +52,14 @@ impl std::fmt::Display for FunctionResult {
                 format!("({} other previous tries)", self.event_chain.len() - 1).yellow()
             )?;
         }
+        if let Some(request) = &self.dry_run_request {
+            writeln!(
+                f,
+                "{}",
+                format!("---Dry Run Request ({})---", request.client).blue()
+            )?;
+            return write!(f, "{}", request.request);
+        }
         writeln!(f, "{}", self.llm_response())?;
         match &self.result_with_constraints() {
             Some(Ok(val)) => {
@@ -58,6 +88,9 @@ impl FunctionResult {
     ) -> Self {
         Self {
             event_chain: vec![(scope, response, parsed, baml_value)],
+            pii_audit: vec![],
+            dry_run_request: None,
+            confidence: None,
         }
     }
 
@@ -72,6 +105,17 @@ impl FunctionResult {
         &self.event_chain
     }
 
+    pub(crate) fn into_event_chain(
+        self,
+    ) -> Vec<(
+        OrchestrationScope,
+        LLMResponse,
+        Option<Result<BamlValueWithFlags>>,
+        Option<Result<ResponseBamlValue>>,
+    )> {
+        self.event_chain
+    }
+
     pub fn new_chain(
         chain: Vec<(
             OrchestrationScope,
@@ -84,13 +128,104 @@ impl FunctionResult {
             anyhow::bail!("No events in the chain");
         }
 
-        Ok(Self { event_chain: chain })
+        Ok(Self {
+            event_chain: chain,
+            pii_audit: vec![],
+            dry_run_request: None,
+            confidence: None,
+        })
+    }
+
+    /// Every request made for the call in order, including the retries and
+    /// fallbacks before the final one.
+    pub fn attempts(&self) -> Vec<Attempt> {
+        attempts::from_event_chain(&self.event_chain)
+    }
+
+    pub(crate) fn with_pii_audit(mut self, pii_audit: Vec<MaskedSpan>) -> Self {
+        self.pii_audit = pii_audit;
+        self
+    }
+
+    pub(crate) fn with_dry_run_request(mut self, request: Option<DryRunRequest>) -> Self {
+        self.dry_run_request = request;
+        self
+    }
+
+    /// Replaces what the last attempt parsed to, e.g. with the vote of a
+    /// `@@self_consistency` function.
+    pub(crate) fn with_parsed(mut self, parsed: BamlValueWithFlags) -> Self {
+        let last = self.event_chain.last_mut().unwrap();
+        last.3 = Some(Ok(parsed_value_to_response(&parsed)));
+        last.2 = Some(Ok(parsed));
+        self
+    }
+
+    /// Replaces the result of the last attempt, e.g. with what the function's
+    /// post-processors made of it.
+    pub(crate) fn with_result(mut self, value: ResponseBamlValue) -> Self {
+        self.event_chain.last_mut().unwrap().3 = Some(Ok(value));
+        self
+    }
+
+    pub(crate) fn with_confidence(mut self, confidence: Option<IndexMap<String, f64>>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// For functions with `@@confidence`, how likely the model thought each
+    /// enum value was. The scores sum to 1.
+    pub fn confidence(&self) -> Option<&IndexMap<String, f64>> {
+        self.confidence.as_ref()
+    }
+
+    /// Token usage and estimated cost of every request of the call, including
+    /// retries and fallbacks.
+    pub fn usage(&self, prices: &PriceTable) -> UsageTotals {
+        let mut usage = UsageTotals::default();
+        usage.record_with_prices(self, prices);
+        usage
+    }
+
+    /// The estimated cost of the call in USD, `None` if a model it used has
+    /// no price in `prices`.
+    pub fn estimated_cost_usd(&self, prices: &PriceTable) -> Option<f64> {
+        let usage = self.usage(prices);
+        usage.unpriced_models.is_empty().then_some(usage.cost_usd)
+    }
+
+    /// The request that would have been sent, if the call was a dry run.
+    pub fn dry_run_request(&self) -> Option<&DryRunRequest> {
+        self.dry_run_request.as_ref()
+    }
+
+    /// What `mask_pii` masked in the prompt of the last attempt.
+    pub fn pii_audit(&self) -> &[MaskedSpan] {
+        &self.pii_audit
     }
 
     pub fn content(&self) -> Result<&str> {
         self.llm_response().content()
     }
 
+    /// The log probability of each token of the final response, if its client
+    /// asked for them with `logprobs true`.
+    pub fn logprobs(&self) -> Option<&[TokenLogprob]> {
+        match self.llm_response() {
+            LLMResponse::Success(response) => response.metadata.logprobs.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The provider's JSON body of the final response, if its client set
+    /// `capture_raw_response true`.
+    pub fn raw_response(&self) -> Option<&serde_json::Value> {
+        match self.llm_response() {
+            LLMResponse::Success(response) => response.metadata.raw_response.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn llm_response(&self) -> &LLMResponse {
         &self.event_chain.last().unwrap().1
     }
@@ -132,11 +267,29 @@ impl FunctionResult {
         }
     }
 
+    /// For the partial results of a stream, how far along each value nested
+    /// in the result is, e.g. to show the fields still streaming differently,
+    /// see [`BamlValueWithFlags::stream_states`]. Every value of a final
+    /// result is complete, or pending if the model left it out. `None` if
+    /// the response didn't parse.
+    pub fn stream_states(&self) -> Option<BamlMap<String, StreamState>> {
+        self.parsed_content()
+            .ok()
+            .map(BamlValueWithFlags::stream_states)
+    }
+
     pub fn result_with_constraints(&self) -> &Option<Result<ResponseBamlValue>> {
         &self.event_chain.last().unwrap().3
     }
 
     pub fn result_with_constraints_content(&self) -> Result<&ResponseBamlValue> {
+        if let Some(request) = &self.dry_run_request {
+            anyhow::bail!(
+                "Dry run: built the request for {} without sending it:\n{}",
+                request.client,
+                request.request
+            );
+        }
         self.result_with_constraints()
             .as_ref()
             .map(|res| {
@@ -150,6 +303,9 @@ impl FunctionResult {
     }
 
     fn format_err(&self, err: &anyhow::Error) -> anyhow::Error {
+        if let Some(e @ ExposedError::PartialFailure { .. }) = err.downcast_ref::<ExposedError>() {
+            return anyhow::anyhow!(e.clone());
+        }
         // Capture the actual error to preserve its details
         let actual_error = err.to_string();
         anyhow::anyhow!(ExposedError::ValidationError {
@@ -186,6 +342,8 @@ pub struct TestResponse {
     pub function_response: FunctionResult,
     pub function_span: Option<uuid::Uuid>,
     pub constraints_result: TestConstraintsResult,
+    /// `None` if the test has no `@@expect` or its output didn't parse.
+    pub expectation_diff: Option<ExpectationDiff>,
 }
 
 impl std::fmt::Display for TestResponse {
@@ -223,6 +381,7 @@ pub enum TestFailReason<'a> {
         checks: Vec<(String, bool)>,
         failed_assert: Option<String>,
     },
+    TestExpectationFailure(&'a ExpectationDiff),
 }
 
 impl PartialEq for TestFailReason<'_> {
@@ -236,6 +395,7 @@ impl PartialEq for TestFailReason<'_> {
             (Self::TestFinishReasonFailed(a), Self::TestFinishReasonFailed(b)) => {
                 a.to_string() == b.to_string()
             }
+            (Self::TestExpectationFailure(a), Self::TestExpectationFailure(b)) => a == b,
             _ => false,
         }
     }
@@ -246,6 +406,10 @@ impl Eq for TestFailReason<'_> {}
 impl TestResponse {
     pub fn status(&self) -> TestStatus {
         let func_res = &self.function_response;
+        // Building the request is all a dry run checks.
+        if func_res.dry_run_request().is_some() {
+            return TestStatus::Pass;
+        }
         if let Some(parsed) = func_res.result_with_constraints() {
             if parsed.is_ok() {
                 match self.constraints_result.clone() {
@@ -264,7 +428,12 @@ impl TestResponse {
                                 failed_assert,
                             })
                         } else {
-                            TestStatus::Pass
+                            match &self.expectation_diff {
+                                Some(diff) if !diff.is_empty() => {
+                                    TestStatus::Fail(TestFailReason::TestExpectationFailure(diff))
+                                }
+                                _ => TestStatus::Pass,
+                            }
                         }
                     }
                 }
@@ -309,3 +478,32 @@ impl Termination for TestResponse {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_failures_keep_their_partial_value() {
+        let error = ExposedError::PartialFailure {
+            prompt: "Extract the ticket".to_string(),
+            raw_output: r#"{"title": "Crash"#.to_string(),
+            message: "connection reset".to_string(),
+            partial: Some(BamlValue::String("Crash".to_string())),
+        };
+        let result = FunctionResult::new(
+            OrchestrationScope::default(),
+            LLMResponse::InternalFailure("connection reset".to_string()),
+            None,
+            Some(Err(anyhow::anyhow!(error))),
+        );
+
+        let err = result.result_with_constraints_content().unwrap_err();
+        match err.downcast_ref::<ExposedError>() {
+            Some(ExposedError::PartialFailure { partial, .. }) => {
+                assert_eq!(partial, &Some(BamlValue::String("Crash".to_string())))
+            }
+            other => panic!("Expected a partial failure, got {other:?}"),
+        }
+    }
+}
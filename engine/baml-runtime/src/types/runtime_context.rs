@@ -1,10 +1,15 @@
 use anyhow::Result;
-use baml_types::{BamlValue, EvaluationContext, UnresolvedValue};
+use baml_types::{BamlValue, EnumMatching, EvaluationContext, NumberLocale, UnresolvedValue};
 use indexmap::IndexMap;
 use internal_baml_core::ir::FieldType;
 use std::{collections::HashMap, sync::Arc};
 
+use crate::clock::{SystemClock, TimeProvider};
 use crate::internal::llm_client::llm_provider::LLMProvider;
+use crate::internal::llm_client::output_tool::OutputTool;
+use crate::tracing::TraceParent;
+use crate::transport::{HttpTransport, ReqwestTransport};
+use crate::types::{HttpRecorder, Reask, RuntimeHooks};
 
 #[derive(Debug, Clone)]
 pub struct SpanCtx {
@@ -56,6 +61,32 @@ pub struct RuntimeContext {
     pub client_overrides: Option<(Option<String>, HashMap<String, Arc<LLMProvider>>)>,
     pub class_override: IndexMap<String, RuntimeClassOverride>,
     pub enum_overrides: IndexMap<String, RuntimeEnumOverride>,
+    /// Overrides the `@@match` of every enum, set with a `TypeBuilder`.
+    pub(crate) enum_matching: Option<EnumMatching>,
+    /// How numbers written as strings are parsed, set with a `TypeBuilder`.
+    pub(crate) number_locale: NumberLocale,
+    /// Set when the call was given a `Collector`.
+    pub(crate) http_recorder: Option<HttpRecorder>,
+    /// Build requests instead of sending them, see `BamlRuntime::set_dry_run`.
+    pub(crate) dry_run: bool,
+    /// Set for functions with `@@confidence`, so clients that can return
+    /// logprobs ask for them.
+    pub(crate) request_logprobs: bool,
+    /// The function's output type as a tool, for clients with `tools true`.
+    /// Only set for calls whose output types aren't changed by a
+    /// `TypeBuilder`.
+    pub(crate) output_tool: Option<OutputTool>,
+    /// The hooks registered on the runtime, see `BamlRuntime::add_hook`.
+    pub(crate) hooks: RuntimeHooks,
+    /// Appended to the prompt, see `BamlRuntime::reask`.
+    pub(crate) reask: Option<Arc<Reask>>,
+    /// See `BamlRuntime::set_time_provider`.
+    pub(crate) clock: Arc<dyn TimeProvider>,
+    /// See `BamlRuntime::set_http_transport`.
+    pub(crate) http_transport: Arc<dyn HttpTransport>,
+    /// The application's trace, as a child of the function's span, sent as
+    /// the `traceparent` header of LLM requests.
+    pub(crate) trace_parent: Option<TraceParent>,
 }
 
 impl RuntimeContext {
@@ -71,6 +102,41 @@ impl RuntimeContext {
         self.env.get("BOUNDARY_PROXY_URL").map(|s| s.as_str())
     }
 
+    pub fn tag(&self, key: &str) -> Option<&BamlValue> {
+        self.tags.get(key)
+    }
+
+    /// The tag as a string. Numbers and booleans are formatted, since ids are
+    /// often passed as either.
+    pub fn tag_string(&self, key: &str) -> Option<String> {
+        match self.tag(key)? {
+            BamlValue::String(s) => Some(s.clone()),
+            BamlValue::Int(i) => Some(i.to_string()),
+            BamlValue::Float(f) => Some(f.to_string()),
+            BamlValue::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn tag_int(&self, key: &str) -> Option<i64> {
+        self.tag(key)?.as_int()
+    }
+
+    pub fn tag_bool(&self, key: &str) -> Option<bool> {
+        self.tag(key)?.as_bool()
+    }
+
+    /// Deserializes a tag, e.g. a map of metadata, into `T`.
+    pub fn tag_as<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.tag(key)
+            .map(|value| {
+                serde_json::to_value(value)
+                    .and_then(serde_json::from_value)
+                    .map_err(|e| anyhow::anyhow!("Tag `{key}` has an unexpected type: {e}"))
+            })
+            .transpose()
+    }
+
     pub fn new(
         baml_src: Arc<BamlSrcReader>,
         env: HashMap<String, String>,
@@ -86,6 +152,17 @@ impl RuntimeContext {
             client_overrides,
             class_override,
             enum_overrides,
+            enum_matching: None,
+            number_locale: NumberLocale::default(),
+            http_recorder: None,
+            dry_run: false,
+            request_logprobs: false,
+            output_tool: None,
+            hooks: RuntimeHooks::default(),
+            reask: None,
+            clock: Arc::new(SystemClock),
+            http_transport: Arc::new(ReqwestTransport),
+            trace_parent: None,
         }
     }
 
@@ -0,0 +1,713 @@
+use anyhow::Result;
+use baml_types::BamlValue;
+use internal_baml_core::ir::repr::IntermediateRepr;
+use internal_baml_jinja::RenderedPrompt;
+use jsonish::BamlValueWithFlags;
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+
+use std::sync::Arc;
+
+use crate::{
+    errors::ExposedError,
+    internal::{
+        llm_client::{
+            orchestrator::{next_after_failure, OrchestratorNodeIterator},
+            parsed_value_to_response,
+            traits::{WithClientProperties, WithPrompt, WithSingleCallable},
+            LLMCompleteResponse, LLMResponse, ResponseBamlValue,
+        },
+        prompt_renderer::PromptRenderer,
+    },
+    pii::PiiTokenMap,
+    DryRunRequest, FunctionResult, RuntimeContext,
+};
+
+/// What a [`FunctionCallStepper`] does when next advanced.
+///
+/// `attempt` indexes the client graph of the function, with each retry and
+/// fallback being its own attempt.
+#[derive(Debug, Clone)]
+pub enum CallStep {
+    /// Render the prompt for `client`. `delay` is how long the retry policy
+    /// waits before this attempt; [`FunctionCallStepper::advance`] does not
+    /// wait, so the host can schedule it however it likes.
+    RenderPrompt {
+        attempt: usize,
+        client: String,
+        delay: Duration,
+    },
+    /// Send `prompt` to `client`. This is the only step with side effects, and
+    /// can be replaced with [`FunctionCallStepper::provide_response`].
+    HttpRequest {
+        attempt: usize,
+        client: String,
+        prompt: RenderedPrompt,
+    },
+    /// Look at the response of the client: successes are parsed, failures
+    /// move on to the next attempt.
+    RawResponse {
+        attempt: usize,
+        response: LLMResponse,
+    },
+    /// Parse the response into the function's return type.
+    Parse {
+        attempt: usize,
+        response: LLMCompleteResponse,
+    },
+    /// Evaluate the `@check`s of the parsed value.
+    ConstraintCheck {
+        attempt: usize,
+        response: LLMCompleteResponse,
+        parsed: BamlValueWithFlags,
+    },
+    /// Nothing left to do, see [`FunctionCallStepper::into_result`].
+    Done,
+}
+
+/// A [`FunctionCallStepper`] between two steps, see
+/// [`FunctionCallStepper::checkpoint`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallCheckpoint {
+    pub function_name: String,
+    /// The client of each attempt, which must be the same on resume.
+    pub clients: Vec<String>,
+    /// The responses of the attempts before the current one.
+    pub failed_attempts: Vec<(usize, LLMResponse)>,
+    pub step: CheckpointStep,
+}
+
+/// The [`CallStep`]s a [`CallCheckpoint`] can resume from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CheckpointStep {
+    RenderPrompt {
+        attempt: usize,
+        delay: Duration,
+    },
+    HttpRequest {
+        attempt: usize,
+        prompt: RenderedPrompt,
+    },
+    RawResponse {
+        attempt: usize,
+        response: LLMResponse,
+    },
+}
+
+/// Like the event chain of a [`FunctionResult`], by attempt.
+type Event = (
+    usize,
+    LLMResponse,
+    Option<Result<BamlValueWithFlags>>,
+    Option<Result<ResponseBamlValue>>,
+);
+
+/// A function call that runs one [`CallStep`] at a time, so the host can
+/// checkpoint or intercept between steps, e.g. to record each LLM request as
+/// a side effect in a durable execution framework.
+///
+/// Running every step gives the same result as `call_function`, except that
+/// no tracing span is emitted.
+pub struct FunctionCallStepper {
+    pub(crate) function_name: String,
+    pub(crate) params: BamlValue,
+    pub(crate) renderer: PromptRenderer,
+    pub(crate) ir: Arc<IntermediateRepr>,
+    pub(crate) orchestrator: OrchestratorNodeIterator,
+    pub(crate) ctx: RuntimeContext,
+    step: CallStep,
+    events: Vec<Event>,
+    /// What `mask_pii` masked in the current attempt's prompt. `None` if the
+    /// call was resumed past rendering it.
+    pii: Option<PiiTokenMap>,
+    dry_run_request: Option<DryRunRequest>,
+}
+
+impl FunctionCallStepper {
+    pub(crate) fn new(
+        function_name: String,
+        params: BamlValue,
+        renderer: PromptRenderer,
+        ir: Arc<IntermediateRepr>,
+        orchestrator: OrchestratorNodeIterator,
+        ctx: RuntimeContext,
+    ) -> Self {
+        let mut stepper = Self {
+            function_name,
+            params,
+            renderer,
+            ir,
+            orchestrator,
+            ctx,
+            step: CallStep::Done,
+            events: vec![],
+            pii: None,
+            dry_run_request: None,
+        };
+        stepper.step = stepper.attempt_step(0, Duration::ZERO);
+        stepper
+    }
+
+    pub fn function_name(&self) -> &str {
+        &self.function_name
+    }
+
+    /// The step the next [`Self::advance`] runs.
+    pub fn step(&self) -> &CallStep {
+        &self.step
+    }
+
+    fn attempt_step(&self, attempt: usize, delay: Duration) -> CallStep {
+        match self.orchestrator.get(attempt) {
+            Some(node) => CallStep::RenderPrompt {
+                attempt,
+                client: node.scope.name(),
+                delay,
+            },
+            None => CallStep::Done,
+        }
+    }
+
+    /// Runs the current step and returns the next one.
+    pub async fn advance(&mut self) -> &CallStep {
+        self.step = match std::mem::replace(&mut self.step, CallStep::Done) {
+            CallStep::RenderPrompt {
+                attempt, client, ..
+            } => {
+                let node = &self.orchestrator[attempt];
+                match node
+                    .render_prompt(&self.ir, &self.renderer, &self.ctx, &self.params)
+                    .await
+                {
+                    Ok(prompt) => {
+                        let (prompt, pii) = self.renderer.mask_pii(prompt);
+                        self.pii = Some(pii);
+                        CallStep::HttpRequest {
+                            attempt,
+                            client,
+                            prompt,
+                        }
+                    }
+                    Err(e) => {
+                        self.events.push((
+                            attempt,
+                            LLMResponse::InternalFailure(e.to_string()),
+                            None,
+                            None,
+                        ));
+                        if self.ctx.dry_run {
+                            CallStep::Done
+                        } else {
+                            self.attempt_step(attempt + 1, Duration::ZERO)
+                        }
+                    }
+                }
+            }
+            CallStep::HttpRequest {
+                attempt, prompt, ..
+            } if self.ctx.dry_run => {
+                let (response, request) = self.orchestrator[attempt]
+                    .dry_run(&self.ctx, &prompt, false)
+                    .await;
+                self.events.push((attempt, response, None, None));
+                self.dry_run_request = request;
+                CallStep::Done
+            }
+            CallStep::HttpRequest {
+                attempt, prompt, ..
+            } => {
+                let response = self.orchestrator[attempt]
+                    .single_call(&self.ctx, &prompt)
+                    .await;
+                CallStep::RawResponse { attempt, response }
+            }
+            CallStep::RawResponse { attempt, response } => match response {
+                LLMResponse::Success(response) => CallStep::Parse { attempt, response },
+                response => {
+                    let (next, delay) =
+                        next_after_failure(&self.orchestrator, attempt, &response, &self.ir);
+                    self.events.push((attempt, response, None, None));
+                    self.attempt_step(next, delay.unwrap_or_default())
+                }
+            },
+            CallStep::Parse { attempt, response } => {
+                let pii = self.pii_tokens(attempt).await;
+                let node = &self.orchestrator[attempt];
+                let parsed = if node
+                    .finish_reason_filter()
+                    .is_allowed(response.metadata.finish_reason.as_ref())
+                {
+                    let content = pii.restore(&response.content);
+                    if response.metadata.structured_output {
+                        self.renderer.parse_json(&content)
+                    } else {
+                        self.renderer.parse(&content, false)
+                    }
+                } else {
+                    Err(anyhow::anyhow!(ExposedError::FinishReasonError {
+                        prompt: response.prompt.to_string(),
+                        raw_output: response.content.clone(),
+                        message: "Finish reason not allowed".to_string(),
+                        finish_reason: response.metadata.finish_reason.clone(),
+                    }))
+                };
+                match parsed {
+                    Ok(parsed) => CallStep::ConstraintCheck {
+                        attempt,
+                        response,
+                        parsed,
+                    },
+                    // Like `call_function`, a response that fails to parse
+                    // is not retried.
+                    Err(e) => {
+                        self.ctx.hooks.parse_error(&response, &e);
+                        self.events.push((
+                            attempt,
+                            LLMResponse::Success(response),
+                            None,
+                            Some(Err(e)),
+                        ));
+                        CallStep::Done
+                    }
+                }
+            }
+            CallStep::ConstraintCheck {
+                attempt,
+                response,
+                parsed,
+            } => {
+                let checked = parsed_value_to_response(&parsed);
+                self.events.push((
+                    attempt,
+                    LLMResponse::Success(response),
+                    Some(Ok(parsed)),
+                    Some(Ok(checked)),
+                ));
+                CallStep::Done
+            }
+            CallStep::Done => CallStep::Done,
+        };
+        &self.step
+    }
+
+    /// The masked PII of `attempt`'s prompt, masking it again if the call was
+    /// resumed from a checkpoint. Masking is deterministic, so the tokens are
+    /// the same as in the prompt that was sent.
+    async fn pii_tokens(&mut self, attempt: usize) -> PiiTokenMap {
+        if let Some(pii) = &self.pii {
+            return pii.clone();
+        }
+        let pii = match self.orchestrator[attempt]
+            .render_prompt(&self.ir, &self.renderer, &self.ctx, &self.params)
+            .await
+        {
+            Ok(prompt) => self.renderer.mask_pii(prompt).1,
+            Err(_) => PiiTokenMap::default(),
+        };
+        self.pii = Some(pii.clone());
+        pii
+    }
+
+    /// Uses `response` instead of sending the current request, e.g. one
+    /// recorded by an earlier run of the same call.
+    pub fn provide_response(&mut self, response: LLMResponse) -> Result<()> {
+        let CallStep::HttpRequest { attempt, .. } = self.step else {
+            anyhow::bail!("Can only provide a response to an HttpRequest step");
+        };
+        self.step = CallStep::RawResponse { attempt, response };
+        Ok(())
+    }
+
+    /// Runs the current attempt again from its prompt, instead of moving on
+    /// to the next client in the graph. A response that was received is kept
+    /// in the result's history.
+    pub fn retry(&mut self) -> Result<()> {
+        let attempt = match std::mem::replace(&mut self.step, CallStep::Done) {
+            CallStep::HttpRequest { attempt, .. } => attempt,
+            CallStep::RawResponse { attempt, response } => {
+                self.events.push((attempt, response, None, None));
+                attempt
+            }
+            step => {
+                self.step = step;
+                anyhow::bail!("Can only retry an HttpRequest or RawResponse step");
+            }
+        };
+        self.step = self.attempt_step(attempt, Duration::ZERO);
+        Ok(())
+    }
+
+    /// Runs the remaining steps, waiting between retries like `call_function`.
+    pub async fn run(mut self) -> Result<FunctionResult> {
+        loop {
+            match &self.step {
+                CallStep::Done => break,
+                CallStep::RenderPrompt { delay, .. } if !delay.is_zero() => {
+                    self.ctx.clock.sleep(*delay).await;
+                }
+                _ => {}
+            }
+            self.advance().await;
+        }
+        self.into_result()
+    }
+
+    /// The result of the call, once its last step is done.
+    pub fn into_result(self) -> Result<FunctionResult> {
+        let pii_audit = self
+            .pii
+            .as_ref()
+            .map(|pii| pii.spans().to_vec())
+            .unwrap_or_default();
+        if !matches!(self.step, CallStep::Done) {
+            anyhow::bail!(
+                "{} has not finished running, its next step is {:?}",
+                self.function_name,
+                self.step
+            );
+        }
+        FunctionResult::new_chain(
+            self.events
+                .into_iter()
+                .map(|(attempt, response, parsed, checked)| {
+                    let scope = self.orchestrator[attempt].scope.clone();
+                    (scope, response, parsed, checked)
+                })
+                .collect(),
+        )
+        .map(|result| {
+            result
+                .with_pii_audit(pii_audit)
+                .with_dry_run_request(self.dry_run_request)
+        })
+    }
+
+    /// The state of the call before its current step, to resume it in
+    /// another process with `BamlRuntime::resume_function`. Parsing is
+    /// redone on resume, so `Parse` and `ConstraintCheck` are saved as the
+    /// `RawResponse` they came from.
+    pub fn checkpoint(&self) -> Result<CallCheckpoint> {
+        let step = match &self.step {
+            CallStep::RenderPrompt { attempt, delay, .. } => CheckpointStep::RenderPrompt {
+                attempt: *attempt,
+                delay: *delay,
+            },
+            CallStep::HttpRequest {
+                attempt, prompt, ..
+            } => CheckpointStep::HttpRequest {
+                attempt: *attempt,
+                prompt: prompt.clone(),
+            },
+            CallStep::RawResponse { attempt, response } => CheckpointStep::RawResponse {
+                attempt: *attempt,
+                response: response.clone(),
+            },
+            CallStep::Parse { attempt, response }
+            | CallStep::ConstraintCheck {
+                attempt, response, ..
+            } => CheckpointStep::RawResponse {
+                attempt: *attempt,
+                response: LLMResponse::Success(response.clone()),
+            },
+            CallStep::Done => anyhow::bail!(
+                "{} has finished running, use its result instead",
+                self.function_name
+            ),
+        };
+        Ok(CallCheckpoint {
+            function_name: self.function_name.clone(),
+            clients: self.clients(),
+            // Until the call is done, no attempt has been parsed.
+            failed_attempts: self
+                .events
+                .iter()
+                .map(|(attempt, response, ..)| (*attempt, response.clone()))
+                .collect(),
+            step,
+        })
+    }
+
+    /// Continues from `checkpoint`, which must come from a call of the same
+    /// function with the same clients.
+    pub(crate) fn restore(&mut self, checkpoint: CallCheckpoint) -> Result<()> {
+        if checkpoint.function_name != self.function_name {
+            anyhow::bail!(
+                "Can't resume {} from a checkpoint of {}",
+                self.function_name,
+                checkpoint.function_name
+            );
+        }
+        let clients = self.clients();
+        if checkpoint.clients != clients {
+            anyhow::bail!(
+                "The clients of {} changed since the checkpoint: it used [{}], now [{}]",
+                self.function_name,
+                checkpoint.clients.join(", "),
+                clients.join(", ")
+            );
+        }
+        self.events = checkpoint
+            .failed_attempts
+            .into_iter()
+            .map(|(attempt, response)| (attempt, response, None, None))
+            .collect();
+        self.step = match checkpoint.step {
+            CheckpointStep::RenderPrompt { attempt, delay } => CallStep::RenderPrompt {
+                attempt,
+                client: clients[attempt].clone(),
+                delay,
+            },
+            CheckpointStep::HttpRequest { attempt, prompt } => CallStep::HttpRequest {
+                attempt,
+                client: clients[attempt].clone(),
+                prompt,
+            },
+            CheckpointStep::RawResponse { attempt, response } => {
+                CallStep::RawResponse { attempt, response }
+            }
+        };
+        Ok(())
+    }
+
+    fn clients(&self) -> Vec<String> {
+        self.orchestrator
+            .iter()
+            .map(|node| node.scope.name())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use baml_types::BamlMap;
+
+    use super::*;
+    use crate::internal::llm_client::{ErrorCode, LLMCompleteResponseMetadata, LLMErrorResponse};
+    use crate::BamlRuntime;
+
+    const BAML: &str = r##"
+        client<llm> GPT4 {
+          provider openai
+          options {
+            model gpt-4o
+            api_key "sk-test"
+          }
+        }
+
+        client<llm> Backup {
+          provider openai
+          options {
+            model gpt-4o-mini
+            api_key "sk-test"
+          }
+        }
+
+        client<llm> Resilient {
+          provider fallback
+          options {
+            strategy [GPT4, Backup]
+          }
+        }
+
+        class Person {
+          name string
+          age int @check(adult, {{ this >= 18 }})
+        }
+
+        function ExtractPerson(text: string) -> Person {
+          client Resilient
+          prompt #"Extract the person from {{ text }}. {{ ctx.output_format }}"#
+        }
+    "##;
+
+    fn success(prompt: &RenderedPrompt, content: &str) -> LLMResponse {
+        LLMResponse::Success(LLMCompleteResponse {
+            client: "GPT4".into(),
+            model: "gpt-4o".into(),
+            prompt: prompt.clone(),
+            request_options: Default::default(),
+            content: content.into(),
+            start_time: web_time::SystemTime::now(),
+            latency: Duration::ZERO,
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: Some("stop".into()),
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        })
+    }
+
+    #[test]
+    fn steps_through_fallbacks() {
+        let files = HashMap::from([("main.baml", BAML)]);
+        let runtime =
+            BamlRuntime::from_file_content("baml_src", &files, HashMap::<&str, &str>::new())
+                .unwrap();
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+        let params = BamlMap::from([("text".to_string(), BamlValue::String("Greg, 17".into()))]);
+        let mut stepper = runtime
+            .step_function("ExtractPerson".into(), &params, &ctx, None, None)
+            .unwrap();
+
+        let rt = runtime.async_runtime.clone();
+        assert!(matches!(
+            stepper.step(),
+            CallStep::RenderPrompt { attempt: 0, .. }
+        ));
+        let CallStep::HttpRequest { prompt, .. } = rt.block_on(stepper.advance()).clone() else {
+            panic!("expected an HttpRequest step, got {:?}", stepper.step());
+        };
+        assert!(stepper.retry().is_ok());
+        rt.block_on(stepper.advance());
+
+        stepper
+            .provide_response(LLMResponse::InternalFailure("rate limited".into()))
+            .unwrap();
+        let step = rt.block_on(stepper.advance());
+        assert!(matches!(step, CallStep::RenderPrompt { attempt: 1, .. }));
+        rt.block_on(stepper.advance());
+
+        stepper
+            .provide_response(success(&prompt, r#"{"name": "Greg", "age": 17}"#))
+            .unwrap();
+        assert!(matches!(
+            rt.block_on(stepper.advance()),
+            CallStep::Parse { .. }
+        ));
+        let step = rt.block_on(stepper.advance());
+        assert!(matches!(step, CallStep::ConstraintCheck { .. }));
+        assert!(matches!(rt.block_on(stepper.advance()), CallStep::Done));
+
+        let result = stepper.into_result().unwrap();
+        assert_eq!(result.event_chain().len(), 2);
+        assert!(result.scope().name().contains("Backup"));
+        let checked = result.result_with_constraints_content().unwrap();
+        assert!(format!("{:?}", checked).contains(r#"status: "failed""#));
+    }
+
+    fn failure(prompt: &RenderedPrompt, status: u16) -> LLMResponse {
+        LLMResponse::LLMFailure(LLMErrorResponse {
+            client: "GPT4".into(),
+            model: None,
+            prompt: prompt.clone(),
+            request_options: Default::default(),
+            start_time: web_time::SystemTime::now(),
+            latency: Duration::ZERO,
+            message: format!("HTTP {status}"),
+            code: ErrorCode::from_u16(status),
+        })
+    }
+
+    #[test]
+    fn skips_retries_not_in_retry_on() {
+        let baml = BAML.replace(
+            "client<llm> GPT4 {\n",
+            "client<llm> GPT4 {\n          retry_policy Transient\n",
+        ) + r##"
+            retry_policy Transient {
+              max_retries 2
+              retry_on [429, 503]
+            }
+        "##;
+        let files = HashMap::from([("main.baml", baml.as_str())]);
+        let runtime =
+            BamlRuntime::from_file_content("baml_src", &files, HashMap::<&str, &str>::new())
+                .unwrap();
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+        let params = BamlMap::from([("text".to_string(), BamlValue::String("Greg, 42".into()))]);
+        let rt = runtime.async_runtime.clone();
+
+        for (status, next_attempt) in [(429, 1), (400, 3)] {
+            let mut stepper = runtime
+                .step_function("ExtractPerson".into(), &params, &ctx, None, None)
+                .unwrap();
+            let CallStep::HttpRequest { prompt, .. } = rt.block_on(stepper.advance()).clone()
+            else {
+                panic!("expected an HttpRequest step, got {:?}", stepper.step());
+            };
+            stepper.provide_response(failure(&prompt, status)).unwrap();
+            match rt.block_on(stepper.advance()) {
+                CallStep::RenderPrompt {
+                    attempt, client, ..
+                } => {
+                    assert_eq!(*attempt, next_attempt, "after a {status}");
+                    assert_eq!(client.contains("Backup"), status == 400);
+                }
+                step => panic!("expected a RenderPrompt step, got {step:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() {
+        let files = HashMap::from([("main.baml", BAML)]);
+        let runtime =
+            BamlRuntime::from_file_content("baml_src", &files, HashMap::<&str, &str>::new())
+                .unwrap();
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+        let params = BamlMap::from([("text".to_string(), BamlValue::String("Greg, 42".into()))]);
+        let rt = runtime.async_runtime.clone();
+
+        let mut stepper = runtime
+            .step_function("ExtractPerson".into(), &params, &ctx, None, None)
+            .unwrap();
+        let CallStep::HttpRequest { prompt, .. } = rt.block_on(stepper.advance()).clone() else {
+            panic!("expected an HttpRequest step, got {:?}", stepper.step());
+        };
+        stepper
+            .provide_response(LLMResponse::InternalFailure("timed out".into()))
+            .unwrap();
+        rt.block_on(stepper.advance());
+        rt.block_on(stepper.advance());
+        let checkpoint = serde_json::to_string(&stepper.checkpoint().unwrap()).unwrap();
+        drop(stepper);
+
+        let checkpoint: CallCheckpoint = serde_json::from_str(&checkpoint).unwrap();
+        let mut stepper = runtime
+            .resume_function(checkpoint, &params, &ctx, None, None)
+            .unwrap();
+        let CallStep::HttpRequest {
+            attempt: 1,
+            prompt: resumed_prompt,
+            ..
+        } = stepper.step().clone()
+        else {
+            panic!("expected an HttpRequest step, got {:?}", stepper.step());
+        };
+        assert_eq!(resumed_prompt, prompt);
+
+        stepper
+            .provide_response(success(&prompt, r#"{"name": "Greg", "age": 42}"#))
+            .unwrap();
+        let result = rt.block_on(stepper.run()).unwrap();
+        assert_eq!(result.event_chain().len(), 2);
+        assert!(result.parsed_content().is_ok());
+
+        let mut checkpoint: CallCheckpoint = serde_json::from_str(
+            &serde_json::to_string(
+                &runtime
+                    .step_function("ExtractPerson".into(), &params, &ctx, None, None)
+                    .unwrap()
+                    .checkpoint()
+                    .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        checkpoint.clients.pop();
+        assert!(runtime
+            .resume_function(checkpoint, &params, &ctx, None, None)
+            .is_err());
+    }
+}
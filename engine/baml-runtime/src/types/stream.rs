@@ -1,18 +1,22 @@
 use anyhow::Result;
 
+use futures::future::{AbortHandle, AbortRegistration, Abortable};
 use internal_baml_core::ir::repr::IntermediateRepr;
 
 use std::sync::Arc;
 
 use crate::{
     client_registry::ClientRegistry,
+    clock::TimeProvider,
+    errors::ExposedError,
     internal::{
         llm_client::orchestrator::{orchestrate_stream, OrchestratorNodeIterator},
         prompt_renderer::PromptRenderer,
     },
     tracing::BamlTracer,
+    transport::HttpTransport,
     type_builder::TypeBuilder,
-    FunctionResult, RuntimeContextManager,
+    Collector, FunctionResult, MetricsRegistry, RuntimeContextManager, RuntimeHooks, UsageTracker,
 };
 
 /// Wrapper that holds a stream of responses from a BAML function call.
@@ -27,10 +31,41 @@ pub struct FunctionResultStream {
     pub(crate) ir: Arc<IntermediateRepr>,
     pub(crate) orchestrator: OrchestratorNodeIterator,
     pub(crate) tracer: Arc<BamlTracer>,
+    pub(crate) collector: Option<Collector>,
+    pub(crate) metrics: Option<MetricsRegistry>,
+    pub(crate) usage: Option<UsageTracker>,
+    pub(crate) dry_run: bool,
+    pub(crate) hooks: RuntimeHooks,
+    pub(crate) clock: Arc<dyn TimeProvider>,
+    pub(crate) http_transport: Arc<dyn HttpTransport>,
+    pub(crate) cancel: CancelHandle,
+    pub(crate) cancel_registration: Option<AbortRegistration>,
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) tokio_runtime: Arc<tokio::runtime::Runtime>,
 }
 
+/// Cancels a [`FunctionResultStream`] from another task or thread, see
+/// [`FunctionResultStream::cancel_handle`].
+#[derive(Debug, Clone)]
+pub struct CancelHandle(AbortHandle);
+
+impl CancelHandle {
+    pub(crate) fn new_pair() -> (Self, AbortRegistration) {
+        let (handle, registration) = AbortHandle::new_pair();
+        (Self(handle), registration)
+    }
+
+    /// Drops the request in flight, if any, and makes the stream finish with
+    /// [`ExposedError::Cancelled`]. Does nothing once the stream finished.
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 // JsFuture is !Send, so when building for WASM, we have to drop that requirement from StreamCallback
 static_assertions::assert_impl_all!(FunctionResultStream: Send);
@@ -55,6 +90,12 @@ first.scope.clone();
 */
 
 impl FunctionResultStream {
+    /// A handle to cancel the stream while [`Self::run`] holds it, including
+    /// before it starts.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn run_sync<F>(
         &mut self,
@@ -91,10 +132,20 @@ impl FunctionResultStream {
             .tracer
             .start_span(&self.function_name, ctx, &local_params);
 
+        let collector_call = self
+            .collector
+            .as_ref()
+            .map(|c| c.start(&self.function_name));
+        let metrics_call = self.metrics.as_ref().map(|m| m.start(&self.function_name));
         let rctx = ctx.create_ctx(tb, cb);
         let res = match rctx {
-            Ok(rctx) => {
-                let (history, _) = orchestrate_stream(
+            Ok(mut rctx) => {
+                rctx.http_recorder = collector_call.as_ref().map(|c| c.http_recorder());
+                rctx.dry_run = self.dry_run;
+                rctx.hooks = self.hooks.clone();
+                rctx.clock = self.clock.clone();
+                rctx.http_transport = self.http_transport.clone();
+                let orchestration = orchestrate_stream(
                     local_orchestrator,
                     self.ir.as_ref(),
                     &rctx,
@@ -103,13 +154,36 @@ impl FunctionResultStream {
                     |content| self.renderer.parse(content, true),
                     |content| self.renderer.parse(content, false),
                     on_event,
-                )
-                .await;
-
-                FunctionResult::new_chain(history)
+                );
+                // Only the first run can be cancelled, later ones have nothing
+                // left to call.
+                let outcome = match self.cancel_registration.take() {
+                    Some(registration) => Abortable::new(orchestration, registration).await,
+                    None => Ok(orchestration.await),
+                };
+
+                match outcome {
+                    Ok((history, _, pii_audit, dry_run_request)) => {
+                        FunctionResult::new_chain(history).map(|result| {
+                            result
+                                .with_pii_audit(pii_audit)
+                                .with_dry_run_request(dry_run_request)
+                        })
+                    }
+                    Err(_) => Err(anyhow::anyhow!(ExposedError::Cancelled)),
+                }
             }
             Err(e) => Err(e),
         };
+        if let Some(collector_call) = collector_call {
+            collector_call.finish(&res);
+        }
+        if let Some(metrics_call) = metrics_call {
+            metrics_call.finish(&res);
+        }
+        if let Some(usage) = self.usage.as_ref().filter(|_| !self.dry_run) {
+            usage.record(&self.function_name, &res);
+        }
 
         let mut target_id = None;
         if let Some(span) = span {
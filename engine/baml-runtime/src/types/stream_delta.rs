@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How partial results of a stream are surfaced to a consumer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamMode {
+    /// Every event carries the full partial result so far.
+    #[default]
+    Cumulative,
+    /// Every event carries only the changes since the previous event.
+    Delta,
+}
+
+/// A single change between two consecutive partial results.
+///
+/// `path` is a JSON pointer (RFC 6901). `old` is `None` for added values and
+/// `new` is `None` for removed ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StreamPatch {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+/// Turns a sequence of partial results into a sequence of patches. The first
+/// partial is emitted as a single patch of the root.
+#[derive(Debug, Default)]
+pub struct StreamDeltaEncoder {
+    last: Option<Value>,
+}
+
+impl StreamDeltaEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The changes since the previous partial, empty if there were none.
+    pub fn next(&mut self, partial: Value) -> Vec<StreamPatch> {
+        let mut patches = vec![];
+        match &self.last {
+            Some(last) => diff(last, &partial, &mut String::new(), &mut patches),
+            None => patches.push(StreamPatch {
+                path: String::new(),
+                old: None,
+                new: Some(partial.clone()),
+            }),
+        }
+        self.last = Some(partial);
+        patches
+    }
+}
+
+fn diff(old: &Value, new: &Value, path: &mut String, patches: &mut Vec<StreamPatch>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                with_segment(path, key, |path| match new.get(key) {
+                    Some(new_value) => diff(old_value, new_value, path, patches),
+                    None => patches.push(StreamPatch {
+                        path: path.clone(),
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                });
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    with_segment(path, key, |path| {
+                        patches.push(StreamPatch {
+                            path: path.clone(),
+                            old: None,
+                            new: Some(new_value.clone()),
+                        })
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                with_segment(path, &i.to_string(), |path| {
+                    match (old.get(i), new.get(i)) {
+                        (Some(old_value), Some(new_value)) => {
+                            diff(old_value, new_value, path, patches)
+                        }
+                        (old_value, new_value) => patches.push(StreamPatch {
+                            path: path.clone(),
+                            old: old_value.cloned(),
+                            new: new_value.cloned(),
+                        }),
+                    }
+                });
+            }
+        }
+        (old, new) if old != new => patches.push(StreamPatch {
+            path: path.clone(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn with_segment(path: &mut String, segment: &str, f: impl FnOnce(&mut String)) {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    f(path);
+    path.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn first_partial_is_the_root() {
+        let mut encoder = StreamDeltaEncoder::new();
+        let patches = encoder.next(json!({"name": "He"}));
+        assert_eq!(
+            patches,
+            vec![StreamPatch {
+                path: "".into(),
+                old: None,
+                new: Some(json!({"name": "He"})),
+            }]
+        );
+    }
+
+    #[test]
+    fn emits_only_changes() {
+        let mut encoder = StreamDeltaEncoder::new();
+        encoder.next(json!({"name": "He", "tags": ["a"], "a/b": null}));
+
+        let patches = encoder.next(json!({"name": "Hello", "tags": ["a", "b"], "a/b": null}));
+        assert_eq!(
+            patches,
+            vec![
+                StreamPatch {
+                    path: "/name".into(),
+                    old: Some(json!("He")),
+                    new: Some(json!("Hello")),
+                },
+                StreamPatch {
+                    path: "/tags/1".into(),
+                    old: None,
+                    new: Some(json!("b")),
+                },
+            ]
+        );
+
+        let patches = encoder.next(json!({"name": "Hello", "tags": ["a", "b"], "a/b": 1}));
+        assert_eq!(patches[0].path, "/a~1b");
+        assert!(encoder
+            .next(json!({"name": "Hello", "tags": ["a", "b"], "a/b": 1}))
+            .is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use baml_types::BamlValue;
+
+/// Keys starting with this prefix are set by BAML itself, e.g. `baml.language`.
+pub const RESERVED_TAG_PREFIX: &str = "baml.";
+pub const MAX_TAG_KEY_LENGTH: usize = 256;
+/// How deeply maps, lists and classes may be nested inside a single tag.
+pub const MAX_TAG_DEPTH: usize = 8;
+/// Upper bound for all tags of a context, measured as serialized JSON.
+pub const MAX_TAGS_SIZE: usize = 64 * 1024;
+
+/// Checks tags supplied by a user before they are merged into `existing`.
+pub(crate) fn validate_user_tags(
+    existing: &HashMap<String, BamlValue>,
+    tags: &HashMap<String, BamlValue>,
+) -> Result<()> {
+    for (key, value) in tags {
+        if key.is_empty() {
+            anyhow::bail!("Tag keys must not be empty");
+        }
+        if key.starts_with(RESERVED_TAG_PREFIX) {
+            anyhow::bail!(
+                "Tag `{key}` is reserved: keys starting with `{RESERVED_TAG_PREFIX}` are set by BAML"
+            );
+        }
+        if key.len() > MAX_TAG_KEY_LENGTH {
+            anyhow::bail!(
+                "Tag key `{}...` is longer than {MAX_TAG_KEY_LENGTH} bytes",
+                key.chars().take(32).collect::<String>()
+            );
+        }
+        if depth(value) > MAX_TAG_DEPTH {
+            anyhow::bail!("Tag `{key}` is nested more than {MAX_TAG_DEPTH} levels deep");
+        }
+    }
+
+    let size = existing
+        .iter()
+        .filter(|(key, _)| !tags.contains_key(*key))
+        .chain(tags.iter())
+        .map(|(key, value)| key.len() + serialized_size(value))
+        .sum::<usize>();
+    if size > MAX_TAGS_SIZE {
+        anyhow::bail!("Tags take up {size} bytes, more than the limit of {MAX_TAGS_SIZE} bytes");
+    }
+
+    Ok(())
+}
+
+fn depth(value: &BamlValue) -> usize {
+    match value {
+        BamlValue::Map(items) | BamlValue::Class(_, items) => {
+            1 + items.values().map(depth).max().unwrap_or(0)
+        }
+        BamlValue::List(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn serialized_size(value: &BamlValue) -> usize {
+    serde_json::to_vec(value).map_or(usize::MAX, |bytes| bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use baml_types::BamlMap;
+
+    fn tags(items: &[(&str, BamlValue)]) -> HashMap<String, BamlValue> {
+        items
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn rejects_reserved_keys() {
+        let existing = tags(&[("baml.language", BamlValue::String("python".into()))]);
+        assert!(validate_user_tags(&existing, &tags(&[("user_id", BamlValue::Int(1))])).is_ok());
+        assert!(
+            validate_user_tags(&existing, &tags(&[("baml.language", BamlValue::Null)])).is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_deep_values() {
+        let mut value = BamlValue::Null;
+        for _ in 0..=MAX_TAG_DEPTH {
+            value = BamlValue::Map(BamlMap::from([("a".to_string(), value)]));
+        }
+        assert!(validate_user_tags(&HashMap::new(), &tags(&[("deep", value)])).is_err());
+    }
+
+    #[test]
+    fn rejects_large_values() {
+        let half = BamlValue::String("x".repeat(MAX_TAGS_SIZE / 2));
+        let existing = tags(&[("a", half.clone())]);
+        assert!(validate_user_tags(&HashMap::new(), &tags(&[("b", half.clone())])).is_ok());
+        assert!(validate_user_tags(&existing, &tags(&[("b", half.clone())])).is_err());
+        // Replacing a tag does not count it twice.
+        assert!(validate_user_tags(&existing, &tags(&[("a", half)])).is_ok());
+    }
+}
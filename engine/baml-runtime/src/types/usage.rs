@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::internal::llm_client::LLMResponse;
+use crate::FunctionResult;
+
+use super::cost::{PriceTable, UsageTotals};
+
+/// Sums token usage and estimated cost per function, client and model over
+/// every call made through a [`crate::BamlRuntime`], see
+/// [`crate::BamlRuntime::usage_report`].
+///
+/// A tracker is cheap to clone and clones share their totals.
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    inner: Arc<Mutex<Usage>>,
+}
+
+#[derive(Default)]
+struct Usage {
+    prices: PriceTable,
+    /// Keyed by function, client and model.
+    entries: BTreeMap<(String, String, String), UsageEntry>,
+}
+
+/// Usage of one model through one client, by one function.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageEntry {
+    pub function: String,
+    pub client: String,
+    pub model: String,
+    /// Requests that got a response, including retries and fallbacks.
+    pub requests: u64,
+    #[serde(flatten)]
+    pub usage: UsageTotals,
+}
+
+/// What a [`UsageTracker`] recorded so far.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    /// Ordered by function, client and model.
+    pub entries: Vec<UsageEntry>,
+    pub total: UsageTotals,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prices the calls recorded from now on with `prices`.
+    pub fn set_price_table(&self, prices: PriceTable) {
+        self.inner.lock().unwrap().prices = prices;
+    }
+
+    pub fn price_table(&self) -> PriceTable {
+        self.inner.lock().unwrap().prices.clone()
+    }
+
+    pub fn report(&self) -> UsageReport {
+        let usage = self.inner.lock().unwrap();
+        let mut total = UsageTotals::default();
+        let entries = usage
+            .entries
+            .values()
+            .inspect(|entry| total.add(&entry.usage))
+            .cloned()
+            .collect();
+        UsageReport { entries, total }
+    }
+
+    pub fn reset(&self) {
+        self.inner.lock().unwrap().entries.clear();
+    }
+
+    pub(crate) fn record(&self, function_name: &str, result: &Result<FunctionResult>) {
+        let Ok(result) = result else {
+            return;
+        };
+        let mut usage = self.inner.lock().unwrap();
+        let Usage { prices, entries } = &mut *usage;
+        for (_, response, _, _) in result.event_chain() {
+            let LLMResponse::Success(response) = response else {
+                continue;
+            };
+            let entry = entries
+                .entry((
+                    function_name.to_string(),
+                    response.client.clone(),
+                    response.model.clone(),
+                ))
+                .or_insert_with(|| UsageEntry {
+                    function: function_name.to_string(),
+                    client: response.client.clone(),
+                    model: response.model.clone(),
+                    ..Default::default()
+                });
+            entry.requests += 1;
+            entry.usage.record_response(response, prices);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::llm_client::{
+        orchestrator::{ExecutionScope, OrchestrationScope},
+        LLMCompleteResponse, LLMCompleteResponseMetadata,
+    };
+    use crate::ModelPrice;
+    use internal_baml_jinja::RenderedPrompt;
+
+    fn success(client: &str, model: &str, input: u64, output: u64) -> LLMResponse {
+        LLMResponse::Success(LLMCompleteResponse {
+            client: client.to_string(),
+            model: model.to_string(),
+            prompt: RenderedPrompt::Completion(String::new()),
+            request_options: Default::default(),
+            content: String::new(),
+            start_time: web_time::SystemTime::UNIX_EPOCH,
+            latency: web_time::Duration::from_millis(100),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: None,
+                prompt_tokens: Some(input),
+                output_tokens: Some(output),
+                total_tokens: Some(input + output),
+                cached_input_tokens: None,
+                grounding: None,
+                thinking: None,
+                logprobs: None,
+                candidates: None,
+                structured_output: false,
+                raw_response: None,
+            },
+        })
+    }
+
+    fn result(responses: Vec<LLMResponse>) -> Result<FunctionResult> {
+        let scope = OrchestrationScope {
+            scope: vec![ExecutionScope::Direct("Client".to_string())],
+        };
+        FunctionResult::new_chain(
+            responses
+                .into_iter()
+                .map(|response| (scope.clone(), response, None, None))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn sums_per_function_client_and_model() {
+        let tracker = UsageTracker::new();
+        tracker.set_price_table(PriceTable::new().with_price(
+            "llama3",
+            ModelPrice {
+                input: 1.0,
+                cached_input: 1.0,
+                output: 2.0,
+            },
+        ));
+        tracker.record(
+            "Extract",
+            &result(vec![
+                LLMResponse::InternalFailure("timed out".to_string()),
+                success("Local", "llama3.1", 1_000_000, 500_000),
+            ]),
+        );
+        tracker.record(
+            "Extract",
+            &result(vec![success("Local", "llama3.1", 1_000_000, 0)]),
+        );
+        tracker.record(
+            "Classify",
+            &result(vec![success("Custom", "my-model", 10, 5)]),
+        );
+        tracker.record("Classify", &Err(anyhow::anyhow!("bad arguments")));
+
+        let report = tracker.report();
+        let entries = report
+            .entries
+            .iter()
+            .map(|e| (e.function.as_str(), e.model.as_str(), e.requests))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [("Classify", "my-model", 1), ("Extract", "llama3.1", 2)]
+        );
+        assert_eq!(report.entries[1].usage.input_tokens, 2_000_000);
+        assert!((report.entries[1].usage.cost_usd - 3.0).abs() < 1e-9);
+        assert_eq!(report.total.total_tokens(), 2_500_015);
+        assert!((report.total.cost_usd - 3.0).abs() < 1e-9);
+        assert_eq!(
+            report.total.unpriced_models.iter().collect::<Vec<_>>(),
+            ["my-model"]
+        );
+
+        tracker.clone().reset();
+        assert!(tracker.report().entries.is_empty());
+    }
+}
@@ -498,4 +498,421 @@ test TestTree {
 
         Ok(())
     }
+
+    #[test]
+    fn test_call_function_batch() -> anyhow::Result<()> {
+        let mut runtime = make_test_runtime(
+            r##"
+function Summarize(text: string) -> string {
+  client "openai/gpt-4o"
+  prompt #"
+    Summarize: {{ text }}
+  "#
+}
+        "##,
+        )?;
+        runtime.set_dry_run(true);
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".to_string()), None);
+        let args = [
+            BamlValue::String("first".to_string()),
+            BamlValue::Int(2),
+            BamlValue::String("third".to_string()),
+        ]
+        .into_iter()
+        .map(|text| [("text".to_string(), text)].into_iter().collect())
+        .collect::<Vec<_>>();
+        let options = baml_runtime::BatchOptions {
+            max_concurrency: 2,
+            requests_per_second: Some(100.0),
+        };
+        let results = runtime.async_runtime.block_on(runtime.call_function_batch(
+            "Summarize".to_string(),
+            &args,
+            &ctx,
+            None,
+            None,
+            None,
+            &options,
+        ));
+
+        let requests = results
+            .iter()
+            .map(|(result, _)| {
+                result
+                    .as_ref()
+                    .ok()
+                    .and_then(|r| r.dry_run_request())
+                    .map(|request| request.request.clone())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].as_ref().is_some_and(|b| b.contains("first")));
+        assert!(results[1].0.is_err(), "an int isn't a string");
+        assert!(requests[2].as_ref().is_some_and(|b| b.contains("third")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_function_batch_with_mock_clock() -> anyhow::Result<()> {
+        use baml_runtime::clock::{MockClock, TimeProvider};
+        use std::sync::Arc;
+        use web_time::{Duration, SystemTime};
+
+        let mut runtime = make_test_runtime(
+            r##"
+function Summarize(text: string) -> string {
+  client "openai/gpt-4o"
+  prompt #"
+    Summarize: {{ text }}
+  "#
+}
+        "##,
+        )?;
+        runtime.set_dry_run(true);
+        let clock = Arc::new(MockClock::new(SystemTime::UNIX_EPOCH));
+        runtime.set_time_provider(clock.clone() as Arc<dyn TimeProvider>);
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".to_string()), None);
+        let args = ["first", "second", "third"]
+            .into_iter()
+            .map(|text| {
+                [("text".to_string(), BamlValue::String(text.to_string()))]
+                    .into_iter()
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let options = baml_runtime::BatchOptions {
+            max_concurrency: 1,
+            requests_per_second: Some(1.0),
+        };
+        let results = runtime.async_runtime.block_on(runtime.call_function_batch(
+            "Summarize".to_string(),
+            &args,
+            &ctx,
+            None,
+            None,
+            None,
+            &options,
+        ));
+
+        assert!(results.iter().all(|(result, _)| result.is_ok()));
+        // The second and third calls each waited a second, without sleeping.
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reask() -> anyhow::Result<()> {
+        let mut runtime = make_test_runtime(
+            r##"
+function ExtractName(text: string) -> string {
+  client "openai/gpt-4o"
+  prompt #"
+    Extract the name from: {{ text }}
+  "#
+}
+        "##,
+        )?;
+        runtime.set_dry_run(true);
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".to_string()), None);
+        let params = [(
+            "text".to_string(),
+            BamlValue::String("Hi, I'm Jo".to_string()),
+        )]
+        .into_iter()
+        .collect();
+        let previous = baml_runtime::Reask {
+            raw_output: Some("Hi".to_string()),
+            value: None,
+            errors: vec!["Not a name".to_string()],
+            instructions: Some("Only answer with the name.".to_string()),
+        };
+        let (result, _) = runtime.async_runtime.block_on(runtime.reask(
+            "ExtractName".to_string(),
+            &params,
+            previous,
+            &ctx,
+            None,
+            None,
+            None,
+        ));
+
+        let request = result?.dry_run_request().unwrap().clone();
+        let baml_runtime::RenderedPrompt::Chat(chat) = request.prompt else {
+            panic!("expected a chat prompt");
+        };
+        let roles = chat.iter().map(|m| m.role.as_str()).collect::<Vec<_>>();
+        assert_eq!(roles, ["system", "assistant", "user"]);
+        assert!(request.request.contains("- Not a name"));
+        assert!(request.request.contains("Only answer with the name."));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mock_transport_call() -> anyhow::Result<()> {
+        use baml_runtime::internal::llm_client::ErrorCode;
+        use baml_runtime::transport::{HttpTransport, MockTransport};
+        use std::sync::Arc;
+
+        let mut runtime = make_test_runtime(
+            r##"
+function Summarize(text: string) -> string {
+  client "openai/gpt-4o"
+  prompt #"
+    Summarize: {{ text }}
+  "#
+}
+        "##,
+        )?;
+        let transport = MockTransport::new();
+        transport
+            .push_json(
+                200,
+                serde_json::json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 1700000000,
+                    "model": "gpt-4o-2024-08-06",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "A short summary."},
+                        "finish_reason": "stop",
+                    }],
+                    "usage": {"prompt_tokens": 12, "completion_tokens": 4, "total_tokens": 16},
+                }),
+            )
+            .push_text(429, "Rate limit reached")
+            .push_error("connection refused");
+        runtime.set_http_transport(Arc::new(transport.clone()) as Arc<dyn HttpTransport>);
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".to_string()), None);
+        let params = [("text".to_string(), BamlValue::String("hi".to_string()))]
+            .into_iter()
+            .collect();
+        let call = || {
+            let (result, _) = runtime.async_runtime.block_on(runtime.call_function(
+                "Summarize".to_string(),
+                &params,
+                &ctx,
+                None,
+                None,
+                None,
+            ));
+            result
+        };
+
+        let result = call()?;
+        assert_eq!(result.content()?, "A short summary.");
+        let LLMResponse::Success(response) = result.llm_response() else {
+            panic!("expected a success, got {:?}", result.llm_response());
+        };
+        assert_eq!(response.model, "gpt-4o-2024-08-06");
+        assert_eq!(response.metadata.prompt_tokens, Some(12));
+
+        let result = call()?;
+        let LLMResponse::LLMFailure(error) = result.llm_response() else {
+            panic!("expected a failure, got {:?}", result.llm_response());
+        };
+        assert!(matches!(error.code, ErrorCode::RateLimited));
+        assert!(error.message.contains("Rate limit reached"));
+
+        let result = call()?;
+        let LLMResponse::LLMFailure(error) = result.llm_response() else {
+            panic!("expected a failure, got {:?}", result.llm_response());
+        };
+        assert!(matches!(error.code, ErrorCode::Other(2)));
+        assert_eq!(error.message, "connection refused");
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(
+            requests[0].url,
+            "https://api.openai.com/v1/chat/completions"
+        );
+        assert!(requests[0].headers.contains(&(
+            "authorization".to_string(),
+            "Bearer OPENAI_API_KEY".to_string()
+        )));
+        assert_eq!(requests[0].json().unwrap()["model"], "gpt-4o");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_rate_limit() -> anyhow::Result<()> {
+        use baml_runtime::clock::{MockClock, TimeProvider};
+        use baml_runtime::transport::{HttpTransport, MockTransport};
+        use std::sync::Arc;
+        use web_time::{Duration, SystemTime};
+
+        let mut runtime = make_test_runtime(
+            r##"
+client<llm> RateLimitedGPT {
+  provider openai
+  options {
+    model gpt-4o
+    http {
+      requests_per_minute 60
+      burst 1
+    }
+  }
+}
+
+function Summarize(text: string) -> string {
+  client RateLimitedGPT
+  prompt #"
+    Summarize: {{ text }}
+  "#
+}
+        "##,
+        )?;
+        let transport = MockTransport::new();
+        for _ in 0..3 {
+            transport.push_json(
+                200,
+                serde_json::json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 1700000000,
+                    "model": "gpt-4o-2024-08-06",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "A short summary."},
+                        "finish_reason": "stop",
+                    }],
+                }),
+            );
+        }
+        runtime.set_http_transport(Arc::new(transport.clone()) as Arc<dyn HttpTransport>);
+        let clock = Arc::new(MockClock::new(SystemTime::UNIX_EPOCH));
+        runtime.set_time_provider(clock.clone() as Arc<dyn TimeProvider>);
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".to_string()), None);
+        let params = [("text".to_string(), BamlValue::String("hi".to_string()))]
+            .into_iter()
+            .collect();
+        for _ in 0..3 {
+            let (result, _) = runtime.async_runtime.block_on(runtime.call_function(
+                "Summarize".to_string(),
+                &params,
+                &ctx,
+                None,
+                None,
+                None,
+            ));
+            assert_eq!(result?.content()?, "A short summary.");
+        }
+
+        // The second and third requests each waited a second for the bucket.
+        assert_eq!(transport.requests().len(), 3);
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mock_transport_stream() -> anyhow::Result<()> {
+        use baml_runtime::transport::{HttpTransport, MockTransport};
+        use serde_json::json;
+        use std::cell::Cell;
+        use std::sync::Arc;
+
+        let mut files = HashMap::new();
+        files.insert(
+            "main.baml",
+            r##"
+function Greet(name: string) -> string {
+  client "anthropic/claude-3-5-sonnet-20241022"
+  prompt #"
+    Greet {{ name }}.
+  "#
+}
+            "##,
+        );
+        let mut runtime = BamlRuntime::from_file_content(
+            "baml_src",
+            &files,
+            [("ANTHROPIC_API_KEY", "ANTHROPIC_API_KEY")].into(),
+        )?;
+        let transport = MockTransport::new();
+        transport.push_sse([
+            (
+                "message_start",
+                json!({"type": "message_start", "message": {
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [],
+                    "model": "claude-3-5-sonnet-20241022",
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": 10, "output_tokens": 1},
+                }}),
+            ),
+            (
+                "content_block_start",
+                json!({"type": "content_block_start", "index": 0,
+                       "content_block": {"type": "text", "text": ""}}),
+            ),
+            ("ping", json!({"type": "ping"})),
+            (
+                "content_block_delta",
+                json!({"type": "content_block_delta", "index": 0,
+                       "delta": {"type": "text_delta", "text": "Hello, "}}),
+            ),
+            (
+                "content_block_delta",
+                json!({"type": "content_block_delta", "index": 0,
+                       "delta": {"type": "text_delta", "text": "Jo!"}}),
+            ),
+            (
+                "content_block_stop",
+                json!({"type": "content_block_stop", "index": 0}),
+            ),
+            (
+                "message_delta",
+                json!({"type": "message_delta",
+                       "delta": {"stop_reason": "end_turn", "stop_sequence": null},
+                       "usage": {"output_tokens": 4}}),
+            ),
+            ("message_stop", json!({"type": "message_stop"})),
+        ]);
+        runtime.set_http_transport(Arc::new(transport.clone()) as Arc<dyn HttpTransport>);
+
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".to_string()), None);
+        let params = [("name".to_string(), BamlValue::String("Jo".to_string()))]
+            .into_iter()
+            .collect();
+        let mut stream =
+            runtime.stream_function("Greet".to_string(), &params, &ctx, None, None, None)?;
+        let partials = Cell::new(0);
+        let (result, _) = runtime.async_runtime.block_on(stream.run(
+            Some(|_: baml_runtime::FunctionResult| partials.set(partials.get() + 1)),
+            &ctx,
+            None,
+            None,
+        ));
+
+        let result = result?;
+        assert_eq!(result.content()?, "Hello, Jo!");
+        assert_eq!(partials.get(), 2);
+        let LLMResponse::Success(response) = result.llm_response() else {
+            panic!("expected a success, got {:?}", result.llm_response());
+        };
+        assert!(response.metadata.baml_is_complete);
+        assert_eq!(response.metadata.prompt_tokens, Some(10));
+        assert_eq!(response.metadata.output_tokens, Some(4));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://api.anthropic.com/v1/messages");
+        assert_eq!(requests[0].json().unwrap()["stream"], true);
+
+        Ok(())
+    }
 }
@@ -9,3 +9,20 @@ pub fn version() -> String {
     // register_panic_hook();
     env!("CARGO_PKG_VERSION").to_string()
 }
+
+/// The client `provider`s this bundle was compiled with. Which ones are
+/// included is controlled by the `provider-*` cargo features.
+#[wasm_bindgen]
+pub fn supported_providers() -> Vec<String> {
+    baml_runtime::supported_providers()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// A longer explanation of a diagnostic code such as `BAML0010`, for
+/// "more info" links in editors. Returns `undefined` for unknown codes.
+#[wasm_bindgen]
+pub fn explain_diagnostic(code: &str) -> Option<String> {
+    baml_runtime::internal_baml_diagnostics::explain(code).map(String::from)
+}
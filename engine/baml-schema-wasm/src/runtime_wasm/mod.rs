@@ -2,6 +2,8 @@ pub mod generator;
 pub mod runtime_prompt;
 use crate::runtime_wasm::runtime_prompt::WasmPrompt;
 use anyhow::Context;
+use baml_runtime::client_registry::ClientRegistry;
+use baml_runtime::constraints::{evaluate_sample_constraints, SampleConstraintResult};
 use baml_runtime::internal::llm_client::orchestrator::OrchestrationScope;
 use baml_runtime::internal::llm_client::orchestrator::OrchestratorNode;
 use baml_runtime::internal::prompt_renderer::PromptRenderer;
@@ -11,7 +13,10 @@ use baml_runtime::RenderCurlSettings;
 use baml_runtime::{
     internal::llm_client::LLMResponse, BamlRuntime, DiagnosticsError, IRHelper, RenderedPrompt,
 };
-use baml_types::{BamlMediaType, BamlValue, GeneratorOutputType, TypeValue};
+use baml_types::{
+    BamlMediaType, BamlValue, Constraint, ConstraintLevel, GeneratorOutputType, JinjaExpression,
+    TypeValue,
+};
 use indexmap::IndexMap;
 use internal_baml_codegen::version_check::GeneratorType;
 use internal_baml_codegen::version_check::{check_version, VersionCheckMode};
@@ -125,6 +130,8 @@ impl WasmDiagnosticError {
                     end_column: end.1,
                     r#type: "error".to_string(),
                     message: e.message().to_string(),
+                    code: e.code().to_string(),
+                    code_url: e.code().docs_url(),
                 }
             })
             .chain(self.errors.warnings().iter().map(|e| {
@@ -140,10 +147,20 @@ impl WasmDiagnosticError {
                     end_column: end.1,
                     r#type: "warning".to_string(),
                     message: e.message().to_string(),
+                    code: e.code().to_string(),
+                    code_url: e.code().docs_url(),
                 }
             }))
             .collect()
     }
+
+    /// All errors and warnings as a versioned JSON report, including
+    /// related spans and suggested fixes.
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> JsResult<String> {
+        let report = baml_runtime::internal_baml_diagnostics::DiagnosticsReport::from(&self.errors);
+        serde_json::to_string(&report).map_err(|e| JsError::new(format!("{e:#}").as_str()))
+    }
 }
 
 #[wasm_bindgen(getter_with_clone, inspectable)]
@@ -167,6 +184,10 @@ pub struct WasmError {
     pub end_column: usize,
     #[wasm_bindgen(readonly)]
     pub message: String,
+    #[wasm_bindgen(readonly)]
+    pub code: String,
+    #[wasm_bindgen(readonly)]
+    pub code_url: String,
 }
 
 #[wasm_bindgen]
@@ -343,6 +364,29 @@ impl From<&baml_runtime::internal_baml_diagnostics::Span> for WasmSpan {
     }
 }
 
+/// A prompt failed to render. `span` points into the .baml file when the
+/// error could be traced back to it.
+#[wasm_bindgen(getter_with_clone, inspectable)]
+#[derive(Clone, Debug)]
+pub struct WasmPromptRenderError {
+    #[wasm_bindgen(readonly)]
+    pub message: String,
+    #[wasm_bindgen(readonly)]
+    pub span: Option<WasmSpan>,
+}
+
+impl From<anyhow::Error> for WasmPromptRenderError {
+    fn from(e: anyhow::Error) -> Self {
+        WasmPromptRenderError {
+            message: format!("{e:?}"),
+            span: e
+                .downcast_ref::<baml_runtime::PromptRenderError>()
+                .and_then(|e| e.span.as_ref())
+                .map(WasmSpan::from),
+        }
+    }
+}
+
 impl Default for WasmSpan {
     fn default() -> Self {
         WasmSpan {
@@ -416,6 +460,78 @@ pub struct WasmParsedTestResponse {
     pub explanation: Option<String>,
 }
 
+/// A constraint to run against a sample value, as passed to
+/// `evaluate_constraints`.
+#[derive(Deserialize)]
+struct SampleConstraint {
+    /// `check` or `assert`.
+    level: String,
+    label: Option<String>,
+    /// The Jinja expression, with or without the surrounding `{{ }}`.
+    expression: String,
+}
+
+impl TryFrom<SampleConstraint> for Constraint {
+    type Error = String;
+
+    fn try_from(constraint: SampleConstraint) -> Result<Self, Self::Error> {
+        let level = match constraint.level.as_str() {
+            "check" => ConstraintLevel::Check,
+            "assert" => ConstraintLevel::Assert,
+            other => return Err(format!("Expected `check` or `assert`, got `{other}`")),
+        };
+        let expression = constraint.expression.trim();
+        let expression = expression
+            .strip_prefix("{{")
+            .and_then(|e| e.strip_suffix("}}"))
+            .unwrap_or(expression)
+            .trim();
+        Ok(Constraint {
+            level,
+            expression: JinjaExpression(expression.to_string()),
+            label: constraint.label,
+        })
+    }
+}
+
+#[wasm_bindgen(getter_with_clone, inspectable)]
+#[derive(Clone, Debug)]
+pub struct WasmConstraintResult {
+    #[wasm_bindgen(readonly)]
+    pub label: Option<String>,
+    #[wasm_bindgen(readonly)]
+    pub level: String,
+    #[wasm_bindgen(readonly)]
+    pub expression: String,
+    #[wasm_bindgen(readonly)]
+    /// `succeeded`, `failed`, or `error` if the expression couldn't be
+    /// evaluated.
+    pub status: String,
+    #[wasm_bindgen(readonly)]
+    pub error: Option<String>,
+}
+
+impl From<SampleConstraintResult> for WasmConstraintResult {
+    fn from(SampleConstraintResult { constraint, result }: SampleConstraintResult) -> Self {
+        let (status, error) = match result {
+            Ok(true) => ("succeeded", None),
+            Ok(false) => ("failed", None),
+            Err(e) => ("error", Some(e)),
+        };
+        WasmConstraintResult {
+            label: constraint.label,
+            level: match constraint.level {
+                ConstraintLevel::Check => "check",
+                ConstraintLevel::Assert => "assert",
+            }
+            .to_string(),
+            expression: constraint.expression.0,
+            status: status.to_string(),
+            error,
+        }
+    }
+}
+
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub enum TestStatus {
@@ -505,6 +621,30 @@ impl WasmFunctionResponse {
         )
             .into_wasm()
     }
+
+    /// The log probability of each output token, as a JSON array, if the
+    /// client set `logprobs true`.
+    #[wasm_bindgen]
+    pub fn logprobs(&self) -> Option<String> {
+        self.function_response
+            .logprobs()
+            .and_then(|logprobs| serde_json::to_string(logprobs).ok())
+    }
+
+    /// The provider's JSON body, if the client set `capture_raw_response
+    /// true`.
+    #[wasm_bindgen]
+    pub fn raw_response(&self) -> Option<String> {
+        self.function_response
+            .raw_response()
+            .map(|raw| raw.to_string())
+    }
+
+    /// The retries and fallbacks of the call, as a JSON array.
+    #[wasm_bindgen]
+    pub fn attempts(&self) -> Option<String> {
+        serde_json::to_string(&self.function_response.attempts()).ok()
+    }
 }
 
 // TODO: What is supposed to happen with the serialized baml_value?
@@ -610,6 +750,9 @@ impl WasmTestResponse {
                             TestStatus::ConstraintsFailed
                         }
                     }
+                    baml_runtime::TestFailReason::TestExpectationFailure(_) => {
+                        TestStatus::AssertFailed
+                    }
                 },
             },
             Err(_) => TestStatus::UnableToRun,
@@ -665,6 +808,15 @@ impl WasmTestResponse {
         })
     }
 
+    /// The retries and fallbacks of the call, as a JSON array.
+    #[wasm_bindgen]
+    pub fn attempts(&self) -> Option<String> {
+        self.test_response
+            .as_ref()
+            .ok()
+            .and_then(|r| serde_json::to_string(&r.function_response.attempts()).ok())
+    }
+
     #[wasm_bindgen]
     pub fn failure_message(&self) -> Option<String> {
         match self.test_response.as_ref() {
@@ -798,6 +950,12 @@ impl WithRenderError for baml_runtime::TestFailReason<'_> {
                         baml_runtime::errors::ExposedError::FinishReasonError {
                             message, ..
                         } => Some(message.clone()),
+                        baml_runtime::errors::ExposedError::PartialFailure { message, .. } => {
+                            Some(message.clone())
+                        }
+                        baml_runtime::errors::ExposedError::Cancelled => {
+                            Some(exposed_error.to_string())
+                        }
                     },
                     None => Some(format!("{e:#}")),
                 }
@@ -819,6 +977,7 @@ impl WithRenderError for baml_runtime::TestFailReason<'_> {
                     .map_or("".to_string(), |name| format!("\nFailed assert: {name}"));
                 Some(format!("{checks_msg}{assert_msg}"))
             }
+            baml_runtime::TestFailReason::TestExpectationFailure(diff) => Some(diff.to_string()),
         }
     }
 }
@@ -1172,6 +1331,44 @@ impl WasmRuntime {
         .map(|error| error.msg())
     }
 
+    /// Runs `@check` and `@assert` expressions against `sample_json`, parsed
+    /// as `type_name` the way an LLM response would be, without calling an
+    /// LLM. `constraints` is a list of `{ level, label, expression }`.
+    #[wasm_bindgen]
+    pub fn evaluate_constraints(
+        &self,
+        type_name: &str,
+        constraints: JsValue,
+        sample_json: &str,
+    ) -> Result<Vec<WasmConstraintResult>, JsValue> {
+        let constraints: Vec<SampleConstraint> = serde_wasm_bindgen::from_value(constraints)
+            .map_err(|e| {
+                JsValue::from_str(&format!(
+                    "Expected constraints to be a list of {{ level, label, expression }}. {}",
+                    e
+                ))
+            })?;
+        let constraints = constraints
+            .into_iter()
+            .map(Constraint::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        let ctx = self
+            .runtime
+            .create_ctx_manager(BamlValue::String("wasm".to_string()), None)
+            .create_ctx_with_default();
+        evaluate_sample_constraints(
+            self.runtime.internal().ir(),
+            &ctx,
+            type_name,
+            sample_json,
+            constraints,
+        )
+        .map(|results| results.into_iter().map(Into::into).collect())
+        .map_err(|e| JsValue::from_str(&format!("{e:#}")))
+    }
+
     #[wasm_bindgen]
     pub fn required_env_vars(&self) -> Vec<String> {
         self.runtime
@@ -1542,7 +1739,7 @@ impl WasmFunction {
         test_name: String,
         wasm_call_context: &WasmCallContext,
         get_baml_src_cb: js_sys::Function,
-    ) -> JsResult<WasmPrompt> {
+    ) -> Result<WasmPrompt, WasmPromptRenderError> {
         let ctx = rt
             .runtime
             .create_ctx_manager(
@@ -1553,16 +1750,14 @@ impl WasmFunction {
 
         let params = rt
             .runtime
-            .get_test_params(&self.name, &test_name, &ctx, false)
-            .map_err(|e| JsError::new(format!("{e:?}").as_str()))?;
+            .get_test_params(&self.name, &test_name, &ctx, false)?;
 
         rt.runtime
             .internal()
             .render_prompt(&self.name, &ctx, &params, wasm_call_context.node_index)
             .await
-            .as_ref()
-            .map(|(p, scope, allowed)| (p, scope, allowed).into())
-            .map_err(|e| JsError::new(format!("{e:?}").as_str()))
+            .map(|(p, scope, allowed)| (&p, &scope, &allowed).into())
+            .map_err(WasmPromptRenderError::from)
     }
 
     #[wasm_bindgen]
@@ -1639,9 +1834,15 @@ impl WasmFunction {
         test_name: String,
         on_partial_response: js_sys::Function,
         get_baml_src_cb: js_sys::Function,
+        client_override: Option<String>,
     ) -> Result<WasmTestResponse, JsValue> {
         let rt = &rt.runtime;
 
+        let client_registry = client_override
+            .map(|client| ClientRegistry::with_primary_override(&client))
+            .transpose()
+            .map_err(|e| JsValue::from_str(&format!("Invalid client override: {e:#}")))?;
+
         let function_name = self.name.clone();
 
         let cb = Box::new(move |r| {
@@ -1658,7 +1859,13 @@ impl WasmFunction {
             js_fn_to_baml_src_reader(get_baml_src_cb),
         );
         let (test_response, span) = rt
-            .run_test(&function_name, &test_name, &ctx, Some(cb))
+            .run_test(
+                &function_name,
+                &test_name,
+                &ctx,
+                Some(cb),
+                client_registry.as_ref(),
+            )
             .await;
 
         Ok(WasmTestResponse {
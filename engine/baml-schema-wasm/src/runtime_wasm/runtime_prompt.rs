@@ -159,6 +159,10 @@ impl WasmChatMessagePart {
                     content: format!("Error resolving file '{}': {:#}", f.relpath.display(), e),
                 },
             },
+            BamlMediaContent::FileRef(r) => WasmChatMessagePartMedia {
+                r#type: WasmChatMessagePartMediaType::Error,
+                content: format!("Uploaded file '{}' can't be previewed", r.file_id),
+            },
         })
     }
 }
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use baml_runtime::baml_src_files;
+use clap::{Args, ValueEnum};
+use internal_baml_core::internal_baml_diagnostics::{DiagnosticsReport, SourceFile};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CheckFormat {
+    /// Annotated source snippets, for people.
+    Human,
+    /// A versioned JSON report, for editors and CI.
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    #[arg(long, help = "path/to/baml_src", default_value = "./baml_src")]
+    pub from: PathBuf,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CheckFormat::Human,
+        help = "how to print diagnostics"
+    )]
+    format: CheckFormat,
+}
+
+impl CheckArgs {
+    pub fn run(&self) -> Result<()> {
+        let files = baml_src_files(&self.from)
+            .context("Failed while searching for .baml files in baml_src/")?
+            .into_iter()
+            .map(|path| {
+                let contents = std::fs::read_to_string(&path)
+                    .context(format!("Failed to read {}", path.display()))?;
+                Ok(SourceFile::from((path, contents)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let diagnostics = internal_baml_core::validate(&self.from, files).diagnostics;
+
+        match self.format {
+            CheckFormat::Human => {
+                if diagnostics.has_errors() || diagnostics.has_warnings() {
+                    eprint!("{diagnostics}");
+                } else {
+                    log::info!("No problems found in {}", self.from.display());
+                }
+            }
+            CheckFormat::Json => {
+                let report = DiagnosticsReport::from(&diagnostics);
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+
+        if diagnostics.has_errors() {
+            anyhow::bail!(
+                "Found {} error(s) in {}",
+                diagnostics.errors().len(),
+                self.from.display()
+            );
+        }
+        Ok(())
+    }
+}
@@ -22,9 +22,15 @@ pub(crate) enum Commands {
     #[command(about = "Runs all generators in the baml_src directory")]
     Generate(baml_runtime::cli::generate::GenerateArgs),
 
+    #[command(about = "Validates the baml_src directory and reports diagnostics")]
+    Check(crate::check::CheckArgs),
+
     #[command(about = "Starts a server that translates LLM responses to BAML responses")]
     Serve(baml_runtime::cli::serve::ServeArgs),
 
+    #[command(about = "Runs the tests in the baml_src directory")]
+    Test(baml_runtime::cli::test::TestArgs),
+
     #[command(about = "Starts a development server")]
     Dev(baml_runtime::cli::dev::DevArgs),
 
@@ -37,6 +43,9 @@ pub(crate) enum Commands {
     #[command(about = "Deploy a BAML project to Boundary Cloud")]
     Deploy(crate::deploy::DeployArgs),
 
+    #[command(subcommand, about = "Generate BAML from other schemas or samples")]
+    Import(crate::import::ImportCommands),
+
     #[command(about = "Format BAML source files", name = "fmt", hide = true)]
     Format(crate::format::FormatArgs),
 }
@@ -54,10 +63,18 @@ impl RuntimeCli {
                 args.run(defaults)
             }
             Commands::Init(args) => args.run(defaults),
+            Commands::Check(args) => {
+                args.from = BamlRuntime::parse_baml_src_path(&args.from)?;
+                args.run()
+            }
             Commands::Serve(args) => {
                 args.from = BamlRuntime::parse_baml_src_path(&args.from)?;
                 args.run()
             }
+            Commands::Test(args) => {
+                args.from = BamlRuntime::parse_baml_src_path(&args.from)?;
+                args.run()
+            }
             Commands::Dev(args) => {
                 args.from = BamlRuntime::parse_baml_src_path(&args.from)?;
                 args.run(defaults)
@@ -69,6 +86,7 @@ impl RuntimeCli {
                 t.block_on(async { args.run_async().await })
             }
             Commands::Format(args) => args.run(),
+            Commands::Import(args) => args.run(),
         }
     }
 }
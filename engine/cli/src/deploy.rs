@@ -6,6 +6,7 @@ use dialoguer::theme::ColorfulTheme;
 use dialoguer::Confirm;
 use futures::join;
 use indexmap::IndexMap;
+use internal_baml_core::internal_baml_diagnostics::SourcePath;
 use std::cell::RefCell;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -35,14 +36,18 @@ pub struct DeployArgs {
     pub(super) api_url: String,
 }
 
-fn relative_path_to_baml_src(path: &Path, baml_src: &Path) -> Result<PathBuf> {
-    pathdiff::diff_paths(path, baml_src).ok_or_else(|| {
-        anyhow::anyhow!(
-            "Failed to compute relative path from {} to {}",
-            path.display(),
-            baml_src.display()
-        )
-    })
+/// The deployed file map is keyed by `/`-separated paths relative to baml_src.
+fn relative_path_to_baml_src(path: &Path, baml_src: &Path) -> Result<String> {
+    SourcePath::new(path)
+        .relative_to(&SourcePath::new(baml_src))
+        .map(|relative| SourcePath::new(relative).to_slash_string())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Failed to compute relative path from {} to {}",
+                path.display(),
+                baml_src.display()
+            )
+        })
 }
 
 impl DeployArgs {
@@ -384,9 +389,7 @@ Read the docs to learn more: https://docs.boundaryml.com/cloud
             .into_iter()
             .map(|f| {
                 Ok((
-                    relative_path_to_baml_src(&f, &self.from)?
-                        .to_string_lossy()
-                        .to_string(),
+                    relative_path_to_baml_src(&f, &self.from)?,
                     std::fs::read_to_string(&f)
                         .context(format!("Failed to read {}", f.display()))?,
                 ))
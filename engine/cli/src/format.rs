@@ -8,6 +8,15 @@ use internal_baml_core::internal_baml_schema_ast::{format_schema, FormatOptions}
 pub struct FormatArgs {
     #[arg(long, help = "path/to/baml_src", default_value = "./baml_src")]
     pub from: PathBuf,
+
+    #[arg(
+        long,
+        help = "order client and function fields the same way everywhere, and sort client options"
+    )]
+    pub canonical_order: bool,
+
+    #[arg(long, help = "line up the trailing comments of adjacent fields")]
+    pub align_trailing_comments: bool,
 }
 
 impl FormatArgs {
@@ -18,6 +27,8 @@ impl FormatArgs {
             FormatOptions {
                 indent_width: 4,
                 fail_on_unhandled_rule: false,
+                canonical_order: self.canonical_order,
+                align_trailing_comments: self.align_trailing_comments,
             },
         )?;
 
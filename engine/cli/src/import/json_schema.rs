@@ -0,0 +1,651 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use baml_types::{FieldType, LiteralValue, TypeValue};
+use clap::Args;
+use indexmap::IndexMap;
+use internal_baml_core::internal_baml_schema_ast::{FieldBuilder, SchemaBuilder};
+use serde_json::Value;
+
+use super::{class_name, enum_value_name, field_name, unique};
+
+#[derive(Args, Debug)]
+pub struct JsonSchemaArgs {
+    #[arg(help = "path/to/spec: an OpenAPI spec or a JSON Schema, as JSON or YAML")]
+    pub spec: PathBuf,
+
+    #[arg(
+        long,
+        help = "name of the class for the root of a JSON Schema (default: its title, or the file name)"
+    )]
+    pub name: Option<String>,
+
+    #[arg(long, help = "path/to/output.baml (default: print to stdout)")]
+    pub to: Option<PathBuf>,
+}
+
+impl JsonSchemaArgs {
+    pub(super) fn run(&self) -> Result<()> {
+        let contents = fs::read_to_string(&self.spec)
+            .context(format!("Failed to read {}", self.spec.display()))?;
+        let spec = parse_spec(&self.spec, &contents)
+            .context(format!("Failed to parse {}", self.spec.display()))?;
+        let name = match (&self.name, spec.get("title").and_then(Value::as_str)) {
+            (Some(name), _) => name.clone(),
+            (None, Some(title)) => title.to_string(),
+            (None, None) => self
+                .spec
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+
+        let source = schema_from_spec(&spec, &name)?;
+        match &self.to {
+            Some(to) => {
+                fs::write(to, source)?;
+                log::info!("Wrote {} from {}", to.display(), self.spec.display());
+            }
+            None => print!("{source}"),
+        }
+        Ok(())
+    }
+}
+
+fn parse_spec(path: &Path, contents: &str) -> Result<Value> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => Ok(yaml_to_json(serde_yaml::from_str(contents)?)),
+        _ => Ok(serde_json::from_str(contents)?),
+    }
+}
+
+/// YAML allows keys that aren't strings, like the status codes of an OpenAPI
+/// spec's responses: they are written out as strings.
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(value) => Value::Bool(value),
+        serde_yaml::Value::Number(number) => serde_json::to_value(number).unwrap_or(Value::Null),
+        serde_yaml::Value::String(value) => Value::String(value),
+        serde_yaml::Value::Sequence(items) => {
+            Value::Array(items.into_iter().map(yaml_to_json).collect())
+        }
+        serde_yaml::Value::Mapping(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = match yaml_to_json(key) {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    (key, yaml_to_json(value))
+                })
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+/// Declares a class or enum for each object or enum schema of `spec`, and
+/// renders them as BAML. Schemas are read from an OpenAPI spec's components,
+/// or from a JSON Schema's `$defs` or `definitions`; the root of a JSON Schema
+/// becomes a class named `name`.
+///
+/// Other schemas, like unions and arrays, are inlined where they are used.
+fn schema_from_spec(spec: &Value, name: &str) -> Result<String> {
+    let Some(root) = spec.as_object() else {
+        anyhow::bail!("Expected an OpenAPI spec or a JSON Schema object");
+    };
+
+    let mut defs = IndexMap::new();
+    for (pointer, container) in [
+        ("#/components/schemas/", spec.pointer("/components/schemas")),
+        ("#/definitions/", spec.get("definitions")),
+        ("#/$defs/", spec.get("$defs")),
+    ] {
+        for (key, def) in container.and_then(Value::as_object).into_iter().flatten() {
+            let escaped = key.replace('~', "~0").replace('/', "~1");
+            defs.insert(format!("{pointer}{escaped}"), (key.as_str(), def));
+        }
+    }
+
+    let mut converter = Converter {
+        defs,
+        declared: HashMap::new(),
+        names: HashSet::new(),
+        inlining: vec![],
+        schema: SchemaBuilder::new(),
+    };
+
+    // Name every class and enum first, so that `$ref`s resolve to them
+    // whatever order they are declared in.
+    let mut declarations = vec![];
+    let is_openapi = root.contains_key("openapi") || root.contains_key("swagger");
+    if !is_openapi && is_class(spec) {
+        declarations.push(("#".to_string(), spec));
+    }
+    declarations.extend(
+        converter
+            .defs
+            .iter()
+            .filter(|(_, (_, def))| is_class(def) || is_enum(def))
+            .map(|(reference, (_, def))| (reference.clone(), *def)),
+    );
+    if declarations.is_empty() {
+        anyhow::bail!("Found no object or enum schemas to convert");
+    }
+    for (reference, def) in &declarations {
+        let key = match converter.defs.get(reference) {
+            Some((key, _)) => *key,
+            None => name,
+        };
+        let declared_name = unique(&mut converter.names, class_name(key));
+        let r#type = if is_class(def) {
+            FieldType::Class(declared_name)
+        } else {
+            FieldType::Enum(declared_name)
+        };
+        converter.declared.insert(reference.clone(), r#type);
+    }
+
+    for (reference, def) in declarations {
+        match converter.declared[&reference].clone() {
+            FieldType::Class(name) => converter.add_class(&name, def)?,
+            FieldType::Enum(name) => converter.add_enum(&name, def),
+            _ => unreachable!(),
+        }
+    }
+    converter.schema.to_source()
+}
+
+struct Converter<'a> {
+    /// The schemas a `$ref` can point to, with their keys.
+    defs: IndexMap<String, (&'a str, &'a Value)>,
+    /// The type of each schema converted so far, by `$ref`: its class or
+    /// enum, or the type it was inlined as.
+    declared: HashMap<String, FieldType>,
+    /// The classes and enums declared so far.
+    names: HashSet<String>,
+    /// The `$ref`s being inlined, to catch schemas that contain themselves.
+    inlining: Vec<String>,
+    schema: SchemaBuilder,
+}
+
+impl<'a> Converter<'a> {
+    fn add_class(&mut self, name: &str, schema: &'a Value) -> Result<()> {
+        // Reserve the class's place before its fields add theirs.
+        self.schema.class(name);
+
+        let mut properties = IndexMap::new();
+        let mut required = HashSet::new();
+        self.collect_properties(schema, &mut properties, &mut required)?;
+
+        let mut field_names = HashSet::new();
+        for (key, property) in properties {
+            let mut r#type = self.field_type(property, &format!("{name} {key}"))?;
+            if !required.contains(key) && !r#type.is_optional() {
+                r#type = r#type.as_optional();
+            }
+            let field_name = unique(&mut field_names, field_name(key));
+            let mut field = FieldBuilder::new(&field_name).r#type(r#type);
+            if field_name != key {
+                field = field.alias(key);
+            }
+            if let Some(description) = property.get("description").and_then(Value::as_str) {
+                field = field.description(description);
+            }
+            self.schema.class(name).add_field(field);
+        }
+        Ok(())
+    }
+
+    fn add_enum(&mut self, name: &str, schema: &'a Value) {
+        let mut value_names = HashSet::new();
+        for value in schema["enum"].as_array().into_iter().flatten() {
+            let Some(value) = value.as_str() else {
+                continue;
+            };
+            let value_name = unique(&mut value_names, enum_value_name(value));
+            let mut builder = FieldBuilder::new(&value_name);
+            if value_name != value {
+                builder = builder.alias(value);
+            }
+            self.schema.r#enum(name).add_value(builder);
+        }
+    }
+
+    /// The properties of an object schema, including those it gets from
+    /// `allOf`, like a schema that extends another.
+    fn collect_properties(
+        &self,
+        schema: &'a Value,
+        properties: &mut IndexMap<&'a str, &'a Value>,
+        required: &mut HashSet<&'a str>,
+    ) -> Result<()> {
+        let schema = self.resolve(schema)?;
+        for member in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            self.collect_properties(member, properties, required)?;
+        }
+        for (key, property) in schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            properties.insert(key, property);
+        }
+        required.extend(
+            schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str),
+        );
+        Ok(())
+    }
+
+    /// Follows `$ref`s to the schema they point to.
+    fn resolve(&self, mut schema: &'a Value) -> Result<&'a Value> {
+        for _ in 0..=self.defs.len() {
+            let Some(reference) = schema.get("$ref") else {
+                return Ok(schema);
+            };
+            schema = self.lookup(reference)?.1;
+        }
+        anyhow::bail!("Found a cycle of $refs")
+    }
+
+    fn lookup(&self, reference: &Value) -> Result<(&'a str, &'a Value)> {
+        let Some(reference) = reference.as_str() else {
+            anyhow::bail!("$ref must be a string, found {reference}");
+        };
+        match self.defs.get(reference) {
+            Some(def) => Ok(*def),
+            None => anyhow::bail!(
+                "Can't resolve $ref {reference}: only references to schemas in the same file are supported"
+            ),
+        }
+    }
+
+    /// The type of `schema`. Objects without a `$ref` are declared as classes
+    /// named after `context`, the class and property they appear in.
+    fn field_type(&mut self, schema: &'a Value, context: &str) -> Result<FieldType> {
+        let r#type = self.base_type(schema, context)?;
+        if is_nullable(schema)
+            && !r#type.is_optional()
+            && r#type != FieldType::Primitive(TypeValue::Null)
+        {
+            Ok(r#type.as_optional())
+        } else {
+            Ok(r#type)
+        }
+    }
+
+    fn base_type(&mut self, schema: &'a Value, context: &str) -> Result<FieldType> {
+        if let Some(reference) = schema.get("$ref") {
+            if let Some(r#type) = reference.as_str().and_then(|r| self.declared.get(r)) {
+                let nullable = reference
+                    .as_str()
+                    .and_then(|r| self.defs.get(r))
+                    .is_some_and(|(_, def)| is_nullable(def));
+                return Ok(if nullable && !r#type.is_optional() {
+                    r#type.clone().as_optional()
+                } else {
+                    r#type.clone()
+                });
+            }
+            let (key, def) = self.lookup(reference)?;
+            let reference = reference.as_str().unwrap_or_default();
+            if self.inlining.iter().any(|r| r == reference) {
+                anyhow::bail!(
+                    "{key} contains itself without an object in between, which BAML can't express"
+                );
+            }
+            self.inlining.push(reference.to_string());
+            let r#type = self.field_type(def, key);
+            self.inlining.pop();
+            // Reuse the type, so that any classes it declares are declared once.
+            if let Ok(r#type) = &r#type {
+                self.declared.insert(reference.to_string(), r#type.clone());
+            }
+            return r#type;
+        }
+
+        if let Some(value) = schema.get("const") {
+            return literal(value);
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let literals = values
+                .iter()
+                .filter(|value| !value.is_null())
+                .map(literal)
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(union(literals));
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(members) = schema.get(key).and_then(Value::as_array) {
+                let mut types = vec![];
+                for member in members.iter().filter(|member| types_of(member) != ["null"]) {
+                    types.push(self.field_type(member, context)?);
+                }
+                let nullable = members.iter().any(|member| types_of(member) == ["null"]);
+                let r#type = union(types);
+                return Ok(if nullable && !r#type.is_optional() {
+                    r#type.as_optional()
+                } else {
+                    r#type
+                });
+            }
+        }
+        if is_class(schema) {
+            let name = unique(&mut self.names, class_name(context));
+            self.add_class(&name, schema)?;
+            return Ok(FieldType::Class(name));
+        }
+        if let Some([member]) = schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            return self.field_type(member, context);
+        }
+
+        let types = types_of(schema);
+        if types.is_empty() {
+            return Ok(FieldType::Primitive(TypeValue::String));
+        }
+        let mut members = vec![];
+        for name in types.into_iter().filter(|name| *name != "null") {
+            members.push(match name {
+                "string" => FieldType::Primitive(TypeValue::String),
+                "integer" => FieldType::Primitive(TypeValue::Int),
+                "number" => FieldType::Primitive(TypeValue::Float),
+                "boolean" => FieldType::Primitive(TypeValue::Bool),
+                "array" => match schema.get("items") {
+                    Some(items) => self.field_type(items, context)?.as_list(),
+                    None => FieldType::Primitive(TypeValue::String).as_list(),
+                },
+                "object" => FieldType::map(
+                    FieldType::Primitive(TypeValue::String),
+                    match schema.get("additionalProperties") {
+                        Some(values @ Value::Object(_)) => self.field_type(values, context)?,
+                        _ => FieldType::Primitive(TypeValue::String),
+                    },
+                ),
+                other => anyhow::bail!("Unsupported type {other}"),
+            });
+        }
+        Ok(union(members))
+    }
+}
+
+/// Whether `schema` is an object with known properties, which becomes a
+/// class.
+fn is_class(schema: &Value) -> bool {
+    schema.get("properties").is_some()
+        || schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .is_some_and(|members| members.len() > 1)
+}
+
+/// Whether `schema` is an enum of strings, which becomes a BAML enum.
+fn is_enum(schema: &Value) -> bool {
+    schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .is_some_and(|values| {
+            values.iter().any(Value::is_string)
+                && values
+                    .iter()
+                    .all(|value| value.is_string() || value.is_null())
+        })
+}
+
+/// OpenAPI 3.0 marks nullable schemas with `nullable`, JSON Schema and
+/// OpenAPI 3.1 with a `null` type.
+fn is_nullable(schema: &Value) -> bool {
+    let types = types_of(schema);
+    schema.get("nullable") == Some(&Value::Bool(true))
+        || (types.len() > 1 && types.contains(&"null"))
+        || schema
+            .get("enum")
+            .and_then(Value::as_array)
+            .is_some_and(|values| values.iter().any(Value::is_null))
+}
+
+/// The names in `type`, which is a name or a list of names.
+fn types_of(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    }
+}
+
+/// A literal type for `value`. BAML has no boolean literals, so booleans are
+/// just `bool`.
+fn literal(value: &Value) -> Result<FieldType> {
+    let literal = match value {
+        Value::String(value) => LiteralValue::String(value.clone()),
+        Value::Bool(_) => return Ok(FieldType::Primitive(TypeValue::Bool)),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => LiteralValue::Int(value),
+            None => anyhow::bail!("Only integers can be used as literals, found {number}"),
+        },
+        other => anyhow::bail!(
+            "Only strings, integers and booleans can be used as literals, found {other}"
+        ),
+    };
+    Ok(FieldType::Literal(literal))
+}
+
+fn union(mut types: Vec<FieldType>) -> FieldType {
+    types.dedup();
+    match types.len() {
+        0 => FieldType::Primitive(TypeValue::Null),
+        1 => types.remove(0),
+        _ => FieldType::Union(types),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_openapi_components() {
+        let spec = parse_spec(
+            Path::new("pets.yaml"),
+            indoc::indoc! {r##"
+                openapi: 3.0.3
+                info:
+                  title: Pets
+                  version: "1"
+                paths:
+                  /pets:
+                    get:
+                      responses:
+                        200:
+                          description: OK
+                components:
+                  schemas:
+                    Pet:
+                      type: object
+                      required: [id, name, kind]
+                      properties:
+                        id:
+                          type: integer
+                        name:
+                          type: string
+                          description: What the owner calls it
+                        kind:
+                          $ref: '#/components/schemas/Kind'
+                        tags:
+                          type: array
+                          items:
+                            type: string
+                        owner:
+                          $ref: '#/components/schemas/Owner'
+                        weight:
+                          type: number
+                          nullable: true
+                    Kind:
+                      type: string
+                      enum: [dog, cat, guinea-pig]
+                    Owner:
+                      oneOf:
+                        - $ref: '#/components/schemas/Person'
+                        - type: string
+                    Person:
+                      type: object
+                      properties:
+                        full name:
+                          type: string
+                        address:
+                          type: object
+                          properties:
+                            city:
+                              type: string
+                    Dog:
+                      allOf:
+                        - $ref: '#/components/schemas/Pet'
+                        - type: object
+                          required: [breed]
+                          properties:
+                            breed:
+                              type: string
+                              enum: [lab, poodle]
+                            traits:
+                              type: object
+                              additionalProperties:
+                                type: integer
+            "##},
+        )
+        .unwrap();
+
+        assert_eq!(
+            schema_from_spec(&spec, "pets").unwrap(),
+            indoc::indoc! {r#"
+                class Pet {
+                  id int
+                  name string @description("What the owner calls it")
+                  kind Kind
+                  tags string[]?
+                  owner (Person | string)?
+                  weight float?
+                }
+
+                enum Kind {
+                  dog
+                  cat
+                  GuineaPig @alias("guinea-pig")
+                }
+
+                class Person {
+                  full_name string? @alias("full name")
+                  address PersonAddress?
+                }
+
+                class PersonAddress {
+                  city string?
+                }
+
+                class Dog {
+                  id int
+                  name string @description("What the owner calls it")
+                  kind Kind
+                  tags string[]?
+                  owner (Person | string)?
+                  weight float?
+                  breed ("lab" | "poodle")
+                  traits map<string, int>?
+                }
+            "#}
+        );
+    }
+
+    #[test]
+    fn converts_json_schema() {
+        let spec = serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "invoice",
+            "type": "object",
+            "required": ["number", "lines"],
+            "properties": {
+                "number": { "type": "string" },
+                "status": { "$ref": "#/$defs/status" },
+                "lines": { "type": "array", "items": { "$ref": "#/$defs/line" } },
+                "note": { "type": ["string", "null"] },
+                "parent": { "$ref": "#" }
+            },
+            "$defs": {
+                "status": { "enum": ["DRAFT", "PAID", null] },
+                "line": {
+                    "type": "object",
+                    "required": ["amount", "currency"],
+                    "properties": {
+                        "amount": { "type": "number" },
+                        "currency": { "const": "EUR" }
+                    }
+                }
+            }
+        });
+
+        assert_eq!(
+            schema_from_spec(&spec, "invoice").unwrap(),
+            indoc::indoc! {r#"
+                class Invoice {
+                  number string
+                  status Status?
+                  lines Line[]
+                  note string?
+                  parent Invoice?
+                }
+
+                enum Status {
+                  DRAFT
+                  PAID
+                }
+
+                class Line {
+                  amount float
+                  currency "EUR"
+                }
+            "#}
+        );
+    }
+
+    #[test]
+    fn rejects_schemas_that_contain_themselves() {
+        let spec = serde_json::json!({
+            "openapi": "3.1.0",
+            "components": {
+                "schemas": {
+                    "Json": {
+                        "oneOf": [
+                            { "type": "string" },
+                            { "type": "array", "items": { "$ref": "#/components/schemas/Json" } }
+                        ]
+                    },
+                    "Document": {
+                        "properties": { "body": { "$ref": "#/components/schemas/Json" } }
+                    }
+                }
+            }
+        });
+
+        let err = schema_from_spec(&spec, "spec").unwrap_err();
+        assert!(err.to_string().contains("Json contains itself"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use clap::Subcommand;
+use internal_baml_core::internal_baml_schema_ast::is_valid_identifier;
+
+mod json_schema;
+mod sample;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ImportCommands {
+    #[command(about = "Generate BAML classes and enums from sample JSON documents")]
+    JsonSample(sample::SampleArgs),
+
+    #[command(about = "Generate a BAML class and enums from sample CSV files")]
+    CsvSample(sample::SampleArgs),
+
+    #[command(
+        about = "Generate BAML classes and enums from an OpenAPI spec or a JSON Schema",
+        visible_alias = "openapi"
+    )]
+    JsonSchema(json_schema::JsonSchemaArgs),
+}
+
+impl ImportCommands {
+    pub fn run(&self) -> Result<()> {
+        match self {
+            ImportCommands::JsonSample(args) => args.run(sample::read_json),
+            ImportCommands::CsvSample(args) => args.run(sample::read_csv),
+            ImportCommands::JsonSchema(args) => args.run(),
+        }
+    }
+}
+
+/// `shipping_address` or `shipping-address` becomes `ShippingAddress`.
+pub(super) fn class_name(key: &str) -> String {
+    let name = key
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect::<String>();
+    if is_valid_identifier(&name) {
+        name
+    } else {
+        format!("Class{name}")
+    }
+}
+
+/// `in stock` becomes `InStock`; values that are valid as is are kept.
+pub(super) fn enum_value_name(value: &str) -> String {
+    if is_valid_identifier(value) {
+        value.to_string()
+    } else {
+        class_name(value)
+    }
+}
+
+/// `first name` becomes `first_name`; names that are valid as is are kept.
+pub(super) fn field_name(key: &str) -> String {
+    if is_valid_identifier(key) {
+        return key.to_string();
+    }
+    let name = key
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if is_valid_identifier(&name) {
+        name
+    } else {
+        format!("field_{name}")
+    }
+}
+
+/// `name`, or `name2`, `name3`... if it is already taken.
+pub(super) fn unique(taken: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{name}{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
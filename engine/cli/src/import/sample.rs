@@ -0,0 +1,485 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use baml_types::{BamlMap, BamlValue, FieldType, TypeValue};
+use clap::Args;
+use indexmap::{IndexMap, IndexSet};
+use internal_baml_core::{
+    internal_baml_schema_ast::{FieldBuilder, SchemaBuilder},
+    ir::infer_type,
+};
+
+use super::{class_name, enum_value_name, field_name, unique};
+
+#[derive(Args, Debug)]
+pub struct SampleArgs {
+    #[arg(
+        required = true,
+        help = "path/to/samples: JSON objects or arrays of objects, or CSV files with a header"
+    )]
+    pub samples: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        help = "name of the top-level class (default: from the first file name)"
+    )]
+    pub name: Option<String>,
+
+    #[arg(long, help = "path/to/output.baml (default: print to stdout)")]
+    pub to: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value_t = 5,
+        help = "largest enum to make from a string field's values, or 0 for no enums"
+    )]
+    pub max_enum_values: usize,
+}
+
+impl SampleArgs {
+    pub(super) fn run(&self, read: fn(&str) -> Result<Vec<BamlValue>>) -> Result<()> {
+        let mut samples = vec![];
+        for path in &self.samples {
+            let contents =
+                fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+            samples.extend(read(&contents).context(format!("Failed to parse {}", path.display()))?);
+        }
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => class_name(
+                &self.samples[0]
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+        };
+
+        let source = schema_from_samples(&name, &samples, self.max_enum_values)?;
+        match &self.to {
+            Some(to) => {
+                fs::write(to, source)?;
+                log::info!("Wrote {}", to.display());
+            }
+            None => print!("{source}"),
+        }
+        Ok(())
+    }
+}
+
+/// An object, or the items of an array.
+pub(super) fn read_json(contents: &str) -> Result<Vec<BamlValue>> {
+    match serde_json::from_str(contents)? {
+        BamlValue::List(items) => Ok(items),
+        value => Ok(vec![value]),
+    }
+}
+
+/// An object per row, keyed by the header.
+pub(super) fn read_csv(contents: &str) -> Result<Vec<BamlValue>> {
+    let mut rows = csv_rows(contents)?.into_iter();
+    let Some(header) = rows.next() else {
+        anyhow::bail!("Expected a header row");
+    };
+    rows.enumerate()
+        .map(|(idx, row)| {
+            if row.len() != header.len() {
+                anyhow::bail!(
+                    "Row {} has {} cells, but the header has {}",
+                    idx + 2,
+                    row.len(),
+                    header.len()
+                );
+            }
+            Ok(BamlValue::Map(
+                header
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(|cell| csv_value(cell)))
+                    .collect::<BamlMap<_, _>>(),
+            ))
+        })
+        .collect()
+}
+
+/// Cells are numbers or booleans if they parse as one, and null if empty.
+fn csv_value(cell: &str) -> BamlValue {
+    if cell.is_empty() {
+        BamlValue::Null
+    } else if let Ok(value) = cell.parse::<i64>() {
+        BamlValue::Int(value)
+    } else if let Ok(value) = cell.parse::<f64>() {
+        BamlValue::Float(value)
+    } else if let Ok(value) = cell.parse::<bool>() {
+        BamlValue::Bool(value)
+    } else {
+        BamlValue::String(cell.to_string())
+    }
+}
+
+/// Splits CSV into rows of cells, as in RFC 4180: a cell may be quoted, with
+/// `""` for a quote inside it.
+fn csv_rows(contents: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => cell.push(c),
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => cell.push(c),
+        }
+    }
+    if quoted {
+        anyhow::bail!("A quoted cell is never closed");
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Past this many distinct values, a string field is never an enum, so they
+/// stop being tracked.
+const MAX_TRACKED_VALUES: usize = 100;
+
+/// The shape of the values seen at one place in the samples.
+#[derive(Debug, Clone)]
+enum Shape {
+    /// Nothing was seen, e.g. the items of an empty array.
+    Unknown,
+    Null,
+    /// An `int`, `float` or `bool`.
+    Primitive(FieldType),
+    /// The number of strings seen, and the distinct ones unless there were
+    /// too many.
+    String(usize, Option<IndexSet<String>>),
+    List(Box<Shape>),
+    /// The number of objects seen, and each key with the number of objects
+    /// it was in.
+    Object(usize, IndexMap<String, (usize, Shape)>),
+    /// Values of different kinds, at most one of each.
+    Union(Vec<Shape>),
+}
+
+impl Shape {
+    fn of(value: &BamlValue) -> Shape {
+        match value {
+            BamlValue::Null => Shape::Null,
+            BamlValue::String(value) => Shape::String(1, Some(IndexSet::from([value.to_string()]))),
+            BamlValue::List(items) => Shape::List(Box::new(
+                items
+                    .iter()
+                    .map(Shape::of)
+                    .fold(Shape::Unknown, Shape::merge),
+            )),
+            BamlValue::Map(fields) => Shape::Object(
+                1,
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), (1, Shape::of(value))))
+                    .collect(),
+            ),
+            value => infer_type(value).map_or(Shape::Unknown, Shape::Primitive),
+        }
+    }
+
+    /// Which members of a union two shapes would be merged into.
+    fn kind(&self) -> u8 {
+        match self {
+            Shape::Unknown | Shape::Union(_) => 0,
+            Shape::Null => 1,
+            Shape::Primitive(r#type) if is_number(r#type) => 2,
+            Shape::Primitive(_) => 3,
+            Shape::String(..) => 4,
+            Shape::List(_) => 5,
+            Shape::Object(..) => 6,
+        }
+    }
+
+    /// Widens two shapes into one that covers both: ints and floats make
+    /// floats, and different kinds make a union.
+    fn merge(self, other: Shape) -> Shape {
+        match (self, other) {
+            (Shape::Unknown, shape) | (shape, Shape::Unknown) => shape,
+            (Shape::Union(members), other) | (other, Shape::Union(members)) => {
+                let others = match other {
+                    Shape::Union(others) => others,
+                    other => vec![other],
+                };
+                let mut members = members;
+                for other in others {
+                    match members.iter().position(|m| m.kind() == other.kind()) {
+                        Some(idx) => {
+                            let member = std::mem::replace(&mut members[idx], Shape::Unknown);
+                            members[idx] = member.merge(other);
+                        }
+                        None => members.push(other),
+                    }
+                }
+                Shape::Union(members)
+            }
+            (Shape::Primitive(a), Shape::Primitive(b))
+                if a != b && is_number(&a) && is_number(&b) =>
+            {
+                Shape::Primitive(FieldType::Primitive(TypeValue::Float))
+            }
+            (Shape::String(a_count, a), Shape::String(b_count, b)) => {
+                let values = a.zip(b).and_then(|(mut a, b)| {
+                    a.extend(b);
+                    (a.len() <= MAX_TRACKED_VALUES).then_some(a)
+                });
+                Shape::String(a_count + b_count, values)
+            }
+            (Shape::List(a), Shape::List(b)) => Shape::List(Box::new(a.merge(*b))),
+            (Shape::Object(a_objects, mut a), Shape::Object(b_objects, b)) => {
+                for (key, (count, shape)) in b {
+                    match a.get_mut(&key) {
+                        Some((a_count, a_shape)) => {
+                            *a_count += count;
+                            *a_shape = std::mem::replace(a_shape, Shape::Unknown).merge(shape);
+                        }
+                        None => {
+                            a.insert(key, (count, shape));
+                        }
+                    }
+                }
+                Shape::Object(a_objects + b_objects, a)
+            }
+            (a, b) if a.kind() == b.kind() => a,
+            (a, b) => Shape::Union(vec![a, b]),
+        }
+    }
+}
+
+fn is_number(r#type: &FieldType) -> bool {
+    matches!(
+        r#type,
+        FieldType::Primitive(TypeValue::Int | TypeValue::Float)
+    )
+}
+
+/// Infers classes and enums for `samples`, objects of one class, and renders
+/// them as BAML with `name` as that class.
+fn schema_from_samples(
+    name: &str,
+    samples: &[BamlValue],
+    max_enum_values: usize,
+) -> Result<String> {
+    let shape = samples
+        .iter()
+        .map(Shape::of)
+        .fold(Shape::Unknown, Shape::merge);
+    let Shape::Object(count, fields) = shape else {
+        anyhow::bail!("Expected objects, or arrays of objects");
+    };
+
+    let mut importer = Importer {
+        schema: SchemaBuilder::new(),
+        names: HashSet::new(),
+        max_enum_values,
+    };
+    importer.add_class(name, count, fields);
+    importer.schema.to_source()
+}
+
+struct Importer {
+    schema: SchemaBuilder,
+    /// The classes and enums added so far.
+    names: HashSet<String>,
+    max_enum_values: usize,
+}
+
+impl Importer {
+    fn add_class(
+        &mut self,
+        name: &str,
+        count: usize,
+        fields: IndexMap<String, (usize, Shape)>,
+    ) -> String {
+        let name = unique(&mut self.names, class_name(name));
+        // Reserve the class's place before its fields add theirs.
+        self.schema.class(&name);
+
+        let mut field_names = HashSet::new();
+        for (key, (key_count, shape)) in fields {
+            let mut r#type = self.field_type(&key, shape);
+            if key_count < count && !r#type.is_optional() {
+                r#type = r#type.as_optional();
+            }
+            let field_name = unique(&mut field_names, field_name(&key));
+            let mut field = FieldBuilder::new(&field_name).r#type(r#type);
+            if field_name != key {
+                field = field.alias(&key);
+            }
+            self.schema.class(&name).add_field(field);
+        }
+        name
+    }
+
+    fn add_enum(&mut self, name: &str, values: IndexSet<String>) -> String {
+        let name = unique(&mut self.names, class_name(name));
+        let mut value_names = HashSet::new();
+        for value in values {
+            let value_name = unique(&mut value_names, enum_value_name(&value));
+            let mut builder = FieldBuilder::new(&value_name);
+            if value_name != value {
+                builder = builder.alias(&value);
+            }
+            self.schema.r#enum(&name).add_value(builder);
+        }
+        name
+    }
+
+    /// A string field is an enum if it has a few distinct values, each seen
+    /// at least twice on average, so that one sample doesn't make an enum of
+    /// every string.
+    fn is_enum(&self, count: usize, values: &IndexSet<String>) -> bool {
+        (2..=self.max_enum_values).contains(&values.len()) && count >= 2 * values.len()
+    }
+
+    fn field_type(&mut self, key: &str, shape: Shape) -> FieldType {
+        match shape {
+            Shape::Unknown => FieldType::Primitive(TypeValue::String),
+            Shape::Null => FieldType::Primitive(TypeValue::String).as_optional(),
+            Shape::Primitive(r#type) => r#type,
+            Shape::String(count, Some(values)) if self.is_enum(count, &values) => {
+                FieldType::Enum(self.add_enum(key, values))
+            }
+            Shape::String(..) => FieldType::Primitive(TypeValue::String),
+            Shape::List(items) => self.field_type(key, *items).as_list(),
+            Shape::Object(count, fields) => FieldType::Class(self.add_class(key, count, fields)),
+            Shape::Union(members) => {
+                let nullable = members.iter().any(|m| matches!(m, Shape::Null));
+                let mut types = members
+                    .into_iter()
+                    .filter(|m| !matches!(m, Shape::Null))
+                    .map(|m| self.field_type(key, m))
+                    .collect::<Vec<_>>();
+                let r#type = match types.len() {
+                    0 => FieldType::Primitive(TypeValue::String),
+                    1 => types.remove(0),
+                    _ => FieldType::Union(types),
+                };
+                if nullable {
+                    r#type.as_optional()
+                } else {
+                    r#type
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_classes_from_json_samples() {
+        let mut samples = read_json(
+            r#"[
+                {
+                    "id": 1,
+                    "first name": "Ada",
+                    "score": 9,
+                    "shipping_address": { "city": "London", "zip": null },
+                    "tags": ["math"]
+                },
+                {
+                    "id": 2,
+                    "first name": "Grace",
+                    "score": 9.5,
+                    "shipping_address": { "city": "Arlington", "zip": "22201" },
+                    "tags": []
+                }
+            ]"#,
+        )
+        .unwrap();
+        samples.extend(
+            read_json(r#"{ "id": "u3", "first name": "Alan", "nickname": "Prof" }"#).unwrap(),
+        );
+
+        assert_eq!(
+            schema_from_samples("user", &samples, 5).unwrap(),
+            indoc::indoc! {r#"
+                class User {
+                  id (int | string)
+                  first_name string @alias("first name")
+                  score float?
+                  shipping_address ShippingAddress?
+                  tags string[]?
+                  nickname string?
+                }
+
+                class ShippingAddress {
+                  city string
+                  zip string?
+                }
+            "#}
+        );
+
+        let not_objects = read_json("[1, 2]").unwrap();
+        assert!(schema_from_samples("user", &not_objects, 5).is_err());
+    }
+
+    #[test]
+    fn infers_enums_from_csv_samples() {
+        let samples = read_csv(
+            "sku,status,unit price,note\r\n\
+             A1,in stock,9.99,\r\n\
+             A2,sold out,12,\"says \"\"hi\"\", twice\"\r\n\
+             A3,in stock,3.5,\r\n\
+             A4,sold out,1,\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            samples[1],
+            BamlValue::Map(BamlMap::from_iter([
+                ("sku".to_string(), BamlValue::String("A2".to_string())),
+                (
+                    "status".to_string(),
+                    BamlValue::String("sold out".to_string())
+                ),
+                ("unit price".to_string(), BamlValue::Int(12)),
+                (
+                    "note".to_string(),
+                    BamlValue::String("says \"hi\", twice".to_string())
+                ),
+            ]))
+        );
+
+        assert_eq!(
+            schema_from_samples("product", &samples, 5).unwrap(),
+            indoc::indoc! {r#"
+                class Product {
+                  sku string
+                  status Status
+                  unit_price float @alias("unit price")
+                  note string?
+                }
+
+                enum Status {
+                  InStock @alias("in stock")
+                  SoldOut @alias("sold out")
+                }
+            "#}
+        );
+        assert!(!schema_from_samples("product", &samples, 0)
+            .unwrap()
+            .contains("enum"));
+    }
+}
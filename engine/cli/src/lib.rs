@@ -1,9 +1,11 @@
 pub(crate) mod api_client;
 pub(crate) mod auth;
+pub(crate) mod check;
 pub(crate) mod colordiff;
 pub(crate) mod commands;
 pub(crate) mod deploy;
 pub(crate) mod format;
+pub(crate) mod import;
 pub(crate) mod propelauth;
 pub(crate) mod tui;
 
@@ -3,6 +3,7 @@ use baml_types::{Constraint, ConstraintLevel, FieldType};
 use indexmap::IndexMap;
 use internal_baml_core::{
     configuration::{GeneratorDefaultClientMode, GeneratorOutputType},
+    internal_baml_diagnostics::SourcePath,
     ir::repr::IntermediateRepr,
 };
 use std::{
@@ -36,7 +37,14 @@ pub struct GeneratorArgs {
 }
 
 fn relative_path_to_baml_src(path: &Path, baml_src: &Path) -> Result<PathBuf> {
-    pathdiff::diff_paths(path, baml_src).ok_or_else(|| {
+    // Both sides must be spelled the same way (e.g. no `\\?\` prefix on only
+    // one of them) or pathdiff produces a path that walks out of baml_src.
+    let path = SourcePath::new(path);
+    let baml_src = SourcePath::new(baml_src);
+    if let Some(relative) = path.relative_to(&baml_src) {
+        return Ok(relative.to_path_buf());
+    }
+    pathdiff::diff_paths(&path, &baml_src).ok_or_else(|| {
         anyhow::anyhow!(
             "Failed to compute relative path from {} to {}",
             path.display(),
@@ -55,7 +63,7 @@ impl GeneratorArgs {
         default_client_mode: GeneratorDefaultClientMode,
         on_generate: Vec<String>,
     ) -> Result<Self> {
-        let baml_src = baml_src_dir.into();
+        let baml_src = SourcePath::new(baml_src_dir.into()).into_path_buf();
         let input_file_map: BTreeMap<PathBuf, String> = input_files
             .into_iter()
             .map(|(k, v)| Ok((relative_path_to_baml_src(k, &baml_src)?, v.clone())))
@@ -77,8 +85,11 @@ impl GeneratorArgs {
         self.inlined_file_map
             .iter()
             .map(|(k, v)| {
+                // Keys are embedded in generated code, so keep them identical
+                // regardless of the platform the client was generated on.
+                let key = SourcePath::new(k).to_slash_string();
                 Ok((
-                    serde_json::to_string(&k.display().to_string()).map_err(|e| {
+                    serde_json::to_string(&key).map_err(|e| {
                         anyhow::Error::from(e)
                             .context(format!("Failed to serialize key {:#}", k.display()))
                     })?,
@@ -7,7 +7,7 @@ use crate::{field_type_attributes, type_check_attributes, TypeCheckAttributes};
 
 use super::python_language_features::ToPython;
 use internal_baml_core::ir::{
-    repr::{Docstring, IntermediateRepr},
+    repr::{Docstring, Field, IntermediateRepr},
     ClassWalker, EnumWalker, FieldType, IRHelper,
 };
 
@@ -152,10 +152,7 @@ impl<'ir> From<ClassWalker<'ir>> for PartialPythonClass<'ir> {
                 .map(|f| {
                     (
                         f.elem.name.as_str(),
-                        add_default_value(
-                            &f.elem.r#type.elem,
-                            &f.elem.r#type.elem.to_partial_type_ref(c.db, false),
-                        ),
+                        partial_field_type_ref(c.db, &f.elem),
                         f.elem.docstring.as_ref().map(render_docstring),
                     )
                 })
@@ -165,6 +162,24 @@ impl<'ir> From<ClassWalker<'ir>> for PartialPythonClass<'ir> {
     }
 }
 
+/// The type of `field` in partial classes. `@stream.not_null` fields are never
+/// `None`, and `@stream.with_state` fields come with their `StreamState`.
+fn partial_field_type_ref(ir: &IntermediateRepr, field: &Field) -> String {
+    let mut type_ref = field.r#type.elem.to_partial_type_ref(ir, false);
+    if field.streaming.not_null {
+        if let Some(inner) = type_ref
+            .strip_prefix("Optional[")
+            .and_then(|t| t.strip_suffix(']'))
+        {
+            type_ref = inner.to_string();
+        }
+    }
+    if field.streaming.with_state {
+        return format!("StreamState[{type_ref}]");
+    }
+    add_default_value(&field.r#type.elem, &type_ref)
+}
+
 pub fn add_default_value(node: &FieldType, type_str: &String) -> String {
     if type_str.starts_with("Optional[") {
         format!("{} = None", type_str)
@@ -4,7 +4,7 @@ use anyhow::Result;
 use itertools::Itertools;
 
 use internal_baml_core::ir::{
-    repr::{Docstring, IntermediateRepr},
+    repr::{Docstring, Field, IntermediateRepr},
     ClassWalker, EnumWalker,
 };
 
@@ -24,6 +24,7 @@ pub(crate) struct TypeBuilder<'ir> {
 pub(crate) struct TypescriptTypes<'ir> {
     enums: Vec<TypescriptEnum<'ir>>,
     classes: Vec<TypescriptClass<'ir>>,
+    partial_classes: Vec<TypescriptClass<'ir>>,
 }
 
 struct TypescriptEnum<'ir> {
@@ -55,6 +56,7 @@ impl<'ir> TryFrom<(&'ir IntermediateRepr, &'ir GeneratorArgs)> for TypescriptTyp
                 .walk_classes()
                 .map(|e| Into::<TypescriptClass>::into(&e))
                 .collect::<Vec<_>>(),
+            partial_classes: ir.walk_classes().map(partial_class).collect::<Vec<_>>(),
         })
     }
 }
@@ -132,6 +134,54 @@ impl<'ir> From<&ClassWalker<'ir>> for TypescriptClass<'ir> {
     }
 }
 
+/// The class as it is while streaming, in the `partial_types` namespace.
+fn partial_class(c: ClassWalker<'_>) -> TypescriptClass<'_> {
+    TypescriptClass {
+        name: Cow::Borrowed(c.name()),
+        dynamic: c.item.attributes.get("dynamic_type").is_some(),
+        fields: c
+            .item
+            .elem
+            .static_fields
+            .iter()
+            .map(|f| {
+                let (optional, type_ref) = partial_field_type_ref(c.db, &f.elem);
+                (
+                    Cow::Borrowed(f.elem.name.as_str()),
+                    optional,
+                    type_ref,
+                    f.elem.docstring.as_ref().map(|d| render_docstring(d, true)),
+                )
+            })
+            .collect(),
+        docstring: c
+            .item
+            .elem
+            .docstring
+            .as_ref()
+            .map(|d| render_docstring(d, false)),
+    }
+}
+
+/// Whether `field` may be missing from partial classes, and its type there.
+/// `@stream.not_null` fields are never null, and `@stream.with_state` fields
+/// come with their `StreamState`.
+fn partial_field_type_ref(ir: &IntermediateRepr, field: &Field) -> (bool, String) {
+    let mut type_ref = field.r#type.elem.to_partial_type_ref(ir);
+    if field.streaming.not_null {
+        if let Some(inner) = type_ref
+            .strip_prefix('(')
+            .and_then(|t| t.strip_suffix(" | null)"))
+        {
+            type_ref = inner.to_string();
+        }
+    }
+    if field.streaming.with_state {
+        return (false, format!("StreamState<{type_ref}>"));
+    }
+    (!field.streaming.not_null, type_ref)
+}
+
 pub fn type_name_for_checks(checks: &TypeCheckAttributes) -> String {
     checks
         .0
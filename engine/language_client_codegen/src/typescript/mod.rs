@@ -55,7 +55,7 @@ impl From<TypescriptClient> for SyncTypescriptClient {
 #[derive(Debug)]
 struct TypescriptFunction {
     name: String,
-    // partial_return_type: String,
+    partial_return_type: String,
     return_type: String,
     args: Vec<(String, bool, String)>,
 }
@@ -132,7 +132,7 @@ impl TryFrom<(&'_ IntermediateRepr, &'_ crate::GeneratorArgs)> for TypescriptCli
                         Ok(TypescriptFunction {
                             name: f.name().to_string(),
                             return_type: f.elem().output().to_type_ref(ir),
-                            // partial_return_type: f.elem().output().to_partial_type_ref(ir),
+                            partial_return_type: f.elem().output().to_partial_type_ref(ir),
                             args: f
                                 .inputs()
                                 .iter()
@@ -205,53 +205,63 @@ impl TryFrom<(&'_ IntermediateRepr, &'_ crate::GeneratorArgs)> for TypescriptIni
 trait ToTypeReferenceInClientDefinition {
     fn to_type_ref(&self, ir: &IntermediateRepr) -> String;
 
-    // fn to_partial_type_ref(&self, ir: &IntermediateRepr) -> String;
+    /// The type of a value that is still streaming, with classes from the
+    /// `partial_types` namespace of `types.ts`.
+    fn to_partial_type_ref(&self, ir: &IntermediateRepr) -> String;
 }
 
 impl ToTypeReferenceInClientDefinition for FieldType {
-    // fn to_partial_type_ref(&self, ir: &IntermediateRepr) -> String {
-    //     match self {
-    //         FieldType::Enum(name) => {
-    //             if ir
-    //                 .find_enum(name)
-    //                 .map(|e| e.item.attributes.get("dynamic_type").is_some())
-    //                 .unwrap_or(false)
-    //             {
-    //                 format!("(string | {name} | null)")
-    //             } else {
-    //                 format!("({name} | null)")
-    //             }
-    //         }
-    //         FieldType::Class(name) => format!("(RecursivePartialNull<{name}>)"),
-    //         FieldType::List(inner) => format!("{}[]", inner.to_partial_type_ref(ir)),
-    //         FieldType::Map(key, value) => {
-    //             format!(
-    //                 "(Record<{}, {}> | null)",
-    //                 key.to_type_ref(ir),
-    //                 value.to_partial_type_ref(ir)
-    //             )
-    //         }
-    //         FieldType::Literal(value) => value.to_string(),
-    //         FieldType::Primitive(r#type) => format!("({} | null)", r#type.to_typescript()),
-    //         FieldType::Union(inner) => format!(
-    //             "({} | null)",
-    //             inner
-    //                 .iter()
-    //                 .map(|t| t.to_partial_type_ref(ir))
-    //                 .collect::<Vec<_>>()
-    //                 .join(" | ")
-    //         ),
-    //         FieldType::Tuple(inner) => format!(
-    //             "([{}] | null)",
-    //             inner
-    //                 .iter()
-    //                 .map(|t| t.to_partial_type_ref(ir))
-    //                 .collect::<Vec<_>>()
-    //                 .join(", ")
-    //         ),
-    //         FieldType::Optional(inner) => format!("({} | null)", inner.to_partial_type_ref(ir)),
-    //     }
-    // }
+    fn to_partial_type_ref(&self, ir: &IntermediateRepr) -> String {
+        match self {
+            FieldType::Enum(name) => {
+                if ir
+                    .find_enum(name)
+                    .map(|e| e.item.attributes.get("dynamic_type").is_some())
+                    .unwrap_or(false)
+                {
+                    format!("(string | {name} | null)")
+                } else {
+                    format!("({name} | null)")
+                }
+            }
+            FieldType::Class(name) => format!("(partial_types.{name} | null)"),
+            FieldType::List(inner) => format!("{}[]", inner.to_partial_type_ref(ir)),
+            FieldType::Map(key, value) => {
+                format!(
+                    "(Partial<Record<{}, {}>> | null)",
+                    key.to_type_ref(ir),
+                    value.to_partial_type_ref(ir)
+                )
+            }
+            FieldType::Literal(value) => format!("({value} | null)"),
+            FieldType::Primitive(r#type) => format!("({} | null)", r#type.to_typescript()),
+            FieldType::Union(inner) => format!(
+                "({} | null)",
+                inner
+                    .iter()
+                    .map(|t| t.to_partial_type_ref(ir))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+            FieldType::Tuple(inner) => format!(
+                "([{}] | null)",
+                inner
+                    .iter()
+                    .map(|t| t.to_partial_type_ref(ir))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            FieldType::Optional(inner) => inner.to_partial_type_ref(ir),
+            FieldType::Constrained { base, .. } => match field_type_attributes(self) {
+                Some(checks) => {
+                    let base_type_ref = base.to_partial_type_ref(ir);
+                    let checks_type_ref = type_name_for_checks(&checks);
+                    format!("Checked<{base_type_ref},{checks_type_ref}>")
+                }
+                None => base.to_partial_type_ref(ir),
+            },
+        }
+    }
 
     fn to_type_ref(&self, ir: &IntermediateRepr) -> String {
         match self {
@@ -15,6 +15,7 @@ from .baml_py import (
     BamlAudioPy as Audio,
     invoke_runtime_cli,
     ClientRegistry,
+    Collector,
 )
 from .stream import BamlStream, BamlSyncStream
 from .ctx_manager import CtxManager as BamlCtxManager
@@ -22,6 +23,7 @@ from .ctx_manager import CtxManager as BamlCtxManager
 __all__ = [
     "BamlRuntime",
     "ClientRegistry",
+    "Collector",
     "BamlStream",
     "BamlSyncStream",
     "BamlCtxManager",
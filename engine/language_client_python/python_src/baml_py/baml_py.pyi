@@ -1,4 +1,4 @@
-from typing import Any, Callable, Dict, Optional, Tuple
+from typing import Any, Callable, Dict, List, Optional, Tuple, Union
 
 class FunctionResult:
     """The result of a BAML function call.
@@ -18,6 +18,19 @@ class FunctionResult:
     # Returns True if the function call was successful, False otherwise
     def is_ok(self) -> bool: ...
     def cast_to(self, enum_module: Any, class_module: Any) -> Any: ...
+    # The log probability of each output token, if the client set `logprobs true`
+    @property
+    def logprobs(self) -> Optional[List[Dict[str, Any]]]: ...
+    # The provider's JSON body, if the client set `capture_raw_response true`
+    @property
+    def raw_response(self) -> Optional[Dict[str, Any]]: ...
+    # For functions with `@@confidence`, how likely each enum value was
+    @property
+    def confidence(self) -> Optional[Dict[str, float]]: ...
+    # For partial results of a stream, whether each value in it is "pending",
+    # "incomplete" or "complete", by path, e.g. "vertices.1.name"
+    @property
+    def stream_states(self) -> Optional[Dict[str, str]]: ...
 
     # This is a debug function that returns the internal representation of the response
     # This is not to be relied upon and is subject to change
@@ -39,6 +52,9 @@ class FunctionResultStream:
     def on_event(
         self, on_event: Callable[[FunctionResult], None]
     ) -> FunctionResultStream: ...
+    def cancel(self) -> None:
+        """Aborts the request in flight. `done` then raises a BamlCancelledError."""
+        ...
     async def done(self, ctx: RuntimeContextManager) -> FunctionResult: ...
 
 class SyncFunctionResultStream:
@@ -53,6 +69,9 @@ class SyncFunctionResultStream:
     def on_event(
         self, on_event: Callable[[FunctionResult], None]
     ) -> SyncFunctionResultStream: ...
+    def cancel(self) -> None:
+        """Aborts the request in flight. `done` then raises a BamlCancelledError."""
+        ...
     def done(self, ctx: RuntimeContextManager) -> FunctionResult: ...
 
 class BamlImagePy:
@@ -60,6 +79,8 @@ class BamlImagePy:
     def from_url(url: str) -> BamlImagePy: ...
     @staticmethod
     def from_base64(media_type: str, base64: str) -> BamlImagePy: ...
+    @staticmethod
+    def from_file_id(file_id: str, media_type: Optional[str] = None) -> BamlImagePy: ...
     def is_url(self) -> bool: ...
     def is_base64(self) -> bool: ...
     def as_url(self) -> str: ...
@@ -70,6 +91,8 @@ class BamlAudioPy:
     def from_url(url: str) -> BamlAudioPy: ...
     @staticmethod
     def from_base64(media_type: str, base64: str) -> BamlAudioPy: ...
+    @staticmethod
+    def from_file_id(file_id: str, media_type: Optional[str] = None) -> BamlAudioPy: ...
     def is_url(self) -> bool: ...
     def is_base64(self) -> bool: ...
     def as_url(self) -> str: ...
@@ -77,6 +100,7 @@ class BamlAudioPy:
 
 class RuntimeContextManager:
     def upsert_tags(self, tags: Dict[str, Any]) -> None: ...
+    def set_trace_parent(self, traceparent: Optional[str] = None) -> None: ...
     def deep_clone(self) -> RuntimeContextManager: ...
     def context_depth(self) -> int: ...
 
@@ -90,7 +114,40 @@ class BamlRuntime:
         ctx: RuntimeContextManager,
         tb: Optional[TypeBuilder],
         cr: Optional[ClientRegistry],
+        collector: Optional[Collector] = None,
     ) -> FunctionResult: ...
+    async def call_function_batch(
+        self,
+        function_name: str,
+        args: List[Dict[str, Any]],
+        ctx: RuntimeContextManager,
+        tb: Optional[TypeBuilder],
+        cr: Optional[ClientRegistry],
+        collector: Optional[Collector] = None,
+        max_concurrency: int = 10,
+        requests_per_second: Optional[float] = None,
+    ) -> List[Union[FunctionResult, Exception]]:
+        """Calls `function_name` once per kwargs dict in `args`, with at most
+        `max_concurrency` calls in flight and `requests_per_second` started
+        per second. Returns, per call in order, its result or its error."""
+        ...
+    async def reask(
+        self,
+        function_name: str,
+        args: Dict[str, Any],
+        ctx: RuntimeContextManager,
+        tb: Optional[TypeBuilder],
+        cr: Optional[ClientRegistry],
+        collector: Optional[Collector] = None,
+        raw_output: Optional[str] = None,
+        value: Optional[Any] = None,
+        errors: List[str] = [],
+        instructions: Optional[str] = None,
+    ) -> FunctionResult:
+        """Calls `function_name` again, with the previous answer (`raw_output`
+        as the LLM wrote it, `value` as parsed), the `errors` in it and the
+        `instructions` appended to the prompt."""
+        ...
     @staticmethod
     def from_files(
         root_path: str, files: Dict[str, str], env_vars: Dict[str, str]
@@ -106,6 +163,7 @@ class BamlRuntime:
         ctx: RuntimeContextManager,
         tb: Optional[TypeBuilder],
         cr: Optional[ClientRegistry],
+        collector: Optional[Collector] = None,
     ) -> FunctionResultStream: ...
     def stream_function_sync(
         self,
@@ -115,10 +173,41 @@ class BamlRuntime:
         ctx: RuntimeContextManager,
         tb: Optional[TypeBuilder],
         cr: Optional[ClientRegistry],
+        collector: Optional[Collector] = None,
     ) -> SyncFunctionResultStream: ...
     def create_context_manager(self) -> RuntimeContextManager: ...
     def flush(self) -> None: ...
     def drain_stats(self) -> TraceStats: ...
+    def usage_report(self) -> Dict[str, Any]:
+        """Token usage and estimated cost of every call so far, as
+        `{"entries": [...], "total": {...}}` with an entry per function,
+        client and model."""
+        ...
+    def set_model_price(
+        self,
+        model: str,
+        input: float,
+        output: float,
+        cached_input: Optional[float] = None,
+    ) -> None:
+        """Prices models starting with `model`, in USD per million tokens."""
+        ...
+    def reset_usage(self) -> None: ...
+    def add_hook(self, hook: Any) -> None:
+        """Runs `hook` around every LLM request from now on. It can define
+        `on_before_request(request)`, which can change the request dict's
+        `url`, `headers` and `body` in place or return a changed copy,
+        `on_after_response(response)` and `on_parse_error(response, error)`."""
+        ...
+    def clear_hooks(self) -> None: ...
+    def add_post_processor(
+        self, function_name: str, processor: Callable[[Any], Any], types: Any
+    ) -> None:
+        """Runs `processor` on each result of `function_name` from now on,
+        built with the classes of the `types` module, and returns what it
+        returns instead. Exceptions it raises fail the call."""
+        ...
+    def clear_post_processors(self) -> None: ...
     def set_log_event_callback(
         self, handler: Optional[Callable[[BamlLogEvent], None]]
     ) -> None: ...
@@ -170,6 +259,8 @@ class BamlSpan:
         args: Dict[str, Any],
         ctx: RuntimeContextManager,
     ) -> BamlSpan: ...
+    @property
+    def traceparent(self) -> Optional[str]: ...
     def finish(self, result: Any, ctx: RuntimeContextManager) -> str | None: ...
 
 class TypeBuilder:
@@ -188,6 +279,9 @@ class TypeBuilder:
     def optional(self, inner_type: FieldType) -> FieldType: ...
     def map(self, key_type: FieldType, value_type: FieldType) -> FieldType: ...
     def union(self, *types: FieldType) -> FieldType: ...
+    def add_baml(self, baml: str) -> None: ...
+    def enum_matching(self, mode: str, max_distance: Optional[int] = None) -> None: ...
+    def number_locale(self, locale: str) -> None: ...
 
 class ClientRegistry:
     def __init__(self) -> None: ...
@@ -198,7 +292,22 @@ class ClientRegistry:
         options: Dict[str, Any],
         retry_policy: Optional[str] = None,
     ) -> None: ...
+    def remove_client(self, name: str) -> bool: ...
     def set_primary(self, name: str) -> None: ...
+    def snapshot(self) -> Dict[str, Any]: ...
+
+class Collector:
+    """Gathers the LLM calls, retries, timing, token usage and raw HTTP
+    requests of the function calls it is passed to."""
+
+    def __init__(self) -> None: ...
+    @property
+    def logs(self) -> List[Dict[str, Any]]: ...
+    @property
+    def last(self) -> Optional[Dict[str, Any]]: ...
+    @property
+    def usage(self) -> Dict[str, Optional[int]]: ...
+    def clear(self) -> None: ...
 
 class FieldType:
     def list(self) -> FieldType: ...
@@ -244,3 +353,8 @@ class BamlClientHttpError(BamlClientError):
     """Raised for HTTP-related client errors."""
 
     ...
+
+class BamlCancelledError(BamlError):
+    """Raised when a stream is cancelled before it finishes."""
+
+    ...
@@ -66,6 +66,10 @@ class CtxManager:
         mngr = self.__ctx()
         mngr.upsert_tags(tags)
 
+    def set_trace_parent(self, traceparent: typing.Optional[str]) -> None:
+        mngr = self.__ctx()
+        mngr.set_trace_parent(traceparent)
+
     def get(self) -> RuntimeContextManager:
         return self.__ctx()
 
@@ -2,15 +2,18 @@ from .baml_py import (
     BamlError,
     BamlClientError,
     BamlClientHttpError,
+    BamlCancelledError,
     BamlInvalidArgumentError,
 )
-from .internal_monkeypatch import BamlValidationError
+from .internal_monkeypatch import BamlValidationError, BamlPartialFailureError
 
 
 __all__ = [
     "BamlError",
     "BamlClientError",
     "BamlClientHttpError",
+    "BamlCancelledError",
     "BamlInvalidArgumentError",
     "BamlValidationError",
+    "BamlPartialFailureError",
 ]
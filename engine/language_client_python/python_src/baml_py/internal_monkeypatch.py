@@ -1,5 +1,5 @@
 from .baml_py import BamlError
-from typing import Optional
+from typing import Any, Optional
 
 # Define the BamlValidationError exception with additional fields
 # note on custom exceptions https://github.com/PyO3/pyo3/issues/295
@@ -30,3 +30,20 @@ class BamlClientFinishReasonError(BamlError):
 
     def __repr__(self):
         return f"BamlClientFinishReasonError(message={self.message}, raw_output={self.raw_output}, prompt={self.prompt}, finish_reason={self.finish_reason})"
+
+class BamlPartialFailureError(BamlError):
+    """Raised when a stream fails after content streamed in. `partial` is what
+    that content parsed to, if anything."""
+
+    def __init__(self, prompt: str, message: str, raw_output: str, partial: Optional[Any]):
+        super().__init__(message)
+        self.prompt = prompt
+        self.message = message
+        self.raw_output = raw_output
+        self.partial = partial
+
+    def __str__(self):
+        return f"BamlPartialFailureError(message={self.message}, raw_output={self.raw_output}, prompt={self.prompt})"
+
+    def __repr__(self):
+        return f"BamlPartialFailureError(message={self.message}, raw_output={self.raw_output}, prompt={self.prompt}, partial={self.partial})"
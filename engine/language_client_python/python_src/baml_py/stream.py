@@ -5,7 +5,16 @@ from .baml_py import (
     SyncFunctionResultStream,
     RuntimeContextManager,
 )
-from typing import Callable, Generic, Optional, TypeVar
+from typing import (
+    AsyncIterator,
+    Callable,
+    Dict,
+    Generic,
+    Iterator,
+    Optional,
+    Tuple,
+    TypeVar,
+)
 import threading
 import asyncio
 import concurrent.futures
@@ -64,8 +73,8 @@ class BamlStream(Generic[PartialOutputType, FinalOutputType]):
     def threading_target(self):
         asyncio.run(self.__drive_to_completion(), debug=True)
 
-    async def __aiter__(self):
-        # TODO: This is deliberately __aiter__ and not __iter__ because we want to
+    async def __events(self):
+        # TODO: This is deliberately async and not sync because we want to
         # ensure that the caller is using an async for loop.
         # Eventually we do not want to create a new thread for each stream.
         self.__drive_to_completion_in_bg()
@@ -74,12 +83,30 @@ class BamlStream(Generic[PartialOutputType, FinalOutputType]):
             if event is None:
                 break
             if event.is_ok():
-                yield self.__partial_coerce(event)
+                yield event
+
+    async def __aiter__(self):
+        async for event in self.__events():
+            yield self.__partial_coerce(event)
+
+    async def with_stream_states(
+        self,
+    ) -> AsyncIterator[Tuple[PartialOutputType, Dict[str, str]]]:
+        """Like iterating the stream, but yields each partial result with
+        whether each value in it is "pending", "incomplete" or "complete", by
+        path, e.g. "vertices.1.name"."""
+        async for event in self.__events():
+            yield self.__partial_coerce(event), event.stream_states or {}
 
     async def get_final_response(self):
         final = self.__drive_to_completion_in_bg()
         return self.__final_coerce((await asyncio.wrap_future(final)))
 
+    def cancel(self) -> None:
+        """Aborts the request. Iterating the stream stops, and
+        `get_final_response` raises a BamlCancelledError."""
+        self.__ffi_stream.cancel()
+
 
 class BamlSyncStream(Generic[PartialOutputType, FinalOutputType]):
     __ffi_stream: SyncFunctionResultStream
@@ -129,8 +156,8 @@ class BamlSyncStream(Generic[PartialOutputType, FinalOutputType]):
     def __threading_target(self):
         self.__drive_to_completion()
 
-    def __iter__(self):
-        # TODO: This is deliberately __iter__ and not __aiter__ because we want to
+    def __events(self):
+        # TODO: This is deliberately sync and not async because we want to
         # ensure that the caller is NOT using an async for loop.
         self.__drive_to_completion_in_bg()
         while True:
@@ -138,7 +165,20 @@ class BamlSyncStream(Generic[PartialOutputType, FinalOutputType]):
             if event is None:
                 break
             if event.is_ok():
-                yield self.__partial_coerce(event)
+                yield event
+
+    def __iter__(self):
+        for event in self.__events():
+            yield self.__partial_coerce(event)
+
+    def with_stream_states(
+        self,
+    ) -> Iterator[Tuple[PartialOutputType, Dict[str, str]]]:
+        """Like iterating the stream, but yields each partial result with
+        whether each value in it is "pending", "incomplete" or "complete", by
+        path, e.g. "vertices.1.name"."""
+        for event in self.__events():
+            yield self.__partial_coerce(event), event.stream_states or {}
 
     def get_final_response(self):
         self.__drive_to_completion_in_bg()
@@ -154,3 +194,8 @@ class BamlSyncStream(Generic[PartialOutputType, FinalOutputType]):
             )
 
         return self.__final_coerce(self.__result)
+
+    def cancel(self) -> None:
+        """Aborts the request. Iterating the stream stops, and
+        `get_final_response` raises a BamlCancelledError."""
+        self.__ffi_stream.cancel()
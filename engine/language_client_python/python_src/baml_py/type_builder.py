@@ -1,4 +1,10 @@
+import datetime
+import decimal
+import enum
+import re
+import types
 import typing
+import uuid
 from .baml_py import (
     ClassBuilder,
     EnumBuilder,
@@ -8,6 +14,9 @@ from .baml_py import (
     TypeBuilder as _TypeBuilder,
 )
 
+# typing.Annotated is only in Python 3.9+.
+_ANNOTATED = getattr(typing, "Annotated", object())
+
 
 class TypeBuilder:
     def __init__(self, classes: typing.Set[str], enums: typing.Set[str]):
@@ -52,6 +61,135 @@ class TypeBuilder:
     def union(self, types: typing.List[FieldType]):
         return self._tb.union(*types)
 
+    def add_baml(self, baml: str) -> None:
+        """Adds the classes and enums declared in a snippet of BAML, and
+        the properties it declares on existing dynamic classes."""
+        # A name is only resolved to an enum if the builder knows the enum,
+        # so register the schema's enums that the snippet mentions.
+        for name in self.__enums & set(re.findall(r"\w+", baml)):
+            self._tb.enum(name)
+        self._tb.add_baml(baml)
+
+    def enum_matching(self, mode: str, max_distance: typing.Optional[int] = None) -> None:
+        """Matches the values of every enum this way in calls given this
+        builder, instead of as their @@match says. `mode` is one of
+        "substring", "exact", "case_insensitive", "alias" or "fuzzy", and
+        `max_distance` only applies to "fuzzy"."""
+        self._tb.enum_matching(mode, max_distance)
+
+    def number_locale(self, locale: str) -> None:
+        """Parses numbers written as strings with these separators in calls
+        given this builder: "decimal_point" for 1,234.56 (the default),
+        "decimal_comma" for 1.234,56, or "auto" to judge by each number."""
+        self._tb.number_locale(locale)
+
+    def from_pydantic(self, model: typing.Type[typing.Any]) -> FieldType:
+        """Registers a dynamic class for a Pydantic model, and for the models
+        and enums its fields use, and returns the model's type.
+
+        Properties are named after each field's alias, if it has one, so the
+        output validates with `model.model_validate`. A model or enum named
+        like a type of the schema adds to it, so the type must be @@dynamic.
+        """
+        return self.__python_type(model, {})
+
+    def __python_type(
+        self, annotation: typing.Any, seen: typing.Dict[typing.Any, FieldType]
+    ) -> FieldType:
+        origin = typing.get_origin(annotation)
+        args = typing.get_args(annotation)
+
+        if origin is _ANNOTATED:
+            return self.__python_type(args[0], seen)
+        if annotation is None or annotation is type(None):
+            return self.null()
+        if origin is typing.Union or origin is getattr(types, "UnionType", None):
+            members = [arg for arg in args if arg is not type(None)]
+            if len(members) == 1:
+                field_type = self.__python_type(members[0], seen)
+            else:
+                field_type = self.union(
+                    [self.__python_type(member, seen) for member in members]
+                )
+            return field_type.optional() if len(members) < len(args) else field_type
+        if origin is typing.Literal:
+            literals = [self.__literal(value) for value in args]
+            return literals[0] if len(literals) == 1 else self.union(literals)
+        if origin in (list, set, frozenset) or (origin is tuple and args[1:] == (...,)):
+            return self.__python_type(args[0] if args else str, seen).list()
+        if origin is dict:
+            key, value = args if args else (str, str)
+            return self.map(
+                self.__python_type(key, seen), self.__python_type(value, seen)
+            )
+
+        if annotation in seen:
+            return seen[annotation]
+        if not isinstance(annotation, type):
+            raise TypeError(f"Unsupported type: {annotation!r}")
+        if issubclass(annotation, enum.Enum):
+            return self.__enum_type(annotation, seen)
+        if hasattr(annotation, "model_fields"):
+            return self.__model_type(annotation, seen)
+        # bool is a subclass of int, so check it first.
+        for python_type, field_type in (
+            (bool, self.bool),
+            (int, self.int),
+            (float, self.float),
+            (decimal.Decimal, self.float),
+            (str, self.string),
+            (datetime.date, self.string),
+            (datetime.time, self.string),
+            (uuid.UUID, self.string),
+        ):
+            if issubclass(annotation, python_type):
+                return field_type()
+        raise TypeError(f"Unsupported type: {annotation.__name__}")
+
+    def __literal(self, value: typing.Any) -> FieldType:
+        if isinstance(value, enum.Enum):
+            value = value.value
+        if isinstance(value, bool):
+            return self.literal_bool(value)
+        if isinstance(value, int):
+            return self.literal_int(value)
+        if isinstance(value, str):
+            return self.literal_string(value)
+        raise TypeError(f"Unsupported literal: {value!r}")
+
+    def __model_type(
+        self, model: typing.Type[typing.Any], seen: typing.Dict[typing.Any, FieldType]
+    ) -> FieldType:
+        name = model.__name__
+        if name in self.__enums:
+            raise ValueError(f"Enum with name {name} already exists.")
+        self.__classes.add(name)
+        bldr = self._tb.class_(name)
+        # Register the model first, so fields can refer back to it.
+        seen[model] = bldr.field()
+        for field_name, info in model.model_fields.items():
+            field_type = self.__python_type(info.annotation, seen)
+            if not info.is_required() and not _allows_none(info.annotation):
+                field_type = field_type.optional()
+            prop = bldr.property(info.alias or field_name).type(field_type)
+            if info.description is not None:
+                prop.description(info.description)
+        return seen[model]
+
+    def __enum_type(
+        self, enm: typing.Type[enum.Enum], seen: typing.Dict[typing.Any, FieldType]
+    ) -> FieldType:
+        name = enm.__name__
+        if name in self.__classes:
+            raise ValueError(f"Class with name {name} already exists.")
+        self.__enums.add(name)
+        bldr = self._tb.enum(name)
+        for member in enm:
+            # Name string values after themselves, so the output validates.
+            bldr.value(member.value if isinstance(member.value, str) else member.name)
+        seen[enm] = bldr.field()
+        return seen[enm]
+
     def add_class(self, name: str) -> "NewClassBuilder":
         if name in self.__classes:
             raise ValueError(f"Class with name {name} already exists.")
@@ -69,6 +207,12 @@ class TypeBuilder:
         return NewEnumBuilder(self._tb, name)
 
 
+def _allows_none(annotation: typing.Any) -> bool:
+    if typing.get_origin(annotation) is _ANNOTATED:
+        return _allows_none(typing.get_args(annotation)[0])
+    return annotation is None or type(None) in typing.get_args(annotation)
+
+
 class NewClassBuilder:
     def __init__(self, tb: _TypeBuilder, name: str):
         self.__bldr = tb.class_(name)
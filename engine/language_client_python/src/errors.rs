@@ -1,6 +1,7 @@
 use baml_runtime::{
     errors::ExposedError, internal::llm_client::LLMResponse, scope_diagnostics::ScopeStack,
 };
+use baml_types::BamlValue;
 use pyo3::types::{PyAnyMethods, PyModule, PyModuleMethods};
 use pyo3::{create_exception, pymodule, Bound, PyErr, PyResult, Python};
 
@@ -10,6 +11,7 @@ create_exception!(baml_py, BamlError, pyo3::exceptions::PyException);
 create_exception!(baml_py, BamlInvalidArgumentError, BamlError);
 create_exception!(baml_py, BamlClientError, BamlError);
 create_exception!(baml_py, BamlClientHttpError, BamlClientError);
+create_exception!(baml_py, BamlCancelledError, BamlError);
 
 // Define the BamlValidationError exception with additional fields
 // can't use extends=PyException yet https://github.com/PyO3/pyo3/discussions/3838
@@ -43,6 +45,28 @@ fn raise_baml_client_finish_reason_error(
     })
 }
 
+#[allow(non_snake_case)]
+fn raise_baml_partial_failure_error(
+    prompt: String,
+    message: String,
+    raw_output: String,
+    partial: Option<&BamlValue>,
+) -> PyErr {
+    Python::with_gil(|py| {
+        let internal_monkeypatch = py.import("baml_py.internal_monkeypatch").unwrap();
+        let exception = internal_monkeypatch
+            .getattr("BamlPartialFailureError")
+            .unwrap();
+        let partial = match partial.map(|p| pythonize::pythonize(py, p)).transpose() {
+            Ok(partial) => partial.map(Bound::unbind),
+            Err(e) => return PyErr::from(e),
+        };
+        let args = (prompt, message, raw_output, partial);
+        let inst = exception.call1(args).unwrap();
+        PyErr::from_value(inst)
+    })
+}
+
 /// Defines the errors module with the BamlValidationError exception.
 /// IIRC the name of this function is the name of the module that pyo3 generates (errors.py)
 #[pymodule]
@@ -60,12 +84,21 @@ pub fn errors(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
         "BamlClientHttpError",
         parent_module.py().get_type::<BamlClientHttpError>(),
     )?;
+    parent_module.add(
+        "BamlCancelledError",
+        parent_module.py().get_type::<BamlCancelledError>(),
+    )?;
 
     Ok(())
 }
 
 impl BamlError {
     pub fn from_anyhow(err: anyhow::Error) -> PyErr {
+        // Raised by Python code the runtime called, e.g. a post-processor.
+        let err = match err.downcast::<PyErr>() {
+            Ok(e) => return e,
+            Err(err) => err,
+        };
         if let Some(er) = err.downcast_ref::<ExposedError>() {
             match er {
                 ExposedError::ValidationError {
@@ -88,6 +121,18 @@ impl BamlError {
                     message.clone(),
                     finish_reason.clone(),
                 ),
+                ExposedError::PartialFailure {
+                    prompt,
+                    raw_output,
+                    message,
+                    partial,
+                } => raise_baml_partial_failure_error(
+                    prompt.clone(),
+                    message.clone(),
+                    raw_output.clone(),
+                    partial.as_ref(),
+                ),
+                ExposedError::Cancelled => PyErr::new::<BamlCancelledError, _>(er.to_string()),
             }
         } else if let Some(er) = err.downcast_ref::<ScopeStack>() {
             PyErr::new::<BamlInvalidArgumentError, _>(format!("Invalid argument: {}", er))
@@ -97,7 +142,8 @@ impl BamlError {
                     PyErr::new::<BamlError, _>(format!("Unexpected error from BAML: {}", err))
                 }
                 LLMResponse::LLMFailure(failed) => match &failed.code {
-                    baml_runtime::internal::llm_client::ErrorCode::Other(2) => {
+                    baml_runtime::internal::llm_client::ErrorCode::Other(2)
+                    | baml_runtime::internal::llm_client::ErrorCode::Timeout => {
                         PyErr::new::<BamlClientError, _>(format!(
                             "Something went wrong with the LLM client {}: {}",
                             failed.client, failed.message
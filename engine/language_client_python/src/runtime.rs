@@ -4,13 +4,18 @@ use crate::types::function_results::FunctionResult;
 use crate::types::trace_stats::TraceStats;
 
 use crate::types::function_result_stream::{FunctionResultStream, SyncFunctionResultStream};
+use crate::types::post_processor::PyPostProcessor;
 use crate::types::runtime_ctx_manager::RuntimeContextManager;
+use crate::types::runtime_hook::PyRuntimeHook;
 use crate::types::type_builder::TypeBuilder;
-use crate::types::ClientRegistry;
+use crate::types::{ClientRegistry, Collector};
 use baml_runtime::runtime_interface::ExperimentalTracingInterface;
 use baml_runtime::BamlRuntime as CoreBamlRuntime;
+use baml_runtime::ModelPrice;
 use pyo3::prelude::{pymethods, PyResult};
-use pyo3::{pyclass, IntoPyObjectExt, PyObject, Python};
+use pyo3::types::PyModule;
+use pyo3::{pyclass, Bound, IntoPyObjectExt, PyObject, Python};
+use pythonize::pythonize;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -114,7 +119,7 @@ impl BamlRuntime {
             .into()
     }
 
-    #[pyo3(signature = (function_name, args, ctx, tb, cb))]
+    #[pyo3(signature = (function_name, args, ctx, tb, cb, collector = None))]
     fn call_function(
         &self,
         py: Python<'_>,
@@ -123,6 +128,7 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
     ) -> PyResult<PyObject> {
         let Some(args) = parse_py_type(args.into_bound(py).into_py_any(py)?, false)? else {
             return Err(BamlInvalidArgumentError::new_err(
@@ -140,11 +146,19 @@ impl BamlRuntime {
         let ctx_mng = ctx.inner.clone();
         let tb = tb.map(|tb| tb.inner.clone());
         let cb = cb.map(|cb| cb.inner.clone());
+        let collector = collector.map(|c| c.inner.clone());
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
             let ctx_mng = ctx_mng;
             let (result, _) = baml_runtime
-                .call_function(function_name, &args_map, &ctx_mng, tb.as_ref(), cb.as_ref())
+                .call_function(
+                    function_name,
+                    &args_map,
+                    &ctx_mng,
+                    tb.as_ref(),
+                    cb.as_ref(),
+                    collector.as_ref(),
+                )
                 .await;
 
             result
@@ -154,7 +168,156 @@ impl BamlRuntime {
         .map(|f| f.into())
     }
 
-    #[pyo3(signature = (function_name, args, ctx, tb, cb))]
+    /// Calls `function_name` once per kwargs dict in `args`. Resolves to a
+    /// list with, per call in order, its `FunctionResult` or the error it
+    /// raised.
+    #[pyo3(signature = (
+        function_name, args, ctx, tb, cb, collector = None, max_concurrency = 10,
+        requests_per_second = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn call_function_batch(
+        &self,
+        py: Python<'_>,
+        function_name: String,
+        args: Vec<PyObject>,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
+        max_concurrency: usize,
+        requests_per_second: Option<f64>,
+    ) -> PyResult<PyObject> {
+        if max_concurrency == 0 {
+            return Err(BamlInvalidArgumentError::new_err(
+                "max_concurrency must be at least 1",
+            ));
+        }
+        let args = args
+            .into_iter()
+            .map(|args| {
+                let Some(args) = parse_py_type(args, false)? else {
+                    return Err(BamlInvalidArgumentError::new_err(
+                        "Failed to parse args, perhaps you used a non-serializable type?",
+                    ));
+                };
+                args.as_map_owned().ok_or_else(|| {
+                    BamlInvalidArgumentError::new_err("Failed to parse args. Expect kwargs")
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let baml_runtime = self.inner.clone();
+        let ctx_mng = ctx.inner.clone();
+        let tb = tb.map(|tb| tb.inner.clone());
+        let cb = cb.map(|cb| cb.inner.clone());
+        let collector = collector.map(|c| c.inner.clone());
+        let options = baml_runtime::BatchOptions {
+            max_concurrency,
+            requests_per_second,
+        };
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let results = baml_runtime
+                .call_function_batch(
+                    function_name,
+                    &args,
+                    &ctx_mng,
+                    tb.as_ref(),
+                    cb.as_ref(),
+                    collector.as_ref(),
+                    &options,
+                )
+                .await;
+
+            Python::with_gil(|py| {
+                results
+                    .into_iter()
+                    .map(|(result, _)| match result {
+                        Ok(result) => FunctionResult::from(result).into_py_any(py),
+                        Err(e) => Ok(BamlError::from_anyhow(e).into_value(py).into_any()),
+                    })
+                    .collect::<PyResult<Vec<PyObject>>>()
+            })
+        })
+        .map(|f| f.into())
+    }
+
+    /// Calls `function_name` again, with the previous answer (`raw_output`
+    /// as the LLM wrote it, `value` as parsed), the `errors` in it and the
+    /// `instructions` appended to the prompt.
+    #[pyo3(signature = (
+        function_name, args, ctx, tb, cb, collector = None, raw_output = None, value = None,
+        errors = vec![], instructions = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn reask(
+        &self,
+        py: Python<'_>,
+        function_name: String,
+        args: PyObject,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
+        raw_output: Option<String>,
+        value: Option<PyObject>,
+        errors: Vec<String>,
+        instructions: Option<String>,
+    ) -> PyResult<PyObject> {
+        let Some(args) = parse_py_type(args, false)? else {
+            return Err(BamlInvalidArgumentError::new_err(
+                "Failed to parse args, perhaps you used a non-serializable type?",
+            ));
+        };
+        let Some(args_map) = args.as_map_owned() else {
+            return Err(BamlInvalidArgumentError::new_err(
+                "Failed to parse args. Expect kwargs",
+            ));
+        };
+        let value = match value {
+            Some(value) => parse_py_type(value, false)?
+                .map(serde_json::to_value)
+                .transpose()
+                .map_err(|e| {
+                    BamlInvalidArgumentError::new_err(format!("Failed to serialize value: {e}"))
+                })?,
+            None => None,
+        };
+        let previous = baml_runtime::Reask {
+            raw_output,
+            value,
+            errors,
+            instructions,
+        };
+
+        let baml_runtime = self.inner.clone();
+        let ctx_mng = ctx.inner.clone();
+        let tb = tb.map(|tb| tb.inner.clone());
+        let cb = cb.map(|cb| cb.inner.clone());
+        let collector = collector.map(|c| c.inner.clone());
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let (result, _) = baml_runtime
+                .reask(
+                    function_name,
+                    &args_map,
+                    previous,
+                    &ctx_mng,
+                    tb.as_ref(),
+                    cb.as_ref(),
+                    collector.as_ref(),
+                )
+                .await;
+
+            result
+                .map(FunctionResult::from)
+                .map_err(BamlError::from_anyhow)
+        })
+        .map(|f| f.into())
+    }
+
+    #[pyo3(signature = (function_name, args, ctx, tb, cb, collector = None))]
     fn call_function_sync(
         &self,
         function_name: String,
@@ -162,6 +325,7 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
     ) -> PyResult<FunctionResult> {
         let Some(args) = parse_py_type(args, false)? else {
             return Err(BamlInvalidArgumentError::new_err(
@@ -185,6 +349,7 @@ impl BamlRuntime {
             &ctx_mng,
             tb.as_ref(),
             cb.as_ref(),
+            collector.map(|c| &c.inner),
         );
 
         result
@@ -192,7 +357,7 @@ impl BamlRuntime {
             .map_err(BamlError::from_anyhow)
     }
 
-    #[pyo3(signature = (function_name, args, on_event, ctx, tb, cb))]
+    #[pyo3(signature = (function_name, args, on_event, ctx, tb, cb, collector = None))]
     fn stream_function(
         &self,
         py: Python<'_>,
@@ -202,6 +367,7 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
     ) -> PyResult<FunctionResultStream> {
         let Some(args) = parse_py_type(args.into_bound(py).into_py_any(py)?, false)? else {
             return Err(BamlInvalidArgumentError::new_err(
@@ -222,6 +388,7 @@ impl BamlRuntime {
                 &ctx,
                 tb.map(|tb| tb.inner.clone()).as_ref(),
                 cb.map(|cb| cb.inner.clone()).as_ref(),
+                collector.map(|c| &c.inner),
             )
             .map_err(BamlError::from_anyhow)?;
 
@@ -233,7 +400,7 @@ impl BamlRuntime {
         ))
     }
 
-    #[pyo3(signature = (function_name, args, on_event, ctx, tb, cb))]
+    #[pyo3(signature = (function_name, args, on_event, ctx, tb, cb, collector = None))]
     fn stream_function_sync(
         &self,
         py: Python<'_>,
@@ -243,6 +410,7 @@ impl BamlRuntime {
         ctx: &RuntimeContextManager,
         tb: Option<&TypeBuilder>,
         cb: Option<&ClientRegistry>,
+        collector: Option<&Collector>,
     ) -> PyResult<SyncFunctionResultStream> {
         let Some(args) = parse_py_type(args.into_bound(py).into_py_any(py)?, false)? else {
             return Err(BamlInvalidArgumentError::new_err(
@@ -263,6 +431,7 @@ impl BamlRuntime {
                 &ctx,
                 tb.map(|tb| tb.inner.clone()).as_ref(),
                 cb.map(|cb| cb.inner.clone()).as_ref(),
+                collector.map(|c| &c.inner),
             )
             .map_err(BamlError::from_anyhow)?;
 
@@ -284,6 +453,64 @@ impl BamlRuntime {
         self.inner.drain_stats().into()
     }
 
+    /// Token usage and estimated cost of every call so far, per function,
+    /// client and model, and in total.
+    #[pyo3()]
+    fn usage_report(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.usage_report())?.into())
+    }
+
+    /// Prices models starting with `model`, in USD per million tokens, for
+    /// the calls from now on.
+    #[pyo3(signature = (model, input, output, cached_input = None))]
+    fn set_model_price(&self, model: String, input: f64, output: f64, cached_input: Option<f64>) {
+        let mut prices = self.inner.usage().price_table();
+        prices.set_price(
+            model,
+            ModelPrice {
+                input,
+                cached_input: cached_input.unwrap_or(input),
+                output,
+            },
+        );
+        self.inner.set_price_table(prices);
+    }
+
+    #[pyo3()]
+    fn reset_usage(&self) {
+        self.inner.usage().reset();
+    }
+
+    /// Runs `hook` around every LLM request of the calls started from now on.
+    #[pyo3()]
+    fn add_hook(&self, hook: PyObject) {
+        self.inner.add_hook(Arc::new(PyRuntimeHook::new(hook)));
+    }
+
+    #[pyo3()]
+    fn clear_hooks(&self) {
+        self.inner.hooks().clear();
+    }
+
+    /// Runs `processor` on the results of `function_name`, built with the
+    /// classes and enums of the `types` module.
+    #[pyo3()]
+    fn add_post_processor(
+        &self,
+        function_name: &str,
+        processor: PyObject,
+        types: Bound<'_, PyModule>,
+    ) {
+        let processor = PyPostProcessor::new(processor, types.unbind());
+        self.inner
+            .add_post_processor(function_name, Arc::new(processor));
+    }
+
+    #[pyo3()]
+    fn clear_post_processors(&self) {
+        self.inner.post_processors().clear();
+    }
+
     #[pyo3(signature = (callback = None))]
     fn set_log_event_callback(&self, callback: Option<PyObject>, py: Python<'_>) -> PyResult<()> {
         let baml_runtime = self.inner.clone();
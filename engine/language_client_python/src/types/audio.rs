@@ -29,6 +29,19 @@ impl BamlAudioPy {
         }
     }
 
+    /// A file uploaded to the provider's file API, by its id.
+    #[staticmethod]
+    #[pyo3(signature = (file_id, media_type=None))]
+    fn from_file_id(file_id: String, media_type: Option<String>) -> Self {
+        BamlAudioPy {
+            inner: baml_types::BamlMedia::file_ref(
+                baml_types::BamlMediaType::Audio,
+                file_id,
+                media_type,
+            ),
+        }
+    }
+
     pub fn is_url(&self) -> bool {
         matches!(&self.inner.content, BamlMediaContent::Url(_))
     }
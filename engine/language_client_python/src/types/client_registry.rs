@@ -3,8 +3,9 @@ use std::str::FromStr;
 use baml_runtime::client_registry;
 use pyo3::prelude::{pymethods, PyResult};
 use pyo3::{IntoPyObjectExt, PyObject, Python};
+use pythonize::pythonize;
 
-use crate::errors::BamlInvalidArgumentError;
+use crate::errors::{BamlError, BamlInvalidArgumentError};
 use crate::parse_py_type::parse_py_type;
 use client_registry::ClientProvider;
 
@@ -62,7 +63,18 @@ impl ClientRegistry {
         Ok(())
     }
 
+    /// Returns whether a client by that name was registered.
+    pub fn remove_client(&mut self, name: &str) -> bool {
+        self.inner.remove_client(name).is_some()
+    }
+
     pub fn set_primary(&mut self, primary: String) {
         self.inner.set_primary(primary);
     }
+
+    pub fn snapshot(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let snapshot = serde_json::to_value(self.inner.snapshot())
+            .map_err(|e| BamlError::from_anyhow(e.into()))?;
+        Ok(pythonize(py, &snapshot)?.into())
+    }
 }
@@ -0,0 +1,45 @@
+use pyo3::prelude::{pymethods, PyResult};
+use pyo3::{PyObject, Python};
+use pythonize::pythonize;
+
+crate::lang_wrapper!(Collector, baml_runtime::Collector);
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[pymethods]
+impl Collector {
+    #[new]
+    pub fn new() -> Self {
+        Self {
+            inner: baml_runtime::Collector::new(),
+        }
+    }
+
+    /// Every function call collected so far, oldest first.
+    #[getter]
+    pub fn logs(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.logs())?.into())
+    }
+
+    #[getter]
+    pub fn last(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.last())?.into())
+    }
+
+    #[getter]
+    pub fn usage(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.usage())?.into())
+    }
+
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Collector(logs={})", self.inner.logs().len())
+    }
+}
@@ -11,7 +11,8 @@ crate::lang_wrapper!(
     baml_runtime::FunctionResultStream, thread_safe,
     on_event: Option<PyObject>,
     tb: Option<baml_runtime::type_builder::TypeBuilder>,
-    cb: Option<baml_runtime::client_registry::ClientRegistry>
+    cb: Option<baml_runtime::client_registry::ClientRegistry>,
+    cancel: Option<baml_runtime::CancelHandle>
 );
 
 crate::lang_wrapper!(
@@ -19,7 +20,8 @@ crate::lang_wrapper!(
     baml_runtime::FunctionResultStream, sync_thread_safe,
     on_event: Option<PyObject>,
     tb: Option<baml_runtime::type_builder::TypeBuilder>,
-    cb: Option<baml_runtime::client_registry::ClientRegistry>
+    cb: Option<baml_runtime::client_registry::ClientRegistry>,
+    cancel: Option<baml_runtime::CancelHandle>
 );
 
 impl FunctionResultStream {
@@ -30,6 +32,7 @@ impl FunctionResultStream {
         cb: Option<baml_runtime::client_registry::ClientRegistry>,
     ) -> Self {
         Self {
+            cancel: Some(inner.cancel_handle()),
             inner: std::sync::Arc::new(tokio::sync::Mutex::new(inner)),
             on_event: event,
             tb,
@@ -46,6 +49,7 @@ impl SyncFunctionResultStream {
         cb: Option<baml_runtime::client_registry::ClientRegistry>,
     ) -> Self {
         Self {
+            cancel: Some(inner.cancel_handle()),
             inner: std::sync::Arc::new(std::sync::Mutex::new(inner)),
             on_event: event,
             tb,
@@ -60,6 +64,13 @@ impl FunctionResultStream {
         "FunctionResultStream".to_string()
     }
 
+    /// Aborts the request in flight. `done` then raises a BamlCancelledError.
+    fn cancel(&self) {
+        if let Some(cancel) = &self.cancel {
+            cancel.cancel();
+        }
+    }
+
     /// Set the callback to be called when an event is received
     ///
     /// Callback will take an instance of FunctionResult
@@ -109,6 +120,13 @@ impl SyncFunctionResultStream {
         "SyncFunctionResultStream".to_string()
     }
 
+    /// Aborts the request in flight. `done` then raises a BamlCancelledError.
+    fn cancel(&self) {
+        if let Some(cancel) = &self.cancel {
+            cancel.cancel();
+        }
+    }
+
     /// Set the callback to be called when an event is received
     ///
     /// Callback will take an instance of FunctionResult
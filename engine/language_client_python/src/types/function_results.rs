@@ -2,6 +2,7 @@ use baml_types::{BamlValueWithMeta, ResponseCheck};
 use pyo3::prelude::{pymethods, PyResult};
 use pyo3::types::{PyAnyMethods, PyDict, PyModule, PyTuple, PyType};
 use pyo3::{Bound, IntoPyObject, IntoPyObjectExt, PyAny, PyObject, Python};
+use pythonize::pythonize;
 
 use crate::errors::BamlError;
 
@@ -19,6 +20,36 @@ impl FunctionResult {
         self.inner.result_with_constraints_content().is_ok()
     }
 
+    /// The log probability of each output token, as a list of
+    /// `{"token", "logprob", "top_logprobs"}` dicts, or `None` if the client
+    /// didn't ask for them.
+    #[getter]
+    fn logprobs(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.logprobs())?.into())
+    }
+
+    /// The provider's JSON body, parsed into dicts and lists, or `None` if
+    /// the client didn't set `capture_raw_response true`.
+    #[getter]
+    fn raw_response(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.raw_response())?.into())
+    }
+
+    /// For functions with `@@confidence`, a dict from each enum value to how
+    /// likely the model thought it was.
+    #[getter]
+    fn confidence(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.confidence())?.into())
+    }
+
+    /// For partial results of a stream, a dict from the path of each value
+    /// in the result, e.g. `vertices.1.name`, to `"pending"`, `"incomplete"`
+    /// or `"complete"`. `None` if the response didn't parse.
+    #[getter]
+    fn stream_states(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize(py, &self.inner.stream_states())?.into())
+    }
+
     /// This is a debug function that returns the internal representation of the response
     /// This is not to be relied upon and is subject to change
     fn unstable_internal_repr(&self) -> String {
@@ -72,7 +103,7 @@ fn pythonize_checks<'a>(
     Ok(dict)
 }
 
-fn pythonize_strict(
+pub(crate) fn pythonize_strict(
     py: Python<'_>,
     parsed: BamlValueWithMeta<Vec<ResponseCheck>>,
     enum_module: &Bound<'_, PyModule>,
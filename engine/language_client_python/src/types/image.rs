@@ -28,6 +28,19 @@ impl BamlImagePy {
         }
     }
 
+    /// A file uploaded to the provider's file API, by its id.
+    #[staticmethod]
+    #[pyo3(signature = (file_id, media_type=None))]
+    fn from_file_id(file_id: String, media_type: Option<String>) -> Self {
+        BamlImagePy {
+            inner: baml_types::BamlMedia::file_ref(
+                baml_types::BamlMediaType::Image,
+                file_id,
+                media_type,
+            ),
+        }
+    }
+
     pub fn is_url(&self) -> bool {
         matches!(&self.inner.content, baml_types::BamlMediaContent::Url(_))
     }
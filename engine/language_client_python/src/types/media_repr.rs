@@ -1,7 +1,7 @@
 use std::ffi::CString;
 
 use anyhow::Result;
-use baml_types::{BamlMedia, BamlMediaContent, BamlMediaType, MediaBase64, MediaUrl};
+use baml_types::{BamlMedia, BamlMediaContent, BamlMediaType, MediaBase64, MediaFileRef, MediaUrl};
 use pyo3::{
     ffi::c_str,
     types::{PyAnyMethods, PyModule, PyType},
@@ -27,6 +27,7 @@ pub struct UserFacingBamlMedia {
 pub enum UserFacingBamlMediaContent {
     Url { url: String },
     Base64 { base64: String },
+    FileRef { file_id: String },
 }
 
 impl UserFacingBamlMedia {
@@ -39,6 +40,9 @@ impl UserFacingBamlMedia {
                 UserFacingBamlMediaContent::Base64 { base64 } => {
                     BamlMediaContent::Base64(MediaBase64 { base64 })
                 }
+                UserFacingBamlMediaContent::FileRef { file_id } => {
+                    BamlMediaContent::FileRef(MediaFileRef { file_id })
+                }
             },
         }
     }
@@ -57,6 +61,9 @@ impl TryInto<UserFacingBamlMedia> for &BamlMedia {
                 BamlMediaContent::Base64(base64) => UserFacingBamlMediaContent::Base64 {
                     base64: base64.base64.clone(),
                 },
+                BamlMediaContent::FileRef(file_ref) => UserFacingBamlMediaContent::FileRef {
+                    file_id: file_ref.file_id.clone(),
+                },
                 BamlMediaContent::File(_) => {
                     anyhow::bail!("Cannot convert file media to user facing media")
                 }
@@ -3,16 +3,20 @@ mod lang_wrapper;
 pub use client_registry::ClientRegistry;
 pub(crate) mod audio;
 pub(crate) mod client_registry;
+pub(crate) mod collector;
 pub(crate) mod function_result_stream;
 pub(crate) mod function_results;
 pub(crate) mod image;
 pub(super) mod media_repr;
+pub(crate) mod post_processor;
 pub(crate) mod runtime_ctx_manager;
+pub(crate) mod runtime_hook;
 pub(crate) mod span;
 pub(crate) mod trace_stats;
 pub(crate) mod type_builder;
 
 pub use audio::BamlAudioPy;
+pub use collector::Collector;
 pub use function_result_stream::{FunctionResultStream, SyncFunctionResultStream};
 pub use function_results::FunctionResult;
 pub use image::BamlImagePy;
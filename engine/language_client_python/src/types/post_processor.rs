@@ -0,0 +1,43 @@
+use baml_runtime::internal::llm_client::ResponseBamlValue;
+use baml_runtime::PostProcessor;
+use baml_types::BamlValueWithMeta;
+use pyo3::types::PyModule;
+use pyo3::{Py, PyObject, Python};
+
+use crate::parse_py_type::parse_py_type;
+
+use super::function_results::pythonize_strict;
+
+/// A Python callable registered with `BamlRuntime.add_post_processor`. It
+/// gets the result as the generated client returns it, and returns the result
+/// to return instead. Exceptions it raises reach the caller as they are.
+pub(crate) struct PyPostProcessor {
+    processor: PyObject,
+    /// The generated client's `types` module, to build the result with.
+    types: Py<PyModule>,
+}
+
+impl PyPostProcessor {
+    pub(crate) fn new(processor: PyObject, types: Py<PyModule>) -> Self {
+        Self { processor, types }
+    }
+}
+
+impl PostProcessor for PyPostProcessor {
+    fn process(&self, value: ResponseBamlValue) -> anyhow::Result<ResponseBamlValue> {
+        Python::with_gil(|py| {
+            let types = self.types.bind(py);
+            let arg = pythonize_strict(py, value.clone(), types, types)?;
+            let returned = self.processor.call1(py, (arg,))?;
+            let Some(processed) = parse_py_type(returned, false)? else {
+                anyhow::bail!("The post-processor returned a value that can't be serialized");
+            };
+            // Checks only carry over to a value that didn't change.
+            if processed == value.clone().value() {
+                Ok(value)
+            } else {
+                Ok(BamlValueWithMeta::with_default_meta(&processed))
+            }
+        })
+    }
+}
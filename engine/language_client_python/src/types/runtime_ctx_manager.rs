@@ -1,7 +1,7 @@
 use pyo3::prelude::{pymethods, PyResult};
 use pyo3::{IntoPyObjectExt, PyObject, Python};
 
-use crate::errors::BamlError;
+use crate::errors::{BamlError, BamlInvalidArgumentError};
 use crate::parse_py_type::parse_py_type;
 
 crate::lang_wrapper!(RuntimeContextManager, baml_runtime::RuntimeContextManager);
@@ -17,10 +17,22 @@ impl RuntimeContextManager {
         let Some(tags) = tags.as_map_owned() else {
             return Err(BamlError::new_err("Failed to parse tags"));
         };
-        self.inner.upsert_tags(tags.into_iter().collect());
+        self.inner
+            .upsert_tags(tags.into_iter().collect())
+            .map_err(|e| BamlInvalidArgumentError::new_err(format!("{e:#}")))?;
         Ok(true)
     }
 
+    #[pyo3(signature = (traceparent=None))]
+    fn set_trace_parent(&self, traceparent: Option<&str>) -> PyResult<()> {
+        let trace_parent = traceparent
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| BamlInvalidArgumentError::new_err(format!("{e:#}")))?;
+        self.inner.set_trace_parent(trace_parent);
+        Ok(())
+    }
+
     #[pyo3()]
     fn deep_clone(&self) -> Self {
         RuntimeContextManager {
@@ -0,0 +1,104 @@
+use baml_runtime::internal::llm_client::{LLMCompleteResponse, LLMResponse};
+use baml_runtime::{HookRequest, RuntimeHook};
+use pyo3::types::PyAnyMethods;
+use pyo3::{Bound, PyAny, PyObject, PyResult, Python};
+use pythonize::{depythonize, pythonize};
+use serde_json::json;
+
+/// A Python object registered with `BamlRuntime.add_hook`. It can define any
+/// of `on_before_request(request)`, `on_after_response(response)` and
+/// `on_parse_error(response, error)`.
+pub(crate) struct PyRuntimeHook {
+    hook: PyObject,
+}
+
+impl PyRuntimeHook {
+    pub(crate) fn new(hook: PyObject) -> Self {
+        Self { hook }
+    }
+
+    /// The hook's `name` method, if it defines one.
+    fn method<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let hook = self.hook.bind(py);
+        if !hook.hasattr(name)? {
+            return Ok(None);
+        }
+        hook.getattr(name).map(Some)
+    }
+}
+
+fn success_dict(response: &LLMCompleteResponse) -> serde_json::Value {
+    json!({
+        "client": response.client,
+        "model": response.model,
+        "content": response.content,
+        "finish_reason": response.metadata.finish_reason,
+        "input_tokens": response.metadata.prompt_tokens,
+        "output_tokens": response.metadata.output_tokens,
+        "latency_ms": response.latency.as_millis() as u64,
+        "error": None::<String>,
+    })
+}
+
+fn response_dict(response: &LLMResponse) -> serde_json::Value {
+    match response {
+        LLMResponse::Success(response) => success_dict(response),
+        LLMResponse::LLMFailure(e) => json!({
+            "client": e.client,
+            "model": e.model,
+            "content": None::<String>,
+            "latency_ms": e.latency.as_millis() as u64,
+            "error": e.message,
+        }),
+        LLMResponse::UserFailure(message) | LLMResponse::InternalFailure(message) => json!({
+            "client": None::<String>,
+            "model": None::<String>,
+            "content": None::<String>,
+            "error": message,
+        }),
+    }
+}
+
+impl RuntimeHook for PyRuntimeHook {
+    /// The hook gets the request as a dict it can change in place, or return
+    /// a changed copy of.
+    fn on_before_request(&self, request: &mut HookRequest) -> anyhow::Result<()> {
+        Python::with_gil(|py| {
+            let Some(method) = self.method(py, "on_before_request")? else {
+                return Ok(());
+            };
+            let dict = pythonize(py, &*request)?;
+            let returned = method.call1((dict.clone(),))?;
+            let changed = if returned.is_none() { dict } else { returned };
+            *request = depythonize(&changed)?;
+            Ok(())
+        })
+    }
+
+    fn on_after_response(&self, response: &LLMResponse) {
+        let result = Python::with_gil(|py| -> PyResult<()> {
+            if let Some(method) = self.method(py, "on_after_response")? {
+                method.call1((pythonize(py, &response_dict(response))?,))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            log::error!("Error calling on_after_response hook: {:?}", e);
+        }
+    }
+
+    fn on_parse_error(&self, response: &LLMCompleteResponse, error: &anyhow::Error) {
+        let result = Python::with_gil(|py| -> PyResult<()> {
+            if let Some(method) = self.method(py, "on_parse_error")? {
+                method.call1((
+                    pythonize(py, &success_dict(response))?,
+                    format!("{error:#}"),
+                ))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            log::error!("Error calling on_parse_error hook: {:?}", e);
+        }
+    }
+}
@@ -42,6 +42,16 @@ impl BamlSpan {
         })
     }
 
+    /// The W3C `traceparent` of this span, if the application's trace was
+    /// set with `set_trace_parent`.
+    #[getter]
+    fn traceparent(&self) -> Option<String> {
+        self.inner
+            .as_ref()
+            .and_then(|span| span.as_ref()?.trace_parent())
+            .map(|trace_parent| trace_parent.to_string())
+    }
+
     // method to finish
     fn finish(
         &mut self,
@@ -1,5 +1,5 @@
 use baml_runtime::type_builder::{self, WithMeta};
-use baml_types::BamlValue;
+use baml_types::{BamlValue, EnumMatching, NumberLocale};
 use pyo3::{
     prelude::PyAnyMethods,
     pymethods,
@@ -7,6 +7,8 @@ use pyo3::{
     Bound, PyResult,
 };
 
+use crate::errors::BamlError;
+
 crate::lang_wrapper!(TypeBuilder, type_builder::TypeBuilder);
 crate::lang_wrapper!(EnumBuilder, type_builder::EnumBuilder, sync_thread_safe, name: String);
 crate::lang_wrapper!(ClassBuilder, type_builder::ClassBuilder, sync_thread_safe, name: String);
@@ -51,6 +53,31 @@ impl TypeBuilder {
         }
     }
 
+    /// Parses a snippet of BAML classes and enums and adds them to the
+    /// builder, see `baml_runtime::type_builder::TypeBuilder::add_baml`.
+    pub fn add_baml(&self, baml: &str) -> PyResult<()> {
+        self.inner.add_baml(baml).map_err(BamlError::from_anyhow)
+    }
+
+    /// Matches the values of every enum this way in calls given this
+    /// builder, instead of as their `@@match` says.
+    #[pyo3(signature = (mode, max_distance = None))]
+    pub fn enum_matching(&self, mode: &str, max_distance: Option<usize>) -> PyResult<()> {
+        let matching = EnumMatching::from_mode(mode, max_distance)
+            .ok_or_else(|| BamlError::new_err(format!("Unknown match mode: {mode}")))?;
+        self.inner.set_enum_matching(matching);
+        Ok(())
+    }
+
+    /// Parses numbers written as strings with these separators in calls
+    /// given this builder.
+    pub fn number_locale(&self, locale: &str) -> PyResult<()> {
+        let locale = NumberLocale::from_name(locale)
+            .ok_or_else(|| BamlError::new_err(format!("Unknown number locale: {locale}")))?;
+        self.inner.set_number_locale(locale);
+        Ok(())
+    }
+
     pub fn literal_string(&self, value: &str) -> FieldType {
         baml_types::FieldType::literal_string(value.to_string()).into()
     }
@@ -3,6 +3,7 @@
 export declare class BamlAudio {
   static fromUrl(url: string): BamlAudio
   static fromBase64(mediaType: string, base64: string): BamlAudio
+  static fromFileId(fileId: string, mediaType?: string | undefined | null): BamlAudio
   isUrl(): boolean
   asUrl(): string
   asBase64(): [string, string]
@@ -12,6 +13,7 @@ export declare class BamlAudio {
 export declare class BamlImage {
   static fromUrl(url: string): BamlImage
   static fromBase64(mediaType: string, base64: string): BamlImage
+  static fromFileId(fileId: string, mediaType?: string | undefined | null): BamlImage
   isUrl(): boolean
   asUrl(): string
   asBase64(): [string, string]
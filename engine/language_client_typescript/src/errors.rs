@@ -31,6 +31,16 @@ pub fn from_anyhow_error(err: anyhow::Error) -> napi::Error {
                 message,
                 finish_reason.as_ref().map(|f| f.as_str()),
             ),
+            ExposedError::PartialFailure {
+                prompt,
+                raw_output,
+                message,
+                partial,
+            } => throw_baml_partial_failure_error(prompt, raw_output, message, partial.as_ref()),
+            ExposedError::Cancelled => napi::Error::new(
+                napi::Status::Cancelled,
+                format!("BamlError: BamlCancelledError: {}", er),
+            ),
         }
     } else if let Some(er) = err.downcast_ref::<ScopeStack>() {
         invalid_argument_error(&format!("{}", er))
@@ -41,7 +51,8 @@ pub fn from_anyhow_error(err: anyhow::Error) -> napi::Error {
                 format!("BamlError: Unexpected error from BAML: {}", err),
             ),
             LLMResponse::LLMFailure(failed) => match &failed.code {
-                baml_runtime::internal::llm_client::ErrorCode::Other(2) => napi::Error::new(
+                baml_runtime::internal::llm_client::ErrorCode::Other(2)
+                | baml_runtime::internal::llm_client::ErrorCode::Timeout => napi::Error::new(
                     napi::Status::GenericFailure,
                     format!(
                         "BamlError: BamlClientError: Something went wrong with the LLM client: {}",
@@ -101,3 +112,19 @@ pub fn throw_baml_client_finish_reason_error(prompt: &str, raw_output: &str, mes
     });
     napi::Error::new(napi::Status::GenericFailure, error_json.to_string())
 }
+
+pub fn throw_baml_partial_failure_error(
+    prompt: &str,
+    raw_output: &str,
+    message: &str,
+    partial: Option<&baml_types::BamlValue>,
+) -> napi::Error {
+    let error_json = serde_json::json!({
+        "type": "BamlPartialFailureError",
+        "prompt": prompt,
+        "raw_output": raw_output,
+        "message": format!("BamlPartialFailureError: {}", message),
+        "partial": partial,
+    });
+    napi::Error::new(napi::Status::GenericFailure, error_json.to_string())
+}
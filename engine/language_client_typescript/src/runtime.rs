@@ -117,7 +117,14 @@ impl BamlRuntime {
 
         let fut = async move {
             let result = baml_runtime
-                .call_function(function_name, &args_map, &ctx_mng, tb.as_ref(), cb.as_ref())
+                .call_function(
+                    function_name,
+                    &args_map,
+                    &ctx_mng,
+                    tb.as_ref(),
+                    cb.as_ref(),
+                    None,
+                )
                 .await;
 
             result
@@ -158,6 +165,7 @@ impl BamlRuntime {
             &ctx_mng,
             tb.as_ref(),
             cb.as_ref(),
+            None,
         );
 
         result.map(FunctionResult::from).map_err(from_anyhow_error)
@@ -196,6 +204,7 @@ impl BamlRuntime {
                 &ctx,
                 tb.as_ref(),
                 client_registry.as_ref(),
+                None,
             )
             .map_err(from_anyhow_error)?;
 
@@ -240,6 +249,7 @@ impl BamlRuntime {
                 &ctx,
                 tb.as_ref(),
                 client_registry.as_ref(),
+                None,
             )
             .map_err(from_anyhow_error)?;
 
@@ -28,6 +28,19 @@ impl BamlAudio {
         External::new(aud)
     }
 
+    /// A file uploaded to the provider's file API, by its id.
+    #[napi(ts_return_type = "BamlAudio")]
+    pub fn from_file_id(file_id: String, media_type: Option<String>) -> External<BamlAudio> {
+        let aud = BamlAudio {
+            inner: baml_types::BamlMedia::file_ref(
+                baml_types::BamlMediaType::Audio,
+                file_id,
+                media_type,
+            ),
+        };
+        External::new(aud)
+    }
+
     #[napi(js_name = "isUrl")]
     pub fn is_url(&self) -> bool {
         matches!(&self.inner.content, baml_types::BamlMediaContent::Url(_))
@@ -62,6 +75,10 @@ impl BamlAudio {
                 "base64": base64.base64,
                 "media_type": self.inner.mime_type.clone().unwrap_or("".to_string())
             }),
+            baml_types::BamlMediaContent::FileRef(file_ref) => json!({
+                "file_id": file_ref.file_id,
+                "media_type": self.inner.mime_type
+            }),
             _ => "Unknown BamlAudioPy variant".into(),
         })
     }
@@ -27,6 +27,19 @@ impl BamlImage {
         External::new(img)
     }
 
+    /// A file uploaded to the provider's file API, by its id.
+    #[napi(ts_return_type = "BamlImage")]
+    pub fn from_file_id(file_id: String, media_type: Option<String>) -> External<BamlImage> {
+        let img = BamlImage {
+            inner: baml_types::BamlMedia::file_ref(
+                baml_types::BamlMediaType::Image,
+                file_id,
+                media_type,
+            ),
+        };
+        External::new(img)
+    }
+
     #[napi(js_name = "isUrl")]
     pub fn is_url(&self) -> bool {
         matches!(&self.inner.content, baml_types::BamlMediaContent::Url(_))
@@ -61,6 +74,10 @@ impl BamlImage {
                 "base64": base64.base64,
                 "media_type": self.inner.mime_type.clone().unwrap_or("".to_string())
             }),
+            baml_types::BamlMediaContent::FileRef(file_ref) => json!({
+                "file_id": file_ref.file_id,
+                "media_type": self.inner.mime_type
+            }),
             _ => "Unknown BamlImagePy variant".into(),
         })
     }
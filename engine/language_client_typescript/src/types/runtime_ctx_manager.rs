@@ -19,8 +19,9 @@ impl RuntimeContextManager {
             return Err(invalid_argument_error("Invalid tags"));
         };
 
-        self.inner.upsert_tags(tags.into_iter().collect());
-        Ok(())
+        self.inner
+            .upsert_tags(tags.into_iter().collect())
+            .map_err(|e| invalid_argument_error(&format!("{e:#}")))
     }
 
     #[napi]
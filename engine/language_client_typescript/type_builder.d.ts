@@ -4,6 +4,11 @@ type NameOf<T extends string> = IsLiteral<T> extends true ? T : 'DynamicType';
 type CheckNever<T, TypeName extends string, Value extends string> = [T] extends [never] ? `Error: Attempt to add value '${Value}' which is already a part of '${NameOf<TypeName>}'.` : T;
 type ExcludeFrom<T, U> = T extends U ? never : T;
 type RestrictNot<Name extends string, Value extends string, T extends string> = IsLiteral<T> extends true ? CheckNever<ExcludeFrom<Value, T>, Name, Value> : Value;
+/** The parts of a zod schema that `fromZod` reads, so that zod isn't a dependency. */
+export type ZodLike = {
+    _def: any;
+    description?: string;
+};
 export declare class TypeBuilder {
     private tb;
     protected classes: Set<string>;
@@ -26,6 +31,21 @@ export declare class TypeBuilder {
     union(types: FieldType[]): FieldType;
     classBuilder<Name extends string, Properties extends string>(name: Name, properties: Properties[]): ClassBuilder<Name, Properties>;
     enumBuilder<Name extends string, T extends string>(name: Name, values: T[]): EnumBuilder<Name, T>;
+    /**
+     * Registers a dynamic class named `name` for a zod object schema, and
+     * classes and enums for the objects and `z.enum`s it contains, named after
+     * their property, and returns its type. Output parses with `schema.parse`.
+     *
+     * If `name` is a class of the schema, the properties are added to it, so it
+     * must be @@dynamic.
+     */
+    fromZod(name: string, schema: ZodLike): FieldType;
+    private zodObject;
+    /** The type of `schema`, declaring objects and enums as `name`. */
+    private zodType;
+    private zodLiteral;
+    /** `name`, or `name2`, `name3`... if a class or enum has it already. */
+    private uniqueName;
     addClass<Name extends string>(name: Name): ClassBuilder<Name>;
     addEnum<Name extends string>(name: Name): EnumBuilder<Name>;
 }
@@ -17,6 +17,13 @@ type RestrictNot<Name extends string, Value extends string, T extends string> =
   ? CheckNever<ExcludeFrom<Value, T>, Name, Value>
   : Value
 
+/** The parts of a zod schema that `fromZod` reads, so that zod isn't a dependency. */
+export type ZodLike = { _def: any; description?: string }
+
+/** `shipping_address` becomes `ShippingAddress`. */
+const pascalCase = (name: string) =>
+  name.replace(/(?:^|[^A-Za-z0-9]+)([A-Za-z0-9])/g, (_, first: string) => first.toUpperCase())
+
 export class TypeBuilder {
   private tb: _TypeBuilder
   protected classes: Set<string>
@@ -87,6 +94,135 @@ export class TypeBuilder {
     return new EnumBuilder(this.tb, name, new Set(values))
   }
 
+  /**
+   * Registers a dynamic class named `name` for a zod object schema, and
+   * classes and enums for the objects and `z.enum`s it contains, named after
+   * their property, and returns its type. Output parses with `schema.parse`.
+   *
+   * If `name` is a class of the schema, the properties are added to it, so it
+   * must be @@dynamic.
+   */
+  fromZod(name: string, schema: ZodLike): FieldType {
+    if (schema._def.typeName !== 'ZodObject') {
+      throw new Error(`fromZod expects a z.object(), got ${schema._def.typeName}`)
+    }
+    if (this.enums.has(name)) {
+      throw new Error(`Enum ${name} already exists`)
+    }
+    return this.zodObject(name, schema, new Map())
+  }
+
+  private zodObject(name: string, schema: ZodLike, seen: Map<ZodLike, FieldType>): FieldType {
+    this.classes.add(name)
+    const bldr = this.tb.getClass(name)
+    // Register the object first, so that properties can refer back to it.
+    const type = bldr.field()
+    seen.set(schema, type)
+    const shape: Record<string, ZodLike> = schema._def.shape()
+    for (const [key, property] of Object.entries(shape)) {
+      const prop = bldr.property(key).setType(this.zodType(property, `${name}${pascalCase(key)}`, seen))
+      if (property.description !== undefined) {
+        prop.description(property.description)
+      }
+    }
+    return type
+  }
+
+  /** The type of `schema`, declaring objects and enums as `name`. */
+  private zodType(schema: ZodLike, name: string, seen: Map<ZodLike, FieldType>): FieldType {
+    const known = seen.get(schema)
+    if (known !== undefined) {
+      return known
+    }
+
+    const def = schema._def
+    switch (def.typeName) {
+      case 'ZodString':
+      case 'ZodDate':
+        return this.string()
+      case 'ZodNumber':
+        return def.checks.some((check: { kind: string }) => check.kind === 'int') ? this.int() : this.float()
+      case 'ZodBigInt':
+        return this.int()
+      case 'ZodBoolean':
+        return this.bool()
+      case 'ZodNull':
+      case 'ZodUndefined':
+        return this.null()
+      case 'ZodLiteral':
+        return this.zodLiteral(def.value)
+      case 'ZodNativeEnum': {
+        // Numeric enums map their values back to their keys too.
+        const values = Object.keys(def.values)
+          .filter((key) => typeof def.values[def.values[key]] !== 'number')
+          .map((key) => this.zodLiteral(def.values[key]))
+        return values.length === 1 ? values[0] : this.union(values)
+      }
+      case 'ZodEnum': {
+        const enumName = this.uniqueName(name)
+        this.enums.add(enumName)
+        const bldr = this.tb.getEnum(enumName)
+        for (const value of def.values) {
+          bldr.value(value)
+        }
+        const type = bldr.field()
+        seen.set(schema, type)
+        return type
+      }
+      case 'ZodObject':
+        return this.zodObject(this.uniqueName(name), schema, seen)
+      case 'ZodArray':
+        return this.zodType(def.type, name, seen).list()
+      case 'ZodSet':
+        return this.zodType(def.valueType, name, seen).list()
+      case 'ZodRecord':
+      case 'ZodMap':
+        return this.map(this.zodType(def.keyType, name, seen), this.zodType(def.valueType, name, seen))
+      case 'ZodUnion':
+      case 'ZodDiscriminatedUnion':
+        return this.union(Array.from(def.options as Iterable<ZodLike>, (option) => this.zodType(option, name, seen)))
+      case 'ZodOptional':
+      case 'ZodNullable':
+      case 'ZodDefault':
+        return this.zodType(def.innerType, name, seen).optional()
+      case 'ZodCatch':
+      case 'ZodReadonly':
+        return this.zodType(def.innerType, name, seen)
+      case 'ZodBranded':
+        return this.zodType(def.type, name, seen)
+      case 'ZodEffects':
+        return this.zodType(def.schema, name, seen)
+      case 'ZodLazy':
+        return this.zodType(def.getter(), name, seen)
+      default:
+        throw new Error(`fromZod doesn't support ${def.typeName}`)
+    }
+  }
+
+  private zodLiteral(value: unknown): FieldType {
+    switch (typeof value) {
+      case 'string':
+        return this.literalString(value)
+      case 'number':
+        if (Number.isInteger(value)) {
+          return this.literalInt(value)
+        }
+        break
+      case 'boolean':
+        return this.literalBool(value)
+    }
+    throw new Error(`fromZod doesn't support the literal ${String(value)}`)
+  }
+
+  /** `name`, or `name2`, `name3`... if a class or enum has it already. */
+  private uniqueName(name: string): string {
+    let candidate = name
+    for (let suffix = 2; this.classes.has(candidate) || this.enums.has(candidate); suffix++) {
+      candidate = `${name}${suffix}`
+    }
+    return candidate
+  }
+
   addClass<Name extends string>(name: Name): ClassBuilder<Name> {
     if (this.classes.has(name)) {
       throw new Error(`Class ${name} already exists`)
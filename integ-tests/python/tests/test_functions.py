@@ -2,7 +2,8 @@ import uuid
 import json
 import os
 import time
-from typing import List
+from typing import List, Optional
+import pydantic
 import pytest
 from assertpy import assert_that
 from dotenv import load_dotenv
@@ -801,6 +802,28 @@ async def test_dynamic_class_nested_output_no_stream():
     )
 
 
+@pytest.mark.asyncio
+async def test_dynamic_class_output_from_pydantic():
+    class Name(pydantic.BaseModel):
+        first_name: str
+        last_name: Optional[str] = None
+
+    class Person(pydantic.BaseModel):
+        name: Name
+        hair_color: str = pydantic.Field(description="As one word")
+
+    tb = TypeBuilder()
+    tb.DynamicOutput.add_property("person", tb.from_pydantic(Person))
+
+    output = await b.MyFunc(
+        input="My name is Mark Gonzalez. My hair is black and I'm 6 feet tall.",
+        baml_options={"tb": tb},
+    )
+    person = Person.model_validate(output.model_dump()["person"])
+    assert person.name.first_name == "Mark"
+    assert person.hair_color == "black"
+
+
 @pytest.mark.asyncio
 async def test_dynamic_class_nested_output_stream():
     tb = TypeBuilder()
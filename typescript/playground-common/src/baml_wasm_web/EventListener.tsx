@@ -461,7 +461,9 @@ const renderPromptAtomAsync = atom(async (get) => {
       return await vscode.readFile(path)
     })
   } catch (e) {
-    if (e instanceof Error) {
+    if (e instanceof wasm.WasmPromptRenderError) {
+      return e.message
+    } else if (e instanceof Error) {
       return e.message
     } else {
       return `${e}`
@@ -8,6 +8,9 @@ import { vscode } from '../../utils/vscode'
 const isRunningAtom = atom(false)
 export const showTestsAtom = atom(false)
 export const showClientGraphAtom = atom(false)
+/// Runs tests against this client instead of the function's own, e.g. `GPT4`
+/// or `openai/gpt-4o-mini`.
+export const testClientOverrideAtom = atom<string | undefined>(undefined)
 
 export type TestStatusType = 'queued' | 'running' | 'done' | 'error'
 export type DoneTestStatusType =
@@ -120,6 +123,7 @@ export const useRunHooks = () => {
       async (get, set, testNames: string[]) => {
         const runtime = get(selectedRuntimeAtom)
         const func = get(selectedFunctionAtom)
+        const clientOverride = get(testClientOverrideAtom)
         if (!runtime || !func) {
           // Refuse to run a test if no runtime is selected
           return
@@ -197,6 +201,7 @@ export const useRunHooks = () => {
                   async (path: string) => {
                     return await vscode.readFile(path)
                   },
+                  clientOverride,
                 )
                 .then((res) => {
                   let elapsed = new Date().getTime() - now
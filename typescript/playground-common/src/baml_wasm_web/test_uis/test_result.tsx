@@ -286,6 +286,39 @@ const ParsedTestResult: React.FC<{ doneStatus: string; parsed?: WasmParsedTestRe
   }
 }
 
+type Attempt = {
+  client: string
+  path: string
+  latency_ms: number | null
+  outcome: 'success' | 'parse_failure' | 'llm_failure' | 'user_failure' | 'internal_failure'
+  error_code: unknown
+  next: { type: 'retry'; policy: string; retry: number; delay_ms: number } | { type: 'next_client' } | { type: 'stop' }
+}
+
+// Only shown when a retry or fallback happened.
+const AttemptTimeline: React.FC<{ attempts: string | undefined }> = ({ attempts }) => {
+  const parsed: Attempt[] = attempts ? JSON.parse(attempts) : []
+  if (parsed.length < 2) {
+    return null
+  }
+  return (
+    <ol className='pl-4 list-decimal'>
+      {parsed.map((attempt, i) => (
+        <li key={i}>
+          <b>{attempt.path}</b>: {attempt.outcome.replace('_', ' ')}
+          {attempt.latency_ms !== null && <> after {attempt.latency_ms}ms</>}
+          {attempt.next.type === 'retry' && (
+            <>
+              , retry {attempt.next.retry} of {attempt.next.policy} in {attempt.next.delay_ms}ms
+            </>
+          )}
+          {attempt.next.type === 'next_client' && <>, trying the next client</>}
+        </li>
+      ))}
+    </ol>
+  )
+}
+
 const LLMTestResult: React.FC<{ test: WasmTestResponse; doneStatus: DoneTestStatusType; testLatency: number }> = ({
   test,
   doneStatus,
@@ -337,6 +370,7 @@ const LLMTestResult: React.FC<{ test: WasmTestResponse; doneStatus: DoneTestStat
             {detailsText}
             {latencyMs !== undefined && bamlOverheadLatency > 0 && <> (+ {bamlOverheadLatency}ms for BAML)</>}
           </div>
+          <AttemptTimeline attempts={test.attempts()} />
           <div className='grid grid-cols-2 gap-2'>
             <div className='flex flex-col'>
               {llm_response?.output_tokens === undefined